@@ -15,10 +15,11 @@
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
 use millau_runtime::{
-	AccountId, AuraConfig, BalancesConfig, BridgeRialtoConfig, GenesisConfig, GrandpaConfig, SessionConfig,
-	SessionKeys, Signature, SudoConfig, SystemConfig, WASM_BINARY,
+	AccountId, BabeConfig, BalancesConfig, BridgeCouncilConfig, BridgeRialtoConfig, BridgeWestendConfig, GenesisConfig,
+	GrandpaConfig, SessionConfig, SessionKeys, Signature, SudoConfig, SystemConfig, WASM_BINARY,
 };
-use sp_consensus_aura::sr25519::AuthorityId as AuraId;
+use pallet_substrate_bridge::InitializationData;
+use sp_consensus_babe::AuthorityId as BabeId;
 use sp_core::{sr25519, Pair, Public};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::traits::{IdentifyAccount, Verify};
@@ -26,6 +27,15 @@ use sp_runtime::traits::{IdentifyAccount, Verify};
 /// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
 pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig>;
 
+/// Telemetry endpoint of the staging network.
+const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
+/// Number of validators of the staging network.
+const STAGING_VALIDATORS: usize = 3;
+/// Bootnodes of the staging network.
+///
+/// Additional nodes may always be specified using the `--bootnodes` CLI option.
+const STAGING_BOOTNODES: &[&str] = &[];
+
 /// The chain specification option. This is expected to come in from the CLI and
 /// is little more than one of a number of alternatives which can easily be converted
 /// from a string (`--chain=...`) into a `ChainSpec`.
@@ -35,6 +45,14 @@ pub enum Alternative {
 	Development,
 	/// Whatever the current runtime is, with simple Alice/Bob/Charlie/Dave/Eve auths.
 	LocalTestnet,
+	/// Shared test network of the Millau <> Rialto bridge.
+	///
+	/// Session keys of validators are placeholders, derived from the well-known seeds (see
+	/// `staging_authority_keys`). They're expected to be rotated using `session.setKeys` once
+	/// the network is launched. Bridge pallets are initialized at genesis if initialization data
+	/// is provided (see `load_with_bridge_config`), or the bridge state may be imported into the
+	/// chain spec using the `import-bridge-state` subcommand.
+	Staging,
 }
 
 /// Helper function to generate a crypto pair from seed
@@ -54,24 +72,50 @@ where
 	AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
 }
 
-/// Helper function to generate an authority key for Aura
-pub fn get_authority_keys_from_seed(s: &str) -> (AccountId, AuraId, GrandpaId) {
+/// Return placeholder authority keys of the `index`-th validator of the staging network.
+pub fn staging_authority_keys(index: usize) -> (AccountId, BabeId, GrandpaId) {
+	get_authority_keys_from_seed(&format!("MillauStaging//{}", index))
+}
+
+/// Return placeholder account of the staging network sudo (and bridge pallets owner).
+pub fn staging_root_key() -> AccountId {
+	get_account_id_from_seed::<sr25519::Public>("MillauStaging")
+}
+
+/// Helper function to generate an authority key for Babe
+pub fn get_authority_keys_from_seed(s: &str) -> (AccountId, BabeId, GrandpaId) {
 	(
 		get_account_id_from_seed::<sr25519::Public>(s),
-		get_from_seed::<AuraId>(s),
+		get_from_seed::<BabeId>(s),
 		get_from_seed::<GrandpaId>(s),
 	)
 }
 
+/// Genesis configuration of the bridge pallets.
+///
+/// By default, Rialto headers pallet is not initialized at genesis and is waiting for the `initialize`
+/// call. If initialization data is provided, the pallet is initialized at genesis instead.
+#[derive(Clone, Debug, Default)]
+pub struct BridgeGenesisConfig {
+	/// Initialization data of the Rialto headers pallet.
+	pub rialto: Option<InitializationData<bp_rialto::Header>>,
+}
+
 impl Alternative {
 	/// Get an actual chain config from one of the alternatives.
 	pub(crate) fn load(self) -> Result<ChainSpec, String> {
+		self.load_with_bridge_config(Default::default())
+	}
+
+	/// Get an actual chain config from one of the alternatives, with given genesis configuration
+	/// of the bridge pallets.
+	pub fn load_with_bridge_config(self, bridge_config: BridgeGenesisConfig) -> Result<ChainSpec, String> {
 		Ok(match self {
 			Alternative::Development => ChainSpec::from_genesis(
 				"Development",
 				"dev",
 				sc_service::ChainType::Development,
-				|| {
+				move || {
 					testnet_genesis(
 						vec![get_authority_keys_from_seed("Alice")],
 						get_account_id_from_seed::<sr25519::Public>("Alice"),
@@ -82,6 +126,7 @@ impl Alternative {
 							get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 						],
 						true,
+						bridge_config.clone(),
 					)
 				},
 				vec![],
@@ -94,7 +139,7 @@ impl Alternative {
 				"Local Testnet",
 				"local_testnet",
 				sc_service::ChainType::Local,
-				|| {
+				move || {
 					testnet_genesis(
 						vec![
 							get_authority_keys_from_seed("Alice"),
@@ -119,6 +164,7 @@ impl Alternative {
 							get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 						],
 						true,
+						bridge_config.clone(),
 					)
 				},
 				vec![],
@@ -127,19 +173,50 @@ impl Alternative {
 				None,
 				None,
 			),
+			Alternative::Staging => ChainSpec::from_genesis(
+				"Millau Staging",
+				"millau_staging",
+				sc_service::ChainType::Live,
+				move || {
+					let authorities = (0..STAGING_VALIDATORS).map(staging_authority_keys).collect::<Vec<_>>();
+					let endowed_accounts = std::iter::once(staging_root_key())
+						.chain(authorities.iter().map(|authority| authority.0.clone()))
+						.collect();
+					testnet_genesis(
+						authorities,
+						staging_root_key(),
+						endowed_accounts,
+						false,
+						bridge_config.clone(),
+					)
+				},
+				STAGING_BOOTNODES
+					.iter()
+					.map(|bootnode| bootnode.parse())
+					.collect::<Result<Vec<_>, _>>()
+					.map_err(|e| format!("Invalid staging bootnode address: {:?}", e))?,
+				Some(
+					sc_telemetry::TelemetryEndpoints::new(vec![(STAGING_TELEMETRY_URL.into(), 0)])
+						.map_err(|e| format!("Invalid staging telemetry URL: {:?}", e))?,
+				),
+				Some("millau-staging"),
+				None,
+				None,
+			),
 		})
 	}
 }
 
-fn session_keys(aura: AuraId, grandpa: GrandpaId) -> SessionKeys {
-	SessionKeys { aura, grandpa }
+fn session_keys(babe: BabeId, grandpa: GrandpaId) -> SessionKeys {
+	SessionKeys { babe, grandpa }
 }
 
 fn testnet_genesis(
-	initial_authorities: Vec<(AccountId, AuraId, GrandpaId)>,
+	initial_authorities: Vec<(AccountId, BabeId, GrandpaId)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
 	_enable_println: bool,
+	bridge_config: BridgeGenesisConfig,
 ) -> GenesisConfig {
 	GenesisConfig {
 		frame_system: Some(SystemConfig {
@@ -149,18 +226,27 @@ fn testnet_genesis(
 		pallet_balances: Some(BalancesConfig {
 			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
 		}),
-		pallet_aura: Some(AuraConfig {
+		pallet_babe: Some(BabeConfig {
 			authorities: Vec::new(),
 		}),
 		pallet_grandpa: Some(GrandpaConfig {
 			authorities: Vec::new(),
 		}),
 		pallet_substrate_bridge: Some(BridgeRialtoConfig {
-			// We'll initialize the pallet with a dispatchable instead.
+			// If initialization data is not provided, we'll initialize the pallet with a dispatchable instead.
+			init_data: bridge_config.rialto,
+			owner: Some(root_key.clone()),
+		}),
+		pallet_substrate_bridge_Instance1: Some(BridgeWestendConfig {
+			// Westend headers bridge is always initialized with a dispatchable
 			init_data: None,
 			owner: Some(root_key.clone()),
 		}),
 		pallet_sudo: Some(SudoConfig { key: root_key }),
+		pallet_collective_Instance1: Some(BridgeCouncilConfig {
+			members: initial_authorities.iter().map(|x| x.0.clone()).collect(),
+			phantom: Default::default(),
+		}),
 		pallet_session: Some(SessionConfig {
 			keys: initial_authorities
 				.iter()
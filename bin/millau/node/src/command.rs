@@ -14,12 +14,15 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::cli::{Cli, Subcommand};
+use crate::cli::{Cli, ImportBridgeStateCmd, Subcommand, TryRuntimeCmd};
 use crate::service;
 use crate::service::new_partial;
 use millau_runtime::Block;
 use sc_cli::{ChainSpec, Role, RuntimeVersion, SubstrateCli};
-use sc_service::PartialComponents;
+use sc_service::{Configuration, PartialComponents, PruningMode, TaskExecutor, TaskManager};
+
+/// Number of blocks which state is kept by default, if state pruning is enabled.
+const DEFAULT_STATE_PRUNING_DEPTH: u32 = 256;
 
 impl SubstrateCli for Cli {
 	fn impl_name() -> String {
@@ -55,14 +58,13 @@ impl SubstrateCli for Cli {
 	}
 
 	fn load_spec(&self, id: &str) -> Result<Box<dyn sc_service::ChainSpec>, String> {
-		Ok(Box::new(
-			match id {
-				"" | "dev" => crate::chain_spec::Alternative::Development,
-				"local" => crate::chain_spec::Alternative::LocalTestnet,
-				_ => return Err(format!("Unsupported chain specification: {}", id)),
-			}
-			.load()?,
-		))
+		Ok(Box::new(match id {
+			"" | "dev" => crate::chain_spec::Alternative::Development.load()?,
+			"local" => crate::chain_spec::Alternative::LocalTestnet.load()?,
+			"millau-staging" => crate::chain_spec::Alternative::Staging.load()?,
+			// chain spec file may be used to launch the node with pre-initialized bridge pallets
+			path => crate::chain_spec::ChainSpec::from_json_file(std::path::PathBuf::from(path))?,
+		}))
 	}
 }
 
@@ -84,6 +86,23 @@ pub fn run() -> sc_cli::Result<()> {
 				Ok(())
 			}
 		}
+		Some(Subcommand::BenchmarkWeights(cmd)) => {
+			if cfg!(feature = "runtime-benchmarks") {
+				let runner = cli.create_runner(cmd)?;
+
+				runner.sync_run(|config| {
+					cmd.run::<Block, service::Executor>(config, millau_runtime::BENCHMARKED_BRIDGE_PALLETS)
+				})
+			} else {
+				println!(
+					"Benchmarking wasn't enabled when building the node. \
+				You can enable it with `--features runtime-benchmarks`."
+				);
+				Ok(())
+			}
+		}
+		Some(Subcommand::TryRuntime(cmd)) => try_runtime_upgrade(cmd),
+		Some(Subcommand::ImportBridgeState(cmd)) => import_bridge_state(&cli, cmd),
 		Some(Subcommand::Key(cmd)) => cmd.run(),
 		Some(Subcommand::Sign(cmd)) => cmd.run(),
 		Some(Subcommand::Verify(cmd)) => cmd.run(),
@@ -152,10 +171,135 @@ pub fn run() -> sc_cli::Result<()> {
 		}
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
-			runner.run_node_until_exit(|config| match config.role {
+			let bridge_peer_rpc = cli.bridge_peer_rpc.clone();
+			let retain_bridge_proof_state = cli.retain_bridge_proof_state;
+			runner.run_node_until_exit(|mut config| match config.role {
 				Role::Light => service::new_light(config),
-				_ => service::new_full(config),
+				_ => {
+					configure_bridge_proof_state_retention(&mut config, retain_bridge_proof_state);
+					service::new_full(config, bridge_peer_rpc)
+				}
 			})
 		}
 	}
 }
+
+/// Run runtime upgrade against the state snapshot, using native runtime.
+#[cfg(feature = "try-runtime")]
+fn try_runtime_upgrade(cmd: &TryRuntimeCmd) -> sc_cli::Result<()> {
+	use millau_runtime::BuildStorage;
+
+	let snapshot = crate::chain_spec::ChainSpec::from_json_file(cmd.snapshot.clone()).map_err(sc_cli::Error::Input)?;
+	let storage = snapshot.build_storage().map_err(sc_cli::Error::Input)?;
+	let weight = sp_io::TestExternalities::new(storage)
+		.execute_with(millau_runtime::try_runtime_upgrade)
+		.map_err(|e| sc_cli::Error::Input(format!("Runtime upgrade checks have failed: {}", e)))?;
+	println!("Runtime upgrade checks have passed. Upgrade weight: {}", weight);
+	Ok(())
+}
+
+/// Run runtime upgrade against the state snapshot, using native runtime.
+#[cfg(not(feature = "try-runtime"))]
+fn try_runtime_upgrade(_cmd: &TryRuntimeCmd) -> sc_cli::Result<()> {
+	println!(
+		"Runtime upgrade rehearsal wasn't enabled when building the node. \
+	You can enable it with `--features try-runtime`."
+	);
+	Ok(())
+}
+
+/// Print raw chain spec, which genesis storage entries of bridge pallets are replaced with entries
+/// from the bridge state snapshot.
+fn import_bridge_state(cli: &Cli, cmd: &ImportBridgeStateCmd) -> sc_cli::Result<()> {
+	let spec = cli.load_spec(&cmd.chain).map_err(sc_cli::Error::Input)?;
+	let mut raw_spec: serde_json::Value = serde_json::from_str(&spec.as_json(true).map_err(sc_cli::Error::Input)?)
+		.map_err(|e| sc_cli::Error::Input(format!("Failed to parse raw chain spec: {}", e)))?;
+	let snapshot: serde_json::Value = serde_json::from_slice(&std::fs::read(&cmd.snapshot)?)
+		.map_err(|e| sc_cli::Error::Input(format!("Failed to parse bridge state snapshot: {}", e)))?;
+
+	let invalid_snapshot = |what| sc_cli::Error::Input(format!("Invalid bridge state snapshot: missing {}", what));
+	let pallet_prefixes = snapshot["pallets"]
+		.as_object()
+		.ok_or_else(|| invalid_snapshot("pallets"))?
+		.values()
+		.map(|prefix| prefix.as_str().ok_or_else(|| invalid_snapshot("pallet storage prefix")))
+		.collect::<Result<Vec<_>, _>>()?;
+	let snapshot_storage = snapshot["storage"]
+		.as_object()
+		.ok_or_else(|| invalid_snapshot("storage"))?;
+	let genesis_storage = raw_spec["genesis"]["raw"]["top"]
+		.as_object_mut()
+		.ok_or_else(|| sc_cli::Error::Input("Raw chain spec has no genesis storage".into()))?;
+
+	let replaced_keys = genesis_storage
+		.keys()
+		.filter(|key| pallet_prefixes.iter().any(|prefix| key.starts_with(prefix)))
+		.cloned()
+		.collect::<Vec<_>>();
+	for key in replaced_keys {
+		genesis_storage.remove(&key);
+	}
+	genesis_storage.extend(snapshot_storage.clone());
+
+	let json = serde_json::to_string_pretty(&raw_spec)
+		.map_err(|e| sc_cli::Error::Input(format!("Failed to serialize chain spec: {}", e)))?;
+	println!("{}", json);
+	Ok(())
+}
+
+/// Start full node within the current process, using given command line arguments.
+pub fn start_full_node<I>(args: I, task_executor: TaskExecutor) -> sc_cli::Result<TaskManager>
+where
+	I: IntoIterator,
+	I::Item: Into<std::ffi::OsString> + Clone,
+{
+	let cli = Cli::try_from_iter(args).map_err(|e| sc_cli::Error::Input(e.to_string()))?;
+	if cli.subcommand.is_some() {
+		return Err(sc_cli::Error::Input(
+			"Subcommands are not supported by the in-process node".into(),
+		));
+	}
+
+	let mut config = cli.create_configuration(&cli.run, task_executor)?;
+	configure_bridge_proof_state_retention(&mut config, cli.retain_bridge_proof_state);
+	Ok(service::new_full(config, cli.bridge_peer_rpc)?)
+}
+
+/// Make sure that the node keeps state of blocks that may be used to anchor bridge proofs.
+///
+/// If `retain_bridge_proof_state` is true, state pruning is relaxed, so that state of at least
+/// `BRIDGE_PROOF_ANCHOR_DEPTH` blocks is kept. Otherwise the warning is printed if the node
+/// would be unable to serve proofs of messages that are still deliverable.
+fn configure_bridge_proof_state_retention(config: &mut Configuration, retain_bridge_proof_state: bool) {
+	let required_depth = millau_runtime::BRIDGE_PROOF_ANCHOR_DEPTH as u32;
+	let max_blocks = match config.pruning {
+		PruningMode::Constrained(ref mut constraints) => &mut constraints.max_blocks,
+		PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => return,
+	};
+
+	let kept_depth = max_blocks.unwrap_or(DEFAULT_STATE_PRUNING_DEPTH);
+	if kept_depth >= required_depth {
+		return;
+	}
+
+	if retain_bridge_proof_state {
+		log::info!(
+			target: "bridge",
+			"Keeping state of {} latest blocks (instead of {}) to serve bridge proofs",
+			required_depth,
+			kept_depth,
+		);
+		*max_blocks = Some(required_depth);
+	} else {
+		log::warn!(
+			target: "bridge",
+			"State pruning only keeps state of {} latest blocks, but bridge proofs may be anchored at blocks \
+			that are up to {} blocks deep. The node may be unable to serve proofs of messages that are still \
+			deliverable to the Rialto chain. Use `--retain-bridge-proof-state`, `--pruning={}` or \
+			`--pruning=archive` to fix that.",
+			kept_depth,
+			required_depth,
+			required_depth,
+		);
+	}
+}
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Substrate Node Template CLI library.
+//! Millau bridge node library.
 #![warn(missing_docs)]
 
 mod chain_spec;
@@ -22,6 +22,9 @@ mod chain_spec;
 mod service;
 mod cli;
 mod command;
+mod metrics;
+
+pub use sc_service::{TaskExecutor, TaskManager};
 
 /// Node run result.
 pub type Result = sc_cli::Result<()>;
@@ -30,3 +33,15 @@ pub type Result = sc_cli::Result<()>;
 pub fn run() -> Result {
 	command::run()
 }
+
+/// Start full node within the current process.
+///
+/// Arguments are the same as the node binary arguments (the first argument is the binary name).
+/// Subcommands are not supported. The node is running until returned `TaskManager` is dropped.
+pub fn start_full_node<I>(args: I, task_executor: TaskExecutor) -> sc_cli::Result<TaskManager>
+where
+	I: IntoIterator,
+	I::Item: Into<std::ffi::OsString> + Clone,
+{
+	command::start_full_node(args, task_executor)
+}
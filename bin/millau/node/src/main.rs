@@ -18,13 +18,7 @@
 
 #![warn(missing_docs)]
 
-mod chain_spec;
-#[macro_use]
-mod service;
-mod cli;
-mod command;
-
 /// Run the Millau Node
 fn main() -> sc_cli::Result<()> {
-	command::run()
+	millau_bridge_node::run()
 }
@@ -0,0 +1,187 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Prometheus metrics of the bridge pallets, derived from the on-chain state.
+//!
+//! Metrics are sampled whenever new block is finalized, so they are available on every
+//! node (not only on nodes that are used by relays).
+
+use bp_message_lane::LanesApi;
+use bp_runtime::BridgeStatusApi;
+use futures::StreamExt;
+use millau_runtime::{
+	bridge_rpc::{BridgedBlockHash, BridgedBlockNumber, BRIDGE_INSTANCE},
+	opaque::Block,
+};
+use sc_client_api::BlockchainEvents;
+use sp_api::ProvideRuntimeApi;
+use sp_runtime::{generic::BlockId, traits::UniqueSaturatedInto};
+use std::{
+	sync::Arc,
+	time::{SystemTime, UNIX_EPOCH},
+};
+use substrate_prometheus_endpoint::{register, Gauge, GaugeVec, Opts, PrometheusError, Registry, U64};
+
+/// Bridge pallets metrics.
+#[derive(Clone)]
+pub struct BridgeMetrics {
+	outbound_queued_messages: GaugeVec<U64>,
+	inbound_unconfirmed_messages: GaugeVec<U64>,
+	best_bridged_header: Gauge<U64>,
+	best_finalized_bridged_header: Gauge<U64>,
+	last_header_import_time: Gauge<U64>,
+	is_headers_pallet_halted: Gauge<U64>,
+	is_message_lane_pallet_halted: Gauge<U64>,
+}
+
+impl BridgeMetrics {
+	/// Create and register bridge metrics in the registry.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(BridgeMetrics {
+			outbound_queued_messages: register(
+				GaugeVec::new(
+					Opts::new(
+						"bridge_outbound_queued_messages",
+						"Number of messages that are queued at the outbound lane",
+					),
+					&["lane"],
+				)?,
+				registry,
+			)?,
+			inbound_unconfirmed_messages: register(
+				GaugeVec::new(
+					Opts::new(
+						"bridge_inbound_unconfirmed_messages",
+						"Number of messages that are received by the inbound lane, but not yet confirmed",
+					),
+					&["lane"],
+				)?,
+				registry,
+			)?,
+			best_bridged_header: register(
+				Gauge::new(
+					"bridge_best_rialto_header",
+					"Number of the best Rialto header, imported by the headers pallet",
+				)?,
+				registry,
+			)?,
+			best_finalized_bridged_header: register(
+				Gauge::new(
+					"bridge_best_finalized_rialto_header",
+					"Number of the best finalized Rialto header, imported by the headers pallet",
+				)?,
+				registry,
+			)?,
+			last_header_import_time: register(
+				Gauge::new(
+					"bridge_last_rialto_header_import_time",
+					"UNIX timestamp (in seconds) when the best Rialto header has last changed",
+				)?,
+				registry,
+			)?,
+			is_headers_pallet_halted: register(
+				Gauge::new(
+					"bridge_is_headers_pallet_halted",
+					"1 if headers pallet operations are halted",
+				)?,
+				registry,
+			)?,
+			is_message_lane_pallet_halted: register(
+				Gauge::new(
+					"bridge_is_message_lane_pallet_halted",
+					"1 if message lane pallet operations are halted",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
+/// Update bridge metrics on every finalized block.
+pub async fn run<Client>(client: Arc<Client>, metrics: BridgeMetrics)
+where
+	Client: BlockchainEvents<Block> + ProvideRuntimeApi<Block>,
+	Client::Api: LanesApi<Block> + BridgeStatusApi<Block, BridgedBlockNumber, BridgedBlockHash>,
+{
+	let mut best_bridged_header = None;
+	let mut finality_notifications = client.finality_notification_stream();
+	while let Some(notification) = finality_notifications.next().await {
+		let at = BlockId::Hash(notification.hash);
+		let runtime_api = client.runtime_api();
+
+		match runtime_api.lanes(&at, BRIDGE_INSTANCE) {
+			Ok(Some(lanes)) => {
+				for lane in lanes {
+					let lane_label = hex_lane_id(&lane.lane);
+					metrics
+						.outbound_queued_messages
+						.with_label_values(&[lane_label.as_str()])
+						.set(lane.outbound_queued_messages());
+					metrics
+						.inbound_unconfirmed_messages
+						.with_label_values(&[lane_label.as_str()])
+						.set(lane.inbound_unconfirmed_messages());
+				}
+			}
+			Ok(None) => log::warn!(target: "bridge", "Runtime doesn't support Rialto message lanes"),
+			Err(error) => log::warn!(target: "bridge", "Failed to read message lanes: {:?}", error),
+		}
+
+		match runtime_api.bridge_status(&at, BRIDGE_INSTANCE) {
+			Ok(Some(status)) => {
+				let new_best_bridged_header = status
+					.best_headers
+					.iter()
+					.map(|(number, _)| *number)
+					.chain(std::iter::once(status.best_finalized.0))
+					.max()
+					.unwrap_or_default();
+				if best_bridged_header.map_or(false, |best| best != new_best_bridged_header) {
+					metrics.last_header_import_time.set(unix_timestamp());
+				}
+				best_bridged_header = Some(new_best_bridged_header);
+
+				metrics
+					.best_bridged_header
+					.set(new_best_bridged_header.unique_saturated_into());
+				metrics
+					.best_finalized_bridged_header
+					.set(status.best_finalized.0.unique_saturated_into());
+				metrics
+					.is_headers_pallet_halted
+					.set(status.is_headers_pallet_halted as u64);
+				metrics
+					.is_message_lane_pallet_halted
+					.set(status.is_message_lane_pallet_halted as u64);
+			}
+			Ok(None) => log::warn!(target: "bridge", "Runtime doesn't support Rialto bridge"),
+			Err(error) => log::warn!(target: "bridge", "Failed to read bridge status: {:?}", error),
+		}
+	}
+}
+
+/// Return hex-encoded lane id, used as metric label.
+fn hex_lane_id(lane: &bp_message_lane::LaneId) -> String {
+	lane.to_string()
+}
+
+/// Return current UNIX timestamp in seconds.
+fn unix_timestamp() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|duration| duration.as_secs())
+		.unwrap_or_default()
+}
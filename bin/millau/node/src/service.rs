@@ -16,28 +16,20 @@
 
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
-// =====================================================================================
-// =====================================================================================
-// =====================================================================================
-// UPDATE GUIDE:
-// 1) replace everything with node-template/src/service.rs contents (found in main Substrate repo);
-// 2) the only thing to keep from old code, is `rpc_extensions_builder` - we use our own custom RPCs;
-// 3) fix compilation errors;
-// 4) test :)
-// =====================================================================================
-// =====================================================================================
-// =====================================================================================
-
-use bp_message_lane::{LaneId, MessageNonce};
-use bp_runtime::{InstanceId, RIALTO_BRIDGE_INSTANCE};
-use millau_runtime::{self, opaque::Block, RuntimeApi};
+// Everything that is specific to the bridged chain comes from the `millau_runtime::bridge_rpc`
+// module, so this file doesn't need any changes when bridge instance is added to the runtime.
+
+use millau_runtime::{
+	self,
+	bridge_rpc::{BridgedBlockHash, BridgedBlockNumber, InboundRelayer, MessageLaneRpc, OutboundMessageFee},
+	opaque::Block,
+	RuntimeApi,
+};
 use sc_client_api::{ExecutorProvider, RemoteBackend};
 use sc_executor::native_executor_instance;
 pub use sc_executor::NativeExecutor;
 use sc_finality_grandpa::{FinalityProofProvider as GrandpaFinalityProofProvider, SharedVoterState};
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
-use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
-use sp_core::storage::StorageKey;
 use sp_inherents::InherentDataProviders;
 use std::sync::Arc;
 use std::time::Duration;
@@ -65,8 +57,13 @@ pub fn new_partial(
 		sp_consensus::DefaultImportQueue<Block, FullClient>,
 		sc_transaction_pool::FullPool<Block, FullClient>,
 		(
-			sc_finality_grandpa::GrandpaBlockImport<FullBackend, Block, FullClient, FullSelectChain>,
+			sc_consensus_babe::BabeBlockImport<
+				Block,
+				FullClient,
+				sc_finality_grandpa::GrandpaBlockImport<FullBackend, Block, FullClient, FullSelectChain>,
+			>,
 			sc_finality_grandpa::LinkHalf<Block, FullClient, FullSelectChain>,
+			sc_consensus_babe::BabeLink<Block>,
 		),
 	>,
 	ServiceError,
@@ -88,15 +85,19 @@ pub fn new_partial(
 	let (grandpa_block_import, grandpa_link) =
 		sc_finality_grandpa::block_import(client.clone(), &(client.clone() as Arc<_>), select_chain.clone())?;
 
-	let aura_block_import =
-		sc_consensus_aura::AuraBlockImport::<_, _, _, AuraPair>::new(grandpa_block_import.clone(), client.clone());
+	let (babe_block_import, babe_link) = sc_consensus_babe::block_import(
+		sc_consensus_babe::Config::get_or_compute(&*client)?,
+		grandpa_block_import.clone(),
+		client.clone(),
+	)?;
 
-	let import_queue = sc_consensus_aura::import_queue::<_, _, _, AuraPair, _, _>(
-		sc_consensus_aura::slot_duration(&*client)?,
-		aura_block_import,
-		Some(Box::new(grandpa_block_import.clone())),
+	let import_queue = sc_consensus_babe::import_queue(
+		babe_link.clone(),
+		babe_block_import.clone(),
+		Some(Box::new(grandpa_block_import)),
 		None,
 		client.clone(),
+		select_chain.clone(),
 		inherent_data_providers.clone(),
 		&task_manager.spawn_handle(),
 		config.prometheus_registry(),
@@ -112,12 +113,12 @@ pub fn new_partial(
 		select_chain,
 		transaction_pool,
 		inherent_data_providers,
-		other: (grandpa_block_import, grandpa_link),
+		other: (babe_block_import, grandpa_link, babe_link),
 	})
 }
 
 /// Builds a new service for a full client.
-pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
+pub fn new_full(config: Configuration, bridge_peer_rpc: Option<String>) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -127,7 +128,7 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 		select_chain,
 		transaction_pool,
 		inherent_data_providers,
-		other: (block_import, grandpa_link),
+		other: (block_import, grandpa_link, babe_link),
 	} = new_partial(&config)?;
 
 	let finality_proof_provider = GrandpaFinalityProofProvider::new_for_service(backend.clone(), client.clone());
@@ -163,35 +164,11 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 	let telemetry_connection_sinks = sc_service::TelemetryConnectionSinks::default();
 
 	let rpc_extensions_builder = {
-		// This struct is here to ease update process.
-
-		/// Millau runtime from message-lane RPC point of view.
-		struct MillauMessageLaneKeys;
-
-		impl pallet_message_lane_rpc::Runtime for MillauMessageLaneKeys {
-			fn message_key(&self, instance: &InstanceId, lane: &LaneId, nonce: MessageNonce) -> Option<StorageKey> {
-				match *instance {
-					RIALTO_BRIDGE_INSTANCE => Some(millau_runtime::rialto_messages::message_key(lane, nonce)),
-					_ => None,
-				}
-			}
-
-			fn outbound_lane_data_key(&self, instance: &InstanceId, lane: &LaneId) -> Option<StorageKey> {
-				match *instance {
-					RIALTO_BRIDGE_INSTANCE => Some(millau_runtime::rialto_messages::outbound_lane_data_key(lane)),
-					_ => None,
-				}
-			}
-
-			fn inbound_lane_data_key(&self, instance: &InstanceId, lane: &LaneId) -> Option<StorageKey> {
-				match *instance {
-					RIALTO_BRIDGE_INSTANCE => Some(millau_runtime::rialto_messages::inbound_lane_data_key(lane)),
-					_ => None,
-				}
-			}
-		}
-
-		use pallet_message_lane_rpc::{MessageLaneApi, MessageLaneRpcHandler};
+		use pallet_message_lane_rpc::{
+			MessageLaneApi, MessageLaneAuthorApi, MessageLaneAuthorRpcHandler, MessageLaneDecoderApi,
+			MessageLaneDecoderRpcHandler, MessageLaneRpcHandler,
+		};
+		use pallet_substrate_bridge_rpc::{BridgeApi, BridgeRpcHandler};
 		use sc_finality_grandpa_rpc::{GrandpaApi, GrandpaRpcHandler};
 		use sc_rpc::DenyUnsafe;
 		use substrate_frame_rpc_system::{FullSystem, SystemApi};
@@ -199,12 +176,13 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 		let backend = backend.clone();
 		let client = client.clone();
 		let pool = transaction_pool.clone();
+		let keystore = keystore.clone() as sp_core::traits::BareCryptoStorePtr;
 
 		let justification_stream = grandpa_link.justification_stream();
 		let shared_authority_set = grandpa_link.shared_authority_set().clone();
 		let finality_proof_provider = GrandpaFinalityProofProvider::new_for_service(backend.clone(), client.clone());
 
-		Box::new(move |_, subscription_executor| {
+		Box::new(move |deny_unsafe, subscription_executor| {
 			let shared_voter_state = SharedVoterState::empty();
 
 			let mut io = jsonrpc_core::IoHandler::default();
@@ -217,18 +195,41 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 				shared_authority_set.clone(),
 				shared_voter_state,
 				justification_stream.clone(),
-				subscription_executor,
+				subscription_executor.clone(),
 				finality_proof_provider.clone(),
 			)));
-			io.extend_with(MessageLaneApi::to_delegate(MessageLaneRpcHandler::new(
+			io.extend_with(MessageLaneApi::<_, OutboundMessageFee, InboundRelayer>::to_delegate(
+				MessageLaneRpcHandler::new(backend.clone(), client.clone(), subscription_executor),
+			));
+			io.extend_with(BridgeApi::<_, BridgedBlockNumber, BridgedBlockHash>::to_delegate(
+				BridgeRpcHandler::new(client.clone(), bridge_peer_rpc.clone()),
+			));
+			io.extend_with(MessageLaneAuthorApi::<OutboundMessageFee>::to_delegate(
+				MessageLaneAuthorRpcHandler::new(
+					client.clone(),
+					pool.clone(),
+					keystore.clone(),
+					MessageLaneRpc,
+					deny_unsafe,
+				),
+			));
+			io.extend_with(MessageLaneDecoderApi::to_delegate(MessageLaneDecoderRpcHandler::new(
 				backend.clone(),
-				Arc::new(MillauMessageLaneKeys),
+				client.clone(),
+				MessageLaneRpc,
 			)));
 
 			io
 		})
 	};
 
+	if let Some(ref prometheus_registry) = prometheus_registry {
+		let bridge_metrics = crate::metrics::BridgeMetrics::register(prometheus_registry)?;
+		task_manager
+			.spawn_handle()
+			.spawn("bridge-metrics", crate::metrics::run(client.clone(), bridge_metrics));
+	}
+
 	sc_service::spawn_tasks(sc_service::SpawnTasksParams {
 		network: network.clone(),
 		client: client.clone(),
@@ -251,22 +252,24 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 
 		let can_author_with = sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone());
 
-		let aura = sc_consensus_aura::start_aura::<_, _, _, _, _, AuraPair, _, _, _>(
-			sc_consensus_aura::slot_duration(&*client)?,
-			client.clone(),
+		let babe = sc_consensus_babe::start_babe(sc_consensus_babe::BabeParams {
+			keystore: keystore.clone(),
+			client: client.clone(),
 			select_chain,
+			env: proposer,
 			block_import,
-			proposer,
-			network.clone(),
-			inherent_data_providers.clone(),
+			sync_oracle: network.clone(),
+			inherent_data_providers: inherent_data_providers.clone(),
 			force_authoring,
-			keystore.clone(),
+			babe_link,
 			can_author_with,
-		)?;
+		})?;
 
-		// the AURA authoring task is considered essential, i.e. if it
+		// the BABE authoring task is considered essential, i.e. if it
 		// fails we take down the service with it.
-		task_manager.spawn_essential_handle().spawn_blocking("aura", aura);
+		task_manager
+			.spawn_essential_handle()
+			.spawn_blocking("babe-proposer", babe);
 	}
 
 	// if the node isn't actively participating in consensus then it doesn't
@@ -340,12 +343,21 @@ pub fn new_light(config: Configuration) -> Result<TaskManager, ServiceError> {
 	let finality_proof_import = grandpa_block_import.clone();
 	let finality_proof_request_builder = finality_proof_import.create_finality_proof_request_builder();
 
-	let import_queue = sc_consensus_aura::import_queue::<_, _, _, AuraPair, _, _>(
-		sc_consensus_aura::slot_duration(&*client)?,
+	let (babe_block_import, babe_link) = sc_consensus_babe::block_import(
+		sc_consensus_babe::Config::get_or_compute(&*client)?,
 		grandpa_block_import,
+		client.clone(),
+	)?;
+
+	let select_chain = sc_consensus::LongestChain::new(backend.clone());
+
+	let import_queue = sc_consensus_babe::import_queue(
+		babe_link,
+		babe_block_import,
 		None,
 		Some(Box::new(finality_proof_import)),
 		client.clone(),
+		select_chain,
 		InherentDataProviders::new(),
 		&task_manager.spawn_handle(),
 		config.prometheus_registry(),
@@ -377,12 +389,29 @@ pub fn new_light(config: Configuration) -> Result<TaskManager, ServiceError> {
 		);
 	}
 
+	let rpc_extensions_builder = {
+		use pallet_message_lane_rpc::{LightMessageLaneRpcHandler, MessageLaneApi};
+
+		let client = client.clone();
+		let on_demand = on_demand.clone();
+
+		// GRANDPA RPC isn't available here, because light client doesn't run GRANDPA voter
+		Box::new(move |_, _| {
+			let mut io = jsonrpc_core::IoHandler::default();
+			io.extend_with(MessageLaneApi::<_, OutboundMessageFee, InboundRelayer>::to_delegate(
+				LightMessageLaneRpcHandler::new(client.clone(), on_demand.clone()),
+			));
+
+			io
+		})
+	};
+
 	sc_service::spawn_tasks(sc_service::SpawnTasksParams {
 		remote_blockchain: Some(backend.remote_blockchain()),
 		transaction_pool,
 		task_manager: &mut task_manager,
 		on_demand: Some(on_demand),
-		rpc_extensions_builder: Box::new(|_, _| ()),
+		rpc_extensions_builder,
 		telemetry_connection_sinks: sc_service::TelemetryConnectionSinks::default(),
 		config,
 		client,
@@ -28,18 +28,23 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+#[cfg(feature = "std")]
+pub mod bridge_rpc;
+pub mod rialto_heartbeat;
 pub mod rialto_messages;
 
-use codec::Decode;
+use bridge_runtime_common::messages;
+use codec::{Decode, Encode};
 use pallet_grandpa::{fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList};
 use sp_api::impl_runtime_apis;
-use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
-use sp_runtime::traits::{Block as BlockT, IdentityLookup, NumberFor, OpaqueKeys};
+use sp_runtime::traits::{
+	Block as BlockT, Extrinsic as ExtrinsicT, IdentityLookup, NumberFor, OpaqueKeys, SaturatedConversion, Verify,
+};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, MultiSignature, MultiSigner,
+	ApplyExtrinsicResult, MultiSignature, MultiSigner, RuntimeDebug,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -49,16 +54,23 @@ use sp_version::RuntimeVersion;
 // A few exports that help ease life for downstream crates.
 pub use frame_support::{
 	construct_runtime, parameter_types,
-	traits::{Currency, ExistenceRequirement, Imbalance, KeyOwnerProofSystem, Randomness},
+	traits::{Currency, ExistenceRequirement, Imbalance, InstanceFilter, KeyOwnerProofSystem, Randomness},
 	weights::{IdentityFee, RuntimeDbWeight, Weight},
 	StorageValue,
 };
 
+pub use frame_system::Call as SystemCall;
 pub use pallet_balances::Call as BalancesCall;
 pub use pallet_message_lane::Call as MessageLaneCall;
-pub use pallet_substrate_bridge::Call as BridgeRialtoCall;
+pub use pallet_proxy::Call as ProxyCall;
 pub use pallet_sudo::Call as SudoCall;
 pub use pallet_timestamp::Call as TimestampCall;
+pub use pallet_utility::Call as UtilityCall;
+
+/// Call of the Rialto headers bridge pallet.
+pub type BridgeRialtoCall = pallet_substrate_bridge::Call<Runtime>;
+/// Call of the Westend headers bridge pallet.
+pub type BridgeWestendCall = pallet_substrate_bridge::Call<Runtime, WestendBridgeInstance>;
 
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
@@ -82,7 +94,7 @@ pub type AccountIndex = u32;
 pub type Balance = bp_millau::Balance;
 
 /// Index of a transaction in the chain.
-pub type Index = u32;
+pub type Index = bp_millau::Index;
 
 /// A hash of some data used by the chain.
 pub type Hash = bp_millau::Hash;
@@ -112,7 +124,7 @@ pub mod opaque {
 
 impl_opaque_keys! {
 	pub struct SessionKeys {
-		pub aura: Aura,
+		pub babe: Babe,
 		pub grandpa: Grandpa,
 	}
 }
@@ -132,11 +144,30 @@ pub const MILLISECS_PER_BLOCK: u64 = 6000;
 
 pub const SLOT_DURATION: u64 = MILLISECS_PER_BLOCK;
 
+/// Authorities are changing every 5 minutes.
+pub const EPOCH_DURATION_IN_SLOTS: u64 = 5 * MINUTES;
+
+/// 1 in 4 blocks (on average, not counting collisions) will be primary BABE blocks.
+pub const PRIMARY_PROBABILITY: (u64, u64) = (1, 4);
+
 // These time units are defined in number of blocks.
 pub const MINUTES: BlockNumber = 60_000 / (MILLISECS_PER_BLOCK as BlockNumber);
 pub const HOURS: BlockNumber = MINUTES * 60;
 pub const DAYS: BlockNumber = HOURS * 24;
 
+/// Maximal depth of the Millau block (counted back from the best finalized block), which state may
+/// be used to prove messages and lane states to the Rialto chain.
+///
+/// Proofs are anchored at the Millau header that is known to the Millau headers pallet at Rialto.
+/// This header lags behind the best finalized Millau header while the headers relay is catching up,
+/// and messages stay deliverable until delivery is confirmed, so nodes that are serving proofs to
+/// relayers must keep state of at least this number of blocks.
+pub const BRIDGE_PROOF_ANCHOR_DEPTH: BlockNumber = DAYS;
+
+// TODO: benchmark bridge pallets (https://github.com/paritytech/parity-bridges-common/issues/78)
+/// Bridge pallets, which benchmarks are used to generate weights of the runtime.
+pub const BENCHMARKED_BRIDGE_PALLETS: &[&str] = &[];
+
 /// The version information used to identify this runtime when compiled natively.
 #[cfg(feature = "std")]
 pub fn native_version() -> NativeVersion {
@@ -218,9 +249,33 @@ impl frame_system::Trait for Runtime {
 	type SystemWeightInfo = ();
 }
 
-impl pallet_aura::Trait for Runtime {
-	type AuthorityId = AuraId;
+parameter_types! {
+	pub const EpochDuration: u64 = EPOCH_DURATION_IN_SLOTS;
+	pub const ExpectedBlockTime: u64 = MILLISECS_PER_BLOCK;
+}
+
+impl pallet_babe::Trait for Runtime {
+	type EpochDuration = EpochDuration;
+	type ExpectedBlockTime = ExpectedBlockTime;
+	// session module is the trigger
+	type EpochChangeTrigger = pallet_babe::ExternalTrigger;
+	type KeyOwnerProofSystem = ();
+	type KeyOwnerProof =
+		<Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, pallet_babe::AuthorityId)>>::Proof;
+	type KeyOwnerIdentification =
+		<Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, pallet_babe::AuthorityId)>>::IdentificationTuple;
+	type HandleEquivocation = ();
+	// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+	type WeightInfo = ();
 }
+
+parameter_types! {
+	/// Bridge message calls that have failed to dispatch may be retried later.
+	pub const StoreFailedBridgeCalls: bool = true;
+	/// Failed dispatch of the sovereign account message is reported if the account has less funds.
+	pub const MinSovereignAccountBalance: bp_millau::Balance = 1_000_000;
+}
+
 impl pallet_bridge_call_dispatch::Trait for Runtime {
 	type Event = Event;
 	type MessageId = (bp_message_lane::LaneId, bp_message_lane::MessageNonce);
@@ -228,6 +283,10 @@ impl pallet_bridge_call_dispatch::Trait for Runtime {
 	type SourceChainAccountPublic = MultiSigner;
 	type TargetChainAccountPublic = MultiSigner;
 	type TargetChainSignature = MultiSignature;
+	type CallWeightAdjuster = ();
+	type StoreFailedCalls = StoreFailedBridgeCalls;
+	type Currency = pallet_balances::Module<Runtime>;
+	type MinSovereignAccountBalance = MinSovereignAccountBalance;
 }
 
 impl pallet_grandpa::Trait for Runtime {
@@ -249,7 +308,7 @@ parameter_types! {
 impl pallet_timestamp::Trait for Runtime {
 	/// A timestamp: milliseconds since the unix epoch.
 	type Moment = u64;
-	type OnTimestampSet = Aura;
+	type OnTimestampSet = Babe;
 	type MinimumPeriod = MinimumPeriod;
 	// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
 	type WeightInfo = ();
@@ -294,17 +353,120 @@ impl pallet_sudo::Trait for Runtime {
 }
 
 parameter_types! {
-	/// Authorities are changing every 5 minutes.
-	pub const Period: BlockNumber = 5 * MINUTES;
-	pub const Offset: BlockNumber = 0;
+	pub const BridgeCouncilMotionDuration: BlockNumber = 5 * MINUTES;
+	pub const BridgeCouncilMaxProposals: u32 = 16;
+	pub const BridgeCouncilMaxMembers: u32 = 16;
+}
+
+/// Instance of the collective pallet that administers bridge pallets.
+pub type BridgeCouncilInstance = pallet_collective::Instance1;
+
+impl pallet_collective::Trait<BridgeCouncilInstance> for Runtime {
+	type Origin = Origin;
+	type Proposal = Call;
+	type Event = Event;
+	type MotionDuration = BridgeCouncilMotionDuration;
+	type MaxProposals = BridgeCouncilMaxProposals;
+	type MaxMembers = BridgeCouncilMaxMembers;
+	type DefaultVote = pallet_collective::PrimeDefaultVote;
+	// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+	type WeightInfo = ();
+}
+
+/// Origin that administers bridge pallets (halts and resumes operations, changes owners): at
+/// least 2/3 of the bridge council members.
+pub type EnsureBridgeCouncil = pallet_collective::EnsureProportionAtLeast<
+	sp_core::u32_trait::_2,
+	sp_core::u32_trait::_3,
+	AccountId,
+	BridgeCouncilInstance,
+>;
+
+/// The type used to represent the kinds of proxying allowed.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, RuntimeDebug)]
+pub enum ProxyType {
+	/// Proxy may submit any call on behalf of the proxied account.
+	Any,
+	/// Proxy may only submit relay calls (headers, finality proofs, messages and delivery
+	/// confirmations) of Rialto and Westend bridges. Other calls of bridge pallets (e.g.
+	/// `send_message` or owner calls) are rejected. Relay calls may also be wrapped into `utility`
+	/// batches - every batched call is still checked by this filter.
+	///
+	/// This is the type that should be given to relayer accounts, so that the funded account may
+	/// be kept in cold storage.
+	BridgeRelay,
+}
+
+impl Default for ProxyType {
+	fn default() -> Self {
+		ProxyType::Any
+	}
+}
+
+impl InstanceFilter<Call> for ProxyType {
+	fn filter(&self, call: &Call) -> bool {
+		match self {
+			ProxyType::Any => true,
+			ProxyType::BridgeRelay => matches!(
+				call,
+				Call::BridgeRialto(BridgeRialtoCall::import_signed_header(..))
+					| Call::BridgeRialto(BridgeRialtoCall::finalize_header(..))
+					| Call::BridgeRialtoMessageLane(MessageLaneCall::receive_messages_proof(..))
+					| Call::BridgeRialtoMessageLane(MessageLaneCall::receive_messages_delivery_proof(..))
+					| Call::BridgeWestend(BridgeWestendCall::import_signed_header(..))
+					| Call::BridgeWestend(BridgeWestendCall::finalize_header(..))
+					| Call::Utility(..)
+			),
+		}
+	}
+
+	fn is_superset(&self, other: &Self) -> bool {
+		match (self, other) {
+			(ProxyType::Any, _) => true,
+			(_, ProxyType::Any) => false,
+			(ProxyType::BridgeRelay, ProxyType::BridgeRelay) => true,
+		}
+	}
+}
+
+parameter_types! {
+	pub const ProxyDepositBase: Balance = 1_000;
+	pub const ProxyDepositFactor: Balance = 100;
+	pub const MaxProxies: u16 = 32;
+	pub const MaxPending: u32 = 32;
+	pub const AnnouncementDepositBase: Balance = 1_000;
+	pub const AnnouncementDepositFactor: Balance = 100;
+}
+
+impl pallet_proxy::Trait for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type ProxyType = ProxyType;
+	type ProxyDepositBase = ProxyDepositBase;
+	type ProxyDepositFactor = ProxyDepositFactor;
+	type MaxProxies = MaxProxies;
+	// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+	type WeightInfo = ();
+	type MaxPending = MaxPending;
+	type CallHasher = Hashing;
+	type AnnouncementDepositBase = AnnouncementDepositBase;
+	type AnnouncementDepositFactor = AnnouncementDepositFactor;
+}
+
+impl pallet_utility::Trait for Runtime {
+	type Event = Event;
+	type Call = Call;
+	// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+	type WeightInfo = ();
 }
 
 impl pallet_session::Trait for Runtime {
 	type Event = Event;
 	type ValidatorId = <Self as frame_system::Trait>::AccountId;
 	type ValidatorIdOf = ();
-	type ShouldEndSession = pallet_session::PeriodicSessions<Period, Offset>;
-	type NextSessionRotation = pallet_session::PeriodicSessions<Period, Offset>;
+	type ShouldEndSession = Babe;
+	type NextSessionRotation = Babe;
 	type SessionManager = pallet_shift_session_manager::Module<Runtime>;
 	type SessionHandler = <SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
 	type Keys = SessionKeys;
@@ -315,6 +477,17 @@ impl pallet_session::Trait for Runtime {
 
 impl pallet_substrate_bridge::Trait for Runtime {
 	type BridgedChain = bp_rialto::Rialto;
+	type AdminOrigin = EnsureBridgeCouncil;
+	type FinalityVerifier = pallet_substrate_bridge::GrandpaFinalityVerifier<bp_rialto::Header>;
+}
+
+/// Instance of the headers bridge pallet that is tracking Westend headers.
+pub type WestendBridgeInstance = pallet_substrate_bridge::Instance1;
+
+impl pallet_substrate_bridge::Trait<WestendBridgeInstance> for Runtime {
+	type BridgedChain = bp_westend::Westend;
+	type AdminOrigin = EnsureBridgeCouncil;
+	type FinalityVerifier = pallet_substrate_bridge::GrandpaFinalityVerifier<bp_westend::Header>;
 }
 
 impl pallet_shift_session_manager::Trait for Runtime {}
@@ -322,12 +495,24 @@ impl pallet_shift_session_manager::Trait for Runtime {}
 parameter_types! {
 	pub const MaxMessagesToPruneAtOnce: bp_message_lane::MessageNonce = 8;
 	pub const MaxUnconfirmedMessagesAtInboundLane: bp_message_lane::MessageNonce = bp_millau::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE;
+	pub const MaxMessagesProofSize: u32 = bp_millau::MAX_MESSAGES_PROOF_SIZE;
+	pub const MaxMessagesDeliveryProofSize: u32 = bp_millau::MAX_MESSAGES_DELIVERY_PROOF_SIZE;
+	pub MessageLaneWeights: bp_message_lane_weights::MessageLaneWeights = bp_millau::message_lane_weights();
+}
+
+impl pallet_bridge_relayers::Trait for Runtime {
+	type Event = Event;
+	type Currency = pallet_balances::Module<Runtime>;
 }
 
 impl pallet_message_lane::Trait for Runtime {
 	type Event = Event;
+	type AdminOrigin = EnsureBridgeCouncil;
 	type MaxMessagesToPruneAtOnce = MaxMessagesToPruneAtOnce;
 	type MaxUnconfirmedMessagesAtInboundLane = MaxUnconfirmedMessagesAtInboundLane;
+	type MaxMessagesProofSize = MaxMessagesProofSize;
+	type MaxMessagesDeliveryProofSize = MaxMessagesDeliveryProofSize;
+	type WeightInfo = pallet_message_lane::WeightsFrom<MessageLaneWeights>;
 
 	type OutboundPayload = crate::rialto_messages::ToRialtoMessagePayload;
 	type OutboundMessageFee = Balance;
@@ -338,13 +523,62 @@ impl pallet_message_lane::Trait for Runtime {
 
 	type TargetHeaderChain = crate::rialto_messages::Rialto;
 	type LaneMessageVerifier = crate::rialto_messages::ToRialtoMessageVerifier;
-	type MessageDeliveryAndDispatchPayment =
-		pallet_message_lane::instant_payments::InstantCurrencyPayments<AccountId, pallet_balances::Module<Runtime>>;
+	type MessageDeliveryAndDispatchPayment = pallet_bridge_relayers::Module<Runtime>;
 
 	type SourceHeaderChain = crate::rialto_messages::Rialto;
 	type MessageDispatch = crate::rialto_messages::FromRialtoMessageDispatch;
 }
 
+impl frame_system::offchain::SigningTypes for Runtime {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+	Call: From<C>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = UncheckedExtrinsic;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Runtime
+where
+	Call: From<LocalCall>,
+{
+	fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: Call,
+		public: <Signature as Verify>::Signer,
+		account: AccountId,
+		nonce: Index,
+	) -> Option<(Call, <UncheckedExtrinsic as ExtrinsicT>::SignaturePayload)> {
+		// the transaction is valid for (at most) `BlockHashCount` blocks
+		let period = BlockHashCount::get()
+			.checked_next_power_of_two()
+			.map(|period| period / 2)
+			.unwrap_or(2) as u64;
+		let current_block = System::block_number().saturated_into::<u64>().saturating_sub(1);
+		let extra = bp_millau::signed_extensions::<Runtime>(generic::Era::mortal(period, current_block), nonce, 0);
+		let raw_payload = SignedPayload::new(call, extra).ok()?;
+		let signature = raw_payload.using_encoded(|payload| C::sign(payload, public))?;
+		let (call, extra, _) = raw_payload.deconstruct();
+		Some((call, (account, signature, extra)))
+	}
+}
+
+parameter_types! {
+	pub const RialtoHeartbeatPeriod: BlockNumber = 100;
+	pub const RialtoHeartbeatLane: bp_message_lane::LaneId = bp_message_lane::LaneId::new(bp_message_lane::LaneNamespace::LEGACY, 0);
+	pub const RialtoSpecVersion: u32 = 1;
+}
+
+impl rialto_heartbeat::Trait for Runtime {
+	type AuthorityId = rialto_heartbeat::crypto::HeartbeatAuthorityId;
+	type HeartbeatPeriod = RialtoHeartbeatPeriod;
+	type HeartbeatLane = RialtoHeartbeatLane;
+	type RialtoSpecVersion = RialtoSpecVersion;
+}
+
 construct_runtime!(
 	pub enum Runtime where
 		Block = Block,
@@ -353,17 +587,23 @@ construct_runtime!(
 	{
 		BridgeRialto: pallet_substrate_bridge::{Module, Call, Storage, Config<T>},
 		BridgeRialtoMessageLane: pallet_message_lane::{Module, Call, Event<T>},
-		BridgeCallDispatch: pallet_bridge_call_dispatch::{Module, Event<T>},
+		BridgeRelayers: pallet_bridge_relayers::{Module, Call, Storage, Event<T>},
+		BridgeCallDispatch: pallet_bridge_call_dispatch::{Module, Call, Storage, Event<T>},
 		System: frame_system::{Module, Call, Config, Storage, Event<T>},
 		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Module, Call, Storage},
+		Babe: pallet_babe::{Module, Call, Storage, Config, Inherent},
 		Timestamp: pallet_timestamp::{Module, Call, Storage, Inherent},
-		Aura: pallet_aura::{Module, Config<T>, Inherent},
 		Grandpa: pallet_grandpa::{Module, Call, Storage, Config, Event},
 		Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
 		TransactionPayment: pallet_transaction_payment::{Module, Storage},
 		Sudo: pallet_sudo::{Module, Call, Config<T>, Storage, Event<T>},
+		BridgeCouncil: pallet_collective::<Instance1>::{Module, Call, Storage, Origin<T>, Event<T>, Config<T>},
 		Session: pallet_session::{Module, Call, Storage, Event, Config<T>},
 		ShiftSessionManager: pallet_shift_session_manager::{Module},
+		Proxy: pallet_proxy::{Module, Call, Storage, Event<T>},
+		Utility: pallet_utility::{Module, Call, Event},
+		BridgeWestend: pallet_substrate_bridge::<Instance1>::{Module, Call, Storage, Config<T>},
+		RialtoHeartbeat: rialto_heartbeat::{Module},
 	}
 );
 
@@ -378,15 +618,7 @@ pub type SignedBlock = generic::SignedBlock<Block>;
 /// BlockId type as expected by this runtime.
 pub type BlockId = generic::BlockId<Block>;
 /// The SignedExtension to the basic transaction logic.
-pub type SignedExtra = (
-	frame_system::CheckSpecVersion<Runtime>,
-	frame_system::CheckTxVersion<Runtime>,
-	frame_system::CheckGenesis<Runtime>,
-	frame_system::CheckEra<Runtime>,
-	frame_system::CheckNonce<Runtime>,
-	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
-);
+pub type SignedExtra = bp_millau::SignedExtensions<Runtime>;
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
 /// Unchecked extrinsic type as expected by this runtime.
@@ -397,6 +629,24 @@ pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Call, SignedExt
 pub type Executive =
 	frame_executive::Executive<Runtime, Block, frame_system::ChainContext<Runtime>, Runtime, AllModules>;
 
+/// Rehearse runtime upgrade: check storage of bridge pallets, run `on_runtime_upgrade` of all
+/// pallets and check their storage again.
+///
+/// Must be called within externalities that are built from the state snapshot.
+#[cfg(feature = "try-runtime")]
+pub fn try_runtime_upgrade() -> Result<Weight, &'static str> {
+	use frame_support::traits::OnRuntimeUpgrade;
+
+	BridgeRialtoMessageLane::pre_upgrade()?;
+
+	let weight = <frame_system::Module<Runtime> as OnRuntimeUpgrade>::on_runtime_upgrade()
+		.saturating_add(<AllModules as OnRuntimeUpgrade>::on_runtime_upgrade());
+
+	BridgeRialtoMessageLane::post_upgrade()?;
+
+	Ok(weight)
+}
+
 impl_runtime_apis! {
 	impl sp_api::Core<Block> for Runtime {
 		fn version() -> RuntimeVersion {
@@ -449,6 +699,19 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<
+		Block,
+		Balance,
+		UncheckedExtrinsic,
+	> for Runtime {
+		fn query_info(
+			uxt: UncheckedExtrinsic,
+			len: u32,
+		) -> pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo<Balance> {
+			TransactionPayment::query_info(uxt, len)
+		}
+	}
+
 	impl sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block> for Runtime {
 		fn validate_transaction(
 			source: TransactionSource,
@@ -464,13 +727,47 @@ impl_runtime_apis! {
 		}
 	}
 
-	impl sp_consensus_aura::AuraApi<Block, AuraId> for Runtime {
-		fn slot_duration() -> u64 {
-			Aura::slot_duration()
+	impl sp_consensus_babe::BabeApi<Block> for Runtime {
+		fn configuration() -> sp_consensus_babe::BabeGenesisConfiguration {
+			// The choice of `c` parameter (where `1 - c` represents the
+			// probability of a slot being empty), is done in accordance to the
+			// slot duration and expected target block time, for safely
+			// resisting network delays of maximum two seconds.
+			// <https://research.web3.foundation/en/latest/polkadot/BABE/Babe/#6-practical-results>
+			sp_consensus_babe::BabeGenesisConfiguration {
+				slot_duration: Babe::slot_duration(),
+				epoch_length: EpochDuration::get(),
+				c: PRIMARY_PROBABILITY,
+				genesis_authorities: Babe::authorities(),
+				randomness: Babe::randomness(),
+				allowed_slots: sp_consensus_babe::AllowedSlots::PrimaryAndSecondaryPlainSlots,
+			}
+		}
+
+		fn current_epoch_start() -> sp_consensus_babe::SlotNumber {
+			Babe::current_epoch_start()
+		}
+
+		fn submit_report_equivocation_unsigned_extrinsic(
+			equivocation_proof: sp_consensus_babe::EquivocationProof<<Block as BlockT>::Header>,
+			key_owner_proof: sp_consensus_babe::OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			let key_owner_proof = key_owner_proof.decode()?;
+
+			Babe::submit_unsigned_equivocation_report(
+				equivocation_proof,
+				key_owner_proof,
+			)
 		}
 
-		fn authorities() -> Vec<AuraId> {
-			Aura::authorities()
+		fn generate_key_ownership_proof(
+			_slot_number: sp_consensus_babe::SlotNumber,
+			_authority_id: sp_consensus_babe::AuthorityId,
+		) -> Option<sp_consensus_babe::OpaqueKeyOwnershipProof> {
+			// NOTE: this is the only implementation possible since we've
+			// defined our key owner proof type as a bottom type (i.e. a type
+			// with no values).
+			None
 		}
 	}
 
@@ -517,6 +814,37 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl bp_runtime::BridgeStatusApi<Block, bp_rialto::BlockNumber, bp_rialto::Hash> for Runtime {
+		fn bridge_status(
+			instance: bp_runtime::InstanceId,
+		) -> Option<bp_runtime::BridgeStatus<bp_rialto::BlockNumber, bp_rialto::Hash>> {
+			match instance {
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => {
+					let best_finalized = BridgeRialto::best_finalized();
+					Some(bp_runtime::BridgeStatus {
+						best_headers: BridgeRialto::best_headers(),
+						best_finalized: (best_finalized.number, best_finalized.hash()),
+						is_headers_pallet_halted: BridgeRialto::is_halted(),
+						is_message_lane_pallet_halted: BridgeRialtoMessageLane::is_halted(),
+					})
+				}
+				_ => None,
+			}
+		}
+	}
+
+	impl bp_runtime::SovereignAccountApi<Block, AccountId, Balance> for Runtime {
+		fn sovereign_account(instance: bp_runtime::InstanceId) -> Option<(AccountId, Balance)> {
+			match instance {
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => Some((
+					BridgeCallDispatch::sovereign_account(instance),
+					BridgeCallDispatch::sovereign_account_balance(instance),
+				)),
+				_ => None,
+			}
+		}
+	}
+
 	impl bp_rialto::RialtoHeaderApi<Block> for Runtime {
 		fn best_blocks() -> Vec<(bp_rialto::BlockNumber, bp_rialto::Hash)> {
 			BridgeRialto::best_headers()
@@ -540,40 +868,227 @@ impl_runtime_apis! {
 		}
 	}
 
-	// TODO: runtime should support several chains (https://github.com/paritytech/parity-bridges-common/issues/457)
+	impl bp_westend::WestendHeaderApi<Block> for Runtime {
+		fn best_blocks() -> Vec<(bp_westend::BlockNumber, bp_westend::Hash)> {
+			BridgeWestend::best_headers()
+		}
+
+		fn finalized_block() -> (bp_westend::BlockNumber, bp_westend::Hash) {
+			let header = BridgeWestend::best_finalized();
+			(header.number, header.hash())
+		}
+
+		fn incomplete_headers() -> Vec<(bp_westend::BlockNumber, bp_westend::Hash)> {
+			BridgeWestend::require_justifications()
+		}
+
+		fn is_known_block(hash: bp_westend::Hash) -> bool {
+			BridgeWestend::is_known_header(hash)
+		}
+
+		fn is_finalized_block(hash: bp_westend::Hash) -> bool {
+			BridgeWestend::is_finalized_header(hash)
+		}
+	}
+
 	impl bp_message_lane::OutboundLaneApi<Block> for Runtime {
 		fn messages_dispatch_weight(
+			instance: bp_runtime::InstanceId,
 			lane: bp_message_lane::LaneId,
 			begin: bp_message_lane::MessageNonce,
 			end: bp_message_lane::MessageNonce,
-		) -> Vec<(bp_message_lane::MessageNonce, Weight)> {
-			(begin..=end).filter_map(|nonce| {
-				let encoded_payload = BridgeRialtoMessageLane::outbound_message_payload(lane, nonce)?;
-				let decoded_payload = rialto_messages::ToRialtoMessagePayload::decode(
-					&mut &encoded_payload[..]
-				).ok()?;
-				Some((nonce, decoded_payload.weight))
-			})
-			.collect()
+		) -> Option<Vec<(bp_message_lane::MessageNonce, Weight)>> {
+			match instance {
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => Some((begin..=end).filter_map(|nonce| {
+					let encoded_payload = BridgeRialtoMessageLane::outbound_message_payload(lane, nonce)?;
+					let decoded_payload = rialto_messages::ToRialtoMessagePayload::decode(
+						&mut &encoded_payload[..]
+					).ok()?;
+					Some((nonce, decoded_payload.weight))
+				})
+				.collect()),
+				_ => None,
+			}
 		}
 
-		fn latest_received_nonce(lane: bp_message_lane::LaneId) -> bp_message_lane::MessageNonce {
-			BridgeRialtoMessageLane::outbound_latest_received_nonce(lane)
+		fn latest_received_nonce(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+		) -> Option<bp_message_lane::MessageNonce> {
+			match instance {
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => Some(BridgeRialtoMessageLane::outbound_latest_received_nonce(lane)),
+				_ => None,
+			}
 		}
 
-		fn latest_generated_nonce(lane: bp_message_lane::LaneId) -> bp_message_lane::MessageNonce {
-			BridgeRialtoMessageLane::outbound_latest_generated_nonce(lane)
+		fn latest_generated_nonce(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+		) -> Option<bp_message_lane::MessageNonce> {
+			match instance {
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => Some(BridgeRialtoMessageLane::outbound_latest_generated_nonce(lane)),
+				_ => None,
+			}
+		}
+	}
+
+	impl bp_message_lane::OutboundLaneFeeApi<Block, Balance> for Runtime {
+		fn estimate_message_delivery_and_dispatch_fee(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+			payload: bp_message_lane::MessagePayload,
+		) -> Option<Balance> {
+			match instance {
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => {
+					let decoded_payload = rialto_messages::ToRialtoMessagePayload::decode(&mut &payload[..]).ok()?;
+					<messages::source::FromThisChainMessageFeeEstimator<
+						rialto_messages::WithRialtoMessageBridge,
+					> as bp_message_lane::source_chain::MessageFeeEstimator<_>>::estimate_message_fee(
+						&lane,
+						&decoded_payload,
+					).ok()
+				}
+				_ => None,
+			}
+		}
+
+		fn messages_fee(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+			begin: bp_message_lane::MessageNonce,
+			end: bp_message_lane::MessageNonce,
+		) -> Option<Balance> {
+			match instance {
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => (begin..=end).try_fold(0, |total: Balance, nonce| {
+					total.checked_add(BridgeRialtoMessageLane::outbound_message_fee(lane, nonce)?)
+				}),
+				_ => None,
+			}
+		}
+	}
+
+	impl bp_message_lane::MessageLaneStorageApi<Block> for Runtime {
+		fn messages_keys(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+			begin: bp_message_lane::MessageNonce,
+			end: bp_message_lane::MessageNonce,
+		) -> Option<Vec<Vec<u8>>> {
+			match instance {
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => Some(
+					(begin..=end).map(|nonce| rialto_messages::message_key(&lane, nonce).0).collect()
+				),
+				_ => None,
+			}
+		}
+
+		fn outbound_lane_data_key(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+		) -> Option<Vec<u8>> {
+			match instance {
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => Some(rialto_messages::outbound_lane_data_key(&lane).0),
+				_ => None,
+			}
+		}
+
+		fn inbound_lane_data_key(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+		) -> Option<Vec<u8>> {
+			match instance {
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => Some(rialto_messages::inbound_lane_data_key(&lane).0),
+				_ => None,
+			}
+		}
+
+		fn storage_version_key(instance: bp_runtime::InstanceId) -> Option<Vec<u8>> {
+			match instance {
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => Some(rialto_messages::storage_version_key().0),
+				_ => None,
+			}
+		}
+
+		fn inbound_lane_nonces(
+			instance: bp_runtime::InstanceId,
+			encoded_lane_data: Vec<u8>,
+		) -> Option<(bp_message_lane::MessageNonce, bp_message_lane::MessageNonce)> {
+			match instance {
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => {
+					bp_message_lane::InboundLaneData::<bp_rialto::AccountId>::decode(&mut &encoded_lane_data[..])
+						.ok()
+						.map(|data| (data.latest_received_nonce, data.latest_confirmed_nonce))
+				}
+				_ => None,
+			}
+		}
+	}
+
+	impl bp_message_lane::LanesApi<Block> for Runtime {
+		fn lanes(instance: bp_runtime::InstanceId) -> Option<Vec<bp_message_lane::LaneState>> {
+			match instance {
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => Some(BridgeRialtoMessageLane::lanes()),
+				_ => None,
+			}
+		}
+	}
+
+	impl bp_message_lane::InboundLaneRelayersApi<Block, bp_rialto::AccountId> for Runtime {
+		fn unrewarded_relayers(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+		) -> Option<Vec<bp_message_lane::UnrewardedRelayer<bp_rialto::AccountId>>> {
+			match instance {
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => Some(BridgeRialtoMessageLane::inbound_unrewarded_relayers(lane)),
+				_ => None,
+			}
+		}
+
+		fn max_unrewarded_relayer_entries(
+			instance: bp_runtime::InstanceId,
+		) -> Option<bp_message_lane::MessageNonce> {
+			match instance {
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => Some(MaxUnconfirmedMessagesAtInboundLane::get()),
+				_ => None,
+			}
+		}
+	}
+
+	impl bp_message_lane::InboundMessageDispatchApi<Block> for Runtime {
+		fn estimate_message_dispatch_weight(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+			payload: bp_message_lane::MessagePayload,
+		) -> Option<Weight> {
+			match instance {
+				// dispatch weight doesn't depend on the message fee
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => {
+					BridgeRialtoMessageLane::inbound_message_dispatch_weight(lane, payload, 0)
+				}
+				_ => None,
+			}
 		}
 	}
 
-	// TODO: runtime should support several chains (https://github.com/paritytech/parity-bridges-common/issues/457)
 	impl bp_message_lane::InboundLaneApi<Block> for Runtime {
-		fn latest_received_nonce(lane: bp_message_lane::LaneId) -> bp_message_lane::MessageNonce {
-			BridgeRialtoMessageLane::inbound_latest_received_nonce(lane)
+		fn latest_received_nonce(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+		) -> Option<bp_message_lane::MessageNonce> {
+			match instance {
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => Some(BridgeRialtoMessageLane::inbound_latest_received_nonce(lane)),
+				_ => None,
+			}
 		}
 
-		fn latest_confirmed_nonce(lane: bp_message_lane::LaneId) -> bp_message_lane::MessageNonce {
-			BridgeRialtoMessageLane::inbound_latest_confirmed_nonce(lane)
+		fn latest_confirmed_nonce(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+		) -> Option<bp_message_lane::MessageNonce> {
+			match instance {
+				bp_runtime::RIALTO_BRIDGE_INSTANCE => Some(BridgeRialtoMessageLane::inbound_latest_confirmed_nonce(lane)),
+				_ => None,
+			}
 		}
 	}
 }
@@ -0,0 +1,154 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Module that periodically sends heartbeat messages from Millau to Rialto.
+//!
+//! Every `HeartbeatPeriod` blocks, the off-chain worker submits message with the number of the
+//! current Millau block to the Rialto bridge. The message is dispatched at Rialto as
+//! `frame_system::remark` call of the account that is derived from the heartbeat key.
+//!
+//! The heartbeat key (`brhb` key type) must be added to the node keystore (e.g. using the
+//! `author_insertKey` RPC) and the account must have enough funds to pay message fees.
+//! Otherwise, the off-chain worker does nothing.
+
+use crate::rialto_messages::{ToRialtoMessagePayload, WithRialtoMessageBridge};
+use crate::Balance;
+
+use bp_message_lane::LaneId;
+use bridge_runtime_common::messages::{self, MessageBridge};
+use codec::Encode;
+use frame_support::{decl_module, traits::Get};
+use frame_system::offchain::{CreateSignedTransaction, SigningTypes};
+use pallet_bridge_call_dispatch::{CallOrigin, MessageCall};
+use pallet_message_lane::offchain::{submit_message, SubmitMessageError};
+use sp_core::crypto::KeyTypeId;
+use sp_runtime::{traits::Zero, MultiSigner};
+
+/// Key type of the heartbeat keys.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"brhb");
+
+/// Off-chain storage deduplication key of heartbeat messages.
+const HEARTBEAT_DEDUP_KEY: &[u8] = b"rialto-heartbeat";
+
+/// Heartbeat keys crypto.
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_runtime::{
+		app_crypto::{app_crypto, sr25519},
+		MultiSignature, MultiSigner,
+	};
+
+	app_crypto!(sr25519, KEY_TYPE);
+
+	/// Identifier of the heartbeat key.
+	pub struct HeartbeatAuthorityId;
+
+	impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for HeartbeatAuthorityId {
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+}
+
+/// The module configuration trait.
+pub trait Trait:
+	CreateSignedTransaction<pallet_message_lane::Call<Self>>
+	+ SigningTypes<Public = MultiSigner>
+	+ pallet_message_lane::Trait<OutboundPayload = ToRialtoMessagePayload, OutboundMessageFee = Balance>
+{
+	/// Crypto of the heartbeat keys.
+	type AuthorityId: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>;
+	/// Number of blocks between two heartbeat messages.
+	type HeartbeatPeriod: Get<Self::BlockNumber>;
+	/// Lane that is used to send heartbeat messages.
+	type HeartbeatLane: Get<LaneId>;
+	/// Specification version of the Rialto runtime.
+	type RialtoSpecVersion: Get<u32>;
+}
+
+decl_module! {
+	/// Rialto heartbeat module.
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn offchain_worker(block: T::BlockNumber) {
+			let heartbeat_period = T::HeartbeatPeriod::get();
+			if heartbeat_period.is_zero() || !(block % heartbeat_period).is_zero() {
+				return;
+			}
+
+			let fee = match heartbeat_fee::<T>(block) {
+				Ok(fee) => fee,
+				Err(err) => {
+					frame_support::debug::warn!("Failed to estimate heartbeat message fee: {}", err);
+					return;
+				}
+			};
+
+			match send_heartbeat::<T>(block, fee) {
+				Ok(()) => frame_support::debug::trace!("Sent heartbeat message at block {:?}", block),
+				Err(SubmitMessageError::AlreadySubmitted) => (),
+				Err(err) => frame_support::debug::warn!(
+					"Failed to send heartbeat message at block {:?}: {:?}",
+					block,
+					err,
+				),
+			}
+		}
+	}
+}
+
+/// Return payload of the heartbeat message for given block.
+fn heartbeat_payload<T: Trait>(block: T::BlockNumber, source_account: MultiSigner) -> ToRialtoMessagePayload {
+	let remark = block.encode();
+	ToRialtoMessagePayload {
+		spec_version: T::RialtoSpecVersion::get(),
+		weight: bp_rialto::MAX_SYSTEM_REMARK_CALL_WEIGHT,
+		origin: CallOrigin::SourceAccount(source_account),
+		call: MessageCall::Single(
+			(
+				bp_rialto::SYSTEM_PALLET_INDEX,
+				bp_rialto::SYSTEM_REMARK_CALL_INDEX,
+				remark,
+			)
+				.encode(),
+		),
+	}
+}
+
+/// Estimate fee of the heartbeat message for given block.
+fn heartbeat_fee<T: Trait>(block: T::BlockNumber) -> Result<Balance, &'static str> {
+	// heartbeat keys are sr25519 keys, so the message size doesn't depend on the actual key
+	let payload = heartbeat_payload::<T>(block, sp_core::sr25519::Public::default().into());
+	messages::source::estimate_message_dispatch_and_delivery_fee_in_this_chain_tokens::<WithRialtoMessageBridge>(
+		&payload,
+		WithRialtoMessageBridge::RELAYER_FEE_PERCENT,
+	)
+}
+
+/// Submit heartbeat message for given block.
+fn send_heartbeat<T: Trait>(block: T::BlockNumber, fee: Balance) -> Result<(), SubmitMessageError> {
+	submit_message::<T, pallet_message_lane::DefaultInstance, T::AuthorityId, _>(
+		HEARTBEAT_DEDUP_KEY,
+		block,
+		T::HeartbeatPeriod::get(),
+		|account| {
+			(
+				T::HeartbeatLane::get(),
+				heartbeat_payload::<T>(block, account.public.clone()),
+				fee,
+			)
+		},
+	)
+}
@@ -53,6 +53,11 @@ pub fn inbound_lane_data_key(lane: &LaneId) -> StorageKey {
 	>(lane)
 }
 
+/// Storage key of the message lane pallet storage version in the runtime storage.
+pub fn storage_version_key() -> StorageKey {
+	pallet_message_lane::storage_keys::storage_version_key::<<Millau as ChainWithMessageLanes>::MessageLaneInstance>()
+}
+
 /// Message payload for Millau -> Rialto messages.
 pub type ToRialtoMessagePayload = messages::source::FromThisChainMessagePayload<WithRialtoMessageBridge>;
 
@@ -63,10 +68,10 @@ pub type ToRialtoMessageVerifier = messages::source::FromThisChainMessageVerifie
 pub type FromRialtoMessagePayload = messages::target::FromBridgedChainMessagePayload<WithRialtoMessageBridge>;
 
 /// Messages proof for Rialto -> Millau messages.
-type FromRialtoMessagesProof = messages::target::FromBridgedChainMessagesProof<WithRialtoMessageBridge>;
+pub type FromRialtoMessagesProof = messages::target::FromBridgedChainMessagesProof<bp_rialto::Hash>;
 
 /// Messages delivery proof for Millau -> Rialto messages.
-type ToRialtoMessagesDeliveryProof = messages::source::FromBridgedChainMessagesDeliveryProof<WithRialtoMessageBridge>;
+pub type ToRialtoMessagesDeliveryProof = messages::source::FromBridgedChainMessagesDeliveryProof<bp_rialto::Hash>;
 
 /// Call-dispatch based message dispatch for Rialto -> Millau messages.
 pub type FromRialtoMessageDispatch = messages::target::FromBridgedChainMessageDispatch<
@@ -117,6 +122,11 @@ impl MessageBridge for WithRialtoMessageBridge {
 		// 1:1 conversion that will probably change in the future
 		this_balance as _
 	}
+
+	fn bridged_balance_to_this_balance(bridged_balance: bp_rialto::Balance) -> bp_millau::Balance {
+		// 1:1 conversion that will probably change in the future
+		bridged_balance as _
+	}
 }
 
 /// Millau chain from message lane point of view.
@@ -170,7 +180,11 @@ impl TargetHeaderChain<ToRialtoMessagePayload, bp_rialto::AccountId> for Rialto
 	fn verify_messages_delivery_proof(
 		proof: Self::MessagesDeliveryProof,
 	) -> Result<(LaneId, InboundLaneData<bp_millau::AccountId>), Self::Error> {
-		messages::source::verify_messages_delivery_proof::<WithRialtoMessageBridge, Runtime>(proof)
+		messages::source::verify_messages_delivery_proof::<
+			WithRialtoMessageBridge,
+			Runtime,
+			pallet_substrate_bridge::DefaultInstance,
+		>(proof)
 	}
 }
 
@@ -186,6 +200,10 @@ impl SourceHeaderChain<bp_rialto::Balance> for Rialto {
 	fn verify_messages_proof(
 		proof: Self::MessagesProof,
 	) -> Result<ProvedMessages<Message<bp_rialto::Balance>>, Self::Error> {
-		messages::target::verify_messages_proof::<WithRialtoMessageBridge, Runtime>(proof)
+		messages::target::verify_messages_proof::<
+			WithRialtoMessageBridge,
+			Runtime,
+			pallet_substrate_bridge::DefaultInstance,
+		>(proof)
 	}
 }
@@ -14,18 +14,34 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
+use pallet_bridged_assets::AssetMetadata;
+use pallet_substrate_bridge::InitializationData;
 use rialto_runtime::{
-	AccountId, AuraConfig, BalancesConfig, BridgeKovanConfig, BridgeMillauConfig, BridgeRialtoPoAConfig, GenesisConfig,
-	GrandpaConfig, SessionConfig, SessionKeys, Signature, SudoConfig, SystemConfig, WASM_BINARY,
+	AccountId, AuraConfig, BalancesConfig, BridgeCouncilConfig, BridgeKovanConfig, BridgeMillauConfig,
+	BridgeRialtoPoAConfig, BridgeThirdChainConfig, BridgedAssetsConfig, BridgedMillauBalancesConfig, ContractsConfig,
+	ContractsSchedule, GenesisConfig, GrandpaConfig, MillauConversionRateConfig, SessionConfig, SessionKeys, Signature,
+	SudoConfig, SystemConfig, MILLAU_TOKEN_ASSET_ID, WASM_BINARY,
 };
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{sr25519, Pair, Public};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
-use sp_runtime::traits::{IdentifyAccount, Verify};
+use sp_runtime::{
+	traits::{IdentifyAccount, One, Verify},
+	FixedU128,
+};
 
 /// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
 pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig>;
 
+/// Telemetry endpoint of the staging network.
+const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
+/// Number of validators of the staging network.
+const STAGING_VALIDATORS: usize = 3;
+/// Bootnodes of the staging network.
+///
+/// Additional nodes may always be specified using the `--bootnodes` CLI option.
+const STAGING_BOOTNODES: &[&str] = &[];
+
 /// The chain specification option. This is expected to come in from the CLI and
 /// is little more than one of a number of alternatives which can easily be converted
 /// from a string (`--chain=...`) into a `ChainSpec`.
@@ -35,6 +51,14 @@ pub enum Alternative {
 	Development,
 	/// Whatever the current runtime is, with simple Alice/Bob/Charlie/Dave/Eve auths.
 	LocalTestnet,
+	/// Shared test network of the Rialto <> Millau bridge.
+	///
+	/// Session keys of validators are placeholders, derived from the well-known seeds (see
+	/// `staging_authority_keys`). They're expected to be rotated using `session.setKeys` once
+	/// the network is launched. Bridge pallets are initialized at genesis if initialization data
+	/// is provided (see `load_with_bridge_config`), or the bridge state may be imported into the
+	/// chain spec using the `import-bridge-state` subcommand.
+	Staging,
 }
 
 /// Helper function to generate a crypto pair from seed
@@ -54,6 +78,16 @@ where
 	AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
 }
 
+/// Return placeholder authority keys of the `index`-th validator of the staging network.
+pub fn staging_authority_keys(index: usize) -> (AccountId, AuraId, GrandpaId) {
+	get_authority_keys_from_seed(&format!("RialtoStaging//{}", index))
+}
+
+/// Return placeholder account of the staging network sudo (and bridge pallets owner).
+pub fn staging_root_key() -> AccountId {
+	get_account_id_from_seed::<sr25519::Public>("RialtoStaging")
+}
+
 /// Helper function to generate an authority key for Aura
 pub fn get_authority_keys_from_seed(s: &str) -> (AccountId, AuraId, GrandpaId) {
 	(
@@ -63,15 +97,33 @@ pub fn get_authority_keys_from_seed(s: &str) -> (AccountId, AuraId, GrandpaId) {
 	)
 }
 
+/// Genesis configuration of the bridge pallets.
+///
+/// By default, Millau and third chain headers pallets are not initialized at genesis and are waiting for the `initialize`
+/// call. If initialization data is provided, the pallet is initialized at genesis instead.
+#[derive(Clone, Debug, Default)]
+pub struct BridgeGenesisConfig {
+	/// Initialization data of the Millau headers pallet.
+	pub millau: Option<InitializationData<bp_millau::Header>>,
+	/// Initialization data of the third chain headers pallet.
+	pub third_chain: Option<InitializationData<bp_millau::Header>>,
+}
+
 impl Alternative {
 	/// Get an actual chain config from one of the alternatives.
 	pub(crate) fn load(self) -> Result<ChainSpec, String> {
+		self.load_with_bridge_config(Default::default())
+	}
+
+	/// Get an actual chain config from one of the alternatives, with given genesis configuration
+	/// of the bridge pallets.
+	pub fn load_with_bridge_config(self, bridge_config: BridgeGenesisConfig) -> Result<ChainSpec, String> {
 		Ok(match self {
 			Alternative::Development => ChainSpec::from_genesis(
 				"Development",
 				"dev",
 				sc_service::ChainType::Development,
-				|| {
+				move || {
 					testnet_genesis(
 						vec![get_authority_keys_from_seed("Alice")],
 						get_account_id_from_seed::<sr25519::Public>("Alice"),
@@ -82,6 +134,7 @@ impl Alternative {
 							get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 						],
 						true,
+						bridge_config.clone(),
 					)
 				},
 				vec![],
@@ -94,7 +147,7 @@ impl Alternative {
 				"Local Testnet",
 				"local_testnet",
 				sc_service::ChainType::Local,
-				|| {
+				move || {
 					testnet_genesis(
 						vec![
 							get_authority_keys_from_seed("Alice"),
@@ -119,6 +172,7 @@ impl Alternative {
 							get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 						],
 						true,
+						bridge_config.clone(),
 					)
 				},
 				vec![],
@@ -127,6 +181,36 @@ impl Alternative {
 				None,
 				None,
 			),
+			Alternative::Staging => ChainSpec::from_genesis(
+				"Rialto Staging",
+				"rialto_staging",
+				sc_service::ChainType::Live,
+				move || {
+					let authorities = (0..STAGING_VALIDATORS).map(staging_authority_keys).collect::<Vec<_>>();
+					let endowed_accounts = std::iter::once(staging_root_key())
+						.chain(authorities.iter().map(|authority| authority.0.clone()))
+						.collect();
+					testnet_genesis(
+						authorities,
+						staging_root_key(),
+						endowed_accounts,
+						false,
+						bridge_config.clone(),
+					)
+				},
+				STAGING_BOOTNODES
+					.iter()
+					.map(|bootnode| bootnode.parse())
+					.collect::<Result<Vec<_>, _>>()
+					.map_err(|e| format!("Invalid staging bootnode address: {:?}", e))?,
+				Some(
+					sc_telemetry::TelemetryEndpoints::new(vec![(STAGING_TELEMETRY_URL.into(), 0)])
+						.map_err(|e| format!("Invalid staging telemetry URL: {:?}", e))?,
+				),
+				Some("rialto-staging"),
+				None,
+				None,
+			),
 		})
 	}
 }
@@ -139,7 +223,8 @@ fn testnet_genesis(
 	initial_authorities: Vec<(AccountId, AuraId, GrandpaId)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
-	_enable_println: bool,
+	enable_println: bool,
+	bridge_config: BridgeGenesisConfig,
 ) -> GenesisConfig {
 	GenesisConfig {
 		frame_system: Some(SystemConfig {
@@ -149,6 +234,25 @@ fn testnet_genesis(
 		pallet_balances: Some(BalancesConfig {
 			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
 		}),
+		pallet_balances_Instance1: Some(BridgedMillauBalancesConfig {
+			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
+		}),
+		pallet_bridged_assets: Some(BridgedAssetsConfig {
+			assets: vec![(
+				MILLAU_TOKEN_ASSET_ID,
+				AssetMetadata {
+					symbol: b"MLAU".to_vec(),
+					decimals: 0,
+					origin_chain: bp_runtime::MILLAU_BRIDGE_INSTANCE,
+					origin_lane: bp_message_lane::LaneId::from_bytes([0, 0, 0, 0]),
+				},
+			)],
+		}),
+		pallet_bridge_conversion_rate: Some(MillauConversionRateConfig {
+			feeders: vec![root_key.clone()],
+			phantom: Default::default(),
+			initial_rate: Some(FixedU128::one()),
+		}),
 		pallet_aura: Some(AuraConfig {
 			authorities: Vec::new(),
 		}),
@@ -158,11 +262,26 @@ fn testnet_genesis(
 			authorities: Vec::new(),
 		}),
 		pallet_substrate_bridge: Some(BridgeMillauConfig {
-			// We'll initialize the pallet with a dispatchable instead.
-			init_data: None,
+			// If initialization data is not provided, we'll initialize the pallet with a dispatchable instead.
+			init_data: bridge_config.millau,
+			owner: Some(root_key.clone()),
+		}),
+		pallet_substrate_bridge_Instance1: Some(BridgeThirdChainConfig {
+			// If initialization data is not provided, we'll initialize the pallet with a dispatchable instead.
+			init_data: bridge_config.third_chain,
 			owner: Some(root_key.clone()),
 		}),
 		pallet_sudo: Some(SudoConfig { key: root_key }),
+		pallet_collective_Instance1: Some(BridgeCouncilConfig {
+			members: initial_authorities.iter().map(|x| x.0.clone()).collect(),
+			phantom: Default::default(),
+		}),
+		pallet_contracts: Some(ContractsConfig {
+			current_schedule: ContractsSchedule {
+				enable_println,
+				..Default::default()
+			},
+		}),
 		pallet_session: Some(SessionConfig {
 			keys: initial_authorities
 				.iter()
@@ -15,6 +15,7 @@
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
 use sc_cli::RunCmd;
+use std::path::PathBuf;
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -24,6 +25,17 @@ pub struct Cli {
 
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// HTTP RPC address of the bridged chain node. If specified, it is used to compute
+	/// headers lag in the `bridge_status` RPC.
+	#[structopt(long)]
+	pub bridge_peer_rpc: Option<String>,
+
+	/// Keep state of blocks that may be used to anchor bridge proofs, even if state pruning is
+	/// enabled. Without this flag, the node may be unable to serve proofs of messages that are
+	/// still deliverable to the Millau chain.
+	#[structopt(long)]
+	pub retain_bridge_proof_state: bool,
 }
 
 /// Possible subcommands of the main binary.
@@ -64,4 +76,33 @@ pub enum Subcommand {
 	/// The custom benchmark subcommmand benchmarking runtime pallets.
 	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
+
+	/// Benchmark bridge pallets and generate (or check) weights file of the runtime.
+	#[structopt(name = "benchmark-weights")]
+	BenchmarkWeights(bridge_weights::WeightsCmd),
+
+	/// Rehearse runtime upgrade (including storage migrations of bridge pallets) against the state snapshot.
+	TryRuntime(TryRuntimeCmd),
+
+	/// Build raw chain spec, which genesis contains bridge pallets storage from the snapshot.
+	ImportBridgeState(ImportBridgeStateCmd),
+}
+
+/// The `try-runtime` subcommand.
+#[derive(Debug, StructOpt)]
+pub struct TryRuntimeCmd {
+	/// Path to the chain spec with the state snapshot, exported by the `export-state` subcommand.
+	#[structopt(long)]
+	pub snapshot: PathBuf,
+}
+
+/// The `import-bridge-state` subcommand.
+#[derive(Debug, StructOpt)]
+pub struct ImportBridgeStateCmd {
+	/// Id or path of the chain spec, which genesis storage is extended with the snapshot.
+	#[structopt(long, default_value = "dev")]
+	pub chain: String,
+	/// Path to the bridge state snapshot, exported by the substrate relay.
+	#[structopt(long)]
+	pub snapshot: PathBuf,
 }
@@ -0,0 +1,47 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Rialto bridge node library.
+#![warn(missing_docs)]
+
+mod chain_spec;
+#[macro_use]
+mod service;
+mod cli;
+mod command;
+mod metrics;
+
+pub use sc_service::{TaskExecutor, TaskManager};
+
+/// Node run result.
+pub type Result = sc_cli::Result<()>;
+
+/// Run node.
+pub fn run() -> Result {
+	command::run()
+}
+
+/// Start full node within the current process.
+///
+/// Arguments are the same as the node binary arguments (the first argument is the binary name).
+/// Subcommands are not supported. The node is running until returned `TaskManager` is dropped.
+pub fn start_full_node<I>(args: I, task_executor: TaskExecutor) -> sc_cli::Result<TaskManager>
+where
+	I: IntoIterator,
+	I::Item: Into<std::ffi::OsString> + Clone,
+{
+	command::start_full_node(args, task_executor)
+}
@@ -16,28 +16,21 @@
 
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
-// =====================================================================================
-// =====================================================================================
-// =====================================================================================
-// UPDATE GUIDE:
-// 1) replace everything with node-template/src/service.rs contents (found in main Substrate repo);
-// 2) the only thing to keep from old code, is `rpc_extensions_builder` - we use our own custom RPCs;
-// 3) fix compilation errors;
-// 4) test :)
-// =====================================================================================
-// =====================================================================================
-// =====================================================================================
-
-use bp_message_lane::{LaneId, MessageNonce};
-use bp_runtime::{InstanceId, MILLAU_BRIDGE_INSTANCE};
-use rialto_runtime::{self, opaque::Block, RuntimeApi};
+// Everything that is specific to the bridged chain comes from the `rialto_runtime::bridge_rpc`
+// module, so this file doesn't need any changes when bridge instance is added to the runtime.
+
+use rialto_runtime::{
+	self,
+	bridge_rpc::{BridgedBlockHash, BridgedBlockNumber, InboundRelayer, MessageLaneRpc, OutboundMessageFee},
+	opaque::Block,
+	RuntimeApi,
+};
 use sc_client_api::{ExecutorProvider, RemoteBackend};
 use sc_executor::native_executor_instance;
 pub use sc_executor::NativeExecutor;
 use sc_finality_grandpa::{FinalityProofProvider as GrandpaFinalityProofProvider, SharedVoterState};
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
-use sp_core::storage::StorageKey;
 use sp_inherents::InherentDataProviders;
 use std::sync::Arc;
 use std::time::Duration;
@@ -117,7 +110,7 @@ pub fn new_partial(
 }
 
 /// Builds a new service for a full client.
-pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
+pub fn new_full(config: Configuration, bridge_peer_rpc: Option<String>) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -163,47 +156,24 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 	let telemetry_connection_sinks = sc_service::TelemetryConnectionSinks::default();
 
 	let rpc_extensions_builder = {
-		// This struct is here to ease update process.
-
-		/// Rialto runtime from message-lane RPC point of view.
-		struct RialtoMessageLaneKeys;
-
-		impl pallet_message_lane_rpc::Runtime for RialtoMessageLaneKeys {
-			fn message_key(&self, instance: &InstanceId, lane: &LaneId, nonce: MessageNonce) -> Option<StorageKey> {
-				match *instance {
-					MILLAU_BRIDGE_INSTANCE => Some(rialto_runtime::millau_messages::message_key(lane, nonce)),
-					_ => None,
-				}
-			}
-
-			fn outbound_lane_data_key(&self, instance: &InstanceId, lane: &LaneId) -> Option<StorageKey> {
-				match *instance {
-					MILLAU_BRIDGE_INSTANCE => Some(rialto_runtime::millau_messages::outbound_lane_data_key(lane)),
-					_ => None,
-				}
-			}
-
-			fn inbound_lane_data_key(&self, instance: &InstanceId, lane: &LaneId) -> Option<StorageKey> {
-				match *instance {
-					MILLAU_BRIDGE_INSTANCE => Some(rialto_runtime::millau_messages::inbound_lane_data_key(lane)),
-					_ => None,
-				}
-			}
-		}
-
-		use pallet_message_lane_rpc::{MessageLaneApi, MessageLaneRpcHandler};
+		use pallet_message_lane_rpc::{
+			MessageLaneApi, MessageLaneAuthorApi, MessageLaneAuthorRpcHandler, MessageLaneDecoderApi,
+			MessageLaneDecoderRpcHandler, MessageLaneRpcHandler,
+		};
+		use pallet_substrate_bridge_rpc::{BridgeApi, BridgeRpcHandler};
 		use sc_finality_grandpa_rpc::{GrandpaApi, GrandpaRpcHandler};
 		use sc_rpc::DenyUnsafe;
 		use substrate_frame_rpc_system::{FullSystem, SystemApi};
 		let backend = backend.clone();
 		let client = client.clone();
 		let pool = transaction_pool.clone();
+		let keystore = keystore.clone() as sp_core::traits::BareCryptoStorePtr;
 
 		let justification_stream = grandpa_link.justification_stream();
 		let shared_authority_set = grandpa_link.shared_authority_set().clone();
 		let finality_proof_provider = GrandpaFinalityProofProvider::new_for_service(backend.clone(), client.clone());
 
-		Box::new(move |_, subscription_executor| {
+		Box::new(move |deny_unsafe, subscription_executor| {
 			let shared_voter_state = SharedVoterState::empty();
 
 			let mut io = jsonrpc_core::IoHandler::default();
@@ -216,18 +186,41 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 				shared_authority_set.clone(),
 				shared_voter_state,
 				justification_stream.clone(),
-				subscription_executor,
+				subscription_executor.clone(),
 				finality_proof_provider.clone(),
 			)));
-			io.extend_with(MessageLaneApi::to_delegate(MessageLaneRpcHandler::new(
+			io.extend_with(MessageLaneApi::<_, OutboundMessageFee, InboundRelayer>::to_delegate(
+				MessageLaneRpcHandler::new(backend.clone(), client.clone(), subscription_executor),
+			));
+			io.extend_with(BridgeApi::<_, BridgedBlockNumber, BridgedBlockHash>::to_delegate(
+				BridgeRpcHandler::new(client.clone(), bridge_peer_rpc.clone()),
+			));
+			io.extend_with(MessageLaneAuthorApi::<OutboundMessageFee>::to_delegate(
+				MessageLaneAuthorRpcHandler::new(
+					client.clone(),
+					pool.clone(),
+					keystore.clone(),
+					MessageLaneRpc,
+					deny_unsafe,
+				),
+			));
+			io.extend_with(MessageLaneDecoderApi::to_delegate(MessageLaneDecoderRpcHandler::new(
 				backend.clone(),
-				Arc::new(RialtoMessageLaneKeys),
+				client.clone(),
+				MessageLaneRpc,
 			)));
 
 			io
 		})
 	};
 
+	if let Some(ref prometheus_registry) = prometheus_registry {
+		let bridge_metrics = crate::metrics::BridgeMetrics::register(prometheus_registry)?;
+		task_manager
+			.spawn_handle()
+			.spawn("bridge-metrics", crate::metrics::run(client.clone(), bridge_metrics));
+	}
+
 	sc_service::spawn_tasks(sc_service::SpawnTasksParams {
 		network: network.clone(),
 		client: client.clone(),
@@ -376,12 +369,29 @@ pub fn new_light(config: Configuration) -> Result<TaskManager, ServiceError> {
 		);
 	}
 
+	let rpc_extensions_builder = {
+		use pallet_message_lane_rpc::{LightMessageLaneRpcHandler, MessageLaneApi};
+
+		let client = client.clone();
+		let on_demand = on_demand.clone();
+
+		// GRANDPA RPC isn't available here, because light client doesn't run GRANDPA voter
+		Box::new(move |_, _| {
+			let mut io = jsonrpc_core::IoHandler::default();
+			io.extend_with(MessageLaneApi::<_, OutboundMessageFee, InboundRelayer>::to_delegate(
+				LightMessageLaneRpcHandler::new(client.clone(), on_demand.clone()),
+			));
+
+			io
+		})
+	};
+
 	sc_service::spawn_tasks(sc_service::SpawnTasksParams {
 		remote_blockchain: Some(backend.remote_blockchain()),
 		transaction_pool,
 		task_manager: &mut task_manager,
 		on_demand: Some(on_demand),
-		rpc_extensions_builder: Box::new(|_, _| ()),
+		rpc_extensions_builder,
 		telemetry_connection_sinks: sc_service::TelemetryConnectionSinks::default(),
 		config,
 		client,
@@ -0,0 +1,111 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Registry of the bridge instances, served by the Rialto node RPC.
+//!
+//! Everything that is specific to the bridged chain is declared here, so the node is able to serve
+//! RPC of the bridge instance without knowing runtime types. Adding new bridge instance only
+//! requires changes in this module.
+
+use crate::{
+	millau_messages, opaque::Block, Balance, Call, ChargeTransactionPayment, MessageLaneCall, Runtime, SignedPayload,
+	UncheckedExtrinsic, VERSION,
+};
+
+use codec::{Decode, Encode};
+use pallet_message_lane_rpc::{
+	DecodedMessagePayload, MessagePayloadDecoder, SendMessageTransactionBuilder, TransactionSigner,
+};
+use sp_core::crypto::{key_types, KeyTypeId};
+use sp_runtime::{generic::Era, traits::IdentifyAccount, MultiSigner, OpaqueExtrinsic};
+
+/// Bridge instance, served by the node RPC.
+pub const BRIDGE_INSTANCE: bp_runtime::InstanceId = bp_runtime::MILLAU_BRIDGE_INSTANCE;
+
+/// Type of fee that is paid for sending messages to the bridged chain.
+pub type OutboundMessageFee = Balance;
+/// Identifier of relayers that are delivering messages from the bridged chain.
+pub type InboundRelayer = bp_millau::AccountId;
+/// Number of the bridged chain block.
+pub type BridgedBlockNumber = bp_millau::BlockNumber;
+/// Hash of the bridged chain block.
+pub type BridgedBlockHash = bp_millau::Hash;
+
+/// Message lane RPC helpers of the Rialto <-> Millau bridge.
+pub struct MessageLaneRpc;
+
+impl SendMessageTransactionBuilder<Block, OutboundMessageFee> for MessageLaneRpc {
+	type Index = crate::Index;
+
+	const SIGNER_KEY_TYPE: KeyTypeId = key_types::AURA;
+
+	const INSTANCE: bp_runtime::InstanceId = BRIDGE_INSTANCE;
+
+	fn build_send_message_transaction(
+		&self,
+		signer: TransactionSigner<crate::Hash, crate::Index>,
+		lane: bp_message_lane::LaneId,
+		payload: Vec<u8>,
+		fee: OutboundMessageFee,
+	) -> Result<OpaqueExtrinsic, String> {
+		let payload = millau_messages::ToMillauMessagePayload::decode(&mut &payload[..])
+			.map_err(|e| format!("Failed to decode message payload: {}", e))?;
+		let call = Call::BridgeMillauMessageLane(MessageLaneCall::send_message(lane, payload, fee));
+
+		let raw_payload = SignedPayload::from_raw(
+			call,
+			bp_rialto::signed_extensions::<Runtime, ChargeTransactionPayment>(Era::Immortal, signer.nonce, 0),
+			bp_rialto::additional_signed(
+				VERSION.spec_version,
+				VERSION.transaction_version,
+				signer.genesis_hash,
+				signer.genesis_hash,
+			),
+		);
+		let signature = raw_payload.using_encoded(signer.sign)?;
+		let (call, extra, _) = raw_payload.deconstruct();
+
+		let signer_account = MultiSigner::from(signer.public).into_account();
+		let transaction = UncheckedExtrinsic::new_signed(call, signer_account, signature.into(), extra);
+		OpaqueExtrinsic::from_bytes(&transaction.encode()).map_err(|e| format!("Failed to encode transaction: {}", e))
+	}
+}
+
+impl MessagePayloadDecoder for MessageLaneRpc {
+	fn decode_outbound_payload(&self, payload: &[u8]) -> Option<DecodedMessagePayload> {
+		let payload = millau_messages::ToMillauMessagePayload::decode(&mut &payload[..]).ok()?;
+		Some(DecodedMessagePayload {
+			spec_version: payload.spec_version,
+			weight: payload.weight,
+			origin: format!("{:?}", payload.origin),
+			call: None,
+			encoded_call: payload.call.encode().into(),
+		})
+	}
+
+	fn decode_inbound_payload(&self, payload: &[u8]) -> Option<DecodedMessagePayload> {
+		let payload = millau_messages::FromMillauMessagePayload::decode(&mut &payload[..])
+			.ok()?
+			.into_inner();
+		Some(DecodedMessagePayload {
+			spec_version: payload.spec_version,
+			weight: payload.weight,
+			origin: format!("{:?}", payload.origin),
+			call: Some(format!("{:?}", payload.call)),
+			encoded_call: payload.call.encode().into(),
+		})
+	}
+}
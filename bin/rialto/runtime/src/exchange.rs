@@ -26,11 +26,16 @@
 //! 4) wait until the required PoA header and its finality are provided
 //!    to the PoA -> Substrate bridge module (it can be provided by you);
 //! 5) receive tokens by providing proof-of-inclusion of PoA transaction.
+//!
+//! ERC-20 tokens may be exchanged the same way. The only difference is that at step (1) you need
+//! to call `transfer(LOCK_FUNDS_ADDRESS, amount)` method of the token contract, appending
+//! SCALE-encoded id of Substrate account that will receive funds to the call data. The token must
+//! be mapped to some asset of the Substrate chain (see `set_token_asset` call of the exchange module).
 
 use bp_currency_exchange::{
 	Error as ExchangeError, LockFundsTransaction, MaybeLockFundsTransaction, Result as ExchangeResult,
 };
-use bp_eth_poa::{transaction_decode_rlp, RawTransaction, RawTransactionReceipt};
+use bp_eth_poa::{transaction_decode_rlp, Address, RawTransaction, RawTransactionReceipt, Receipt, H256, U256};
 use codec::{Decode, Encode};
 use frame_support::RuntimeDebug;
 use hex_literal::hex;
@@ -39,6 +44,17 @@ use sp_std::vec::Vec;
 /// Ethereum address where locked PoA funds must be sent to.
 pub const LOCK_FUNDS_ADDRESS: [u8; 20] = hex!("DEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEF");
 
+/// Signature (first topic) of the ERC-20 `Transfer(address,address,uint256)` event.
+pub const ERC20_TRANSFER_EVENT_SIGNATURE: [u8; 32] =
+	hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+/// Selector of the ERC-20 `transfer(address,uint256)` method.
+pub const ERC20_TRANSFER_SELECTOR: [u8; 4] = hex!("a9059cbb");
+
+/// Size of the ERC-20 `transfer(address,uint256)` call data (selector + two arguments). Funds
+/// recipient is appended to the call data.
+const ERC20_TRANSFER_CALL_DATA_SIZE: usize = 4 + 32 + 32;
+
 /// Ethereum transaction inclusion proof.
 #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
 pub struct EthereumTransactionInclusionProof {
@@ -51,6 +67,15 @@ pub struct EthereumTransactionInclusionProof {
 	pub proof: Vec<(RawTransaction, RawTransactionReceipt)>,
 }
 
+impl bp_runtime::Size for EthereumTransactionInclusionProof {
+	fn size_hint(&self) -> u32 {
+		self.proof.iter().fold(0u32, |size, (transaction, receipt)| {
+			size.saturating_add(transaction.len() as u32)
+				.saturating_add(receipt.len() as u32)
+		})
+	}
+}
+
 /// We uniquely identify transfer by the pair (sender, nonce).
 ///
 /// The assumption is that this pair will never appear more than once in
@@ -69,30 +94,45 @@ pub struct EthereumTransactionTag {
 pub struct EthTransaction;
 
 impl MaybeLockFundsTransaction for EthTransaction {
-	type Transaction = RawTransaction;
+	type Transaction = (RawTransaction, RawTransactionReceipt);
 	type Id = EthereumTransactionTag;
 	type Recipient = crate::AccountId;
 	type Amount = crate::Balance;
+	type Token = Address;
 
 	fn parse(
-		raw_tx: &Self::Transaction,
-	) -> ExchangeResult<LockFundsTransaction<Self::Id, Self::Recipient, Self::Amount>> {
+		(raw_tx, raw_tx_receipt): &Self::Transaction,
+	) -> ExchangeResult<LockFundsTransaction<Self::Id, Self::Recipient, Self::Amount, Self::Token>> {
 		let tx = transaction_decode_rlp(raw_tx).map_err(|_| ExchangeError::InvalidTransaction)?;
 
-		// we only accept transactions sending funds directly to the pre-configured address
-		if tx.unsigned.to != Some(LOCK_FUNDS_ADDRESS.into()) {
-			frame_support::debug::trace!(
-				target: "runtime",
-				"Failed to parse fund locks transaction. Invalid peer recipient: {:?}",
-				tx.unsigned.to,
-			);
+		// we only accept transactions sending funds directly to the pre-configured address and
+		// ERC-20 token transfers to this address
+		let erc20_transfer = tx
+			.unsigned
+			.to
+			.filter(|_| is_erc20_transfer_call(&tx.unsigned.payload))
+			.and_then(|token| erc20_locked_value(token, raw_tx_receipt).map(|value| (token, value)));
+		let (token, recipient_payload, value) = match (tx.unsigned.to, erc20_transfer) {
+			(Some(to), _) if to == LOCK_FUNDS_ADDRESS.into() => (None, &tx.unsigned.payload[..], tx.unsigned.value),
+			(_, Some((token, value))) => (
+				Some(token),
+				tx.unsigned.payload.get(ERC20_TRANSFER_CALL_DATA_SIZE..).unwrap_or(&[]),
+				value,
+			),
+			_ => {
+				frame_support::debug::trace!(
+					target: "runtime",
+					"Failed to parse fund locks transaction. Invalid peer recipient: {:?}",
+					tx.unsigned.to,
+				);
 
-			return Err(ExchangeError::InvalidTransaction);
-		}
+				return Err(ExchangeError::InvalidTransaction);
+			}
+		};
 
 		let mut recipient_raw = sp_core::H256::default();
-		match tx.unsigned.payload.len() {
-			32 => recipient_raw.as_fixed_bytes_mut().copy_from_slice(&tx.unsigned.payload),
+		match recipient_payload.len() {
+			32 => recipient_raw.as_fixed_bytes_mut().copy_from_slice(recipient_payload),
 			len => {
 				frame_support::debug::trace!(
 					target: "runtime",
@@ -103,13 +143,13 @@ impl MaybeLockFundsTransaction for EthTransaction {
 				return Err(ExchangeError::InvalidRecipient);
 			}
 		}
-		let amount = tx.unsigned.value.low_u128();
+		let amount = value.low_u128();
 
-		if tx.unsigned.value != amount.into() {
+		if value != amount.into() {
 			frame_support::debug::trace!(
 				target: "runtime",
 				"Failed to parse fund locks transaction. Invalid amount: {}",
-				tx.unsigned.value,
+				value,
 			);
 
 			return Err(ExchangeError::InvalidAmount);
@@ -122,10 +162,47 @@ impl MaybeLockFundsTransaction for EthTransaction {
 			},
 			recipient: crate::AccountId::from(*recipient_raw.as_fixed_bytes()),
 			amount,
+			token,
 		})
 	}
 }
 
+/// Returns true if the payload is the `transfer(address,uint256)` call data with appended funds
+/// recipient.
+///
+/// Other token methods (e.g. `transferFrom`) are emitting the same `Transfer` event, but the call
+/// data layout is different, so we can't read the recipient from it.
+fn is_erc20_transfer_call(payload: &[u8]) -> bool {
+	payload.len() == ERC20_TRANSFER_CALL_DATA_SIZE + 32 && payload[..4] == ERC20_TRANSFER_SELECTOR
+}
+
+/// Returns value of the ERC-20 `Transfer` event, emitted by the `token` contract, that transfers
+/// tokens to the `LOCK_FUNDS_ADDRESS`. Returns None if there's no such event in the receipt.
+///
+/// Reverted transactions are never emitting events, so we don't need to check receipt status here.
+fn erc20_locked_value(token: Address, raw_tx_receipt: &[u8]) -> Option<U256> {
+	let receipt = Receipt::decode_rlp(raw_tx_receipt).ok()?;
+	let lock_funds_topic = address_topic(LOCK_FUNDS_ADDRESS.into());
+	receipt
+		.logs
+		.iter()
+		.find(|log| {
+			log.address == token
+				&& log.topics.len() == 3
+				&& log.topics[0] == ERC20_TRANSFER_EVENT_SIGNATURE.into()
+				&& log.topics[2] == lock_funds_topic
+				&& log.data.len() == 32
+		})
+		.map(|log| U256::from_big_endian(&log.data))
+}
+
+/// Returns event topic that corresponds to indexed address argument.
+fn address_topic(address: Address) -> H256 {
+	let mut topic = H256::zero();
+	topic.as_bytes_mut()[12..].copy_from_slice(address.as_bytes());
+	topic
+}
+
 /// Prepares everything required to bench claim of funds locked by given transaction.
 #[cfg(feature = "runtime-benchmarks")]
 pub(crate) fn prepare_environment_for_claim<T: pallet_bridge_eth_poa::Trait<I>, I: frame_support::traits::Instance>(
@@ -182,6 +259,7 @@ pub(crate) fn prepare_ethereum_transaction(
 			gas_used: Default::default(),
 			log_bloom: Default::default(),
 			logs: Vec::new(),
+			transaction_type: None,
 		}
 		.rlp(),
 	)
@@ -196,10 +274,56 @@ mod tests {
 		hex!("1cbd2d43530a44705ad088af313e18f80b53ef16b36177cd4b77b846f2a5f07c").into()
 	}
 
+	const TOKEN_ADDRESS: [u8; 20] = hex!("84dd11eb2a29615303d18149c0dbfa24167f8966");
+
+	fn prepare_erc20_transaction(
+		payload_editor: impl Fn(&mut Vec<u8>),
+		editor: impl Fn(&mut bp_eth_poa::LogEntry),
+	) -> (RawTransaction, RawTransactionReceipt) {
+		use bp_eth_poa::{LogEntry, TransactionOutcome};
+
+		let mut amount = [0u8; 32];
+		U256::from(100u64).to_big_endian(&mut amount);
+
+		let (raw_tx, _) = prepare_ethereum_transaction(&ferdie(), |tx| {
+			let recipient = tx.payload.clone();
+			tx.to = Some(TOKEN_ADDRESS.into());
+			tx.value = 0.into();
+			tx.payload = ERC20_TRANSFER_SELECTOR.to_vec();
+			tx.payload
+				.extend_from_slice(address_topic(LOCK_FUNDS_ADDRESS.into()).as_bytes());
+			tx.payload.extend_from_slice(&amount);
+			tx.payload.extend_from_slice(&recipient);
+			payload_editor(&mut tx.payload);
+		});
+		let mut log = LogEntry {
+			address: TOKEN_ADDRESS.into(),
+			topics: vec![
+				ERC20_TRANSFER_EVENT_SIGNATURE.into(),
+				address_topic(hex!("00a329c0648769a73afac7f9381e08fb43dbea72").into()),
+				address_topic(LOCK_FUNDS_ADDRESS.into()),
+			],
+			data: amount.to_vec(),
+		};
+		editor(&mut log);
+
+		(
+			raw_tx,
+			Receipt {
+				outcome: TransactionOutcome::StatusCode(1),
+				gas_used: Default::default(),
+				log_bloom: log.bloom(),
+				logs: vec![log],
+				transaction_type: None,
+			}
+			.rlp(),
+		)
+	}
+
 	#[test]
 	fn valid_transaction_accepted() {
 		assert_eq!(
-			EthTransaction::parse(&prepare_ethereum_transaction(&ferdie(), |_| {}).0),
+			EthTransaction::parse(&prepare_ethereum_transaction(&ferdie(), |_| {})),
 			Ok(LockFundsTransaction {
 				id: EthereumTransactionTag {
 					account: hex!("00a329c0648769a73afac7f9381e08fb43dbea72"),
@@ -207,14 +331,76 @@ mod tests {
 				},
 				recipient: ferdie(),
 				amount: 100,
+				token: None,
 			}),
 		);
 	}
 
+	#[test]
+	fn valid_erc20_transaction_accepted() {
+		assert_eq!(
+			EthTransaction::parse(&prepare_erc20_transaction(|_| {}, |_| {})),
+			Ok(LockFundsTransaction {
+				id: EthereumTransactionTag {
+					account: hex!("00a329c0648769a73afac7f9381e08fb43dbea72"),
+					nonce: 0.into(),
+				},
+				recipient: ferdie(),
+				amount: 100,
+				token: Some(TOKEN_ADDRESS.into()),
+			}),
+		);
+	}
+
+	#[test]
+	fn erc20_transaction_to_other_address_rejected() {
+		assert_eq!(
+			EthTransaction::parse(&prepare_erc20_transaction(
+				|_| {},
+				|log| {
+					log.topics[2] = address_topic(TOKEN_ADDRESS.into());
+				}
+			)),
+			Err(ExchangeError::InvalidTransaction),
+		);
+	}
+
+	#[test]
+	fn erc20_transaction_with_event_from_other_contract_rejected() {
+		assert_eq!(
+			EthTransaction::parse(&prepare_erc20_transaction(
+				|_| {},
+				|log| {
+					log.address = LOCK_FUNDS_ADDRESS.into();
+				}
+			)),
+			Err(ExchangeError::InvalidTransaction),
+		);
+	}
+
+	#[test]
+	fn erc20_transfer_from_transaction_rejected() {
+		assert_eq!(
+			EthTransaction::parse(&prepare_erc20_transaction(
+				|payload| payload[..4].copy_from_slice(&hex!("23b872dd")),
+				|_| {},
+			)),
+			Err(ExchangeError::InvalidTransaction),
+		);
+	}
+
+	#[test]
+	fn erc20_transaction_with_invalid_call_data_size_rejected() {
+		assert_eq!(
+			EthTransaction::parse(&prepare_erc20_transaction(|payload| payload.push(0), |_| {})),
+			Err(ExchangeError::InvalidTransaction),
+		);
+	}
+
 	#[test]
 	fn invalid_transaction_rejected() {
 		assert_eq!(
-			EthTransaction::parse(&Vec::new()),
+			EthTransaction::parse(&(Vec::new(), Vec::new())),
 			Err(ExchangeError::InvalidTransaction),
 		);
 	}
@@ -222,12 +408,9 @@ mod tests {
 	#[test]
 	fn transaction_with_invalid_peer_recipient_rejected() {
 		assert_eq!(
-			EthTransaction::parse(
-				&prepare_ethereum_transaction(&ferdie(), |tx| {
-					tx.to = None;
-				})
-				.0
-			),
+			EthTransaction::parse(&prepare_ethereum_transaction(&ferdie(), |tx| {
+				tx.to = None;
+			})),
 			Err(ExchangeError::InvalidTransaction),
 		);
 	}
@@ -235,12 +418,9 @@ mod tests {
 	#[test]
 	fn transaction_with_invalid_recipient_rejected() {
 		assert_eq!(
-			EthTransaction::parse(
-				&prepare_ethereum_transaction(&ferdie(), |tx| {
-					tx.payload.clear();
-				})
-				.0
-			),
+			EthTransaction::parse(&prepare_ethereum_transaction(&ferdie(), |tx| {
+				tx.payload.clear();
+			})),
 			Err(ExchangeError::InvalidRecipient),
 		);
 	}
@@ -248,12 +428,9 @@ mod tests {
 	#[test]
 	fn transaction_with_invalid_amount_rejected() {
 		assert_eq!(
-			EthTransaction::parse(
-				&prepare_ethereum_transaction(&ferdie(), |tx| {
-					tx.value = sp_core::U256::from(u128::max_value()) + sp_core::U256::from(1);
-				})
-				.0
-			),
+			EthTransaction::parse(&prepare_ethereum_transaction(&ferdie(), |tx| {
+				tx.value = sp_core::U256::from(u128::max_value()) + sp_core::U256::from(1);
+			})),
 			Err(ExchangeError::InvalidAmount),
 		);
 	}
@@ -16,7 +16,7 @@
 
 use crate::exchange::EthereumTransactionInclusionProof;
 
-use bp_eth_poa::{Address, AuraHeader, RawTransaction, U256};
+use bp_eth_poa::{Address, AuraHeader, RawTransaction, RawTransactionReceipt, U256};
 use bp_header_chain::BaseHeaderChain;
 use frame_support::RuntimeDebug;
 use hex_literal::hex;
@@ -28,6 +28,7 @@ use sp_std::prelude::*;
 
 frame_support::parameter_types! {
 	pub const FinalityVotesCachingInterval: Option<u64> = Some(16);
+	pub const HeadersCompactionDepth: Option<u64> = Some(FINALIZED_HEADERS_TO_KEEP_IN_FULL);
 	pub BridgeAuraConfiguration: AuraConfiguration =
 		kovan_aura_configuration();
 	pub BridgeValidatorsConfiguration: ValidatorsConfiguration =
@@ -38,6 +39,10 @@ frame_support::parameter_types! {
 /// finalized blocks on current Kovan chain.
 const FINALIZED_HEADERS_TO_KEEP: u64 = 20_000;
 
+/// Max number of finalized headers to keep in full. Older finalized headers are compacted,
+/// so transactions from these headers can't be verified anymore.
+const FINALIZED_HEADERS_TO_KEEP_IN_FULL: u64 = 2_000;
+
 /// Aura engine configuration for Kovan chain.
 pub fn kovan_aura_configuration() -> AuraConfiguration {
 	AuraConfiguration {
@@ -150,7 +155,7 @@ impl TChainTime for ChainTime {
 pub struct KovanBlockchain;
 
 impl BaseHeaderChain for KovanBlockchain {
-	type Transaction = RawTransaction;
+	type Transaction = (RawTransaction, RawTransactionReceipt);
 	type TransactionInclusionProof = EthereumTransactionInclusionProof;
 
 	fn verify_transaction_inclusion_proof(proof: &Self::TransactionInclusionProof) -> Option<Self::Transaction> {
@@ -161,7 +166,7 @@ impl BaseHeaderChain for KovanBlockchain {
 			return None;
 		}
 
-		proof.proof.get(proof.index as usize).map(|(tx, _)| tx.clone())
+		proof.proof.get(proof.index as usize).cloned()
 	}
 }
 
@@ -32,11 +32,15 @@ pub mod exchange;
 
 #[cfg(feature = "runtime-benchmarks")]
 pub mod benches;
+#[cfg(feature = "std")]
+pub mod bridge_rpc;
 pub mod kovan;
 pub mod millau_messages;
 pub mod rialto_poa;
+pub mod third_chain_messages;
 
-use codec::Decode;
+use bridge_runtime_common::messages;
+use codec::{Decode, Encode};
 use pallet_grandpa::{fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList};
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -45,7 +49,7 @@ use sp_runtime::traits::{Block as BlockT, IdentityLookup, NumberFor, OpaqueKeys}
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, MultiSignature, MultiSigner,
+	ApplyExtrinsicResult, MultiSignature, MultiSigner, RuntimeDebug,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -55,7 +59,7 @@ use sp_version::RuntimeVersion;
 // A few exports that help ease life for downstream crates.
 pub use frame_support::{
 	construct_runtime, parameter_types,
-	traits::{Currency, ExistenceRequirement, Imbalance, KeyOwnerProofSystem, Randomness},
+	traits::{Currency, ExistenceRequirement, Imbalance, InstanceFilter, KeyOwnerProofSystem, Randomness},
 	weights::{IdentityFee, RuntimeDbWeight, Weight},
 	StorageValue,
 };
@@ -64,10 +68,13 @@ pub use frame_system::Call as SystemCall;
 pub use pallet_balances::Call as BalancesCall;
 pub use pallet_bridge_currency_exchange::Call as BridgeCurrencyExchangeCall;
 pub use pallet_bridge_eth_poa::Call as BridgeEthPoACall;
+pub use pallet_contracts::{Call as ContractsCall, Schedule as ContractsSchedule};
 pub use pallet_message_lane::Call as MessageLaneCall;
+pub use pallet_proxy::Call as ProxyCall;
 pub use pallet_substrate_bridge::Call as BridgeMillauCall;
 pub use pallet_sudo::Call as SudoCall;
 pub use pallet_timestamp::Call as TimestampCall;
+pub use pallet_utility::Call as UtilityCall;
 
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
@@ -90,8 +97,19 @@ pub type AccountIndex = u32;
 /// Balance of an account.
 pub type Balance = bp_rialto::Balance;
 
+/// Identifier of the asset that PoA tokens are exchanged for.
+pub type AssetId = u32;
+
+/// Identifier of the asset that ERC-20 tokens, locked at the PoA chains, are exchanged for. The
+/// token must be mapped to this asset using `set_token_asset` call of the currency exchange module.
+pub const POA_TOKEN_ASSET_ID: AssetId = 1;
+
+/// Identifier of the bridged Millau token in the bridged assets registry. Fees of Rialto
+/// transactions may be paid in this asset.
+pub const MILLAU_TOKEN_ASSET_ID: AssetId = 0;
+
 /// Index of a transaction in the chain.
-pub type Index = u32;
+pub type Index = bp_rialto::Index;
 
 /// A hash of some data used by the chain.
 pub type Hash = bp_rialto::Hash;
@@ -146,6 +164,18 @@ pub const MINUTES: BlockNumber = 60_000 / (MILLISECS_PER_BLOCK as BlockNumber);
 pub const HOURS: BlockNumber = MINUTES * 60;
 pub const DAYS: BlockNumber = HOURS * 24;
 
+/// Maximal depth of the Rialto block (counted back from the best finalized block), which state may
+/// be used to prove messages and lane states to the Millau chain.
+///
+/// Proofs are anchored at the Rialto header that is known to the Rialto headers pallet at Millau.
+/// This header lags behind the best finalized Rialto header while the headers relay is catching up,
+/// and messages stay deliverable until delivery is confirmed, so nodes that are serving proofs to
+/// relayers must keep state of at least this number of blocks.
+pub const BRIDGE_PROOF_ANCHOR_DEPTH: BlockNumber = DAYS;
+
+/// Bridge pallets, which benchmarks are used to generate weights of the runtime.
+pub const BENCHMARKED_BRIDGE_PALLETS: &[&str] = &["pallet_bridge_eth_poa", "pallet_bridge_currency_exchange"];
+
 /// The version information used to identify this runtime when compiled natively.
 #[cfg(feature = "std")]
 pub fn native_version() -> NativeVersion {
@@ -231,24 +261,35 @@ impl pallet_aura::Trait for Runtime {
 	type AuthorityId = AuraId;
 }
 
+parameter_types! {
+	/// Bond that is reserved for every PoA header, submitted in signed transaction.
+	pub const PoAHeaderBond: Balance = 100_000;
+}
+
 type RialtoPoA = pallet_bridge_eth_poa::Instance1;
 impl pallet_bridge_eth_poa::Trait<RialtoPoA> for Runtime {
-	type AuraConfiguration = rialto_poa::BridgeAuraConfiguration;
+	type Engine = pallet_bridge_eth_poa::AuraConfiguration;
+	type EngineConfiguration = rialto_poa::BridgeAuraConfiguration;
 	type FinalityVotesCachingInterval = rialto_poa::FinalityVotesCachingInterval;
+	type HeadersCompactionDepth = rialto_poa::HeadersCompactionDepth;
 	type ValidatorsConfiguration = rialto_poa::BridgeValidatorsConfiguration;
 	type PruningStrategy = rialto_poa::PruningStrategy;
 	type ChainTime = rialto_poa::ChainTime;
 	type OnHeadersSubmitted = ();
+	type HeadersBond = pallet_bridge_eth_poa::CurrencyHeadersBond<Balances, PoAHeaderBond>;
 }
 
 type Kovan = pallet_bridge_eth_poa::Instance2;
 impl pallet_bridge_eth_poa::Trait<Kovan> for Runtime {
-	type AuraConfiguration = kovan::BridgeAuraConfiguration;
+	type Engine = pallet_bridge_eth_poa::AuraConfiguration;
+	type EngineConfiguration = kovan::BridgeAuraConfiguration;
 	type FinalityVotesCachingInterval = kovan::FinalityVotesCachingInterval;
+	type HeadersCompactionDepth = kovan::HeadersCompactionDepth;
 	type ValidatorsConfiguration = kovan::BridgeValidatorsConfiguration;
 	type PruningStrategy = kovan::PruningStrategy;
 	type ChainTime = kovan::ChainTime;
 	type OnHeadersSubmitted = ();
+	type HeadersBond = pallet_bridge_eth_poa::CurrencyHeadersBond<Balances, PoAHeaderBond>;
 }
 
 type RialtoCurrencyExchange = pallet_bridge_currency_exchange::Instance1;
@@ -260,6 +301,8 @@ impl pallet_bridge_currency_exchange::Trait<RialtoCurrencyExchange> for Runtime
 	type Amount = Balance;
 	type CurrencyConverter = bp_currency_exchange::IdentityCurrencyConverter<Balance>;
 	type DepositInto = DepositInto;
+	type AssetId = AssetId;
+	type DepositAssetInto = DepositAssetInto;
 }
 
 type KovanCurrencyExchange = pallet_bridge_currency_exchange::Instance2;
@@ -271,6 +314,15 @@ impl pallet_bridge_currency_exchange::Trait<KovanCurrencyExchange> for Runtime {
 	type Amount = Balance;
 	type CurrencyConverter = bp_currency_exchange::IdentityCurrencyConverter<Balance>;
 	type DepositInto = DepositInto;
+	type AssetId = AssetId;
+	type DepositAssetInto = DepositAssetInto;
+}
+
+parameter_types! {
+	/// Bridge message calls that have failed to dispatch may be retried later.
+	pub const StoreFailedBridgeCalls: bool = true;
+	/// Failed dispatch of the sovereign account message is reported if the account has less funds.
+	pub const MinSovereignAccountBalance: bp_rialto::Balance = 1_000_000;
 }
 
 impl pallet_bridge_call_dispatch::Trait for Runtime {
@@ -280,6 +332,10 @@ impl pallet_bridge_call_dispatch::Trait for Runtime {
 	type SourceChainAccountPublic = MultiSigner;
 	type TargetChainAccountPublic = MultiSigner;
 	type TargetChainSignature = MultiSignature;
+	type CallWeightAdjuster = crate::millau_messages::FromMillauCallWeightAdjuster;
+	type StoreFailedCalls = StoreFailedBridgeCalls;
+	type Currency = pallet_balances::Module<Runtime>;
+	type MinSovereignAccountBalance = MinSovereignAccountBalance;
 }
 
 pub struct DepositInto;
@@ -289,56 +345,93 @@ impl bp_currency_exchange::DepositInto for DepositInto {
 	type Amount = Balance;
 
 	fn deposit_into(recipient: Self::Recipient, amount: Self::Amount) -> bp_currency_exchange::Result<()> {
-		// let balances module make all checks for us (it won't allow depositing lower than existential
-		// deposit, balance overflow, ...)
-		let deposited = <pallet_balances::Module<Runtime> as Currency<AccountId>>::deposit_creating(&recipient, amount);
-
-		// I'm dropping deposited here explicitly to illustrate the fact that it'll update `TotalIssuance`
-		// on drop
-		let deposited_amount = deposited.peek();
-		drop(deposited);
-
-		// we have 3 cases here:
-		// - deposited == amount: success
-		// - deposited == 0: deposit has failed and no changes to storage were made
-		// - deposited != 0: (should never happen in practice) deposit has been partially completed
-		match deposited_amount {
-			_ if deposited_amount == amount => {
-				frame_support::debug::trace!(
-					target: "runtime",
-					"Deposited {} to {:?}",
-					amount,
-					recipient,
-				);
+		deposit_into_currency::<Balances>(recipient, amount)
+	}
+}
 
-				Ok(())
-			}
-			_ if deposited_amount == 0 => {
-				frame_support::debug::error!(
-					target: "runtime",
-					"Deposit of {} to {:?} has failed",
-					amount,
-					recipient,
-				);
+pub struct DepositAssetInto;
 
-				Err(bp_currency_exchange::Error::DepositFailed)
-			}
+impl bp_currency_exchange::DepositAssetInto for DepositAssetInto {
+	type AssetId = AssetId;
+	type Recipient = AccountId;
+	type Amount = Balance;
+
+	fn deposit_asset_into(
+		asset_id: Self::AssetId,
+		recipient: Self::Recipient,
+		amount: Self::Amount,
+	) -> bp_currency_exchange::Result<()> {
+		match asset_id {
+			POA_TOKEN_ASSET_ID => deposit_into_currency::<BridgedPoATokenBalances>(recipient, amount),
 			_ => {
 				frame_support::debug::error!(
 					target: "runtime",
-					"Deposit of {} to {:?} has partially competed. {} has been deposited",
+					"Deposit of {} to {:?} has failed: unknown asset {}",
 					amount,
 					recipient,
-					deposited_amount,
+					asset_id,
 				);
 
-				// we can't return DepositFailed error here, because storage changes were made
-				Err(bp_currency_exchange::Error::DepositPartiallyFailed)
+				Err(bp_currency_exchange::Error::DepositFailed)
 			}
 		}
 	}
 }
 
+/// Deposit given amount of currency `C` to the recipient account.
+fn deposit_into_currency<C: Currency<AccountId, Balance = Balance>>(
+	recipient: AccountId,
+	amount: Balance,
+) -> bp_currency_exchange::Result<()> {
+	// let balances module make all checks for us (it won't allow depositing lower than existential
+	// deposit, balance overflow, ...)
+	let deposited = C::deposit_creating(&recipient, amount);
+
+	// I'm dropping deposited here explicitly to illustrate the fact that it'll update `TotalIssuance`
+	// on drop
+	let deposited_amount = deposited.peek();
+	drop(deposited);
+
+	// we have 3 cases here:
+	// - deposited == amount: success
+	// - deposited == 0: deposit has failed and no changes to storage were made
+	// - deposited != 0: (should never happen in practice) deposit has been partially completed
+	match deposited_amount {
+		_ if deposited_amount == amount => {
+			frame_support::debug::trace!(
+				target: "runtime",
+				"Deposited {} to {:?}",
+				amount,
+				recipient,
+			);
+
+			Ok(())
+		}
+		_ if deposited_amount == 0 => {
+			frame_support::debug::error!(
+				target: "runtime",
+				"Deposit of {} to {:?} has failed",
+				amount,
+				recipient,
+			);
+
+			Err(bp_currency_exchange::Error::DepositFailed)
+		}
+		_ => {
+			frame_support::debug::error!(
+				target: "runtime",
+				"Deposit of {} to {:?} has partially competed. {} has been deposited",
+				amount,
+				recipient,
+				deposited_amount,
+			);
+
+			// we can't return DepositFailed error here, because storage changes were made
+			Err(bp_currency_exchange::Error::DepositPartiallyFailed)
+		}
+	}
+}
+
 impl pallet_grandpa::Trait for Runtime {
 	type Event = Event;
 	type Call = Call;
@@ -397,16 +490,223 @@ impl pallet_transaction_payment::Trait for Runtime {
 	type FeeMultiplierUpdate = ();
 }
 
+// Balances of bridged Millau tokens. They're endowed at genesis and may be changed by root - there's
+// no token transfer bridge between Millau and Rialto yet.
+type BridgedMillauBalancesInstance = pallet_balances::Instance1;
+impl pallet_balances::Trait<BridgedMillauBalancesInstance> for Runtime {
+	type Balance = Balance;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = frame_support::traits::StorageMapShim<
+		pallet_balances::Account<Runtime, BridgedMillauBalancesInstance>,
+		frame_system::CallOnCreatedAccount<Runtime>,
+		frame_system::CallKillAccount<Runtime>,
+		AccountId,
+		pallet_balances::AccountData<Balance>,
+	>;
+	// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+	type WeightInfo = ();
+	type MaxLocks = MaxLocks;
+}
+
+// Balances of the `POA_TOKEN_ASSET_ID` asset, minted by the currency exchange modules.
+type BridgedPoATokenBalancesInstance = pallet_balances::Instance2;
+impl pallet_balances::Trait<BridgedPoATokenBalancesInstance> for Runtime {
+	type Balance = Balance;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = frame_support::traits::StorageMapShim<
+		pallet_balances::Account<Runtime, BridgedPoATokenBalancesInstance>,
+		frame_system::CallOnCreatedAccount<Runtime>,
+		frame_system::CallKillAccount<Runtime>,
+		AccountId,
+		pallet_balances::AccountData<Balance>,
+	>;
+	// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+	type WeightInfo = ();
+	type MaxLocks = MaxLocks;
+}
+
+parameter_types! {
+	pub const MillauBridgeInstance: bp_runtime::InstanceId = bp_runtime::MILLAU_BRIDGE_INSTANCE;
+	pub const MaxBridgedAssetSymbolLength: u32 = 16;
+	pub const MillauTokenAssetId: AssetId = MILLAU_TOKEN_ASSET_ID;
+	pub const MillauConversionRateMaxAge: BlockNumber = HOURS;
+	pub const MillauConversionRateMinFeeds: u32 = 1;
+}
+
+impl pallet_bridged_assets::Trait for Runtime {
+	type Event = Event;
+	type AssetId = AssetId;
+	type BridgeInstance = MillauBridgeInstance;
+	type MaxSymbolLength = MaxBridgedAssetSymbolLength;
+}
+
+impl pallet_bridge_conversion_rate::Trait for Runtime {
+	type Event = Event;
+	type MaxRateAge = MillauConversionRateMaxAge;
+	type MinFeeds = MillauConversionRateMinFeeds;
+}
+
+impl pallet_bridged_fee_payment::Trait for Runtime {
+	type BridgedCurrency = BridgedMillauBalances;
+	type FeeAssetId = MillauTokenAssetId;
+	type ConversionRate = MillauConversionRate;
+	// fees that are paid in bridged Millau tokens are burned, like fees that are paid in Rialto tokens
+	type OnBridgedTransactionPayment = ();
+}
+
+parameter_types! {
+	pub const TombstoneDeposit: Balance = 16_000_000;
+	pub const RentByteFee: Balance = 4_000_000;
+	pub const RentDepositOffset: Balance = 1_000_000_000;
+	pub const SurchargeReward: Balance = 150_000_000;
+}
+
+impl pallet_contracts::Trait for Runtime {
+	type Time = Timestamp;
+	type Randomness = RandomnessCollectiveFlip;
+	type Currency = Balances;
+	type Event = Event;
+	type DetermineContractAddress = pallet_contracts::SimpleAddressDeterminer<Runtime>;
+	type TrieIdGenerator = pallet_contracts::TrieIdFromParentCounter<Runtime>;
+	type RentPayment = ();
+	type SignedClaimHandicap = pallet_contracts::DefaultSignedClaimHandicap;
+	type TombstoneDeposit = TombstoneDeposit;
+	type StorageSizeOffset = pallet_contracts::DefaultStorageSizeOffset;
+	type RentByteFee = RentByteFee;
+	type RentDepositOffset = RentDepositOffset;
+	type SurchargeReward = SurchargeReward;
+	type MaxDepth = pallet_contracts::DefaultMaxDepth;
+	type MaxValueSize = pallet_contracts::DefaultMaxValueSize;
+	type WeightPrice = pallet_transaction_payment::Module<Self>;
+	// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+	type WeightInfo = ();
+}
+
 impl pallet_sudo::Trait for Runtime {
 	type Event = Event;
 	type Call = Call;
 }
 
+parameter_types! {
+	pub const BridgeCouncilMotionDuration: BlockNumber = 5 * MINUTES;
+	pub const BridgeCouncilMaxProposals: u32 = 16;
+	pub const BridgeCouncilMaxMembers: u32 = 16;
+}
+
+/// Instance of the collective pallet that administers bridge pallets.
+pub type BridgeCouncilInstance = pallet_collective::Instance1;
+
+impl pallet_collective::Trait<BridgeCouncilInstance> for Runtime {
+	type Origin = Origin;
+	type Proposal = Call;
+	type Event = Event;
+	type MotionDuration = BridgeCouncilMotionDuration;
+	type MaxProposals = BridgeCouncilMaxProposals;
+	type MaxMembers = BridgeCouncilMaxMembers;
+	type DefaultVote = pallet_collective::PrimeDefaultVote;
+	// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+	type WeightInfo = ();
+}
+
+/// Origin that administers bridge pallets (halts and resumes operations, changes owners): at
+/// least 2/3 of the bridge council members.
+pub type EnsureBridgeCouncil = pallet_collective::EnsureProportionAtLeast<
+	sp_core::u32_trait::_2,
+	sp_core::u32_trait::_3,
+	AccountId,
+	BridgeCouncilInstance,
+>;
+
+/// The type used to represent the kinds of proxying allowed.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, RuntimeDebug)]
+pub enum ProxyType {
+	/// Proxy may submit any call on behalf of the proxied account.
+	Any,
+	/// Proxy may only submit relay calls (headers, finality proofs, messages and delivery
+	/// confirmations) of both Millau and third chain bridges. Other calls of bridge pallets (e.g.
+	/// `send_message` or owner calls) are rejected. Relay calls may also be wrapped into `utility`
+	/// batches - every batched call is still checked by this filter.
+	///
+	/// This is the type that should be given to relayer accounts, so that the funded account may
+	/// be kept in cold storage.
+	BridgeRelay,
+}
+
+impl Default for ProxyType {
+	fn default() -> Self {
+		ProxyType::Any
+	}
+}
+
+impl InstanceFilter<Call> for ProxyType {
+	fn filter(&self, call: &Call) -> bool {
+		match self {
+			ProxyType::Any => true,
+			ProxyType::BridgeRelay => matches!(
+				call,
+				Call::BridgeMillau(BridgeMillauCall::import_signed_header(..))
+					| Call::BridgeMillau(BridgeMillauCall::finalize_header(..))
+					| Call::BridgeMillauMessageLane(MessageLaneCall::receive_messages_proof(..))
+					| Call::BridgeMillauMessageLane(MessageLaneCall::receive_messages_delivery_proof(..))
+					| Call::BridgeThirdChain(BridgeMillauCall::import_signed_header(..))
+					| Call::BridgeThirdChain(BridgeMillauCall::finalize_header(..))
+					| Call::BridgeThirdChainMessageLane(MessageLaneCall::receive_messages_proof(..))
+					| Call::BridgeThirdChainMessageLane(MessageLaneCall::receive_messages_delivery_proof(..))
+					| Call::Utility(..)
+			),
+		}
+	}
+
+	fn is_superset(&self, other: &Self) -> bool {
+		match (self, other) {
+			(ProxyType::Any, _) => true,
+			(_, ProxyType::Any) => false,
+			(ProxyType::BridgeRelay, ProxyType::BridgeRelay) => true,
+		}
+	}
+}
+
+parameter_types! {
+	pub const ProxyDepositBase: Balance = 1_000;
+	pub const ProxyDepositFactor: Balance = 100;
+	pub const MaxProxies: u16 = 32;
+	pub const MaxPending: u32 = 32;
+	pub const AnnouncementDepositBase: Balance = 1_000;
+	pub const AnnouncementDepositFactor: Balance = 100;
+}
+
+impl pallet_proxy::Trait for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type ProxyType = ProxyType;
+	type ProxyDepositBase = ProxyDepositBase;
+	type ProxyDepositFactor = ProxyDepositFactor;
+	type MaxProxies = MaxProxies;
+	// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+	type WeightInfo = ();
+	type MaxPending = MaxPending;
+	type CallHasher = Hashing;
+	type AnnouncementDepositBase = AnnouncementDepositBase;
+	type AnnouncementDepositFactor = AnnouncementDepositFactor;
+}
+
 parameter_types! {
 	pub const Period: BlockNumber = 4;
 	pub const Offset: BlockNumber = 0;
 }
 
+impl pallet_utility::Trait for Runtime {
+	type Event = Event;
+	type Call = Call;
+	// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+	type WeightInfo = ();
+}
+
 impl pallet_session::Trait for Runtime {
 	type Event = Event;
 	type ValidatorId = <Self as frame_system::Trait>::AccountId;
@@ -423,6 +723,15 @@ impl pallet_session::Trait for Runtime {
 
 impl pallet_substrate_bridge::Trait for Runtime {
 	type BridgedChain = bp_millau::Millau;
+	type AdminOrigin = EnsureBridgeCouncil;
+	type FinalityVerifier = pallet_substrate_bridge::GrandpaFinalityVerifier<bp_millau::Header>;
+}
+
+impl pallet_substrate_bridge::Trait<third_chain_messages::ThirdChainHeadersInstance> for Runtime {
+	// the third chain is using Millau primitives
+	type BridgedChain = bp_millau::Millau;
+	type AdminOrigin = EnsureBridgeCouncil;
+	type FinalityVerifier = pallet_substrate_bridge::GrandpaFinalityVerifier<bp_millau::Header>;
 }
 
 impl pallet_shift_session_manager::Trait for Runtime {}
@@ -430,12 +739,24 @@ impl pallet_shift_session_manager::Trait for Runtime {}
 parameter_types! {
 	pub const MaxMessagesToPruneAtOnce: bp_message_lane::MessageNonce = 8;
 	pub const MaxUnconfirmedMessagesAtInboundLane: bp_message_lane::MessageNonce = bp_rialto::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE;
+	pub const MaxMessagesProofSize: u32 = bp_rialto::MAX_MESSAGES_PROOF_SIZE;
+	pub const MaxMessagesDeliveryProofSize: u32 = bp_rialto::MAX_MESSAGES_DELIVERY_PROOF_SIZE;
+	pub MessageLaneWeights: bp_message_lane_weights::MessageLaneWeights = bp_rialto::message_lane_weights();
+}
+
+impl pallet_bridge_relayers::Trait for Runtime {
+	type Event = Event;
+	type Currency = pallet_balances::Module<Runtime>;
 }
 
 impl pallet_message_lane::Trait for Runtime {
 	type Event = Event;
+	type AdminOrigin = EnsureBridgeCouncil;
 	type MaxMessagesToPruneAtOnce = MaxMessagesToPruneAtOnce;
 	type MaxUnconfirmedMessagesAtInboundLane = MaxUnconfirmedMessagesAtInboundLane;
+	type MaxMessagesProofSize = MaxMessagesProofSize;
+	type MaxMessagesDeliveryProofSize = MaxMessagesDeliveryProofSize;
+	type WeightInfo = pallet_message_lane::WeightsFrom<MessageLaneWeights>;
 
 	type OutboundPayload = crate::millau_messages::ToMillauMessagePayload;
 	type OutboundMessageFee = Balance;
@@ -446,13 +767,36 @@ impl pallet_message_lane::Trait for Runtime {
 
 	type TargetHeaderChain = crate::millau_messages::Millau;
 	type LaneMessageVerifier = crate::millau_messages::ToMillauMessageVerifier;
-	type MessageDeliveryAndDispatchPayment =
-		pallet_message_lane::instant_payments::InstantCurrencyPayments<AccountId, pallet_balances::Module<Runtime>>;
+	type MessageDeliveryAndDispatchPayment = pallet_bridge_relayers::Module<Runtime>;
 
 	type SourceHeaderChain = crate::millau_messages::Millau;
 	type MessageDispatch = crate::millau_messages::FromMillauMessageDispatch;
 }
 
+impl pallet_message_lane::Trait<third_chain_messages::ThirdChainMessageLaneInstance> for Runtime {
+	type Event = Event;
+	type AdminOrigin = EnsureBridgeCouncil;
+	type MaxMessagesToPruneAtOnce = MaxMessagesToPruneAtOnce;
+	type MaxUnconfirmedMessagesAtInboundLane = MaxUnconfirmedMessagesAtInboundLane;
+	type MaxMessagesProofSize = MaxMessagesProofSize;
+	type MaxMessagesDeliveryProofSize = MaxMessagesDeliveryProofSize;
+	type WeightInfo = pallet_message_lane::WeightsFrom<MessageLaneWeights>;
+
+	type OutboundPayload = crate::third_chain_messages::ToThirdChainMessagePayload;
+	type OutboundMessageFee = Balance;
+
+	type InboundPayload = crate::third_chain_messages::FromThirdChainMessagePayload;
+	type InboundMessageFee = bp_millau::Balance;
+	type InboundRelayer = bp_millau::AccountId;
+
+	type TargetHeaderChain = crate::third_chain_messages::ThirdChain;
+	type LaneMessageVerifier = crate::third_chain_messages::ToThirdChainMessageVerifier;
+	type MessageDeliveryAndDispatchPayment = pallet_bridge_relayers::Module<Runtime>;
+
+	type SourceHeaderChain = crate::third_chain_messages::ThirdChain;
+	type MessageDispatch = crate::third_chain_messages::FromThirdChainMessageDispatch;
+}
+
 construct_runtime!(
 	pub enum Runtime where
 		Block = Block,
@@ -464,8 +808,11 @@ construct_runtime!(
 		BridgeRialtoCurrencyExchange: pallet_bridge_currency_exchange::<Instance1>::{Module, Call},
 		BridgeKovanCurrencyExchange: pallet_bridge_currency_exchange::<Instance2>::{Module, Call},
 		BridgeMillau: pallet_substrate_bridge::{Module, Call, Storage, Config<T>},
-		BridgeCallDispatch: pallet_bridge_call_dispatch::{Module, Event<T>},
+		BridgeCallDispatch: pallet_bridge_call_dispatch::{Module, Call, Storage, Event<T>},
 		BridgeMillauMessageLane: pallet_message_lane::{Module, Call, Event<T>},
+		BridgeRelayers: pallet_bridge_relayers::{Module, Call, Storage, Event<T>},
+		BridgeThirdChain: pallet_substrate_bridge::<Instance1>::{Module, Call, Storage, Config<T>},
+		BridgeThirdChainMessageLane: pallet_message_lane::<Instance1>::{Module, Call, Event<T>},
 		System: frame_system::{Module, Call, Config, Storage, Event<T>},
 		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Module, Call, Storage},
 		Timestamp: pallet_timestamp::{Module, Call, Storage, Inherent},
@@ -474,8 +821,16 @@ construct_runtime!(
 		Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
 		TransactionPayment: pallet_transaction_payment::{Module, Storage},
 		Sudo: pallet_sudo::{Module, Call, Config<T>, Storage, Event<T>},
+		BridgeCouncil: pallet_collective::<Instance1>::{Module, Call, Storage, Origin<T>, Event<T>, Config<T>},
 		Session: pallet_session::{Module, Call, Storage, Event, Config<T>},
 		ShiftSessionManager: pallet_shift_session_manager::{Module},
+		Proxy: pallet_proxy::{Module, Call, Storage, Event<T>},
+		Contracts: pallet_contracts::{Module, Call, Config, Storage, Event<T>},
+		Utility: pallet_utility::{Module, Call, Event},
+		BridgedMillauBalances: pallet_balances::<Instance1>::{Module, Call, Storage, Config<T>, Event<T>},
+		BridgedAssets: pallet_bridged_assets::{Module, Call, Storage, Config<T>, Event<T>},
+		MillauConversionRate: pallet_bridge_conversion_rate::{Module, Call, Storage, Config<T>, Event<T>},
+		BridgedPoATokenBalances: pallet_balances::<Instance2>::{Module, Call, Storage, Event<T>},
 	}
 );
 
@@ -489,16 +844,10 @@ pub type Block = generic::Block<Header, UncheckedExtrinsic>;
 pub type SignedBlock = generic::SignedBlock<Block>;
 /// BlockId type as expected by this runtime.
 pub type BlockId = generic::BlockId<Block>;
+/// The SignedExtension that charges transaction fees, either in Rialto or in bridged Millau tokens.
+pub type ChargeTransactionPayment = pallet_bridged_fee_payment::ChargeAssetTransactionPayment<Runtime>;
 /// The SignedExtension to the basic transaction logic.
-pub type SignedExtra = (
-	frame_system::CheckSpecVersion<Runtime>,
-	frame_system::CheckTxVersion<Runtime>,
-	frame_system::CheckGenesis<Runtime>,
-	frame_system::CheckEra<Runtime>,
-	frame_system::CheckNonce<Runtime>,
-	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
-);
+pub type SignedExtra = bp_rialto::SignedExtensions<Runtime, ChargeTransactionPayment>;
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
 /// Unchecked extrinsic type as expected by this runtime.
@@ -509,6 +858,30 @@ pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Call, SignedExt
 pub type Executive =
 	frame_executive::Executive<Runtime, Block, frame_system::ChainContext<Runtime>, Runtime, AllModules>;
 
+/// Rehearse runtime upgrade: check storage of bridge pallets, run `on_runtime_upgrade` of all
+/// pallets and check their storage again.
+///
+/// Must be called within externalities that are built from the state snapshot.
+#[cfg(feature = "try-runtime")]
+pub fn try_runtime_upgrade() -> Result<Weight, &'static str> {
+	use frame_support::traits::OnRuntimeUpgrade;
+
+	BridgeMillauMessageLane::pre_upgrade()?;
+	BridgeThirdChainMessageLane::pre_upgrade()?;
+	BridgeRialtoPoA::pre_upgrade()?;
+	BridgeKovan::pre_upgrade()?;
+
+	let weight = <frame_system::Module<Runtime> as OnRuntimeUpgrade>::on_runtime_upgrade()
+		.saturating_add(<AllModules as OnRuntimeUpgrade>::on_runtime_upgrade());
+
+	BridgeMillauMessageLane::post_upgrade()?;
+	BridgeThirdChainMessageLane::post_upgrade()?;
+	BridgeRialtoPoA::post_upgrade()?;
+	BridgeKovan::post_upgrade()?;
+
+	Ok(weight)
+}
+
 impl_runtime_apis! {
 	impl sp_api::Core<Block> for Runtime {
 		fn version() -> RuntimeVersion {
@@ -561,6 +934,19 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<
+		Block,
+		Balance,
+		UncheckedExtrinsic,
+	> for Runtime {
+		fn query_info(
+			uxt: UncheckedExtrinsic,
+			len: u32,
+		) -> pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo<Balance> {
+			TransactionPayment::query_info(uxt, len)
+		}
+	}
+
 	impl bp_eth_poa::RialtoPoAHeaderApi<Block> for Runtime {
 		fn best_block() -> (u64, bp_eth_poa::H256) {
 			let best_block = BridgeRialtoPoA::best_block();
@@ -601,6 +987,46 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl bp_runtime::BridgeStatusApi<Block, bp_millau::BlockNumber, bp_millau::Hash> for Runtime {
+		fn bridge_status(
+			instance: bp_runtime::InstanceId,
+		) -> Option<bp_runtime::BridgeStatus<bp_millau::BlockNumber, bp_millau::Hash>> {
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE => {
+					let best_finalized = BridgeMillau::best_finalized();
+					Some(bp_runtime::BridgeStatus {
+						best_headers: BridgeMillau::best_headers(),
+						best_finalized: (best_finalized.number, best_finalized.hash()),
+						is_headers_pallet_halted: BridgeMillau::is_halted(),
+						is_message_lane_pallet_halted: BridgeMillauMessageLane::is_halted(),
+					})
+				}
+				bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => {
+					let best_finalized = BridgeThirdChain::best_finalized();
+					Some(bp_runtime::BridgeStatus {
+						best_headers: BridgeThirdChain::best_headers(),
+						best_finalized: (best_finalized.number, best_finalized.hash()),
+						is_headers_pallet_halted: BridgeThirdChain::is_halted(),
+						is_message_lane_pallet_halted: BridgeThirdChainMessageLane::is_halted(),
+					})
+				}
+				_ => None,
+			}
+		}
+	}
+
+	impl bp_runtime::SovereignAccountApi<Block, AccountId, Balance> for Runtime {
+		fn sovereign_account(instance: bp_runtime::InstanceId) -> Option<(AccountId, Balance)> {
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE | bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => Some((
+					BridgeCallDispatch::sovereign_account(instance),
+					BridgeCallDispatch::sovereign_account_balance(instance),
+				)),
+				_ => None,
+			}
+		}
+	}
+
 	impl bp_millau::MillauHeaderApi<Block> for Runtime {
 		fn best_blocks() -> Vec<(bp_millau::BlockNumber, bp_millau::Hash)> {
 			BridgeMillau::best_headers()
@@ -704,40 +1130,253 @@ impl_runtime_apis! {
 		}
 	}
 
-	// TODO: runtime should support several chains (https://github.com/paritytech/parity-bridges-common/issues/457)
 	impl bp_message_lane::OutboundLaneApi<Block> for Runtime {
 		fn messages_dispatch_weight(
+			instance: bp_runtime::InstanceId,
 			lane: bp_message_lane::LaneId,
 			begin: bp_message_lane::MessageNonce,
 			end: bp_message_lane::MessageNonce,
-		) -> Vec<(bp_message_lane::MessageNonce, Weight)> {
-			(begin..=end).filter_map(|nonce| {
-				let encoded_payload = BridgeMillauMessageLane::outbound_message_payload(lane, nonce)?;
-				let decoded_payload = millau_messages::ToMillauMessagePayload::decode(
-					&mut &encoded_payload[..]
-				).ok()?;
-				Some((nonce, decoded_payload.weight))
-			})
-			.collect()
+		) -> Option<Vec<(bp_message_lane::MessageNonce, Weight)>> {
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE => Some((begin..=end).filter_map(|nonce| {
+					let encoded_payload = BridgeMillauMessageLane::outbound_message_payload(lane, nonce)?;
+					let decoded_payload = millau_messages::ToMillauMessagePayload::decode(
+						&mut &encoded_payload[..]
+					).ok()?;
+					Some((nonce, decoded_payload.weight))
+				})
+				.collect()),
+				bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => Some((begin..=end).filter_map(|nonce| {
+					let encoded_payload = BridgeThirdChainMessageLane::outbound_message_payload(lane, nonce)?;
+					let decoded_payload = third_chain_messages::ToThirdChainMessagePayload::decode(
+						&mut &encoded_payload[..]
+					).ok()?;
+					Some((nonce, decoded_payload.weight))
+				})
+				.collect()),
+				_ => None,
+			}
 		}
 
-		fn latest_received_nonce(lane: bp_message_lane::LaneId) -> bp_message_lane::MessageNonce {
-			BridgeMillauMessageLane::outbound_latest_received_nonce(lane)
+		fn latest_received_nonce(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+		) -> Option<bp_message_lane::MessageNonce> {
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE => Some(BridgeMillauMessageLane::outbound_latest_received_nonce(lane)),
+				bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => {
+					Some(BridgeThirdChainMessageLane::outbound_latest_received_nonce(lane))
+				}
+				_ => None,
+			}
 		}
 
-		fn latest_generated_nonce(lane: bp_message_lane::LaneId) -> bp_message_lane::MessageNonce {
-			BridgeMillauMessageLane::outbound_latest_generated_nonce(lane)
+		fn latest_generated_nonce(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+		) -> Option<bp_message_lane::MessageNonce> {
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE => Some(BridgeMillauMessageLane::outbound_latest_generated_nonce(lane)),
+				bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => {
+					Some(BridgeThirdChainMessageLane::outbound_latest_generated_nonce(lane))
+				}
+				_ => None,
+			}
+		}
+	}
+
+	impl bp_message_lane::OutboundLaneFeeApi<Block, Balance> for Runtime {
+		fn estimate_message_delivery_and_dispatch_fee(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+			payload: bp_message_lane::MessagePayload,
+		) -> Option<Balance> {
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE => {
+					let decoded_payload = millau_messages::ToMillauMessagePayload::decode(&mut &payload[..]).ok()?;
+					<messages::source::FromThisChainMessageFeeEstimator<
+						millau_messages::WithMillauMessageBridge,
+					> as bp_message_lane::source_chain::MessageFeeEstimator<_>>::estimate_message_fee(
+						&lane,
+						&decoded_payload,
+					).ok()
+				}
+				bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => {
+					let decoded_payload = third_chain_messages::ToThirdChainMessagePayload::decode(
+						&mut &payload[..]
+					).ok()?;
+					<messages::source::FromThisChainMessageFeeEstimator<
+						third_chain_messages::WithThirdChainMessageBridge,
+					> as bp_message_lane::source_chain::MessageFeeEstimator<_>>::estimate_message_fee(
+						&lane,
+						&decoded_payload,
+					).ok()
+				}
+				_ => None,
+			}
+		}
+
+		fn messages_fee(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+			begin: bp_message_lane::MessageNonce,
+			end: bp_message_lane::MessageNonce,
+		) -> Option<Balance> {
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE => (begin..=end).try_fold(0, |total: Balance, nonce| {
+					total.checked_add(BridgeMillauMessageLane::outbound_message_fee(lane, nonce)?)
+				}),
+				bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => (begin..=end).try_fold(0, |total: Balance, nonce| {
+					total.checked_add(BridgeThirdChainMessageLane::outbound_message_fee(lane, nonce)?)
+				}),
+				_ => None,
+			}
+		}
+	}
+
+	impl bp_message_lane::MessageLaneStorageApi<Block> for Runtime {
+		fn messages_keys(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+			begin: bp_message_lane::MessageNonce,
+			end: bp_message_lane::MessageNonce,
+		) -> Option<Vec<Vec<u8>>> {
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE => Some(
+					(begin..=end).map(|nonce| millau_messages::message_key(&lane, nonce).0).collect()
+				),
+				bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => Some(
+					(begin..=end).map(|nonce| third_chain_messages::message_key(&lane, nonce).0).collect()
+				),
+				_ => None,
+			}
+		}
+
+		fn outbound_lane_data_key(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+		) -> Option<Vec<u8>> {
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE => Some(millau_messages::outbound_lane_data_key(&lane).0),
+				bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => Some(third_chain_messages::outbound_lane_data_key(&lane).0),
+				_ => None,
+			}
+		}
+
+		fn inbound_lane_data_key(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+		) -> Option<Vec<u8>> {
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE => Some(millau_messages::inbound_lane_data_key(&lane).0),
+				bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => Some(third_chain_messages::inbound_lane_data_key(&lane).0),
+				_ => None,
+			}
+		}
+
+		fn storage_version_key(instance: bp_runtime::InstanceId) -> Option<Vec<u8>> {
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE => Some(millau_messages::storage_version_key().0),
+				bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => Some(third_chain_messages::storage_version_key().0),
+				_ => None,
+			}
+		}
+
+		fn inbound_lane_nonces(
+			instance: bp_runtime::InstanceId,
+			encoded_lane_data: Vec<u8>,
+		) -> Option<(bp_message_lane::MessageNonce, bp_message_lane::MessageNonce)> {
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE | bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => {
+					bp_message_lane::InboundLaneData::<bp_millau::AccountId>::decode(&mut &encoded_lane_data[..])
+						.ok()
+						.map(|data| (data.latest_received_nonce, data.latest_confirmed_nonce))
+				}
+				_ => None,
+			}
+		}
+	}
+
+	impl bp_message_lane::LanesApi<Block> for Runtime {
+		fn lanes(instance: bp_runtime::InstanceId) -> Option<Vec<bp_message_lane::LaneState>> {
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE => Some(BridgeMillauMessageLane::lanes()),
+				bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => Some(BridgeThirdChainMessageLane::lanes()),
+				_ => None,
+			}
+		}
+	}
+
+	impl bp_message_lane::InboundLaneRelayersApi<Block, bp_millau::AccountId> for Runtime {
+		fn unrewarded_relayers(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+		) -> Option<Vec<bp_message_lane::UnrewardedRelayer<bp_millau::AccountId>>> {
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE => Some(BridgeMillauMessageLane::inbound_unrewarded_relayers(lane)),
+				bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => {
+					Some(BridgeThirdChainMessageLane::inbound_unrewarded_relayers(lane))
+				}
+				_ => None,
+			}
+		}
+
+		fn max_unrewarded_relayer_entries(
+			instance: bp_runtime::InstanceId,
+		) -> Option<bp_message_lane::MessageNonce> {
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE | bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => {
+					Some(MaxUnconfirmedMessagesAtInboundLane::get())
+				}
+				_ => None,
+			}
+		}
+	}
+
+	impl bp_message_lane::InboundMessageDispatchApi<Block> for Runtime {
+		fn estimate_message_dispatch_weight(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+			payload: bp_message_lane::MessagePayload,
+		) -> Option<Weight> {
+			// dispatch weight doesn't depend on the message fee
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE => {
+					BridgeMillauMessageLane::inbound_message_dispatch_weight(lane, payload, 0)
+				}
+				bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => {
+					BridgeThirdChainMessageLane::inbound_message_dispatch_weight(lane, payload, 0)
+				}
+				_ => None,
+			}
 		}
 	}
 
-	// TODO: runtime should support several chains (https://github.com/paritytech/parity-bridges-common/issues/457)
 	impl bp_message_lane::InboundLaneApi<Block> for Runtime {
-		fn latest_received_nonce(lane: bp_message_lane::LaneId) -> bp_message_lane::MessageNonce {
-			BridgeMillauMessageLane::inbound_latest_received_nonce(lane)
+		fn latest_received_nonce(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+		) -> Option<bp_message_lane::MessageNonce> {
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE => Some(BridgeMillauMessageLane::inbound_latest_received_nonce(lane)),
+				bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => {
+					Some(BridgeThirdChainMessageLane::inbound_latest_received_nonce(lane))
+				}
+				_ => None,
+			}
 		}
 
-		fn latest_confirmed_nonce(lane: bp_message_lane::LaneId) -> bp_message_lane::MessageNonce {
-			BridgeMillauMessageLane::inbound_latest_confirmed_nonce(lane)
+		fn latest_confirmed_nonce(
+			instance: bp_runtime::InstanceId,
+			lane: bp_message_lane::LaneId,
+		) -> Option<bp_message_lane::MessageNonce> {
+			match instance {
+				bp_runtime::MILLAU_BRIDGE_INSTANCE => Some(BridgeMillauMessageLane::inbound_latest_confirmed_nonce(lane)),
+				bp_runtime::THIRD_CHAIN_BRIDGE_INSTANCE => {
+					Some(BridgeThirdChainMessageLane::inbound_latest_confirmed_nonce(lane))
+				}
+				_ => None,
+			}
 		}
 	}
 
@@ -820,7 +1459,9 @@ impl_runtime_apis! {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use bp_currency_exchange::DepositInto;
+	use bp_currency_exchange::{DepositAssetInto, DepositInto};
+	use frame_support::weights::GetDispatchInfo;
+	use pallet_bridge_call_dispatch::AdjustCallWeight;
 
 	fn run_deposit_into_test(test: impl Fn(AccountId) -> Balance) {
 		let mut ext: sp_io::TestExternalities = SystemConfig::default().build_storage::<Runtime>().unwrap().into();
@@ -894,4 +1535,146 @@ mod tests {
 			additional_amount
 		});
 	}
+
+	#[test]
+	fn deposit_asset_into_works() {
+		run_deposit_into_test(|_| {
+			let amount = ExistentialDeposit::get() + 10_000;
+			let new_account: AccountId = [42u8; 32].into();
+			<Runtime as pallet_bridge_currency_exchange::Trait<KovanCurrencyExchange>>::DepositAssetInto::deposit_asset_into(
+				POA_TOKEN_ASSET_ID,
+				new_account.clone(),
+				amount,
+			)
+			.unwrap();
+			assert_eq!(
+				<BridgedPoATokenBalances as Currency<AccountId>>::free_balance(&new_account),
+				amount,
+			);
+			assert_eq!(
+				<BridgedPoATokenBalances as Currency<AccountId>>::total_issuance(),
+				amount
+			);
+
+			// native currency is not minted
+			assert_eq!(<Balances as Currency<AccountId>>::free_balance(&new_account), 0);
+			0
+		});
+	}
+
+	#[test]
+	fn deposit_of_unknown_asset_fails() {
+		run_deposit_into_test(|existing_account| {
+			assert_eq!(
+				<Runtime as pallet_bridge_currency_exchange::Trait<KovanCurrencyExchange>>::DepositAssetInto::deposit_asset_into(
+					POA_TOKEN_ASSET_ID + 1,
+					existing_account.clone(),
+					10_000,
+				),
+				Err(bp_currency_exchange::Error::DepositFailed),
+			);
+			0
+		});
+	}
+
+	#[test]
+	fn bridge_relay_proxy_type_only_allows_relay_calls() {
+		let message_lane_call = Call::BridgeMillauMessageLane(MessageLaneCall::receive_messages_delivery_proof(
+			millau_messages::ToMillauMessagesDeliveryProof {
+				bridged_header_hash: Default::default(),
+				storage_proof: sp_trie::StorageProof::new(vec![]),
+				lane: Default::default(),
+			},
+		));
+		let balances_call: Call = BalancesCall::transfer([42u8; 32].into(), 1).into();
+
+		assert!(ProxyType::Any.filter(&message_lane_call));
+		assert!(ProxyType::Any.filter(&balances_call));
+		assert!(ProxyType::BridgeRelay.filter(&message_lane_call));
+		assert!(!ProxyType::BridgeRelay.filter(&balances_call));
+
+		// relayer may not send messages or administer bridge pallets on behalf of the proxied account
+		let non_relay_calls: Vec<Call> = vec![
+			Call::BridgeMillauMessageLane(MessageLaneCall::send_message(
+				Default::default(),
+				millau_messages::ToMillauMessagePayload {
+					spec_version: 1,
+					weight: 0,
+					origin: pallet_bridge_call_dispatch::CallOrigin::BridgeAccount,
+					call: pallet_bridge_call_dispatch::MessageCall::Single(vec![]),
+				},
+				0,
+			)),
+			Call::BridgeMillauMessageLane(MessageLaneCall::set_owner(None)),
+			Call::BridgeMillauMessageLane(MessageLaneCall::halt_operations()),
+			Call::BridgeThirdChainMessageLane(MessageLaneCall::halt_operations()),
+			Call::BridgeMillau(BridgeMillauCall::set_owner(None)),
+			Call::BridgeMillau(BridgeMillauCall::halt_operations()),
+			Call::BridgeThirdChain(BridgeMillauCall::halt_operations()),
+		];
+		for call in non_relay_calls {
+			assert!(ProxyType::Any.filter(&call));
+			assert!(!ProxyType::BridgeRelay.filter(&call), "{:?} is allowed", call);
+		}
+
+		// batched calls are filtered when the batch is dispatched
+		let batch_call: Call = UtilityCall::batch_all(vec![message_lane_call, balances_call]).into();
+		assert!(ProxyType::BridgeRelay.filter(&batch_call));
+
+		assert!(ProxyType::Any.is_superset(&ProxyType::BridgeRelay));
+		assert!(!ProxyType::BridgeRelay.is_superset(&ProxyType::Any));
+	}
+
+	#[test]
+	fn message_lane_instances_are_using_different_storage() {
+		let lane = bp_message_lane::LaneId::default();
+		assert_ne!(
+			millau_messages::message_key(&lane, 1),
+			third_chain_messages::message_key(&lane, 1),
+		);
+		assert_ne!(
+			millau_messages::outbound_lane_data_key(&lane),
+			third_chain_messages::outbound_lane_data_key(&lane),
+		);
+		assert_ne!(
+			millau_messages::inbound_lane_data_key(&lane),
+			third_chain_messages::inbound_lane_data_key(&lane),
+		);
+	}
+
+	#[test]
+	fn contracts_call_gas_limit_is_derived_from_declared_weight() {
+		type Adjuster = crate::millau_messages::FromMillauCallWeightAdjuster;
+
+		let dest: AccountId = [1u8; 32].into();
+		let contracts_call = |gas_limit| Call::Contracts(ContractsCall::call(dest.clone(), 0, gas_limit, vec![42]));
+		let base_weight = contracts_call(0).get_dispatch_info().weight;
+
+		let adjusted_call = Adjuster::adjust_call(contracts_call(0), base_weight + 1_000);
+		assert_eq!(adjusted_call, contracts_call(1_000));
+		assert_eq!(adjusted_call.get_dispatch_info().weight, base_weight + 1_000);
+
+		// declared weight is lower than base weight => gas limit is zero
+		assert_eq!(Adjuster::adjust_call(contracts_call(100), 0), contracts_call(0));
+
+		// other calls are not changed
+		let remark_call = Call::System(SystemCall::remark(vec![42]));
+		assert_eq!(Adjuster::adjust_call(remark_call.clone(), 1_000), remark_call);
+	}
+
+	#[test]
+	fn system_remark_call_matches_bridged_chains_encoding() {
+		let remark = vec![42u8; 64];
+		let remark_call = Call::System(SystemCall::remark(remark.clone()));
+		assert_eq!(
+			remark_call.encode(),
+			(
+				bp_rialto::SYSTEM_PALLET_INDEX,
+				bp_rialto::SYSTEM_REMARK_CALL_INDEX,
+				remark
+			)
+				.encode(),
+		);
+		assert!(remark_call.get_dispatch_info().weight <= bp_rialto::MAX_SYSTEM_REMARK_CALL_WEIGHT);
+	}
 }
@@ -26,7 +26,7 @@ use bp_message_lane::{
 use bp_runtime::{InstanceId, MILLAU_BRIDGE_INSTANCE};
 use bridge_runtime_common::messages::{self, ChainWithMessageLanes, MessageBridge};
 use frame_support::{
-	weights::{Weight, WeightToFeePolynomial},
+	weights::{GetDispatchInfo, Weight, WeightToFeePolynomial},
 	RuntimeDebug,
 };
 use sp_core::storage::StorageKey;
@@ -53,6 +53,11 @@ pub fn inbound_lane_data_key(lane: &LaneId) -> StorageKey {
 	>(lane)
 }
 
+/// Storage key of the message lane pallet storage version in the runtime storage.
+pub fn storage_version_key() -> StorageKey {
+	pallet_message_lane::storage_keys::storage_version_key::<<Rialto as ChainWithMessageLanes>::MessageLaneInstance>()
+}
+
 /// Message payload for Rialto -> Millau messages.
 pub type ToMillauMessagePayload = messages::source::FromThisChainMessagePayload<WithMillauMessageBridge>;
 
@@ -69,11 +74,35 @@ pub type FromMillauMessageDispatch = messages::target::FromBridgedChainMessageDi
 	pallet_bridge_call_dispatch::DefaultInstance,
 >;
 
+/// Adjusts calls of Millau -> Rialto messages before dispatch.
+///
+/// Gas limit of the `pallet_contracts::Call::call` is replaced with the weight that has been
+/// declared (and paid for) by the message sender, minus the base weight of the call. So the
+/// Millau contract (or account), that is calling Rialto contract over the message lane, only
+/// needs to declare message dispatch weight.
+pub struct FromMillauCallWeightAdjuster;
+
+impl pallet_bridge_call_dispatch::AdjustCallWeight<crate::Call> for FromMillauCallWeightAdjuster {
+	fn adjust_call(call: crate::Call, declared_weight: Weight) -> crate::Call {
+		match call {
+			crate::Call::Contracts(pallet_contracts::Call::call(dest, value, _, data)) => {
+				let base_weight =
+					crate::Call::Contracts(pallet_contracts::Call::call(dest.clone(), value, 0, data.clone()))
+						.get_dispatch_info()
+						.weight;
+				let gas_limit = declared_weight.saturating_sub(base_weight);
+				crate::Call::Contracts(pallet_contracts::Call::call(dest, value, gas_limit, data))
+			}
+			call => call,
+		}
+	}
+}
+
 /// Messages proof for Millau -> Rialto messages.
-type FromMillauMessagesProof = messages::target::FromBridgedChainMessagesProof<WithMillauMessageBridge>;
+pub type FromMillauMessagesProof = messages::target::FromBridgedChainMessagesProof<bp_millau::Hash>;
 
 /// Messages delivery proof for Rialto -> Millau messages.
-type ToMillauMessagesDeliveryProof = messages::source::FromBridgedChainMessagesDeliveryProof<WithMillauMessageBridge>;
+pub type ToMillauMessagesDeliveryProof = messages::source::FromBridgedChainMessagesDeliveryProof<bp_millau::Hash>;
 
 /// Millau <-> Rialto message bridge.
 #[derive(RuntimeDebug, Clone, Copy)]
@@ -117,6 +146,11 @@ impl MessageBridge for WithMillauMessageBridge {
 		// 1:1 conversion that will probably change in the future
 		this_balance as _
 	}
+
+	fn bridged_balance_to_this_balance(bridged_balance: bp_millau::Balance) -> bp_rialto::Balance {
+		// 1:1 conversion that will probably change in the future
+		bridged_balance as _
+	}
 }
 
 /// Rialto chain from message lane point of view.
@@ -170,7 +204,11 @@ impl TargetHeaderChain<ToMillauMessagePayload, bp_millau::AccountId> for Millau
 	fn verify_messages_delivery_proof(
 		proof: Self::MessagesDeliveryProof,
 	) -> Result<(LaneId, InboundLaneData<bp_rialto::AccountId>), Self::Error> {
-		messages::source::verify_messages_delivery_proof::<WithMillauMessageBridge, Runtime>(proof)
+		messages::source::verify_messages_delivery_proof::<
+			WithMillauMessageBridge,
+			Runtime,
+			pallet_substrate_bridge::DefaultInstance,
+		>(proof)
 	}
 }
 
@@ -186,6 +224,10 @@ impl SourceHeaderChain<bp_millau::Balance> for Millau {
 	fn verify_messages_proof(
 		proof: Self::MessagesProof,
 	) -> Result<ProvedMessages<Message<bp_millau::Balance>>, Self::Error> {
-		messages::target::verify_messages_proof::<WithMillauMessageBridge, Runtime>(proof)
+		messages::target::verify_messages_proof::<
+			WithMillauMessageBridge,
+			Runtime,
+			pallet_substrate_bridge::DefaultInstance,
+		>(proof)
 	}
 }
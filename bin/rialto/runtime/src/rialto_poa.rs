@@ -18,7 +18,7 @@
 
 use crate::exchange::EthereumTransactionInclusionProof;
 
-use bp_eth_poa::{Address, AuraHeader, RawTransaction, U256};
+use bp_eth_poa::{Address, AuraHeader, RawTransaction, RawTransactionReceipt, U256};
 use bp_header_chain::BaseHeaderChain;
 use frame_support::RuntimeDebug;
 use hex_literal::hex;
@@ -30,6 +30,7 @@ use sp_std::prelude::*;
 
 frame_support::parameter_types! {
 	pub const FinalityVotesCachingInterval: Option<u64> = Some(8);
+	pub const HeadersCompactionDepth: Option<u64> = Some(FINALIZED_HEADERS_TO_KEEP_IN_FULL);
 	pub BridgeAuraConfiguration: AuraConfiguration =
 		aura_configuration();
 	pub BridgeValidatorsConfiguration: ValidatorsConfiguration =
@@ -39,6 +40,10 @@ frame_support::parameter_types! {
 /// Max number of finalized headers to keep.
 const FINALIZED_HEADERS_TO_KEEP: u64 = 5_000;
 
+/// Max number of finalized headers to keep in full. Older finalized headers are compacted,
+/// so transactions from these headers can't be verified anymore.
+const FINALIZED_HEADERS_TO_KEEP_IN_FULL: u64 = 1_000;
+
 /// Aura engine configuration for Rialto chain.
 pub fn aura_configuration() -> AuraConfiguration {
 	AuraConfiguration {
@@ -125,7 +130,7 @@ impl TChainTime for ChainTime {
 pub struct RialtoBlockchain;
 
 impl BaseHeaderChain for RialtoBlockchain {
-	type Transaction = RawTransaction;
+	type Transaction = (RawTransaction, RawTransactionReceipt);
 	type TransactionInclusionProof = EthereumTransactionInclusionProof;
 
 	fn verify_transaction_inclusion_proof(proof: &Self::TransactionInclusionProof) -> Option<Self::Transaction> {
@@ -136,7 +141,7 @@ impl BaseHeaderChain for RialtoBlockchain {
 			return None;
 		}
 
-		proof.proof.get(proof.index as usize).map(|(tx, _)| tx.clone())
+		proof.proof.get(proof.index as usize).cloned()
 	}
 }
 
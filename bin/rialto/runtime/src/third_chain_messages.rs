@@ -0,0 +1,219 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Everything required to serve ThirdChain <-> Rialto message lanes.
+//!
+//! The third chain is a hypothetical chain that is using Millau primitives. It only exists to
+//! have second header-chain + message-lane pallets pair in the Rialto runtime.
+
+use crate::Runtime;
+
+use bp_message_lane::{
+	source_chain::TargetHeaderChain,
+	target_chain::{ProvedMessages, SourceHeaderChain},
+	InboundLaneData, LaneId, Message, MessageNonce,
+};
+use bp_runtime::{InstanceId, THIRD_CHAIN_BRIDGE_INSTANCE};
+use bridge_runtime_common::messages::{self, ChainWithMessageLanes, MessageBridge};
+use frame_support::{
+	weights::{Weight, WeightToFeePolynomial},
+	RuntimeDebug,
+};
+use sp_core::storage::StorageKey;
+
+/// Instance of the header-chain pallet, that is tracking third chain headers.
+pub type ThirdChainHeadersInstance = pallet_substrate_bridge::Instance1;
+
+/// Instance of the message-lane pallet, that is serving ThirdChain <-> Rialto lanes.
+pub type ThirdChainMessageLaneInstance = pallet_message_lane::Instance1;
+
+/// Storage key of the Rialto -> ThirdChain message in the runtime storage.
+pub fn message_key(lane: &LaneId, nonce: MessageNonce) -> StorageKey {
+	pallet_message_lane::storage_keys::message_key::<Runtime, <Rialto as ChainWithMessageLanes>::MessageLaneInstance>(
+		lane, nonce,
+	)
+}
+
+/// Storage key of the Rialto -> ThirdChain message lane state in the runtime storage.
+pub fn outbound_lane_data_key(lane: &LaneId) -> StorageKey {
+	pallet_message_lane::storage_keys::outbound_lane_data_key::<<Rialto as ChainWithMessageLanes>::MessageLaneInstance>(
+		lane,
+	)
+}
+
+/// Storage key of the ThirdChain -> Rialto message lane state in the runtime storage.
+pub fn inbound_lane_data_key(lane: &LaneId) -> StorageKey {
+	pallet_message_lane::storage_keys::inbound_lane_data_key::<
+		Runtime,
+		<Rialto as ChainWithMessageLanes>::MessageLaneInstance,
+	>(lane)
+}
+
+/// Storage key of the message lane pallet storage version in the runtime storage.
+pub fn storage_version_key() -> StorageKey {
+	pallet_message_lane::storage_keys::storage_version_key::<<Rialto as ChainWithMessageLanes>::MessageLaneInstance>()
+}
+
+/// Message payload for Rialto -> ThirdChain messages.
+pub type ToThirdChainMessagePayload = messages::source::FromThisChainMessagePayload<WithThirdChainMessageBridge>;
+
+/// Message verifier for Rialto -> ThirdChain messages.
+pub type ToThirdChainMessageVerifier = messages::source::FromThisChainMessageVerifier<WithThirdChainMessageBridge>;
+
+/// Message payload for ThirdChain -> Rialto messages.
+pub type FromThirdChainMessagePayload = messages::target::FromBridgedChainMessagePayload<WithThirdChainMessageBridge>;
+
+/// Call-dispatch based message dispatch for ThirdChain -> Rialto messages.
+///
+/// The call-dispatch pallet instance is shared with the Millau bridge. Messages of different
+/// bridges are distinguished by the bridge instance id.
+pub type FromThirdChainMessageDispatch = messages::target::FromBridgedChainMessageDispatch<
+	WithThirdChainMessageBridge,
+	crate::Runtime,
+	pallet_bridge_call_dispatch::DefaultInstance,
+>;
+
+/// Messages proof for ThirdChain -> Rialto messages.
+pub type FromThirdChainMessagesProof = messages::target::FromBridgedChainMessagesProof<bp_millau::Hash>;
+
+/// Messages delivery proof for Rialto -> ThirdChain messages.
+pub type ToThirdChainMessagesDeliveryProof = messages::source::FromBridgedChainMessagesDeliveryProof<bp_millau::Hash>;
+
+/// ThirdChain <-> Rialto message bridge.
+#[derive(RuntimeDebug, Clone, Copy)]
+pub struct WithThirdChainMessageBridge;
+
+impl MessageBridge for WithThirdChainMessageBridge {
+	const INSTANCE: InstanceId = THIRD_CHAIN_BRIDGE_INSTANCE;
+
+	const RELAYER_FEE_PERCENT: u32 = 10;
+
+	type ThisChain = Rialto;
+	type BridgedChain = ThirdChain;
+
+	fn maximal_dispatch_weight_of_message_on_bridged_chain() -> Weight {
+		// we don't want to relay too large messages + keep reserve for future upgrades
+		bp_millau::MAXIMUM_EXTRINSIC_WEIGHT / 2
+	}
+
+	fn weight_of_delivery_transaction() -> Weight {
+		0 // TODO: https://github.com/paritytech/parity-bridges-common/issues/391
+	}
+
+	fn weight_of_delivery_confirmation_transaction_on_this_chain() -> Weight {
+		0 // TODO: https://github.com/paritytech/parity-bridges-common/issues/391
+	}
+
+	fn weight_of_reward_confirmation_transaction_on_target_chain() -> Weight {
+		0 // TODO: https://github.com/paritytech/parity-bridges-common/issues/391
+	}
+
+	fn this_weight_to_this_balance(weight: Weight) -> bp_rialto::Balance {
+		<crate::Runtime as pallet_transaction_payment::Trait>::WeightToFee::calc(&weight)
+	}
+
+	fn bridged_weight_to_bridged_balance(weight: Weight) -> bp_millau::Balance {
+		// we're using the same weights in both chains now
+		<crate::Runtime as pallet_transaction_payment::Trait>::WeightToFee::calc(&weight) as _
+	}
+
+	fn this_balance_to_bridged_balance(this_balance: bp_rialto::Balance) -> bp_millau::Balance {
+		// 1:1 conversion that will probably change in the future
+		this_balance as _
+	}
+
+	fn bridged_balance_to_this_balance(bridged_balance: bp_millau::Balance) -> bp_rialto::Balance {
+		// 1:1 conversion that will probably change in the future
+		bridged_balance as _
+	}
+}
+
+/// Rialto chain from message lane point of view.
+#[derive(RuntimeDebug, Clone, Copy)]
+pub struct Rialto;
+
+impl messages::ChainWithMessageLanes for Rialto {
+	type Hash = bp_rialto::Hash;
+	type AccountId = bp_rialto::AccountId;
+	type Signer = bp_rialto::AccountSigner;
+	type Signature = bp_rialto::Signature;
+	type Call = crate::Call;
+	type Weight = Weight;
+	type Balance = bp_rialto::Balance;
+
+	type MessageLaneInstance = ThirdChainMessageLaneInstance;
+}
+
+/// ThirdChain chain from message lane point of view.
+#[derive(RuntimeDebug, Clone, Copy)]
+pub struct ThirdChain;
+
+impl messages::ChainWithMessageLanes for ThirdChain {
+	type Hash = bp_millau::Hash;
+	type AccountId = bp_millau::AccountId;
+	type Signer = bp_millau::AccountSigner;
+	type Signature = bp_millau::Signature;
+	type Call = (); // unknown to us
+	type Weight = Weight;
+	type Balance = bp_millau::Balance;
+
+	type MessageLaneInstance = pallet_message_lane::DefaultInstance;
+}
+
+impl TargetHeaderChain<ToThirdChainMessagePayload, bp_millau::AccountId> for ThirdChain {
+	type Error = &'static str;
+	// The proof is:
+	// - hash of the header this proof has been created with;
+	// - the storage proof of one or several keys;
+	// - id of the lane we prove state of.
+	type MessagesDeliveryProof = ToThirdChainMessagesDeliveryProof;
+
+	fn verify_message(payload: &ToThirdChainMessagePayload) -> Result<(), Self::Error> {
+		if payload.weight > WithThirdChainMessageBridge::maximal_dispatch_weight_of_message_on_bridged_chain() {
+			return Err("Payload has weight larger than maximum allowed weight");
+		}
+
+		Ok(())
+	}
+
+	fn verify_messages_delivery_proof(
+		proof: Self::MessagesDeliveryProof,
+	) -> Result<(LaneId, InboundLaneData<bp_rialto::AccountId>), Self::Error> {
+		messages::source::verify_messages_delivery_proof::<
+			WithThirdChainMessageBridge,
+			Runtime,
+			ThirdChainHeadersInstance,
+		>(proof)
+	}
+}
+
+impl SourceHeaderChain<bp_millau::Balance> for ThirdChain {
+	type Error = &'static str;
+	// The proof is:
+	// - hash of the header this proof has been created with;
+	// - the storage proof of one or several keys;
+	// - id of the lane we prove messages for;
+	// - inclusive range of messages nonces that are proved.
+	type MessagesProof = FromThirdChainMessagesProof;
+
+	fn verify_messages_proof(
+		proof: Self::MessagesProof,
+	) -> Result<ProvedMessages<Message<bp_millau::Balance>>, Self::Error> {
+		messages::target::verify_messages_proof::<WithThirdChainMessageBridge, Runtime, ThirdChainHeadersInstance>(
+			proof,
+		)
+	}
+}
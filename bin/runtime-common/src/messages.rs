@@ -22,14 +22,15 @@
 
 use bp_message_dispatch::MessageDispatch as _;
 use bp_message_lane::{
-	source_chain::LaneMessageVerifier,
+	source_chain::{LaneMessageVerifier, MessageFeeEstimator, MessageFeeQuote},
 	target_chain::{DispatchMessage, MessageDispatch, ProvedLaneMessages, ProvedMessages},
-	InboundLaneData, LaneId, Message, MessageData, MessageKey, MessageNonce, OutboundLaneData,
+	DecodeVersioned, InboundLaneData, LaneId, Message, MessageData, MessageKey, MessageNonce, OutboundLaneData,
+	StorageVersion, LEGACY_STORAGE_VERSION,
 };
-use bp_runtime::InstanceId;
-use codec::{Compact, Decode, Input};
+use bp_runtime::{InstanceId, Size, StorageProofChecker};
+use codec::{Compact, Decode, Encode, Input};
 use frame_support::{traits::Instance, RuntimeDebug};
-use sp_runtime::traits::{CheckedAdd, CheckedDiv, CheckedMul};
+use sp_runtime::traits::{CheckedAdd, CheckedDiv, CheckedMul, Hash};
 use sp_std::{cmp::PartialOrd, marker::PhantomData, vec::Vec};
 use sp_trie::StorageProof;
 
@@ -60,6 +61,12 @@ pub trait MessageBridge {
 	/// transaction weight.
 	fn weight_of_reward_confirmation_transaction_on_target_chain() -> WeightOf<BridgedChain<Self>>;
 
+	/// Fee (paid in Balance of the Bridged chain) for every byte of the message payload. Large
+	/// payloads are increasing size of the delivery transaction and of the messages proof.
+	fn bridged_balance_per_payload_byte() -> BalanceOf<BridgedChain<Self>> {
+		0u32.into()
+	}
+
 	/// Convert weight of This chain to the fee (paid in Balance) of This chain.
 	fn this_weight_to_this_balance(weight: WeightOf<ThisChain<Self>>) -> BalanceOf<ThisChain<Self>>;
 
@@ -68,6 +75,12 @@ pub trait MessageBridge {
 
 	/// Convert This chain Balance into Bridged chain Balance.
 	fn this_balance_to_bridged_balance(this_balance: BalanceOf<ThisChain<Self>>) -> BalanceOf<BridgedChain<Self>>;
+
+	/// Convert Bridged chain Balance into This chain Balance.
+	///
+	/// The result must be rounded up, so that converting it back with `this_balance_to_bridged_balance`
+	/// never gives a value that is less than the original Bridged chain Balance.
+	fn bridged_balance_to_this_balance(bridged_balance: BalanceOf<BridgedChain<Self>>) -> BalanceOf<ThisChain<Self>>;
 }
 
 /// Chain that has `message-lane` and `call-dispatch` modules.
@@ -77,9 +90,9 @@ pub trait ChainWithMessageLanes {
 	/// Accound id on the chain.
 	type AccountId: Decode;
 	/// Public key of the chain account that may be used to verify signatures.
-	type Signer: Decode;
+	type Signer: Decode + Encode;
 	/// Signature type used on the chain.
-	type Signature: Decode;
+	type Signature: Decode + Encode;
 	/// Call type on the chain.
 	type Call: Decode;
 	/// Type of weight that is used on the chain. This would almost always be a regular
@@ -120,12 +133,22 @@ pub mod source {
 		BridgedChainOpaqueCall,
 	>;
 
-	/// Messages delivery proof from bridged chain:
-	///
-	/// - hash of finalized header;
-	/// - storage proof of inbound lane state;
-	/// - lane id.
-	pub type FromBridgedChainMessagesDeliveryProof<B> = (HashOf<BridgedChain<B>>, StorageProof, LaneId);
+	/// Messages delivery proof from bridged chain.
+	#[derive(Clone, Decode, Encode, Eq, PartialEq, RuntimeDebug)]
+	pub struct FromBridgedChainMessagesDeliveryProof<BridgedHeaderHash> {
+		/// Hash of the finalized bridged header the proof is for.
+		pub bridged_header_hash: BridgedHeaderHash,
+		/// Storage proof of inbound lane state.
+		pub storage_proof: StorageProof,
+		/// Id of the lane.
+		pub lane: LaneId,
+	}
+
+	impl<BridgedHeaderHash> Size for FromBridgedChainMessagesDeliveryProof<BridgedHeaderHash> {
+		fn size_hint(&self) -> u32 {
+			self.storage_proof.size_hint()
+		}
+	}
 
 	/// 'Parsed' message delivery proof - inbound lane id and its state.
 	pub type ParsedMessagesDeliveryProofFromBridgedChain<B> = (LaneId, InboundLaneData<AccountIdOf<ThisChain<B>>>);
@@ -143,14 +166,17 @@ pub mod source {
 		fn verify_message(
 			_submitter: &AccountIdOf<ThisChain<B>>,
 			delivery_and_dispatch_fee: &BalanceOf<ThisChain<B>>,
-			_lane: &LaneId,
+			lane: &LaneId,
 			payload: &FromThisChainMessagePayload<B>,
 		) -> Result<(), Self::Error> {
 			let minimal_fee_in_bridged_tokens =
-				estimate_message_dispatch_and_delivery_fee::<B>(payload, B::RELAYER_FEE_PERCENT)?;
+				FromThisChainMessageFeeEstimator::<B>::quote_message_fee(lane, payload)?
+					.total()
+					.ok_or(FEE_OVERFLOW)?;
 
 			// compare with actual fee paid
-			let actual_fee_in_bridged_tokens = B::this_balance_to_bridged_balance(*delivery_and_dispatch_fee);
+			let actual_fee_in_bridged_tokens =
+				FromThisChainMessageFeeEstimator::<B>::convert_to_target_chain_fee(*delivery_and_dispatch_fee)?;
 			if actual_fee_in_bridged_tokens < minimal_fee_in_bridged_tokens {
 				return Err("Too low fee paid");
 			}
@@ -159,17 +185,52 @@ pub mod source {
 		}
 	}
 
-	/// Estimate delivery and dispatch fee that must be paid for delivering a message to the Bridged chain.
+	/// Error that is returned when fee computation overflows.
+	const FEE_OVERFLOW: &str = "Overflow when computing minimal required message delivery and dispatch fee";
+
+	/// Estimator of the delivery and dispatch fee of This -> Bridged chain messages.
+	#[derive(RuntimeDebug)]
+	pub struct FromThisChainMessageFeeEstimator<B>(PhantomData<B>);
+
+	impl<B: MessageBridge> MessageFeeEstimator<FromThisChainMessagePayload<B>> for FromThisChainMessageFeeEstimator<B> {
+		type SourceChainFee = BalanceOf<ThisChain<B>>;
+		type TargetChainFee = BalanceOf<BridgedChain<B>>;
+		type Error = &'static str;
+
+		fn quote_message_fee(
+			_lane: &LaneId,
+			payload: &FromThisChainMessagePayload<B>,
+		) -> Result<MessageFeeQuote<Self::TargetChainFee>, Self::Error> {
+			quote_message_dispatch_and_delivery_fee::<B>(payload, B::RELAYER_FEE_PERCENT)
+		}
+
+		fn convert_to_source_chain_fee(fee: Self::TargetChainFee) -> Result<Self::SourceChainFee, Self::Error> {
+			Ok(B::bridged_balance_to_this_balance(fee))
+		}
+
+		fn convert_to_target_chain_fee(fee: Self::SourceChainFee) -> Result<Self::TargetChainFee, Self::Error> {
+			Ok(B::this_balance_to_bridged_balance(fee))
+		}
+
+		fn estimate_message_fee(
+			lane: &LaneId,
+			payload: &FromThisChainMessagePayload<B>,
+		) -> Result<Self::SourceChainFee, Self::Error> {
+			let fee = Self::quote_message_fee(lane, payload)?.total().ok_or(FEE_OVERFLOW)?;
+			Self::convert_to_source_chain_fee(fee)
+		}
+	}
+
+	/// Return components of the delivery and dispatch fee that must be paid for delivering a
+	/// message to the Bridged chain.
 	///
 	/// The fee is paid in This chain Balance, but we use Bridged chain balance to avoid additional conversions.
-	/// Returns `None` if overflow has happened.
-	pub fn estimate_message_dispatch_and_delivery_fee<B: MessageBridge>(
+	pub fn quote_message_dispatch_and_delivery_fee<B: MessageBridge>(
 		payload: &FromThisChainMessagePayload<B>,
 		relayer_fee_percent: u32,
-	) -> Result<BalanceOf<BridgedChain<B>>, &'static str> {
+	) -> Result<MessageFeeQuote<BalanceOf<BridgedChain<B>>>, &'static str> {
 		// the fee (in Bridged tokens) of all transactions that are made on the Bridged chain
 		let delivery_fee = B::bridged_weight_to_bridged_balance(B::weight_of_delivery_transaction());
-		let dispatch_fee = B::bridged_weight_to_bridged_balance(payload.weight.into());
 		let reward_confirmation_fee =
 			B::bridged_weight_to_bridged_balance(B::weight_of_reward_confirmation_transaction_on_target_chain());
 
@@ -178,42 +239,78 @@ pub mod source {
 			B::weight_of_delivery_confirmation_transaction_on_this_chain(),
 		));
 
-		// minimal fee (in Bridged tokens) is a sum of all required fees
-		let minimal_fee = delivery_fee
-			.checked_add(&dispatch_fee)
-			.and_then(|fee| fee.checked_add(&reward_confirmation_fee))
-			.and_then(|fee| fee.checked_add(&delivery_confirmation_fee));
-
-		// before returning, add extra fee that is paid to the relayer (relayer interest)
-		minimal_fee
-			.and_then(|fee|
-			// having message with fee that is near the `Balance::MAX_VALUE` of the chain is
-			// unlikely and should be treated as an error
-			// => let's do multiplication first
-			fee
-				.checked_mul(&relayer_fee_percent.into())
-				.and_then(|interest| interest.checked_div(&100u32.into()))
-				.and_then(|interest| fee.checked_add(&interest)))
-			.ok_or("Overflow when computing minimal required message delivery and dispatch fee")
+		let base_fee = delivery_fee
+			.checked_add(&reward_confirmation_fee)
+			.and_then(|fee| fee.checked_add(&delivery_confirmation_fee))
+			.ok_or(FEE_OVERFLOW)?;
+
+		Ok(MessageFeeQuote {
+			base_fee,
+			per_byte_fee: B::bridged_balance_per_payload_byte(),
+			payload_size: payload.encoded_size() as u32,
+			dispatch_weight_fee: B::bridged_weight_to_bridged_balance(payload.weight.into()),
+			relayer_fee_percent,
+		})
+	}
+
+	/// Estimate delivery and dispatch fee that must be paid for delivering a message to the Bridged chain.
+	///
+	/// The fee is paid in This chain Balance, but we use Bridged chain balance to avoid additional conversions.
+	/// Returns error if overflow has happened.
+	pub fn estimate_message_dispatch_and_delivery_fee<B: MessageBridge>(
+		payload: &FromThisChainMessagePayload<B>,
+		relayer_fee_percent: u32,
+	) -> Result<BalanceOf<BridgedChain<B>>, &'static str> {
+		quote_message_dispatch_and_delivery_fee::<B>(payload, relayer_fee_percent)?
+			.total()
+			.ok_or(FEE_OVERFLOW)
+	}
+
+	/// Estimate delivery and dispatch fee (in This chain Balance) that must be paid for delivering
+	/// a message to the Bridged chain.
+	///
+	/// This is the minimal fee that is accepted by the `FromThisChainMessageVerifier`.
+	pub fn estimate_message_dispatch_and_delivery_fee_in_this_chain_tokens<B: MessageBridge>(
+		payload: &FromThisChainMessagePayload<B>,
+		relayer_fee_percent: u32,
+	) -> Result<BalanceOf<ThisChain<B>>, &'static str> {
+		estimate_message_dispatch_and_delivery_fee::<B>(payload, relayer_fee_percent)
+			.map(B::bridged_balance_to_this_balance)
 	}
 
 	/// Verify proof of This -> Bridged chain messages delivery.
-	pub fn verify_messages_delivery_proof<B: MessageBridge, ThisRuntime>(
-		proof: FromBridgedChainMessagesDeliveryProof<B>,
+	pub fn verify_messages_delivery_proof<B: MessageBridge, ThisRuntime, ThisHeadersInstance: Instance>(
+		proof: FromBridgedChainMessagesDeliveryProof<HashOf<BridgedChain<B>>>,
 	) -> Result<ParsedMessagesDeliveryProofFromBridgedChain<B>, &'static str>
 	where
-		ThisRuntime: pallet_substrate_bridge::Trait,
+		ThisRuntime: pallet_substrate_bridge::Trait<ThisHeadersInstance>,
 		ThisRuntime: pallet_message_lane::Trait<MessageLaneInstanceOf<BridgedChain<B>>>,
-		HashOf<BridgedChain<B>>:
-			Into<bp_runtime::HashOf<<ThisRuntime as pallet_substrate_bridge::Trait>::BridgedChain>>,
+		HashOf<BridgedChain<B>>: Into<
+			bp_runtime::HashOf<<ThisRuntime as pallet_substrate_bridge::Trait<ThisHeadersInstance>>::BridgedChain>,
+		>,
 	{
-		let (bridged_header_hash, bridged_storage_proof, lane) = proof;
-		pallet_substrate_bridge::Module::<ThisRuntime>::parse_finalized_storage_proof(
+		let FromBridgedChainMessagesDeliveryProof {
+			bridged_header_hash,
+			storage_proof: bridged_storage_proof,
+			lane,
+		} = proof;
+		pallet_substrate_bridge::Module::<ThisRuntime, ThisHeadersInstance>::parse_finalized_storage_proof(
 			bridged_header_hash.into(),
 			bridged_storage_proof,
 			|storage| {
-				// Messages delivery proof is just proof of single storage key read => any error
-				// is fatal.
+				// Messages delivery proof is just proof of single storage key read (+ optional storage
+				// version) => any error is fatal.
+				let storage_version = decode_storage_version(
+					storage
+						.read_value(
+							pallet_message_lane::storage_keys::storage_version_key::<
+								MessageLaneInstanceOf<BridgedChain<B>>,
+							>()
+							.0
+							.as_ref(),
+						)
+						.map_err(|_| "Failed to read message lane storage version from the proof")?,
+				)?;
 				let storage_inbound_lane_data_key = pallet_message_lane::storage_keys::inbound_lane_data_key::<
 					ThisRuntime,
 					MessageLaneInstanceOf<BridgedChain<B>>,
@@ -222,7 +319,7 @@ pub mod source {
 					.read_value(storage_inbound_lane_data_key.0.as_ref())
 					.map_err(|_| "Failed to read inbound lane state from storage proof")?
 					.ok_or("Inbound lane state is missing from the messages proof")?;
-				let inbound_lane_data = InboundLaneData::decode(&mut &raw_inbound_lane_data[..])
+				let inbound_lane_data = InboundLaneData::decode_versioned(storage_version, &raw_inbound_lane_data)
 					.map_err(|_| "Failed to decode inbound lane state from the proof")?;
 
 				Ok((lane, inbound_lane_data))
@@ -232,6 +329,24 @@ pub mod source {
 	}
 }
 
+/// Maximal number of messages that we preallocate memory for, when parsing messages proof. Nonces
+/// range comes from the (untrusted) proof submitter, so we can't preallocate memory for all
+/// messages of the range before they are actually read from the proof.
+const MAX_PREALLOCATED_PROVED_MESSAGES: MessageNonce = 1024;
+
+/// Decode version of the bridged chain message lane pallet storage, read from the storage proof.
+///
+/// The proof must always contain the storage version key. If the proof shows that the value is
+/// missing, we assume that the bridged chain pallet is using the legacy storage version. If the
+/// proof has no trie nodes to decide that, the proof is rejected by the caller.
+fn decode_storage_version(raw_storage_version: Option<Vec<u8>>) -> Result<StorageVersion, &'static str> {
+	match raw_storage_version {
+		Some(raw_storage_version) => StorageVersion::decode(&mut &raw_storage_version[..])
+			.map_err(|_| "Failed to decode message lane storage version from the proof"),
+		None => Ok(LEGACY_STORAGE_VERSION),
+	}
+}
+
 /// Sub-module that is declaring types required for processing Bridged -> This chain messages.
 pub mod target {
 	use super::*;
@@ -251,32 +366,44 @@ pub mod target {
 		CallOf<ThisChain<B>>,
 	>;
 
-	/// Messages proof from bridged chain:
-	///
-	/// - hash of finalized header;
-	/// - storage proof of messages and (optionally) outbound lane state;
-	/// - lane id;
-	/// - nonces (inclusive range) of messages which are included in this proof.
-	pub type FromBridgedChainMessagesProof<B> = (
-		HashOf<BridgedChain<B>>,
-		StorageProof,
-		LaneId,
-		MessageNonce,
-		MessageNonce,
-	);
+	/// Messages proof from bridged chain.
+	#[derive(Clone, Decode, Encode, Eq, PartialEq, RuntimeDebug)]
+	pub struct FromBridgedChainMessagesProof<BridgedHeaderHash> {
+		/// Hash of the finalized bridged header the proof is for.
+		pub bridged_header_hash: BridgedHeaderHash,
+		/// Storage proof of messages and (optionally) outbound lane state.
+		pub storage_proof: StorageProof,
+		/// Id of the lane.
+		pub lane: LaneId,
+		/// Nonce of the first message being delivered.
+		pub nonces_start: MessageNonce,
+		/// Nonce of the last message being delivered.
+		pub nonces_end: MessageNonce,
+	}
+
+	impl<BridgedHeaderHash> Size for FromBridgedChainMessagesProof<BridgedHeaderHash> {
+		fn size_hint(&self) -> u32 {
+			self.storage_proof.size_hint()
+		}
+	}
 
 	/// Message payload for Bridged -> This messages.
 	pub struct FromBridgedChainMessagePayload<B: MessageBridge>(pub(crate) FromBridgedChainDecodedMessagePayload<B>);
 
+	impl<B: MessageBridge> FromBridgedChainMessagePayload<B> {
+		/// Consume self and return decoded message payload.
+		pub fn into_inner(self) -> FromBridgedChainDecodedMessagePayload<B> {
+			self.0
+		}
+	}
+
 	impl<B: MessageBridge> Decode for FromBridgedChainMessagePayload<B> {
 		fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
-			// for bridged chain our Calls are opaque - they're encoded to Vec<u8> by submitter
-			// => skip encoded vec length here before decoding Call
 			let spec_version = pallet_bridge_call_dispatch::SpecVersion::decode(input)?;
 			let weight = frame_support::weights::Weight::decode(input)?;
 			let origin = FromBridgedChainMessageCallOrigin::<B>::decode(input)?;
-			let _skipped_length = Compact::<u32>::decode(input)?;
-			let call = CallOf::<ThisChain<B>>::decode(input)?;
+			let call = pallet_bridge_call_dispatch::MessageCall::<FromBridgedChainOpaqueCall<B>>::decode(input)?
+				.map(|call| call.0);
 
 			Ok(FromBridgedChainMessagePayload(
 				pallet_bridge_call_dispatch::MessagePayload {
@@ -289,6 +416,18 @@ pub mod target {
 		}
 	}
 
+	/// Call of This chain that has been encoded as opaque call (`Vec<u8>`) on the Bridged chain.
+	struct FromBridgedChainOpaqueCall<B: MessageBridge>(CallOf<ThisChain<B>>);
+
+	impl<B: MessageBridge> Decode for FromBridgedChainOpaqueCall<B> {
+		fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+			// for bridged chain our Calls are opaque - they're encoded to Vec<u8> by submitter
+			// => skip encoded vec length here before decoding Call
+			let _skipped_length = Compact::<u32>::decode(input)?;
+			CallOf::<ThisChain<B>>::decode(input).map(FromBridgedChainOpaqueCall)
+		}
+	}
+
 	/// Dispatching Bridged -> This chain messages.
 	#[derive(RuntimeDebug, Clone, Copy)]
 	pub struct FromBridgedChainMessageDispatch<B, ThisRuntime, ThisCallDispatchInstance> {
@@ -332,81 +471,122 @@ pub mod target {
 	}
 
 	/// Verify proof of Bridged -> This chain messages.
-	pub fn verify_messages_proof<B: MessageBridge, ThisRuntime>(
-		proof: FromBridgedChainMessagesProof<B>,
+	pub fn verify_messages_proof<B: MessageBridge, ThisRuntime, ThisHeadersInstance: Instance>(
+		proof: FromBridgedChainMessagesProof<HashOf<BridgedChain<B>>>,
 	) -> Result<ProvedMessages<Message<BalanceOf<BridgedChain<B>>>>, &'static str>
 	where
-		ThisRuntime: pallet_substrate_bridge::Trait,
+		ThisRuntime: pallet_substrate_bridge::Trait<ThisHeadersInstance>,
 		ThisRuntime: pallet_message_lane::Trait<MessageLaneInstanceOf<BridgedChain<B>>>,
-		HashOf<BridgedChain<B>>:
-			Into<bp_runtime::HashOf<<ThisRuntime as pallet_substrate_bridge::Trait>::BridgedChain>>,
+		HashOf<BridgedChain<B>>: Into<
+			bp_runtime::HashOf<<ThisRuntime as pallet_substrate_bridge::Trait<ThisHeadersInstance>>::BridgedChain>,
+		>,
 	{
-		let (bridged_header_hash, bridged_storage_proof, lane_id, begin, end) = proof;
-		pallet_substrate_bridge::Module::<ThisRuntime>::parse_finalized_storage_proof(
+		let FromBridgedChainMessagesProof {
+			bridged_header_hash,
+			storage_proof: bridged_storage_proof,
+			lane: lane_id,
+			nonces_start: begin,
+			nonces_end: end,
+		} = proof;
+		pallet_substrate_bridge::Module::<ThisRuntime, ThisHeadersInstance>::parse_finalized_storage_proof(
 			bridged_header_hash.into(),
 			bridged_storage_proof,
 			|storage| {
-				// Read messages first. All messages that are claimed to be in the proof must
-				// be in the proof. So any error in `read_value`, or even missing value is fatal.
-				//
-				// Mind that we allow proofs with no messages if outbound lane state is proved.
-				let mut messages = Vec::with_capacity(end.saturating_sub(begin) as _);
-				for nonce in begin..=end {
-					let message_key = MessageKey { lane_id, nonce };
-					let storage_message_key = pallet_message_lane::storage_keys::message_key::<
+				read_messages_from_storage_proof::<B, _>(storage, lane_id, begin, end, |lane_id, nonce| {
+					pallet_message_lane::storage_keys::message_key::<
 						ThisRuntime,
 						MessageLaneInstanceOf<BridgedChain<B>>,
-					>(&lane_id, nonce);
-					let raw_message_data = storage
-						.read_value(storage_message_key.0.as_ref())
-						.map_err(|_| "Failed to read message from storage proof")?
-						.ok_or("Message is missing from the messages proof")?;
-					let message_data = MessageData::<BalanceOf<BridgedChain<B>>>::decode(&mut &raw_message_data[..])
-						.map_err(|_| "Failed to decode message from the proof")?;
-					messages.push(Message {
-						key: message_key,
-						data: message_data,
-					});
-				}
+					>(lane_id, nonce)
+					.0
+				})
+			},
+		)
+		.map_err(<&'static str>::from)?
+	}
 
-				// Now let's check if proof contains outbound lane state proof. It is optional, so we
-				// simply ignore `read_value` errors and missing value.
-				let mut proved_lane_messages = ProvedLaneMessages {
-					lane_state: None,
-					messages,
-				};
-				let storage_outbound_lane_data_key = pallet_message_lane::storage_keys::outbound_lane_data_key::<
-					MessageLaneInstanceOf<BridgedChain<B>>,
-				>(&lane_id);
-				let raw_outbound_lane_data = storage.read_value(storage_outbound_lane_data_key.0.as_ref());
-				if let Ok(Some(raw_outbound_lane_data)) = raw_outbound_lane_data {
-					proved_lane_messages.lane_state = Some(
-						OutboundLaneData::decode(&mut &raw_outbound_lane_data[..])
-							.map_err(|_| "Failed to decode outbound lane data from the proof")?,
-					);
-				}
+	/// Read messages and (optional) outbound lane state from the storage proof of the Bridged chain.
+	///
+	/// The `message_key` function returns storage key of the message with given nonce. The proof
+	/// is checked using the Bridged chain hasher, which isn't necessarily the same as our hasher.
+	pub fn read_messages_from_storage_proof<B: MessageBridge, H: Hash>(
+		storage: StorageProofChecker<H>,
+		lane_id: LaneId,
+		begin: MessageNonce,
+		end: MessageNonce,
+		message_key: impl Fn(&LaneId, MessageNonce) -> Vec<u8>,
+	) -> Result<ProvedMessages<Message<BalanceOf<BridgedChain<B>>>>, &'static str> {
+		// Read messages first. All messages that are claimed to be in the proof must
+		// be in the proof. So any error in `read_value`, or even missing value is fatal.
+		//
+		// Mind that we allow proofs with no messages if outbound lane state is proved.
+		let storage_version = decode_storage_version(
+			storage
+				.read_value(
+					pallet_message_lane::storage_keys::storage_version_key::<MessageLaneInstanceOf<BridgedChain<B>>>()
+						.0
+						.as_ref(),
+				)
+				.map_err(|_| "Failed to read message lane storage version from the proof")?,
+		)?;
+		let mut messages = Vec::with_capacity(
+			end.saturating_sub(begin)
+				.saturating_add(1)
+				.min(MAX_PREALLOCATED_PROVED_MESSAGES) as _,
+		);
+		for nonce in begin..=end {
+			let raw_message_data = storage
+				.read_value(&message_key(&lane_id, nonce))
+				.map_err(|_| "Failed to read message from storage proof")?
+				.ok_or("Message is missing from the messages proof")?;
+			let message_data =
+				MessageData::<BalanceOf<BridgedChain<B>>>::decode_versioned(storage_version, &raw_message_data)
+					.map_err(|_| "Failed to decode message from the proof")?;
+			messages.push(Message {
+				key: MessageKey { lane_id, nonce },
+				data: message_data,
+			});
+		}
 
-				// Now we may actually check if the proof is empty or not.
-				if proved_lane_messages.lane_state.is_none() && proved_lane_messages.messages.is_empty() {
-					return Err("Messages proof is empty");
-				}
+		// Now let's check if proof contains outbound lane state proof. It is optional, so we
+		// simply ignore `read_value` errors and missing value.
+		let mut proved_lane_messages = ProvedLaneMessages {
+			lane_state: None,
+			messages,
+		};
+		let storage_outbound_lane_data_key = pallet_message_lane::storage_keys::outbound_lane_data_key::<
+			MessageLaneInstanceOf<BridgedChain<B>>,
+		>(&lane_id);
+		let raw_outbound_lane_data = storage.read_value(storage_outbound_lane_data_key.0.as_ref());
+		if let Ok(Some(raw_outbound_lane_data)) = raw_outbound_lane_data {
+			proved_lane_messages.lane_state = Some(
+				OutboundLaneData::decode_versioned(storage_version, &raw_outbound_lane_data)
+					.map_err(|_| "Failed to decode outbound lane data from the proof")?,
+			);
+		}
 
-				// We only support single lane messages in this schema
-				let mut proved_messages = ProvedMessages::new();
-				proved_messages.insert(lane_id, proved_lane_messages);
+		// Now we may actually check if the proof is empty or not.
+		if proved_lane_messages.lane_state.is_none() && proved_lane_messages.messages.is_empty() {
+			return Err("Messages proof is empty");
+		}
 
-				Ok(proved_messages)
-			},
-		)
-		.map_err(<&'static str>::from)?
+		// We only support single lane messages in this schema
+		let mut proved_messages = ProvedMessages::new();
+		proved_messages.insert(lane_id, proved_lane_messages);
+
+		Ok(proved_messages)
 	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use bp_message_lane::{
+		source_chain::{MessageDeliveryAndDispatchPayment, TargetHeaderChain},
+		target_chain::SourceHeaderChain,
+	};
 	use codec::{Decode, Encode};
-	use frame_support::weights::Weight;
+	use frame_support::{impl_outer_origin, weights::Weight};
+	use sp_runtime::traits::{BlakeTwo256, Keccak256};
 
 	const DELIVERY_TRANSACTION_WEIGHT: Weight = 100;
 	const DELIVERY_CONFIRMATION_TRANSACTION_WEIGHT: Weight = 100;
@@ -452,6 +632,13 @@ mod tests {
 		fn this_balance_to_bridged_balance(this_balance: ThisChainBalance) -> BridgedChainBalance {
 			BridgedChainBalance(this_balance.0 * THIS_CHAIN_TO_BRIDGED_CHAIN_BALANCE_RATE as u32)
 		}
+
+		fn bridged_balance_to_this_balance(bridged_balance: BridgedChainBalance) -> ThisChainBalance {
+			ThisChainBalance(
+				(bridged_balance.0 + THIS_CHAIN_TO_BRIDGED_CHAIN_BALANCE_RATE - 1)
+					/ THIS_CHAIN_TO_BRIDGED_CHAIN_BALANCE_RATE,
+			)
+		}
 	}
 
 	/// Bridge that is deployed on BridgedChain and allows sending/receiving messages to/from ThisChain;
@@ -491,6 +678,10 @@ mod tests {
 		fn this_balance_to_bridged_balance(_this_balance: BridgedChainBalance) -> ThisChainBalance {
 			unreachable!()
 		}
+
+		fn bridged_balance_to_this_balance(_bridged_balance: ThisChainBalance) -> BridgedChainBalance {
+			unreachable!()
+		}
 	}
 
 	#[derive(Debug, PartialEq, Decode, Encode)]
@@ -615,7 +806,7 @@ mod tests {
 			spec_version: 1,
 			weight: 100,
 			origin: pallet_bridge_call_dispatch::CallOrigin::BridgeAccount,
-			call: ThisChainCall::Transfer.encode(),
+			call: pallet_bridge_call_dispatch::MessageCall::Single(ThisChainCall::Transfer.encode()),
 		}
 		.encode();
 
@@ -629,11 +820,33 @@ mod tests {
 				spec_version: 1,
 				weight: 100,
 				origin: pallet_bridge_call_dispatch::CallOrigin::BridgeAccount,
-				call: ThisChainCall::Transfer,
+				call: pallet_bridge_call_dispatch::MessageCall::Single(ThisChainCall::Transfer),
 			}
 		);
 	}
 
+	#[test]
+	fn batch_message_from_bridged_chain_is_decoded() {
+		let message_on_bridged_chain = source::FromThisChainMessagePayload::<OnBridgedChainBridge> {
+			spec_version: 1,
+			weight: 100,
+			origin: pallet_bridge_call_dispatch::CallOrigin::BridgeAccount,
+			call: pallet_bridge_call_dispatch::MessageCall::Batch(vec![
+				ThisChainCall::Transfer.encode(),
+				ThisChainCall::Transfer.encode(),
+			]),
+		}
+		.encode();
+
+		let message_on_this_chain =
+			target::FromBridgedChainMessagePayload::<OnThisChainBridge>::decode(&mut &message_on_bridged_chain[..])
+				.unwrap();
+		assert_eq!(
+			message_on_this_chain.0.call,
+			pallet_bridge_call_dispatch::MessageCall::Batch(vec![ThisChainCall::Transfer, ThisChainCall::Transfer]),
+		);
+	}
+
 	#[test]
 	fn message_fee_is_checked_by_verifier() {
 		const EXPECTED_MINIMAL_FEE: u32 = 2640;
@@ -643,7 +856,7 @@ mod tests {
 			spec_version: 1,
 			weight: 100,
 			origin: pallet_bridge_call_dispatch::CallOrigin::BridgeAccount,
-			call: vec![42],
+			call: pallet_bridge_call_dispatch::MessageCall::Single(vec![42]),
 		};
 
 		// let's check if estimation matching hardcoded value
@@ -660,7 +873,7 @@ mod tests {
 			source::FromThisChainMessageVerifier::<OnThisChainBridge>::verify_message(
 				&ThisChainAccountId(0),
 				&ThisChainBalance(1),
-				&*b"test",
+				&LaneId::from_bytes(*b"test"),
 				&payload,
 			)
 			.is_err(),
@@ -669,10 +882,349 @@ mod tests {
 			source::FromThisChainMessageVerifier::<OnThisChainBridge>::verify_message(
 				&ThisChainAccountId(0),
 				&ThisChainBalance(1_000_000),
-				&*b"test",
+				&LaneId::from_bytes(*b"test"),
 				&payload,
 			)
 			.is_ok(),
 		);
 	}
+
+	#[test]
+	fn message_fee_is_quoted_by_estimator() {
+		let payload = source::FromThisChainMessagePayload::<OnThisChainBridge> {
+			spec_version: 1,
+			weight: 100,
+			origin: pallet_bridge_call_dispatch::CallOrigin::BridgeAccount,
+			call: pallet_bridge_call_dispatch::MessageCall::Single(vec![42]),
+		};
+
+		let quote = source::FromThisChainMessageFeeEstimator::<OnThisChainBridge>::quote_message_fee(
+			&LaneId::from_bytes(*b"test"),
+			&payload,
+		)
+		.unwrap();
+		assert_eq!(quote.dispatch_weight_fee, BridgedChainBalance(400));
+		assert_eq!(quote.payload_size, payload.encoded_size() as u32);
+		assert_eq!(quote.relayer_fee_percent, OnThisChainBridge::RELAYER_FEE_PERCENT);
+		assert_eq!(
+			quote.total(),
+			source::estimate_message_dispatch_and_delivery_fee::<OnThisChainBridge>(
+				&payload,
+				OnThisChainBridge::RELAYER_FEE_PERCENT,
+			)
+			.ok(),
+		);
+
+		assert_eq!(
+			source::FromThisChainMessageFeeEstimator::<OnThisChainBridge>::estimate_message_fee(
+				&LaneId::from_bytes(*b"test"),
+				&payload
+			),
+			source::estimate_message_dispatch_and_delivery_fee_in_this_chain_tokens::<OnThisChainBridge>(
+				&payload,
+				OnThisChainBridge::RELAYER_FEE_PERCENT,
+			),
+		);
+	}
+
+	#[test]
+	fn message_fee_in_this_chain_tokens_is_accepted_by_verifier() {
+		// payload of the This -> Bridged chain message
+		let payload = source::FromThisChainMessagePayload::<OnThisChainBridge> {
+			spec_version: 1,
+			weight: 100,
+			origin: pallet_bridge_call_dispatch::CallOrigin::BridgeAccount,
+			call: pallet_bridge_call_dispatch::MessageCall::Single(vec![42]),
+		};
+
+		// 2640 Bridged chain tokens are converted to 440 This chain tokens
+		let estimated_fee =
+			source::estimate_message_dispatch_and_delivery_fee_in_this_chain_tokens::<OnThisChainBridge>(
+				&payload,
+				OnThisChainBridge::RELAYER_FEE_PERCENT,
+			)
+			.unwrap();
+		assert_eq!(estimated_fee, ThisChainBalance(440));
+
+		// the estimated fee is enough, but anything below is not
+		assert!(
+			source::FromThisChainMessageVerifier::<OnThisChainBridge>::verify_message(
+				&ThisChainAccountId(0),
+				&estimated_fee,
+				&LaneId::from_bytes(*b"test"),
+				&payload,
+			)
+			.is_ok()
+		);
+		assert!(
+			source::FromThisChainMessageVerifier::<OnThisChainBridge>::verify_message(
+				&ThisChainAccountId(0),
+				&ThisChainBalance(estimated_fee.0 - 1),
+				&LaneId::from_bytes(*b"test"),
+				&payload,
+			)
+			.is_err()
+		);
+	}
+
+	#[test]
+	fn storage_version_is_decoded_from_proof() {
+		assert_eq!(decode_storage_version(None), Ok(LEGACY_STORAGE_VERSION));
+		assert_eq!(
+			decode_storage_version(Some(bp_message_lane::CURRENT_STORAGE_VERSION.encode())),
+			Ok(bp_message_lane::CURRENT_STORAGE_VERSION),
+		);
+		assert!(decode_storage_version(Some(vec![42])).is_err());
+	}
+
+	#[test]
+	fn proof_size_is_storage_proof_size() {
+		let (_, storage_proof) = bp_runtime::craft_valid_storage_proof();
+		let expected_size = bp_runtime::storage_proof_size(&storage_proof).bytes;
+
+		let messages_proof = target::FromBridgedChainMessagesProof {
+			bridged_header_hash: (),
+			storage_proof: storage_proof.clone(),
+			lane: LaneId::default(),
+			nonces_start: 1,
+			nonces_end: 1,
+		};
+		assert_eq!(messages_proof.size_hint(), expected_size);
+		assert!(messages_proof.size_hint() as usize <= messages_proof.encoded_size());
+
+		let delivery_proof = source::FromBridgedChainMessagesDeliveryProof {
+			bridged_header_hash: (),
+			storage_proof,
+			lane: LaneId::default(),
+		};
+		assert_eq!(delivery_proof.size_hint(), expected_size);
+		assert!(delivery_proof.size_hint() as usize <= delivery_proof.encoded_size());
+	}
+
+	/// Runtime that is only used to compute storage keys of the message lane pallet.
+	#[derive(Clone, Eq, PartialEq, Debug)]
+	struct TestRuntime;
+
+	impl_outer_origin! {
+		pub enum Origin for TestRuntime where system = frame_system {}
+	}
+
+	bp_test_utils::impl_test_frame_system! {
+		runtime: TestRuntime,
+		origin: Origin,
+		call: (),
+		account_id: u64,
+		event: (),
+		account_data: (),
+	}
+
+	impl pallet_message_lane::Trait for TestRuntime {
+		type Event = ();
+		type AdminOrigin = frame_system::EnsureRoot<u64>;
+		type MaxMessagesToPruneAtOnce = ();
+		type MaxUnconfirmedMessagesAtInboundLane = ();
+		type MaxMessagesProofSize = ();
+		type MaxMessagesDeliveryProofSize = ();
+		type WeightInfo = ();
+
+		type OutboundPayload = Vec<u8>;
+		type OutboundMessageFee = u64;
+
+		type InboundPayload = Vec<u8>;
+		type InboundMessageFee = u64;
+		type InboundRelayer = u64;
+
+		type TargetHeaderChain = UnusedMessageLaneComponent;
+		type LaneMessageVerifier = UnusedMessageLaneComponent;
+		type MessageDeliveryAndDispatchPayment = UnusedMessageLaneComponent;
+
+		type SourceHeaderChain = UnusedMessageLaneComponent;
+		type MessageDispatch = UnusedMessageLaneComponent;
+	}
+
+	/// Message lane pallet component that must never be called by tests.
+	struct UnusedMessageLaneComponent;
+
+	impl TargetHeaderChain<Vec<u8>, u64> for UnusedMessageLaneComponent {
+		type Error = &'static str;
+		type MessagesDeliveryProof = StorageProof;
+
+		fn verify_message(_payload: &Vec<u8>) -> Result<(), Self::Error> {
+			unreachable!()
+		}
+
+		fn verify_messages_delivery_proof(_proof: StorageProof) -> Result<(LaneId, InboundLaneData<u64>), Self::Error> {
+			unreachable!()
+		}
+	}
+
+	impl LaneMessageVerifier<u64, Vec<u8>, u64> for UnusedMessageLaneComponent {
+		type Error = &'static str;
+
+		fn verify_message(
+			_submitter: &u64,
+			_delivery_and_dispatch_fee: &u64,
+			_lane: &LaneId,
+			_payload: &Vec<u8>,
+		) -> Result<(), Self::Error> {
+			unreachable!()
+		}
+	}
+
+	impl MessageDeliveryAndDispatchPayment<u64, u64> for UnusedMessageLaneComponent {
+		type Error = &'static str;
+
+		fn pay_delivery_and_dispatch_fee(_submitter: &u64, _fee: &u64) -> Result<(), Self::Error> {
+			unreachable!()
+		}
+
+		fn pay_relayer_reward(_confirmation_relayer: &u64, _relayer: &u64, _reward: &u64) {
+			unreachable!()
+		}
+	}
+
+	impl SourceHeaderChain<u64> for UnusedMessageLaneComponent {
+		type Error = &'static str;
+		type MessagesProof = StorageProof;
+
+		fn verify_messages_proof(_proof: StorageProof) -> Result<ProvedMessages<Message<u64>>, Self::Error> {
+			unreachable!()
+		}
+	}
+
+	impl MessageDispatch<u64> for UnusedMessageLaneComponent {
+		type DispatchPayload = Vec<u8>;
+
+		fn dispatch_weight(_message: &DispatchMessage<Vec<u8>, u64>) -> Weight {
+			unreachable!()
+		}
+
+		fn dispatch(_message: DispatchMessage<Vec<u8>, u64>) {
+			unreachable!()
+		}
+	}
+
+	fn test_message_key(lane_id: &LaneId, nonce: MessageNonce) -> Vec<u8> {
+		pallet_message_lane::storage_keys::message_key::<TestRuntime, pallet_message_lane::DefaultInstance>(
+			lane_id, nonce,
+		)
+		.0
+	}
+
+	fn test_message_data(nonce: MessageNonce) -> MessageData<BridgedChainBalance> {
+		MessageData {
+			payload: vec![nonce as u8],
+			fee: BridgedChainBalance(nonce as u32),
+		}
+	}
+
+	fn keccak256_storage_proof(
+		nonces: impl IntoIterator<Item = MessageNonce>,
+		outbound_lane_data: Option<OutboundLaneData>,
+	) -> (<Keccak256 as Hash>::Output, StorageProof) {
+		let lane_id = LaneId::default();
+		let mut entries = vec![(
+			pallet_message_lane::storage_keys::storage_version_key::<pallet_message_lane::DefaultInstance>().0,
+			bp_message_lane::CURRENT_STORAGE_VERSION.encode(),
+		)];
+		entries.extend(
+			nonces
+				.into_iter()
+				.map(|nonce| (test_message_key(&lane_id, nonce), test_message_data(nonce).encode())),
+		);
+		if let Some(outbound_lane_data) = outbound_lane_data {
+			entries.push((
+				pallet_message_lane::storage_keys::outbound_lane_data_key::<pallet_message_lane::DefaultInstance>(
+					&lane_id,
+				)
+				.0,
+				outbound_lane_data.encode(),
+			));
+		}
+
+		bp_runtime::craft_storage_proof::<Keccak256>(entries)
+	}
+
+	fn read_messages_from_keccak256_storage_proof(
+		(root, proof): (<Keccak256 as Hash>::Output, StorageProof),
+		begin: MessageNonce,
+		end: MessageNonce,
+	) -> Result<ProvedMessages<Message<BridgedChainBalance>>, &'static str> {
+		let storage = StorageProofChecker::<Keccak256>::new(root, proof).unwrap();
+		target::read_messages_from_storage_proof::<OnThisChainBridge, _>(
+			storage,
+			LaneId::default(),
+			begin,
+			end,
+			test_message_key,
+		)
+	}
+
+	#[test]
+	fn messages_are_read_from_keccak256_storage_proof() {
+		let lane_id = LaneId::default();
+		let outbound_lane_data = OutboundLaneData {
+			oldest_unpruned_nonce: 1,
+			latest_received_nonce: 0,
+			latest_generated_nonce: 2,
+		};
+
+		assert_eq!(
+			read_messages_from_keccak256_storage_proof(
+				keccak256_storage_proof(1..=2, Some(outbound_lane_data.clone())),
+				1,
+				2,
+			),
+			Ok(vec![(
+				lane_id,
+				ProvedLaneMessages {
+					lane_state: Some(outbound_lane_data),
+					messages: (1..=2)
+						.map(|nonce| Message {
+							key: MessageKey { lane_id, nonce },
+							data: test_message_data(nonce),
+						})
+						.collect(),
+				},
+			)]
+			.into_iter()
+			.collect()),
+		);
+	}
+
+	#[test]
+	fn messages_read_from_keccak256_storage_proof_may_be_empty_if_lane_state_is_proved() {
+		let proved_messages = read_messages_from_keccak256_storage_proof(
+			keccak256_storage_proof(None, Some(OutboundLaneData::default())),
+			1,
+			0,
+		)
+		.unwrap();
+		assert_eq!(
+			proved_messages.get(&LaneId::default()).unwrap().lane_state,
+			Some(OutboundLaneData::default()),
+		);
+	}
+
+	#[test]
+	fn message_missing_from_keccak256_storage_proof_is_rejected() {
+		assert_eq!(
+			read_messages_from_keccak256_storage_proof(keccak256_storage_proof(vec![1], None), 1, 2),
+			Err("Message is missing from the messages proof"),
+		);
+	}
+
+	#[test]
+	fn empty_keccak256_storage_proof_is_rejected() {
+		assert_eq!(
+			read_messages_from_keccak256_storage_proof(keccak256_storage_proof(None, None), 1, 0),
+			Err("Messages proof is empty"),
+		);
+	}
+
+	#[test]
+	fn keccak256_storage_proof_is_not_accepted_by_blake2_checker() {
+		let (root, proof) = keccak256_storage_proof(vec![1], None);
+		assert!(StorageProofChecker::<BlakeTwo256>::new(root, proof).is_err());
+	}
 }
@@ -0,0 +1,197 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Generator of bridge pallets weights.
+//!
+//! Benchmarks of all bridge pallets of the runtime are executed using the reference profile
+//! and their results are converted into weight formulas, which are written to the `weights.rs`
+//! file of the runtime. In the check mode, the file is not changed. Instead, weights from the
+//! file are compared with measured weights and the command fails if they have diverged.
+//!
+//! All committed weights must be generated on the reference machine - the same machine, that
+//! is used by the CI to check weights.
+
+#![warn(missing_docs)]
+
+use codec::{Decode, Encode};
+use frame_benchmarking::{BenchmarkBatch, BenchmarkConfig};
+use sc_cli::{CliConfiguration, SharedParams};
+use sc_client_db::BenchmarkingState;
+use sc_executor::{NativeExecutionDispatch, NativeExecutor, WasmExecutionMethod};
+use sc_service::Configuration;
+use sp_runtime::{
+	traits::{Block as BlockT, NumberFor},
+	BuildStorage,
+};
+use sp_state_machine::{backend::BackendRuntimeCode, ExecutionStrategy, StateMachine};
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+pub use weights::{compare, parse, render, BenchmarkWeight, Formula, PalletWeights};
+
+mod weights;
+
+/// Parameters of benchmarks that are used to generate weights.
+#[derive(Debug, Clone, Copy)]
+pub struct ReferenceProfile {
+	/// Number of steps between lowest and highest values of every benchmark component.
+	pub steps: u32,
+	/// Number of times every step is repeated.
+	pub repeat: u32,
+	/// Size of the database cache (in MiB).
+	pub database_cache_size: usize,
+}
+
+/// Profile that is used to generate all committed weights.
+pub const REFERENCE_PROFILE: ReferenceProfile = ReferenceProfile {
+	steps: 50,
+	repeat: 20,
+	database_cache_size: 128,
+};
+
+/// The `benchmark-weights` subcommand.
+#[derive(Debug, StructOpt)]
+pub struct WeightsCmd {
+	/// Path to the weights file of the runtime.
+	#[structopt(long)]
+	pub output: PathBuf,
+	/// Do not change the weights file. Instead, fail if weights from the file have diverged from
+	/// measured weights.
+	#[structopt(long)]
+	pub check: bool,
+	/// Maximal divergence (in percents) of committed weights from measured weights.
+	#[structopt(long, default_value = "10")]
+	pub tolerance: u32,
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl WeightsCmd {
+	/// Benchmark given pallets of the runtime and generate (or check) the weights file.
+	pub fn run<Block, ExecDispatch>(&self, config: Configuration, pallets: &[&str]) -> sc_cli::Result<()>
+	where
+		Block: BlockT,
+		ExecDispatch: NativeExecutionDispatch + 'static,
+	{
+		if pallets.is_empty() {
+			println!("Runtime has no benchmarked bridge pallets. Nothing to do");
+			return Ok(());
+		}
+
+		let measured = pallets
+			.iter()
+			.map(|pallet| {
+				println!("Benchmarking {}...", pallet);
+				let batches = run_pallet_benchmarks::<Block, ExecDispatch>(&config, pallet)?;
+				PalletWeights::from_batches(pallet.to_string(), batches).map_err(sc_cli::Error::Input)
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+
+		if !self.check {
+			let header = format!(
+				"Weights of bridge pallets, measured at the `{}` chain.\n\n\
+				THIS FILE IS GENERATED BY THE `benchmark-weights` SUBCOMMAND OF THE NODE. DO NOT EDIT IT MANUALLY.\n\
+				Reference profile: {:?}.",
+				config.chain_spec.id(),
+				REFERENCE_PROFILE,
+			);
+			std::fs::write(&self.output, render(&header, &measured))?;
+			println!("Weights have been written to {}", self.output.display());
+			return Ok(());
+		}
+
+		let committed = std::fs::read_to_string(&self.output)
+			.map_err(|e| sc_cli::Error::Input(format!("Failed to read {}: {}", self.output.display(), e)))?;
+		let committed = parse(&committed).map_err(sc_cli::Error::Input)?;
+		let divergences = compare(&committed, &measured, self.tolerance);
+		if !divergences.is_empty() {
+			return Err(sc_cli::Error::Input(format!(
+				"Weights in {} have diverged from measured weights by more than {}%:\n{}",
+				self.output.display(),
+				self.tolerance,
+				divergences.join("\n"),
+			)));
+		}
+
+		println!(
+			"Weights in {} are within {}% of measured weights",
+			self.output.display(),
+			self.tolerance,
+		);
+		Ok(())
+	}
+}
+
+impl CliConfiguration for WeightsCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn chain_id(&self, _is_dev: bool) -> sc_cli::Result<String> {
+		Ok(self.shared_params.chain.clone().unwrap_or_else(|| "dev".into()))
+	}
+}
+
+/// Run all benchmarks of the pallet, using native runtime and genesis state of the chain.
+fn run_pallet_benchmarks<Block, ExecDispatch>(
+	config: &Configuration,
+	pallet: &str,
+) -> sc_cli::Result<Vec<BenchmarkBatch>>
+where
+	Block: BlockT,
+	ExecDispatch: NativeExecutionDispatch + 'static,
+{
+	let genesis_storage = config.chain_spec.build_storage().map_err(sc_cli::Error::Input)?;
+	let state = BenchmarkingState::<Block>::new(genesis_storage, Some(REFERENCE_PROFILE.database_cache_size))
+		.map_err(|e| sc_cli::Error::Input(format!("Failed to create benchmarking state: {:?}", e)))?;
+	let executor = NativeExecutor::<ExecDispatch>::new(WasmExecutionMethod::Interpreted, None, 2);
+	let runtime_code = BackendRuntimeCode::new(&state)
+		.runtime_code()
+		.map_err(|e| sc_cli::Error::Input(e.into()))?;
+	let benchmark_config = BenchmarkConfig {
+		pallet: pallet.as_bytes().to_vec(),
+		benchmark: b"*".to_vec(),
+		lowest_range_values: Vec::new(),
+		highest_range_values: Vec::new(),
+		steps: vec![REFERENCE_PROFILE.steps],
+		repeat: REFERENCE_PROFILE.repeat,
+		verify: false,
+		extra: false,
+	}
+	.encode();
+
+	let mut changes = Default::default();
+	let mut offchain_changes = Default::default();
+	let result = StateMachine::<_, _, NumberFor<Block>, _>::new(
+		&state,
+		None,
+		&mut changes,
+		&mut offchain_changes,
+		&executor,
+		"Benchmark_dispatch_benchmark",
+		&benchmark_config,
+		Default::default(),
+		&runtime_code,
+		sp_core::testing::TaskExecutor::new(),
+	)
+	.execute(ExecutionStrategy::NativeWhenPossible)
+	.map_err(|e| sc_cli::Error::Input(format!("Failed to run {} benchmarks: {:?}", pallet, e)))?;
+
+	Result::<Vec<BenchmarkBatch>, String>::decode(&mut &result[..])
+		.map_err(|e| sc_cli::Error::Input(format!("Failed to decode {} benchmarks results: {:?}", pallet, e)))?
+		.map_err(|e| sc_cli::Error::Input(format!("Failed to run {} benchmarks: {}", pallet, e)))
+}
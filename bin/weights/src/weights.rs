@@ -0,0 +1,478 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Weight formulas of benchmarked calls and the weights file, where they are stored.
+//!
+//! The weights file is the Rust module with single submodule for every benchmarked pallet. Every
+//! submodule has a function for every benchmark of the pallet, which computes weight of the
+//! benchmarked call. The file is generated by this crate and is never edited manually, so it is
+//! also parsed back when committed weights are checked against measured weights.
+
+use frame_benchmarking::{Analysis, BenchmarkBatch, BenchmarkResults, BenchmarkSelector};
+use std::collections::BTreeSet;
+
+/// Weight of single nanosecond of execution time.
+const WEIGHT_PER_NANOS: u128 = 1_000;
+
+/// Linear formula: `base + sum(slope * component)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Formula {
+	/// Value of the formula when all components are zero.
+	pub base: u128,
+	/// Names and slopes of all components with non-zero slopes.
+	pub slopes: Vec<(String, u128)>,
+}
+
+impl Formula {
+	/// Return slope of given component.
+	pub fn slope(&self, component: &str) -> u128 {
+		self.slopes
+			.iter()
+			.find(|(name, _)| name == component)
+			.map(|(_, slope)| *slope)
+			.unwrap_or(0)
+	}
+
+	/// Build formula from the benchmark results analysis.
+	fn from_analysis(analysis: Analysis, multiplier: u128) -> Self {
+		Formula {
+			base: analysis.base.saturating_mul(multiplier),
+			slopes: analysis
+				.names
+				.into_iter()
+				.zip(analysis.slopes)
+				.filter(|(_, slope)| *slope != 0)
+				.map(|(name, slope)| (name, slope.saturating_mul(multiplier)))
+				.collect(),
+		}
+	}
+}
+
+/// Weight of the benchmarked call.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BenchmarkWeight {
+	/// Name of the benchmark.
+	pub name: String,
+	/// Names of all benchmark components.
+	pub components: Vec<String>,
+	/// Weight of the call execution.
+	pub time: Formula,
+	/// Number of database reads.
+	pub reads: Formula,
+	/// Number of database writes.
+	pub writes: Formula,
+}
+
+impl BenchmarkWeight {
+	/// Analyze benchmark results and build weight formula from it.
+	pub fn from_results(name: String, results: &[BenchmarkResults]) -> Result<Self, String> {
+		let analyze = |selector| {
+			Analysis::min_squares_iqr(results, selector)
+				.ok_or_else(|| format!("Failed to analyze results of the {} benchmark", name))
+		};
+
+		let time = analyze(BenchmarkSelector::ExtrinsicTime)?;
+		Ok(BenchmarkWeight {
+			name: name.clone(),
+			components: time.names.clone(),
+			time: Formula::from_analysis(time, WEIGHT_PER_NANOS),
+			reads: Formula::from_analysis(analyze(BenchmarkSelector::Reads)?, 1),
+			writes: Formula::from_analysis(analyze(BenchmarkSelector::Writes)?, 1),
+		})
+	}
+}
+
+/// Weights of all benchmarked calls of the pallet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PalletWeights {
+	/// Name of the pallet.
+	pub pallet: String,
+	/// Weights of all pallet benchmarks.
+	pub benchmarks: Vec<BenchmarkWeight>,
+}
+
+impl PalletWeights {
+	/// Analyze benchmark results of the pallet and build weight formulas from it.
+	pub fn from_batches(pallet: String, batches: Vec<BenchmarkBatch>) -> Result<Self, String> {
+		Ok(PalletWeights {
+			pallet,
+			benchmarks: batches
+				.into_iter()
+				.map(|batch| {
+					BenchmarkWeight::from_results(String::from_utf8_lossy(&batch.benchmark).into(), &batch.results)
+				})
+				.collect::<Result<_, _>>()?,
+		})
+	}
+}
+
+/// Render weights file.
+pub fn render(header: &str, weights: &[PalletWeights]) -> String {
+	let mut file = String::new();
+	for line in header.lines() {
+		file.push_str(format!("//! {}", line).trim_end());
+		file.push('\n');
+	}
+	file.push_str("\n#![allow(clippy::unnecessary_cast)]\n\n");
+	file.push_str("use frame_support::weights::{constants::RocksDbWeight as DbWeight, Weight};\n");
+
+	for pallet in weights {
+		file.push_str(&format!(
+			"\n/// Weights of the `{0}` pallet calls.\npub mod {0} {{\n\tuse super::*;\n",
+			pallet.pallet,
+		));
+		for benchmark in &pallet.benchmarks {
+			let is_used = |component: &String| {
+				[&benchmark.time, &benchmark.reads, &benchmark.writes]
+					.iter()
+					.any(|formula| formula.slope(component) != 0)
+			};
+			let arguments = benchmark
+				.components
+				.iter()
+				.map(|component| {
+					let prefix = if is_used(component) { "" } else { "_" };
+					format!("{}{}: u32", prefix, component)
+				})
+				.collect::<Vec<_>>()
+				.join(", ");
+
+			file.push_str(&format!(
+				"\n\t/// Weight of the `{0}` benchmark.\n\tpub fn {0}({1}) -> Weight {{\n\t\t({2} as Weight)\n",
+				benchmark.name,
+				arguments,
+				render_number(benchmark.time.base),
+			));
+			for (component, slope) in &benchmark.time.slopes {
+				file.push_str(&format!(
+					"\t\t\t.saturating_add(({} as Weight).saturating_mul({} as Weight))\n",
+					render_number(*slope),
+					component,
+				));
+			}
+			for (operation, formula) in &[("reads", &benchmark.reads), ("writes", &benchmark.writes)] {
+				if formula.base != 0 {
+					file.push_str(&format!(
+						"\t\t\t.saturating_add(DbWeight::get().{}({} as Weight))\n",
+						operation,
+						render_number(formula.base),
+					));
+				}
+				for (component, slope) in &formula.slopes {
+					file.push_str(&format!(
+						"\t\t\t.saturating_add(DbWeight::get().{}(({} as Weight).saturating_mul({} as Weight)))\n",
+						operation,
+						render_number(*slope),
+						component,
+					));
+				}
+			}
+			file.push_str("\t}\n");
+		}
+		file.push_str("}\n");
+	}
+
+	file
+}
+
+/// Parse weights file, generated by the `render` function.
+pub fn parse(file: &str) -> Result<Vec<PalletWeights>, String> {
+	let mut weights: Vec<PalletWeights> = Vec::new();
+	for (index, line) in file.lines().enumerate() {
+		let invalid_line = || format!("Unexpected line {} of the weights file: {}", index + 1, line);
+		let line = line.trim();
+
+		if let Some(pallet) = line.strip_prefix("pub mod ").and_then(|rest| rest.strip_suffix(" {")) {
+			weights.push(PalletWeights {
+				pallet: pallet.into(),
+				benchmarks: Vec::new(),
+			});
+			continue;
+		}
+
+		let pallet = match weights.last_mut() {
+			Some(pallet) => pallet,
+			None => continue,
+		};
+		if let Some(signature) = line
+			.strip_prefix("pub fn ")
+			.and_then(|rest| rest.strip_suffix(") -> Weight {"))
+		{
+			let mut signature = signature.splitn(2, '(');
+			let name = signature.next().ok_or_else(invalid_line)?;
+			let components = signature
+				.next()
+				.ok_or_else(invalid_line)?
+				.split(',')
+				.map(|argument| argument.trim())
+				.filter(|argument| !argument.is_empty())
+				.map(|argument| {
+					argument
+						.strip_suffix(": u32")
+						.map(|component| component.trim_start_matches('_').to_string())
+						.ok_or_else(invalid_line)
+				})
+				.collect::<Result<_, _>>()?;
+			pallet.benchmarks.push(BenchmarkWeight {
+				name: name.into(),
+				components,
+				..Default::default()
+			});
+			continue;
+		}
+
+		let benchmark = match pallet.benchmarks.last_mut() {
+			Some(benchmark) => benchmark,
+			None => continue,
+		};
+		let (formula, term) = if let Some(term) = line.strip_prefix(".saturating_add(DbWeight::get().reads(") {
+			(&mut benchmark.reads, term.strip_suffix("))"))
+		} else if let Some(term) = line.strip_prefix(".saturating_add(DbWeight::get().writes(") {
+			(&mut benchmark.writes, term.strip_suffix("))"))
+		} else if let Some(term) = line.strip_prefix(".saturating_add(") {
+			(&mut benchmark.time, term.strip_suffix(")"))
+		} else if line.starts_with('(') {
+			(&mut benchmark.time, Some(line))
+		} else {
+			continue;
+		};
+
+		match parse_term(term.ok_or_else(invalid_line)?).ok_or_else(invalid_line)? {
+			(value, Some(component)) => formula.slopes.push((component, value)),
+			(value, None) => formula.base = value,
+		}
+	}
+
+	Ok(weights)
+}
+
+/// Compare committed weights with measured weights. Returns descriptions of all divergences.
+///
+/// Weights of call execution may diverge by at most `tolerance` percents. Numbers of database
+/// reads and writes are deterministic and must match exactly.
+pub fn compare(committed: &[PalletWeights], measured: &[PalletWeights], tolerance: u32) -> Vec<String> {
+	let mut divergences = Vec::new();
+	let find_benchmark = |weights: &[PalletWeights], pallet: &str, benchmark: &str| {
+		weights
+			.iter()
+			.filter(|weights| weights.pallet == pallet)
+			.flat_map(|weights| weights.benchmarks.iter())
+			.find(|weights| weights.name == benchmark)
+			.cloned()
+	};
+
+	for pallet in committed {
+		for benchmark in &pallet.benchmarks {
+			if find_benchmark(measured, &pallet.pallet, &benchmark.name).is_none() {
+				divergences.push(format!(
+					"{}::{}: benchmark has been removed",
+					pallet.pallet, benchmark.name
+				));
+			}
+		}
+	}
+
+	for pallet in measured {
+		for measured in &pallet.benchmarks {
+			let committed = match find_benchmark(committed, &pallet.pallet, &measured.name) {
+				Some(committed) => committed,
+				None => {
+					divergences.push(format!("{}::{}: weight is missing", pallet.pallet, measured.name));
+					continue;
+				}
+			};
+
+			for (what, committed_formula, measured_formula, tolerance) in &[
+				("weight", &committed.time, &measured.time, tolerance),
+				("reads", &committed.reads, &measured.reads, 0),
+				("writes", &committed.writes, &measured.writes, 0),
+			] {
+				let components = committed_formula
+					.slopes
+					.iter()
+					.chain(measured_formula.slopes.iter())
+					.map(|(component, _)| Some(component.as_str()))
+					.collect::<BTreeSet<_>>();
+				for component in std::iter::once(None).chain(components) {
+					let (committed_value, measured_value) = match component {
+						Some(component) => (committed_formula.slope(component), measured_formula.slope(component)),
+						None => (committed_formula.base, measured_formula.base),
+					};
+					let (min_value, max_value) =
+						(committed_value.min(measured_value), committed_value.max(measured_value));
+					if (max_value - min_value).saturating_mul(100) > max_value.saturating_mul(*tolerance as u128) {
+						divergences.push(format!(
+							"{}::{}: {} {} is {} (committed: {})",
+							pallet.pallet,
+							measured.name,
+							what,
+							component
+								.map(|c| format!("per `{}`", c))
+								.unwrap_or_else(|| "base".into()),
+							measured_value,
+							committed_value,
+						));
+					}
+				}
+			}
+		}
+	}
+
+	divergences
+}
+
+/// Render number, separating every three digits with underscore.
+fn render_number(number: u128) -> String {
+	let digits = number.to_string();
+	let mut rendered = String::with_capacity(digits.len() + digits.len() / 3);
+	for (index, digit) in digits.chars().enumerate() {
+		if index != 0 && (digits.len() - index) % 3 == 0 {
+			rendered.push('_');
+		}
+		rendered.push(digit);
+	}
+	rendered
+}
+
+/// Parse `N as Weight`, `(N as Weight)` or `(N as Weight).saturating_mul(C as Weight)` term.
+fn parse_term(term: &str) -> Option<(u128, Option<String>)> {
+	let (value, rest) = match term.strip_prefix('(') {
+		Some(term) => {
+			let mut parts = term.splitn(2, " as Weight)");
+			(parts.next()?, parts.next()?)
+		}
+		None => (term.strip_suffix(" as Weight")?, ""),
+	};
+	let value = value.replace('_', "").parse().ok()?;
+	match rest {
+		"" => Some((value, None)),
+		rest => rest
+			.strip_prefix(".saturating_mul(")
+			.and_then(|rest| rest.strip_suffix(" as Weight)"))
+			.map(|component| (value, Some(component.into()))),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn formula(base: u128, slopes: &[(&str, u128)]) -> Formula {
+		Formula {
+			base,
+			slopes: slopes.iter().map(|(name, slope)| (name.to_string(), *slope)).collect(),
+		}
+	}
+
+	fn test_weights() -> Vec<PalletWeights> {
+		vec![
+			PalletWeights {
+				pallet: "pallet_first".into(),
+				benchmarks: vec![
+					BenchmarkWeight {
+						name: "call_with_components".into(),
+						components: vec!["n".into(), "s".into(), "u".into()],
+						time: formula(120_500_000, &[("n", 1_000), ("s", 2_000_000)]),
+						reads: formula(3, &[("n", 1)]),
+						writes: formula(0, &[("u", 2)]),
+					},
+					BenchmarkWeight {
+						name: "call_without_components".into(),
+						components: vec![],
+						time: formula(42_000, &[]),
+						reads: formula(1, &[]),
+						writes: formula(1, &[]),
+					},
+				],
+			},
+			PalletWeights {
+				pallet: "pallet_second".into(),
+				benchmarks: vec![BenchmarkWeight {
+					name: "call".into(),
+					components: vec!["n".into()],
+					time: formula(999, &[]),
+					reads: formula(0, &[]),
+					writes: formula(0, &[]),
+				}],
+			},
+		]
+	}
+
+	#[test]
+	fn numbers_are_rendered_with_separators() {
+		assert_eq!(render_number(0), "0");
+		assert_eq!(render_number(999), "999");
+		assert_eq!(render_number(1_000), "1_000");
+		assert_eq!(render_number(120_500_000), "120_500_000");
+	}
+
+	#[test]
+	fn weights_file_is_rendered() {
+		let file = render("Weights of test pallets.", &test_weights());
+		assert!(file.starts_with("//! Weights of test pallets.\n"));
+		assert!(file.contains(
+			"\tpub fn call_with_components(n: u32, s: u32, u: u32) -> Weight {\n\
+			\t\t(120_500_000 as Weight)\n\
+			\t\t\t.saturating_add((1_000 as Weight).saturating_mul(n as Weight))\n\
+			\t\t\t.saturating_add((2_000_000 as Weight).saturating_mul(s as Weight))\n\
+			\t\t\t.saturating_add(DbWeight::get().reads(3 as Weight))\n\
+			\t\t\t.saturating_add(DbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))\n\
+			\t\t\t.saturating_add(DbWeight::get().writes((2 as Weight).saturating_mul(u as Weight)))\n\
+			\t}\n"
+		));
+		assert!(file.contains("\tpub fn call(_n: u32) -> Weight {\n\t\t(999 as Weight)\n\t}\n"));
+	}
+
+	#[test]
+	fn rendered_weights_file_is_parsed() {
+		let weights = test_weights();
+		assert_eq!(parse(&render("Weights of test pallets.", &weights)), Ok(weights));
+	}
+
+	#[test]
+	fn invalid_weights_file_is_not_parsed() {
+		let mut file = render("Weights of test pallets.", &test_weights());
+		file = file.replace("(999 as Weight)", "(999 as u64)");
+		assert!(parse(&file).is_err());
+	}
+
+	#[test]
+	fn weights_within_tolerance_are_accepted() {
+		let committed = test_weights();
+		let mut measured = test_weights();
+		measured[0].benchmarks[0].time = formula(130_000_000, &[("n", 950), ("s", 2_100_000)]);
+		assert_eq!(compare(&committed, &measured, 10), Vec::<String>::new());
+	}
+
+	#[test]
+	fn diverged_weights_are_reported() {
+		let committed = test_weights();
+		let mut measured = test_weights();
+		measured[0].benchmarks[0].time = formula(240_000_000, &[("n", 1_000), ("s", 2_000_000), ("u", 1_000)]);
+		measured[0].benchmarks[1].reads = formula(2, &[]);
+		measured[1].benchmarks[0].name = "renamed_call".into();
+		assert_eq!(
+			compare(&committed, &measured, 10),
+			vec![
+				"pallet_second::call: benchmark has been removed".to_string(),
+				"pallet_first::call_with_components: weight base is 240000000 (committed: 120500000)".to_string(),
+				"pallet_first::call_with_components: weight per `u` is 1000 (committed: 0)".to_string(),
+				"pallet_first::call_without_components: reads base is 2 (committed: 1)".to_string(),
+				"pallet_second::renamed_call: weight is missing".to_string(),
+			],
+		);
+	}
+}
@@ -0,0 +1,481 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fuzzing of the messages proofs and GRANDPA justifications verification.
+//!
+//! Proofs are verified by the Rialto runtime, which is using the trie-based verification of Millau
+//! storage proofs. Every fuzzer input is either treated as an encoded proof (which is decoded and
+//! verified as is), or as a set of structural mutations that are applied to the valid proof. In
+//! both cases the verification must not panic and must never accept more messages (relayers) than
+//! the proof actually contains.
+//!
+//! Justifications are fuzzed the same way: the input is either decoded as a justification, or
+//! mutates the valid justification. The verification must not panic and must never accept the
+//! justification with more precommits than the authority set may produce.
+
+use bp_header_chain::{
+	justification::GrandpaJustification,
+	test_utils::{
+		authority_list, header_id, make_justification_for_header, test_header, TestHeader, TEST_GRANDPA_ROUND,
+		TEST_GRANDPA_SET_ID,
+	},
+	AuthoritySet,
+};
+use bp_message_lane::{
+	source_chain::TargetHeaderChain,
+	target_chain::{ProvedMessagesAccounting, SourceHeaderChain},
+	InboundLaneData, LaneId, MessageData, MessageNonce, OutboundLaneData, CURRENT_STORAGE_VERSION,
+};
+use codec::{Decode, Encode};
+use pallet_message_lane::storage_keys;
+use pallet_substrate_bridge::InitializationData;
+use rialto_runtime::{
+	millau_messages::{FromMillauMessagesProof, Millau, ToMillauMessagePayload, ToMillauMessagesDeliveryProof},
+	Origin, Runtime,
+};
+use sp_runtime::traits::Header as HeaderT;
+use sp_state_machine::{backend::Backend, prove_read, InMemoryBackend};
+use sp_trie::StorageProof;
+
+/// Lane that is used by fuzzing targets.
+pub const FUZZ_LANE: LaneId = LaneId::from_bytes([0, 0, 0, 1]);
+/// Maximal number of messages (or relayers) in the valid proof that is mutated by the fuzzer.
+pub const MAX_ENTRIES_IN_VALID_PROOF: u8 = 16;
+
+/// Instance of the message lane pallet that is deployed at Millau to bridge with Rialto.
+type MillauMessageLaneInstance = pallet_message_lane::DefaultInstance;
+/// Instance of the substrate bridge pallet that is deployed at Rialto to bridge with Millau.
+type MillauBridgeInstance = pallet_substrate_bridge::DefaultInstance;
+
+/// Verify messages proof, built from the fuzzer input.
+///
+/// The first byte of the input selects the mode. If it is even, the rest of the input is decoded
+/// as the messages proof. Otherwise the valid messages proof is mutated. The mutation is encoded
+/// as: `[messages count, flags, nonce argument, proof nodes mutations...]`.
+pub fn fuzz_messages_proof(data: &[u8]) {
+	let (mode, data) = match data.split_first() {
+		Some((mode, data)) => (*mode, data),
+		None => return,
+	};
+
+	if mode % 2 == 0 {
+		let (state_root, _) = valid_messages_proof_nodes(1, true);
+		if let Ok(mut proof) = FromMillauMessagesProof::decode(&mut &data[..]) {
+			with_finalized_bridged_header(state_root, |header_hash| {
+				proof.bridged_header_hash = header_hash;
+				verify_messages_proof(proof);
+			});
+		}
+		return;
+	}
+
+	if data.len() < 3 {
+		return;
+	}
+
+	let messages_count = (data[0] % (MAX_ENTRIES_IN_VALID_PROOF + 1)) as MessageNonce;
+	let with_lane_state = data[1] & 1 != 0;
+	let nonce_argument = data[2] as MessageNonce;
+	let (nonces_start, nonces_end) = match (data[1] >> 1) % 4 {
+		0 => (1, messages_count),
+		1 => (1, messages_count + nonce_argument),
+		2 => (nonce_argument, messages_count),
+		_ => (1, MessageNonce::MAX - nonce_argument),
+	};
+	let mutations = &data[3..];
+	let is_valid_proof = nonces_start == 1
+		&& nonces_end == messages_count
+		&& mutations.is_empty()
+		&& (messages_count != 0 || with_lane_state);
+
+	let (state_root, nodes) = valid_messages_proof_nodes(messages_count, with_lane_state);
+	with_finalized_bridged_header(state_root, |header_hash| {
+		let proof = FromMillauMessagesProof {
+			bridged_header_hash: header_hash,
+			storage_proof: StorageProof::new(mutate_proof_nodes(nodes, mutations)),
+			lane: FUZZ_LANE,
+			nonces_start,
+			nonces_end,
+		};
+		let is_verified = verify_messages_proof(proof);
+		assert!(!is_valid_proof || is_verified, "Valid messages proof has been rejected");
+	});
+}
+
+/// Verify messages delivery proof, built from the fuzzer input.
+///
+/// The first byte of the input selects the mode. If it is even, the rest of the input is decoded
+/// as the messages delivery proof. Otherwise the valid messages delivery proof is mutated. The
+/// mutation is encoded as: `[relayers count, flags, proof nodes mutations...]`.
+pub fn fuzz_messages_delivery_proof(data: &[u8]) {
+	let (mode, data) = match data.split_first() {
+		Some((mode, data)) => (*mode, data),
+		None => return,
+	};
+
+	if mode % 2 == 0 {
+		let (state_root, _) = valid_messages_delivery_proof_nodes(1, true);
+		if let Ok(mut proof) = ToMillauMessagesDeliveryProof::decode(&mut &data[..]) {
+			with_finalized_bridged_header(state_root, |header_hash| {
+				proof.bridged_header_hash = header_hash;
+				verify_messages_delivery_proof(proof);
+			});
+		}
+		return;
+	}
+
+	if data.len() < 2 {
+		return;
+	}
+
+	let relayers_count = (data[0] % (MAX_ENTRIES_IN_VALID_PROOF + 1)) as MessageNonce;
+	let with_storage_version = data[1] & 1 != 0;
+	let lane = if data[1] & 2 != 0 {
+		LaneId::from_bytes([0, 0, 0, 2])
+	} else {
+		FUZZ_LANE
+	};
+	let mutations = &data[2..];
+	let is_valid_proof = lane == FUZZ_LANE && mutations.is_empty();
+
+	let (state_root, nodes) = valid_messages_delivery_proof_nodes(relayers_count, with_storage_version);
+	with_finalized_bridged_header(state_root, |header_hash| {
+		let proof = ToMillauMessagesDeliveryProof {
+			bridged_header_hash: header_hash,
+			storage_proof: StorageProof::new(mutate_proof_nodes(nodes, mutations)),
+			lane,
+		};
+		let is_verified = verify_messages_delivery_proof(proof);
+		assert!(
+			!is_valid_proof || is_verified,
+			"Valid messages delivery proof has been rejected"
+		);
+	});
+}
+
+/// Verify GRANDPA justification, built from the fuzzer input.
+///
+/// The first byte of the input selects the mode. If it is even, the rest of the input is decoded
+/// as the justification of the test header. Otherwise the valid justification is mutated. Every
+/// mutation is encoded with 2 bytes: `[kind, argument]`.
+pub fn fuzz_justification(data: &[u8]) {
+	let (mode, data) = match data.split_first() {
+		Some((mode, data)) => (*mode, data),
+		None => return,
+	};
+
+	if mode % 2 == 0 {
+		if let Ok(justification) = GrandpaJustification::<TestHeader>::decode(&mut &data[..]) {
+			verify_justification(justification);
+		}
+		return;
+	}
+
+	let mut justification = make_justification_for_header(
+		&test_header(1),
+		TEST_GRANDPA_ROUND,
+		TEST_GRANDPA_SET_ID,
+		&authority_list(),
+	);
+	let mutations = data.chunks_exact(2).collect::<Vec<_>>();
+	for mutation in &mutations {
+		mutate_justification(&mut justification, mutation[0], mutation[1]);
+	}
+
+	let is_verified = verify_justification(justification);
+	assert!(
+		!mutations.is_empty() || is_verified,
+		"Valid justification has been rejected"
+	);
+}
+
+/// Verify messages proof and check that the verification result is consistent with the proof.
+/// Returns true if proof has been accepted.
+fn verify_messages_proof(proof: FromMillauMessagesProof) -> bool {
+	let nodes_count = proof.storage_proof.clone().iter_nodes().count();
+	let (lane, nonces_start, nonces_end) = (proof.lane, proof.nonces_start, proof.nonces_end);
+	let proved_messages = match <Millau as SourceHeaderChain<bp_millau::Balance>>::verify_messages_proof(proof) {
+		Ok(proved_messages) => proved_messages,
+		Err(_) => return false,
+	};
+
+	// every message from the declared range must be read from the proof and every message
+	// requires at least one trie node
+	let declared_messages_count = if nonces_start <= nonces_end {
+		nonces_end - nonces_start + 1
+	} else {
+		0
+	};
+	assert_eq!(
+		proved_messages.messages_count() as MessageNonce,
+		declared_messages_count
+	);
+	assert!(proved_messages.messages_count() <= nodes_count);
+	assert!(proved_messages.keys().all(|proved_lane| *proved_lane == lane));
+	assert_eq!(proved_messages.ensure_contiguous_nonces(), Ok(()));
+	true
+}
+
+/// Verify messages delivery proof and check that the verification result is consistent with the
+/// proof. Returns true if proof has been accepted.
+fn verify_messages_delivery_proof(proof: ToMillauMessagesDeliveryProof) -> bool {
+	let proof_size = proof
+		.storage_proof
+		.clone()
+		.iter_nodes()
+		.map(|node| node.len())
+		.sum::<usize>();
+	let lane = proof.lane;
+	let (proved_lane, inbound_lane_data) = match <Millau as TargetHeaderChain<
+		ToMillauMessagePayload,
+		bp_millau::AccountId,
+	>>::verify_messages_delivery_proof(proof)
+	{
+		Ok(result) => result,
+		Err(_) => return false,
+	};
+
+	// inbound lane state is decoded from the proof, so it can't be larger than the proof
+	assert_eq!(proved_lane, lane);
+	assert!(inbound_lane_data.encoded_size() <= proof_size);
+	true
+}
+
+/// Run given closure in the externalities, where the Rialto runtime knows finalized Millau header
+/// with given state root. Hash of this header is passed to the closure.
+pub fn with_finalized_bridged_header<R>(state_root: bp_millau::Hash, f: impl FnOnce(bp_millau::Hash) -> R) -> R {
+	sp_io::TestExternalities::new(Default::default()).execute_with(|| {
+		let header = bp_millau::Header::new(
+			1,
+			Default::default(),
+			state_root,
+			Default::default(),
+			Default::default(),
+		);
+		let header_hash = header.hash();
+		pallet_substrate_bridge::Module::<Runtime, MillauBridgeInstance>::initialize(
+			Origin::root(),
+			InitializationData {
+				header,
+				authority_list: Vec::new(),
+				set_id: 0,
+				scheduled_change: None,
+				is_halted: false,
+			},
+		)
+		.expect("bridge pallet is initialized in fresh externalities; qed");
+
+		f(header_hash)
+	})
+}
+
+/// Return state root and nodes of the valid messages proof of the Millau chain.
+pub fn valid_messages_proof_nodes(
+	messages_count: MessageNonce,
+	with_lane_state: bool,
+) -> (bp_millau::Hash, Vec<Vec<u8>>) {
+	let mut entries = vec![storage_version_entry()];
+	entries.extend((1..=messages_count).map(|nonce| {
+		(
+			storage_keys::message_key::<Runtime, MillauMessageLaneInstance>(&FUZZ_LANE, nonce).0,
+			MessageData::<bp_millau::Balance> {
+				payload: vec![nonce as u8; 32],
+				fee: nonce,
+			}
+			.encode(),
+		)
+	}));
+	if with_lane_state {
+		entries.push((
+			storage_keys::outbound_lane_data_key::<MillauMessageLaneInstance>(&FUZZ_LANE).0,
+			OutboundLaneData {
+				oldest_unpruned_nonce: 1,
+				latest_received_nonce: 0,
+				latest_generated_nonce: messages_count,
+			}
+			.encode(),
+		));
+	}
+
+	prove_storage(entries)
+}
+
+/// Return state root and nodes of the valid messages delivery proof of the Millau chain.
+pub fn valid_messages_delivery_proof_nodes(
+	relayers_count: MessageNonce,
+	with_storage_version: bool,
+) -> (bp_millau::Hash, Vec<Vec<u8>>) {
+	let mut entries = Vec::new();
+	if with_storage_version {
+		entries.push(storage_version_entry());
+	}
+	entries.push((
+		storage_keys::inbound_lane_data_key::<Runtime, MillauMessageLaneInstance>(&FUZZ_LANE).0,
+		InboundLaneData::<bp_rialto::AccountId> {
+			relayers: (1..=relayers_count)
+				.map(|nonce| (nonce, nonce, [nonce as u8; 32].into()))
+				.collect(),
+			latest_received_nonce: relayers_count,
+			latest_confirmed_nonce: 0,
+		}
+		.encode(),
+	));
+
+	prove_storage(entries)
+}
+
+/// Verify justification of the test header and check that the verification result is consistent
+/// with the justification. Returns true if justification has been accepted.
+fn verify_justification(justification: GrandpaJustification<TestHeader>) -> bool {
+	let authority_set = AuthoritySet::new(authority_list(), TEST_GRANDPA_SET_ID);
+	if bp_header_chain::justification::verify_justification::<TestHeader>(header_id(1), &authority_set, &justification)
+		.is_err()
+	{
+		return false;
+	}
+
+	// every authority may only have one precommit and one equivocating precommit
+	assert!(justification.commit.precommits.len() <= 2 * authority_set.authorities.len());
+	true
+}
+
+/// Apply mutation to the justification.
+///
+/// The mutation may remove or duplicate precommits and votes ancestries, corrupt signatures,
+/// change precommits targets and the justification round.
+pub fn mutate_justification(justification: &mut GrandpaJustification<TestHeader>, kind: u8, argument: u8) {
+	let precommits = &mut justification.commit.precommits;
+	let precommit_index = argument as usize % std::cmp::max(precommits.len(), 1);
+	let ancestry_index = argument as usize % std::cmp::max(justification.votes_ancestries.len(), 1);
+	match kind % 8 {
+		0 if !precommits.is_empty() => {
+			precommits.remove(precommit_index);
+		}
+		1 if !precommits.is_empty() => {
+			let precommit = precommits[precommit_index].clone();
+			precommits.push(precommit);
+		}
+		2 if !precommits.is_empty() => {
+			let mut raw_signature = precommits[precommit_index].signature.encode();
+			let byte_index = argument as usize % raw_signature.len();
+			raw_signature[byte_index] ^= kind | 1;
+			precommits[precommit_index].signature =
+				Decode::decode(&mut &raw_signature[..]).expect("signature of the same size is decodable; qed");
+		}
+		3 if !precommits.is_empty() => {
+			let precommit = &mut precommits[precommit_index].precommit;
+			precommit.target_hash = justification.commit.target_hash;
+			precommit.target_number = justification.commit.target_number;
+		}
+		4 if !justification.votes_ancestries.is_empty() => {
+			justification.votes_ancestries.remove(ancestry_index);
+		}
+		5 if !justification.votes_ancestries.is_empty() => {
+			let header = justification.votes_ancestries[ancestry_index].clone();
+			justification.votes_ancestries.push(header);
+		}
+		6 => justification.votes_ancestries.push(test_header(argument as _)),
+		7 => justification.round ^= 1 << (argument % 64),
+		_ => (),
+	}
+}
+
+/// Apply mutations to the storage proof nodes.
+///
+/// Every mutation is encoded with 3 bytes: `[kind, node index, argument]`. The mutation may flip
+/// bits of the node byte, remove, duplicate or truncate the node.
+pub fn mutate_proof_nodes(mut nodes: Vec<Vec<u8>>, mutations: &[u8]) -> Vec<Vec<u8>> {
+	for mutation in mutations.chunks_exact(3) {
+		if nodes.is_empty() {
+			break;
+		}
+
+		let (kind, node_index, argument) = (mutation[0], mutation[1] as usize % nodes.len(), mutation[2]);
+		match kind % 4 {
+			0 => {
+				let node = &mut nodes[node_index];
+				if !node.is_empty() {
+					let byte_index = argument as usize % node.len();
+					node[byte_index] ^= kind | 1;
+				}
+			}
+			1 => {
+				nodes.remove(node_index);
+			}
+			2 => {
+				let node = nodes[node_index].clone();
+				nodes.push(node);
+			}
+			_ => {
+				let node = &mut nodes[node_index];
+				let new_len = argument as usize % (node.len() + 1);
+				node.truncate(new_len);
+			}
+		}
+	}
+	nodes
+}
+
+/// Return storage entry of the message lane pallet storage version.
+fn storage_version_entry() -> (Vec<u8>, Vec<u8>) {
+	(
+		storage_keys::storage_version_key::<MillauMessageLaneInstance>().0,
+		CURRENT_STORAGE_VERSION.encode(),
+	)
+}
+
+/// Build Millau storage with given entries and return its state root and proof of all entries.
+///
+/// The storage version key is always proved (just like the RPC does), even if it isn't in the
+/// storage.
+fn prove_storage(entries: Vec<(Vec<u8>, Vec<u8>)>) -> (bp_millau::Hash, Vec<Vec<u8>>) {
+	let storage_version_key = storage_version_entry().0;
+	let mut keys = entries.iter().map(|(key, _)| key.clone()).collect::<Vec<_>>();
+	if !keys.contains(&storage_version_key) {
+		keys.push(storage_version_key);
+	}
+	let backend = InMemoryBackend::<bp_millau::Hasher>::from(vec![(
+		None,
+		entries.into_iter().map(|(key, value)| (key, Some(value))).collect(),
+	)]);
+	let state_root = backend.storage_root(std::iter::empty()).0;
+	let proof = prove_read(backend, &keys).expect("in-memory backend never fails; qed");
+	(state_root, proof.iter_nodes().collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn valid_proofs_are_accepted() {
+		fuzz_messages_proof(&[1, 4, 1, 0]);
+		fuzz_messages_proof(&[1, 0, 0, 0]);
+		fuzz_messages_delivery_proof(&[1, 4, 1]);
+		fuzz_messages_delivery_proof(&[1, 0, 0]);
+	}
+
+	#[test]
+	fn huge_nonces_range_is_rejected() {
+		fuzz_messages_proof(&[1, 4, 6, 0]);
+	}
+
+	#[test]
+	fn mutated_proofs_are_handled() {
+		fuzz_messages_proof(&[1, 4, 1, 0, 0, 0, 7, 1, 1, 0, 3, 0, 2]);
+		fuzz_messages_delivery_proof(&[1, 4, 1, 2, 0, 0, 3, 0, 1]);
+	}
+}
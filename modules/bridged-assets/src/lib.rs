@@ -0,0 +1,350 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime module that stores metadata of assets that are minted on this chain by the token
+//! transfer bridge.
+//!
+//! The metadata (symbol, decimals, chain and lane where the asset is originated from) is needed
+//! to display bridged balances correctly. It is owned by the bridged chain, where the asset
+//! lives, so metadata updates are normally received over the governance lane: the bridged chain
+//! root sends `update_asset_metadata` call with the `CallOrigin::BridgeAccount` origin, and the
+//! call-dispatch module dispatches it using the bridge account of this chain. This keeps
+//! metadata consistent on both sides of the bridge. Root of this chain may update the metadata
+//! as well.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+use bp_message_lane::LaneId;
+use bp_runtime::{derive_account_id, InstanceId, SourceAccount};
+use codec::{Decode, Encode};
+use frame_support::{
+	decl_error, decl_event, decl_module, decl_storage, ensure, traits::Get, weights::DispatchClass, Parameter,
+	RuntimeDebug,
+};
+use frame_system::RawOrigin;
+use sp_runtime::traits::BadOrigin;
+use sp_std::{marker::PhantomData, prelude::*};
+
+/// Metadata of the bridged asset.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct AssetMetadata {
+	/// Asset symbol.
+	pub symbol: Vec<u8>,
+	/// Number of decimals in the asset balance.
+	pub decimals: u8,
+	/// Bridge instance of the chain where the asset is originated from.
+	pub origin_chain: InstanceId,
+	/// Lane that is used to transfer the asset.
+	pub origin_lane: LaneId,
+}
+
+/// The module configuration trait.
+pub trait Trait<I = DefaultInstance>: frame_system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self, I>> + Into<<Self as frame_system::Trait>::Event>;
+	/// Identifier of the asset.
+	type AssetId: Parameter;
+	/// Instance of the bridge with the chain where assets are originated from. Metadata updates
+	/// are accepted from the bridge account of this instance (i.e. from the bridged chain root).
+	type BridgeInstance: Get<InstanceId>;
+	/// Maximal length of the asset symbol.
+	type MaxSymbolLength: Get<u32>;
+}
+
+decl_error! {
+	pub enum Error for Module<T: Trait<I>, I: Instance> {
+		/// Asset symbol is longer than `MaxSymbolLength`.
+		SymbolTooLong,
+		/// Asset is originated from the chain that is not bridged using `BridgeInstance`.
+		UnexpectedOriginChain,
+		/// There's no metadata for the asset.
+		UnknownAsset,
+	}
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait<I>, I: Instance = DefaultInstance> as BridgedAssets {
+		/// Map of asset id => asset metadata.
+		pub AssetsMetadata get(fn asset_metadata) config(assets):
+			map hasher(blake2_128_concat) T::AssetId => Option<AssetMetadata>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T, I = DefaultInstance> where
+		<T as Trait<I>>::AssetId,
+	{
+		/// Asset metadata has been inserted or updated.
+		AssetMetadataUpdated(AssetId),
+		/// Asset metadata has been removed.
+		AssetMetadataRemoved(AssetId),
+		/// Phantom member, never used.
+		Dummy(PhantomData<I>),
+	}
+);
+
+decl_module! {
+	/// Bridged assets FRAME Pallet.
+	pub struct Module<T: Trait<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
+		type Error = Error<T, I>;
+
+		/// Deposit one of this module's events by using the default implementation.
+		fn deposit_event() = default;
+
+		/// Insert or update metadata of the asset.
+		///
+		/// May only be called either by root, or by the bridged chain governance.
+		#[weight = (T::DbWeight::get().writes(1), DispatchClass::Operational)]
+		pub fn update_asset_metadata(origin, asset_id: T::AssetId, metadata: AssetMetadata) {
+			ensure_governance_or_root::<T, I>(origin)?;
+			ensure!(
+				metadata.symbol.len() <= T::MaxSymbolLength::get() as usize,
+				Error::<T, I>::SymbolTooLong,
+			);
+			ensure!(
+				metadata.origin_chain == T::BridgeInstance::get(),
+				Error::<T, I>::UnexpectedOriginChain,
+			);
+
+			AssetsMetadata::<T, I>::insert(&asset_id, metadata);
+			Self::deposit_event(RawEvent::AssetMetadataUpdated(asset_id));
+		}
+
+		/// Remove metadata of the asset.
+		///
+		/// May only be called either by root, or by the bridged chain governance.
+		#[weight = (T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational)]
+		pub fn remove_asset_metadata(origin, asset_id: T::AssetId) {
+			ensure_governance_or_root::<T, I>(origin)?;
+			ensure!(AssetsMetadata::<T, I>::contains_key(&asset_id), Error::<T, I>::UnknownAsset);
+
+			AssetsMetadata::<T, I>::remove(&asset_id);
+			Self::deposit_event(RawEvent::AssetMetadataRemoved(asset_id));
+		}
+	}
+}
+
+impl<T: Trait<I>, I: Instance> Module<T, I> {
+	/// Return account that is used to dispatch messages from the bridged chain governance.
+	pub fn governance_account_id() -> T::AccountId {
+		derive_account_id(T::BridgeInstance::get(), SourceAccount::<()>::Root)
+	}
+}
+
+/// Ensure that the origin is either root, or the bridged chain governance.
+fn ensure_governance_or_root<T: Trait<I>, I: Instance>(origin: T::Origin) -> Result<(), BadOrigin> {
+	match origin.into() {
+		Ok(RawOrigin::Root) => Ok(()),
+		Ok(RawOrigin::Signed(ref signer)) if *signer == Module::<T, I>::governance_account_id() => Ok(()),
+		_ => Err(BadOrigin),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_support::{
+		assert_noop, assert_ok, impl_outer_event, impl_outer_origin, parameter_types, weights::Weight,
+	};
+	use frame_system::{EventRecord, Phase};
+	use sp_core::H256;
+	use sp_runtime::{
+		testing::Header,
+		traits::{BlakeTwo256, IdentityLookup},
+		Perbill,
+	};
+
+	type AccountId = u64;
+	type AssetId = u32;
+	type BridgedAssets = Module<TestRuntime>;
+	type System = frame_system::Module<TestRuntime>;
+
+	const TEST_BRIDGE_INSTANCE: InstanceId = *b"test";
+	const TEST_ASSET: AssetId = 42;
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct TestRuntime;
+
+	mod bridged_assets {
+		pub use crate::Event;
+	}
+
+	impl_outer_event! {
+		pub enum TestEvent for TestRuntime {
+			frame_system<T>,
+			bridged_assets<T>,
+		}
+	}
+
+	impl_outer_origin! {
+		pub enum Origin for TestRuntime where system = frame_system {}
+	}
+
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: Weight = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+		pub const TestBridgeInstance: InstanceId = TEST_BRIDGE_INSTANCE;
+		pub const MaxSymbolLength: u32 = 8;
+	}
+
+	impl frame_system::Trait for TestRuntime {
+		type Origin = Origin;
+		type Index = u64;
+		type Call = ();
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = AccountId;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = TestEvent;
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type DbWeight = ();
+		type BlockExecutionWeight = ();
+		type ExtrinsicBaseWeight = ();
+		type MaximumExtrinsicWeight = ();
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type MaximumBlockLength = MaximumBlockLength;
+		type Version = ();
+		type PalletInfo = ();
+		type AccountData = ();
+		type OnNewAccount = ();
+		type OnKilledAccount = ();
+		type BaseCallFilter = ();
+		type SystemWeightInfo = ();
+	}
+
+	impl Trait for TestRuntime {
+		type Event = TestEvent;
+		type AssetId = AssetId;
+		type BridgeInstance = TestBridgeInstance;
+		type MaxSymbolLength = MaxSymbolLength;
+	}
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let t = frame_system::GenesisConfig::default()
+			.build_storage::<TestRuntime>()
+			.unwrap();
+		sp_io::TestExternalities::new(t)
+	}
+
+	fn governance_origin() -> Origin {
+		Origin::signed(BridgedAssets::governance_account_id())
+	}
+
+	fn test_metadata() -> AssetMetadata {
+		AssetMetadata {
+			symbol: b"TEST".to_vec(),
+			decimals: 12,
+			origin_chain: TEST_BRIDGE_INSTANCE,
+			origin_lane: LaneId::from_bytes([0, 0, 0, 1]),
+		}
+	}
+
+	#[test]
+	fn metadata_is_updated_by_bridged_chain_governance() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(BridgedAssets::update_asset_metadata(
+				governance_origin(),
+				TEST_ASSET,
+				test_metadata(),
+			));
+
+			assert_eq!(BridgedAssets::asset_metadata(TEST_ASSET), Some(test_metadata()));
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::bridged_assets(Event::<TestRuntime>::AssetMetadataUpdated(TEST_ASSET)),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn metadata_is_updated_and_removed_by_root() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(BridgedAssets::update_asset_metadata(
+				Origin::root(),
+				TEST_ASSET,
+				test_metadata(),
+			));
+			assert_eq!(BridgedAssets::asset_metadata(TEST_ASSET), Some(test_metadata()));
+
+			assert_ok!(BridgedAssets::remove_asset_metadata(Origin::root(), TEST_ASSET));
+			assert_eq!(BridgedAssets::asset_metadata(TEST_ASSET), None);
+		});
+	}
+
+	#[test]
+	fn metadata_is_not_updated_by_regular_account() {
+		new_test_ext().execute_with(|| {
+			assert_noop!(
+				BridgedAssets::update_asset_metadata(Origin::signed(1), TEST_ASSET, test_metadata()),
+				BadOrigin,
+			);
+			assert_noop!(
+				BridgedAssets::remove_asset_metadata(Origin::signed(1), TEST_ASSET),
+				BadOrigin,
+			);
+		});
+	}
+
+	#[test]
+	fn invalid_metadata_is_rejected() {
+		new_test_ext().execute_with(|| {
+			assert_noop!(
+				BridgedAssets::update_asset_metadata(
+					governance_origin(),
+					TEST_ASSET,
+					AssetMetadata {
+						symbol: b"TOO_LONG_SYMBOL".to_vec(),
+						..test_metadata()
+					},
+				),
+				Error::<TestRuntime, DefaultInstance>::SymbolTooLong,
+			);
+			assert_noop!(
+				BridgedAssets::update_asset_metadata(
+					governance_origin(),
+					TEST_ASSET,
+					AssetMetadata {
+						origin_chain: *b"othr",
+						..test_metadata()
+					},
+				),
+				Error::<TestRuntime, DefaultInstance>::UnexpectedOriginChain,
+			);
+		});
+	}
+
+	#[test]
+	fn unknown_asset_metadata_is_not_removed() {
+		new_test_ext().execute_with(|| {
+			assert_noop!(
+				BridgedAssets::remove_asset_metadata(governance_origin(), TEST_ASSET),
+				Error::<TestRuntime, DefaultInstance>::UnknownAsset,
+			);
+		});
+	}
+}
@@ -0,0 +1,614 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Signed extension that allows paying transaction fees in the bridged asset.
+//!
+//! The `ChargeAssetTransactionPayment` replaces `pallet_transaction_payment::ChargeTransactionPayment`
+//! in the runtime signed extensions. By default, fees are paid in this chain tokens, exactly
+//! like they're paid by the `ChargeTransactionPayment`. If the transaction signer has selected
+//! to pay in the bridged asset, the fee is computed by the `pallet_transaction_payment` (so
+//! it is the same fee that would be paid in this chain tokens), converted to the bridged asset
+//! using `ConversionRate` and withdrawn from the `BridgedCurrency` balance of the signer. The
+//! unused part of the fee is refunded after dispatch, using the same rate.
+//!
+//! The asset must be registered in the `pallet-bridged-assets` - if its metadata is removed,
+//! fee payments in this asset are rejected. Payments are also rejected while the conversion
+//! rate is stale.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+use bp_currency_exchange::PriceProvider;
+use codec::{Decode, Encode};
+use frame_support::{
+	traits::{Currency, ExistenceRequirement, Get, Imbalance, OnUnbalanced, WithdrawReason},
+	weights::{DispatchInfo, PostDispatchInfo},
+};
+use pallet_transaction_payment::ChargeTransactionPayment;
+use sp_runtime::{
+	traits::{
+		DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SaturatedConversion, Saturating, SignedExtension, Zero,
+	},
+	transaction_validity::{
+		InvalidTransaction, TransactionPriority, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+	DispatchResult, FixedPointNumber, FixedPointOperand, FixedU128,
+};
+
+/// Custom `InvalidTransaction` code: the fee asset is not registered in the bridged assets registry.
+pub const UNKNOWN_FEE_ASSET: u8 = 1;
+/// Custom `InvalidTransaction` code: the conversion rate of the fee asset is unknown or stale.
+pub const STALE_CONVERSION_RATE: u8 = 2;
+
+/// Balance of this chain tokens.
+pub type BalanceOf<T> =
+	<<T as pallet_transaction_payment::Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+/// Negative imbalance of the bridged asset.
+pub type BridgedNegativeImbalanceOf<T> =
+	<<T as Trait>::BridgedCurrency as Currency<<T as frame_system::Trait>::AccountId>>::NegativeImbalance;
+
+/// The module configuration trait.
+pub trait Trait: pallet_transaction_payment::Trait + pallet_bridged_assets::Trait {
+	/// Currency that holds balances of the bridged asset.
+	type BridgedCurrency: Currency<Self::AccountId, Balance = BalanceOf<Self>> + Send + Sync;
+	/// Identifier of the bridged asset in the bridged assets registry.
+	type FeeAssetId: Get<<Self as pallet_bridged_assets::Trait>::AssetId>;
+	/// Price of one bridged asset unit in this chain tokens.
+	type ConversionRate: PriceProvider;
+	/// Handler for the fees (and tips) that have been paid in the bridged asset.
+	type OnBridgedTransactionPayment: OnUnbalanced<BridgedNegativeImbalanceOf<Self>>;
+}
+
+/// The fee that has been withdrawn in the bridged asset before dispatch.
+pub struct BridgedFeePayment<T: Trait> {
+	/// Account that has paid the fee.
+	who: T::AccountId,
+	/// Tip (in this chain tokens) that is included in the fee.
+	tip: BalanceOf<T>,
+	/// Conversion rate that has been used to compute the fee.
+	rate: FixedU128,
+	/// Withdrawn fee.
+	imbalance: BridgedNegativeImbalanceOf<T>,
+}
+
+/// Require the transactor pay for themselves, either in this chain tokens or in the bridged asset.
+/// It may also include a tip (in this chain tokens) to gain additional priority in the queue.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct ChargeAssetTransactionPayment<T: Trait + Send + Sync> {
+	#[codec(compact)]
+	tip: BalanceOf<T>,
+	pay_in_bridged_asset: bool,
+}
+
+impl<T: Trait + Send + Sync> ChargeAssetTransactionPayment<T>
+where
+	BalanceOf<T>: Send + Sync + From<u64> + FixedPointOperand,
+	T::Call: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+{
+	/// Pay the fee in the bridged asset.
+	pub fn in_bridged_asset(tip: BalanceOf<T>) -> Self {
+		ChargeAssetTransactionPayment {
+			tip,
+			pay_in_bridged_asset: true,
+		}
+	}
+
+	/// Return extension that charges fee in this chain tokens.
+	fn native(&self) -> ChargeTransactionPayment<T> {
+		ChargeTransactionPayment::from(self.tip)
+	}
+
+	/// Withdraw fee in the bridged asset. Returns the fee in this chain tokens and the withdrawn
+	/// payment (if the fee is non-zero).
+	fn withdraw_fee_in_bridged_asset(
+		&self,
+		who: &T::AccountId,
+		info: &DispatchInfoOf<T::Call>,
+		len: usize,
+	) -> Result<(BalanceOf<T>, Option<BridgedFeePayment<T>>), TransactionValidityError> {
+		let fee = pallet_transaction_payment::Module::<T>::compute_fee(len as u32, info, self.tip);
+		if fee.is_zero() {
+			return Ok((fee, None));
+		}
+
+		if pallet_bridged_assets::Module::<T>::asset_metadata(T::FeeAssetId::get()).is_none() {
+			return Err(InvalidTransaction::Custom(UNKNOWN_FEE_ASSET).into());
+		}
+		let rate = T::ConversionRate::price().map_err(|_| InvalidTransaction::Custom(STALE_CONVERSION_RATE))?;
+		let fee_in_bridged_asset = this_to_bridged(fee, rate).ok_or(InvalidTransaction::Payment)?;
+		let imbalance = T::BridgedCurrency::withdraw(
+			who,
+			fee_in_bridged_asset,
+			if self.tip.is_zero() {
+				WithdrawReason::TransactionPayment.into()
+			} else {
+				WithdrawReason::TransactionPayment | WithdrawReason::Tip
+			},
+			ExistenceRequirement::KeepAlive,
+		)
+		.map_err(|_| InvalidTransaction::Payment)?;
+
+		Ok((
+			fee,
+			Some(BridgedFeePayment {
+				who: who.clone(),
+				tip: self.tip,
+				rate,
+				imbalance,
+			}),
+		))
+	}
+}
+
+impl<T: Trait + Send + Sync> From<BalanceOf<T>> for ChargeAssetTransactionPayment<T> {
+	fn from(tip: BalanceOf<T>) -> Self {
+		ChargeAssetTransactionPayment {
+			tip,
+			pay_in_bridged_asset: false,
+		}
+	}
+}
+
+impl<T: Trait + Send + Sync> sp_std::fmt::Debug for ChargeAssetTransactionPayment<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(
+			f,
+			"ChargeAssetTransactionPayment<{:?}, {}>",
+			self.tip, self.pay_in_bridged_asset
+		)
+	}
+
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Trait + Send + Sync> SignedExtension for ChargeAssetTransactionPayment<T>
+where
+	BalanceOf<T>: Send + Sync + From<u64> + FixedPointOperand,
+	T::Call: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+{
+	const IDENTIFIER: &'static str = "ChargeAssetTransactionPayment";
+	type AccountId = T::AccountId;
+	type Call = T::Call;
+	type AdditionalSigned = ();
+	type Pre = (
+		<ChargeTransactionPayment<T> as SignedExtension>::Pre,
+		Option<BridgedFeePayment<T>>,
+	);
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		if !self.pay_in_bridged_asset {
+			return self.native().validate(who, call, info, len);
+		}
+
+		let (fee, _) = self.withdraw_fee_in_bridged_asset(who, info, len)?;
+		Ok(ValidTransaction {
+			priority: fee.saturated_into::<TransactionPriority>(),
+			..Default::default()
+		})
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		if !self.pay_in_bridged_asset {
+			return Ok((self.native().pre_dispatch(who, call, info, len)?, None));
+		}
+
+		let (_, payment) = self.withdraw_fee_in_bridged_asset(who, info, len)?;
+		Ok((Default::default(), payment))
+	}
+
+	fn post_dispatch(
+		pre: Self::Pre,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		let (native_pre, payment) = pre;
+		let payment = match payment {
+			Some(payment) => payment,
+			None => return ChargeTransactionPayment::<T>::post_dispatch(native_pre, info, post_info, len, result),
+		};
+
+		let actual_fee =
+			pallet_transaction_payment::Module::<T>::compute_actual_fee(len as u32, info, post_info, payment.tip);
+		let actual_fee_in_bridged_asset =
+			this_to_bridged(actual_fee, payment.rate).ok_or(InvalidTransaction::Payment)?;
+		let refund = payment.imbalance.peek().saturating_sub(actual_fee_in_bridged_asset);
+		let refund_imbalance = T::BridgedCurrency::deposit_into_existing(&payment.who, refund)
+			.unwrap_or_else(|_| <T::BridgedCurrency as Currency<T::AccountId>>::PositiveImbalance::zero());
+		let actual_payment = payment
+			.imbalance
+			.offset(refund_imbalance)
+			.map_err(|_| InvalidTransaction::Payment)?;
+		T::OnBridgedTransactionPayment::on_unbalanced(actual_payment);
+
+		Ok(())
+	}
+}
+
+/// Convert amount of this chain tokens into the bridged asset amount, using price of one bridged
+/// asset unit in this chain tokens.
+fn this_to_bridged<Amount: FixedPointOperand>(amount: Amount, rate: FixedU128) -> Option<Amount> {
+	rate.reciprocal()?.checked_mul_int(amount)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use bp_currency_exchange::Error as ExchangeError;
+	use bp_message_lane::LaneId;
+	use frame_support::{
+		impl_outer_dispatch, impl_outer_event, impl_outer_origin, parameter_types,
+		traits::StorageMapShim,
+		weights::{IdentityFee, Pays, Weight},
+	};
+	use sp_core::H256;
+	use sp_runtime::{
+		testing::Header,
+		traits::{BlakeTwo256, IdentityLookup},
+		Perbill,
+	};
+
+	type AccountId = u64;
+	type AssetId = u32;
+	type Balances = pallet_balances::Module<TestRuntime>;
+	type BridgedBalances = pallet_balances::Module<TestRuntime, pallet_balances::Instance1>;
+	type System = frame_system::Module<TestRuntime>;
+
+	const TEST_ASSET: AssetId = 42;
+	const TEST_BRIDGE_INSTANCE: [u8; 4] = *b"test";
+	const TEST_ACCOUNT: AccountId = 1;
+	const TEST_BALANCE: u64 = 1_000;
+	const TEST_RATE_KEY: &[u8] = b":test-conversion-rate:";
+
+	#[derive(Clone, Eq, PartialEq, Debug)]
+	pub struct TestRuntime;
+
+	mod bridged_assets {
+		pub use pallet_bridged_assets::Event;
+	}
+
+	impl_outer_event! {
+		pub enum TestEvent for TestRuntime {
+			frame_system<T>,
+			pallet_balances<T>,
+			pallet_balances Instance1<T>,
+			bridged_assets<T>,
+		}
+	}
+
+	impl_outer_origin! {
+		pub enum Origin for TestRuntime where system = frame_system {}
+	}
+
+	impl_outer_dispatch! {
+		pub enum Call for TestRuntime where origin: Origin {
+			frame_system::System,
+		}
+	}
+
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: Weight = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+		pub const ExistentialDeposit: u64 = 1;
+		pub const MaxLocks: u32 = 50;
+		pub const TransactionByteFee: u64 = 1;
+		pub const TestBridgeInstance: [u8; 4] = TEST_BRIDGE_INSTANCE;
+		pub const MaxSymbolLength: u32 = 8;
+		pub const FeeAssetId: AssetId = TEST_ASSET;
+	}
+
+	impl frame_system::Trait for TestRuntime {
+		type Origin = Origin;
+		type Index = u64;
+		type Call = Call;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = AccountId;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = TestEvent;
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type DbWeight = ();
+		type BlockExecutionWeight = ();
+		type ExtrinsicBaseWeight = ();
+		type MaximumExtrinsicWeight = ();
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type MaximumBlockLength = MaximumBlockLength;
+		type Version = ();
+		type PalletInfo = ();
+		type AccountData = pallet_balances::AccountData<u64>;
+		type OnNewAccount = ();
+		type OnKilledAccount = ();
+		type BaseCallFilter = ();
+		type SystemWeightInfo = ();
+	}
+
+	impl pallet_balances::Trait for TestRuntime {
+		type Balance = u64;
+		type Event = TestEvent;
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type AccountStore = System;
+		type WeightInfo = ();
+		type MaxLocks = MaxLocks;
+	}
+
+	impl pallet_balances::Trait<pallet_balances::Instance1> for TestRuntime {
+		type Balance = u64;
+		type Event = TestEvent;
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type AccountStore = StorageMapShim<
+			pallet_balances::Account<TestRuntime, pallet_balances::Instance1>,
+			frame_system::CallOnCreatedAccount<TestRuntime>,
+			frame_system::CallKillAccount<TestRuntime>,
+			AccountId,
+			pallet_balances::AccountData<u64>,
+		>;
+		type WeightInfo = ();
+		type MaxLocks = MaxLocks;
+	}
+
+	impl pallet_transaction_payment::Trait for TestRuntime {
+		type Currency = Balances;
+		type OnTransactionPayment = ();
+		type TransactionByteFee = TransactionByteFee;
+		type WeightToFee = IdentityFee<u64>;
+		type FeeMultiplierUpdate = ();
+	}
+
+	impl pallet_bridged_assets::Trait for TestRuntime {
+		type Event = TestEvent;
+		type AssetId = AssetId;
+		type BridgeInstance = TestBridgeInstance;
+		type MaxSymbolLength = MaxSymbolLength;
+	}
+
+	impl Trait for TestRuntime {
+		type BridgedCurrency = BridgedBalances;
+		type FeeAssetId = FeeAssetId;
+		type ConversionRate = TestConversionRate;
+		type OnBridgedTransactionPayment = ();
+	}
+
+	/// Conversion rate that is read from the test storage.
+	pub struct TestConversionRate;
+
+	impl PriceProvider for TestConversionRate {
+		fn price() -> bp_currency_exchange::Result<FixedU128> {
+			frame_support::storage::unhashed::get(TEST_RATE_KEY).ok_or(ExchangeError::StalePrice)
+		}
+	}
+
+	fn set_rate(rate: Option<FixedU128>) {
+		match rate {
+			Some(rate) => frame_support::storage::unhashed::put(TEST_RATE_KEY, &rate),
+			None => frame_support::storage::unhashed::kill(TEST_RATE_KEY),
+		}
+	}
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let mut t = frame_system::GenesisConfig::default()
+			.build_storage::<TestRuntime>()
+			.unwrap();
+		pallet_balances::GenesisConfig::<TestRuntime> {
+			balances: vec![(TEST_ACCOUNT, TEST_BALANCE)],
+		}
+		.assimilate_storage(&mut t)
+		.unwrap();
+		pallet_balances::GenesisConfig::<TestRuntime, pallet_balances::Instance1> {
+			balances: vec![(TEST_ACCOUNT, TEST_BALANCE)],
+		}
+		.assimilate_storage(&mut t)
+		.unwrap();
+		pallet_bridged_assets::GenesisConfig::<TestRuntime> {
+			assets: vec![(
+				TEST_ASSET,
+				pallet_bridged_assets::AssetMetadata {
+					symbol: b"TEST".to_vec(),
+					decimals: 12,
+					origin_chain: TEST_BRIDGE_INSTANCE,
+					origin_lane: LaneId::from_bytes([0, 0, 0, 1]),
+				},
+			)],
+		}
+		.assimilate_storage(&mut t)
+		.unwrap();
+
+		let mut ext = sp_io::TestExternalities::new(t);
+		ext.execute_with(|| set_rate(Some(FixedU128::saturating_from_integer(2))));
+		ext
+	}
+
+	fn call() -> Call {
+		Call::System(frame_system::Call::remark(vec![]))
+	}
+
+	fn info(weight: Weight) -> DispatchInfo {
+		DispatchInfo {
+			weight,
+			..Default::default()
+		}
+	}
+
+	fn post_info(actual_weight: Weight) -> PostDispatchInfo {
+		PostDispatchInfo {
+			actual_weight: Some(actual_weight),
+			pays_fee: Pays::Yes,
+		}
+	}
+
+	#[test]
+	fn fee_is_paid_in_this_chain_tokens_by_default() {
+		new_test_ext().execute_with(|| {
+			let pre = ChargeAssetTransactionPayment::<TestRuntime>::from(0)
+				.pre_dispatch(&TEST_ACCOUNT, &call(), &info(100), 10)
+				.unwrap();
+			assert_eq!(Balances::free_balance(TEST_ACCOUNT), TEST_BALANCE - 110);
+			assert_eq!(BridgedBalances::free_balance(TEST_ACCOUNT), TEST_BALANCE);
+
+			assert!(ChargeAssetTransactionPayment::<TestRuntime>::post_dispatch(
+				pre,
+				&info(100),
+				&post_info(50),
+				10,
+				&Ok(()),
+			)
+			.is_ok());
+			assert_eq!(Balances::free_balance(TEST_ACCOUNT), TEST_BALANCE - 60);
+			assert_eq!(BridgedBalances::free_balance(TEST_ACCOUNT), TEST_BALANCE);
+		});
+	}
+
+	#[test]
+	fn fee_is_converted_and_paid_in_bridged_asset() {
+		new_test_ext().execute_with(|| {
+			// fee is 110 tokens = 55 bridged asset units
+			let pre = ChargeAssetTransactionPayment::<TestRuntime>::in_bridged_asset(0)
+				.pre_dispatch(&TEST_ACCOUNT, &call(), &info(100), 10)
+				.unwrap();
+			assert_eq!(Balances::free_balance(TEST_ACCOUNT), TEST_BALANCE);
+			assert_eq!(BridgedBalances::free_balance(TEST_ACCOUNT), TEST_BALANCE - 55);
+
+			// rate change doesn't affect refund
+			set_rate(Some(FixedU128::saturating_from_integer(10)));
+
+			// actual fee is 60 tokens = 30 bridged asset units
+			assert!(ChargeAssetTransactionPayment::<TestRuntime>::post_dispatch(
+				pre,
+				&info(100),
+				&post_info(50),
+				10,
+				&Ok(()),
+			)
+			.is_ok());
+			assert_eq!(Balances::free_balance(TEST_ACCOUNT), TEST_BALANCE);
+			assert_eq!(BridgedBalances::free_balance(TEST_ACCOUNT), TEST_BALANCE - 30);
+		});
+	}
+
+	#[test]
+	fn tip_is_paid_in_bridged_asset() {
+		new_test_ext().execute_with(|| {
+			// fee is 130 tokens (including tip) = 65 bridged asset units
+			ChargeAssetTransactionPayment::<TestRuntime>::in_bridged_asset(20)
+				.pre_dispatch(&TEST_ACCOUNT, &call(), &info(100), 10)
+				.unwrap();
+			assert_eq!(BridgedBalances::free_balance(TEST_ACCOUNT), TEST_BALANCE - 65);
+		});
+		new_test_ext().execute_with(|| {
+			assert_eq!(
+				ChargeAssetTransactionPayment::<TestRuntime>::in_bridged_asset(20)
+					.validate(&TEST_ACCOUNT, &call(), &info(100), 10)
+					.map(|v| v.priority),
+				Ok(130),
+			);
+		});
+	}
+
+	#[test]
+	fn free_transaction_does_not_require_conversion_rate() {
+		new_test_ext().execute_with(|| {
+			set_rate(None);
+
+			let free_info = DispatchInfo {
+				weight: 100,
+				pays_fee: Pays::No,
+				..Default::default()
+			};
+			let pre = ChargeAssetTransactionPayment::<TestRuntime>::in_bridged_asset(0)
+				.pre_dispatch(&TEST_ACCOUNT, &call(), &free_info, 10)
+				.unwrap();
+			assert!(ChargeAssetTransactionPayment::<TestRuntime>::post_dispatch(
+				pre,
+				&free_info,
+				&post_info(50),
+				10,
+				&Ok(()),
+			)
+			.is_ok());
+			assert_eq!(BridgedBalances::free_balance(TEST_ACCOUNT), TEST_BALANCE);
+		});
+	}
+
+	#[test]
+	fn fee_is_not_paid_in_bridged_asset_if_rate_is_stale() {
+		new_test_ext().execute_with(|| {
+			set_rate(None);
+
+			let ext = ChargeAssetTransactionPayment::<TestRuntime>::in_bridged_asset(0);
+			assert_eq!(
+				ext.validate(&TEST_ACCOUNT, &call(), &info(100), 10),
+				Err(InvalidTransaction::Custom(STALE_CONVERSION_RATE).into()),
+			);
+			assert_eq!(BridgedBalances::free_balance(TEST_ACCOUNT), TEST_BALANCE);
+		});
+	}
+
+	#[test]
+	fn fee_is_not_paid_in_unregistered_asset() {
+		new_test_ext().execute_with(|| {
+			pallet_bridged_assets::AssetsMetadata::<TestRuntime>::remove(TEST_ASSET);
+
+			let ext = ChargeAssetTransactionPayment::<TestRuntime>::in_bridged_asset(0);
+			assert_eq!(
+				ext.validate(&TEST_ACCOUNT, &call(), &info(100), 10),
+				Err(InvalidTransaction::Custom(UNKNOWN_FEE_ASSET).into()),
+			);
+		});
+	}
+
+	#[test]
+	fn fee_is_not_paid_if_bridged_balance_is_too_low() {
+		new_test_ext().execute_with(|| {
+			set_rate(Some(FixedU128::saturating_from_rational(1, 10)));
+
+			// fee is 110 tokens = 1100 bridged asset units
+			let ext = ChargeAssetTransactionPayment::<TestRuntime>::in_bridged_asset(0);
+			assert_eq!(
+				ext.validate(&TEST_ACCOUNT, &call(), &info(100), 10),
+				Err(InvalidTransaction::Payment.into()),
+			);
+			assert_eq!(Balances::free_balance(TEST_ACCOUNT), TEST_BALANCE);
+		});
+	}
+}
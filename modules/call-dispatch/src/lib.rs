@@ -22,30 +22,57 @@
 //! which is being checked before dispatch.
 //!
 //! In case of succesful dispatch event is emitted.
+//!
+//! The message may also carry a batch of calls (see `MessageCall::Batch`). Calls of the batch are
+//! dispatched within single storage transaction: if any call fails, changes of all calls are
+//! reverted.
+//!
+//! The account, derived from the `SourceAccount::Root` of the bridged chain, is the sovereign
+//! account of the bridged chain. It may be endowed (by root) or topped up (by anyone) using the
+//! pallet calls, so that messages with the `CallOrigin::BridgeAccount` origin are able to pay
+//! for their operations.
+//!
+//! If `StoreFailedCalls` is enabled, calls that have been decoded and verified, but failed
+//! to dispatch, are stored in the runtime storage. Anyone may retry them later using the
+//! `retry_failed_call` call, paying for the dispatch weight.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 
 use bp_message_dispatch::{MessageDispatch, Weight};
-use bp_runtime::{bridge_account_id, InstanceId, CALL_DISPATCH_MODULE_PREFIX};
+use bp_runtime::{derive_account_id, InstanceId, SourceAccount};
 use codec::{Decode, Encode};
 use frame_support::{
-	decl_event, decl_module, decl_storage,
+	decl_error, decl_event, decl_module, decl_storage,
 	dispatch::{Dispatchable, Parameter},
-	traits::Get,
-	weights::{extract_actual_weight, GetDispatchInfo},
+	ensure,
+	storage::with_transaction,
+	traits::{Currency, ExistenceRequirement, Get, Imbalance},
+	weights::{extract_actual_weight, DispatchInfo, GetDispatchInfo},
 	RuntimeDebug,
 };
 use frame_system::{ensure_root, ensure_signed, RawOrigin};
 use sp_runtime::{
-	traits::{BadOrigin, IdentifyAccount, Verify},
-	DispatchResult,
+	traits::{BadOrigin, IdentifyAccount, Verify, Zero},
+	DispatchResult, TransactionOutcome,
 };
 use sp_std::{marker::PhantomData, prelude::*};
 
+// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+/// Upper bound of failed call retry transaction weight (excluding the call weight itself).
+const RETRY_BASE_WEIGHT: Weight = 0;
+
 /// Spec version type.
 pub type SpecVersion = u32;
 
+/// Balance type of the pallet currency.
+pub type BalanceOf<T, I = DefaultInstance> =
+	<<T as Trait<I>>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+/// Nonce of the source chain account, which messages are dispatched with `CallOrigin::RealAccount`
+/// origin.
+pub type SourceAccountNonce = u64;
+
 /// Origin of the call on the target chain.
 #[derive(RuntimeDebug, Encode, Decode, Clone, PartialEq, Eq)]
 pub enum CallOrigin<SourceChainAccountPublic, TargetChainAccountPublic, TargetChainSignature> {
@@ -57,10 +84,21 @@ pub enum CallOrigin<SourceChainAccountPublic, TargetChainAccountPublic, TargetCh
 	/// from source chain `root` account (default implementation), `BridgeAccount` represents the
 	/// source-chain-root origin on the target chain and can be used to send and authorize
 	/// "control plane" messages between the two runtimes.
+	///
+	/// The account id is `derive_account_id(bridge, SourceAccount::Root)`.
 	BridgeAccount,
+	/// Call is originated from account, that is derived from the `SourceChainAccountPublic`
+	/// (`derive_account_id(bridge, SourceAccount::Account(source_public))`). Nobody knows the
+	/// private key of this account, so it is only controlled by the source chain account.
+	/// The source chain must ensure that the message is sent by the owner of
+	/// `SourceChainAccountPublic` account (use the `fn verify_sending_message()`).
+	SourceAccount(SourceChainAccountPublic),
 	/// Call is originated from account, identified by `TargetChainAccountPublic`. The proof
 	/// that the `SourceChainAccountPublic` controls `TargetChainAccountPublic` is the
-	/// `TargetChainSignature` over `(Call, SourceChainAccountPublic).encode()`.
+	/// `TargetChainSignature` over `(MessageCall, SourceChainAccountPublic, InstanceId, SourceAccountNonce).encode()`
+	/// (use the `fn real_account_signature_payload()`), where the nonce is the current value of
+	/// `SourceAccountNonces` for the bridge instance and source account. The nonce is incremented
+	/// whenever the proof is accepted, so the same proof can't be used twice.
 	/// The source chain must ensure that the message is sent by the owner of
 	/// `SourceChainAccountPublic` account (use the `fn verify_sending_message()`).
 	RealAccount(SourceChainAccountPublic, TargetChainAccountPublic, TargetChainSignature),
@@ -73,14 +111,72 @@ pub struct MessagePayload<SourceChainAccountPublic, TargetChainAccountPublic, Ta
 	/// runtime version. Otherwise we risk to misinterpret encoded calls.
 	pub spec_version: SpecVersion,
 	/// Weight of the call, declared by the message sender. If it is less than actual
-	/// static weight, the call is not dispatched.
+	/// static weight, the call is not dispatched. For batches, this is the aggregate weight
+	/// of all calls.
 	pub weight: Weight,
 	/// Call origin to be used during dispatch.
 	pub origin: CallOrigin<SourceChainAccountPublic, TargetChainAccountPublic, TargetChainSignature>,
-	/// The call itself.
+	/// The call (or calls) itself.
+	pub call: MessageCall<Call>,
+}
+
+/// Call(s) of the message.
+#[derive(RuntimeDebug, Encode, Decode, Clone, PartialEq, Eq)]
+pub enum MessageCall<Call> {
+	/// Single call.
+	Single(Call),
+	/// Batch of calls that are dispatched in order, from the same origin. Either all calls
+	/// succeed, or changes of all calls are reverted. Calls of the batch are not adjusted by the
+	/// `CallWeightAdjuster` and failed batches are never stored for later retry.
+	Batch(Vec<Call>),
+}
+
+impl<Call> MessageCall<Call> {
+	/// Returns all calls of the message.
+	pub fn calls(&self) -> &[Call] {
+		match *self {
+			MessageCall::Single(ref call) => sp_std::slice::from_ref(call),
+			MessageCall::Batch(ref calls) => calls,
+		}
+	}
+
+	/// Converts every call of the message using given function.
+	pub fn map<R>(self, mut f: impl FnMut(Call) -> R) -> MessageCall<R> {
+		match self {
+			MessageCall::Single(call) => MessageCall::Single(f(call)),
+			MessageCall::Batch(calls) => MessageCall::Batch(calls.into_iter().map(f).collect()),
+		}
+	}
+}
+
+/// Message call that has failed to dispatch and may be retried later.
+#[derive(RuntimeDebug, Encode, Decode, Clone, PartialEq, Eq)]
+pub struct FailedCall<AccountId, Call> {
+	/// Account that the call is dispatched from.
+	pub origin: AccountId,
+	/// Weight of the call, declared by the message sender.
+	pub weight: Weight,
+	/// The call itself (adjusted by the `CallWeightAdjuster`).
 	pub call: Call,
 }
 
+/// Adjusts the call before it is dispatched, given the weight that has been declared (and paid for)
+/// by the message sender.
+///
+/// Some calls (e.g. calls to smart contracts) are accepting weight or gas limit as an argument. The
+/// message sender can't always compute this limit at the source chain, so this hook may be used to
+/// derive it from the declared dispatch weight.
+pub trait AdjustCallWeight<Call> {
+	/// Returns adjusted call that will be dispatched instead of the original call.
+	fn adjust_call(call: Call, declared_weight: Weight) -> Call;
+}
+
+impl<Call> AdjustCallWeight<Call> for () {
+	fn adjust_call(call: Call, _declared_weight: Weight) -> Call {
+		call
+	}
+}
+
 /// The module configuration trait.
 pub trait Trait<I = DefaultInstance>: frame_system::Trait {
 	/// The overarching event type.
@@ -103,16 +199,53 @@ pub trait Trait<I = DefaultInstance>: frame_system::Trait {
 			Origin = <Self as frame_system::Trait>::Origin,
 			PostInfo = frame_support::dispatch::PostDispatchInfo,
 		>;
+	/// Adjusts the call using weight that has been declared by the message sender. The origin
+	/// proof is still verified against the original (non-adjusted) call.
+	type CallWeightAdjuster: AdjustCallWeight<<Self as Trait<I>>::Call>;
+	/// If true, message calls that have failed to dispatch are stored in the runtime storage
+	/// and may be retried later using `retry_failed_call`. Otherwise, dispatch failure is final.
+	type StoreFailedCalls: Get<bool>;
+	/// Currency that is used to fund sovereign accounts of bridged chains.
+	type Currency: Currency<Self::AccountId>;
+	/// If dispatch of the message from the sovereign account fails and the free balance of the
+	/// account is below this value, the `SovereignAccountUnderfunded` event is deposited.
+	type MinSovereignAccountBalance: Get<BalanceOf<Self, I>>;
 }
 
 decl_storage! {
 	trait Store for Module<T: Trait<I>, I: Instance = DefaultInstance> as CallDispatch {
+		/// Map of (bridge instance, source chain account) => nonce, that must be included in the
+		/// `CallOrigin::RealAccount` origin proof of the next message from this account.
+		pub SourceAccountNonces get(fn source_account_nonce): double_map
+			hasher(blake2_128_concat) InstanceId,
+			hasher(blake2_128_concat) T::SourceChainAccountPublic
+			=> SourceAccountNonce;
+		/// Map of (bridge instance, message id) => message call that has failed to dispatch. Only
+		/// filled if `StoreFailedCalls` is enabled.
+		pub FailedCalls get(fn failed_call): double_map
+			hasher(blake2_128_concat) InstanceId,
+			hasher(blake2_128_concat) T::MessageId
+			=> Option<FailedCall<T::AccountId, <T as Trait<I>>::Call>>;
+	}
+}
+
+decl_error! {
+	pub enum Error for Module<T: Trait<I>, I: Instance> {
+		/// There's no stored failed call for given message.
+		FailedCallNotFound,
+		/// Declared weight is less than the weight of the stored failed call.
+		InvalidCallWeight,
+		/// Nothing has been minted when endowing the sovereign account (e.g. because the amount
+		/// is below the existential deposit).
+		SovereignAccountNotEndowed,
 	}
 }
 
 decl_event!(
 	pub enum Event<T, I = DefaultInstance> where
-		<T as Trait<I>>::MessageId
+		<T as Trait<I>>::MessageId,
+		AccountId = <T as frame_system::Trait>::AccountId,
+		Balance = BalanceOf<T, I>,
 	{
 		/// Message has been rejected by dispatcher because of spec version mismatch.
 		/// Last two arguments are: expected and passed spec version.
@@ -124,6 +257,13 @@ decl_event!(
 		MessageSignatureMismatch(InstanceId, MessageId),
 		/// Message has been dispatched with given result.
 		MessageDispatched(InstanceId, MessageId, DispatchResult),
+		/// Message call has failed to dispatch and has been stored, so it may be retried later.
+		MessageCallStored(InstanceId, MessageId),
+		/// Sovereign account of the bridged chain has been funded with given amount.
+		SovereignAccountFunded(InstanceId, AccountId, Balance),
+		/// Message from the sovereign account has failed to dispatch and the free balance of the
+		/// sovereign account is below `MinSovereignAccountBalance`. Last argument is the free balance.
+		SovereignAccountUnderfunded(InstanceId, MessageId, AccountId, Balance),
 		/// Phantom member, never used.
 		Dummy(PhantomData<I>),
 	}
@@ -134,6 +274,79 @@ decl_module! {
 	pub struct Module<T: Trait<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
 		/// Deposit one of this module's events by using the default implementation.
 		fn deposit_event() = default;
+
+		type Error = Error<T, I>;
+
+		/// Retry dispatch of the message call that has previously failed.
+		///
+		/// The call is dispatched from the same origin as the original call. The `weight` must be
+		/// at least equal to the weight, declared by the message sender. If dispatch fails again,
+		/// the call stays in the storage and may be retried later.
+		#[weight = RETRY_BASE_WEIGHT + weight]
+		pub fn retry_failed_call(origin, bridge: InstanceId, id: T::MessageId, weight: Weight) -> DispatchResult {
+			let _ = ensure_signed(origin)?;
+
+			let failed_call = FailedCalls::<T, I>::get(bridge, &id).ok_or(Error::<T, I>::FailedCallNotFound)?;
+			if weight < failed_call.weight {
+				return Err(Error::<T, I>::InvalidCallWeight.into());
+			}
+
+			let dispatch_result = failed_call.call.dispatch(RawOrigin::Signed(failed_call.origin).into());
+			frame_support::debug::trace!(
+				"Failed message {:?}/{:?} call has been retried. Result: {:?}",
+				bridge,
+				id,
+				dispatch_result,
+			);
+
+			if dispatch_result.is_ok() {
+				FailedCalls::<T, I>::remove(bridge, &id);
+			}
+
+			Self::deposit_event(RawEvent::MessageDispatched(
+				bridge,
+				id,
+				dispatch_result.map(drop).map_err(|e| e.error),
+			));
+
+			Ok(())
+		}
+
+		/// Endow sovereign account of the bridged chain with given amount. The amount is minted.
+		///
+		/// May only be called by root.
+		#[weight = T::DbWeight::get().reads_writes(1, 1)]
+		pub fn endow_sovereign_account(origin, bridge: InstanceId, amount: BalanceOf<T, I>) {
+			ensure_root(origin)?;
+
+			let sovereign_account = Self::sovereign_account(bridge);
+			let minted = T::Currency::deposit_creating(&sovereign_account, amount).peek();
+			ensure!(!minted.is_zero(), Error::<T, I>::SovereignAccountNotEndowed);
+			Self::deposit_event(RawEvent::SovereignAccountFunded(bridge, sovereign_account, minted));
+		}
+
+		/// Transfer given amount from the sender account to the sovereign account of the bridged chain.
+		#[weight = T::DbWeight::get().reads_writes(2, 2)]
+		pub fn top_up_sovereign_account(origin, bridge: InstanceId, amount: BalanceOf<T, I>) {
+			let sender = ensure_signed(origin)?;
+
+			let sovereign_account = Self::sovereign_account(bridge);
+			T::Currency::transfer(&sender, &sovereign_account, amount, ExistenceRequirement::KeepAlive)?;
+			Self::deposit_event(RawEvent::SovereignAccountFunded(bridge, sovereign_account, amount));
+		}
+	}
+}
+
+impl<T: Trait<I>, I: Instance> Module<T, I> {
+	/// Returns sovereign account of the bridged chain in given bridge instance. This is the account
+	/// that is used to dispatch messages with the `CallOrigin::BridgeAccount` origin.
+	pub fn sovereign_account(bridge: InstanceId) -> T::AccountId {
+		derive_account_id(bridge, SourceAccount::<T::SourceChainAccountPublic>::Root)
+	}
+
+	/// Returns free balance of the sovereign account of the bridged chain in given bridge instance.
+	pub fn sovereign_account_balance(bridge: InstanceId) -> BalanceOf<T, I> {
+		T::Currency::free_balance(&Self::sovereign_account(bridge))
 	}
 }
 
@@ -171,10 +384,22 @@ impl<T: Trait<I>, I: Instance> MessageDispatch<T::MessageId> for Module<T, I> {
 		}
 
 		// verify weight
-		// (we want passed weight to be at least equal to pre-dispatch weight of the call
+		// (we want passed weight to be at least equal to pre-dispatch weight of all calls
 		// because otherwise Calls may be dispatched at lower price)
-		let dispatch_info = message.call.get_dispatch_info();
-		let expected_weight = dispatch_info.weight;
+		let call = match message.call {
+			MessageCall::Single(ref call) => {
+				MessageCall::Single(T::CallWeightAdjuster::adjust_call(call.clone(), message.weight))
+			}
+			MessageCall::Batch(ref calls) => MessageCall::Batch(calls.clone()),
+		};
+		let dispatch_infos = call
+			.calls()
+			.iter()
+			.map(|call| call.get_dispatch_info())
+			.collect::<Vec<_>>();
+		let expected_weight = dispatch_infos.iter().fold(0, |total: Weight, dispatch_info| {
+			total.saturating_add(dispatch_info.weight)
+		});
 		if message.weight < expected_weight {
 			frame_support::debug::trace!(
 				"Message {:?}/{:?}: passed weight is too low. Expected at least {:?}, got {:?}",
@@ -193,12 +418,15 @@ impl<T: Trait<I>, I: Instance> MessageDispatch<T::MessageId> for Module<T, I> {
 		}
 
 		// prepare dispatch origin
+		let is_sovereign_origin = matches!(message.origin, CallOrigin::BridgeAccount);
 		let origin_account = match message.origin {
-			CallOrigin::BridgeAccount => bridge_account_id(bridge, CALL_DISPATCH_MODULE_PREFIX),
+			CallOrigin::BridgeAccount => Self::sovereign_account(bridge),
+			CallOrigin::SourceAccount(source_public) => {
+				derive_account_id(bridge, SourceAccount::Account(source_public))
+			}
 			CallOrigin::RealAccount(source_public, target_public, target_signature) => {
-				let mut signed_message = Vec::new();
-				message.call.encode_to(&mut signed_message);
-				source_public.encode_to(&mut signed_message);
+				let nonce = SourceAccountNonces::<T, I>::get(bridge, &source_public);
+				let signed_message = real_account_signature_payload(&message.call, &source_public, bridge, nonce);
 
 				let target_account = target_public.into_account();
 				if !target_signature.verify(&signed_message[..], &target_account) {
@@ -213,14 +441,18 @@ impl<T: Trait<I>, I: Instance> MessageDispatch<T::MessageId> for Module<T, I> {
 					return;
 				}
 
+				SourceAccountNonces::<T, I>::insert(bridge, &source_public, nonce + 1);
 				target_account
 			}
 		};
 
 		// finally dispatch message
-		let origin = RawOrigin::Signed(origin_account).into();
-		let dispatch_result = message.call.dispatch(origin);
-		let actual_call_weight = extract_actual_weight(&dispatch_result, &dispatch_info);
+		let failed_call = match call {
+			MessageCall::Single(ref call) if T::StoreFailedCalls::get() => Some(call.clone()),
+			_ => None,
+		};
+		let (dispatch_result, actual_call_weight) =
+			dispatch_message_call::<T, I>(origin_account.clone(), call, &dispatch_infos);
 		frame_support::debug::trace!(
 			"Message {:?}/{:?} has been dispatched. Weight: {} of {}. Result: {:?}",
 			bridge,
@@ -230,14 +462,92 @@ impl<T: Trait<I>, I: Instance> MessageDispatch<T::MessageId> for Module<T, I> {
 			dispatch_result,
 		);
 
-		Self::deposit_event(RawEvent::MessageDispatched(
-			bridge,
-			id,
-			dispatch_result.map(drop).map_err(|e| e.error),
-		));
+		let is_dispatch_failed = dispatch_result.is_err();
+		Self::deposit_event(RawEvent::MessageDispatched(bridge, id.clone(), dispatch_result));
+
+		// let operators know that the sovereign account needs to be funded
+		if is_dispatch_failed && is_sovereign_origin {
+			let free_balance = T::Currency::free_balance(&origin_account);
+			if free_balance < T::MinSovereignAccountBalance::get() {
+				frame_support::debug::trace!(
+					"Message {:?}/{:?} has failed to dispatch from underfunded sovereign account {:?}: {:?}",
+					bridge,
+					id,
+					origin_account,
+					free_balance,
+				);
+				Self::deposit_event(RawEvent::SovereignAccountUnderfunded(
+					bridge,
+					id.clone(),
+					origin_account.clone(),
+					free_balance,
+				));
+			}
+		}
+
+		// store failed call, so it may be retried later
+		if let Some(failed_call) = failed_call.filter(|_| is_dispatch_failed) {
+			FailedCalls::<T, I>::insert(
+				bridge,
+				&id,
+				FailedCall {
+					origin: origin_account,
+					weight: message.weight,
+					call: failed_call,
+				},
+			);
+			Self::deposit_event(RawEvent::MessageCallStored(bridge, id));
+		}
 	}
 }
 
+/// Dispatch message call(s) from given account. Returns dispatch result and actual weight of the
+/// dispatched calls.
+///
+/// Calls of the batch are dispatched within single storage transaction, which is reverted (with
+/// all events, deposited by the calls) if any call fails.
+fn dispatch_message_call<T: Trait<I>, I: Instance>(
+	origin_account: T::AccountId,
+	call: MessageCall<<T as Trait<I>>::Call>,
+	dispatch_infos: &[DispatchInfo],
+) -> (DispatchResult, Weight) {
+	match call {
+		MessageCall::Single(call) => {
+			let dispatch_result = call.dispatch(RawOrigin::Signed(origin_account).into());
+			let actual_weight = extract_actual_weight(&dispatch_result, &dispatch_infos[0]);
+			(dispatch_result.map(drop).map_err(|e| e.error), actual_weight)
+		}
+		MessageCall::Batch(calls) => with_transaction(|| {
+			let mut actual_weight: Weight = 0;
+			for (index, (call, dispatch_info)) in calls.into_iter().zip(dispatch_infos).enumerate() {
+				let dispatch_result = call.dispatch(RawOrigin::Signed(origin_account.clone()).into());
+				actual_weight = actual_weight.saturating_add(extract_actual_weight(&dispatch_result, dispatch_info));
+				if let Err(e) = dispatch_result {
+					frame_support::debug::trace!(
+						"Call #{} of the batch has failed: {:?}. Reverting all calls of the batch",
+						index,
+						e.error,
+					);
+					return TransactionOutcome::Rollback((Err(e.error), actual_weight));
+				}
+			}
+
+			TransactionOutcome::Commit((Ok(()), actual_weight))
+		}),
+	}
+}
+
+/// Returns payload that must be signed by the target chain account to prove that the source chain
+/// account controls it (see `CallOrigin::RealAccount`).
+pub fn real_account_signature_payload<Call: Encode, SourceChainAccountPublic: Encode>(
+	call: &Call,
+	source_public: &SourceChainAccountPublic,
+	bridge: InstanceId,
+	nonce: SourceAccountNonce,
+) -> Vec<u8> {
+	(call, source_public, bridge, nonce).encode()
+}
+
 /// Verify payload of the message at the sending side.
 pub fn verify_sending_message<
 	ThisChainOuterOrigin,
@@ -260,7 +570,7 @@ where
 			ensure_root(sender_origin)?;
 			Ok(None)
 		}
-		CallOrigin::RealAccount(ref this_account_public, _, _) => {
+		CallOrigin::SourceAccount(ref this_account_public) | CallOrigin::RealAccount(ref this_account_public, _, _) => {
 			let this_chain_account_id = ensure_signed(sender_origin)?;
 			if this_chain_account_id != this_account_public.clone().into_account() {
 				return Err(BadOrigin);
@@ -274,7 +584,9 @@ where
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use frame_support::{impl_outer_dispatch, impl_outer_event, impl_outer_origin, parameter_types, weights::Weight};
+	use frame_support::{
+		assert_ok, impl_outer_dispatch, impl_outer_event, impl_outer_origin, parameter_types, weights::Weight,
+	};
 	use frame_system::{EventRecord, Phase};
 	use sp_core::H256;
 	use sp_runtime::{
@@ -286,6 +598,7 @@ mod tests {
 	type AccountId = u64;
 	type CallDispatch = Module<TestRuntime>;
 	type System = frame_system::Module<TestRuntime>;
+	type Balances = pallet_balances::Module<TestRuntime>;
 
 	type MessageId = [u8; 4];
 
@@ -321,6 +634,7 @@ mod tests {
 	impl_outer_event! {
 		pub enum TestEvent for TestRuntime {
 			frame_system<T>,
+			pallet_balances<T>,
 			call_dispatch<T>,
 		}
 	}
@@ -341,6 +655,7 @@ mod tests {
 		pub const MaximumBlockWeight: Weight = 1024;
 		pub const MaximumBlockLength: u32 = 2 * 1024;
 		pub const AvailableBlockRatio: Perbill = Perbill::one();
+		pub const ExistentialDeposit: u64 = 1;
 	}
 
 	impl frame_system::Trait for TestRuntime {
@@ -364,13 +679,23 @@ mod tests {
 		type MaximumBlockLength = MaximumBlockLength;
 		type Version = ();
 		type PalletInfo = ();
-		type AccountData = ();
+		type AccountData = pallet_balances::AccountData<u64>;
 		type OnNewAccount = ();
 		type OnKilledAccount = ();
 		type BaseCallFilter = ();
 		type SystemWeightInfo = ();
 	}
 
+	impl pallet_balances::Trait for TestRuntime {
+		type MaxLocks = ();
+		type Balance = u64;
+		type DustRemoval = ();
+		type Event = TestEvent;
+		type ExistentialDeposit = ExistentialDeposit;
+		type AccountStore = System;
+		type WeightInfo = ();
+	}
+
 	impl Trait for TestRuntime {
 		type Event = TestEvent;
 		type MessageId = MessageId;
@@ -378,6 +703,40 @@ mod tests {
 		type TargetChainAccountPublic = TestAccountPublic;
 		type TargetChainSignature = TestSignature;
 		type Call = Call;
+		type CallWeightAdjuster = ();
+		type StoreFailedCalls = StoreFailedCalls;
+		type Currency = Balances;
+		type MinSovereignAccountBalance = MinSovereignAccountBalance;
+	}
+
+	/// Failed calls are only stored in tests that have called `StoreFailedCalls::enable()`.
+	pub struct StoreFailedCalls;
+
+	impl StoreFailedCalls {
+		fn enable() {
+			frame_support::storage::unhashed::put(b":store-failed-calls:", &true);
+		}
+	}
+
+	impl Get<bool> for StoreFailedCalls {
+		fn get() -> bool {
+			frame_support::storage::unhashed::get(b":store-failed-calls:").unwrap_or(false)
+		}
+	}
+
+	/// Minimal sovereign account balance is zero, unless test has called `MinSovereignAccountBalance::set()`.
+	pub struct MinSovereignAccountBalance;
+
+	impl MinSovereignAccountBalance {
+		fn set(balance: u64) {
+			frame_support::storage::unhashed::put(b":min-sovereign-account-balance:", &balance);
+		}
+	}
+
+	impl Get<u64> for MinSovereignAccountBalance {
+		fn get() -> u64 {
+			frame_support::storage::unhashed::get(b":min-sovereign-account-balance:").unwrap_or(0)
+		}
 	}
 
 	const TEST_SPEC_VERSION: SpecVersion = 0;
@@ -397,7 +756,18 @@ mod tests {
 			spec_version: TEST_SPEC_VERSION,
 			weight: TEST_WEIGHT,
 			origin: CallOrigin::BridgeAccount,
-			call,
+			call: MessageCall::Single(call),
+		}
+	}
+
+	fn prepare_bridge_batch_message(
+		calls: Vec<Call>,
+	) -> <Module<TestRuntime> as MessageDispatch<<TestRuntime as Trait>::MessageId>>::Message {
+		MessagePayload {
+			spec_version: TEST_SPEC_VERSION,
+			weight: TEST_WEIGHT,
+			origin: CallOrigin::BridgeAccount,
+			call: MessageCall::Batch(calls),
 		}
 	}
 
@@ -540,11 +910,16 @@ mod tests {
 			let real_account_id = 1;
 			System::inc_account_nonce(real_account_id);
 			// 'create' bridge account
-			let bridge_account_id: AccountId = bridge_account_id(*b"ethb", CALL_DISPATCH_MODULE_PREFIX);
+			let bridge_account_id: AccountId = derive_account_id(*b"ethb", SourceAccount::<TestAccountPublic>::Root);
 			System::inc_account_nonce(bridge_account_id);
+			// 'create' derived account
+			let source_account_id: AccountId =
+				derive_account_id(*b"ethb", SourceAccount::Account(TestAccountPublic(real_account_id)));
+			System::inc_account_nonce(source_account_id);
 
 			assert_eq!(System::account_nonce(real_account_id), 1);
 			assert_eq!(System::account_nonce(bridge_account_id), 1);
+			assert_eq!(System::account_nonce(source_account_id), 1);
 
 			// kill real account
 			dispatch_suicide(CallOrigin::RealAccount(
@@ -555,6 +930,12 @@ mod tests {
 			assert_eq!(System::account_nonce(real_account_id), 0);
 			assert_eq!(System::account_nonce(bridge_account_id), 1);
 
+			// kill derived account
+			dispatch_suicide(CallOrigin::SourceAccount(TestAccountPublic(real_account_id)));
+			assert_eq!(System::account_nonce(real_account_id), 0);
+			assert_eq!(System::account_nonce(bridge_account_id), 1);
+			assert_eq!(System::account_nonce(source_account_id), 0);
+
 			// kill bridge account
 			dispatch_suicide(CallOrigin::BridgeAccount);
 			assert_eq!(System::account_nonce(real_account_id), 0);
@@ -562,6 +943,356 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn source_account_nonce_is_incremented_when_origin_proof_is_accepted() {
+		new_test_ext().execute_with(|| {
+			let dispatch_remark = |bridge: InstanceId, call_origin| {
+				let mut message =
+					prepare_bridge_message(Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3])));
+				message.origin = call_origin;
+				CallDispatch::dispatch(bridge, [0; 4], message);
+			};
+			let real_account =
+				|signer| CallOrigin::RealAccount(TestAccountPublic(1), TestAccountPublic(1), TestSignature(signer));
+
+			// messages from bridge account are not changing nonces
+			dispatch_remark(*b"ethb", CallOrigin::BridgeAccount);
+			assert_eq!(CallDispatch::source_account_nonce(*b"ethb", TestAccountPublic(1)), 0);
+
+			// invalid origin proof is not changing nonce
+			dispatch_remark(*b"ethb", real_account(2));
+			assert_eq!(CallDispatch::source_account_nonce(*b"ethb", TestAccountPublic(1)), 0);
+
+			// valid origin proof increments nonce of given bridge instance only
+			dispatch_remark(*b"ethb", real_account(1));
+			dispatch_remark(*b"ethb", real_account(1));
+			dispatch_remark(*b"subb", real_account(1));
+			assert_eq!(CallDispatch::source_account_nonce(*b"ethb", TestAccountPublic(1)), 2);
+			assert_eq!(CallDispatch::source_account_nonce(*b"subb", TestAccountPublic(1)), 1);
+		});
+	}
+
+	#[test]
+	fn real_account_signature_payload_includes_bridge_and_nonce() {
+		let call = Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3]));
+		let payload = real_account_signature_payload(&call, &TestAccountPublic(1), *b"ethb", 0);
+
+		assert_eq!(payload, (call.clone(), TestAccountPublic(1), *b"ethb", 0u64).encode());
+		assert_ne!(
+			payload,
+			real_account_signature_payload(&call, &TestAccountPublic(1), *b"ethb", 1)
+		);
+		assert_ne!(
+			payload,
+			real_account_signature_payload(&call, &TestAccountPublic(1), *b"subb", 0)
+		);
+	}
+
+	#[test]
+	fn failed_call_is_not_stored_if_disabled() {
+		new_test_ext().execute_with(|| {
+			let message = prepare_bridge_message(Call::System(<frame_system::Call<TestRuntime>>::fill_block(
+				Perbill::from_percent(10),
+			)));
+
+			CallDispatch::dispatch(*b"ethb", [0; 4], message);
+
+			assert_eq!(CallDispatch::failed_call(*b"ethb", [0; 4]), None);
+		});
+	}
+
+	#[test]
+	fn failed_call_is_stored_and_may_be_retried() {
+		new_test_ext().execute_with(|| {
+			StoreFailedCalls::enable();
+
+			// suicide fails while bridge account is referenced
+			let origin = b"ethb".to_owned();
+			let id = [0; 4];
+			let bridge_account_id: AccountId = derive_account_id(origin, SourceAccount::<TestAccountPublic>::Root);
+			System::inc_ref(&bridge_account_id);
+			let call = Call::System(<frame_system::Call<TestRuntime>>::suicide());
+			let message = prepare_bridge_message(call.clone());
+
+			System::set_block_number(1);
+			CallDispatch::dispatch(origin, id, message.clone());
+
+			assert_eq!(
+				CallDispatch::failed_call(origin, id),
+				Some(FailedCall {
+					origin: bridge_account_id,
+					weight: TEST_WEIGHT,
+					call,
+				}),
+			);
+			assert_eq!(
+				System::events().last().map(|record| record.event.clone()),
+				Some(TestEvent::call_dispatch(Event::<TestRuntime>::MessageCallStored(
+					origin, id
+				))),
+			);
+
+			// retry with too low weight is rejected
+			assert_eq!(
+				CallDispatch::retry_failed_call(Origin::signed(1), origin, id, TEST_WEIGHT - 1),
+				Err(Error::<TestRuntime, DefaultInstance>::InvalidCallWeight.into()),
+			);
+
+			// retry fails again, so call is kept in the storage
+			assert_eq!(
+				CallDispatch::retry_failed_call(Origin::signed(1), origin, id, TEST_WEIGHT),
+				Ok(()),
+			);
+			assert!(CallDispatch::failed_call(origin, id).is_some());
+
+			// retry succeeds when failure reason is gone
+			System::dec_ref(&bridge_account_id);
+			assert_eq!(
+				CallDispatch::retry_failed_call(Origin::signed(1), origin, id, TEST_WEIGHT),
+				Ok(()),
+			);
+			assert_eq!(CallDispatch::failed_call(origin, id), None);
+			assert_eq!(
+				System::events().last().map(|record| record.event.clone()),
+				Some(TestEvent::call_dispatch(Event::<TestRuntime>::MessageDispatched(
+					origin,
+					id,
+					Ok(())
+				))),
+			);
+
+			// and now there's nothing to retry
+			assert_eq!(
+				CallDispatch::retry_failed_call(Origin::signed(1), origin, id, TEST_WEIGHT),
+				Err(Error::<TestRuntime, DefaultInstance>::FailedCallNotFound.into()),
+			);
+		});
+	}
+
+	#[test]
+	fn batch_is_dispatched_if_all_calls_succeed() {
+		new_test_ext().execute_with(|| {
+			let origin = b"ethb".to_owned();
+			let id = [0; 4];
+			let message = prepare_bridge_batch_message(vec![
+				Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3])),
+				Call::System(<frame_system::Call<TestRuntime>>::remark(vec![4, 5, 6])),
+			]);
+
+			System::set_block_number(1);
+			CallDispatch::dispatch(origin, id, message);
+
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::call_dispatch(Event::<TestRuntime>::MessageDispatched(origin, id, Ok(()))),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn batch_is_reverted_if_any_call_fails() {
+		new_test_ext().execute_with(|| {
+			StoreFailedCalls::enable();
+
+			// first call kills the bridge account (so its nonce is reset) and the second call fails,
+			// because it requires root origin => changes of the first call must be reverted
+			let origin = b"ethb".to_owned();
+			let id = [0; 4];
+			let bridge_account_id: AccountId = derive_account_id(origin, SourceAccount::<TestAccountPublic>::Root);
+			System::inc_account_nonce(bridge_account_id);
+			let message = prepare_bridge_batch_message(vec![
+				Call::System(<frame_system::Call<TestRuntime>>::suicide()),
+				Call::System(<frame_system::Call<TestRuntime>>::set_heap_pages(1)),
+			]);
+
+			System::set_block_number(1);
+			CallDispatch::dispatch(origin, id, message);
+
+			assert_eq!(System::account_nonce(bridge_account_id), 1);
+			assert!(matches!(
+				System::events().last().map(|record| record.event.clone()),
+				Some(TestEvent::call_dispatch(Event::<TestRuntime>::MessageDispatched(
+					_,
+					_,
+					Err(_)
+				))),
+			));
+			// failed batches are not stored
+			assert_eq!(CallDispatch::failed_call(origin, id), None);
+
+			// when all calls succeed, changes of all calls are applied
+			let message = prepare_bridge_batch_message(vec![
+				Call::System(<frame_system::Call<TestRuntime>>::suicide()),
+				Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3])),
+			]);
+			CallDispatch::dispatch(origin, id, message);
+
+			assert_eq!(System::account_nonce(bridge_account_id), 0);
+			assert_eq!(
+				System::events().last().map(|record| record.event.clone()),
+				Some(TestEvent::call_dispatch(Event::<TestRuntime>::MessageDispatched(
+					origin,
+					id,
+					Ok(())
+				))),
+			);
+		});
+	}
+
+	#[test]
+	fn batch_is_not_dispatched_if_aggregate_weight_is_too_low() {
+		new_test_ext().execute_with(|| {
+			let origin = b"ethb".to_owned();
+			let id = [0; 4];
+			let remark = Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3]));
+			let remark_weight = remark.get_dispatch_info().weight;
+			let mut message = prepare_bridge_batch_message(vec![remark.clone(), remark]);
+			message.weight = remark_weight * 2 - 1;
+
+			System::set_block_number(1);
+			CallDispatch::dispatch(origin, id, message);
+
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::call_dispatch(Event::<TestRuntime>::MessageWeightMismatch(
+						origin,
+						id,
+						remark_weight * 2,
+						remark_weight * 2 - 1,
+					)),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn sovereign_account_is_endowed_by_root() {
+		new_test_ext().execute_with(|| {
+			let sovereign_account = CallDispatch::sovereign_account(*b"ethb");
+			assert_eq!(
+				sovereign_account,
+				derive_account_id::<AccountId, TestAccountPublic>(*b"ethb", SourceAccount::Root)
+			);
+
+			System::set_block_number(1);
+			assert_eq!(
+				CallDispatch::endow_sovereign_account(Origin::signed(1), *b"ethb", 100),
+				Err(DispatchError::BadOrigin),
+			);
+			assert_eq!(
+				CallDispatch::endow_sovereign_account(Origin::root(), *b"ethb", 100),
+				Ok(())
+			);
+			assert_eq!(CallDispatch::sovereign_account_balance(*b"ethb"), 100);
+			assert_eq!(
+				System::events().last().map(|record| record.event.clone()),
+				Some(TestEvent::call_dispatch(Event::<TestRuntime>::SovereignAccountFunded(
+					*b"ethb",
+					sovereign_account,
+					100,
+				))),
+			);
+			assert_eq!(CallDispatch::sovereign_account_balance(*b"subb"), 0);
+		});
+	}
+
+	#[test]
+	fn sovereign_account_is_not_endowed_if_nothing_is_minted() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			assert_eq!(
+				CallDispatch::endow_sovereign_account(Origin::root(), *b"ethb", 0),
+				Err(Error::<TestRuntime, DefaultInstance>::SovereignAccountNotEndowed.into()),
+			);
+			assert_eq!(CallDispatch::sovereign_account_balance(*b"ethb"), 0);
+			assert!(System::events().is_empty());
+		});
+	}
+
+	#[test]
+	fn sovereign_account_is_topped_up_by_anyone() {
+		new_test_ext().execute_with(|| {
+			let _ = Balances::deposit_creating(&1, 100);
+
+			System::set_block_number(1);
+			assert_eq!(
+				CallDispatch::top_up_sovereign_account(Origin::signed(1), *b"ethb", 60),
+				Ok(())
+			);
+			assert_eq!(CallDispatch::sovereign_account_balance(*b"ethb"), 60);
+			assert_eq!(Balances::free_balance(1), 40);
+			assert_eq!(
+				System::events().last().map(|record| record.event.clone()),
+				Some(TestEvent::call_dispatch(Event::<TestRuntime>::SovereignAccountFunded(
+					*b"ethb",
+					CallDispatch::sovereign_account(*b"ethb"),
+					60,
+				))),
+			);
+
+			// sender account is kept alive
+			assert!(CallDispatch::top_up_sovereign_account(Origin::signed(1), *b"ethb", 40).is_err());
+			assert_eq!(CallDispatch::sovereign_account_balance(*b"ethb"), 60);
+		});
+	}
+
+	#[test]
+	fn underfunded_sovereign_account_is_reported_when_dispatch_fails() {
+		new_test_ext().execute_with(|| {
+			MinSovereignAccountBalance::set(10);
+
+			let origin = b"ethb".to_owned();
+			let id = [0; 4];
+			let sovereign_account = CallDispatch::sovereign_account(origin);
+			let failing_message = || {
+				prepare_bridge_message(Call::System(<frame_system::Call<TestRuntime>>::fill_block(
+					Perbill::from_percent(10),
+				)))
+			};
+			let underfunded_event = |balance| {
+				TestEvent::call_dispatch(Event::<TestRuntime>::SovereignAccountUnderfunded(
+					origin,
+					id,
+					sovereign_account,
+					balance,
+				))
+			};
+
+			// dispatch fails and sovereign account has no funds => event is deposited
+			System::set_block_number(1);
+			CallDispatch::dispatch(origin, id, failing_message());
+			assert_eq!(
+				System::events().last().map(|record| record.event.clone()),
+				Some(underfunded_event(0)),
+			);
+
+			// dispatch fails, but sovereign account has enough funds => no event
+			System::reset_events();
+			assert_ok!(CallDispatch::endow_sovereign_account(Origin::root(), origin, 10));
+			CallDispatch::dispatch(origin, id, failing_message());
+			assert!(!System::events()
+				.iter()
+				.any(|record| record.event == underfunded_event(10)));
+
+			// dispatch from other accounts is never reported
+			System::reset_events();
+			let mut message = failing_message();
+			message.origin = CallOrigin::SourceAccount(TestAccountPublic(1));
+			CallDispatch::dispatch(origin, id, message);
+			assert!(!System::events().iter().any(|record| matches!(
+				record.event,
+				TestEvent::call_dispatch(Event::<TestRuntime>::SovereignAccountUnderfunded(..))
+			)));
+		});
+	}
+
 	#[test]
 	fn origin_is_checked_when_verify_sending_message() {
 		let mut message = prepare_bridge_message(Call::System(<frame_system::Call<TestRuntime>>::suicide()));
@@ -596,5 +1327,20 @@ mod tests {
 			verify_sending_message(Origin::from(RawOrigin::Signed(2)), &message),
 			Ok(Some(2))
 		));
+
+		// CallOrigin::SourceAccount is only allowed when message is sent by the same account
+		message.origin = CallOrigin::SourceAccount(TestAccountPublic(2));
+		assert!(matches!(
+			verify_sending_message(Origin::from(RawOrigin::Root), &message),
+			Err(BadOrigin)
+		));
+		assert!(matches!(
+			verify_sending_message(Origin::from(RawOrigin::Signed(1)), &message),
+			Err(BadOrigin)
+		));
+		assert!(matches!(
+			verify_sending_message(Origin::from(RawOrigin::Signed(2)), &message),
+			Ok(Some(2))
+		));
 	}
 }
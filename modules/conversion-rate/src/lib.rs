@@ -0,0 +1,366 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime module that stores conversion rate between tokens of the bridged chain and tokens of
+//! this chain. The rate is used to compute fees of the messages that are sent to the bridged chain.
+//!
+//! The rate is fed by whitelisted feeders (normally relayers, who are interested in having the
+//! actual rate). If several feeders are whitelisted, the rate is the median of fresh feeds. If
+//! the rate hasn't been updated for `MaxRateAge` blocks, it is considered stale and no longer
+//! returned by `conversion_rate()`, so operations that depend on the rate must be paused until
+//! feeders provide the new value.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+use bp_currency_exchange::{Error as ExchangeError, PriceProvider};
+use frame_support::{decl_error, decl_event, decl_module, decl_storage, ensure, traits::Get, weights::DispatchClass};
+use frame_system::{ensure_root, ensure_signed};
+use sp_runtime::{
+	traits::{Saturating, Zero},
+	FixedPointNumber, FixedU128,
+};
+use sp_std::{marker::PhantomData, prelude::*};
+
+/// The module configuration trait.
+pub trait Trait<I = DefaultInstance>: frame_system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self, I>> + Into<<Self as frame_system::Trait>::Event>;
+	/// Maximal age (in blocks) of the conversion rate and of the individual feeds. Older rate is
+	/// considered stale.
+	type MaxRateAge: Get<Self::BlockNumber>;
+	/// Minimal number of fresh feeds that is required to update the conversion rate.
+	type MinFeeds: Get<u32>;
+}
+
+decl_error! {
+	pub enum Error for Module<T: Trait<I>, I: Instance> {
+		/// The account is not allowed to feed conversion rate.
+		NotFeeder,
+		/// Conversion rate must be positive.
+		ZeroConversionRate,
+	}
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait<I>, I: Instance = DefaultInstance> as BridgeConversionRate {
+		/// Accounts that are allowed to feed conversion rate.
+		pub Feeders get(fn feeders) config(): Vec<T::AccountId>;
+		/// Map of feeder => last conversion rate and number of block where it has been fed.
+		pub Feeds: map hasher(blake2_128_concat) T::AccountId => Option<(FixedU128, T::BlockNumber)>;
+		/// Last conversion rate and number of block where it has been updated.
+		pub StoredConversionRate get(fn stored_conversion_rate): Option<(FixedU128, T::BlockNumber)>;
+	}
+	add_extra_genesis {
+		config(phantom): sp_std::marker::PhantomData<I>;
+		config(initial_rate): Option<FixedU128>;
+		build(|config| {
+			if let Some(initial_rate) = config.initial_rate {
+				StoredConversionRate::<T, I>::put((initial_rate, T::BlockNumber::zero()));
+			}
+		})
+	}
+}
+
+decl_event!(
+	pub enum Event<T, I = DefaultInstance> where
+		<T as frame_system::Trait>::AccountId,
+	{
+		/// Conversion rate has been updated.
+		ConversionRateUpdated(FixedU128),
+		/// Feeder has provided new conversion rate.
+		ConversionRateFed(AccountId, FixedU128),
+		/// Phantom member, never used.
+		Dummy(PhantomData<I>),
+	}
+);
+
+decl_module! {
+	/// Bridge conversion rate FRAME Pallet.
+	pub struct Module<T: Trait<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
+		type Error = Error<T, I>;
+
+		/// Deposit one of this module's events by using the default implementation.
+		fn deposit_event() = default;
+
+		/// Change set of accounts that are allowed to feed conversion rate. Feeds of removed
+		/// feeders are discarded.
+		///
+		/// May only be called by root.
+		#[weight = (T::DbWeight::get().reads_writes(1, 1 + feeders.len() as u64), DispatchClass::Operational)]
+		pub fn set_feeders(origin, feeders: Vec<T::AccountId>) {
+			ensure_root(origin)?;
+			for feeder in Feeders::<T, I>::get() {
+				if !feeders.contains(&feeder) {
+					Feeds::<T, I>::remove(&feeder);
+				}
+			}
+			Feeders::<T, I>::put(feeders);
+		}
+
+		/// Provide new conversion rate.
+		///
+		/// May only be called by one of `Feeders`.
+		#[weight = T::DbWeight::get().reads_writes(2 + T::MinFeeds::get() as u64, 2)]
+		pub fn feed_conversion_rate(origin, rate: FixedU128) {
+			let feeder = ensure_signed(origin)?;
+			let feeders = Feeders::<T, I>::get();
+			ensure!(feeders.contains(&feeder), Error::<T, I>::NotFeeder);
+			ensure!(rate != Zero::zero(), Error::<T, I>::ZeroConversionRate);
+
+			let now = frame_system::Module::<T>::block_number();
+			Feeds::<T, I>::insert(&feeder, (rate, now));
+			Self::deposit_event(RawEvent::ConversionRateFed(feeder, rate));
+
+			let fresh_rates = feeders
+				.iter()
+				.filter_map(|feeder| Feeds::<T, I>::get(feeder))
+				.filter(|(_, fed_at)| !is_stale::<T, I>(*fed_at, now))
+				.map(|(rate, _)| rate)
+				.collect::<Vec<_>>();
+			if fresh_rates.len() < T::MinFeeds::get() as usize {
+				return Ok(());
+			}
+
+			let rate = median(fresh_rates);
+			StoredConversionRate::<T, I>::put((rate, now));
+			Self::deposit_event(RawEvent::ConversionRateUpdated(rate));
+		}
+	}
+}
+
+impl<T: Trait<I>, I: Instance> Module<T, I> {
+	/// Return actual conversion rate. Returns `None` if the rate is unknown or stale.
+	///
+	/// The rate is the price of one bridged chain token in this chain tokens.
+	pub fn conversion_rate() -> Option<FixedU128> {
+		Self::stored_conversion_rate()
+			.filter(|(_, updated_at)| !is_stale::<T, I>(*updated_at, frame_system::Module::<T>::block_number()))
+			.map(|(rate, _)| rate)
+	}
+
+	/// Returns true if conversion rate is unknown or stale, i.e. all operations that depend on
+	/// the rate must be paused.
+	pub fn is_rate_stale() -> bool {
+		Self::conversion_rate().is_none()
+	}
+}
+
+impl<T: Trait<I>, I: Instance> PriceProvider for Module<T, I> {
+	fn price() -> bp_currency_exchange::Result<FixedU128> {
+		Self::conversion_rate().ok_or(ExchangeError::StalePrice)
+	}
+}
+
+/// Returns true if value that is updated at given block is stale.
+fn is_stale<T: Trait<I>, I: Instance>(updated_at: T::BlockNumber, now: T::BlockNumber) -> bool {
+	now.saturating_sub(updated_at) > T::MaxRateAge::get()
+}
+
+/// Compute median of given (non-empty) set of rates.
+fn median(mut rates: Vec<FixedU128>) -> FixedU128 {
+	rates.sort();
+	let middle = rates.len() / 2;
+	if rates.len() % 2 == 0 {
+		rates[middle - 1].saturating_add(rates[middle]) / FixedU128::saturating_from_integer(2)
+	} else {
+		rates[middle]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_support::{
+		assert_noop, assert_ok, impl_outer_event, impl_outer_origin, parameter_types, weights::Weight,
+	};
+	use sp_core::H256;
+	use sp_runtime::{
+		testing::Header,
+		traits::{BadOrigin, BlakeTwo256, IdentityLookup},
+		Perbill,
+	};
+
+	type AccountId = u64;
+	type ConversionRate = Module<TestRuntime>;
+	type System = frame_system::Module<TestRuntime>;
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct TestRuntime;
+
+	mod conversion_rate {
+		pub use crate::Event;
+	}
+
+	impl_outer_event! {
+		pub enum TestEvent for TestRuntime {
+			frame_system<T>,
+			conversion_rate<T>,
+		}
+	}
+
+	impl_outer_origin! {
+		pub enum Origin for TestRuntime where system = frame_system {}
+	}
+
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: Weight = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+		pub const MaxRateAge: u64 = 10;
+		pub const MinFeeds: u32 = 2;
+	}
+
+	impl frame_system::Trait for TestRuntime {
+		type Origin = Origin;
+		type Index = u64;
+		type Call = ();
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = AccountId;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = TestEvent;
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type DbWeight = ();
+		type BlockExecutionWeight = ();
+		type ExtrinsicBaseWeight = ();
+		type MaximumExtrinsicWeight = ();
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type MaximumBlockLength = MaximumBlockLength;
+		type Version = ();
+		type PalletInfo = ();
+		type AccountData = ();
+		type OnNewAccount = ();
+		type OnKilledAccount = ();
+		type BaseCallFilter = ();
+		type SystemWeightInfo = ();
+	}
+
+	impl Trait for TestRuntime {
+		type Event = TestEvent;
+		type MaxRateAge = MaxRateAge;
+		type MinFeeds = MinFeeds;
+	}
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let mut t = frame_system::GenesisConfig::default()
+			.build_storage::<TestRuntime>()
+			.unwrap();
+		GenesisConfig::<TestRuntime> {
+			feeders: vec![1, 2, 3],
+			phantom: Default::default(),
+			initial_rate: None,
+		}
+		.assimilate_storage(&mut t)
+		.unwrap();
+		sp_io::TestExternalities::new(t)
+	}
+
+	fn rate(n: u128, d: u128) -> FixedU128 {
+		FixedU128::saturating_from_rational(n, d)
+	}
+
+	#[test]
+	fn rate_is_not_fed_by_unknown_account() {
+		new_test_ext().execute_with(|| {
+			assert_noop!(
+				ConversionRate::feed_conversion_rate(Origin::signed(4), rate(1, 1)),
+				Error::<TestRuntime, DefaultInstance>::NotFeeder,
+			);
+			assert_noop!(
+				ConversionRate::feed_conversion_rate(Origin::signed(1), rate(0, 1)),
+				Error::<TestRuntime, DefaultInstance>::ZeroConversionRate,
+			);
+		});
+	}
+
+	#[test]
+	fn rate_is_median_of_fresh_feeds() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(ConversionRate::feed_conversion_rate(Origin::signed(1), rate(1, 1)));
+			assert_eq!(ConversionRate::conversion_rate(), None);
+
+			assert_ok!(ConversionRate::feed_conversion_rate(Origin::signed(2), rate(2, 1)));
+			assert_eq!(ConversionRate::conversion_rate(), Some(rate(3, 2)));
+
+			assert_ok!(ConversionRate::feed_conversion_rate(Origin::signed(3), rate(10, 1)));
+			assert_eq!(ConversionRate::conversion_rate(), Some(rate(2, 1)));
+
+			// feeds of 1 and 2 are stale now
+			System::set_block_number(12);
+			assert_ok!(ConversionRate::feed_conversion_rate(Origin::signed(3), rate(5, 1)));
+			assert_eq!(ConversionRate::conversion_rate(), None);
+
+			assert_ok!(ConversionRate::feed_conversion_rate(Origin::signed(1), rate(3, 1)));
+			assert_eq!(ConversionRate::conversion_rate(), Some(rate(4, 1)));
+		});
+	}
+
+	#[test]
+	fn rate_becomes_stale() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(ConversionRate::feed_conversion_rate(Origin::signed(1), rate(1, 1)));
+			assert_ok!(ConversionRate::feed_conversion_rate(Origin::signed(2), rate(1, 1)));
+			assert!(!ConversionRate::is_rate_stale());
+
+			System::set_block_number(11);
+			assert!(!ConversionRate::is_rate_stale());
+
+			System::set_block_number(12);
+			assert!(ConversionRate::is_rate_stale());
+			assert_eq!(ConversionRate::stored_conversion_rate(), Some((rate(1, 1), 1)));
+		});
+	}
+
+	#[test]
+	fn pallet_is_price_provider() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(ConversionRate::feed_conversion_rate(Origin::signed(1), rate(3, 1)));
+			assert_ok!(ConversionRate::feed_conversion_rate(Origin::signed(2), rate(3, 1)));
+			assert_eq!(ConversionRate::peer_to_this(10u64), Ok(30));
+			assert_eq!(ConversionRate::this_to_peer(30u64), Ok(10));
+
+			System::set_block_number(12);
+			assert_eq!(ConversionRate::peer_to_this(10u64), Err(ExchangeError::StalePrice));
+		});
+	}
+
+	#[test]
+	fn feeds_of_removed_feeders_are_discarded() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(ConversionRate::feed_conversion_rate(Origin::signed(1), rate(1, 1)));
+			assert_ok!(ConversionRate::feed_conversion_rate(Origin::signed(2), rate(1, 1)));
+
+			assert_noop!(ConversionRate::set_feeders(Origin::signed(1), vec![1]), BadOrigin);
+			assert_ok!(ConversionRate::set_feeders(Origin::root(), vec![1, 4]));
+
+			assert_eq!(ConversionRate::feeders(), vec![1, 4]);
+			assert!(Feeds::<TestRuntime>::get(1).is_some());
+			assert!(Feeds::<TestRuntime>::get(2).is_none());
+			assert_noop!(
+				ConversionRate::feed_conversion_rate(Origin::signed(2), rate(1, 1)),
+				Error::<TestRuntime, DefaultInstance>::NotFeeder,
+			);
+		});
+	}
+}
@@ -19,15 +19,20 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use bp_currency_exchange::{
-	CurrencyConverter, DepositInto, Error as ExchangeError, MaybeLockFundsTransaction, RecipientsMap,
+	CurrencyConverter, DepositAssetInto, DepositInto, Error as ExchangeError, MaybeLockFundsTransaction, RecipientsMap,
 };
 use bp_header_chain::BaseHeaderChain;
-use frame_support::{decl_error, decl_module, decl_storage, ensure};
+use bp_runtime::Size;
+use frame_support::{decl_error, decl_module, decl_storage, ensure, weights::Weight, Parameter};
 use sp_runtime::DispatchResult;
 
 #[cfg(feature = "runtime-benchmarks")]
 pub mod benchmarking;
 
+// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+/// Weight of verifying single byte of the transaction inclusion proof.
+const PROOF_BYTE_WEIGHT: Weight = 0;
+
 /// Called when transaction is submitted to the exchange module.
 pub trait OnTransactionSubmitted<AccountId> {
 	/// Called when valid transaction is submitted and accepted by the module.
@@ -58,8 +63,15 @@ pub trait Trait<I = DefaultInstance>: frame_system::Trait {
 	>;
 	/// Something that could grant money.
 	type DepositInto: DepositInto<Recipient = Self::AccountId, Amount = Self::Amount>;
+	/// This blockchain asset identifier type.
+	type AssetId: Parameter;
+	/// Something that could grant assets, mapped to the peer blockchain tokens.
+	type DepositAssetInto: DepositAssetInto<AssetId = Self::AssetId, Recipient = Self::AccountId, Amount = Self::Amount>;
 }
 
+/// Peer blockchain token type.
+pub type PeerTokenOf<T, I> = <<T as Trait<I>>::PeerMaybeLockFundsTransaction as MaybeLockFundsTransaction>::Token;
+
 decl_error! {
 	pub enum Error for Module<T: Trait<I>, I: Instance> {
 		/// Invalid peer blockchain transaction provided.
@@ -70,6 +82,8 @@ decl_error! {
 		InvalidRecipient,
 		/// Cannot map from peer recipient to this blockchain recipient.
 		FailedToMapRecipients,
+		/// Peer blockchain token is not mapped to any asset of this blockchain.
+		UnknownToken,
 		/// Failed to convert from peer blockchain currency to this blockhain currency.
 		FailedToConvertCurrency,
 		/// Deposit has failed.
@@ -80,13 +94,15 @@ decl_error! {
 		UnfinalizedTransaction,
 		/// Transaction funds are already claimed.
 		AlreadyClaimed,
+		/// Price of the peer blockchain currency is unknown or stale.
+		StalePrice,
 	}
 }
 
 decl_module! {
 	pub struct Module<T: Trait<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
 		/// Imports lock fund transaction of the peer blockchain.
-		#[weight = 0] // TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+		#[weight = PROOF_BYTE_WEIGHT.saturating_mul(proof.size_hint() as Weight)]
 		pub fn import_peer_transaction(
 			origin,
 			proof: <<T as Trait<I>>::PeerBlockchain as BaseHeaderChain>::TransactionInclusionProof,
@@ -101,7 +117,10 @@ decl_module! {
 			{
 				// if any changes were made to the storage, we can't just return error here, because
 				// otherwise the same proof may be imported again
-				let deposit_result = T::DepositInto::deposit_into(deposit.recipient, deposit.amount);
+				let deposit_result = match deposit.asset_id {
+					Some(asset_id) => T::DepositAssetInto::deposit_asset_into(asset_id, deposit.recipient, deposit.amount),
+					None => T::DepositInto::deposit_into(deposit.recipient, deposit.amount),
+				};
 				match deposit_result {
 					Ok(_) => (),
 					Err(ExchangeError::DepositPartiallyFailed) => (),
@@ -121,6 +140,20 @@ decl_module! {
 
 			Ok(())
 		}
+
+		/// Map peer blockchain token to the asset of this blockchain. If `asset_id` is None,
+		/// existing mapping is removed and transfers of this token are no longer accepted.
+		///
+		/// May only be called from root origin.
+		#[weight = 0] // TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+		pub fn set_token_asset(origin, token: PeerTokenOf<T, I>, asset_id: Option<T::AssetId>) {
+			frame_system::ensure_root(origin)?;
+
+			match asset_id {
+				Some(asset_id) => TokenAssets::<T, I>::insert(&token, asset_id),
+				None => TokenAssets::<T, I>::remove(&token),
+			}
+		}
 	}
 }
 
@@ -128,6 +161,8 @@ decl_storage! {
 	trait Store for Module<T: Trait<I>, I: Instance = DefaultInstance> as Bridge {
 		/// All transfers that have already been claimed.
 		Transfers: map hasher(blake2_128_concat) <T::PeerMaybeLockFundsTransaction as MaybeLockFundsTransaction>::Id => ();
+		/// Mapping of peer blockchain tokens to this blockchain assets.
+		TokenAssets: map hasher(blake2_128_concat) PeerTokenOf<T, I> => Option<T::AssetId>;
 	}
 }
 
@@ -156,9 +191,11 @@ impl<T: Trait<I>, I: Instance> From<ExchangeError> for Error<T, I> {
 			ExchangeError::InvalidAmount => Error::InvalidAmount,
 			ExchangeError::InvalidRecipient => Error::InvalidRecipient,
 			ExchangeError::FailedToMapRecipients => Error::FailedToMapRecipients,
+			ExchangeError::UnknownToken => Error::UnknownToken,
 			ExchangeError::FailedToConvertCurrency => Error::FailedToConvertCurrency,
 			ExchangeError::DepositFailed => Error::DepositFailed,
 			ExchangeError::DepositPartiallyFailed => Error::DepositPartiallyFailed,
+			ExchangeError::StalePrice => Error::StalePrice,
 		}
 	}
 }
@@ -175,6 +212,8 @@ struct DepositDetails<T: Trait<I>, I: Instance> {
 	pub recipient: <T::RecipientsMap as RecipientsMap>::Recipient,
 	/// Transfer amount.
 	pub amount: <T::CurrencyConverter as CurrencyConverter>::TargetAmount,
+	/// Transferred asset. None if native currency is transferred.
+	pub asset_id: Option<T::AssetId>,
 }
 
 /// Verify and parse transaction proof, preparing everything required for importing
@@ -195,6 +234,12 @@ fn prepare_deposit_details<T: Trait<I>, I: Instance>(
 		Error::<T, I>::AlreadyClaimed
 	);
 
+	// if peer token has been locked, it must be mapped to some asset of this blockchain
+	let asset_id = match transaction.token {
+		Some(token) => Some(TokenAssets::<T, I>::get(&token).ok_or(Error::<T, I>::UnknownToken)?),
+		None => None,
+	};
+
 	// grant recipient
 	let recipient = T::RecipientsMap::map(transaction.recipient).map_err(Error::<T, I>::from)?;
 	let amount = T::CurrencyConverter::convert(transaction.amount).map_err(Error::<T, I>::from)?;
@@ -203,6 +248,7 @@ fn prepare_deposit_details<T: Trait<I>, I: Instance>(
 		transfer_id,
 		recipient,
 		amount,
+		asset_id,
 	})
 }
 
@@ -210,6 +256,7 @@ fn prepare_deposit_details<T: Trait<I>, I: Instance>(
 mod tests {
 	use super::*;
 	use bp_currency_exchange::LockFundsTransaction;
+	use codec::{Decode, Encode};
 	use frame_support::{assert_noop, assert_ok, impl_outer_origin, parameter_types, weights::Weight};
 	use sp_core::H256;
 	use sp_runtime::{
@@ -226,8 +273,13 @@ mod tests {
 	const INVALID_AMOUNT: u64 = 0;
 	const MAX_DEPOSIT_AMOUNT: u64 = 1000;
 	const SUBMITTER: u64 = 2000;
+	const KNOWN_TOKEN: u64 = 3000;
+	const UNKNOWN_TOKEN: u64 = 3001;
+	const KNOWN_TOKEN_ASSET: u32 = 1;
+	const UNSUPPORTED_TOKEN: u64 = 3002;
+	const UNSUPPORTED_TOKEN_ASSET: u32 = 2;
 
-	type RawTransaction = LockFundsTransaction<u64, u64, u64>;
+	type RawTransaction = LockFundsTransaction<u64, u64, u64, u64>;
 
 	pub struct DummyTransactionSubmissionHandler;
 
@@ -237,11 +289,20 @@ mod tests {
 		}
 	}
 
+	#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+	pub struct DummyTransactionProof(bool, RawTransaction);
+
+	impl Size for DummyTransactionProof {
+		fn size_hint(&self) -> u32 {
+			0
+		}
+	}
+
 	pub struct DummyBlockchain;
 
 	impl BaseHeaderChain for DummyBlockchain {
 		type Transaction = RawTransaction;
-		type TransactionInclusionProof = (bool, RawTransaction);
+		type TransactionInclusionProof = DummyTransactionProof;
 
 		fn verify_transaction_inclusion_proof(proof: &Self::TransactionInclusionProof) -> Option<RawTransaction> {
 			if proof.0 {
@@ -259,6 +320,7 @@ mod tests {
 		type Id = u64;
 		type Recipient = AccountId;
 		type Amount = u64;
+		type Token = u64;
 
 		fn parse(tx: &Self::Transaction) -> bp_currency_exchange::Result<RawTransaction> {
 			match tx.id {
@@ -311,6 +373,25 @@ mod tests {
 		}
 	}
 
+	pub struct DummyDepositAssetInto;
+
+	impl DepositAssetInto for DummyDepositAssetInto {
+		type AssetId = u32;
+		type Recipient = AccountId;
+		type Amount = u64;
+
+		fn deposit_asset_into(
+			asset_id: Self::AssetId,
+			_recipient: Self::Recipient,
+			_amount: Self::Amount,
+		) -> bp_currency_exchange::Result<()> {
+			match asset_id {
+				KNOWN_TOKEN_ASSET => Ok(()),
+				_ => Err(ExchangeError::DepositFailed),
+			}
+		}
+	}
+
 	#[derive(Clone, Eq, PartialEq)]
 	pub struct TestRuntime;
 
@@ -361,6 +442,8 @@ mod tests {
 		type Amount = u64;
 		type CurrencyConverter = DummyCurrencyConverter;
 		type DepositInto = DummyDepositInto;
+		type AssetId = u32;
+		type DepositAssetInto = DummyDepositAssetInto;
 	}
 
 	type Exchange = Module<TestRuntime>;
@@ -377,14 +460,38 @@ mod tests {
 			id,
 			recipient: 1,
 			amount: 2,
+			token: None,
 		}
 	}
 
+	fn token_transaction(id: u64, token: u64) -> RawTransaction {
+		RawTransaction {
+			token: Some(token),
+			..transaction(id)
+		}
+	}
+
+	fn map_tokens() {
+		assert_ok!(Exchange::set_token_asset(
+			Origin::root(),
+			KNOWN_TOKEN,
+			Some(KNOWN_TOKEN_ASSET)
+		));
+		assert_ok!(Exchange::set_token_asset(
+			Origin::root(),
+			UNSUPPORTED_TOKEN,
+			Some(UNSUPPORTED_TOKEN_ASSET)
+		));
+	}
+
 	#[test]
 	fn unfinalized_transaction_rejected() {
 		new_test_ext().execute_with(|| {
 			assert_noop!(
-				Exchange::import_peer_transaction(Origin::signed(SUBMITTER), (false, transaction(0))),
+				Exchange::import_peer_transaction(
+					Origin::signed(SUBMITTER),
+					DummyTransactionProof(false, transaction(0))
+				),
 				Error::<TestRuntime, DefaultInstance>::UnfinalizedTransaction,
 			);
 		});
@@ -396,7 +503,7 @@ mod tests {
 			assert_noop!(
 				Exchange::import_peer_transaction(
 					Origin::signed(SUBMITTER),
-					(true, transaction(INVALID_TRANSACTION_ID)),
+					DummyTransactionProof(true, transaction(INVALID_TRANSACTION_ID)),
 				),
 				Error::<TestRuntime, DefaultInstance>::InvalidTransaction,
 			);
@@ -410,7 +517,7 @@ mod tests {
 			assert_noop!(
 				Exchange::import_peer_transaction(
 					Origin::signed(SUBMITTER),
-					(true, transaction(ALREADY_CLAIMED_TRANSACTION_ID)),
+					DummyTransactionProof(true, transaction(ALREADY_CLAIMED_TRANSACTION_ID)),
 				),
 				Error::<TestRuntime, DefaultInstance>::AlreadyClaimed,
 			);
@@ -423,7 +530,7 @@ mod tests {
 			let mut transaction = transaction(0);
 			transaction.recipient = UNKNOWN_RECIPIENT_ID;
 			assert_noop!(
-				Exchange::import_peer_transaction(Origin::signed(SUBMITTER), (true, transaction)),
+				Exchange::import_peer_transaction(Origin::signed(SUBMITTER), DummyTransactionProof(true, transaction)),
 				Error::<TestRuntime, DefaultInstance>::FailedToMapRecipients,
 			);
 		});
@@ -435,7 +542,7 @@ mod tests {
 			let mut transaction = transaction(0);
 			transaction.amount = INVALID_AMOUNT;
 			assert_noop!(
-				Exchange::import_peer_transaction(Origin::signed(SUBMITTER), (true, transaction)),
+				Exchange::import_peer_transaction(Origin::signed(SUBMITTER), DummyTransactionProof(true, transaction)),
 				Error::<TestRuntime, DefaultInstance>::FailedToConvertCurrency,
 			);
 		});
@@ -447,7 +554,7 @@ mod tests {
 			let mut transaction = transaction(0);
 			transaction.amount = MAX_DEPOSIT_AMOUNT + 1;
 			assert_noop!(
-				Exchange::import_peer_transaction(Origin::signed(SUBMITTER), (true, transaction)),
+				Exchange::import_peer_transaction(Origin::signed(SUBMITTER), DummyTransactionProof(true, transaction)),
 				Error::<TestRuntime, DefaultInstance>::DepositFailed,
 			);
 		});
@@ -460,7 +567,7 @@ mod tests {
 			transaction.amount = MAX_DEPOSIT_AMOUNT;
 			assert_ok!(Exchange::import_peer_transaction(
 				Origin::signed(SUBMITTER),
-				(true, transaction),
+				DummyTransactionProof(true, transaction),
 			),);
 
 			// ensure that the transfer has been marked as completed
@@ -475,7 +582,7 @@ mod tests {
 		new_test_ext().execute_with(|| {
 			assert_ok!(Exchange::import_peer_transaction(
 				Origin::signed(SUBMITTER),
-				(true, transaction(0)),
+				DummyTransactionProof(true, transaction(0)),
 			),);
 
 			// ensure that the transfer has been marked as completed
@@ -484,4 +591,65 @@ mod tests {
 			assert!(<Exchange as crate::Store>::Transfers::contains_key(SUBMITTER));
 		});
 	}
+
+	#[test]
+	fn token_asset_is_set_by_root_only() {
+		new_test_ext().execute_with(|| {
+			assert_noop!(
+				Exchange::set_token_asset(Origin::signed(SUBMITTER), KNOWN_TOKEN, Some(KNOWN_TOKEN_ASSET)),
+				frame_support::error::BadOrigin,
+			);
+
+			map_tokens();
+			assert_eq!(
+				<Exchange as crate::Store>::TokenAssets::get(KNOWN_TOKEN),
+				Some(KNOWN_TOKEN_ASSET)
+			);
+
+			assert_ok!(Exchange::set_token_asset(Origin::root(), KNOWN_TOKEN, None));
+			assert_eq!(<Exchange as crate::Store>::TokenAssets::get(KNOWN_TOKEN), None);
+		});
+	}
+
+	#[test]
+	fn transaction_with_unknown_token_rejected() {
+		new_test_ext().execute_with(|| {
+			map_tokens();
+			assert_noop!(
+				Exchange::import_peer_transaction(
+					Origin::signed(SUBMITTER),
+					DummyTransactionProof(true, token_transaction(0, UNKNOWN_TOKEN)),
+				),
+				Error::<TestRuntime, DefaultInstance>::UnknownToken,
+			);
+		});
+	}
+
+	#[test]
+	fn token_transaction_with_invalid_deposit_rejected() {
+		new_test_ext().execute_with(|| {
+			map_tokens();
+			assert_noop!(
+				Exchange::import_peer_transaction(
+					Origin::signed(SUBMITTER),
+					DummyTransactionProof(true, token_transaction(0, UNSUPPORTED_TOKEN)),
+				),
+				Error::<TestRuntime, DefaultInstance>::DepositFailed,
+			);
+		});
+	}
+
+	#[test]
+	fn valid_token_transaction_accepted() {
+		new_test_ext().execute_with(|| {
+			map_tokens();
+			assert_ok!(Exchange::import_peer_transaction(
+				Origin::signed(SUBMITTER),
+				DummyTransactionProof(true, token_transaction(0, KNOWN_TOKEN)),
+			));
+
+			// ensure that the transfer has been marked as completed
+			assert!(<Exchange as crate::Store>::Transfers::contains_key(0u64));
+		});
+	}
 }
@@ -0,0 +1,418 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Clique (EIP-225) consensus engine, used by Görli and similar networks.
+//!
+//! Signers votes are not tallied by the module - validators set is only changed at epoch
+//! checkpoint headers, which are including full list of signers in their extra data.
+
+use crate::error::Error;
+use crate::{ConsensusEngine, ImportContext, Storage};
+use bp_eth_poa::{public_to_address, Address, AuraHeader, H256, U256};
+use codec::{Decode, Encode};
+use sp_io::crypto::secp256k1_ecdsa_recover;
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+
+/// Difficulty of the header that is sealed by in-turn signer.
+const DIFFICULTY_IN_TURN: u64 = 2;
+/// Difficulty of the header that is sealed by out-of-turn signer.
+const DIFFICULTY_NO_TURN: u64 = 1;
+/// Nonce of the header that votes for adding new signer.
+const NONCE_AUTH_VOTE: u64 = u64::max_value();
+/// Nonce of the header that votes for removing existing signer.
+const NONCE_DROP_VOTE: u64 = 0;
+
+/// Clique engine configuration parameters.
+///
+/// Validators lists (both configured and read from checkpoint headers) must be sorted
+/// in ascending order, because in-turn signer is selected by its position in the list.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+pub struct CliqueConfiguration {
+	/// Minimal number of seconds between consecutive headers.
+	pub period: u64,
+	/// Number of headers after which signers list is checkpointed. If zero, checkpoints
+	/// are never expected.
+	pub epoch: u64,
+	/// Minimum gas limit.
+	pub min_gas_limit: U256,
+	/// Maximum gas limit.
+	pub max_gas_limit: U256,
+}
+
+impl CliqueConfiguration {
+	/// Returns true if given header is the epoch checkpoint header.
+	fn is_checkpoint(&self, header: &AuraHeader) -> bool {
+		header.number.checked_rem(self.epoch) == Some(0)
+	}
+}
+
+impl ConsensusEngine for CliqueConfiguration {
+	fn contextless_checks(&self, header: &AuraHeader) -> Result<(), Error> {
+		// Clique seal is (mix digest, nonce) and signature is stored in the extra data
+		if header.seal.len() != 2 {
+			return Err(Error::InvalidSealArity);
+		}
+		if header.gas_limit < self.min_gas_limit {
+			return Err(Error::InvalidGasLimit);
+		}
+		if header.gas_limit > self.max_gas_limit {
+			return Err(Error::InvalidGasLimit);
+		}
+
+		// signers list is only included in checkpoint headers
+		let is_checkpoint = self.is_checkpoint(header);
+		let signers = header.clique_signers().ok_or(Error::ExtraDataOutOfBounds)?;
+		match (is_checkpoint, signers.is_empty()) {
+			(true, true) => return Err(Error::InvalidCheckpoint),
+			(false, false) => return Err(Error::ExtraDataOutOfBounds),
+			_ => (),
+		}
+
+		// checkpoint headers are not allowed to vote
+		if is_checkpoint && header.author != Address::zero() {
+			return Err(Error::InvalidCheckpoint);
+		}
+		match header.clique_nonce() {
+			Some(NONCE_DROP_VOTE) => (),
+			Some(NONCE_AUTH_VOTE) if !is_checkpoint => (),
+			_ => return Err(Error::InvalidNonce),
+		}
+
+		if header.clique_mix_digest() != Some(H256::zero()) {
+			return Err(Error::InvalidMixDigest);
+		}
+		if !header.has_empty_uncles() {
+			return Err(Error::InvalidUnclesHash);
+		}
+
+		Ok(())
+	}
+
+	fn contextual_checks<S: Storage>(
+		&self,
+		storage: &S,
+		context: &ImportContext<S::Submitter>,
+		header: &AuraHeader,
+	) -> Result<(), Error> {
+		if header.timestamp < context.parent_header().timestamp.saturating_add(self.period) {
+			return Err(Error::TimestampTooClose);
+		}
+
+		// signer is allowed to seal at most one of `validators.len() / 2 + 1` consecutive headers
+		let signer = recover_signer(header).ok_or(Error::NotValidator)?;
+		let recent_headers = context.validators_set().validators.len() / 2;
+		let is_recent_signer = crate::ancestry(storage, header.parent_hash)
+			.take(recent_headers)
+			.any(|(_, ancestor)| recover_signer(&ancestor) == Some(signer));
+		if is_recent_signer {
+			return Err(Error::RecentlySigned);
+		}
+
+		Ok(())
+	}
+
+	fn validator_checks(&self, validators: &[Address], header: &AuraHeader) -> Result<(), Error> {
+		let signer = recover_signer(header).ok_or(Error::NotValidator)?;
+		let signer_index = validators
+			.iter()
+			.position(|validator| *validator == signer)
+			.ok_or(Error::NotValidator)?;
+
+		let is_in_turn = header.number % validators.len() as u64 == signer_index as u64;
+		let expected_difficulty = if is_in_turn {
+			DIFFICULTY_IN_TURN
+		} else {
+			DIFFICULTY_NO_TURN
+		};
+		if header.difficulty != expected_difficulty.into() {
+			return Err(Error::InvalidDifficulty);
+		}
+
+		Ok(())
+	}
+
+	fn header_author(&self, header: &AuraHeader) -> Address {
+		recover_signer(header).unwrap_or_default()
+	}
+
+	fn enacted_validators(&self, header: &AuraHeader) -> Option<Vec<Address>> {
+		if self.is_checkpoint(header) {
+			header.clique_signers()
+		} else {
+			None
+		}
+	}
+
+	fn two_thirds_majority_transition(&self) -> u64 {
+		u64::max_value()
+	}
+}
+
+/// Recover address of the Clique header signer.
+fn recover_signer(header: &AuraHeader) -> Option<Address> {
+	let signature = header.clique_signature()?;
+	let message = header.clique_seal_hash()?;
+	secp256k1_ecdsa_recover(signature.as_fixed_bytes(), message.as_fixed_bytes())
+		.ok()
+		.map(|public| public_to_address(&public))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::import::import_header;
+	use crate::mock::{
+		insert_header, run_test_with_genesis, test_validators_config, validator, validator_address,
+		validators_addresses, AccountId, ConstChainTime, KeepSomeHeadersBehindBest, TestRuntime, GAS_LIMIT,
+	};
+	use crate::verification::verify_aura_header;
+	use crate::BridgeStorage;
+	use bp_eth_poa::{rlp_encode, signatures::SignHeader, CLIQUE_EXTRA_SEAL, CLIQUE_EXTRA_VANITY};
+	use hex_literal::hex;
+
+	const EMPTY_UNCLES_HASH: [u8; 32] = hex!("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347");
+	const TOTAL_VALIDATORS: usize = 3;
+	const PERIOD: u64 = 15;
+	const EPOCH: u64 = 4;
+
+	fn test_clique_config() -> CliqueConfiguration {
+		CliqueConfiguration {
+			period: PERIOD,
+			epoch: EPOCH,
+			min_gas_limit: 0x1388.into(),
+			max_gas_limit: U256::max_value(),
+		}
+	}
+
+	fn genesis() -> AuraHeader {
+		AuraHeader {
+			uncles_hash: EMPTY_UNCLES_HASH.into(),
+			gas_limit: GAS_LIMIT.into(),
+			extra_data: vec![0; CLIQUE_EXTRA_VANITY + CLIQUE_EXTRA_SEAL],
+			seal: vec![rlp_encode(&H256::zero()), rlp_encode(&vec![0u8; 8])],
+			..Default::default()
+		}
+	}
+
+	fn clique_header(parent: &AuraHeader, signers: &[Address]) -> AuraHeader {
+		let mut extra_data = vec![0; CLIQUE_EXTRA_VANITY];
+		extra_data.extend(signers.iter().flat_map(|signer| signer.as_bytes().to_vec()));
+		extra_data.extend(vec![0; CLIQUE_EXTRA_SEAL]);
+
+		AuraHeader {
+			parent_hash: parent.compute_hash(),
+			number: parent.number + 1,
+			timestamp: parent.timestamp + PERIOD,
+			difficulty: DIFFICULTY_NO_TURN.into(),
+			extra_data,
+			..genesis()
+		}
+	}
+
+	fn in_turn(mut header: AuraHeader) -> AuraHeader {
+		header.difficulty = DIFFICULTY_IN_TURN.into();
+		header
+	}
+
+	fn verify_with_ancestry(ancestry: Vec<AuraHeader>, header: &AuraHeader) -> Result<ImportContext<AccountId>, Error> {
+		run_test_with_genesis(genesis(), TOTAL_VALIDATORS, |_| {
+			let mut storage = BridgeStorage::<TestRuntime>::new();
+			for ancestor in ancestry {
+				insert_header(&mut storage, ancestor);
+			}
+
+			verify_aura_header(
+				&storage,
+				&test_clique_config(),
+				None,
+				header,
+				&ConstChainTime::default(),
+			)
+		})
+	}
+
+	fn default_verify(header: &AuraHeader) -> Result<ImportContext<AccountId>, Error> {
+		verify_with_ancestry(Vec::new(), header)
+	}
+
+	#[test]
+	fn recovers_header_signer() {
+		let header = clique_header(&genesis(), &[]).sign_clique_by(&validator(2));
+		assert_eq!(recover_signer(&header), Some(validator_address(2)));
+		assert_eq!(test_clique_config().header_author(&header), validator_address(2));
+
+		// when signature is missing
+		let mut header = header;
+		header.extra_data.truncate(CLIQUE_EXTRA_SEAL - 1);
+		assert_eq!(recover_signer(&header), None);
+	}
+
+	#[test]
+	fn verifies_seal() {
+		// when nonce is missing
+		let mut header = clique_header(&genesis(), &[]);
+		header.seal.pop();
+		assert_eq!(
+			default_verify(&header.sign_clique_by(&validator(2))),
+			Err(Error::InvalidSealArity)
+		);
+
+		// when mix digest is not zero
+		let mut header = clique_header(&genesis(), &[]);
+		header.seal[0] = rlp_encode(&H256::from_low_u64_be(1));
+		assert_eq!(
+			default_verify(&header.sign_clique_by(&validator(2))),
+			Err(Error::InvalidMixDigest)
+		);
+
+		// when nonce is neither auth, nor drop vote
+		let mut header = clique_header(&genesis(), &[]);
+		header.seal[1] = rlp_encode(&vec![0u8, 0, 0, 0, 0, 0, 0, 1]);
+		assert_eq!(
+			default_verify(&header.sign_clique_by(&validator(2))),
+			Err(Error::InvalidNonce)
+		);
+
+		// when header has uncles
+		let mut header = clique_header(&genesis(), &[]);
+		header.uncles_hash = Default::default();
+		assert_eq!(
+			default_verify(&header.sign_clique_by(&validator(2))),
+			Err(Error::InvalidUnclesHash)
+		);
+
+		// when non-checkpoint header has signers list
+		let header = clique_header(&genesis(), &validators_addresses(3)).sign_clique_by(&validator(2));
+		assert_eq!(default_verify(&header), Err(Error::ExtraDataOutOfBounds));
+	}
+
+	#[test]
+	fn verifies_difficulty() {
+		// header#1 is in-turn for validator#1
+		let header1 = clique_header(&genesis(), &[]);
+		assert_eq!(
+			default_verify(&header1.clone().sign_clique_by(&validator(1))),
+			Err(Error::InvalidDifficulty)
+		);
+		assert!(default_verify(&in_turn(header1.clone()).sign_clique_by(&validator(1))).is_ok());
+
+		// and out-of-turn for validator#2
+		assert_eq!(
+			default_verify(&in_turn(header1.clone()).sign_clique_by(&validator(2))),
+			Err(Error::InvalidDifficulty)
+		);
+		assert!(default_verify(&header1.sign_clique_by(&validator(2))).is_ok());
+	}
+
+	#[test]
+	fn verifies_signer() {
+		let header = clique_header(&genesis(), &[]).sign_clique_by(&validator(TOTAL_VALIDATORS));
+		assert_eq!(default_verify(&header), Err(Error::NotValidator));
+	}
+
+	#[test]
+	fn verifies_timestamp_period() {
+		let mut header1 = clique_header(&genesis(), &[]);
+		header1.timestamp = genesis().timestamp + PERIOD - 1;
+		assert_eq!(
+			default_verify(&header1.sign_clique_by(&validator(2))),
+			Err(Error::TimestampTooClose)
+		);
+	}
+
+	#[test]
+	fn verifies_recent_signers() {
+		// with 3 validators, the same signer can't seal two consecutive headers
+		let header1 = in_turn(clique_header(&genesis(), &[])).sign_clique_by(&validator(1));
+		let header2 = clique_header(&header1, &[]).sign_clique_by(&validator(1));
+		assert_eq!(
+			verify_with_ancestry(vec![header1.clone()], &header2),
+			Err(Error::RecentlySigned)
+		);
+
+		// but it could seal every other header
+		let header2 = in_turn(clique_header(&header1, &[])).sign_clique_by(&validator(2));
+		let header3 = clique_header(&header2, &[]).sign_clique_by(&validator(1));
+		assert!(verify_with_ancestry(vec![header1, header2], &header3).is_ok());
+	}
+
+	#[test]
+	fn verifies_checkpoint() {
+		let mut headers = vec![genesis()];
+		for number in 1..EPOCH {
+			let header = in_turn(clique_header(headers.last().unwrap(), &[]));
+			headers.push(header.sign_clique_by(&validator(number as usize % TOTAL_VALIDATORS)));
+		}
+		let parent = headers.last().unwrap().clone();
+		let ancestry = headers.into_iter().skip(1).collect::<Vec<_>>();
+
+		// when checkpoint header has no signers list
+		let checkpoint = clique_header(&parent, &[]).sign_clique_by(&validator(0));
+		assert_eq!(
+			verify_with_ancestry(ancestry.clone(), &checkpoint),
+			Err(Error::InvalidCheckpoint)
+		);
+
+		// when checkpoint header votes
+		let mut checkpoint = clique_header(&parent, &validators_addresses(2));
+		checkpoint.author = validator_address(0);
+		assert_eq!(
+			verify_with_ancestry(ancestry.clone(), &checkpoint.sign_clique_by(&validator(2))),
+			Err(Error::InvalidCheckpoint)
+		);
+
+		// when checkpoint header is valid
+		let checkpoint = clique_header(&parent, &validators_addresses(2)).sign_clique_by(&validator(2));
+		assert!(verify_with_ancestry(ancestry, &checkpoint).is_ok());
+		assert_eq!(
+			test_clique_config().enacted_validators(&checkpoint),
+			Some(validators_addresses(2))
+		);
+	}
+
+	#[test]
+	fn checkpoint_header_enacts_signers_list() {
+		run_test_with_genesis(genesis(), TOTAL_VALIDATORS, |_| {
+			let mut storage = BridgeStorage::<TestRuntime>::new();
+			let mut parent = genesis();
+			for number in 1..=EPOCH {
+				let signers = if number == EPOCH {
+					validators_addresses(2)
+				} else {
+					Vec::new()
+				};
+				let header = in_turn(clique_header(&parent, &signers))
+					.sign_clique_by(&validator(number as usize % TOTAL_VALIDATORS));
+				import_header(
+					&mut storage,
+					&mut KeepSomeHeadersBehindBest::default(),
+					&test_clique_config(),
+					&test_validators_config(),
+					None,
+					header.clone(),
+					&ConstChainTime::default(),
+					None,
+				)
+				.unwrap();
+				parent = header;
+			}
+
+			let context = storage.import_context(None, &parent.compute_hash()).unwrap();
+			assert_eq!(context.validators_set().validators, validators_addresses(2));
+		});
+	}
+}
@@ -64,6 +64,20 @@ pub enum Error {
 	TryingToFinalizeSibling = 20,
 	/// Header timestamp is ahead of on-chain timestamp
 	HeaderTimestampIsAhead = 21,
+	/// Submitter is unable to bond submitted headers.
+	NotEnoughFundsForBond = 22,
+	/// Clique checkpoint header has invalid signers list.
+	InvalidCheckpoint = 23,
+	/// Header mix digest is invalid.
+	InvalidMixDigest = 24,
+	/// Header nonce is invalid.
+	InvalidNonce = 25,
+	/// Header uncles hash is invalid.
+	InvalidUnclesHash = 26,
+	/// Header timestamp is too close to the parent header timestamp.
+	TimestampTooClose = 27,
+	/// Signer has signed one of recent headers.
+	RecentlySigned = 28,
 }
 
 impl Error {
@@ -91,6 +105,13 @@ impl Error {
 			Error::UnsignedTooFarInTheFuture => "The unsigned header is too far in future",
 			Error::TryingToFinalizeSibling => "Trying to finalize sibling of finalized block",
 			Error::HeaderTimestampIsAhead => "Header timestamp is ahead of on-chain timestamp",
+			Error::NotEnoughFundsForBond => "Submitter is unable to bond submitted headers",
+			Error::InvalidCheckpoint => "Checkpoint header has invalid signers list",
+			Error::InvalidMixDigest => "Header has invalid mix digest",
+			Error::InvalidNonce => "Header has invalid nonce",
+			Error::InvalidUnclesHash => "Header has invalid uncles hash",
+			Error::TimestampTooClose => "Header timestamp is too close to parent timestamp",
+			Error::RecentlySigned => "Header signer has recently signed another header",
 		}
 	}
 
@@ -15,7 +15,7 @@
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::error::Error;
-use crate::Storage;
+use crate::{ConsensusEngine, Storage};
 use bp_eth_poa::{public_to_address, Address, AuraHeader, HeaderId, SealedEmptyStep, H256};
 use codec::{Decode, Encode};
 use sp_io::crypto::secp256k1_ecdsa_recover;
@@ -80,14 +80,14 @@ pub struct FinalityAncestor<Submitter> {
 /// Tries to finalize blocks when given block is imported.
 ///
 /// Returns numbers and hashes of finalized blocks in ascending order.
-pub fn finalize_blocks<S: Storage>(
+pub fn finalize_blocks<S: Storage, E: ConsensusEngine>(
 	storage: &S,
 	best_finalized: HeaderId,
 	header_validators: (HeaderId, &[Address]),
 	id: HeaderId,
 	submitter: Option<&S::Submitter>,
 	header: &AuraHeader,
-	two_thirds_majority_transition: u64,
+	engine: &E,
 ) -> Result<FinalityEffects<S::Submitter>, Error> {
 	// compute count of voters for every unfinalized block in ancestry
 	let validators = header_validators.1.iter().collect();
@@ -105,6 +105,7 @@ pub fn finalize_blocks<S: Storage>(
 		id,
 		header,
 		submitter.cloned(),
+		engine,
 	)?;
 
 	// now let's iterate in reverse order && find just finalized blocks
@@ -114,7 +115,7 @@ pub fn finalize_blocks<S: Storage>(
 		if !is_finalized(
 			&validators,
 			&current_votes,
-			ancestor.id.number >= two_thirds_majority_transition,
+			ancestor.id.number >= engine.two_thirds_majority_transition(),
 		) {
 			break;
 		}
@@ -140,13 +141,14 @@ fn is_finalized(
 }
 
 /// Prepare 'votes' of header and its ancestors' signers.
-fn prepare_votes<Submitter>(
+fn prepare_votes<Submitter, E: ConsensusEngine>(
 	mut cached_votes: CachedFinalityVotes<Submitter>,
 	best_finalized: HeaderId,
 	validators: &BTreeSet<&Address>,
 	id: HeaderId,
 	header: &AuraHeader,
 	submitter: Option<Submitter>,
+	engine: &E,
 ) -> Result<FinalityVotes<Submitter>, Error> {
 	// if we have reached finalized block sibling, then we're trying
 	// to switch finalized blocks
@@ -155,7 +157,8 @@ fn prepare_votes<Submitter>(
 	}
 
 	// this fn can only work with single validators set
-	if !validators.contains(&header.author) {
+	let header_author = engine.header_author(header);
+	if !validators.contains(&header_author) {
 		return Err(Error::NotValidator);
 	}
 
@@ -183,7 +186,7 @@ fn prepare_votes<Submitter>(
 	while let Some((ancestor_id, ancestor_submitter, ancestor)) = cached_votes.unaccounted_ancestry.pop_front() {
 		let mut signers = empty_steps_signers(&ancestor);
 		sp_std::mem::swap(&mut signers, &mut parent_empty_step_signers);
-		signers.insert(ancestor.author);
+		signers.insert(engine.header_author(&ancestor));
 
 		add_signers_votes(validators, &signers, &mut votes.votes)?;
 
@@ -197,8 +200,8 @@ fn prepare_votes<Submitter>(
 
 	// add votes from block itself
 	let mut header_signers = BTreeSet::new();
-	header_signers.insert(header.author);
-	*votes.votes.entry(header.author).or_insert(0) += 1;
+	header_signers.insert(header_author);
+	*votes.votes.entry(header_author).or_insert(0) += 1;
 	votes.ancestry.push_back(FinalityAncestor {
 		id,
 		submitter,
@@ -282,7 +285,9 @@ impl<Submitter> Default for FinalityVotes<Submitter> {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::mock::{insert_header, run_test, validator, validators_addresses, HeaderBuilder, TestRuntime};
+	use crate::mock::{
+		insert_header, run_test, test_aura_config, validator, validators_addresses, HeaderBuilder, TestRuntime,
+	};
 	use crate::{BridgeStorage, FinalityCache, HeaderToImport};
 	use frame_support::StorageMap;
 
@@ -299,7 +304,7 @@ mod tests {
 					Default::default(),
 					None,
 					&AuraHeader::default(),
-					0,
+					&test_aura_config(),
 				),
 				Err(Error::NotValidator),
 			);
@@ -334,7 +339,7 @@ mod tests {
 					id1,
 					None,
 					&header_to_import.header,
-					u64::max_value(),
+					&test_aura_config(),
 				)
 				.map(|eff| eff.finalized_headers),
 				Ok(Vec::new()),
@@ -353,7 +358,7 @@ mod tests {
 					id2,
 					None,
 					&header_to_import.header,
-					u64::max_value(),
+					&test_aura_config(),
 				)
 				.map(|eff| eff.finalized_headers),
 				Ok(Vec::new()),
@@ -372,7 +377,7 @@ mod tests {
 					id3,
 					None,
 					&header_to_import.header,
-					u64::max_value(),
+					&test_aura_config(),
 				)
 				.map(|eff| eff.finalized_headers),
 				Ok(vec![(id1, None)]),
@@ -403,7 +408,7 @@ mod tests {
 			.collect::<Vec<_>>();
 		let header5 = headers[4].clone();
 		assert_eq!(
-			prepare_votes::<()>(
+			prepare_votes::<(), _>(
 				CachedFinalityVotes {
 					stopped_at_finalized_sibling: false,
 					unaccounted_ancestry: vec![(headers[3].compute_id(), None, headers[3].clone()),]
@@ -421,6 +426,7 @@ mod tests {
 				header5.compute_id(),
 				&header5,
 				None,
+				&test_aura_config(),
 			)
 			.unwrap(),
 			FinalityVotes {
@@ -480,6 +486,7 @@ mod tests {
 					id7,
 					headers.get(6).unwrap(),
 					None,
+					&test_aura_config(),
 				)
 				.unwrap(),
 				expected_votes_at_7,
@@ -506,6 +513,7 @@ mod tests {
 					id7,
 					headers.get(6).unwrap(),
 					None,
+					&test_aura_config(),
 				)
 				.unwrap(),
 				expected_votes_at_7,
@@ -529,6 +537,7 @@ mod tests {
 					id7,
 					headers.get(6).unwrap(),
 					None,
+					&test_aura_config(),
 				)
 				.unwrap(),
 				expected_votes_at_7,
@@ -539,7 +548,7 @@ mod tests {
 	#[test]
 	fn prepare_votes_fails_when_finalized_sibling_is_in_ancestry() {
 		assert_eq!(
-			prepare_votes::<()>(
+			prepare_votes::<(), _>(
 				CachedFinalityVotes {
 					stopped_at_finalized_sibling: true,
 					..Default::default()
@@ -549,6 +558,7 @@ mod tests {
 				Default::default(),
 				&Default::default(),
 				None,
+				&test_aura_config(),
 			),
 			Err(Error::TryingToFinalizeSibling),
 		);
@@ -18,7 +18,7 @@ use crate::error::Error;
 use crate::finality::finalize_blocks;
 use crate::validators::{Validators, ValidatorsConfiguration};
 use crate::verification::{is_importable_header, verify_aura_header};
-use crate::{AuraConfiguration, ChainTime, ChangeToEnact, PruningStrategy, Storage};
+use crate::{ChainTime, ChangeToEnact, ConsensusEngine, PruningStrategy, Storage};
 use bp_eth_poa::{AuraHeader, HeaderId, Receipt};
 use sp_std::{collections::btree_map::BTreeMap, prelude::*};
 
@@ -33,10 +33,10 @@ use sp_std::{collections::btree_map::BTreeMap, prelude::*};
 /// imported in this case.
 /// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/415)
 #[allow(clippy::too_many_arguments)]
-pub fn import_headers<S: Storage, PS: PruningStrategy, CT: ChainTime>(
+pub fn import_headers<S: Storage, PS: PruningStrategy, E: ConsensusEngine, CT: ChainTime>(
 	storage: &mut S,
 	pruning_strategy: &mut PS,
-	aura_config: &AuraConfiguration,
+	engine: &E,
 	validators_config: &ValidatorsConfiguration,
 	submitter: Option<S::Submitter>,
 	headers: Vec<(AuraHeader, Option<Vec<Receipt>>)>,
@@ -49,7 +49,7 @@ pub fn import_headers<S: Storage, PS: PruningStrategy, CT: ChainTime>(
 		let import_result = import_header(
 			storage,
 			pruning_strategy,
-			aura_config,
+			engine,
 			validators_config,
 			submitter.clone(),
 			header,
@@ -85,10 +85,10 @@ pub type FinalizedHeaders<S> = Vec<(HeaderId, Option<<S as Storage>::Submitter>)
 /// Returns imported block id and list of all finalized headers.
 /// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/415)
 #[allow(clippy::too_many_arguments)]
-pub fn import_header<S: Storage, PS: PruningStrategy, CT: ChainTime>(
+pub fn import_header<S: Storage, PS: PruningStrategy, E: ConsensusEngine, CT: ChainTime>(
 	storage: &mut S,
 	pruning_strategy: &mut PS,
-	aura_config: &AuraConfiguration,
+	engine: &E,
 	validators_config: &ValidatorsConfiguration,
 	submitter: Option<S::Submitter>,
 	header: AuraHeader,
@@ -99,7 +99,7 @@ pub fn import_header<S: Storage, PS: PruningStrategy, CT: ChainTime>(
 	let (header_id, finalized_id) = is_importable_header(storage, &header)?;
 
 	// verify header
-	let import_context = verify_aura_header(storage, aura_config, submitter, &header, chain_time)?;
+	let import_context = verify_aura_header(storage, engine, submitter, &header, chain_time)?;
 
 	// check if block schedules new validators
 	let validators = Validators::new(validators_config);
@@ -114,9 +114,14 @@ pub fn import_header<S: Storage, PS: PruningStrategy, CT: ChainTime>(
 		header_id,
 		import_context.submitter(),
 		&header,
-		aura_config.two_thirds_majority_transition,
+		engine,
 	)?;
 	let enacted_change = enacted_change
+		.or_else(|| {
+			engine
+				.enacted_validators(&header)
+				.filter(|validators| *validators != validators_set.validators)
+		})
 		.map(|validators| ChangeToEnact {
 			signal_block: None,
 			validators,
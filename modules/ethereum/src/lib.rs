@@ -21,18 +21,25 @@
 use crate::finality::{CachedFinalityVotes, FinalityVotes};
 use bp_eth_poa::{Address, AuraHeader, HeaderId, RawTransaction, RawTransactionReceipt, Receipt, H256, U256};
 use codec::{Decode, Encode};
-use frame_support::{decl_module, decl_storage, traits::Get};
+use frame_support::{
+	decl_module, decl_storage,
+	traits::{Currency, Get, ReservableCurrency},
+	weights::Weight,
+};
 use sp_runtime::{
+	traits::{SaturatedConversion, Saturating},
 	transaction_validity::{
 		InvalidTransaction, TransactionLongevity, TransactionPriority, TransactionSource, TransactionValidity,
 		UnknownTransaction, ValidTransaction,
 	},
 	RuntimeDebug,
 };
-use sp_std::{cmp::Ord, collections::btree_map::BTreeMap, prelude::*};
+use sp_std::{cmp::Ord, collections::btree_map::BTreeMap, marker::PhantomData, prelude::*};
 
+pub use clique::CliqueConfiguration;
 pub use validators::{ValidatorsConfiguration, ValidatorsSource};
 
+mod clique;
 mod error;
 mod finality;
 mod import;
@@ -50,6 +57,8 @@ pub mod test_utils;
 
 /// Maximal number of blocks we're pruning in single import call.
 const MAX_BLOCKS_TO_PRUNE_IN_SINGLE_IMPORT: u64 = 8;
+/// Maximal number of blocks we're compacting in single import call.
+const MAX_BLOCKS_TO_COMPACT_IN_SINGLE_IMPORT: u64 = 8;
 
 /// Authority round engine configuration parameters.
 #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
@@ -105,6 +114,18 @@ pub struct StoredHeader<Submitter> {
 	pub last_signal_block: Option<HeaderId>,
 }
 
+/// Compacted block header as it is stored in the runtime storage.
+///
+/// Old finalized headers are compacted to save the storage. Transactions from compacted
+/// headers can't be verified anymore, but we still know that the header has been imported.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+pub struct CompactedHeader {
+	/// Number of the header.
+	pub number: u64,
+	/// Hash of the parent header.
+	pub parent_hash: H256,
+}
+
 /// Validators set as it is stored in the runtime storage.
 #[derive(Encode, Decode, PartialEq, RuntimeDebug)]
 #[cfg_attr(test, derive(Clone))]
@@ -345,6 +366,35 @@ impl ChainTime for () {
 	}
 }
 
+/// Consensus engine of the bridged chain.
+///
+/// The engine is responsible for verifying header seal and checking that the header has been
+/// produced by the right validator. Checks that are common for all engines (header number,
+/// gas used, timestamp) and validators set changes are handled by the module itself.
+pub trait ConsensusEngine {
+	/// Perform engine-specific checks that only require header itself.
+	fn contextless_checks(&self, header: &AuraHeader) -> Result<(), error::Error>;
+	/// Perform engine-specific checks that require access to parent header (and possibly
+	/// to other header ancestors).
+	fn contextual_checks<S: Storage>(
+		&self,
+		storage: &S,
+		context: &ImportContext<S::Submitter>,
+		header: &AuraHeader,
+	) -> Result<(), error::Error>;
+	/// Check that header is sealed by one of given validators.
+	///
+	/// This check must not require access to parent header, because it is also used to verify
+	/// headers with unknown parent in the transaction pool.
+	fn validator_checks(&self, validators: &[Address], header: &AuraHeader) -> Result<(), error::Error>;
+	/// Returns address of validator that has sealed given (verified) header.
+	fn header_author(&self, header: &AuraHeader) -> Address;
+	/// Returns validators set that is enacted by the header itself, if engine supports that.
+	fn enacted_validators(&self, header: &AuraHeader) -> Option<Vec<Address>>;
+	/// First block for which a 2/3 quorum (instead of 1/2) is required.
+	fn two_thirds_majority_transition(&self) -> u64;
+}
+
 /// Callbacks for header submission rewards/penalties.
 pub trait OnHeadersSubmitted<AccountId> {
 	/// Called when valid headers have been submitted.
@@ -369,10 +419,67 @@ impl<AccountId> OnHeadersSubmitted<AccountId> for () {
 	fn on_valid_headers_finalized(_submitter: AccountId, _finalized: u64) {}
 }
 
+/// Bond that is locked for every submitted header until it is either finalized or pruned.
+///
+/// The bond of finalized header is returned to the submitter. The bond of header that has been
+/// pruned without being finalized (i.e. header that is not on the canonical chain) is slashed.
+/// Unsigned headers are never bonded.
+pub trait HeadersBond<AccountId> {
+	/// Returns true if submitter is able to bond given number of headers.
+	fn can_bond(submitter: &AccountId, headers: u64) -> bool;
+	/// Lock bond for single header. Returns false if bond can't be locked.
+	fn bond(submitter: &AccountId) -> bool;
+	/// Return bond of single header to the submitter.
+	fn release(submitter: &AccountId);
+	/// Slash bond of single header.
+	fn slash(submitter: &AccountId);
+}
+
+impl<AccountId> HeadersBond<AccountId> for () {
+	fn can_bond(_submitter: &AccountId, _headers: u64) -> bool {
+		true
+	}
+	fn bond(_submitter: &AccountId) -> bool {
+		true
+	}
+	fn release(_submitter: &AccountId) {}
+	fn slash(_submitter: &AccountId) {}
+}
+
+/// Headers bond that reserves `Bond` funds of the submitter for every header.
+///
+/// Slashed funds are burned.
+pub struct CurrencyHeadersBond<C, Bond>(PhantomData<(C, Bond)>);
+
+impl<AccountId, C, Bond> HeadersBond<AccountId> for CurrencyHeadersBond<C, Bond>
+where
+	C: ReservableCurrency<AccountId>,
+	Bond: Get<<C as Currency<AccountId>>::Balance>,
+{
+	fn can_bond(submitter: &AccountId, headers: u64) -> bool {
+		let total_bond = Bond::get().saturating_mul(headers.saturated_into());
+		C::can_reserve(submitter, total_bond)
+	}
+
+	fn bond(submitter: &AccountId) -> bool {
+		C::reserve(submitter, Bond::get()).is_ok()
+	}
+
+	fn release(submitter: &AccountId) {
+		C::unreserve(submitter, Bond::get());
+	}
+
+	fn slash(submitter: &AccountId) {
+		let _ = C::slash_reserved(submitter, Bond::get());
+	}
+}
+
 /// The module configuration trait.
 pub trait Trait<I = DefaultInstance>: frame_system::Trait {
-	/// Aura configuration.
-	type AuraConfiguration: Get<AuraConfiguration>;
+	/// Consensus engine of the bridged chain.
+	type Engine: ConsensusEngine;
+	/// Consensus engine configuration.
+	type EngineConfiguration: Get<Self::Engine>;
 	/// Validators configuration.
 	type ValidatorsConfiguration: Get<validators::ValidatorsConfiguration>;
 
@@ -383,6 +490,10 @@ pub trait Trait<I = DefaultInstance>: frame_system::Trait {
 	/// be any significant finalization delays), or something that is bit larger
 	/// than average finalization delay.
 	type FinalityVotesCachingInterval: Get<Option<u64>>;
+	/// Number of finalized headers (behind best finalized header) that are kept in full.
+	/// Older finalized headers are compacted until they're pruned.
+	/// If None, compaction is disabled.
+	type HeadersCompactionDepth: Get<Option<u64>>;
 	/// Headers pruning strategy.
 	type PruningStrategy: PruningStrategy;
 	/// Header timestamp verification against current on-chain time.
@@ -390,10 +501,16 @@ pub trait Trait<I = DefaultInstance>: frame_system::Trait {
 
 	/// Handler for headers submission result.
 	type OnHeadersSubmitted: OnHeadersSubmitted<Self::AccountId>;
+	/// Bond that is locked for every signed header until it is finalized or pruned.
+	type HeadersBond: HeadersBond<Self::AccountId>;
 }
 
 decl_module! {
 	pub struct Module<T: Trait<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
+		fn on_runtime_upgrade() -> Weight {
+			initialize_compaction::<T, I>()
+		}
+
 		/// Import single Aura header. Requires transaction to be **UNSIGNED**.
 		#[weight = 0] // TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
 		pub fn import_unsigned_header(origin, header: AuraHeader, receipts: Option<Vec<Receipt>>) {
@@ -402,7 +519,7 @@ decl_module! {
 			import::import_header(
 				&mut BridgeStorage::<T, I>::new(),
 				&mut T::PruningStrategy::default(),
-				&T::AuraConfiguration::get(),
+				&T::EngineConfiguration::get(),
 				&T::ValidatorsConfiguration::get(),
 				None,
 				header,
@@ -415,16 +532,22 @@ decl_module! {
 		/// Ignores non-fatal errors (like when known header is provided), rewards
 		/// for successful headers import and penalizes for fatal errors.
 		///
+		/// Submitter must be able to bond every submitted header (see `HeadersBond`).
+		///
 		/// This should be used with caution - passing too many headers could lead to
 		/// enormous block production/import time.
 		#[weight = 0] // TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
 		pub fn import_signed_headers(origin, headers_with_receipts: Vec<(AuraHeader, Option<Vec<Receipt>>)>) {
 			let submitter = frame_system::ensure_signed(origin)?;
+			if !T::HeadersBond::can_bond(&submitter, headers_with_receipts.len() as u64) {
+				return Err(error::Error::NotEnoughFundsForBond.msg().into());
+			}
+
 			let mut finalized_headers = BTreeMap::new();
 			let import_result = import::import_headers(
 				&mut BridgeStorage::<T, I>::new(),
 				&mut T::PruningStrategy::default(),
-				&T::AuraConfiguration::get(),
+				&T::EngineConfiguration::get(),
 				&T::ValidatorsConfiguration::get(),
 				Some(submitter.clone()),
 				headers_with_receipts,
@@ -464,8 +587,12 @@ decl_storage! {
 		FinalizedBlock: HeaderId;
 		/// Range of blocks that we want to prune.
 		BlocksToPrune: PruningRange;
+		/// Number of the oldest block(s) that haven't been compacted yet.
+		OldestUncompactedBlock: u64;
 		/// Map of imported headers by hash.
 		Headers: map hasher(identity) H256 => Option<StoredHeader<T::AccountId>>;
+		/// Map of compacted headers by hash.
+		CompactedHeaders: map hasher(identity) H256 => Option<CompactedHeader>;
 		/// Map of imported header hashes by number.
 		HeadersByNumber: map hasher(blake2_128_concat) u64 => Option<Vec<H256>>;
 		/// Map of cached finality data by header hash.
@@ -478,6 +605,10 @@ decl_storage! {
 		/// the reference count. When we prune this header, we decrease the reference count.
 		/// When it reaches zero, we are free to prune validator set as well.
 		ValidatorsSetsRc: map hasher(twox_64_concat) u64 => Option<u64>;
+		/// Map of submitters of bonded headers by header hash.
+		HeaderBonds: map hasher(identity) H256 => Option<T::AccountId>;
+		/// Number of currently bonded headers by submitter.
+		pub BondedHeaders: map hasher(blake2_128_concat) T::AccountId => u64;
 		/// Map of validators set changes scheduled by given header.
 		ScheduledChanges: map hasher(identity) H256 => Option<AuraScheduledChange>;
 	}
@@ -529,7 +660,7 @@ impl<T: Trait<I>, I: Instance> Module<T, I> {
 
 	/// Returns true if header is known to the runtime.
 	pub fn is_known_block(hash: H256) -> bool {
-		BridgeStorage::<T, I>::new().header(&hash).is_some()
+		BridgeStorage::<T, I>::new().header(&hash).is_some() || CompactedHeaders::<I>::contains_key(hash)
 	}
 
 	/// Verify that transaction is included into given finalized block.
@@ -550,7 +681,7 @@ impl<T: Trait<I>, I: Instance> frame_support::unsigned::ValidateUnsigned for Mod
 			Self::Call::import_unsigned_header(ref header, ref receipts) => {
 				let accept_result = verification::accept_aura_header_into_pool(
 					&BridgeStorage::<T, I>::new(),
-					&T::AuraConfiguration::get(),
+					&T::EngineConfiguration::get(),
 					&T::ValidatorsConfiguration::get(),
 					&pool_configuration(),
 					header,
@@ -665,8 +796,18 @@ impl<T: Trait<I>, I: Instance> BridgeStorage<T, I> {
 				hash,
 			);
 
+			CompactedHeaders::<I>::remove(hash);
 			ScheduledChanges::<I>::remove(hash);
 			FinalityCache::<T, I>::remove(hash);
+			if let Some(submitter) = self.take_header_bond(&hash) {
+				// bonds of canonical headers are released on finalization => if header at finalized
+				// height is still bonded, it is not canonical
+				if number <= finalized_number {
+					T::HeadersBond::slash(&submitter);
+				} else {
+					T::HeadersBond::release(&submitter);
+				}
+			}
 			if let Some(header) = header {
 				ValidatorsSetsRc::<I>::mutate(header.next_validators_set_id, |rc| match *rc {
 					Some(rc) if rc > 1 => Some(rc - 1),
@@ -681,6 +822,108 @@ impl<T: Trait<I>, I: Instance> BridgeStorage<T, I> {
 			}
 		}
 	}
+
+	/// Compact old finalized blocks.
+	///
+	/// All blocks with number < `compact_end` are compacted. We never compact blocks that
+	/// are not yet finalized, so `compact_end` must be less than or equal to best finalized
+	/// block number.
+	fn compact_blocks(&self, mut max_blocks_to_compact: u64, compact_end: u64) {
+		// there's no need to compact blocks that are going to be pruned anyway
+		let oldest_uncompacted_block = OldestUncompactedBlock::<I>::get();
+		let begin = sp_std::cmp::max(oldest_uncompacted_block, BlocksToPrune::<I>::get().oldest_block_to_keep);
+
+		let mut new_oldest_uncompacted_block = begin;
+		frame_support::debug::trace!(target: "runtime", "Compacting blocks in range [{}..{})", begin, compact_end);
+		for number in begin..compact_end {
+			// if we can't compact anything => break
+			if max_blocks_to_compact == 0 {
+				break;
+			}
+
+			for hash in HeadersByNumber::<I>::get(number).unwrap_or_default() {
+				self.compact_block(number, hash);
+				max_blocks_to_compact = max_blocks_to_compact.saturating_sub(1);
+			}
+
+			new_oldest_uncompacted_block = number + 1;
+		}
+
+		if new_oldest_uncompacted_block != oldest_uncompacted_block {
+			OldestUncompactedBlock::<I>::put(new_oldest_uncompacted_block);
+		}
+	}
+
+	/// Replace full header with its compacted version.
+	fn compact_block(&self, number: u64, hash: H256) {
+		let header = match Headers::<T, I>::take(&hash) {
+			Some(header) => header,
+			None => return,
+		};
+
+		frame_support::debug::trace!(
+			target: "runtime",
+			"Compacting PoA header: ({}, {})",
+			number,
+			hash,
+		);
+
+		FinalityCache::<T, I>::remove(hash);
+		ValidatorsSetsRc::<I>::mutate(header.next_validators_set_id, |rc| match *rc {
+			Some(rc) if rc > 1 => Some(rc - 1),
+			_ => None,
+		});
+		CompactedHeaders::<I>::insert(
+			hash,
+			CompactedHeader {
+				number,
+				parent_hash: header.header.parent_hash,
+			},
+		);
+	}
+
+	/// Lock bond of header, submitted by given submitter.
+	fn bond_header(&self, hash: H256, submitter: &T::AccountId) {
+		if !T::HeadersBond::bond(submitter) {
+			frame_support::debug::trace!(
+				target: "runtime",
+				"Failed to bond PoA header: {}",
+				hash,
+			);
+			return;
+		}
+
+		HeaderBonds::<T, I>::insert(hash, submitter);
+		BondedHeaders::<T, I>::mutate(submitter, |count| *count += 1);
+	}
+
+	/// Forget bond of given header. Returns submitter of the header if it has been bonded.
+	fn take_header_bond(&self, hash: &H256) -> Option<T::AccountId> {
+		let submitter = HeaderBonds::<T, I>::take(hash)?;
+		BondedHeaders::<T, I>::mutate_exists(&submitter, |count| {
+			*count = count.map(|count| count.saturating_sub(1)).filter(|count| *count != 0);
+		});
+		Some(submitter)
+	}
+
+	/// Release bonds of all headers in (prev_finalized_number; finalized.number] range that are
+	/// ancestors of (or equal to) the `finalized` header.
+	fn release_finalized_headers_bonds(&self, prev_finalized_number: u64, finalized: HeaderId) {
+		let mut current = finalized;
+		while current.number > prev_finalized_number {
+			if let Some(submitter) = self.take_header_bond(&current.hash) {
+				T::HeadersBond::release(&submitter);
+			}
+
+			current = match Headers::<T, I>::get(&current.hash) {
+				Some(header) if current.number != 0 => HeaderId {
+					number: current.number - 1,
+					hash: header.header.parent_hash,
+				},
+				_ => break,
+			};
+		}
+	}
 }
 
 impl<T: Trait<I>, I: Instance> Storage for BridgeStorage<T, I> {
@@ -825,6 +1068,10 @@ impl<T: Trait<I>, I: Instance> Storage for BridgeStorage<T, I> {
 			header.id.hash,
 		);
 
+		if let Some(ref submitter) = header.context.submitter {
+			self.bond_header(header.id.hash, submitter);
+		}
+
 		let last_signal_block = header.context.last_signal_block();
 		HeadersByNumber::<I>::append(header.id.number, header.id.hash);
 		Headers::<T, I>::insert(
@@ -853,11 +1100,20 @@ impl<T: Trait<I>, I: Instance> Storage for BridgeStorage<T, I> {
 				finalized.hash,
 			);
 
+			self.release_finalized_headers_bonds(FinalizedBlock::<I>::get().number, finalized);
 			FinalizedBlock::<I>::put(finalized);
 		}
 
 		// and now prune headers if we need to
 		self.prune_blocks(MAX_BLOCKS_TO_PRUNE_IN_SINGLE_IMPORT, finalized_number, prune_end);
+
+		// and compact old finalized headers that we keep
+		if let Some(compaction_depth) = T::HeadersCompactionDepth::get() {
+			self.compact_blocks(
+				MAX_BLOCKS_TO_COMPACT_IN_SINGLE_IMPORT,
+				finalized_number.saturating_sub(compaction_depth),
+			);
+		}
 	}
 }
 
@@ -886,6 +1142,7 @@ pub(crate) fn initialize_storage<T: Trait<I>, I: Instance>(
 		oldest_unpruned_block: initial_header.number,
 		oldest_block_to_keep: initial_header.number,
 	});
+	OldestUncompactedBlock::<I>::put(initial_header.number);
 	HeadersByNumber::<I>::insert(initial_header.number, vec![initial_hash]);
 	Headers::<T, I>::insert(
 		initial_hash,
@@ -909,6 +1166,58 @@ pub(crate) fn initialize_storage<T: Trait<I>, I: Instance>(
 	ValidatorsSetsRc::<I>::insert(0, 1);
 }
 
+/// Initialize headers compaction of the module that has been deployed before compaction
+/// has been introduced.
+///
+/// Compaction starts from the oldest unpruned block, so all headers that have been imported
+/// earlier are compacted gradually, during regular headers import.
+fn initialize_compaction<T: Trait<I>, I: Instance>() -> Weight {
+	if OldestUncompactedBlock::<I>::exists() {
+		return T::DbWeight::get().reads(1);
+	}
+
+	OldestUncompactedBlock::<I>::put(BlocksToPrune::<I>::get().oldest_unpruned_block);
+	T::DbWeight::get().reads_writes(2, 1)
+}
+
+/// Storage checks that are performed when rehearsing runtime upgrade against the state snapshot.
+#[cfg(feature = "try-runtime")]
+impl<T: Trait<I>, I: Instance> Module<T, I> {
+	/// Check pallet storage before `on_runtime_upgrade` is called.
+	pub fn pre_upgrade() -> Result<(), &'static str> {
+		ensure_pruning_range_is_valid::<I>()
+	}
+
+	/// Check pallet storage after `on_runtime_upgrade` is called.
+	pub fn post_upgrade() -> Result<(), &'static str> {
+		ensure_pruning_range_is_valid::<I>()?;
+		frame_support::ensure!(
+			OldestUncompactedBlock::<I>::exists(),
+			"Headers compaction is not initialized"
+		);
+		frame_support::ensure!(
+			OldestUncompactedBlock::<I>::get() <= FinalizedBlock::<I>::get().number + 1,
+			"Headers compaction is ahead of finalization"
+		);
+		Ok(())
+	}
+}
+
+/// Ensure that the range of blocks to prune is consistent with the finalized block.
+#[cfg(feature = "try-runtime")]
+fn ensure_pruning_range_is_valid<I: Instance>() -> Result<(), &'static str> {
+	let blocks_to_prune = BlocksToPrune::<I>::get();
+	frame_support::ensure!(
+		blocks_to_prune.oldest_unpruned_block <= blocks_to_prune.oldest_block_to_keep,
+		"Oldest unpruned block is newer than the oldest block to keep"
+	);
+	frame_support::ensure!(
+		blocks_to_prune.oldest_block_to_keep <= FinalizedBlock::<I>::get().number,
+		"Non-finalized blocks are scheduled for pruning"
+	);
+	Ok(())
+}
+
 /// Verify that transaction is included into given finalized block.
 pub fn verify_transaction_finalized<S: Storage>(
 	storage: &S,
@@ -1046,8 +1355,8 @@ pub(crate) mod tests {
 	use super::*;
 	use crate::finality::FinalityAncestor;
 	use crate::mock::{
-		genesis, insert_header, run_test, run_test_with_genesis, validators_addresses, HeaderBuilder, TestRuntime,
-		GAS_LIMIT,
+		genesis, insert_header, run_test, run_test_with_genesis, validators_addresses, HeaderBuilder, HeadersBondsLog,
+		Origin, TestHeadersBond, TestRuntime, GAS_LIMIT, POOR_SUBMITTER,
 	};
 	use bp_eth_poa::compute_merkle_root;
 
@@ -1064,6 +1373,7 @@ pub(crate) mod tests {
 			gas_used: Default::default(),
 			log_bloom: Default::default(),
 			logs: Vec::new(),
+			transaction_type: None,
 		}
 		.rlp()
 	}
@@ -1256,6 +1566,133 @@ pub(crate) mod tests {
 		});
 	}
 
+	fn is_compacted(number: u64) -> bool {
+		let hashes = HeadersByNumber::<DefaultInstance>::get(&number).unwrap();
+		hashes.iter().all(|hash| {
+			!Headers::<TestRuntime>::contains_key(hash)
+				&& CompactedHeaders::<DefaultInstance>::get(hash).map(|header| header.number) == Some(number)
+				&& Module::<TestRuntime>::is_known_block(*hash)
+		})
+	}
+
+	#[test]
+	fn blocks_are_compacted_if_limit_is_non_zero() {
+		with_headers_to_prune(|storage| {
+			// try to compact blocks [0; 10)
+			storage.compact_blocks(7, 10);
+			// 1 header with number = 0 is compacted (1 total)
+			assert!(is_compacted(0));
+			// 5 headers with number = 1 are compacted (6 total)
+			assert!(is_compacted(1));
+			// all 5 headers with number = 2 are compacted (11 total)
+			assert!(is_compacted(2));
+			assert!(!is_compacted(3));
+			assert_eq!(OldestUncompactedBlock::<DefaultInstance>::get(), 3);
+
+			// try to compact blocks [3; 5)
+			storage.compact_blocks(0xFFFF, 5);
+			assert!(is_compacted(3));
+			assert!(is_compacted(4));
+			assert!(!is_compacted(5));
+			assert_eq!(OldestUncompactedBlock::<DefaultInstance>::get(), 5);
+		});
+	}
+
+	#[test]
+	fn blocks_scheduled_for_pruning_are_not_compacted() {
+		with_headers_to_prune(|storage| {
+			BlocksToPrune::<DefaultInstance>::put(PruningRange {
+				oldest_unpruned_block: 0,
+				oldest_block_to_keep: 3,
+			});
+
+			// try to compact blocks [0; 5)
+			storage.compact_blocks(0xFFFF, 5);
+			assert!(!is_compacted(2));
+			assert!(is_compacted(3));
+			assert!(is_compacted(4));
+			assert_eq!(OldestUncompactedBlock::<DefaultInstance>::get(), 5);
+		});
+	}
+
+	#[test]
+	fn compacted_blocks_are_pruned() {
+		with_headers_to_prune(|storage| {
+			let hashes = HeadersByNumber::<DefaultInstance>::get(&1).unwrap();
+
+			// compact blocks [0; 5) and then prune blocks [0; 3)
+			storage.compact_blocks(0xFFFF, 5);
+			storage.prune_blocks(0xFFFF, 10, 3);
+			assert!(hashes
+				.iter()
+				.all(|hash| !CompactedHeaders::<DefaultInstance>::contains_key(hash)));
+			assert!(is_compacted(3));
+		});
+	}
+
+	#[test]
+	fn transaction_from_compacted_block_is_not_verified() {
+		run_test(TOTAL_VALIDATORS, |_| {
+			let mut storage = BridgeStorage::<TestRuntime>::new();
+			insert_header(&mut storage, example_header_parent());
+			insert_header(&mut storage, example_header());
+			storage.finalize_and_prune_headers(Some(example_header().compute_id()), 0);
+			let verify =
+				|hash| verify_transaction_finalized(&storage, hash, 0, &[(example_tx(), example_tx_receipt(true))]);
+			assert!(verify(example_header_parent().compute_hash()));
+
+			// compact blocks [0; 2)
+			storage.compact_blocks(0xFFFF, 2);
+			assert!(!verify(example_header_parent().compute_hash()));
+			assert!(verify(example_header().compute_hash()));
+		});
+	}
+
+	#[test]
+	fn compaction_is_initialized_on_runtime_upgrade() {
+		run_test(TOTAL_VALIDATORS, |_| {
+			OldestUncompactedBlock::<DefaultInstance>::kill();
+			BlocksToPrune::<DefaultInstance>::put(PruningRange {
+				oldest_unpruned_block: 3,
+				oldest_block_to_keep: 5,
+			});
+
+			initialize_compaction::<TestRuntime, DefaultInstance>();
+			assert_eq!(OldestUncompactedBlock::<DefaultInstance>::get(), 3);
+
+			// second call doesn't change anything
+			OldestUncompactedBlock::<DefaultInstance>::put(4);
+			initialize_compaction::<TestRuntime, DefaultInstance>();
+			assert_eq!(OldestUncompactedBlock::<DefaultInstance>::get(), 4);
+		});
+	}
+
+	#[test]
+	#[cfg(feature = "try-runtime")]
+	fn runtime_upgrade_checks_are_passing_after_compaction_is_initialized() {
+		run_test(TOTAL_VALIDATORS, |_| {
+			OldestUncompactedBlock::<DefaultInstance>::kill();
+
+			assert_eq!(Module::<TestRuntime>::pre_upgrade(), Ok(()));
+			assert_eq!(
+				Module::<TestRuntime>::post_upgrade(),
+				Err("Headers compaction is not initialized"),
+			);
+
+			initialize_compaction::<TestRuntime, DefaultInstance>();
+			assert_eq!(Module::<TestRuntime>::post_upgrade(), Ok(()));
+
+			BlocksToPrune::<DefaultInstance>::put(PruningRange {
+				oldest_unpruned_block: 0,
+				oldest_block_to_keep: 1,
+			});
+			assert_eq!(
+				Module::<TestRuntime>::pre_upgrade(),
+				Err("Non-finalized blocks are scheduled for pruning"),
+			);
+		});
+	}
+
 	#[test]
 	fn finality_votes_are_cached() {
 		run_test(TOTAL_VALIDATORS, |ctx| {
@@ -1385,6 +1822,77 @@ pub(crate) mod tests {
 		});
 	}
 
+	#[test]
+	fn headers_bonds_are_released_on_finalization_and_slashed_on_pruning() {
+		run_test(TOTAL_VALIDATORS, |_| {
+			let mut finalized_header_sibling = example_header();
+			finalized_header_sibling.timestamp = 1;
+			let finalized_header_sibling_hash = finalized_header_sibling.compute_hash();
+
+			let mut storage = BridgeStorage::<TestRuntime>::new();
+			insert_header(&mut storage, example_header_parent());
+			insert_header(&mut storage, example_header());
+			insert_header(&mut storage, finalized_header_sibling);
+			storage.bond_header(example_header_parent().compute_hash(), &1);
+			storage.bond_header(example_header().compute_hash(), &1);
+			storage.bond_header(finalized_header_sibling_hash, &2);
+			assert_eq!(BondedHeaders::<TestRuntime>::get(&1), 2);
+			assert_eq!(BondedHeaders::<TestRuntime>::get(&2), 1);
+
+			// when header#2 is finalized, bonds of header#1 and header#2 are released
+			storage.finalize_and_prune_headers(Some(example_header().compute_id()), 0);
+			assert!(!BondedHeaders::<TestRuntime>::contains_key(&1));
+			assert_eq!(BondedHeaders::<TestRuntime>::get(&2), 1);
+
+			// when sibling of finalized header is pruned, its bond is slashed
+			storage.finalize_and_prune_headers(None, 3);
+			assert!(!BondedHeaders::<TestRuntime>::contains_key(&2));
+			assert!(!HeaderBonds::<TestRuntime>::contains_key(&finalized_header_sibling_hash));
+			assert_eq!(
+				TestHeadersBond::log(),
+				HeadersBondsLog {
+					bonded: vec![1, 1, 2],
+					released: vec![1, 1],
+					slashed: vec![2],
+				},
+			);
+		});
+	}
+
+	#[test]
+	fn headers_bonds_are_released_when_unfinalized_headers_are_pruned() {
+		run_test(TOTAL_VALIDATORS, |_| {
+			let mut storage = BridgeStorage::<TestRuntime>::new();
+			insert_header(&mut storage, example_header_parent());
+			storage.bond_header(example_header_parent().compute_hash(), &1);
+
+			storage.finalize_and_prune_headers(None, 2);
+			assert!(!BondedHeaders::<TestRuntime>::contains_key(&1));
+			assert_eq!(
+				TestHeadersBond::log(),
+				HeadersBondsLog {
+					bonded: vec![1],
+					released: vec![1],
+					slashed: vec![],
+				},
+			);
+		});
+	}
+
+	#[test]
+	fn import_signed_headers_rejects_headers_that_can_not_be_bonded() {
+		run_test(TOTAL_VALIDATORS, |ctx| {
+			let header = HeaderBuilder::with_parent(&ctx.genesis).sign_by_set(&ctx.validators);
+			let header_hash = header.compute_hash();
+			assert_eq!(
+				Module::<TestRuntime>::import_signed_headers(Origin::signed(POOR_SUBMITTER), vec![(header, None)]),
+				Err(error::Error::NotEnoughFundsForBond.msg().into()),
+			);
+			assert!(!Headers::<TestRuntime>::contains_key(&header_hash));
+			assert_eq!(TestHeadersBond::log(), HeadersBondsLog::default());
+		});
+	}
+
 	#[test]
 	fn verify_transaction_finalized_works_for_best_finalized_header() {
 		run_test_with_genesis(example_header(), TOTAL_VALIDATORS, |_| {
@@ -1401,6 +1909,33 @@ pub(crate) mod tests {
 		});
 	}
 
+	#[test]
+	fn verify_transaction_finalized_works_for_typed_transaction_receipt() {
+		let typed_tx_receipt = Receipt {
+			outcome: bp_eth_poa::TransactionOutcome::StatusCode(1),
+			gas_used: Default::default(),
+			log_bloom: Default::default(),
+			logs: Vec::new(),
+			transaction_type: Some(bp_eth_poa::DYNAMIC_FEE_TRANSACTION_TYPE),
+		}
+		.rlp();
+		let mut header = example_header();
+		header.receipts_root = compute_merkle_root(vec![typed_tx_receipt.clone()].into_iter());
+
+		run_test_with_genesis(header.clone(), TOTAL_VALIDATORS, |_| {
+			let storage = BridgeStorage::<TestRuntime>::new();
+			assert_eq!(
+				verify_transaction_finalized(
+					&storage,
+					header.compute_hash(),
+					0,
+					&[(example_tx(), typed_tx_receipt.clone())],
+				),
+				true,
+			);
+		});
+	}
+
 	#[test]
 	fn verify_transaction_finalized_works_for_best_finalized_header_ancestor() {
 		run_test(TOTAL_VALIDATORS, |_| {
@@ -18,7 +18,7 @@ pub use crate::test_utils::{insert_header, validator_utils::*, validators_change
 pub use bp_eth_poa::signatures::secret_to_address;
 
 use crate::validators::{ValidatorsConfiguration, ValidatorsSource};
-use crate::{AuraConfiguration, ChainTime, GenesisConfig, PruningStrategy, Trait};
+use crate::{AuraConfiguration, ChainTime, GenesisConfig, HeadersBond, PruningStrategy, Trait};
 use bp_eth_poa::{Address, AuraHeader, H256, U256};
 use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
 use secp256k1::SecretKey;
@@ -27,9 +27,17 @@ use sp_runtime::{
 	traits::{BlakeTwo256, IdentityLookup},
 	Perbill,
 };
+use std::cell::RefCell;
 
 pub type AccountId = u64;
 
+/// Account that is unable to bond any headers.
+pub const POOR_SUBMITTER: AccountId = 42;
+
+thread_local! {
+	static HEADERS_BONDS: RefCell<HeadersBondsLog> = RefCell::new(Default::default());
+}
+
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct TestRuntime;
 
@@ -74,17 +82,21 @@ impl frame_system::Trait for TestRuntime {
 
 parameter_types! {
 	pub const TestFinalityVotesCachingInterval: Option<u64> = Some(16);
+	pub const TestHeadersCompactionDepth: Option<u64> = None;
 	pub TestAuraConfiguration: AuraConfiguration = test_aura_config();
 	pub TestValidatorsConfiguration: ValidatorsConfiguration = test_validators_config();
 }
 
 impl Trait for TestRuntime {
-	type AuraConfiguration = TestAuraConfiguration;
+	type Engine = AuraConfiguration;
+	type EngineConfiguration = TestAuraConfiguration;
 	type ValidatorsConfiguration = TestValidatorsConfiguration;
 	type FinalityVotesCachingInterval = TestFinalityVotesCachingInterval;
+	type HeadersCompactionDepth = TestHeadersCompactionDepth;
 	type PruningStrategy = KeepSomeHeadersBehindBest;
 	type ChainTime = ConstChainTime;
 	type OnHeadersSubmitted = ();
+	type HeadersBond = TestHeadersBond;
 }
 
 /// Test context.
@@ -146,6 +158,7 @@ pub fn run_test_with_genesis<T>(
 		.unwrap(),
 	)
 	.execute_with(|| {
+		HEADERS_BONDS.with(|log| *log.borrow_mut() = Default::default());
 		test(TestContext {
 			genesis,
 			total_validators,
@@ -180,3 +193,43 @@ impl ChainTime for ConstChainTime {
 		timestamp > now
 	}
 }
+
+/// Operations, performed by the `TestHeadersBond`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HeadersBondsLog {
+	/// Submitters of bonded headers.
+	pub bonded: Vec<AccountId>,
+	/// Submitters of headers with released bonds.
+	pub released: Vec<AccountId>,
+	/// Submitters of headers with slashed bonds.
+	pub slashed: Vec<AccountId>,
+}
+
+/// Headers bond that is only recording performed operations.
+pub struct TestHeadersBond;
+
+impl TestHeadersBond {
+	/// Returns log of all operations, performed in current test.
+	pub fn log() -> HeadersBondsLog {
+		HEADERS_BONDS.with(|log| log.borrow().clone())
+	}
+}
+
+impl HeadersBond<AccountId> for TestHeadersBond {
+	fn can_bond(submitter: &AccountId, _headers: u64) -> bool {
+		*submitter != POOR_SUBMITTER
+	}
+
+	fn bond(submitter: &AccountId) -> bool {
+		HEADERS_BONDS.with(|log| log.borrow_mut().bonded.push(*submitter));
+		true
+	}
+
+	fn release(submitter: &AccountId) {
+		HEADERS_BONDS.with(|log| log.borrow_mut().released.push(*submitter));
+	}
+
+	fn slash(submitter: &AccountId) {
+		HEADERS_BONDS.with(|log| log.borrow_mut().slashed.push(*submitter));
+	}
+}
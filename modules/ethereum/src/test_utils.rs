@@ -248,6 +248,7 @@ pub fn validators_change_receipt(parent_hash: H256) -> Receipt {
 		gas_used: 0.into(),
 		log_bloom: (&[0xff; 256]).into(),
 		outcome: TransactionOutcome::Unknown,
+		transaction_type: None,
 		logs: vec![LogEntry {
 			address: [3; 20].into(),
 			topics: vec![CHANGE_EVENT_HASH.into(), parent_hash],
@@ -16,7 +16,9 @@
 
 use crate::error::Error;
 use crate::validators::{Validators, ValidatorsConfiguration};
-use crate::{AuraConfiguration, AuraScheduledChange, ChainTime, ImportContext, PoolConfiguration, Storage};
+use crate::{
+	AuraConfiguration, AuraScheduledChange, ChainTime, ConsensusEngine, ImportContext, PoolConfiguration, Storage,
+};
 use bp_eth_poa::{
 	public_to_address, step_validator, Address, AuraHeader, HeaderId, Receipt, SealedEmptyStep, H256, H520, U128, U256,
 };
@@ -46,9 +48,9 @@ pub fn is_importable_header<S: Storage>(storage: &S, header: &AuraHeader) -> Res
 /// Try accept unsigned aura header into transaction pool.
 ///
 /// Returns required and provided tags.
-pub fn accept_aura_header_into_pool<S: Storage, CT: ChainTime>(
+pub fn accept_aura_header_into_pool<S: Storage, E: ConsensusEngine, CT: ChainTime>(
 	storage: &S,
-	config: &AuraConfiguration,
+	engine: &E,
 	validators_config: &ValidatorsConfiguration,
 	pool_config: &PoolConfiguration,
 	header: &AuraHeader,
@@ -59,7 +61,7 @@ pub fn accept_aura_header_into_pool<S: Storage, CT: ChainTime>(
 	let (header_id, _) = is_importable_header(storage, header)?;
 
 	// we can always do contextless checks
-	contextless_checks(config, header, chain_time)?;
+	contextless_checks(engine, header, chain_time)?;
 
 	// we want to avoid having same headers twice in the pool
 	// => we're strict about receipts here - if we need them, we require receipts to be Some,
@@ -87,7 +89,7 @@ pub fn accept_aura_header_into_pool<S: Storage, CT: ChainTime>(
 	// we want to see at most one header with given number from single authority
 	// => every header is providing tag (block_number + authority)
 	// => since only one tx in the pool can provide the same tag, they're auto-deduplicated
-	let provides_number_and_authority_tag = (header.number, header.author).encode();
+	let provides_number_and_authority_tag = (header.number, engine.header_author(header)).encode();
 
 	// we want to see several 'future' headers in the pool at once, but we may not have access to
 	// previous headers here
@@ -99,8 +101,8 @@ pub fn accept_aura_header_into_pool<S: Storage, CT: ChainTime>(
 	let context = storage.import_context(None, &header.parent_hash);
 	let tags = match context {
 		Some(context) => {
-			let header_step = contextual_checks(config, &context, None, header)?;
-			validator_checks(config, &context.validators_set().validators, header, header_step)?;
+			engine.contextual_checks(storage, &context, header)?;
+			engine.validator_checks(&context.validators_set().validators, header)?;
 
 			// since our parent is already in the storage, we do not require it
 			// to be in the transaction pool
@@ -114,17 +116,15 @@ pub fn accept_aura_header_into_pool<S: Storage, CT: ChainTime>(
 			// => the best thing we can do is to believe that there are no forks in
 			// PoA chain AND that the header is produced either by previous, or next
 			// scheduled validators set change
-			let header_step = header.step().ok_or(Error::MissingStep)?;
 			let best_context = storage.import_context(None, &best_id.hash).expect(
 				"import context is None only when header is missing from the storage;\
 							best header is always in the storage; qed",
 			);
-			let validators_check_result =
-				validator_checks(config, &best_context.validators_set().validators, header, header_step);
+			let validators_check_result = engine.validator_checks(&best_context.validators_set().validators, header);
 			if let Err(error) = validators_check_result {
 				find_next_validators_signal(storage, &best_context)
 					.ok_or(error)
-					.and_then(|next_validators| validator_checks(config, &next_validators, header, header_step))?;
+					.and_then(|next_validators| engine.validator_checks(&next_validators, header))?;
 			}
 
 			// since our parent is missing from the storage, we **DO** require it
@@ -153,16 +153,16 @@ pub fn accept_aura_header_into_pool<S: Storage, CT: ChainTime>(
 	Ok(tags)
 }
 
-/// Verify header by Aura rules.
-pub fn verify_aura_header<S: Storage, CT: ChainTime>(
+/// Verify header by consensus engine rules.
+pub fn verify_aura_header<S: Storage, E: ConsensusEngine, CT: ChainTime>(
 	storage: &S,
-	config: &AuraConfiguration,
+	engine: &E,
 	submitter: Option<S::Submitter>,
 	header: &AuraHeader,
 	chain_time: &CT,
 ) -> Result<ImportContext<S::Submitter>, Error> {
 	// let's do the lightest check first
-	contextless_checks(config, header, chain_time)?;
+	contextless_checks(engine, header, chain_time)?;
 
 	// the rest of checks requires access to the parent header
 	let context = storage.import_context(submitter, &header.parent_hash).ok_or_else(|| {
@@ -175,37 +175,72 @@ pub fn verify_aura_header<S: Storage, CT: ChainTime>(
 
 		Error::MissingParentBlock
 	})?;
-	let header_step = contextual_checks(config, &context, None, header)?;
-	validator_checks(config, &context.validators_set().validators, header, header_step)?;
+	engine.contextual_checks(storage, &context, header)?;
+	engine.validator_checks(&context.validators_set().validators, header)?;
 
 	Ok(context)
 }
 
+impl ConsensusEngine for AuraConfiguration {
+	fn contextless_checks(&self, header: &AuraHeader) -> Result<(), Error> {
+		let expected_seal_fields = expected_header_seal_fields(self, header);
+		if header.seal.len() != expected_seal_fields {
+			return Err(Error::InvalidSealArity);
+		}
+		if header.gas_limit < self.min_gas_limit {
+			return Err(Error::InvalidGasLimit);
+		}
+		if header.gas_limit > self.max_gas_limit {
+			return Err(Error::InvalidGasLimit);
+		}
+		if header.number != 0 && header.extra_data.len() as u64 > self.maximum_extra_data_size {
+			return Err(Error::ExtraDataOutOfBounds);
+		}
+
+		Ok(())
+	}
+
+	fn contextual_checks<S: Storage>(
+		&self,
+		_storage: &S,
+		context: &ImportContext<S::Submitter>,
+		header: &AuraHeader,
+	) -> Result<(), Error> {
+		contextual_checks(self, context, header)
+	}
+
+	fn validator_checks(&self, validators: &[Address], header: &AuraHeader) -> Result<(), Error> {
+		let header_step = header.step().ok_or(Error::MissingStep)?;
+		validator_checks(self, validators, header, header_step)
+	}
+
+	fn header_author(&self, header: &AuraHeader) -> Address {
+		header.author
+	}
+
+	fn enacted_validators(&self, _header: &AuraHeader) -> Option<Vec<Address>> {
+		None
+	}
+
+	fn two_thirds_majority_transition(&self) -> u64 {
+		self.two_thirds_majority_transition
+	}
+}
+
 /// Perform basic checks that only require header itself.
-fn contextless_checks<CT: ChainTime>(
-	config: &AuraConfiguration,
+fn contextless_checks<E: ConsensusEngine, CT: ChainTime>(
+	engine: &E,
 	header: &AuraHeader,
 	chain_time: &CT,
 ) -> Result<(), Error> {
-	let expected_seal_fields = expected_header_seal_fields(config, header);
-	if header.seal.len() != expected_seal_fields {
-		return Err(Error::InvalidSealArity);
-	}
+	engine.contextless_checks(header)?;
+
 	if header.number >= u64::max_value() {
 		return Err(Error::RidiculousNumber);
 	}
 	if header.gas_used > header.gas_limit {
 		return Err(Error::TooMuchGasUsed);
 	}
-	if header.gas_limit < config.min_gas_limit {
-		return Err(Error::InvalidGasLimit);
-	}
-	if header.gas_limit > config.max_gas_limit {
-		return Err(Error::InvalidGasLimit);
-	}
-	if header.number != 0 && header.extra_data.len() as u64 > config.maximum_extra_data_size {
-		return Err(Error::ExtraDataOutOfBounds);
-	}
 
 	// we can't detect if block is from future in runtime
 	// => let's only do an overflow check
@@ -220,14 +255,13 @@ fn contextless_checks<CT: ChainTime>(
 	Ok(())
 }
 
-/// Perform checks that require access to parent header.
+/// Perform Aura checks that require access to parent header.
 fn contextual_checks<Submitter>(
 	config: &AuraConfiguration,
 	context: &ImportContext<Submitter>,
-	validators_override: Option<&[Address]>,
 	header: &AuraHeader,
-) -> Result<u64, Error> {
-	let validators = validators_override.unwrap_or_else(|| &context.validators_set().validators);
+) -> Result<(), Error> {
+	let validators = &context.validators_set().validators;
 	let header_step = header.step().ok_or(Error::MissingStep)?;
 	let parent_step = context.parent_header().step().ok_or(Error::MissingStep)?;
 
@@ -276,10 +310,10 @@ fn contextual_checks<Submitter>(
 		}
 	}
 
-	Ok(header_step)
+	Ok(())
 }
 
-/// Check that block is produced by expected validator.
+/// Check that block is produced by expected Aura validator.
 fn validator_checks(
 	config: &AuraConfiguration,
 	validators: &[Address],
@@ -392,7 +426,7 @@ mod tests {
 	fn verify_with_config(config: &AuraConfiguration, header: &AuraHeader) -> Result<ImportContext<AccountId>, Error> {
 		run_test_with_genesis(genesis(), TOTAL_VALIDATORS, |_| {
 			let storage = BridgeStorage::<TestRuntime>::new();
-			verify_aura_header(&storage, &config, None, header, &ConstChainTime::default())
+			verify_aura_header(&storage, config, None, header, &ConstChainTime::default())
 		})
 	}
 
@@ -773,6 +807,7 @@ mod tests {
 					log_bloom: (&[0xff; 256]).into(),
 					logs: vec![],
 					outcome: TransactionOutcome::Unknown,
+					transaction_type: None,
 				}]),
 			),),
 			Err(Error::RedundantTransactionsReceipts),
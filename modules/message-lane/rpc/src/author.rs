@@ -0,0 +1,229 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC methods for composing and submitting message-lane transactions.
+
+use crate::error::{Error, FutureResult};
+
+use bp_message_lane::{LaneId, MessageNonce, OutboundLaneApi};
+use bp_runtime::InstanceId;
+use frame_system_rpc_runtime_api::AccountNonceApi;
+use futures::{FutureExt, TryFutureExt};
+use jsonrpc_derive::rpc;
+use sc_rpc_api::DenyUnsafe;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	crypto::{CryptoTypePublicPair, KeyTypeId},
+	sr25519,
+	traits::{BareCryptoStore, BareCryptoStorePtr},
+	Bytes,
+};
+use sp_runtime::{
+	codec::Codec,
+	generic::BlockId,
+	traits::{Block as BlockT, IdentifyAccount},
+	transaction_validity::TransactionSource,
+	AccountId32, MultiSigner,
+};
+use sp_transaction_pool::TransactionPool;
+use std::{convert::TryFrom, marker::PhantomData, sync::Arc};
+
+/// Account that signs `send_message` transactions.
+pub struct TransactionSigner<'a, Hash, Index> {
+	/// Public key of the signer.
+	pub public: sr25519::Public,
+	/// Nonce of the signer account.
+	pub nonce: Index,
+	/// Hash of the genesis block of this chain.
+	pub genesis_hash: Hash,
+	/// Signs given SCALE-encoded transaction payload.
+	pub sign: &'a dyn Fn(&[u8]) -> Result<sr25519::Signature, String>,
+}
+
+/// Builder of the signed `send_message` transactions.
+///
+/// The message lane pallet is generic over message payload and fee types, so the transaction
+/// is built by the runtime crate, which knows the actual runtime types.
+pub trait SendMessageTransactionBuilder<Block: BlockT, OutboundMessageFee>: Send + Sync + 'static {
+	/// Type of account nonce.
+	type Index: Codec;
+
+	/// Type of the node keystore key that is used to sign transactions. It is expected to be the
+	/// key of the block authoring engine (e.g. Aura or BABE), used by the chain.
+	const SIGNER_KEY_TYPE: KeyTypeId;
+
+	/// Instance of the message lane pallet that is used to send messages.
+	const INSTANCE: InstanceId;
+
+	/// Build transaction that sends message with given SCALE-encoded payload over given lane
+	/// and sign it by given signer.
+	fn build_send_message_transaction(
+		&self,
+		signer: TransactionSigner<Block::Hash, Self::Index>,
+		lane: LaneId,
+		payload: Vec<u8>,
+		fee: OutboundMessageFee,
+	) -> Result<Block::Extrinsic, String>;
+}
+
+/// Provides RPC methods for sending messages.
+#[rpc]
+pub trait MessageLaneAuthorApi<OutboundMessageFee> {
+	/// Sends message with given SCALE-encoded payload over given lane. The transaction is signed
+	/// using key from the node keystore and submitted to the transaction pool. Returns nonce that
+	/// is expected to be assigned to the message.
+	///
+	/// This is an unsafe method that is only available when node runs with unsafe RPC methods enabled.
+	#[rpc(name = "bridge_sendMessage")]
+	fn send_message(&self, lane: LaneId, payload: Bytes, fee: OutboundMessageFee) -> FutureResult<MessageNonce>;
+}
+
+/// Implements the MessageLaneAuthorApi trait for sending messages. Transactions are signed by the
+/// first sr25519 key of `SendMessageTransactionBuilder::SIGNER_KEY_TYPE` type from the node keystore.
+pub struct MessageLaneAuthorRpcHandler<Block, Client, Pool, Builder> {
+	client: Arc<Client>,
+	pool: Arc<Pool>,
+	keystore: BareCryptoStorePtr,
+	builder: Builder,
+	deny_unsafe: DenyUnsafe,
+	_phantom: PhantomData<Block>,
+}
+
+impl<Block, Client, Pool, Builder> MessageLaneAuthorRpcHandler<Block, Client, Pool, Builder> {
+	/// Creates new message lane author RPC handler.
+	pub fn new(
+		client: Arc<Client>,
+		pool: Arc<Pool>,
+		keystore: BareCryptoStorePtr,
+		builder: Builder,
+		deny_unsafe: DenyUnsafe,
+	) -> Self {
+		MessageLaneAuthorRpcHandler {
+			client,
+			pool,
+			keystore,
+			builder,
+			deny_unsafe,
+			_phantom: Default::default(),
+		}
+	}
+}
+
+impl<Block, Client, Pool, Builder, OutboundMessageFee> MessageLaneAuthorApi<OutboundMessageFee>
+	for MessageLaneAuthorRpcHandler<Block, Client, Pool, Builder>
+where
+	Block: BlockT,
+	Client: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	Client::Api: OutboundLaneApi<Block> + AccountNonceApi<Block, AccountId32, Builder::Index>,
+	Pool: TransactionPool<Block = Block> + 'static,
+	Builder: SendMessageTransactionBuilder<Block, OutboundMessageFee>,
+	OutboundMessageFee: Send + Sync + 'static,
+{
+	fn send_message(&self, lane: LaneId, payload: Bytes, fee: OutboundMessageFee) -> FutureResult<MessageNonce> {
+		if let Err(error) = self.deny_unsafe.check_if_safe() {
+			return Box::new(jsonrpc_core::futures::future::err(error.into()));
+		}
+
+		let best_block = self.client.info().best_hash;
+		let at = BlockId::Hash(best_block);
+
+		// the nonce is assigned when transaction is dispatched, so if there are other messages sent
+		// over the same lane in the same block, actual nonce will differ from the returned one
+		let expected_nonce = match self
+			.client
+			.runtime_api()
+			.latest_generated_nonce(&at, Builder::INSTANCE, lane)
+		{
+			Ok(Some(latest_generated_nonce)) => latest_generated_nonce + 1,
+			Ok(None) => return Box::new(jsonrpc_core::futures::future::err(Error::UnknownInstance.into())),
+			Err(error) => {
+				return Box::new(jsonrpc_core::futures::future::err(
+					Error::RuntimeApi(format!("{:?}", error)).into(),
+				))
+			}
+		};
+		let transaction = match self.build_transaction(&at, lane, payload.0, fee) {
+			Ok(transaction) => transaction,
+			Err(error) => {
+				return Box::new(jsonrpc_core::futures::future::err(
+					Error::TransactionBuildFailed(error).into(),
+				))
+			}
+		};
+
+		Box::new(
+			self.pool
+				.submit_one(&at, TransactionSource::Local, transaction)
+				.map_ok(move |_| expected_nonce)
+				.map_err(|error| jsonrpc_core::Error::from(Error::TransactionSubmitFailed(format!("{:?}", error))))
+				.boxed()
+				.compat(),
+		)
+	}
+}
+
+impl<Block, Client, Pool, Builder> MessageLaneAuthorRpcHandler<Block, Client, Pool, Builder>
+where
+	Block: BlockT,
+	Client: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	Client::Api: AccountNonceApi<Block, AccountId32, Builder::Index>,
+{
+	/// Build and sign `send_message` transaction that is valid at given block.
+	fn build_transaction<OutboundMessageFee>(
+		&self,
+		at: &BlockId<Block>,
+		lane: LaneId,
+		payload: Vec<u8>,
+		fee: OutboundMessageFee,
+	) -> Result<Block::Extrinsic, String>
+	where
+		Builder: SendMessageTransactionBuilder<Block, OutboundMessageFee>,
+	{
+		let keystore = self.keystore.read();
+		let public = keystore
+			.sr25519_public_keys(Builder::SIGNER_KEY_TYPE)
+			.into_iter()
+			.next()
+			.ok_or_else(|| "There are no signer keys in the node keystore".to_string())?;
+		let public_key = CryptoTypePublicPair::from(&public);
+		let account = MultiSigner::from(public).into_account();
+		let nonce = self
+			.client
+			.runtime_api()
+			.account_nonce(at, account)
+			.map_err(|e| format!("Failed to read account nonce: {:?}", e))?;
+		let sign = |payload: &[u8]| {
+			let signature = keystore
+				.sign_with(Builder::SIGNER_KEY_TYPE, &public_key, payload)
+				.map_err(|e| format!("Failed to sign transaction: {:?}", e))?;
+			sr25519::Signature::try_from(&signature[..])
+				.map_err(|_| "Keystore has returned invalid signature".to_string())
+		};
+
+		self.builder.build_send_message_transaction(
+			TransactionSigner {
+				public,
+				nonce,
+				genesis_hash: self.client.info().genesis_hash,
+				sign: &sign,
+			},
+			lane,
+			payload,
+			fee,
+		)
+	}
+}
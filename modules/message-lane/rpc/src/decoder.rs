@@ -0,0 +1,167 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC methods for decoding message payloads.
+
+use crate::error::{Error, FutureResult};
+use crate::{at_block, read_storage_value, runtime_api_err};
+
+use bp_message_lane::{LaneId, MessageLaneStorageApi, MessageNonce, MessagePayload, Weight};
+use bp_runtime::InstanceId;
+use jsonrpc_derive::rpc;
+use sc_client_api::Backend as BackendT;
+use serde::Serialize;
+use sp_api::ProvideRuntimeApi;
+use sp_core::{storage::StorageKey, Bytes};
+use sp_runtime::{codec::Decode, generic::BlockId, traits::Block as BlockT};
+use std::{marker::PhantomData, sync::Arc};
+
+/// Human-readable message payload.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedMessagePayload {
+	/// Runtime specification version of the target chain, declared by the message sender.
+	pub spec_version: u32,
+	/// Weight of the call, declared by the message sender.
+	pub weight: Weight,
+	/// Dispatch origin of the call.
+	pub origin: String,
+	/// Decoded call, if it may be decoded by this node. Calls of bridged chain are never decoded.
+	pub call: Option<String>,
+	/// SCALE-encoded call (single call or batch of calls). Calls of bridged chain are encoded as
+	/// opaque byte vectors.
+	pub encoded_call: Bytes,
+}
+
+/// Decoder of the message payloads.
+///
+/// The message lane pallet treats payloads as opaque bytes, so they're decoded by the node,
+/// which knows the actual runtime types.
+pub trait MessagePayloadDecoder: Send + Sync + 'static {
+	/// Decode payload of the message that has been sent by this chain.
+	fn decode_outbound_payload(&self, payload: &[u8]) -> Option<DecodedMessagePayload>;
+	/// Decode payload of the message that has been sent by the bridged chain.
+	fn decode_inbound_payload(&self, payload: &[u8]) -> Option<DecodedMessagePayload>;
+}
+
+/// Provides RPC methods for decoding message payloads.
+#[rpc]
+pub trait MessageLaneDecoderApi<BlockHash> {
+	/// Returns decoded payload of the outbound message with given nonce, read from the storage
+	/// at given block.
+	#[rpc(name = "messageLane_decodeOutboundMessagePayload")]
+	fn decode_outbound_message_payload(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		nonce: MessageNonce,
+		block: Option<BlockHash>,
+	) -> FutureResult<DecodedMessagePayload>;
+
+	/// Returns decoded SCALE-encoded payload of the message, sent by the bridged chain.
+	#[rpc(name = "messageLane_decodeInboundMessagePayload")]
+	fn decode_inbound_message_payload(&self, payload: Bytes) -> FutureResult<DecodedMessagePayload>;
+}
+
+/// Implements the MessageLaneDecoderApi trait for decoding message payloads.
+pub struct MessageLaneDecoderRpcHandler<Block, Backend, Client, Decoder> {
+	backend: Arc<Backend>,
+	client: Arc<Client>,
+	decoder: Decoder,
+	_phantom: PhantomData<Block>,
+}
+
+impl<Block, Backend, Client, Decoder> MessageLaneDecoderRpcHandler<Block, Backend, Client, Decoder> {
+	/// Creates new message lane decoder RPC handler.
+	pub fn new(backend: Arc<Backend>, client: Arc<Client>, decoder: Decoder) -> Self {
+		MessageLaneDecoderRpcHandler {
+			backend,
+			client,
+			decoder,
+			_phantom: Default::default(),
+		}
+	}
+}
+
+impl<Block, Backend, Client, Decoder> MessageLaneDecoderApi<Block::Hash>
+	for MessageLaneDecoderRpcHandler<Block, Backend, Client, Decoder>
+where
+	Block: BlockT,
+	Backend: BackendT<Block> + 'static,
+	Client: ProvideRuntimeApi<Block> + Send + Sync + 'static,
+	Client::Api: MessageLaneStorageApi<Block>,
+	Decoder: MessagePayloadDecoder,
+{
+	fn decode_outbound_message_payload(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		nonce: MessageNonce,
+		block: Option<Block::Hash>,
+	) -> FutureResult<DecodedMessagePayload> {
+		Box::new(jsonrpc_core::futures::future::result(
+			self.outbound_message_payload(instance, lane, nonce, block)
+				.and_then(|payload| {
+					self.decoder
+						.decode_outbound_payload(&payload)
+						.ok_or(Error::PayloadDecodeFailed)
+				})
+				.map_err(Into::into),
+		))
+	}
+
+	fn decode_inbound_message_payload(&self, payload: Bytes) -> FutureResult<DecodedMessagePayload> {
+		Box::new(jsonrpc_core::futures::future::result(
+			self.decoder
+				.decode_inbound_payload(&payload.0)
+				.ok_or(Error::PayloadDecodeFailed)
+				.map_err(Into::into),
+		))
+	}
+}
+
+impl<Block, Backend, Client, Decoder> MessageLaneDecoderRpcHandler<Block, Backend, Client, Decoder>
+where
+	Block: BlockT,
+	Backend: BackendT<Block> + 'static,
+	Client: ProvideRuntimeApi<Block>,
+	Client::Api: MessageLaneStorageApi<Block>,
+{
+	/// Read payload of the outbound message from the storage at given block.
+	fn outbound_message_payload(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		nonce: MessageNonce,
+		block: Option<Block::Hash>,
+	) -> Result<MessagePayload, Error> {
+		let block = at_block(&*self.backend, block)?;
+		let key = self
+			.client
+			.runtime_api()
+			.messages_keys(&BlockId::Hash(block), instance, lane, nonce, nonce)
+			.map_err(runtime_api_err)?
+			.ok_or(Error::UnknownInstance)?
+			.into_iter()
+			.next()
+			.map(StorageKey)
+			.ok_or(Error::UnknownMessage)?;
+		let encoded_message_data = read_storage_value(&*self.backend, block, &key)?.ok_or(Error::UnknownMessage)?;
+
+		// payload is the first field of the `MessageData`, so we don't need to know fee type here
+		MessagePayload::decode(&mut &encoded_message_data[..]).map_err(|_| Error::PayloadDecodeFailed)
+	}
+}
@@ -25,6 +25,56 @@ pub enum Error {
 	/// When unknown instance id is passed.
 	#[display(fmt = "Message lane instance is unknown")]
 	UnknownInstance,
+	/// When runtime is unable to estimate message fee. This may happen if message payload
+	/// can't be decoded or if fee computation overflows.
+	#[display(fmt = "Failed to estimate message delivery and dispatch fee")]
+	#[from(ignore)]
+	FeeEstimationFailed,
+	/// When block is unknown to the node.
+	#[display(fmt = "Block is unknown")]
+	#[from(ignore)]
+	UnknownBlock,
+	/// When state of the block has been pruned by the node.
+	#[display(fmt = "State of the block is unavailable")]
+	#[from(ignore)]
+	StateUnavailable,
+	/// When passed nonces range is empty.
+	#[display(fmt = "Messages nonces range is empty")]
+	#[from(ignore)]
+	InvalidNoncesRange,
+	/// When fee of some message in the range is unknown to the runtime. This may happen if
+	/// message is not yet sent or is already pruned.
+	#[display(fmt = "Fee of some message in the range is unavailable")]
+	#[from(ignore)]
+	MessagesFeeUnavailable,
+	/// When message is not found in the storage.
+	#[display(fmt = "Message is unknown")]
+	#[from(ignore)]
+	UnknownMessage,
+	/// When message payload can't be decoded.
+	#[display(fmt = "Failed to decode message payload")]
+	#[from(ignore)]
+	PayloadDecodeFailed,
+	/// When proof of even single message doesn't fit into size limit.
+	#[display(fmt = "Proof of the first message doesn't fit into size limit")]
+	#[from(ignore)]
+	MessagesProofTooLarge,
+	/// When node is unable to build `send_message` transaction.
+	#[display(fmt = "Failed to build transaction: {}", _0)]
+	#[from(ignore)]
+	TransactionBuildFailed(String),
+	/// When transaction pool rejects `send_message` transaction.
+	#[display(fmt = "Failed to submit transaction: {}", _0)]
+	#[from(ignore)]
+	TransactionSubmitFailed(String),
+	/// When method is not supported by light client.
+	#[display(fmt = "Method is not supported by light client")]
+	#[from(ignore)]
+	NotSupportedByLightClient,
+	/// Runtime API call error.
+	#[display(fmt = "Runtime API call error: {}", _0)]
+	#[from(ignore)]
+	RuntimeApi(String),
 	/// Client error.
 	#[display(fmt = "Client error: {}", _0)]
 	Client(Box<dyn std::error::Error + Send>),
@@ -34,6 +84,18 @@ impl std::error::Error for Error {
 	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
 		match self {
 			Error::UnknownInstance => None,
+			Error::FeeEstimationFailed => None,
+			Error::UnknownBlock => None,
+			Error::StateUnavailable => None,
+			Error::InvalidNoncesRange => None,
+			Error::MessagesProofTooLarge => None,
+			Error::MessagesFeeUnavailable => None,
+			Error::UnknownMessage => None,
+			Error::PayloadDecodeFailed => None,
+			Error::TransactionBuildFailed(_) => None,
+			Error::TransactionSubmitFailed(_) => None,
+			Error::NotSupportedByLightClient => None,
+			Error::RuntimeApi(_) => None,
 			Error::Client(ref err) => Some(&**err),
 		}
 	}
@@ -42,6 +104,17 @@ impl std::error::Error for Error {
 impl From<Error> for jsonrpc_core::Error {
 	fn from(e: Error) -> Self {
 		const UNKNOW_INSTANCE_CODE: i64 = 1;
+		const FEE_ESTIMATION_FAILED_CODE: i64 = 2;
+		const INVALID_NONCES_RANGE_CODE: i64 = 3;
+		const MESSAGES_PROOF_TOO_LARGE_CODE: i64 = 4;
+		const TRANSACTION_BUILD_FAILED_CODE: i64 = 5;
+		const TRANSACTION_SUBMIT_FAILED_CODE: i64 = 6;
+		const UNKNOWN_BLOCK_CODE: i64 = 7;
+		const STATE_UNAVAILABLE_CODE: i64 = 8;
+		const MESSAGES_FEE_UNAVAILABLE_CODE: i64 = 9;
+		const UNKNOWN_MESSAGE_CODE: i64 = 10;
+		const PAYLOAD_DECODE_FAILED_CODE: i64 = 11;
+		const NOT_SUPPORTED_BY_LIGHT_CLIENT_CODE: i64 = 12;
 
 		match e {
 			Error::UnknownInstance => jsonrpc_core::Error {
@@ -49,6 +122,66 @@ impl From<Error> for jsonrpc_core::Error {
 				message: "Unknown instance passed".into(),
 				data: None,
 			},
+			Error::FeeEstimationFailed => jsonrpc_core::Error {
+				code: jsonrpc_core::ErrorCode::ServerError(FEE_ESTIMATION_FAILED_CODE),
+				message: "Failed to estimate message fee".into(),
+				data: None,
+			},
+			Error::UnknownBlock => jsonrpc_core::Error {
+				code: jsonrpc_core::ErrorCode::ServerError(UNKNOWN_BLOCK_CODE),
+				message: "Unknown block passed".into(),
+				data: None,
+			},
+			Error::StateUnavailable => jsonrpc_core::Error {
+				code: jsonrpc_core::ErrorCode::ServerError(STATE_UNAVAILABLE_CODE),
+				message: "State of the block is pruned".into(),
+				data: None,
+			},
+			Error::InvalidNoncesRange => jsonrpc_core::Error {
+				code: jsonrpc_core::ErrorCode::ServerError(INVALID_NONCES_RANGE_CODE),
+				message: "Empty nonces range passed".into(),
+				data: None,
+			},
+			Error::MessagesProofTooLarge => jsonrpc_core::Error {
+				code: jsonrpc_core::ErrorCode::ServerError(MESSAGES_PROOF_TOO_LARGE_CODE),
+				message: "Messages proof doesn't fit into size limit".into(),
+				data: None,
+			},
+			Error::MessagesFeeUnavailable => jsonrpc_core::Error {
+				code: jsonrpc_core::ErrorCode::ServerError(MESSAGES_FEE_UNAVAILABLE_CODE),
+				message: "Fee of some message is unavailable".into(),
+				data: None,
+			},
+			Error::UnknownMessage => jsonrpc_core::Error {
+				code: jsonrpc_core::ErrorCode::ServerError(UNKNOWN_MESSAGE_CODE),
+				message: "Unknown message".into(),
+				data: None,
+			},
+			Error::PayloadDecodeFailed => jsonrpc_core::Error {
+				code: jsonrpc_core::ErrorCode::ServerError(PAYLOAD_DECODE_FAILED_CODE),
+				message: "Failed to decode message payload".into(),
+				data: None,
+			},
+			Error::TransactionBuildFailed(e) => jsonrpc_core::Error {
+				code: jsonrpc_core::ErrorCode::ServerError(TRANSACTION_BUILD_FAILED_CODE),
+				message: format!("Failed to build transaction: {}", e),
+				data: None,
+			},
+			Error::TransactionSubmitFailed(e) => jsonrpc_core::Error {
+				code: jsonrpc_core::ErrorCode::ServerError(TRANSACTION_SUBMIT_FAILED_CODE),
+				message: format!("Failed to submit transaction: {}", e),
+				data: None,
+			},
+			Error::NotSupportedByLightClient => jsonrpc_core::Error {
+				code: jsonrpc_core::ErrorCode::ServerError(NOT_SUPPORTED_BY_LIGHT_CLIENT_CODE),
+				message: "Method is not supported by light client".into(),
+				data: None,
+			},
+			Error::RuntimeApi(e) => jsonrpc_core::Error {
+				code: jsonrpc_core::ErrorCode::InternalError,
+				message: format!("Runtime API call error: {}", e),
+				data: None,
+			},
 			Error::Client(e) => jsonrpc_core::Error {
 				code: jsonrpc_core::ErrorCode::InternalError,
 				message: format!("Unknown error occured: {}", e),
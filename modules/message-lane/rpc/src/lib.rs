@@ -18,20 +18,39 @@
 
 use crate::error::{Error, FutureResult};
 
-use bp_message_lane::{LaneId, MessageNonce};
+use bp_message_lane::{
+	InboundLaneRelayersApi, LaneId, LaneState, LanesApi, MessageLaneStorageApi, MessageNonce, OutboundLaneApi,
+	OutboundLaneData, OutboundLaneFeeApi, UnrewardedRelayer, Weight,
+};
 use bp_runtime::InstanceId;
-use futures::{FutureExt, TryFutureExt};
-use jsonrpc_core::futures::Future as _;
+use futures::{FutureExt, StreamExt, TryFutureExt, TryStreamExt};
+use jsonrpc_core::futures::{
+	future::Executor as Executor01, future::Future as Future01, sink::Sink as Sink01, stream::Stream as Stream01,
+};
 use jsonrpc_derive::rpc;
-use sc_client_api::Backend as BackendT;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use sc_client_api::{Backend as BackendT, BlockchainEvents};
+use serde::Serialize;
+use sp_api::ProvideRuntimeApi;
 use sp_blockchain::{Error as BlockchainError, HeaderBackend};
 use sp_core::{storage::StorageKey, Bytes};
-use sp_runtime::{codec::Encode, generic::BlockId, traits::Block as BlockT};
-use sp_state_machine::prove_read;
+use sp_runtime::{
+	codec::{Codec, Decode, Encode},
+	generic::BlockId,
+	traits::Block as BlockT,
+};
+use sp_state_machine::{prove_read, Backend as StateBackend};
 use sp_trie::StorageProof;
-use std::sync::Arc;
+use std::{ops::RangeInclusive, sync::Arc};
 
+pub use author::{MessageLaneAuthorApi, MessageLaneAuthorRpcHandler, SendMessageTransactionBuilder, TransactionSigner};
+pub use decoder::{DecodedMessagePayload, MessageLaneDecoderApi, MessageLaneDecoderRpcHandler, MessagePayloadDecoder};
+pub use light::LightMessageLaneRpcHandler;
+
+mod author;
+mod decoder;
 mod error;
+mod light;
 
 /// Trie-based storage proof that the message(s) with given key(s) have been sent by the bridged chain.
 /// SCALE-encoded trie nodes array `Vec<Vec<u8>>`.
@@ -41,19 +60,143 @@ pub type MessagesProof = Bytes;
 /// SCALE-encoded trie nodes array `Vec<Vec<u8>>`.
 pub type MessagesDeliveryProof = Bytes;
 
-/// Runtime adapter.
-pub trait Runtime: Send + Sync + 'static {
-	/// Return runtime storage key for given message. May return None if instance is unknown.
-	fn message_key(&self, instance: &InstanceId, lane: &LaneId, nonce: MessageNonce) -> Option<StorageKey>;
-	/// Return runtime storage key for outbound lane state. May return None if instance is unknown.
-	fn outbound_lane_data_key(&self, instance: &InstanceId, lane: &LaneId) -> Option<StorageKey>;
-	/// Return runtime storage key for inbound lane state. May return None if instance is unknown.
-	fn inbound_lane_data_key(&self, instance: &InstanceId, lane: &LaneId) -> Option<StorageKey>;
+/// Trie-based storage proof of arbitrary storage keys. Every trie node, shared by proofs of several
+/// keys, is included in the proof once.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizedStorageProof {
+	/// SCALE-encoded trie nodes array `Vec<Vec<u8>>`.
+	pub proof: Bytes,
+	/// Size of the SCALE-encoded proof in bytes.
+	pub size: u32,
+}
+
+/// Proof of the longest prefix of requested messages range, that fits into given size limit.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizedMessagesProof {
+	/// Nonce of the last message, included in the proof. The proof includes all messages in
+	/// the range [begin; end].
+	pub end: MessageNonce,
+	/// Messages proof.
+	pub proof: MessagesProof,
+	/// Size of the SCALE-encoded proof in bytes.
+	pub size: u32,
+	/// Cumulative dispatch weight of all messages, included in the proof.
+	pub dispatch_weight: Weight,
+}
+
+/// Notification about range of messages that have been sent over the outbound lane, or
+/// range of messages which delivery has been confirmed by the bridged chain.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessagesNotification<BlockHash> {
+	/// Hash of the block where lane state has been changed.
+	pub block: BlockHash,
+	/// Nonce of the first message in the range.
+	pub begin: MessageNonce,
+	/// Nonce of the last message in the range.
+	pub end: MessageNonce,
+}
+
+/// Notification about inbound lane nonces change.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InboundLaneNotification<BlockHash> {
+	/// Hash of the block where lane state has been changed.
+	pub block: BlockHash,
+	/// Nonce of latest message, received by the inbound lane.
+	pub latest_received_nonce: MessageNonce,
+	/// Nonce of latest message, which delivery has been confirmed to the bridged chain.
+	pub latest_confirmed_nonce: MessageNonce,
+}
+
+/// Message lane details.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaneDetails {
+	/// Lane identifier.
+	pub lane: LaneId,
+	/// Nonce of oldest message that is still stored in the outbound lane.
+	pub outbound_oldest_unpruned_nonce: MessageNonce,
+	/// Nonce of latest message, received by the bridged chain.
+	pub outbound_latest_received_nonce: MessageNonce,
+	/// Nonce of latest message, generated by the outbound lane.
+	pub outbound_latest_generated_nonce: MessageNonce,
+	/// Number of messages that are queued at the outbound lane and not yet received by the bridged chain.
+	pub outbound_queued_messages: MessageNonce,
+	/// Nonce of latest message, received by the inbound lane.
+	pub inbound_latest_received_nonce: MessageNonce,
+	/// Nonce of latest message, which delivery has been confirmed to the bridged chain.
+	pub inbound_latest_confirmed_nonce: MessageNonce,
+	/// Number of messages that are received by the inbound lane, but not yet confirmed.
+	pub inbound_unconfirmed_messages: MessageNonce,
+	/// True if lane operations are halted.
+	pub is_halted: bool,
+}
+
+impl From<LaneState> for LaneDetails {
+	fn from(state: LaneState) -> Self {
+		LaneDetails {
+			lane: state.lane,
+			outbound_oldest_unpruned_nonce: state.outbound.oldest_unpruned_nonce,
+			outbound_latest_received_nonce: state.outbound.latest_received_nonce,
+			outbound_latest_generated_nonce: state.outbound.latest_generated_nonce,
+			outbound_queued_messages: state.outbound_queued_messages(),
+			inbound_latest_received_nonce: state.inbound_latest_received_nonce,
+			inbound_latest_confirmed_nonce: state.inbound_latest_confirmed_nonce,
+			inbound_unconfirmed_messages: state.inbound_unconfirmed_messages(),
+			is_halted: state.is_halted,
+		}
+	}
+}
+
+/// Relayer that has delivered messages over the inbound lane, but hasn't been rewarded yet.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnrewardedRelayerDetails<InboundRelayer> {
+	/// Identifier of the relayer account at the bridged chain.
+	pub relayer: InboundRelayer,
+	/// Nonce of the first message, delivered by the relayer.
+	pub begin: MessageNonce,
+	/// Nonce of the last message, delivered by the relayer.
+	pub end: MessageNonce,
+	/// Number of messages, delivered by the relayer.
+	pub messages: MessageNonce,
+}
+
+/// Unrewarded relayers of the inbound lane.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnrewardedRelayers<InboundRelayer> {
+	/// Unrewarded relayer entries, ordered by message nonces.
+	pub relayers: Vec<UnrewardedRelayerDetails<InboundRelayer>>,
+	/// Maximal number of unrewarded relayer entries. When this limit is reached, the lane
+	/// rejects new messages until delivery is confirmed.
+	pub max_entries: MessageNonce,
+}
+
+impl<InboundRelayer> From<UnrewardedRelayer<InboundRelayer>> for UnrewardedRelayerDetails<InboundRelayer> {
+	fn from(entry: UnrewardedRelayer<InboundRelayer>) -> Self {
+		UnrewardedRelayerDetails {
+			relayer: entry.relayer,
+			begin: entry.begin,
+			end: entry.end,
+			messages: entry.end.saturating_sub(entry.begin) + 1,
+		}
+	}
 }
 
 /// Provides RPC methods for interacting with message-lane pallet.
+///
+/// Methods that accept optional `block` argument are answered using state of given block, or
+/// state of the best block if it is not specified. State of historical blocks is only available
+/// until it is pruned by the node.
 #[rpc]
-pub trait MessageLaneApi<BlockHash> {
+pub trait MessageLaneApi<BlockHash, OutboundMessageFee, InboundRelayer> {
+	/// RPC metadata.
+	type Metadata;
+
 	/// Returns storage proof of messages in given inclusive range. The state of outbound
 	/// lane is included in the proof if `include_outbound_lane_state` is true.
 	#[rpc(name = "messageLane_proveMessages")]
@@ -67,6 +210,22 @@ pub trait MessageLaneApi<BlockHash> {
 		block: Option<BlockHash>,
 	) -> FutureResult<MessagesProof>;
 
+	/// Returns storage proof of the longest prefix of messages in given inclusive range, that
+	/// fits into `max_proof_size` bytes. The state of outbound lane is included in the proof if
+	/// `include_outbound_lane_state` is true.
+	#[rpc(name = "messageLane_proveMessagesWithSizeLimit")]
+	#[allow(clippy::too_many_arguments)]
+	fn prove_messages_with_size_limit(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		begin: MessageNonce,
+		end: MessageNonce,
+		include_outbound_lane_state: bool,
+		max_proof_size: u32,
+		block: Option<BlockHash>,
+	) -> FutureResult<SizedMessagesProof>;
+
 	/// Returns proof-of-message(s) delivery.
 	#[rpc(name = "messageLane_proveMessagesDelivery")]
 	fn prove_messages_delivery(
@@ -75,32 +234,170 @@ pub trait MessageLaneApi<BlockHash> {
 		lane: LaneId,
 		block: Option<BlockHash>,
 	) -> FutureResult<MessagesDeliveryProof>;
+
+	/// Returns minimal delivery and dispatch fee that must be paid by the submitter of the message
+	/// with given SCALE-encoded payload. The fee is computed using conversion rate and message
+	/// weight that are known to the runtime at given block.
+	#[rpc(name = "messageLane_estimateFee")]
+	fn estimate_fee(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		payload: Bytes,
+		block: Option<BlockHash>,
+	) -> FutureResult<OutboundMessageFee>;
+
+	/// Returns storage proof of given storage keys at given block. Keys that are missing from
+	/// the storage are proved to be missing.
+	#[rpc(name = "messageLane_proveStorage")]
+	fn prove_storage(&self, keys: Vec<StorageKey>, block: Option<BlockHash>) -> FutureResult<SizedStorageProof>;
+
+	/// Returns cumulative delivery and dispatch fee, paid by submitters of outbound messages in
+	/// given inclusive range. Once delivery of these messages is confirmed, this fee is paid to
+	/// the relayers that have delivered them.
+	#[rpc(name = "messageLane_messagesFee")]
+	fn messages_fee(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		begin: MessageNonce,
+		end: MessageNonce,
+		block: Option<BlockHash>,
+	) -> FutureResult<OutboundMessageFee>;
+
+	/// Returns relayers that have delivered messages over given inbound lane, but haven't been
+	/// rewarded yet. Reward of every entry is the `messageLane_messagesFee` of its nonces range,
+	/// computed by the bridged chain node.
+	#[rpc(name = "messageLane_unrewardedRelayers")]
+	fn unrewarded_relayers(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		block: Option<BlockHash>,
+	) -> FutureResult<UnrewardedRelayers<InboundRelayer>>;
+
+	/// Returns details of all lanes that are known to the message lane pallet instance.
+	#[rpc(name = "messageLane_lanes")]
+	fn lanes(&self, instance: InstanceId, block: Option<BlockHash>) -> FutureResult<Vec<LaneDetails>>;
+
+	/// Subscribe to messages that are sent over given outbound lane.
+	#[pubsub(
+		subscription = "messageLane_outboundMessages",
+		subscribe,
+		name = "messageLane_subscribeOutboundMessages"
+	)]
+	fn subscribe_outbound_messages(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<MessagesNotification<BlockHash>>,
+		instance: InstanceId,
+		lane: LaneId,
+	);
+
+	/// Unsubscribe from outbound lane messages.
+	#[pubsub(
+		subscription = "messageLane_outboundMessages",
+		unsubscribe,
+		name = "messageLane_unsubscribeOutboundMessages"
+	)]
+	fn unsubscribe_outbound_messages(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool>;
+
+	/// Subscribe to confirmations of messages delivery, received by given outbound lane.
+	#[pubsub(
+		subscription = "messageLane_deliveryConfirmations",
+		subscribe,
+		name = "messageLane_subscribeDeliveryConfirmations"
+	)]
+	fn subscribe_delivery_confirmations(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<MessagesNotification<BlockHash>>,
+		instance: InstanceId,
+		lane: LaneId,
+	);
+
+	/// Unsubscribe from messages delivery confirmations.
+	#[pubsub(
+		subscription = "messageLane_deliveryConfirmations",
+		unsubscribe,
+		name = "messageLane_unsubscribeDeliveryConfirmations"
+	)]
+	fn unsubscribe_delivery_confirmations(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool>;
+
+	/// Subscribe to nonces changes of given inbound lane.
+	#[pubsub(
+		subscription = "messageLane_inboundLaneNonces",
+		subscribe,
+		name = "messageLane_subscribeInboundLaneNonces"
+	)]
+	fn subscribe_inbound_lane_nonces(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<InboundLaneNotification<BlockHash>>,
+		instance: InstanceId,
+		lane: LaneId,
+	);
+
+	/// Unsubscribe from inbound lane nonces changes.
+	#[pubsub(
+		subscription = "messageLane_inboundLaneNonces",
+		unsubscribe,
+		name = "messageLane_unsubscribeInboundLaneNonces"
+	)]
+	fn unsubscribe_inbound_lane_nonces(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool>;
 }
 
 /// Implements the MessageLaneApi trait for interacting with message lanes.
-pub struct MessageLaneRpcHandler<Block, Backend, R> {
+pub struct MessageLaneRpcHandler<Block, Backend, Client> {
 	backend: Arc<Backend>,
-	runtime: Arc<R>,
+	client: Arc<Client>,
+	manager: SubscriptionManager,
 	_phantom: std::marker::PhantomData<Block>,
 }
 
-impl<Block, Backend, R> MessageLaneRpcHandler<Block, Backend, R> {
+impl<Block, Backend, Client> MessageLaneRpcHandler<Block, Backend, Client> {
 	/// Creates new mesage lane RPC handler.
-	pub fn new(backend: Arc<Backend>, runtime: Arc<R>) -> Self {
+	pub fn new<E>(backend: Arc<Backend>, client: Arc<Client>, executor: E) -> Self
+	where
+		E: Executor01<Box<dyn Future01<Item = (), Error = ()> + Send>> + Send + Sync + 'static,
+	{
 		Self {
 			backend,
-			runtime,
+			client,
+			manager: SubscriptionManager::new(Arc::new(executor)),
 			_phantom: Default::default(),
 		}
 	}
 }
 
-impl<Block, Backend, R> MessageLaneApi<Block::Hash> for MessageLaneRpcHandler<Block, Backend, R>
+impl<Block, Backend, Client, OutboundMessageFee, InboundRelayer>
+	MessageLaneApi<Block::Hash, OutboundMessageFee, InboundRelayer> for MessageLaneRpcHandler<Block, Backend, Client>
 where
 	Block: BlockT,
 	Backend: BackendT<Block> + 'static,
-	R: Runtime,
+	Client: BlockchainEvents<Block> + ProvideRuntimeApi<Block> + Send + Sync + 'static,
+	Client::Api: MessageLaneStorageApi<Block>
+		+ OutboundLaneApi<Block>
+		+ OutboundLaneFeeApi<Block, OutboundMessageFee>
+		+ InboundLaneRelayersApi<Block, InboundRelayer>
+		+ LanesApi<Block>,
+	OutboundMessageFee: Serialize + Send + Sync + 'static,
+	InboundRelayer: Codec + Serialize + Send + Sync + 'static,
 {
+	type Metadata = sc_rpc_api::Metadata;
+
 	fn prove_messages(
 		&self,
 		instance: InstanceId,
@@ -110,25 +407,47 @@ where
 		include_outbound_lane_state: bool,
 		block: Option<Block::Hash>,
 	) -> FutureResult<MessagesProof> {
-		let runtime = self.runtime.clone();
-		let outbound_lane_data_key = if include_outbound_lane_state {
-			Some(runtime.outbound_lane_data_key(&instance, &lane))
-		} else {
-			None
-		};
+		let backend = self.backend.clone();
+		let keys = at_block(&*self.backend, block).and_then(|block| {
+			let mut keys = self.messages_keys(block, instance, lane, begin, end)?;
+			if include_outbound_lane_state {
+				keys.push(self.outbound_lane_data_key(block, instance, lane)?);
+			}
+			keys.push(self.storage_version_key(block, instance)?);
+			Ok((block, keys))
+		});
 		Box::new(
-			prove_keys_read(
-				self.backend.clone(),
+			futures::future::ready(keys)
+				.and_then(move |(block, keys)| prove_keys_read(backend, block, keys))
+				.boxed()
+				.compat()
+				.map(serialize_storage_proof)
+				.map_err(Into::into),
+		)
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	fn prove_messages_with_size_limit(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		begin: MessageNonce,
+		end: MessageNonce,
+		include_outbound_lane_state: bool,
+		max_proof_size: u32,
+		block: Option<Block::Hash>,
+	) -> FutureResult<SizedMessagesProof> {
+		Box::new(jsonrpc_core::futures::future::result(
+			self.prove_messages_with_size_limit_at(
+				instance,
+				lane,
+				begin..=end,
+				include_outbound_lane_state,
+				max_proof_size,
 				block,
-				(begin..=end)
-					.map(move |nonce| runtime.message_key(&instance, &lane, nonce))
-					.chain(outbound_lane_data_key.into_iter()),
 			)
-			.boxed()
-			.compat()
-			.map(serialize_storage_proof)
 			.map_err(Into::into),
-		)
+		))
 	}
 
 	fn prove_messages_delivery(
@@ -137,36 +456,548 @@ where
 		lane: LaneId,
 		block: Option<Block::Hash>,
 	) -> FutureResult<MessagesDeliveryProof> {
-		Box::new(
-			prove_keys_read(
-				self.backend.clone(),
+		let backend = self.backend.clone();
+		let keys = at_block(&*self.backend, block).and_then(|block| {
+			Ok((
 				block,
-				vec![self.runtime.inbound_lane_data_key(&instance, &lane)],
-			)
-			.boxed()
-			.compat()
-			.map(serialize_storage_proof)
-			.map_err(Into::into),
+				vec![
+					self.inbound_lane_data_key(block, instance, lane)?,
+					self.storage_version_key(block, instance)?,
+				],
+			))
+		});
+		Box::new(
+			futures::future::ready(keys)
+				.and_then(move |(block, keys)| prove_keys_read(backend, block, keys))
+				.boxed()
+				.compat()
+				.map(serialize_storage_proof)
+				.map_err(Into::into),
+		)
+	}
+
+	fn estimate_fee(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		payload: Bytes,
+		block: Option<Block::Hash>,
+	) -> FutureResult<OutboundMessageFee> {
+		Box::new(jsonrpc_core::futures::future::result(
+			self.estimate_fee_at(instance, lane, payload, block).map_err(Into::into),
+		))
+	}
+
+	fn prove_storage(&self, keys: Vec<StorageKey>, block: Option<Block::Hash>) -> FutureResult<SizedStorageProof> {
+		let backend = self.backend.clone();
+		let block = at_block(&*self.backend, block);
+		Box::new(
+			futures::future::ready(block)
+				.and_then(move |block| prove_keys_read(backend, block, keys))
+				.boxed()
+				.compat()
+				.map(|proof| {
+					let proof = serialize_storage_proof(proof);
+					SizedStorageProof {
+						size: proof.0.len() as u32,
+						proof,
+					}
+				})
+				.map_err(Into::into),
+		)
+	}
+
+	fn messages_fee(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		begin: MessageNonce,
+		end: MessageNonce,
+		block: Option<Block::Hash>,
+	) -> FutureResult<OutboundMessageFee> {
+		Box::new(jsonrpc_core::futures::future::result(
+			self.messages_fee_at(instance, lane, begin..=end, block)
+				.map_err(Into::into),
+		))
+	}
+
+	fn unrewarded_relayers(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		block: Option<Block::Hash>,
+	) -> FutureResult<UnrewardedRelayers<InboundRelayer>> {
+		Box::new(jsonrpc_core::futures::future::result(
+			self.unrewarded_relayers_at(instance, lane, block).map_err(Into::into),
+		))
+	}
+
+	fn lanes(&self, instance: InstanceId, block: Option<Block::Hash>) -> FutureResult<Vec<LaneDetails>> {
+		Box::new(jsonrpc_core::futures::future::result(
+			self.lanes_at(instance, block)
+				.map(|lanes| lanes.into_iter().map(Into::into).collect())
+				.map_err(Into::into),
+		))
+	}
+
+	fn subscribe_outbound_messages(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<MessagesNotification<Block::Hash>>,
+		instance: InstanceId,
+		lane: LaneId,
+	) {
+		self.subscribe_storage_value(
+			subscriber,
+			|block| self.outbound_lane_data_key(block, instance, lane),
+			|_, encoded_lane_data| decode_outbound_lane_data(encoded_lane_data),
+			|block, prev: &OutboundLaneData, new: &OutboundLaneData| {
+				messages_notification(block, prev.latest_generated_nonce, new.latest_generated_nonce)
+			},
+		)
+	}
+
+	fn unsubscribe_outbound_messages(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool> {
+		Ok(self.manager.cancel(id))
+	}
+
+	fn subscribe_delivery_confirmations(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<MessagesNotification<Block::Hash>>,
+		instance: InstanceId,
+		lane: LaneId,
+	) {
+		self.subscribe_storage_value(
+			subscriber,
+			|block| self.outbound_lane_data_key(block, instance, lane),
+			|_, encoded_lane_data| decode_outbound_lane_data(encoded_lane_data),
+			|block, prev: &OutboundLaneData, new: &OutboundLaneData| {
+				messages_notification(block, prev.latest_received_nonce, new.latest_received_nonce)
+			},
 		)
 	}
+
+	fn unsubscribe_delivery_confirmations(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool> {
+		Ok(self.manager.cancel(id))
+	}
+
+	fn subscribe_inbound_lane_nonces(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<InboundLaneNotification<Block::Hash>>,
+		instance: InstanceId,
+		lane: LaneId,
+	) {
+		let client = self.client.clone();
+		self.subscribe_storage_value(
+			subscriber,
+			|block| self.inbound_lane_data_key(block, instance, lane),
+			move |block, encoded_lane_data| {
+				client
+					.runtime_api()
+					.inbound_lane_nonces(&BlockId::Hash(block), instance, encoded_lane_data.to_vec())
+					.ok()
+					.flatten()
+			},
+			|block, prev: &(MessageNonce, MessageNonce), new: &(MessageNonce, MessageNonce)| {
+				if prev == new {
+					return None;
+				}
+
+				Some(InboundLaneNotification {
+					block,
+					latest_received_nonce: new.0,
+					latest_confirmed_nonce: new.1,
+				})
+			},
+		)
+	}
+
+	fn unsubscribe_inbound_lane_nonces(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool> {
+		Ok(self.manager.cancel(id))
+	}
+}
+
+impl<Block, Backend, Client> MessageLaneRpcHandler<Block, Backend, Client>
+where
+	Block: BlockT,
+	Backend: BackendT<Block> + 'static,
+	Client: BlockchainEvents<Block> + ProvideRuntimeApi<Block> + Send + Sync + 'static,
+	Client::Api: MessageLaneStorageApi<Block>,
+{
+	/// Return runtime storage keys of messages in given inclusive range, using runtime API at given block.
+	fn messages_keys(
+		&self,
+		block: Block::Hash,
+		instance: InstanceId,
+		lane: LaneId,
+		begin: MessageNonce,
+		end: MessageNonce,
+	) -> Result<Vec<StorageKey>, Error> {
+		self.client
+			.runtime_api()
+			.messages_keys(&BlockId::Hash(block), instance, lane, begin, end)
+			.map_err(runtime_api_err)?
+			.map(|keys| keys.into_iter().map(StorageKey).collect())
+			.ok_or(Error::UnknownInstance)
+	}
+
+	/// Return runtime storage key of outbound lane state, using runtime API at given block.
+	fn outbound_lane_data_key(
+		&self,
+		block: Block::Hash,
+		instance: InstanceId,
+		lane: LaneId,
+	) -> Result<StorageKey, Error> {
+		self.client
+			.runtime_api()
+			.outbound_lane_data_key(&BlockId::Hash(block), instance, lane)
+			.map_err(runtime_api_err)?
+			.map(StorageKey)
+			.ok_or(Error::UnknownInstance)
+	}
+
+	/// Return runtime storage key of inbound lane state, using runtime API at given block.
+	fn inbound_lane_data_key(
+		&self,
+		block: Block::Hash,
+		instance: InstanceId,
+		lane: LaneId,
+	) -> Result<StorageKey, Error> {
+		self.client
+			.runtime_api()
+			.inbound_lane_data_key(&BlockId::Hash(block), instance, lane)
+			.map_err(runtime_api_err)?
+			.map(StorageKey)
+			.ok_or(Error::UnknownInstance)
+	}
+
+	/// Return runtime storage key of the pallet storage version, using runtime API at given block.
+	///
+	/// The key is included in every proof, even if the pallet is still using the legacy storage
+	/// (without the version), so that the verifier may check that the version is missing.
+	fn storage_version_key(&self, block: Block::Hash, instance: InstanceId) -> Result<StorageKey, Error> {
+		self.client
+			.runtime_api()
+			.storage_version_key(&BlockId::Hash(block), instance)
+			.map_err(runtime_api_err)?
+			.map(StorageKey)
+			.ok_or(Error::UnknownInstance)
+	}
+
+	/// Prove longest prefix of given messages range that fits into `max_proof_size` bytes.
+	fn prove_messages_with_size_limit_at(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		nonces: RangeInclusive<MessageNonce>,
+		include_outbound_lane_state: bool,
+		max_proof_size: u32,
+		block: Option<Block::Hash>,
+	) -> Result<SizedMessagesProof, Error>
+	where
+		Client: ProvideRuntimeApi<Block>,
+		Client::Api: OutboundLaneApi<Block>,
+	{
+		let (begin, end) = nonces.into_inner();
+		if begin > end {
+			return Err(Error::InvalidNoncesRange);
+		}
+
+		let block = at_block(&*self.backend, block)?;
+		let message_keys = self.messages_keys(block, instance, lane, begin, end)?;
+		let outbound_lane_data_key = if include_outbound_lane_state {
+			Some(self.outbound_lane_data_key(block, instance, lane)?)
+		} else {
+			None
+		};
+		let storage_version_key = self.storage_version_key(block, instance)?;
+		let prove_messages_prefix = |prefix_end: MessageNonce| -> Result<Bytes, Error> {
+			let keys = message_keys[..=(prefix_end - begin) as usize]
+				.iter()
+				.chain(outbound_lane_data_key.iter())
+				.chain(std::iter::once(&storage_version_key))
+				.cloned()
+				.collect::<Vec<_>>();
+			prove_keys_read_at(&*self.backend, block, keys).map(serialize_storage_proof)
+		};
+
+		// including more messages in the proof never makes it smaller, so we may use binary search
+		// to find the longest prefix that fits the limit
+		let mut best_proof = None;
+		let (mut low, mut high) = (begin, end);
+		while low <= high {
+			let mid = low + (high - low) / 2;
+			let proof = prove_messages_prefix(mid)?;
+			if proof.0.len() <= max_proof_size as usize {
+				best_proof = Some((mid, proof));
+				low = mid + 1;
+			} else if mid == begin {
+				break;
+			} else {
+				high = mid - 1;
+			}
+		}
+
+		let (end, proof) = best_proof.ok_or(Error::MessagesProofTooLarge)?;
+		let dispatch_weight = self
+			.client
+			.runtime_api()
+			.messages_dispatch_weight(&BlockId::Hash(block), instance, lane, begin, end)
+			.map_err(runtime_api_err)?
+			.ok_or(Error::UnknownInstance)?
+			.into_iter()
+			.fold(0, |total: Weight, (_, weight)| total.saturating_add(weight));
+
+		Ok(SizedMessagesProof {
+			end,
+			size: proof.0.len() as u32,
+			proof,
+			dispatch_weight,
+		})
+	}
+
+	/// Estimate fee of the message with given encoded payload, using runtime API at given block.
+	fn estimate_fee_at<OutboundMessageFee>(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		payload: Bytes,
+		block: Option<Block::Hash>,
+	) -> Result<OutboundMessageFee, Error>
+	where
+		Client: ProvideRuntimeApi<Block>,
+		Client::Api: OutboundLaneFeeApi<Block, OutboundMessageFee>,
+	{
+		let block = at_block(&*self.backend, block)?;
+
+		// fee API returns `None` for unknown instances too, so we're using storage API to report
+		// unknown instances properly
+		self.outbound_lane_data_key(block, instance, lane)?;
+
+		self.client
+			.runtime_api()
+			.estimate_message_delivery_and_dispatch_fee(&BlockId::Hash(block), instance, lane, payload.0)
+			.map_err(runtime_api_err)?
+			.ok_or(Error::FeeEstimationFailed)
+	}
+
+	/// Compute cumulative fee of messages in given inclusive range, using runtime API at given block.
+	fn messages_fee_at<OutboundMessageFee>(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		nonces: RangeInclusive<MessageNonce>,
+		block: Option<Block::Hash>,
+	) -> Result<OutboundMessageFee, Error>
+	where
+		Client: ProvideRuntimeApi<Block>,
+		Client::Api: OutboundLaneFeeApi<Block, OutboundMessageFee>,
+	{
+		let (begin, end) = nonces.into_inner();
+		if begin > end {
+			return Err(Error::InvalidNoncesRange);
+		}
+
+		let block = at_block(&*self.backend, block)?;
+
+		// fee API returns `None` for unknown instances too, so we're using storage API to report
+		// unknown instances properly
+		self.outbound_lane_data_key(block, instance, lane)?;
+
+		self.client
+			.runtime_api()
+			.messages_fee(&BlockId::Hash(block), instance, lane, begin, end)
+			.map_err(runtime_api_err)?
+			.ok_or(Error::MessagesFeeUnavailable)
+	}
+
+	/// Read unrewarded relayers of given inbound lane, using runtime API at given block.
+	fn unrewarded_relayers_at<InboundRelayer>(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		block: Option<Block::Hash>,
+	) -> Result<UnrewardedRelayers<InboundRelayer>, Error>
+	where
+		Client: ProvideRuntimeApi<Block>,
+		Client::Api: InboundLaneRelayersApi<Block, InboundRelayer>,
+		InboundRelayer: Codec,
+	{
+		let block = at_block(&*self.backend, block)?;
+		let runtime_api = self.client.runtime_api();
+		let relayers = runtime_api
+			.unrewarded_relayers(&BlockId::Hash(block), instance, lane)
+			.map_err(runtime_api_err)?
+			.ok_or(Error::UnknownInstance)?;
+		let max_entries = runtime_api
+			.max_unrewarded_relayer_entries(&BlockId::Hash(block), instance)
+			.map_err(runtime_api_err)?
+			.ok_or(Error::UnknownInstance)?;
+		Ok(UnrewardedRelayers {
+			relayers: relayers.into_iter().map(Into::into).collect(),
+			max_entries,
+		})
+	}
+
+	/// Read state of all lanes, using runtime API at given block.
+	fn lanes_at(&self, instance: InstanceId, block: Option<Block::Hash>) -> Result<Vec<LaneState>, Error>
+	where
+		Client: ProvideRuntimeApi<Block>,
+		Client::Api: LanesApi<Block>,
+	{
+		let block = at_block(&*self.backend, block)?;
+		self.client
+			.runtime_api()
+			.lanes(&BlockId::Hash(block), instance)
+			.map_err(runtime_api_err)?
+			.ok_or(Error::UnknownInstance)
+	}
+
+	/// Subscribe to changes of runtime storage value with the key, computed by `key` function at
+	/// the best block.
+	///
+	/// The value is decoded using `decode` function, which is given hash of the block where value
+	/// has been read. Missing (or undecodable) value is replaced with default value. Every time
+	/// the value is changed, the `notification` function is called with previous and new value.
+	/// If it returns `Some(_)`, the notification is sent to the subscriber.
+	fn subscribe_storage_value<T, V>(
+		&self,
+		subscriber: Subscriber<T>,
+		key: impl FnOnce(Block::Hash) -> Result<StorageKey, Error>,
+		decode: impl Fn(Block::Hash, &[u8]) -> Option<V> + Send + 'static,
+		notification: impl Fn(Block::Hash, &V, &V) -> Option<T> + Send + 'static,
+	) where
+		T: Serialize + Send + 'static,
+		V: Default + Send + 'static,
+	{
+		let best_block = unwrap_or_best(&*self.backend, None);
+		let key = match key(best_block) {
+			Ok(key) => key,
+			Err(error) => {
+				let _ = subscriber.reject(error.into());
+				return;
+			}
+		};
+
+		let initial_value = match read_storage_value(&*self.backend, best_block, &key) {
+			Ok(initial_value) => initial_value,
+			Err(error) => {
+				let _ = subscriber.reject(error.into());
+				return;
+			}
+		};
+		let changes = match self
+			.client
+			.storage_changes_notification_stream(Some(&[key.clone()]), None)
+		{
+			Ok(changes) => changes,
+			Err(error) => {
+				let _ = subscriber.reject(blockchain_err(error).into());
+				return;
+			}
+		};
+
+		let mut prev_value = initial_value
+			.and_then(|value| decode(best_block, &value))
+			.unwrap_or_default();
+		let stream = changes
+			.filter_map(move |(block, changes)| {
+				let new_value = changes
+					.iter()
+					.find(|(child_key, changed_key, _)| child_key.is_none() && **changed_key == key)
+					.and_then(|(_, _, value)| value.and_then(|value| decode(block, &value.0)))
+					.unwrap_or_default();
+				let result = notification(block, &prev_value, &new_value);
+				prev_value = new_value;
+				futures::future::ready(result)
+			})
+			.map(Ok::<_, ()>)
+			.compat();
+
+		self.manager.add(subscriber, |sink| {
+			sink.sink_map_err(|error| log::warn!(target: "bridge", "Error sending notifications: {:?}", error))
+				.send_all(stream.map(Ok))
+				.map(|_| ())
+		});
+	}
+}
+
+/// Decode outbound lane state.
+fn decode_outbound_lane_data(encoded_lane_data: &[u8]) -> Option<OutboundLaneData> {
+	OutboundLaneData::decode(&mut &encoded_lane_data[..]).ok()
+}
+
+/// Return notification about messages in range (prev_nonce; new_nonce], if it isn't empty.
+fn messages_notification<BlockHash>(
+	block: BlockHash,
+	prev_nonce: MessageNonce,
+	new_nonce: MessageNonce,
+) -> Option<MessagesNotification<BlockHash>> {
+	if new_nonce <= prev_nonce {
+		return None;
+	}
+
+	Some(MessagesNotification {
+		block,
+		begin: prev_nonce + 1,
+		end: new_nonce,
+	})
+}
+
+/// Read value of given storage key at given block.
+fn read_storage_value<Block, Backend>(
+	backend: &Backend,
+	block: Block::Hash,
+	key: &StorageKey,
+) -> Result<Option<Vec<u8>>, Error>
+where
+	Block: BlockT,
+	Backend: BackendT<Block>,
+{
+	let state = backend.state_at(BlockId::Hash(block)).map_err(blockchain_err)?;
+	state
+		.storage(&key.0)
+		.map_err(|error| blockchain_err(BlockchainError::Backend(format!("{}", error))))
 }
 
 async fn prove_keys_read<Block, Backend>(
 	backend: Arc<Backend>,
-	block: Option<Block::Hash>,
-	keys: impl IntoIterator<Item = Option<StorageKey>>,
+	block: Block::Hash,
+	keys: Vec<StorageKey>,
 ) -> Result<StorageProof, Error>
 where
 	Block: BlockT,
 	Backend: BackendT<Block> + 'static,
 {
-	let block = unwrap_or_best(&*backend, block);
+	prove_keys_read_at(&*backend, block, keys)
+}
+
+/// Prove read of given storage keys at given block.
+fn prove_keys_read_at<Block, Backend>(
+	backend: &Backend,
+	block: Block::Hash,
+	keys: Vec<StorageKey>,
+) -> Result<StorageProof, Error>
+where
+	Block: BlockT,
+	Backend: BackendT<Block>,
+{
 	let state = backend.state_at(BlockId::Hash(block)).map_err(blockchain_err)?;
-	let keys = keys
-		.into_iter()
-		.map(|key| key.ok_or(Error::UnknownInstance).map(|key| key.0))
-		.collect::<Result<Vec<_>, _>>()?;
-	let storage_proof = prove_read(state, keys)
+	let storage_proof = prove_read(state, keys.into_iter().map(|key| key.0))
 		.map_err(BlockchainError::Execution)
 		.map_err(blockchain_err)?;
 	Ok(storage_proof)
@@ -184,6 +1015,26 @@ fn unwrap_or_best<Block: BlockT>(backend: &impl BackendT<Block>, block: Option<B
 	}
 }
 
+/// Return given block (or best block, if it isn't specified), making sure that the block is known
+/// to the node and that its state is not yet pruned.
+fn at_block<Block: BlockT>(backend: &impl BackendT<Block>, block: Option<Block::Hash>) -> Result<Block::Hash, Error> {
+	let block = unwrap_or_best(backend, block);
+	let number = backend
+		.blockchain()
+		.number(block)
+		.map_err(blockchain_err)?
+		.ok_or(Error::UnknownBlock)?;
+	if !backend.have_state_at(&block, number) {
+		return Err(Error::StateUnavailable);
+	}
+
+	Ok(block)
+}
+
 fn blockchain_err(err: BlockchainError) -> Error {
 	Error::Client(Box::new(err))
 }
+
+fn runtime_api_err(err: impl std::fmt::Debug) -> Error {
+	Error::RuntimeApi(format!("{:?}", err))
+}
@@ -0,0 +1,351 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Message-lane RPC methods for light clients.
+//!
+//! Light client has no state, so all runtime API calls are executed by remote full nodes
+//! (using on-demand requests). Storage proofs and subscriptions are not supported.
+
+use crate::error::{Error, FutureResult};
+use crate::{
+	blockchain_err, InboundLaneNotification, LaneDetails, MessageLaneApi, MessagesDeliveryProof, MessagesNotification,
+	MessagesProof, SizedMessagesProof, SizedStorageProof, UnrewardedRelayers,
+};
+
+use bp_message_lane::{LaneId, LaneState, MessageNonce, UnrewardedRelayer};
+use bp_runtime::InstanceId;
+use futures::{FutureExt, TryFutureExt};
+use jsonrpc_core::futures::future::Future as Future01;
+use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId};
+use sc_client_api::light::{Fetcher, RemoteCallRequest};
+use serde::Serialize;
+use sp_blockchain::HeaderBackend;
+use sp_core::{storage::StorageKey, Bytes};
+use sp_runtime::{
+	codec::{Decode, Encode},
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT},
+};
+use std::{marker::PhantomData, sync::Arc};
+
+/// Implements the MessageLaneApi trait for light clients.
+pub struct LightMessageLaneRpcHandler<Block, Client, F> {
+	remote: RemoteRuntime<Block, Client, F>,
+}
+
+impl<Block, Client, F> LightMessageLaneRpcHandler<Block, Client, F> {
+	/// Creates new light client message lane RPC handler.
+	pub fn new(client: Arc<Client>, fetcher: Arc<F>) -> Self {
+		LightMessageLaneRpcHandler {
+			remote: RemoteRuntime {
+				client,
+				fetcher,
+				_phantom: Default::default(),
+			},
+		}
+	}
+}
+
+impl<Block, Client, F, OutboundMessageFee, InboundRelayer>
+	MessageLaneApi<Block::Hash, OutboundMessageFee, InboundRelayer> for LightMessageLaneRpcHandler<Block, Client, F>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block> + Send + Sync + 'static,
+	F: Fetcher<Block> + 'static,
+	OutboundMessageFee: Decode + Serialize + Send + Sync + 'static,
+	InboundRelayer: Decode + Serialize + Send + Sync + 'static,
+{
+	type Metadata = sc_rpc_api::Metadata;
+
+	fn prove_messages(
+		&self,
+		_instance: InstanceId,
+		_lane: LaneId,
+		_begin: MessageNonce,
+		_end: MessageNonce,
+		_include_outbound_lane_state: bool,
+		_block: Option<Block::Hash>,
+	) -> FutureResult<MessagesProof> {
+		not_supported()
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	fn prove_messages_with_size_limit(
+		&self,
+		_instance: InstanceId,
+		_lane: LaneId,
+		_begin: MessageNonce,
+		_end: MessageNonce,
+		_include_outbound_lane_state: bool,
+		_max_proof_size: u32,
+		_block: Option<Block::Hash>,
+	) -> FutureResult<SizedMessagesProof> {
+		not_supported()
+	}
+
+	fn prove_messages_delivery(
+		&self,
+		_instance: InstanceId,
+		_lane: LaneId,
+		_block: Option<Block::Hash>,
+	) -> FutureResult<MessagesDeliveryProof> {
+		not_supported()
+	}
+
+	fn estimate_fee(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		payload: Bytes,
+		block: Option<Block::Hash>,
+	) -> FutureResult<OutboundMessageFee> {
+		let remote = self.remote.clone();
+		Box::new(
+			async move {
+				let header = remote.header(block)?;
+				remote.ensure_known_outbound_lane(&header, instance, lane).await?;
+				remote
+					.call::<Option<OutboundMessageFee>>(
+						&header,
+						"OutboundLaneFeeApi_estimate_message_delivery_and_dispatch_fee",
+						(instance, lane, payload.0).encode(),
+					)
+					.await?
+					.ok_or(Error::FeeEstimationFailed)
+			}
+			.boxed()
+			.compat()
+			.map_err(Into::into),
+		)
+	}
+
+	fn prove_storage(&self, _keys: Vec<StorageKey>, _block: Option<Block::Hash>) -> FutureResult<SizedStorageProof> {
+		not_supported()
+	}
+
+	fn messages_fee(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		begin: MessageNonce,
+		end: MessageNonce,
+		block: Option<Block::Hash>,
+	) -> FutureResult<OutboundMessageFee> {
+		let remote = self.remote.clone();
+		Box::new(
+			async move {
+				if begin > end {
+					return Err(Error::InvalidNoncesRange);
+				}
+
+				let header = remote.header(block)?;
+				remote.ensure_known_outbound_lane(&header, instance, lane).await?;
+				remote
+					.call::<Option<OutboundMessageFee>>(
+						&header,
+						"OutboundLaneFeeApi_messages_fee",
+						(instance, lane, begin, end).encode(),
+					)
+					.await?
+					.ok_or(Error::MessagesFeeUnavailable)
+			}
+			.boxed()
+			.compat()
+			.map_err(Into::into),
+		)
+	}
+
+	fn unrewarded_relayers(
+		&self,
+		instance: InstanceId,
+		lane: LaneId,
+		block: Option<Block::Hash>,
+	) -> FutureResult<UnrewardedRelayers<InboundRelayer>> {
+		let remote = self.remote.clone();
+		Box::new(
+			async move {
+				let header = remote.header(block)?;
+				let relayers = remote
+					.call::<Option<Vec<UnrewardedRelayer<InboundRelayer>>>>(
+						&header,
+						"InboundLaneRelayersApi_unrewarded_relayers",
+						(instance, lane).encode(),
+					)
+					.await?
+					.ok_or(Error::UnknownInstance)?;
+				let max_entries = remote
+					.call::<Option<MessageNonce>>(
+						&header,
+						"InboundLaneRelayersApi_max_unrewarded_relayer_entries",
+						instance.encode(),
+					)
+					.await?
+					.ok_or(Error::UnknownInstance)?;
+				Ok(UnrewardedRelayers {
+					relayers: relayers.into_iter().map(Into::into).collect(),
+					max_entries,
+				})
+			}
+			.boxed()
+			.compat()
+			.map_err(Into::into),
+		)
+	}
+
+	fn lanes(&self, instance: InstanceId, block: Option<Block::Hash>) -> FutureResult<Vec<LaneDetails>> {
+		let remote = self.remote.clone();
+		Box::new(
+			async move {
+				let header = remote.header(block)?;
+				let lanes = remote
+					.call::<Option<Vec<LaneState>>>(&header, "LanesApi_lanes", instance.encode())
+					.await?
+					.ok_or(Error::UnknownInstance)?;
+				Ok(lanes.into_iter().map(Into::into).collect())
+			}
+			.boxed()
+			.compat()
+			.map_err(Into::into),
+		)
+	}
+
+	fn subscribe_outbound_messages(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<MessagesNotification<Block::Hash>>,
+		_instance: InstanceId,
+		_lane: LaneId,
+	) {
+		let _ = subscriber.reject(Error::NotSupportedByLightClient.into());
+	}
+
+	fn unsubscribe_outbound_messages(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		_id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool> {
+		Ok(false)
+	}
+
+	fn subscribe_delivery_confirmations(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<MessagesNotification<Block::Hash>>,
+		_instance: InstanceId,
+		_lane: LaneId,
+	) {
+		let _ = subscriber.reject(Error::NotSupportedByLightClient.into());
+	}
+
+	fn unsubscribe_delivery_confirmations(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		_id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool> {
+		Ok(false)
+	}
+
+	fn subscribe_inbound_lane_nonces(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<InboundLaneNotification<Block::Hash>>,
+		_instance: InstanceId,
+		_lane: LaneId,
+	) {
+		let _ = subscriber.reject(Error::NotSupportedByLightClient.into());
+	}
+
+	fn unsubscribe_inbound_lane_nonces(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		_id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool> {
+		Ok(false)
+	}
+}
+
+/// Runtime, which calls are executed by remote full nodes.
+struct RemoteRuntime<Block, Client, F> {
+	client: Arc<Client>,
+	fetcher: Arc<F>,
+	_phantom: PhantomData<Block>,
+}
+
+impl<Block, Client, F> Clone for RemoteRuntime<Block, Client, F> {
+	fn clone(&self) -> Self {
+		RemoteRuntime {
+			client: self.client.clone(),
+			fetcher: self.fetcher.clone(),
+			_phantom: Default::default(),
+		}
+	}
+}
+
+impl<Block, Client, F> RemoteRuntime<Block, Client, F>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block>,
+	F: Fetcher<Block>,
+{
+	/// Return header of given block (or of the best block, if it isn't specified).
+	fn header(&self, block: Option<Block::Hash>) -> Result<Block::Header, Error> {
+		let block = block.unwrap_or_else(|| self.client.info().best_hash);
+		self.client
+			.header(BlockId::Hash(block))
+			.map_err(blockchain_err)?
+			.ok_or(Error::UnknownBlock)
+	}
+
+	/// Make sure that the runtime knows given message lane instance.
+	async fn ensure_known_outbound_lane(
+		&self,
+		header: &Block::Header,
+		instance: InstanceId,
+		lane: LaneId,
+	) -> Result<(), Error> {
+		self.call::<Option<Vec<u8>>>(
+			header,
+			"MessageLaneStorageApi_outbound_lane_data_key",
+			(instance, lane).encode(),
+		)
+		.await?
+		.map(drop)
+		.ok_or(Error::UnknownInstance)
+	}
+
+	/// Call runtime API method at given block and decode its result.
+	async fn call<R: Decode>(&self, header: &Block::Header, method: &str, call_data: Vec<u8>) -> Result<R, Error> {
+		let encoded_result = self
+			.fetcher
+			.remote_call(RemoteCallRequest {
+				block: header.hash(),
+				header: header.clone(),
+				method: method.into(),
+				call_data,
+				retry_count: None,
+			})
+			.await
+			.map_err(blockchain_err)?;
+		R::decode(&mut &encoded_result[..]).map_err(|error| Error::RuntimeApi(format!("{}: {}", method, error)))
+	}
+}
+
+/// Return error saying that the method is not supported by light client.
+fn not_supported<T: Send + 'static>() -> FutureResult<T> {
+	Box::new(jsonrpc_core::futures::future::err(
+		Error::NotSupportedByLightClient.into(),
+	))
+}
@@ -18,8 +18,9 @@
 
 use bp_message_lane::{
 	target_chain::{DispatchMessage, DispatchMessageData, MessageDispatch},
-	InboundLaneData, LaneId, MessageKey, MessageNonce, OutboundLaneData,
+	InboundLaneData, LaneId, MessageData, MessageKey, MessageNonce, OutboundLaneData,
 };
+use frame_support::weights::Weight;
 use sp_std::prelude::PartialEq;
 
 /// Inbound lane storage.
@@ -37,6 +38,16 @@ pub trait InboundLaneStorage {
 	fn data(&self) -> InboundLaneData<Self::Relayer>;
 	/// Update lane data in the storage.
 	fn set_data(&mut self, data: InboundLaneData<Self::Relayer>);
+	/// Return nonce of the oldest received, but not yet dispatched message.
+	fn oldest_undispatched_nonce(&self) -> Option<MessageNonce>;
+	/// Update nonce of the oldest received, but not yet dispatched message.
+	fn set_oldest_undispatched_nonce(&mut self, nonce: Option<MessageNonce>);
+	/// Returns saved received, but not yet dispatched message.
+	fn undispatched_message(&self, nonce: &MessageNonce) -> Option<MessageData<Self::MessageFee>>;
+	/// Save received, but not yet dispatched message.
+	fn save_undispatched_message(&mut self, nonce: MessageNonce, message_data: MessageData<Self::MessageFee>);
+	/// Remove received, but not yet dispatched message.
+	fn remove_undispatched_message(&mut self, nonce: &MessageNonce);
 }
 
 /// Inbound messages lane.
@@ -84,6 +95,14 @@ impl<S: InboundLaneStorage> InboundLane<S> {
 		Some(outbound_lane_data.latest_received_nonce)
 	}
 
+	/// Returns true if there are received messages that are not yet dispatched.
+	///
+	/// New messages must not be dispatched immediately while there are undispatched messages at
+	/// the lane (or messages will be dispatched out of order).
+	pub fn has_undispatched_messages(&self) -> bool {
+		self.storage.oldest_undispatched_nonce().is_some()
+	}
+
 	/// Receive new message.
 	pub fn receive_message<P: MessageDispatch<S::MessageFee>>(
 		&mut self,
@@ -91,6 +110,98 @@ impl<S: InboundLaneStorage> InboundLane<S> {
 		nonce: MessageNonce,
 		message_data: DispatchMessageData<P::DispatchPayload, S::MessageFee>,
 	) -> bool {
+		if !self.receive_message_nonce(relayer, nonce) {
+			return false;
+		}
+
+		P::dispatch(DispatchMessage {
+			key: MessageKey {
+				lane_id: self.storage.id(),
+				nonce,
+			},
+			data: message_data,
+		});
+
+		true
+	}
+
+	/// Receive new message, but do not dispatch it. The message is saved in the storage and may
+	/// be dispatched later using `dispatch_undispatched_messages`.
+	pub fn receive_undispatched_message(
+		&mut self,
+		relayer: S::Relayer,
+		nonce: MessageNonce,
+		message_data: MessageData<S::MessageFee>,
+	) -> bool {
+		if !self.receive_message_nonce(relayer, nonce) {
+			return false;
+		}
+
+		self.storage.save_undispatched_message(nonce, message_data);
+		if self.storage.oldest_undispatched_nonce().is_none() {
+			self.storage.set_oldest_undispatched_nonce(Some(nonce));
+		}
+
+		true
+	}
+
+	/// Dispatch received, but not yet dispatched messages (in order), until either all messages
+	/// are dispatched, or dispatch weight of the next message exceeds remaining `max_weight`.
+	///
+	/// Returns number of dispatched messages and their total dispatch weight.
+	pub fn dispatch_undispatched_messages<P: MessageDispatch<S::MessageFee>>(
+		&mut self,
+		max_weight: Weight,
+	) -> (MessageNonce, Weight) {
+		let latest_received_nonce = self.storage.data().latest_received_nonce;
+		let mut dispatched_messages = 0;
+		let mut dispatched_weight: Weight = 0;
+		while let Some(nonce) = self.storage.oldest_undispatched_nonce() {
+			let next_undispatched_nonce = if nonce < latest_received_nonce {
+				Some(nonce + 1)
+			} else {
+				None
+			};
+
+			// all messages in [oldest_undispatched_nonce; latest_received_nonce] range must be saved,
+			// but if the storage is corrupted, we'd better skip the message than block the lane forever
+			let message_data = match self.storage.undispatched_message(&nonce) {
+				Some(message_data) => message_data,
+				None => {
+					frame_support::debug::error!(
+						"Undispatched message {} of lane {:?} is missing from the storage",
+						nonce,
+						self.storage.id(),
+					);
+					self.storage.set_oldest_undispatched_nonce(next_undispatched_nonce);
+					continue;
+				}
+			};
+			let message: DispatchMessage<P::DispatchPayload, S::MessageFee> = DispatchMessage {
+				key: MessageKey {
+					lane_id: self.storage.id(),
+					nonce,
+				},
+				data: message_data.into(),
+			};
+			let message_weight = P::dispatch_weight(&message);
+			if dispatched_weight.saturating_add(message_weight) > max_weight {
+				break;
+			}
+
+			self.storage.remove_undispatched_message(&nonce);
+			self.storage.set_oldest_undispatched_nonce(next_undispatched_nonce);
+			P::dispatch(message);
+
+			dispatched_messages += 1;
+			dispatched_weight += message_weight;
+		}
+
+		(dispatched_messages, dispatched_weight)
+	}
+
+	/// Verify nonce of the new message and update lane state.
+	fn receive_message_nonce(&mut self, relayer: S::Relayer, nonce: MessageNonce) -> bool {
 		let mut data = self.storage.data();
 		let is_correct_message = nonce == data.latest_received_nonce + 1;
 		if !is_correct_message {
@@ -117,14 +228,6 @@ impl<S: InboundLaneStorage> InboundLane<S> {
 
 		self.storage.set_data(data);
 
-		P::dispatch(DispatchMessage {
-			key: MessageKey {
-				lane_id: self.storage.id(),
-				nonce,
-			},
-			data: message_data,
-		});
-
 		true
 	}
 }
@@ -351,6 +454,85 @@ mod tests {
 			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
 			receive_regular_message(&mut lane, 1);
 			assert_eq!(lane.storage.data().latest_received_nonce, 1);
+			assert!(TestMessageDispatch::is_dispatched(1));
+		});
+	}
+
+	#[test]
+	fn undispatched_message_is_saved_and_not_dispatched() {
+		run_test(|| {
+			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
+			assert!(lane.receive_undispatched_message(TEST_RELAYER_A, 1, message_data(REGULAR_PAYLOAD)));
+			assert!(lane.receive_undispatched_message(TEST_RELAYER_A, 2, message_data(REGULAR_PAYLOAD)));
+			assert!(!lane.receive_undispatched_message(TEST_RELAYER_A, 4, message_data(REGULAR_PAYLOAD)));
+
+			assert_eq!(lane.storage.data().latest_received_nonce, 2);
+			assert_eq!(lane.storage.data().relayers, vec![(1, 2, TEST_RELAYER_A)]);
+			assert_eq!(lane.storage.oldest_undispatched_nonce(), Some(1));
+			assert_eq!(
+				lane.storage.undispatched_message(&2),
+				Some(message_data(REGULAR_PAYLOAD))
+			);
+			assert!(lane.has_undispatched_messages());
+			assert!(!TestMessageDispatch::is_dispatched(1));
+			assert!(!TestMessageDispatch::is_dispatched(2));
+		});
+	}
+
+	#[test]
+	fn undispatched_messages_are_dispatched_in_order_until_weight_is_exhausted() {
+		run_test(|| {
+			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
+			for nonce in 1..4 {
+				assert!(lane.receive_undispatched_message(TEST_RELAYER_A, nonce, message_data(REGULAR_PAYLOAD)));
+			}
+
+			// weight is not enough to dispatch even single message
+			assert_eq!(
+				lane.dispatch_undispatched_messages::<TestMessageDispatch>(REGULAR_PAYLOAD.1 - 1),
+				(0, 0),
+			);
+			assert_eq!(lane.storage.oldest_undispatched_nonce(), Some(1));
+
+			// weight is enough to dispatch two messages
+			assert_eq!(
+				lane.dispatch_undispatched_messages::<TestMessageDispatch>(REGULAR_PAYLOAD.1 * 2 + 1),
+				(2, REGULAR_PAYLOAD.1 * 2),
+			);
+			assert!(TestMessageDispatch::is_dispatched(1));
+			assert!(TestMessageDispatch::is_dispatched(2));
+			assert!(!TestMessageDispatch::is_dispatched(3));
+			assert_eq!(lane.storage.undispatched_message(&1), None);
+			assert_eq!(lane.storage.oldest_undispatched_nonce(), Some(3));
+
+			// the rest is dispatched
+			assert_eq!(
+				lane.dispatch_undispatched_messages::<TestMessageDispatch>(REGULAR_PAYLOAD.1 * 10),
+				(1, REGULAR_PAYLOAD.1),
+			);
+			assert!(TestMessageDispatch::is_dispatched(3));
+			assert_eq!(lane.storage.oldest_undispatched_nonce(), None);
+			assert!(!lane.has_undispatched_messages());
+		});
+	}
+
+	#[test]
+	fn missing_undispatched_message_is_skipped() {
+		run_test(|| {
+			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
+			for nonce in 1..4 {
+				assert!(lane.receive_undispatched_message(TEST_RELAYER_A, nonce, message_data(REGULAR_PAYLOAD)));
+			}
+			lane.storage.remove_undispatched_message(&2);
+
+			assert_eq!(
+				lane.dispatch_undispatched_messages::<TestMessageDispatch>(REGULAR_PAYLOAD.1 * 10),
+				(2, REGULAR_PAYLOAD.1 * 2),
+			);
+			assert!(TestMessageDispatch::is_dispatched(1));
+			assert!(!TestMessageDispatch::is_dispatched(2));
+			assert!(TestMessageDispatch::is_dispatched(3));
+			assert!(!lane.has_undispatched_messages());
 		});
 	}
 }
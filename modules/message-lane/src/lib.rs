@@ -23,9 +23,18 @@
 //! 5) messages are processed in order (ordered by assigned nonce);
 //! 6) relay may send proof-of-delivery back to this chain.
 //!
+//! Messages that do not fit into the dispatch weight, declared by the delivery transaction, are
+//! received, but not dispatched. They may be dispatched later using `dispatch_undispatched_messages()`
+//! call.
+//!
 //! Once message is sent, its progress can be tracked by looking at module events.
 //! The assigned nonce is reported using `MessageAccepted` event. When message is
 //! delivered to the the bridged chain, it is reported using `MessagesDelivered` event.
+//! Messages that have been accepted in the block are also listed in the `OutboundMessagesDigest`
+//! digest item of this block header, so light clients may detect new messages without storage
+//! queries.
+//!
+//! Off-chain workers of other pallets may send messages using helpers from the `offchain` module.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -34,31 +43,93 @@ use crate::outbound_lane::{OutboundLane, OutboundLaneStorage};
 
 use bp_message_lane::{
 	source_chain::{LaneMessageVerifier, MessageDeliveryAndDispatchPayment, TargetHeaderChain},
-	target_chain::{DispatchMessage, MessageDispatch, ProvedLaneMessages, ProvedMessages, SourceHeaderChain},
-	InboundLaneData, LaneId, MessageData, MessageKey, MessageNonce, MessagePayload, OutboundLaneData,
+	target_chain::{DispatchMessage, MessageDispatch, ProvedMessagesAccounting, SourceHeaderChain},
+	InboundLaneData, LaneId, LaneState, Message, MessageData, MessageKey, MessageNonce, MessagePayload,
+	OutboundLaneData, OutboundMessagesDigest, StorageVersion, UnrewardedRelayer, CURRENT_STORAGE_VERSION,
+	LEGACY_STORAGE_VERSION, STORAGE_VERSION_V1,
 };
+use bp_message_lane_weights::{MessageLaneWeights, DEFAULT_MESSAGE_LANE_WEIGHTS};
+use bp_runtime::Size;
 use codec::{Decode, Encode};
 use frame_support::{
 	decl_error, decl_event, decl_module, decl_storage,
-	traits::Get,
+	dispatch::{DispatchErrorWithPostInfo, DispatchResultWithPostInfo},
+	ensure,
+	traits::{EnsureOrigin, Get},
 	weights::{DispatchClass, Weight},
-	Parameter, StorageMap,
+	IterableStorageMap, Parameter, StorageMap,
 };
 use frame_system::{ensure_signed, RawOrigin};
-use sp_runtime::{traits::BadOrigin, DispatchResult};
-use sp_std::{cell::RefCell, marker::PhantomData, prelude::*};
+use sp_runtime::{
+	generic::DigestItem,
+	traits::{BadOrigin, Hash},
+	DispatchResult,
+};
+use sp_std::{cell::RefCell, collections::btree_map::BTreeMap, marker::PhantomData, prelude::*};
 
 mod inbound_lane;
 mod outbound_lane;
 
 pub mod instant_payments;
+pub mod offchain;
 
 #[cfg(test)]
 mod mock;
+#[cfg(test)]
+mod proptests;
+
+/// Weight functions of the pallet transactions.
+pub trait WeightInfo {
+	/// Weight of the `receive_messages_proof` transaction.
+	fn receive_messages_proof(messages_count: u32, proof_size: u32, dispatch_weight: Weight) -> Weight;
+	/// Weight of the `receive_messages_delivery_proof` transaction.
+	fn receive_messages_delivery_proof(proof_size: u32) -> Weight;
+	/// Weight of the `dispatch_undispatched_messages` transaction.
+	fn dispatch_undispatched_messages(dispatch_weight: Weight) -> Weight;
+	/// Weight of saving received, but not dispatched messages by the `receive_messages_proof`
+	/// transaction.
+	fn save_undispatched_messages(messages_count: u32, messages_size: u32) -> Weight;
+}
+
+/// Weight functions that are using given parameters of the shared weight formulas. Use the same
+/// parameters in the relay, so that its estimations match the actual weights.
+pub struct WeightsFrom<W>(PhantomData<W>);
+
+impl<W: Get<MessageLaneWeights>> WeightInfo for WeightsFrom<W> {
+	fn receive_messages_proof(messages_count: u32, proof_size: u32, dispatch_weight: Weight) -> Weight {
+		W::get().receive_messages_proof(messages_count, proof_size, dispatch_weight)
+	}
+
+	fn receive_messages_delivery_proof(proof_size: u32) -> Weight {
+		W::get().receive_messages_delivery_proof(proof_size)
+	}
+
+	fn dispatch_undispatched_messages(dispatch_weight: Weight) -> Weight {
+		W::get().dispatch_undispatched_messages(dispatch_weight)
+	}
+
+	fn save_undispatched_messages(messages_count: u32, messages_size: u32) -> Weight {
+		W::get().save_undispatched_messages(messages_count, messages_size)
+	}
+}
+
+impl WeightInfo for () {
+	fn receive_messages_proof(messages_count: u32, proof_size: u32, dispatch_weight: Weight) -> Weight {
+		DEFAULT_MESSAGE_LANE_WEIGHTS.receive_messages_proof(messages_count, proof_size, dispatch_weight)
+	}
+
+	fn receive_messages_delivery_proof(proof_size: u32) -> Weight {
+		DEFAULT_MESSAGE_LANE_WEIGHTS.receive_messages_delivery_proof(proof_size)
+	}
+
+	fn dispatch_undispatched_messages(dispatch_weight: Weight) -> Weight {
+		DEFAULT_MESSAGE_LANE_WEIGHTS.dispatch_undispatched_messages(dispatch_weight)
+	}
 
-// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
-/// Upper bound of delivery transaction weight.
-const DELIVERY_BASE_WEIGHT: Weight = 0;
+	fn save_undispatched_messages(messages_count: u32, messages_size: u32) -> Weight {
+		DEFAULT_MESSAGE_LANE_WEIGHTS.save_undispatched_messages(messages_count, messages_size)
+	}
+}
 
 /// The module configuration trait
 pub trait Trait<I = DefaultInstance>: frame_system::Trait {
@@ -66,6 +137,9 @@ pub trait Trait<I = DefaultInstance>: frame_system::Trait {
 
 	/// They overarching event type.
 	type Event: From<Event<Self, I>> + Into<<Self as frame_system::Trait>::Event>;
+	/// Origin that is allowed to change pallet owner and to halt/resume pallet operations, in
+	/// addition to root and `ModuleOwner`. It could be e.g. a council or other collective.
+	type AdminOrigin: EnsureOrigin<Self::Origin>;
 	/// Maximal number of messages that may be pruned during maintenance. Maintenance occurs
 	/// whenever outbound lane is updated - i.e. when new message is sent, or receival is
 	/// confirmed. The reason is that if you want to use lane, you should be ready to pay
@@ -82,6 +156,14 @@ pub trait Trait<I = DefaultInstance>: frame_system::Trait {
 	/// transaction#2 with individual messages [3; 4], this would be treated as single "Message" and
 	/// would occupy single unit of `MaxUnconfirmedMessagesAtInboundLane` limit.
 	type MaxUnconfirmedMessagesAtInboundLane: Get<MessageNonce>;
+	/// Maximal size (in bytes, as reported by the `Size` trait) of the messages proof. Larger proofs
+	/// are rejected before verification.
+	type MaxMessagesProofSize: Get<u32>;
+	/// Maximal size (in bytes, as reported by the `Size` trait) of the messages delivery proof.
+	/// Larger proofs are rejected before verification.
+	type MaxMessagesDeliveryProofSize: Get<u32>;
+	/// Weight functions of the pallet transactions.
+	type WeightInfo: WeightInfo;
 
 	/// Payload type of outbound messages. This payload is dispatched on the bridged chain.
 	type OutboundPayload: Parameter;
@@ -91,7 +173,7 @@ pub trait Trait<I = DefaultInstance>: frame_system::Trait {
 	/// Payload type of inbound messages. This payload is dispatched on this chain.
 	type InboundPayload: Decode;
 	/// Message fee type of inbound messages. This fee is paid on the bridged chain.
-	type InboundMessageFee: Decode;
+	type InboundMessageFee: Parameter;
 	/// Identifier of relayer that deliver messages to this chain. Relayer reward is paid on the bridged chain.
 	type InboundRelayer: Parameter;
 
@@ -137,6 +219,16 @@ decl_error! {
 		InvalidMessagesDispatchWeight,
 		/// Invalid messages delivery proof has been submitted.
 		InvalidMessagesDeliveryProof,
+		/// There are no received, but not yet dispatched messages at the lane.
+		NoUndispatchedMessages,
+		/// Submitted messages proof is larger than `MaxMessagesProofSize`.
+		MessagesProofTooLarge,
+		/// Submitted messages delivery proof is larger than `MaxMessagesDeliveryProofSize`.
+		MessagesDeliveryProofTooLarge,
+		/// The number of messages in the proof is larger than the declared messages count.
+		InvalidMessagesCount,
+		/// The pallet only accepts messages of whitelisted lanes and the lane is not whitelisted.
+		LaneIsNotWhitelisted,
 	}
 }
 
@@ -151,20 +243,41 @@ decl_storage! {
 		pub ModuleOwner get(fn module_owner): Option<T::AccountId>;
 		/// If true, all pallet transactions are failed immediately.
 		pub IsHalted get(fn is_halted) config(): bool;
+		/// If true, only whitelisted lanes (see `WhitelistedLanes`) may be used to send and
+		/// receive messages.
+		pub IsWhitelistedLanesOnly get(fn is_whitelisted_lanes_only) config(): bool;
+		/// Set of lanes that may be used when `IsWhitelistedLanesOnly` is true.
+		pub WhitelistedLanes: map hasher(blake2_128_concat) LaneId => bool;
+		/// Version of the pallet storage encoding. It is `LEGACY_STORAGE_VERSION` until the storage
+		/// is migrated to (or the chain is started with) the explicit storage version.
+		pub PalletStorageVersion get(fn storage_version): StorageVersion;
 		/// Map of lane id => inbound lane data.
 		pub InboundLanes: map hasher(blake2_128_concat) LaneId => InboundLaneData<T::InboundRelayer>;
+		/// Map of lane id => nonce of the oldest received, but not yet dispatched inbound message. All
+		/// messages in range [oldest undispatched nonce; latest received nonce] are undispatched.
+		pub InboundOldestUndispatchedNonces: map hasher(blake2_128_concat) LaneId => Option<MessageNonce>;
+		/// All received, but not yet dispatched inbound messages.
+		pub InboundUndispatchedMessages: map hasher(blake2_128_concat) MessageKey => Option<MessageData<T::InboundMessageFee>>;
 		/// Map of lane id => outbound lane data.
 		pub OutboundLanes: map hasher(blake2_128_concat) LaneId => OutboundLaneData;
 		/// All queued outbound messages.
 		pub OutboundMessages: map hasher(blake2_128_concat) MessageKey => Option<MessageData<T::OutboundMessageFee>>;
+		/// Map of lane id => inclusive range of nonces of outbound messages, accepted in the current
+		/// block. It is used to build outbound messages digest and is cleared at the end of every block.
+		pub OutboundMessagesInBlock: map hasher(blake2_128_concat) LaneId => Option<(MessageNonce, MessageNonce)>;
 	}
 	add_extra_genesis {
 		config(phantom): sp_std::marker::PhantomData<I>;
 		config(owner): Option<T::AccountId>;
+		config(whitelisted_lanes): Vec<LaneId>;
 		build(|config| {
+			PalletStorageVersion::<I>::put(CURRENT_STORAGE_VERSION);
 			if let Some(ref owner) = config.owner {
 				<ModuleOwner<T, I>>::put(owner);
 			}
+			for lane_id in &config.whitelisted_lanes {
+				WhitelistedLanes::<I>::insert(lane_id, true);
+			}
 		})
 	}
 }
@@ -187,9 +300,21 @@ decl_module! {
 		/// Deposit one of this module's events by using the default implementation.
 		fn deposit_event() = default;
 
+		fn on_runtime_upgrade() -> Weight {
+			migrate_storage::<T, I>()
+		}
+
+		/// Deposit outbound messages digest for every lane that has accepted new messages in this block.
+		fn on_finalize() {
+			for (lane_id, (begin, end)) in OutboundMessagesInBlock::<I>::drain() {
+				let digest = outbound_messages_digest::<T, I>(lane_id, begin, end);
+				frame_system::Module::<T>::deposit_log(DigestItem::Other(digest.to_digest_item_data()));
+			}
+		}
+
 		/// Change `ModuleOwner`.
 		///
-		/// May only be called either by root, or by `ModuleOwner`.
+		/// May only be called either by root, by `AdminOrigin`, or by `ModuleOwner`.
 		#[weight = (T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational)]
 		pub fn set_owner(origin, new_owner: Option<T::AccountId>) {
 			ensure_owner_or_root::<T, I>(origin)?;
@@ -207,7 +332,7 @@ decl_module! {
 
 		/// Halt all pallet operations. Operations may be resumed using `resume_operations` call.
 		///
-		/// May only be called either by root, or by `ModuleOwner`.
+		/// May only be called either by root, by `AdminOrigin`, or by `ModuleOwner`.
 		#[weight = (T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational)]
 		pub fn halt_operations(origin) {
 			ensure_owner_or_root::<T, I>(origin)?;
@@ -217,7 +342,7 @@ decl_module! {
 
 		/// Resume all pallet operations. May be called even if pallet is halted.
 		///
-		/// May only be called either by root, or by `ModuleOwner`.
+		/// May only be called either by root, by `AdminOrigin`, or by `ModuleOwner`.
 		#[weight = (T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational)]
 		pub fn resume_operations(origin) {
 			ensure_owner_or_root::<T, I>(origin)?;
@@ -225,6 +350,33 @@ decl_module! {
 			frame_support::debug::info!("Resuming pallet operations.");
 		}
 
+		/// Enable or disable whitelisted-lanes-only mode. In this mode, messages may only be sent
+		/// and received over lanes that are added to the whitelist using `set_lane_whitelisted`.
+		///
+		/// May only be called either by root, by `AdminOrigin`, or by `ModuleOwner`.
+		#[weight = (T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational)]
+		pub fn set_whitelisted_lanes_only(origin, whitelisted_lanes_only: bool) {
+			ensure_owner_or_root::<T, I>(origin)?;
+			IsWhitelistedLanesOnly::<I>::put(whitelisted_lanes_only);
+			frame_support::debug::info!("Setting whitelisted lanes only mode to: {}", whitelisted_lanes_only);
+		}
+
+		/// Add lane to or remove lane from the whitelist. The whitelist is only used in the
+		/// whitelisted-lanes-only mode.
+		///
+		/// May only be called either by root, by `AdminOrigin`, or by `ModuleOwner`.
+		#[weight = (T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational)]
+		pub fn set_lane_whitelisted(origin, lane_id: LaneId, is_whitelisted: bool) {
+			ensure_owner_or_root::<T, I>(origin)?;
+			if is_whitelisted {
+				WhitelistedLanes::<I>::insert(lane_id, true);
+				frame_support::debug::info!("Added lane {:?} to the whitelist.", lane_id);
+			} else {
+				WhitelistedLanes::<I>::remove(lane_id);
+				frame_support::debug::info!("Removed lane {:?} from the whitelist.", lane_id);
+			}
+		}
+
 		/// Send message over lane.
 		#[weight = 0] // TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
 		pub fn send_message(
@@ -235,6 +387,7 @@ decl_module! {
 		) -> DispatchResult {
 			ensure_operational::<T, I>()?;
 			let submitter = ensure_signed(origin)?;
+			ensure_lane_whitelisted::<T, I>(lane_id)?;
 
 			// let's first check if message can be delivered to target chain
 			T::TargetHeaderChain::verify_message(&payload).map_err(|err| {
@@ -286,6 +439,9 @@ decl_module! {
 				fee: delivery_and_dispatch_fee,
 			});
 			lane.prune_messages(T::MaxMessagesToPruneAtOnce::get());
+			OutboundMessagesInBlock::<I>::mutate(lane_id, |range| {
+				*range = Some((range.map(|(begin, _)| begin).unwrap_or(nonce), nonce));
+			});
 
 			frame_support::debug::trace!(
 				"Accepted message {} to lane {:?}",
@@ -299,50 +455,98 @@ decl_module! {
 		}
 
 		/// Receive messages proof from bridged chain.
-		#[weight = DELIVERY_BASE_WEIGHT + dispatch_weight]
+		///
+		/// Messages are dispatched in order, until their total dispatch weight exceeds declared
+		/// `dispatch_weight`. Remaining messages are received, but not dispatched. They may be
+		/// dispatched later using `dispatch_undispatched_messages` call.
+		///
+		/// The transaction weight depends on the declared number of messages and the proof size.
+		/// Proofs that are larger than `MaxMessagesProofSize` are rejected before verification.
+		/// Only the actually spent dispatch weight is charged: rejected transactions and
+		/// transactions with already received messages are refunding the declared dispatch weight.
+		/// Saving undispatched messages to the storage is also charged. The weight of saving all
+		/// declared messages is reserved upfront and the unused part is refunded.
+		#[weight = T::WeightInfo::receive_messages_proof(*messages_count, proof.size_hint(), *dispatch_weight)
+			.saturating_add(T::WeightInfo::save_undispatched_messages(*messages_count, proof.size_hint()))]
 		pub fn receive_messages_proof(
 			origin,
 			relayer_id: T::InboundRelayer,
 			proof: MessagesProofOf<T, I>,
+			messages_count: u32,
 			dispatch_weight: Weight,
-		) -> DispatchResult {
-			ensure_operational::<T, I>()?;
+		) -> DispatchResultWithPostInfo {
+			let proof_size = proof.size_hint();
+			let unverified_proof_rejection_weight = T::WeightInfo::receive_messages_proof(0, 0, 0);
+			let verified_proof_rejection_weight = T::WeightInfo::receive_messages_proof(messages_count, proof_size, 0);
+
+			ensure_operational::<T, I>()
+				.map_err(|err| cheap_rejection(err, unverified_proof_rejection_weight))?;
 			let _ = ensure_signed(origin)?;
+			ensure!(
+				proof_size <= T::MaxMessagesProofSize::get(),
+				cheap_rejection(Error::<T, I>::MessagesProofTooLarge, unverified_proof_rejection_weight)
+			);
 
 			// verify messages proof && convert proof into messages
-			let messages = verify_and_decode_messages_proof::<T::SourceHeaderChain, T::InboundMessageFee, T::InboundPayload>(proof)
+			let messages = T::SourceHeaderChain::verify_messages_proof(proof)
 				.map_err(|err| {
 					frame_support::debug::trace!(
 						"Rejecting invalid messages proof: {:?}",
 						err,
 					);
 
-					Error::<T, I>::InvalidMessagesProof
+					cheap_rejection(Error::<T, I>::InvalidMessagesProof, verified_proof_rejection_weight)
 				})?;
 
-			// verify that relayer is paying actual dispatch weight
-			let actual_dispatch_weight: Weight = messages
-				.values()
-				.map(|lane_messages| lane_messages
+			// the relayer has paid for delivering `messages_count` messages => reject the proof if
+			// it has more messages
+			ensure!(
+				messages.messages_count() <= messages_count as usize,
+				cheap_rejection(Error::<T, I>::InvalidMessagesCount, verified_proof_rejection_weight)
+			);
+
+			// every message must be proved at the lane it has been sent over
+			let wrong_lane_message = messages.iter().find_map(|(lane_id, lane_data)| {
+				lane_data
 					.messages
 					.iter()
-					.map(T::MessageDispatch::dispatch_weight)
-					.sum::<Weight>()
-				)
-				.sum();
-			if dispatch_weight < actual_dispatch_weight {
+					.find(|message| message.key.lane_id != *lane_id)
+					.map(|message| (*lane_id, message.key.lane_id))
+			});
+			if let Some((lane_id, message_lane_id)) = wrong_lane_message {
 				frame_support::debug::trace!(
-					"Rejecting messages proof because of dispatch weight mismatch: declared={}, expected={}",
-					dispatch_weight,
-					actual_dispatch_weight,
+					"Rejecting messages proof with message of lane {:?} at lane {:?}",
+					message_lane_id,
+					lane_id,
 				);
 
-				return Err(Error::<T, I>::InvalidMessagesDispatchWeight.into());
+				return Err(cheap_rejection(Error::<T, I>::InvalidMessagesProof, verified_proof_rejection_weight));
+			}
+
+			// messages of every lane must be ordered by nonce and must not have gaps
+			messages.ensure_contiguous_nonces().map_err(|(lane_id, gap)| {
+				frame_support::debug::trace!(
+					"Rejecting messages proof with nonces gap at lane {:?}: expected {}, found {}",
+					lane_id,
+					gap.expected,
+					gap.actual,
+				);
+
+				cheap_rejection(Error::<T, I>::InvalidMessagesProof, verified_proof_rejection_weight)
+			})?;
+
+			// in whitelisted-lanes-only mode, messages of all lanes must be whitelisted
+			for lane_id in messages.keys() {
+				ensure_lane_whitelisted::<T, I>(*lane_id)
+					.map_err(|err| cheap_rejection(err, verified_proof_rejection_weight))?;
 			}
 
-			// dispatch messages and (optionally) update lane(s) state(s)
+			// dispatch messages (while relayer is paying for dispatch) and (optionally) update lane(s) state(s)
 			let mut total_messages = 0;
 			let mut valid_messages = 0;
+			let mut undispatched_messages = 0;
+			let mut undispatched_messages_size: u32 = 0;
+			let mut remaining_dispatch_weight = dispatch_weight;
 			for (lane_id, lane_data) in messages {
 				let mut lane = inbound_lane::<T, I>(lane_id);
 
@@ -358,30 +562,110 @@ decl_module! {
 				}
 
 				for message in lane_data.messages {
-					debug_assert_eq!(message.key.lane_id, lane_id);
-
 					total_messages += 1;
-					if lane.receive_message::<T::MessageDispatch>(relayer_id.clone(), message.key.nonce, message.data) {
+					let dispatch_message: DispatchMessage<T::InboundPayload, T::InboundMessageFee> =
+						message.clone().into();
+					let message_dispatch_weight = T::MessageDispatch::dispatch_weight(&dispatch_message);
+					let message_size = message.data.payload.len() as u32;
+					let is_message_dispatched = !lane.has_undispatched_messages()
+						&& message_dispatch_weight <= remaining_dispatch_weight;
+					let is_message_received = if is_message_dispatched {
+						lane.receive_message::<T::MessageDispatch>(
+							relayer_id.clone(),
+							message.key.nonce,
+							dispatch_message.data,
+						)
+					} else {
+						lane.receive_undispatched_message(relayer_id.clone(), message.key.nonce, message.data)
+					};
+					if is_message_received {
 						valid_messages += 1;
+						if is_message_dispatched {
+							remaining_dispatch_weight -= message_dispatch_weight;
+						} else {
+							undispatched_messages += 1;
+							undispatched_messages_size = undispatched_messages_size.saturating_add(message_size);
+						}
 					}
 				}
 			}
 
 			frame_support::debug::trace!(
-				"Received messages: total={}, valid={}",
+				"Received messages: total={}, valid={}, undispatched={}",
 				total_messages,
 				valid_messages,
+				undispatched_messages,
+			);
+
+			// messages are a part of the proof, so the size of saved messages can't exceed the proof
+			// size, which is used to compute the reserved weight
+			let spent_dispatch_weight = dispatch_weight - remaining_dispatch_weight;
+			let actual_weight = T::WeightInfo::receive_messages_proof(messages_count, proof_size, spent_dispatch_weight)
+				.saturating_add(T::WeightInfo::save_undispatched_messages(
+					undispatched_messages,
+					sp_std::cmp::min(undispatched_messages_size, proof_size),
+				));
+			Ok(Some(actual_weight).into())
+		}
+
+		/// Dispatch received, but not yet dispatched messages of given inbound lane.
+		///
+		/// Messages are dispatched in order, until either all messages are dispatched, or declared
+		/// `dispatch_weight` is not enough to dispatch the next message.
+		#[weight = T::WeightInfo::dispatch_undispatched_messages(*dispatch_weight)]
+		pub fn dispatch_undispatched_messages(
+			origin,
+			lane_id: LaneId,
+			dispatch_weight: Weight,
+		) -> DispatchResult {
+			ensure_operational::<T, I>()?;
+			let _ = ensure_signed(origin)?;
+
+			let mut lane = inbound_lane::<T, I>(lane_id);
+			ensure!(lane.has_undispatched_messages(), Error::<T, I>::NoUndispatchedMessages);
+
+			let (dispatched_messages, actual_dispatch_weight) =
+				lane.dispatch_undispatched_messages::<T::MessageDispatch>(dispatch_weight);
+			if dispatched_messages == 0 {
+				frame_support::debug::trace!(
+					"Rejecting undispatched messages dispatch at lane {:?}: declared weight {} is not enough",
+					lane_id,
+					dispatch_weight,
+				);
+
+				return Err(Error::<T, I>::InvalidMessagesDispatchWeight.into());
+			}
+
+			frame_support::debug::trace!(
+				"Dispatched {} undispatched messages at lane {:?}: weight={}",
+				dispatched_messages,
+				lane_id,
+				actual_dispatch_weight,
 			);
 
 			Ok(())
 		}
 
 		/// Receive messages delivery proof from bridged chain.
-		#[weight = 0] // TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
-		pub fn receive_messages_delivery_proof(origin, proof: MessagesDeliveryProofOf<T, I>) -> DispatchResult {
-			ensure_operational::<T, I>()?;
+		///
+		/// The transaction weight depends on the proof size. Proofs that are larger than
+		/// `MaxMessagesDeliveryProofSize` are rejected before verification and only the base
+		/// weight is charged for them.
+		#[weight = T::WeightInfo::receive_messages_delivery_proof(proof.size_hint())]
+		pub fn receive_messages_delivery_proof(
+			origin,
+			proof: MessagesDeliveryProofOf<T, I>,
+		) -> DispatchResultWithPostInfo {
+			let unverified_proof_rejection_weight = T::WeightInfo::receive_messages_delivery_proof(0);
+
+			ensure_operational::<T, I>()
+				.map_err(|err| cheap_rejection(err, unverified_proof_rejection_weight))?;
 
 			let confirmation_relayer = ensure_signed(origin)?;
+			ensure!(
+				proof.size_hint() <= T::MaxMessagesDeliveryProofSize::get(),
+				cheap_rejection(Error::<T, I>::MessagesDeliveryProofTooLarge, unverified_proof_rejection_weight)
+			);
 			let (lane_id, lane_data) = T::TargetHeaderChain::verify_messages_delivery_proof(proof).map_err(|err| {
 				frame_support::debug::trace!(
 					"Rejecting invalid messages delivery proof: {:?}",
@@ -391,6 +675,12 @@ decl_module! {
 				Error::<T, I>::InvalidMessagesDeliveryProof
 			})?;
 
+			// the proof can't confirm delivery of messages that have never been sent
+			ensure!(
+				lane_data.latest_received_nonce <= Self::outbound_latest_generated_nonce(lane_id),
+				Error::<T, I>::InvalidMessagesDeliveryProof
+			);
+
 			// mark messages as delivered
 			let mut lane = outbound_lane::<T, I>(lane_id);
 			let received_range = lane.confirm_delivery(lane_data.latest_received_nonce);
@@ -424,7 +714,7 @@ decl_module! {
 				lane_id,
 			);
 
-			Ok(())
+			Ok(().into())
 		}
 	}
 }
@@ -435,6 +725,16 @@ impl<T: Trait<I>, I: Instance> Module<T, I> {
 		OutboundMessages::<T, I>::get(MessageKey { lane_id: lane, nonce }).map(|message_data| message_data.payload)
 	}
 
+	/// Get delivery and dispatch fee, paid by the submitter of given outbound message.
+	pub fn outbound_message_fee(lane: LaneId, nonce: MessageNonce) -> Option<T::OutboundMessageFee> {
+		OutboundMessages::<T, I>::get(MessageKey { lane_id: lane, nonce }).map(|message_data| message_data.fee)
+	}
+
+	/// Returns true if messages may be sent and received over given lane.
+	pub fn is_lane_accepted(lane: LaneId) -> bool {
+		!IsWhitelistedLanesOnly::<I>::get() || WhitelistedLanes::<I>::get(lane)
+	}
+
 	/// Get nonce of latest generated message at given outbound lane.
 	pub fn outbound_latest_generated_nonce(lane: LaneId) -> MessageNonce {
 		OutboundLanes::<I>::get(&lane).latest_generated_nonce
@@ -454,6 +754,66 @@ impl<T: Trait<I>, I: Instance> Module<T, I> {
 	pub fn inbound_latest_confirmed_nonce(lane: LaneId) -> MessageNonce {
 		InboundLanes::<T, I>::get(&lane).latest_confirmed_nonce
 	}
+
+	/// Get nonce of the oldest received, but not yet dispatched message at given inbound lane.
+	pub fn inbound_oldest_undispatched_nonce(lane: LaneId) -> Option<MessageNonce> {
+		InboundOldestUndispatchedNonces::<I>::get(&lane)
+	}
+
+	/// Get relayers that have delivered messages over given inbound lane, but haven't been rewarded yet.
+	pub fn inbound_unrewarded_relayers(lane: LaneId) -> Vec<UnrewardedRelayer<T::InboundRelayer>> {
+		InboundLanes::<T, I>::get(&lane)
+			.relayers
+			.into_iter()
+			.map(|(begin, end, relayer)| UnrewardedRelayer { relayer, begin, end })
+			.collect()
+	}
+
+	/// Get weight that `MessageDispatch::dispatch_weight` reports for the inbound message with given
+	/// encoded payload, if it is delivered as the next message of given inbound lane.
+	///
+	/// Returns `None` if payload can't be decoded.
+	pub fn inbound_message_dispatch_weight(
+		lane: LaneId,
+		payload: MessagePayload,
+		fee: T::InboundMessageFee,
+	) -> Option<Weight> {
+		let message: DispatchMessage<T::InboundPayload, T::InboundMessageFee> = Message {
+			key: MessageKey {
+				lane_id: lane,
+				nonce: Self::inbound_latest_received_nonce(lane) + 1,
+			},
+			data: MessageData { payload, fee },
+		}
+		.into();
+		match message.data.payload {
+			Ok(_) => Some(T::MessageDispatch::dispatch_weight(&message)),
+			Err(_) => None,
+		}
+	}
+
+	/// Get state of all lanes that have either outbound, or inbound lane state stored in the runtime storage.
+	pub fn lanes() -> Vec<LaneState> {
+		let is_halted = IsHalted::<I>::get();
+		let mut lanes = BTreeMap::new();
+		let lane_state = |lane: LaneId| LaneState {
+			lane,
+			outbound: Default::default(),
+			inbound_latest_received_nonce: 0,
+			inbound_latest_confirmed_nonce: 0,
+			is_halted,
+		};
+		for (lane, data) in OutboundLanes::<I>::iter() {
+			lanes.entry(lane).or_insert_with(|| lane_state(lane)).outbound = data;
+		}
+		for (lane, data) in InboundLanes::<T, I>::iter() {
+			let state = lanes.entry(lane).or_insert_with(|| lane_state(lane));
+			state.inbound_latest_received_nonce = data.latest_received_nonce;
+			state.inbound_latest_confirmed_nonce = data.latest_confirmed_nonce;
+		}
+
+		lanes.into_iter().map(|(_, state)| state).collect()
+	}
 }
 
 /// Getting storage keys for messages and lanes states. These keys are normally used when building
@@ -481,6 +841,14 @@ pub mod storage_keys {
 		StorageKey(raw_storage_key)
 	}
 
+	/// Storage key of the pallet storage version in the runtime storage.
+	pub fn storage_version_key<I: Instance>() -> StorageKey {
+		StorageKey(
+			<PalletStorageVersion<I> as frame_support::storage::generator::StorageValue<StorageVersion>>::storage_value_final_key()
+				.to_vec(),
+		)
+	}
+
 	/// Storage key of the outbound message lane state in the runtime storage.
 	pub fn outbound_lane_data_key<I: Instance>(lane: &LaneId) -> StorageKey {
 		StorageKey(OutboundLanes::<I>::storage_map_final_key(*lane))
@@ -490,10 +858,184 @@ pub mod storage_keys {
 	pub fn inbound_lane_data_key<T: Trait<I>, I: Instance>(lane: &LaneId) -> StorageKey {
 		StorageKey(InboundLanes::<T, I>::storage_map_final_key(*lane))
 	}
+
+	/// Storage key of the oldest undispatched nonce of the inbound lane in the runtime storage.
+	pub fn inbound_oldest_undispatched_nonce_key<I: Instance>(lane: &LaneId) -> StorageKey {
+		StorageKey(InboundOldestUndispatchedNonces::<I>::storage_map_final_key(*lane))
+	}
+
+	/// Storage key of the received, but not yet dispatched inbound message in the runtime storage.
+	pub fn inbound_undispatched_message_key<T: Trait<I>, I: Instance>(
+		lane: &LaneId,
+		nonce: MessageNonce,
+	) -> StorageKey {
+		let message_key = MessageKey { lane_id: *lane, nonce };
+		StorageKey(InboundUndispatchedMessages::<T, I>::storage_map_final_key(message_key))
+	}
+}
+
+/// Build outbound messages digest for messages in given inclusive range.
+fn outbound_messages_digest<T: Trait<I>, I: Instance>(
+	lane_id: LaneId,
+	begin: MessageNonce,
+	end: MessageNonce,
+) -> OutboundMessagesDigest<T::Hash> {
+	let leaves = (begin..=end)
+		.filter_map(|nonce| {
+			let key = MessageKey { lane_id, nonce };
+			OutboundMessages::<T, I>::get(&key).map(|data| (key, data).encode())
+		})
+		.collect();
+	OutboundMessagesDigest {
+		instance_prefix: I::PREFIX.as_bytes().to_vec(),
+		lane: lane_id,
+		begin,
+		end,
+		messages_root: T::Hashing::ordered_trie_root(leaves),
+	}
+}
+
+/// Migrate pallet storage to the `CURRENT_STORAGE_VERSION`.
+///
+/// Every storage version upgrade is a separate step, so the storage of any previous version may
+/// be migrated.
+fn migrate_storage<T: Trait<I>, I: Instance>() -> Weight {
+	let initial_storage_version = PalletStorageVersion::<I>::get();
+	let mut storage_version = initial_storage_version;
+	let mut weight = T::DbWeight::get().reads(1);
+
+	if storage_version == LEGACY_STORAGE_VERSION {
+		// encoding of all values is the same in legacy and v1 versions => we only need to start
+		// tracking the storage version
+		storage_version = STORAGE_VERSION_V1;
+	}
+
+	if storage_version != initial_storage_version {
+		frame_support::debug::info!(
+			"Message lane pallet storage has been migrated from version {} to version {}",
+			initial_storage_version,
+			storage_version,
+		);
+
+		PalletStorageVersion::<I>::put(storage_version);
+		weight = weight.saturating_add(T::DbWeight::get().writes(1));
+	}
+
+	weight
+}
+
+/// Storage checks that are performed when rehearsing runtime upgrade against the state snapshot.
+#[cfg(feature = "try-runtime")]
+impl<T: Trait<I>, I: Instance> Module<T, I> {
+	/// Check pallet storage before `on_runtime_upgrade` is called.
+	pub fn pre_upgrade() -> Result<(), &'static str> {
+		let storage_version = PalletStorageVersion::<I>::get();
+		ensure!(
+			storage_version <= CURRENT_STORAGE_VERSION,
+			"Message lane pallet storage version is unknown"
+		);
+		ensure_lanes_storage_is_valid::<T, I>(storage_version)
+	}
+
+	/// Check pallet storage after `on_runtime_upgrade` is called.
+	pub fn post_upgrade() -> Result<(), &'static str> {
+		ensure!(
+			PalletStorageVersion::<I>::get() == CURRENT_STORAGE_VERSION,
+			"Message lane pallet storage is not migrated to the current version"
+		);
+		ensure_lanes_storage_is_valid::<T, I>(CURRENT_STORAGE_VERSION)
+	}
+}
+
+/// Ensure that all lanes and messages are decodable using given storage version and that nonces
+/// of all lanes are consistent.
+#[cfg(feature = "try-runtime")]
+fn ensure_lanes_storage_is_valid<T: Trait<I>, I: Instance>(
+	storage_version: StorageVersion,
+) -> Result<(), &'static str> {
+	use frame_support::storage::StoragePrefixedMap;
+
+	let inbound_lanes = decode_all_values::<InboundLaneData<T::InboundRelayer>>(
+		&InboundLanes::<T, I>::final_prefix(),
+		storage_version,
+	)?;
+	for inbound_lane in inbound_lanes {
+		ensure!(
+			inbound_lane.latest_confirmed_nonce <= inbound_lane.latest_received_nonce,
+			"Inbound lane has confirmed messages that are not received"
+		);
+	}
+
+	let outbound_lanes = decode_all_values::<OutboundLaneData>(&OutboundLanes::<I>::final_prefix(), storage_version)?;
+	for outbound_lane in outbound_lanes {
+		ensure!(
+			outbound_lane.latest_received_nonce <= outbound_lane.latest_generated_nonce,
+			"Outbound lane has received messages that are not generated"
+		);
+		ensure!(
+			outbound_lane.oldest_unpruned_nonce <= outbound_lane.latest_received_nonce + 1,
+			"Outbound lane has pruned messages that are not received"
+		);
+	}
+
+	decode_all_values::<MessageData<T::OutboundMessageFee>>(
+		&OutboundMessages::<T, I>::final_prefix(),
+		storage_version,
+	)?;
+	decode_all_values::<MessageData<T::InboundMessageFee>>(
+		&InboundUndispatchedMessages::<T, I>::final_prefix(),
+		storage_version,
+	)?;
+
+	Ok(())
+}
+
+/// Decode all values of the storage map with given prefix, using given storage version.
+#[cfg(feature = "try-runtime")]
+fn decode_all_values<V: bp_message_lane::DecodeVersioned>(
+	prefix: &[u8],
+	storage_version: StorageVersion,
+) -> Result<Vec<V>, &'static str> {
+	let mut values = Vec::new();
+	let mut previous_key = prefix.to_vec();
+	while let Some(key) = sp_io::storage::next_key(&previous_key).filter(|key| key.starts_with(prefix)) {
+		let encoded_value = frame_support::storage::unhashed::get_raw(&key).unwrap_or_default();
+		let value = V::decode_versioned(storage_version, &encoded_value)
+			.map_err(|_| "Failed to decode message lane pallet storage value")?;
+		values.push(value);
+		previous_key = key;
+	}
+
+	Ok(values)
+}
+
+impl<T: Trait<I>, I: Instance> From<Error<T, I>> for bp_runtime::BridgeError {
+	fn from(error: Error<T, I>) -> Self {
+		match error {
+			Error::Halted => bp_runtime::BridgeError::Halted,
+			Error::LaneIsNotWhitelisted => bp_runtime::BridgeError::LaneNotAllowed,
+			Error::MessageRejectedByChainVerifier | Error::MessageRejectedByLaneVerifier => {
+				bp_runtime::BridgeError::MessageRejected
+			}
+			Error::FailedToWithdrawMessageFee => bp_runtime::BridgeError::FeePaymentFailed,
+			Error::InvalidMessagesProof | Error::InvalidMessagesDeliveryProof => bp_runtime::BridgeError::InvalidProof,
+			Error::InvalidMessagesDispatchWeight | Error::InvalidMessagesCount => {
+				bp_runtime::BridgeError::WeightMismatch
+			}
+			Error::MessagesProofTooLarge | Error::MessagesDeliveryProofTooLarge => {
+				bp_runtime::BridgeError::ProofTooLarge
+			}
+			Error::NoUndispatchedMessages | Error::__Ignore(_, _) => bp_runtime::BridgeError::Other,
+		}
+	}
 }
 
-/// Ensure that the origin is either root, or `ModuleOwner`.
+/// Ensure that the origin is either root, `AdminOrigin`, or `ModuleOwner`.
 fn ensure_owner_or_root<T: Trait<I>, I: Instance>(origin: T::Origin) -> Result<(), BadOrigin> {
+	let origin = match T::AdminOrigin::try_origin(origin) {
+		Ok(_) => return Ok(()),
+		Err(origin) => origin,
+	};
 	match origin.into() {
 		Ok(RawOrigin::Root) => Ok(()),
 		Ok(RawOrigin::Signed(ref signer)) if Some(signer) == Module::<T, I>::module_owner().as_ref() => Ok(()),
@@ -510,6 +1052,24 @@ fn ensure_operational<T: Trait<I>, I: Instance>() -> Result<(), Error<T, I>> {
 	}
 }
 
+/// Ensure that messages may be sent and received over given lane.
+fn ensure_lane_whitelisted<T: Trait<I>, I: Instance>(lane_id: LaneId) -> Result<(), Error<T, I>> {
+	if Module::<T, I>::is_lane_accepted(lane_id) {
+		Ok(())
+	} else {
+		Err(Error::<T, I>::LaneIsNotWhitelisted)
+	}
+}
+
+/// Error of the transaction that has been rejected early. Only `actual_weight` is charged,
+/// instead of the declared transaction weight.
+fn cheap_rejection<T: Trait<I>, I: Instance>(error: Error<T, I>, actual_weight: Weight) -> DispatchErrorWithPostInfo {
+	DispatchErrorWithPostInfo {
+		post_info: Some(actual_weight).into(),
+		error: error.into(),
+	}
+}
+
 /// Creates new inbound lane object, backed by runtime storage.
 fn inbound_lane<T: Trait<I>, I: Instance>(lane_id: LaneId) -> InboundLane<RuntimeInboundLaneStorage<T, I>> {
 	InboundLane::new(RuntimeInboundLaneStorage {
@@ -567,6 +1127,41 @@ impl<T: Trait<I>, I: Instance> InboundLaneStorage for RuntimeInboundLaneStorage<
 		) = Some(data.clone());
 		InboundLanes::<T, I>::insert(&self.lane_id, data)
 	}
+
+	fn oldest_undispatched_nonce(&self) -> Option<MessageNonce> {
+		InboundOldestUndispatchedNonces::<I>::get(&self.lane_id)
+	}
+
+	fn set_oldest_undispatched_nonce(&mut self, nonce: Option<MessageNonce>) {
+		match nonce {
+			Some(nonce) => InboundOldestUndispatchedNonces::<I>::insert(&self.lane_id, nonce),
+			None => InboundOldestUndispatchedNonces::<I>::remove(&self.lane_id),
+		}
+	}
+
+	fn undispatched_message(&self, nonce: &MessageNonce) -> Option<MessageData<T::InboundMessageFee>> {
+		InboundUndispatchedMessages::<T, I>::get(MessageKey {
+			lane_id: self.lane_id,
+			nonce: *nonce,
+		})
+	}
+
+	fn save_undispatched_message(&mut self, nonce: MessageNonce, message_data: MessageData<T::InboundMessageFee>) {
+		InboundUndispatchedMessages::<T, I>::insert(
+			MessageKey {
+				lane_id: self.lane_id,
+				nonce,
+			},
+			message_data,
+		);
+	}
+
+	fn remove_undispatched_message(&mut self, nonce: &MessageNonce) {
+		InboundUndispatchedMessages::<T, I>::remove(MessageKey {
+			lane_id: self.lane_id,
+			nonce: *nonce,
+		});
+	}
 }
 
 /// Runtime outbound lane storage.
@@ -616,37 +1211,22 @@ impl<T: Trait<I>, I: Instance> OutboundLaneStorage for RuntimeOutboundLaneStorag
 	}
 }
 
-/// Verify messages proof and return proved messages with decoded payload.
-fn verify_and_decode_messages_proof<Chain: SourceHeaderChain<Fee>, Fee, DispatchPayload: Decode>(
-	proof: Chain::MessagesProof,
-) -> Result<ProvedMessages<DispatchMessage<DispatchPayload, Fee>>, Chain::Error> {
-	Chain::verify_messages_proof(proof).map(|messages_by_lane| {
-		messages_by_lane
-			.into_iter()
-			.map(|(lane, lane_data)| {
-				(
-					lane,
-					ProvedLaneMessages {
-						lane_state: lane_data.lane_state,
-						messages: lane_data.messages.into_iter().map(Into::into).collect(),
-					},
-				)
-			})
-			.collect()
-	})
-}
-
 #[cfg(test)]
 mod tests {
 	use super::*;
 	use crate::mock::{
-		message, run_test, Origin, TestEvent, TestMessageDeliveryAndDispatchPayment, TestMessagesProof, TestRuntime,
-		PAYLOAD_REJECTED_BY_TARGET_CHAIN, REGULAR_PAYLOAD, TEST_LANE_ID, TEST_RELAYER_A, TEST_RELAYER_B,
+		message, message_data, run_test, Origin, TestEvent, TestMessageDeliveryAndDispatchPayment, TestMessageDispatch,
+		TestMessageFee, TestMessagesDeliveryProof, TestMessagesProof, TestRuntime, TestSourceHeaderChain,
+		TestWeightInfo, PAYLOAD_REJECTED_BY_TARGET_CHAIN, REGULAR_PAYLOAD, TEST_ADMIN, TEST_LANE_ID, TEST_RELAYER_A,
+		TEST_RELAYER_B,
 	};
-	use frame_support::{assert_noop, assert_ok};
+	use bp_message_lane::target_chain::ProvedLaneMessages;
+	use frame_support::{assert_noop, assert_ok, weights::GetDispatchInfo};
 	use frame_system::{EventRecord, Module as System, Phase};
 	use hex_literal::hex;
 	use sp_runtime::DispatchError;
+	use sp_trie::StorageProof;
+	use std::collections::VecDeque;
 
 	fn send_regular_message() {
 		System::<TestRuntime>::set_block_number(1);
@@ -679,13 +1259,13 @@ mod tests {
 
 		assert_ok!(Module::<TestRuntime>::receive_messages_delivery_proof(
 			Origin::signed(1),
-			Ok((
+			TestMessagesDeliveryProof(Ok((
 				TEST_LANE_ID,
 				InboundLaneData {
 					latest_received_nonce: 1,
 					..Default::default()
 				}
-			)),
+			))),
 		));
 
 		assert_eq!(
@@ -698,6 +1278,21 @@ mod tests {
 		);
 	}
 
+	fn messages_proof_rejection(
+		error: Error<TestRuntime, DefaultInstance>,
+		messages_count: u32,
+		proof_size: u32,
+	) -> DispatchErrorWithPostInfo {
+		cheap_rejection(
+			error,
+			TestWeightInfo::receive_messages_proof(messages_count, proof_size, 0),
+		)
+	}
+
+	fn messages_delivery_proof_rejection(error: Error<TestRuntime, DefaultInstance>) -> DispatchErrorWithPostInfo {
+		cheap_rejection(error, TestWeightInfo::receive_messages_delivery_proof(0))
+	}
+
 	#[test]
 	fn pallet_owner_may_change_owner() {
 		run_test(|| {
@@ -756,6 +1351,15 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn pallet_may_be_halted_by_admin() {
+		run_test(|| {
+			assert_ok!(Module::<TestRuntime>::halt_operations(Origin::signed(TEST_ADMIN)));
+			assert_ok!(Module::<TestRuntime>::set_owner(Origin::signed(TEST_ADMIN), Some(2)));
+			assert_ok!(Module::<TestRuntime>::resume_operations(Origin::signed(TEST_ADMIN)));
+		});
+	}
+
 	#[test]
 	fn pallet_rejects_transactions_if_halted() {
 		run_test(|| {
@@ -779,62 +1383,189 @@ mod tests {
 					Origin::signed(1),
 					TEST_RELAYER_A,
 					Ok(vec![message(2, REGULAR_PAYLOAD)]).into(),
+					1,
 					REGULAR_PAYLOAD.1,
 				),
-				Error::<TestRuntime, DefaultInstance>::Halted,
+				messages_proof_rejection(Error::<TestRuntime, DefaultInstance>::Halted, 0, 0),
 			);
 
 			assert_noop!(
 				Module::<TestRuntime>::receive_messages_delivery_proof(
 					Origin::signed(1),
-					Ok((
+					TestMessagesDeliveryProof(Ok((
 						TEST_LANE_ID,
 						InboundLaneData {
 							latest_received_nonce: 1,
 							..Default::default()
 						}
-					)),
+					))),
 				),
+				messages_delivery_proof_rejection(Error::<TestRuntime, DefaultInstance>::Halted),
+			);
+
+			assert_noop!(
+				Module::<TestRuntime>::dispatch_undispatched_messages(Origin::signed(1), TEST_LANE_ID, 0),
 				Error::<TestRuntime, DefaultInstance>::Halted,
 			);
 		});
 	}
 
 	#[test]
-	fn send_message_works() {
+	fn whitelisted_lanes_only_mode_is_managed_by_admin() {
 		run_test(|| {
-			send_regular_message();
+			assert_noop!(
+				Module::<TestRuntime>::set_whitelisted_lanes_only(Origin::signed(1), true),
+				DispatchError::BadOrigin,
+			);
+			assert_noop!(
+				Module::<TestRuntime>::set_lane_whitelisted(Origin::signed(1), TEST_LANE_ID, true),
+				DispatchError::BadOrigin,
+			);
+
+			assert_ok!(Module::<TestRuntime>::set_whitelisted_lanes_only(
+				Origin::signed(TEST_ADMIN),
+				true
+			));
+			assert!(!Module::<TestRuntime>::is_lane_accepted(TEST_LANE_ID));
+			assert_ok!(Module::<TestRuntime>::set_lane_whitelisted(
+				Origin::signed(TEST_ADMIN),
+				TEST_LANE_ID,
+				true
+			));
+			assert!(Module::<TestRuntime>::is_lane_accepted(TEST_LANE_ID));
+			assert_ok!(Module::<TestRuntime>::set_lane_whitelisted(
+				Origin::root(),
+				TEST_LANE_ID,
+				false
+			));
+			assert!(!Module::<TestRuntime>::is_lane_accepted(TEST_LANE_ID));
+			assert_ok!(Module::<TestRuntime>::set_whitelisted_lanes_only(Origin::root(), false));
+			assert!(Module::<TestRuntime>::is_lane_accepted(TEST_LANE_ID));
 		});
 	}
 
 	#[test]
-	fn chain_verifier_rejects_invalid_message_in_send_message() {
+	fn pallet_rejects_messages_of_not_whitelisted_lanes() {
 		run_test(|| {
-			// messages with this payload are rejected by target chain verifier
+			IsWhitelistedLanesOnly::<DefaultInstance>::put(true);
+
 			assert_noop!(
 				Module::<TestRuntime>::send_message(
 					Origin::signed(1),
 					TEST_LANE_ID,
-					PAYLOAD_REJECTED_BY_TARGET_CHAIN,
-					PAYLOAD_REJECTED_BY_TARGET_CHAIN.1
+					REGULAR_PAYLOAD,
+					REGULAR_PAYLOAD.1,
 				),
-				Error::<TestRuntime, DefaultInstance>::MessageRejectedByChainVerifier,
+				Error::<TestRuntime, DefaultInstance>::LaneIsNotWhitelisted,
 			);
-		});
-	}
 
-	#[test]
-	fn lane_verifier_rejects_invalid_message_in_send_message() {
-		run_test(|| {
-			// messages with zero fee are rejected by lane verifier
+			let proof: TestMessagesProof = Ok(vec![message(1, REGULAR_PAYLOAD)]).into();
+			let proof_size = proof.size_hint();
 			assert_noop!(
-				Module::<TestRuntime>::send_message(Origin::signed(1), TEST_LANE_ID, REGULAR_PAYLOAD, 0),
-				Error::<TestRuntime, DefaultInstance>::MessageRejectedByLaneVerifier,
-			);
-		});
-	}
-
-	#[test]
+				Module::<TestRuntime>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					proof,
+					1,
+					REGULAR_PAYLOAD.1,
+				),
+				messages_proof_rejection(
+					Error::<TestRuntime, DefaultInstance>::LaneIsNotWhitelisted,
+					1,
+					proof_size
+				),
+			);
+			assert_eq!(TestMessageDispatch::dispatches(), 0);
+
+			// messages of whitelisted lanes are accepted
+			WhitelistedLanes::<DefaultInstance>::insert(TEST_LANE_ID, true);
+			send_regular_message();
+			assert_ok!(Module::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).latest_received_nonce, 1);
+		});
+	}
+
+	#[test]
+	fn send_message_works() {
+		run_test(|| {
+			send_regular_message();
+		});
+	}
+
+	#[test]
+	fn outbound_messages_digest_is_deposited_on_finalize() {
+		run_test(|| {
+			use frame_support::traits::OnFinalize;
+
+			// no messages => no digest
+			Module::<TestRuntime>::on_finalize(0);
+			assert!(System::<TestRuntime>::digest().logs.is_empty());
+
+			send_regular_message();
+			assert_ok!(Module::<TestRuntime>::send_message(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				REGULAR_PAYLOAD.1,
+			));
+			assert_eq!(
+				OutboundMessagesInBlock::<DefaultInstance>::get(TEST_LANE_ID),
+				Some((1, 2))
+			);
+
+			let expected_digest = outbound_messages_digest::<TestRuntime, DefaultInstance>(TEST_LANE_ID, 1, 2);
+			Module::<TestRuntime>::on_finalize(1);
+
+			let logs = System::<TestRuntime>::digest().logs;
+			assert_eq!(logs, vec![DigestItem::Other(expected_digest.to_digest_item_data())]);
+			let digest = logs[0]
+				.as_other()
+				.and_then(|data| OutboundMessagesDigest::<sp_core::H256>::from_digest_item_data(data))
+				.unwrap();
+			assert_eq!(digest.instance_prefix, b"MessageLane".to_vec());
+			assert_eq!((digest.lane, digest.begin, digest.end), (TEST_LANE_ID, 1, 2));
+			assert_ne!(
+				digest.messages_root,
+				outbound_messages_digest::<TestRuntime, DefaultInstance>(TEST_LANE_ID, 1, 1).messages_root,
+			);
+			assert_eq!(OutboundMessagesInBlock::<DefaultInstance>::get(TEST_LANE_ID), None);
+		});
+	}
+
+	#[test]
+	fn chain_verifier_rejects_invalid_message_in_send_message() {
+		run_test(|| {
+			// messages with this payload are rejected by target chain verifier
+			assert_noop!(
+				Module::<TestRuntime>::send_message(
+					Origin::signed(1),
+					TEST_LANE_ID,
+					PAYLOAD_REJECTED_BY_TARGET_CHAIN,
+					PAYLOAD_REJECTED_BY_TARGET_CHAIN.1
+				),
+				Error::<TestRuntime, DefaultInstance>::MessageRejectedByChainVerifier,
+			);
+		});
+	}
+
+	#[test]
+	fn lane_verifier_rejects_invalid_message_in_send_message() {
+		run_test(|| {
+			// messages with zero fee are rejected by lane verifier
+			assert_noop!(
+				Module::<TestRuntime>::send_message(Origin::signed(1), TEST_LANE_ID, REGULAR_PAYLOAD, 0),
+				Error::<TestRuntime, DefaultInstance>::MessageRejectedByLaneVerifier,
+			);
+		});
+	}
+
+	#[test]
 	fn message_send_fails_if_submitter_cant_pay_message_fee() {
 		run_test(|| {
 			TestMessageDeliveryAndDispatchPayment::reject_payments();
@@ -857,6 +1588,7 @@ mod tests {
 				Origin::signed(1),
 				TEST_RELAYER_A,
 				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
 				REGULAR_PAYLOAD.1,
 			));
 
@@ -890,6 +1622,7 @@ mod tests {
 				Origin::signed(1),
 				TEST_RELAYER_A,
 				message_proof,
+				1,
 				REGULAR_PAYLOAD.1,
 			));
 
@@ -907,13 +1640,113 @@ mod tests {
 	}
 
 	#[test]
-	fn receive_messages_proof_rejects_invalid_dispatch_weight() {
+	fn receive_messages_proof_does_not_dispatch_messages_above_declared_dispatch_weight() {
+		run_test(|| {
+			assert_ok!(Module::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![
+					message(1, REGULAR_PAYLOAD),
+					message(2, REGULAR_PAYLOAD),
+					message(3, REGULAR_PAYLOAD),
+				])
+				.into(),
+				3,
+				REGULAR_PAYLOAD.1 * 2 - 1,
+			));
+
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).latest_received_nonce, 3);
+			assert!(TestMessageDispatch::is_dispatched(1));
+			assert!(!TestMessageDispatch::is_dispatched(2));
+			assert!(!TestMessageDispatch::is_dispatched(3));
+			assert_eq!(
+				Module::<TestRuntime>::inbound_oldest_undispatched_nonce(TEST_LANE_ID),
+				Some(2)
+			);
+			assert_eq!(
+				InboundUndispatchedMessages::<TestRuntime>::get(MessageKey {
+					lane_id: TEST_LANE_ID,
+					nonce: 3
+				}),
+				Some(message_data(REGULAR_PAYLOAD)),
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_does_not_dispatch_messages_while_lane_has_undispatched_messages() {
+		run_test(|| {
+			assert_ok!(Module::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1 - 1,
+			));
+			assert_ok!(Module::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(2, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+
+			assert!(!TestMessageDispatch::is_dispatched(1));
+			assert!(!TestMessageDispatch::is_dispatched(2));
+			assert_eq!(
+				Module::<TestRuntime>::inbound_oldest_undispatched_nonce(TEST_LANE_ID),
+				Some(1)
+			);
+		});
+	}
+
+	#[test]
+	fn dispatch_undispatched_messages_works() {
+		run_test(|| {
+			assert_ok!(Module::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD), message(2, REGULAR_PAYLOAD)]).into(),
+				2,
+				0,
+			));
+
+			assert_ok!(Module::<TestRuntime>::dispatch_undispatched_messages(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD.1,
+			));
+			assert!(TestMessageDispatch::is_dispatched(1));
+			assert!(!TestMessageDispatch::is_dispatched(2));
+
+			assert_ok!(Module::<TestRuntime>::dispatch_undispatched_messages(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD.1,
+			));
+			assert!(TestMessageDispatch::is_dispatched(2));
+			assert_eq!(
+				Module::<TestRuntime>::inbound_oldest_undispatched_nonce(TEST_LANE_ID),
+				None
+			);
+		});
+	}
+
+	#[test]
+	fn dispatch_undispatched_messages_rejects_invalid_dispatch_weight() {
 		run_test(|| {
+			assert_ok!(Module::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				0,
+			));
+
 			assert_noop!(
-				Module::<TestRuntime>::receive_messages_proof(
+				Module::<TestRuntime>::dispatch_undispatched_messages(
 					Origin::signed(1),
-					TEST_RELAYER_A,
-					Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+					TEST_LANE_ID,
 					REGULAR_PAYLOAD.1 - 1,
 				),
 				Error::<TestRuntime, DefaultInstance>::InvalidMessagesDispatchWeight,
@@ -921,17 +1754,284 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn dispatch_undispatched_messages_fails_if_there_are_no_undispatched_messages() {
+		run_test(|| {
+			assert_noop!(
+				Module::<TestRuntime>::dispatch_undispatched_messages(Origin::signed(1), TEST_LANE_ID, 0),
+				Error::<TestRuntime, DefaultInstance>::NoUndispatchedMessages,
+			);
+		});
+	}
+
 	#[test]
 	fn receive_messages_proof_rejects_invalid_proof() {
 		run_test(|| {
+			let proof: TestMessagesProof = Err(()).into();
+			let proof_size = proof.size_hint();
 			assert_noop!(
 				Module::<TestRuntime, DefaultInstance>::receive_messages_proof(
 					Origin::signed(1),
 					TEST_RELAYER_A,
-					Err(()).into(),
+					proof,
+					0,
+					0,
+				),
+				messages_proof_rejection(
+					Error::<TestRuntime, DefaultInstance>::InvalidMessagesProof,
 					0,
+					proof_size
+				),
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_rejects_proof_with_more_than_declared_messages() {
+		run_test(|| {
+			let proof: TestMessagesProof = Ok(vec![message(1, REGULAR_PAYLOAD), message(2, REGULAR_PAYLOAD)]).into();
+			let proof_size = proof.size_hint();
+			assert_noop!(
+				Module::<TestRuntime, DefaultInstance>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					proof,
+					1,
+					REGULAR_PAYLOAD.1 * 2,
+				),
+				messages_proof_rejection(
+					Error::<TestRuntime, DefaultInstance>::InvalidMessagesCount,
+					1,
+					proof_size
+				),
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_rejects_proof_with_nonces_gap() {
+		run_test(|| {
+			System::<TestRuntime>::set_block_number(1);
+			let proof: TestMessagesProof = Ok(vec![message(1, REGULAR_PAYLOAD), message(3, REGULAR_PAYLOAD)]).into();
+			let proof_size = proof.size_hint();
+			assert_noop!(
+				Module::<TestRuntime, DefaultInstance>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					proof,
+					2,
+					REGULAR_PAYLOAD.1 * 2,
+				),
+				messages_proof_rejection(
+					Error::<TestRuntime, DefaultInstance>::InvalidMessagesProof,
+					2,
+					proof_size
 				),
-				Error::<TestRuntime, DefaultInstance>::InvalidMessagesProof,
+			);
+			assert_eq!(System::<TestRuntime>::events(), vec![]);
+			assert_eq!(TestMessageDispatch::dispatches(), 0);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_rejects_message_of_wrong_lane() {
+		run_test(|| {
+			System::<TestRuntime>::set_block_number(1);
+			let mut wrong_lane_message = message(1, REGULAR_PAYLOAD);
+			wrong_lane_message.key.lane_id = bp_test_utils::test_lane_id(2);
+			let proof = TestMessagesProof::new(Ok(vec![(
+				TEST_LANE_ID,
+				ProvedLaneMessages {
+					lane_state: None,
+					messages: vec![wrong_lane_message],
+				},
+			)]));
+			let proof_size = proof.size_hint();
+
+			assert_noop!(
+				Module::<TestRuntime, DefaultInstance>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					proof,
+					1,
+					REGULAR_PAYLOAD.1,
+				),
+				messages_proof_rejection(
+					Error::<TestRuntime, DefaultInstance>::InvalidMessagesProof,
+					1,
+					proof_size
+				),
+			);
+			assert_eq!(System::<TestRuntime>::events(), vec![]);
+			assert_eq!(TestMessageDispatch::dispatches(), 0);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_rejects_tampered_message_payload() {
+		run_test(|| {
+			System::<TestRuntime>::set_block_number(1);
+			let mut proof: TestMessagesProof = Ok(vec![message(1, REGULAR_PAYLOAD)]).into();
+			if let Ok(ref mut messages_by_lane) = proof.result {
+				messages_by_lane[0].1.messages[0].data.payload = (42u64, REGULAR_PAYLOAD.1).encode();
+			}
+			let proof_size = proof.size_hint();
+
+			assert_noop!(
+				Module::<TestRuntime, DefaultInstance>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					proof,
+					1,
+					REGULAR_PAYLOAD.1,
+				),
+				messages_proof_rejection(
+					Error::<TestRuntime, DefaultInstance>::InvalidMessagesProof,
+					1,
+					proof_size
+				),
+			);
+			assert_eq!(System::<TestRuntime>::events(), vec![]);
+			assert_eq!(TestMessageDispatch::dispatches(), 0);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_rejects_proof_at_stale_header() {
+		run_test(|| {
+			System::<TestRuntime>::set_block_number(1);
+			TestSourceHeaderChain::prune_headers_before(10);
+			let proof = TestMessagesProof::from(Ok(vec![message(1, REGULAR_PAYLOAD)])).at_header(9);
+			let proof_size = proof.size_hint();
+
+			assert_noop!(
+				Module::<TestRuntime, DefaultInstance>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					proof,
+					1,
+					REGULAR_PAYLOAD.1,
+				),
+				messages_proof_rejection(
+					Error::<TestRuntime, DefaultInstance>::InvalidMessagesProof,
+					1,
+					proof_size
+				),
+			);
+			assert_eq!(System::<TestRuntime>::events(), vec![]);
+
+			// the same proof at the non-pruned header is accepted
+			assert_ok!(Module::<TestRuntime, DefaultInstance>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				TestMessagesProof::from(Ok(vec![message(1, REGULAR_PAYLOAD)])).at_header(10),
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+			assert!(TestMessageDispatch::is_dispatched(1));
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_refunds_dispatch_weight_of_already_received_messages() {
+		run_test(|| {
+			let proof: TestMessagesProof = Ok(vec![message(1, REGULAR_PAYLOAD), message(2, REGULAR_PAYLOAD)]).into();
+			let proof_size = proof.size_hint();
+			let receive_proof = || {
+				Module::<TestRuntime, DefaultInstance>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					proof.clone(),
+					2,
+					REGULAR_PAYLOAD.1 * 2,
+				)
+			};
+
+			// first delivery is paying for dispatch of both messages
+			assert_eq!(
+				receive_proof(),
+				Ok(Some(TestWeightInfo::receive_messages_proof(
+					2,
+					proof_size,
+					REGULAR_PAYLOAD.1 * 2
+				))
+				.into()),
+			);
+
+			// repeated delivery of the same messages is cheap
+			let storage_root = sp_io::storage::root();
+			assert_eq!(
+				receive_proof(),
+				Ok(Some(TestWeightInfo::receive_messages_proof(2, proof_size, 0)).into()),
+			);
+			assert_eq!(sp_io::storage::root(), storage_root);
+			assert_eq!(TestMessageDispatch::dispatches(), 2);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_charges_for_saved_undispatched_messages() {
+		run_test(|| {
+			let proof: TestMessagesProof = Ok(vec![message(1, REGULAR_PAYLOAD), message(2, REGULAR_PAYLOAD)]).into();
+			let proof_size = proof.size_hint();
+			let message_size = message_data(REGULAR_PAYLOAD).payload.len() as u32;
+
+			// saving all messages is reserved upfront
+			let call = Call::<TestRuntime>::receive_messages_proof(TEST_RELAYER_A, proof.clone(), 2, REGULAR_PAYLOAD.1);
+			assert_eq!(
+				call.get_dispatch_info().weight,
+				TestWeightInfo::receive_messages_proof(2, proof_size, REGULAR_PAYLOAD.1)
+					+ TestWeightInfo::save_undispatched_messages(2, proof_size),
+			);
+
+			// the first message is dispatched and the second is saved => relayer pays for saving it
+			assert_eq!(
+				Module::<TestRuntime>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					proof,
+					2,
+					REGULAR_PAYLOAD.1,
+				),
+				Ok(Some(
+					TestWeightInfo::receive_messages_proof(2, proof_size, REGULAR_PAYLOAD.1)
+						+ TestWeightInfo::save_undispatched_messages(1, message_size)
+				)
+				.into()),
+			);
+			assert!(TestMessageDispatch::is_dispatched(1));
+			assert!(!TestMessageDispatch::is_dispatched(2));
+
+			// declaring zero dispatch weight doesn't make saving messages free
+			let proof: TestMessagesProof = Ok(vec![message(3, REGULAR_PAYLOAD)]).into();
+			let proof_size = proof.size_hint();
+			assert_eq!(
+				Module::<TestRuntime>::receive_messages_proof(Origin::signed(1), TEST_RELAYER_A, proof, 1, 0),
+				Ok(Some(
+					TestWeightInfo::receive_messages_proof(1, proof_size, 0)
+						+ TestWeightInfo::save_undispatched_messages(1, message_size)
+				)
+				.into()),
+			);
+			assert!(TestWeightInfo::save_undispatched_messages(1, message_size) > 0);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_rejects_too_large_proof() {
+		run_test(|| {
+			let proof: TestMessagesProof = Ok(vec![message(1, REGULAR_PAYLOAD); 64]).into();
+			assert!(proof.size_hint() > crate::mock::MaxMessagesProofSize::get());
+
+			assert_noop!(
+				Module::<TestRuntime, DefaultInstance>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					proof,
+					64,
+					0,
+				),
+				messages_proof_rejection(Error::<TestRuntime, DefaultInstance>::MessagesProofTooLarge, 0, 0),
 			);
 		});
 	}
@@ -968,14 +2068,14 @@ mod tests {
 			// this reports delivery of message 1 => reward is paid to TEST_RELAYER_A
 			assert_ok!(Module::<TestRuntime>::receive_messages_delivery_proof(
 				Origin::signed(1),
-				Ok((
+				TestMessagesDeliveryProof(Ok((
 					TEST_LANE_ID,
 					InboundLaneData {
 						relayers: vec![(1, 1, TEST_RELAYER_A)].into_iter().collect(),
 						latest_received_nonce: 1,
 						..Default::default()
 					}
-				)),
+				))),
 			));
 			assert!(TestMessageDeliveryAndDispatchPayment::is_reward_paid(
 				TEST_RELAYER_A,
@@ -989,7 +2089,7 @@ mod tests {
 			// this reports delivery of both message 1 and message 2 => reward is paid only to TEST_RELAYER_B
 			assert_ok!(Module::<TestRuntime>::receive_messages_delivery_proof(
 				Origin::signed(1),
-				Ok((
+				TestMessagesDeliveryProof(Ok((
 					TEST_LANE_ID,
 					InboundLaneData {
 						relayers: vec![(1, 1, TEST_RELAYER_A), (2, 2, TEST_RELAYER_B)]
@@ -998,7 +2098,7 @@ mod tests {
 						latest_received_nonce: 2,
 						..Default::default()
 					}
-				)),
+				))),
 			));
 			assert!(!TestMessageDeliveryAndDispatchPayment::is_reward_paid(
 				TEST_RELAYER_A,
@@ -1011,16 +2111,184 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn duplicate_messages_delivery_by_another_relayer_is_noop() {
+		run_test(|| {
+			let proof =
+				|| -> TestMessagesProof { Ok(vec![message(1, REGULAR_PAYLOAD), message(2, REGULAR_PAYLOAD)]).into() };
+			assert_ok!(Module::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				proof(),
+				2,
+				REGULAR_PAYLOAD.1 * 2,
+			));
+			assert_eq!(TestMessageDispatch::dispatches(), 2);
+
+			// when both relayers are racing, the transaction of the slower relayer still succeeds,
+			// but it doesn't change the storage and doesn't dispatch messages again
+			let storage_root = sp_io::storage::root();
+			assert_ok!(Module::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_B,
+				proof(),
+				2,
+				REGULAR_PAYLOAD.1 * 2,
+			));
+			assert_eq!(sp_io::storage::root(), storage_root);
+			assert_eq!(TestMessageDispatch::dispatches(), 2);
+			assert_eq!(
+				InboundLanes::<TestRuntime>::get(TEST_LANE_ID).relayers,
+				vec![(1, 2, TEST_RELAYER_A)].into_iter().collect::<VecDeque<_>>(),
+			);
+		});
+	}
+
+	#[test]
+	fn overlapping_messages_deliveries_are_rewarded_to_first_relayers() {
+		run_test(|| {
+			for fee in &[1000, 2000, 4000] {
+				assert_ok!(Module::<TestRuntime>::send_message(
+					Origin::signed(1),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
+					*fee,
+				));
+			}
+
+			// relayer A delivers messages 1 and 2, relayer B delivers messages 1, 2 and 3. Only
+			// message 3 is received from relayer B
+			assert_ok!(Module::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD), message(2, REGULAR_PAYLOAD)]).into(),
+				2,
+				REGULAR_PAYLOAD.1 * 2,
+			));
+			assert_ok!(Module::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_B,
+				Ok(vec![
+					message(1, REGULAR_PAYLOAD),
+					message(2, REGULAR_PAYLOAD),
+					message(3, REGULAR_PAYLOAD),
+				])
+				.into(),
+				3,
+				REGULAR_PAYLOAD.1 * 3,
+			));
+			assert_eq!(TestMessageDispatch::dispatches(), 3);
+
+			let inbound_lane_data = InboundLanes::<TestRuntime>::get(TEST_LANE_ID);
+			assert_eq!(
+				inbound_lane_data.relayers,
+				vec![(1, 2, TEST_RELAYER_A), (3, 3, TEST_RELAYER_B)]
+					.into_iter()
+					.collect::<VecDeque<_>>(),
+			);
+
+			// every relayer is rewarded only for messages it has delivered, even if the delivery
+			// is confirmed twice
+			for _ in 0..2 {
+				assert_ok!(Module::<TestRuntime>::receive_messages_delivery_proof(
+					Origin::signed(1),
+					TestMessagesDeliveryProof(Ok((TEST_LANE_ID, inbound_lane_data.clone()))),
+				));
+			}
+			assert_eq!(
+				TestMessageDeliveryAndDispatchPayment::relayer_rewards(TEST_RELAYER_A),
+				3000
+			);
+			assert_eq!(
+				TestMessageDeliveryAndDispatchPayment::relayer_rewards(TEST_RELAYER_B),
+				4000
+			);
+		});
+	}
+
 	#[test]
 	fn receive_messages_delivery_proof_rejects_invalid_proof() {
 		run_test(|| {
 			assert_noop!(
-				Module::<TestRuntime>::receive_messages_delivery_proof(Origin::signed(1), Err(()),),
+				Module::<TestRuntime>::receive_messages_delivery_proof(
+					Origin::signed(1),
+					TestMessagesDeliveryProof(Err(())),
+				),
 				Error::<TestRuntime, DefaultInstance>::InvalidMessagesDeliveryProof,
 			);
 		});
 	}
 
+	#[test]
+	fn receive_messages_delivery_proof_rejects_too_large_proof() {
+		run_test(|| {
+			let proof = TestMessagesDeliveryProof(Ok((
+				TEST_LANE_ID,
+				InboundLaneData {
+					relayers: (1..=64).map(|nonce| (nonce, nonce, TEST_RELAYER_A)).collect(),
+					latest_received_nonce: 64,
+					..Default::default()
+				},
+			)));
+			assert!(proof.size_hint() > crate::mock::MaxMessagesDeliveryProofSize::get());
+
+			assert_noop!(
+				Module::<TestRuntime>::receive_messages_delivery_proof(Origin::signed(1), proof),
+				messages_delivery_proof_rejection(Error::<TestRuntime, DefaultInstance>::MessagesDeliveryProofTooLarge),
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_delivery_proof_rejects_proof_of_wrong_lane() {
+		run_test(|| {
+			send_regular_message();
+			System::<TestRuntime>::reset_events();
+
+			// no messages have been sent over the other lane
+			assert_noop!(
+				Module::<TestRuntime>::receive_messages_delivery_proof(
+					Origin::signed(1),
+					TestMessagesDeliveryProof(Ok((
+						bp_test_utils::test_lane_id(2),
+						InboundLaneData {
+							relayers: vec![(1, 1, TEST_RELAYER_A)].into_iter().collect(),
+							latest_received_nonce: 1,
+							..Default::default()
+						}
+					))),
+				),
+				Error::<TestRuntime, DefaultInstance>::InvalidMessagesDeliveryProof,
+			);
+			assert_eq!(System::<TestRuntime>::events(), vec![]);
+			assert_eq!(
+				TestMessageDeliveryAndDispatchPayment::relayer_rewards(TEST_RELAYER_A),
+				0
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_delivery_proof_at_stale_header_is_noop() {
+		run_test(|| {
+			send_regular_message();
+			receive_messages_delivery_proof();
+			System::<TestRuntime>::reset_events();
+
+			// the proof of the older inbound lane state doesn't confirm anything and is not rewarded
+			let storage_root = sp_io::storage::root();
+			assert_eq!(
+				Module::<TestRuntime>::receive_messages_delivery_proof(
+					Origin::signed(1),
+					TestMessagesDeliveryProof(Ok((TEST_LANE_ID, Default::default()))),
+				),
+				Ok(().into()),
+			);
+			assert_eq!(sp_io::storage::root(), storage_root);
+			assert_eq!(System::<TestRuntime>::events(), vec![]);
+		});
+	}
+
 	#[test]
 	fn receive_messages_accepts_single_message_with_invalid_payload() {
 		run_test(|| {
@@ -1031,6 +2299,7 @@ mod tests {
 				Origin::signed(1),
 				TEST_RELAYER_A,
 				Ok(vec![invalid_message]).into(),
+				1,
 				0, // weight may be zero in this case (all messages are improperly encoded)
 			),);
 
@@ -1053,6 +2322,7 @@ mod tests {
 					message(3, REGULAR_PAYLOAD),
 				])
 				.into(),
+				3,
 				REGULAR_PAYLOAD.1 + REGULAR_PAYLOAD.1,
 			),);
 
@@ -1060,12 +2330,105 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn storage_version_key_computed_properly() {
+		// If this test fails, then something has been changed in module storage that is breaking all
+		// previously crafted messages proofs.
+		assert_eq!(
+			storage_keys::storage_version_key::<DefaultInstance>().0,
+			hex!("87f1ffe31b52878f09495ca7482df1a41601562ebcdff856cb2f34e65f3b2659").to_vec(),
+		);
+	}
+
+	#[test]
+	fn legacy_storage_is_migrated_to_current_version() {
+		run_test(|| {
+			assert_eq!(Module::<TestRuntime>::storage_version(), LEGACY_STORAGE_VERSION);
+
+			migrate_storage::<TestRuntime, DefaultInstance>();
+			assert_eq!(Module::<TestRuntime>::storage_version(), CURRENT_STORAGE_VERSION);
+
+			// second migration is noop
+			migrate_storage::<TestRuntime, DefaultInstance>();
+			assert_eq!(Module::<TestRuntime>::storage_version(), CURRENT_STORAGE_VERSION);
+		});
+	}
+
+	#[test]
+	#[cfg(feature = "try-runtime")]
+	fn runtime_upgrade_checks_are_passing() {
+		run_test(|| {
+			send_regular_message();
+			receive_messages_delivery_proof();
+
+			assert_ok!(Module::<TestRuntime>::pre_upgrade());
+			assert_eq!(
+				Module::<TestRuntime>::post_upgrade(),
+				Err("Message lane pallet storage is not migrated to the current version"),
+			);
+
+			migrate_storage::<TestRuntime, DefaultInstance>();
+			assert_ok!(Module::<TestRuntime>::post_upgrade());
+		});
+	}
+
+	#[test]
+	#[cfg(feature = "try-runtime")]
+	fn runtime_upgrade_checks_are_failing_on_corrupted_storage() {
+		run_test(|| {
+			send_regular_message();
+
+			OutboundLanes::<DefaultInstance>::mutate(&TEST_LANE_ID, |data| data.latest_received_nonce = 2);
+			assert_eq!(
+				Module::<TestRuntime>::pre_upgrade(),
+				Err("Outbound lane has received messages that are not generated"),
+			);
+
+			OutboundLanes::<DefaultInstance>::mutate(&TEST_LANE_ID, |data| data.latest_received_nonce = 0);
+			frame_support::storage::unhashed::put_raw(
+				&storage_keys::message_key::<TestRuntime, DefaultInstance>(&TEST_LANE_ID, 1).0,
+				&[1, 2, 3],
+			);
+			assert_eq!(
+				Module::<TestRuntime>::pre_upgrade(),
+				Err("Failed to decode message lane pallet storage value"),
+			);
+		});
+	}
+
+	#[test]
+	fn lane_data_is_decoded_using_storage_version() {
+		use bp_message_lane::DecodeVersioned;
+
+		let outbound_lane_data = OutboundLaneData {
+			oldest_unpruned_nonce: 1,
+			latest_received_nonce: 2,
+			latest_generated_nonce: 3,
+		};
+		let encoded_outbound_lane_data = outbound_lane_data.encode();
+		assert_eq!(
+			OutboundLaneData::decode_versioned(LEGACY_STORAGE_VERSION, &encoded_outbound_lane_data).ok(),
+			Some(outbound_lane_data.clone()),
+		);
+		assert_eq!(
+			OutboundLaneData::decode_versioned(STORAGE_VERSION_V1, &encoded_outbound_lane_data).ok(),
+			Some(outbound_lane_data),
+		);
+		assert!(OutboundLaneData::decode_versioned(CURRENT_STORAGE_VERSION + 1, &encoded_outbound_lane_data).is_err());
+
+		let encoded_message_data = message_data(REGULAR_PAYLOAD).encode();
+		assert_eq!(
+			MessageData::<TestMessageFee>::decode_versioned(CURRENT_STORAGE_VERSION, &encoded_message_data).ok(),
+			Some(message_data(REGULAR_PAYLOAD)),
+		);
+	}
+
 	#[test]
 	fn storage_message_key_computed_properly() {
 		// If this test fails, then something has been changed in module storage that is breaking all
 		// previously crafted messages proofs.
 		assert_eq!(
-			storage_keys::message_key::<TestRuntime, DefaultInstance>(&*b"test", 42).0,
+			storage_keys::message_key::<TestRuntime, DefaultInstance>(&LaneId::from_bytes(*b"test"), 42).0,
 			hex!("87f1ffe31b52878f09495ca7482df1a48a395e6242c6813b196ca31ed0547ea79446af0e09063bd4a7874aef8a997cec746573742a00000000000000").to_vec(),
 		);
 	}
@@ -1075,7 +2438,7 @@ mod tests {
 		// If this test fails, then something has been changed in module storage that is breaking all
 		// previously crafted outbound lane state proofs.
 		assert_eq!(
-			storage_keys::outbound_lane_data_key::<DefaultInstance>(&*b"test").0,
+			storage_keys::outbound_lane_data_key::<DefaultInstance>(&LaneId::from_bytes(*b"test")).0,
 			hex!("87f1ffe31b52878f09495ca7482df1a496c246acb9b55077390e3ca723a0ca1f44a8995dd50b6657a037a7839304535b74657374").to_vec(),
 		);
 	}
@@ -1085,8 +2448,203 @@ mod tests {
 		// If this test fails, then something has been changed in module storage that is breaking all
 		// previously crafted inbound lane state proofs.
 		assert_eq!(
-			storage_keys::inbound_lane_data_key::<TestRuntime, DefaultInstance>(&*b"test").0,
+			storage_keys::inbound_lane_data_key::<TestRuntime, DefaultInstance>(&LaneId::from_bytes(*b"test")).0,
 			hex!("87f1ffe31b52878f09495ca7482df1a4e5f83cf83f2127eb47afdc35d6e43fab44a8995dd50b6657a037a7839304535b74657374").to_vec(),
 		);
 	}
+
+	#[test]
+	fn inbound_oldest_undispatched_nonce_key_computed_properly() {
+		assert_eq!(
+			storage_keys::inbound_oldest_undispatched_nonce_key::<DefaultInstance>(&LaneId::from_bytes(*b"test")).0,
+			hex!("87f1ffe31b52878f09495ca7482df1a4f134c8ff76c7b024301652a14a0b066344a8995dd50b6657a037a7839304535b74657374").to_vec(),
+		);
+	}
+
+	#[test]
+	fn inbound_undispatched_message_key_computed_properly() {
+		assert_eq!(
+			storage_keys::inbound_undispatched_message_key::<TestRuntime, DefaultInstance>(
+				&LaneId::from_bytes(*b"test"),
+				42,
+			)
+			.0,
+			hex!("87f1ffe31b52878f09495ca7482df1a4324709bcc14e573f5cb2c137bd6c66839446af0e09063bd4a7874aef8a997cec746573742a00000000000000").to_vec(),
+		);
+	}
+
+	#[test]
+	fn lanes_are_enumerated() {
+		run_test(|| {
+			const OTHER_LANE_ID: LaneId = LaneId::from_bytes([0, 0, 0, 2]);
+
+			assert_eq!(Module::<TestRuntime>::lanes(), vec![]);
+
+			send_regular_message();
+			InboundLanes::<TestRuntime, DefaultInstance>::insert(
+				OTHER_LANE_ID,
+				InboundLaneData {
+					latest_confirmed_nonce: 8,
+					latest_received_nonce: 10,
+					relayers: vec![(9, 10, TEST_RELAYER_A)].into_iter().collect(),
+				},
+			);
+			IsHalted::<DefaultInstance>::put(true);
+
+			let lanes = Module::<TestRuntime>::lanes();
+			assert_eq!(
+				lanes,
+				vec![
+					LaneState {
+						lane: TEST_LANE_ID,
+						outbound: OutboundLaneData {
+							oldest_unpruned_nonce: 1,
+							latest_received_nonce: 0,
+							latest_generated_nonce: 1,
+						},
+						inbound_latest_received_nonce: 0,
+						inbound_latest_confirmed_nonce: 0,
+						is_halted: true,
+					},
+					LaneState {
+						lane: OTHER_LANE_ID,
+						outbound: Default::default(),
+						inbound_latest_received_nonce: 10,
+						inbound_latest_confirmed_nonce: 8,
+						is_halted: true,
+					},
+				],
+			);
+			assert_eq!(lanes[0].outbound_queued_messages(), 1);
+			assert_eq!(lanes[1].inbound_unconfirmed_messages(), 2);
+		});
+	}
+
+	#[test]
+	fn unrewarded_relayers_and_messages_fee_are_read() {
+		run_test(|| {
+			send_regular_message();
+			InboundLanes::<TestRuntime, DefaultInstance>::insert(
+				TEST_LANE_ID,
+				InboundLaneData {
+					latest_confirmed_nonce: 8,
+					latest_received_nonce: 12,
+					relayers: vec![(9, 10, TEST_RELAYER_A), (11, 12, TEST_RELAYER_B)]
+						.into_iter()
+						.collect(),
+				},
+			);
+
+			assert_eq!(
+				Module::<TestRuntime>::inbound_unrewarded_relayers(TEST_LANE_ID),
+				vec![
+					UnrewardedRelayer {
+						relayer: TEST_RELAYER_A,
+						begin: 9,
+						end: 10,
+					},
+					UnrewardedRelayer {
+						relayer: TEST_RELAYER_B,
+						begin: 11,
+						end: 12,
+					},
+				],
+			);
+			assert_eq!(
+				Module::<TestRuntime>::outbound_message_fee(TEST_LANE_ID, 1),
+				Some(REGULAR_PAYLOAD.1),
+			);
+			assert_eq!(Module::<TestRuntime>::outbound_message_fee(TEST_LANE_ID, 2), None);
+		});
+	}
+
+	#[test]
+	fn inbound_message_dispatch_weight_is_estimated() {
+		run_test(|| {
+			assert_eq!(
+				Module::<TestRuntime>::inbound_message_dispatch_weight(TEST_LANE_ID, REGULAR_PAYLOAD.encode(), 0),
+				Some(REGULAR_PAYLOAD.1),
+			);
+			assert_eq!(
+				Module::<TestRuntime>::inbound_message_dispatch_weight(TEST_LANE_ID, vec![42], 0),
+				None,
+			);
+		});
+	}
+
+	#[test]
+	fn pallet_errors_are_classified() {
+		let classify = |error: Error<TestRuntime, DefaultInstance>| bp_runtime::BridgeError::from(error);
+		assert_eq!(classify(Error::Halted), bp_runtime::BridgeError::Halted);
+		assert_eq!(
+			classify(Error::LaneIsNotWhitelisted),
+			bp_runtime::BridgeError::LaneNotAllowed
+		);
+		assert_eq!(
+			classify(Error::MessagesProofTooLarge),
+			bp_runtime::BridgeError::ProofTooLarge
+		);
+		assert_eq!(
+			classify(Error::InvalidMessagesCount),
+			bp_runtime::BridgeError::WeightMismatch
+		);
+		assert!(classify(Error::InvalidMessagesProof).is_retryable());
+		assert!(!classify(Error::MessageRejectedByLaneVerifier).is_retryable());
+	}
+
+	/// Return storage proof of `messages_count` regular messages at the test lane and (optionally)
+	/// of the outbound lane state. This is what relayers are proving when delivering messages.
+	fn regular_messages_storage_proof(messages_count: MessageNonce, with_outbound_lane_state: bool) -> StorageProof {
+		let mut entries = (1..=messages_count)
+			.map(|nonce| {
+				(
+					storage_keys::message_key::<TestRuntime, DefaultInstance>(&TEST_LANE_ID, nonce).0,
+					message_data(REGULAR_PAYLOAD).encode(),
+				)
+			})
+			.collect::<Vec<_>>();
+		if with_outbound_lane_state {
+			entries.push((
+				storage_keys::outbound_lane_data_key::<DefaultInstance>(&TEST_LANE_ID).0,
+				OutboundLaneData {
+					oldest_unpruned_nonce: 1,
+					latest_received_nonce: 0,
+					latest_generated_nonce: messages_count,
+				}
+				.encode(),
+			));
+		}
+		bp_runtime::craft_storage_proof::<<TestRuntime as frame_system::Trait>::Hashing>(entries).1
+	}
+
+	#[test]
+	fn messages_proof_size_is_within_budget() {
+		// If this test fails, then something (trie layout, storage keys, codec) has been changed and
+		// relayers are now paying more for the same messages delivery. Budgets are ~10% above sizes
+		// of proofs that have been generated when the test has been added. If the change is intended,
+		// update budgets and weights of the delivery transaction.
+		const BUDGETS: [(MessageNonce, bool, u32); 6] = [
+			(1, false, 100),
+			(1, true, 250),
+			(8, false, 900),
+			(8, true, 1_050),
+			(64, false, 7_100),
+			(64, true, 7_250),
+		];
+
+		for (messages_count, with_outbound_lane_state, budget) in BUDGETS.iter().cloned() {
+			let proof = regular_messages_storage_proof(messages_count, with_outbound_lane_state);
+			let proof_size = bp_runtime::storage_proof_size(&proof).bytes;
+			let encoded_size = proof.encode().len() as u32;
+			assert!(
+				proof_size <= budget && encoded_size <= budget,
+				"Proof of {} messages (with outbound lane state: {}) has size {} (encoded: {}). Budget: {}",
+				messages_count,
+				with_outbound_lane_state,
+				proof_size,
+				encoded_size,
+				budget,
+			);
+		}
+	}
 }
@@ -14,24 +14,22 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::Trait;
+use crate::{Trait, WeightsFrom};
 
 use bp_message_lane::{
 	source_chain::{LaneMessageVerifier, MessageDeliveryAndDispatchPayment, TargetHeaderChain},
 	target_chain::{DispatchMessage, MessageDispatch, ProvedLaneMessages, ProvedMessages, SourceHeaderChain},
 	InboundLaneData, LaneId, Message, MessageData, MessageKey, MessageNonce,
 };
+use bp_message_lane_weights::MessageLaneWeights;
+use bp_runtime::Size;
+use bp_test_utils::TestExternalitiesBuilder;
 use codec::{Decode, Encode};
 use frame_support::{impl_outer_event, impl_outer_origin, parameter_types, weights::Weight};
-use sp_core::H256;
-use sp_runtime::{
-	testing::Header as SubstrateHeader,
-	traits::{BlakeTwo256, IdentityLookup},
-	Perbill,
-};
 use std::collections::BTreeMap;
 
-pub type AccountId = u64;
+pub use bp_test_utils::{AccountId, TEST_LANE_ID};
+
 pub type TestPayload = (u64, Weight);
 pub type TestMessageFee = u64;
 pub type TestRelayer = u64;
@@ -54,50 +52,43 @@ impl_outer_origin! {
 	pub enum Origin for TestRuntime where system = frame_system {}
 }
 
-parameter_types! {
-	pub const BlockHashCount: u64 = 250;
-	pub const MaximumBlockWeight: Weight = 1024;
-	pub const MaximumBlockLength: u32 = 2 * 1024;
-	pub const AvailableBlockRatio: Perbill = Perbill::one();
-}
-
-impl frame_system::Trait for TestRuntime {
-	type Origin = Origin;
-	type Index = u64;
-	type Call = ();
-	type BlockNumber = u64;
-	type Hash = H256;
-	type Hashing = BlakeTwo256;
-	type AccountId = AccountId;
-	type Lookup = IdentityLookup<Self::AccountId>;
-	type Header = SubstrateHeader;
-	type Event = TestEvent;
-	type BlockHashCount = BlockHashCount;
-	type MaximumBlockWeight = MaximumBlockWeight;
-	type DbWeight = ();
-	type BlockExecutionWeight = ();
-	type ExtrinsicBaseWeight = ();
-	type MaximumExtrinsicWeight = MaximumBlockWeight;
-	type AvailableBlockRatio = AvailableBlockRatio;
-	type MaximumBlockLength = MaximumBlockLength;
-	type Version = ();
-	type PalletInfo = ();
-	type AccountData = ();
-	type OnNewAccount = ();
-	type OnKilledAccount = ();
-	type BaseCallFilter = ();
-	type SystemWeightInfo = ();
+bp_test_utils::impl_test_frame_system! {
+	runtime: TestRuntime,
+	origin: Origin,
+	call: (),
+	account_id: AccountId,
+	event: TestEvent,
+	account_data: (),
 }
 
 parameter_types! {
 	pub const MaxMessagesToPruneAtOnce: u64 = 10;
 	pub const MaxUnconfirmedMessagesAtInboundLane: u64 = 16;
+	pub const MaxMessagesProofSize: u32 = 1024;
+	pub const MaxMessagesDeliveryProofSize: u32 = 1024;
+	pub const TestMessageLaneWeights: MessageLaneWeights = MessageLaneWeights {
+		delivery_base: 1000,
+		delivery_per_message: 100,
+		delivery_per_proof_byte: 10,
+		confirmation_base: 2000,
+		confirmation_per_proof_byte: 20,
+		dispatch_base: 3000,
+		undispatched_per_message: 200,
+		undispatched_per_byte: 30,
+	};
 }
 
+/// Weight functions that are used in tests.
+pub type TestWeightInfo = WeightsFrom<TestMessageLaneWeights>;
+
 impl Trait for TestRuntime {
 	type Event = TestEvent;
+	type AdminOrigin = frame_system::EnsureSignedBy<TestAdmins, AccountId>;
 	type MaxMessagesToPruneAtOnce = MaxMessagesToPruneAtOnce;
 	type MaxUnconfirmedMessagesAtInboundLane = MaxUnconfirmedMessagesAtInboundLane;
+	type MaxMessagesProofSize = MaxMessagesProofSize;
+	type MaxMessagesDeliveryProofSize = MaxMessagesDeliveryProofSize;
+	type WeightInfo = TestWeightInfo;
 
 	type OutboundPayload = TestPayload;
 	type OutboundMessageFee = TestMessageFee;
@@ -114,6 +105,9 @@ impl Trait for TestRuntime {
 	type MessageDispatch = TestMessageDispatch;
 }
 
+/// Account id of test bridge administrator.
+pub const TEST_ADMIN: AccountId = 200;
+
 /// Account id of test relayer.
 pub const TEST_RELAYER_A: AccountId = 100;
 
@@ -126,9 +120,6 @@ pub const TEST_RELAYER_C: AccountId = 102;
 /// Error that is returned by all test implementations.
 pub const TEST_ERROR: &str = "Test error";
 
-/// Lane that we're using in tests.
-pub const TEST_LANE_ID: LaneId = [0, 0, 0, 1];
-
 /// Regular message payload.
 pub const REGULAR_PAYLOAD: TestPayload = (0, 50);
 
@@ -139,27 +130,66 @@ pub const PAYLOAD_REJECTED_BY_TARGET_CHAIN: TestPayload = (1, 50);
 pub type MessagesByLaneVec = Vec<(LaneId, ProvedLaneMessages<Message<TestMessageFee>>)>;
 
 /// Test messages proof.
+///
+/// The proof is generated at some source chain header and commits to proved messages. So proofs
+/// with tampered messages, or proofs that are generated at pruned headers are rejected.
 #[derive(Debug, Encode, Decode, Clone, PartialEq, Eq)]
 pub struct TestMessagesProof {
 	pub result: Result<MessagesByLaneVec, ()>,
+	/// Number of the source chain header, at which the proof has been generated.
+	pub at_header: u64,
+	/// Commitment to proved messages.
+	pub commitment: [u8; 32],
+}
+
+impl TestMessagesProof {
+	/// Create proof of given messages, generated at the source chain header `0`.
+	pub fn new(result: Result<MessagesByLaneVec, ()>) -> Self {
+		let commitment = sp_io::hashing::blake2_256(&result.encode());
+		TestMessagesProof {
+			result,
+			at_header: 0,
+			commitment,
+		}
+	}
+
+	/// Return the same proof, generated at given source chain header.
+	pub fn at_header(mut self, at_header: u64) -> Self {
+		self.at_header = at_header;
+		self
+	}
+}
+
+impl Size for TestMessagesProof {
+	fn size_hint(&self) -> u32 {
+		self.result.encoded_size() as u32
+	}
 }
 
 impl From<Result<Vec<Message<TestMessageFee>>, ()>> for TestMessagesProof {
 	fn from(result: Result<Vec<Message<TestMessageFee>>, ()>) -> Self {
-		Self {
-			result: result.map(|messages| {
-				let mut messages_by_lane: BTreeMap<LaneId, ProvedLaneMessages<Message<TestMessageFee>>> =
-					BTreeMap::new();
-				for message in messages {
-					messages_by_lane
-						.entry(message.key.lane_id)
-						.or_default()
-						.messages
-						.push(message);
-				}
-				messages_by_lane.into_iter().collect()
-			}),
-		}
+		let result = result.map(|messages| {
+			let mut messages_by_lane: BTreeMap<LaneId, ProvedLaneMessages<Message<TestMessageFee>>> = BTreeMap::new();
+			for message in messages {
+				messages_by_lane
+					.entry(message.key.lane_id)
+					.or_default()
+					.messages
+					.push(message);
+			}
+			messages_by_lane.into_iter().collect()
+		});
+		Self::new(result)
+	}
+}
+
+/// Test messages delivery proof.
+#[derive(Debug, Encode, Decode, Clone, PartialEq, Eq)]
+pub struct TestMessagesDeliveryProof(pub Result<(LaneId, InboundLaneData<TestRelayer>), ()>);
+
+impl Size for TestMessagesDeliveryProof {
+	fn size_hint(&self) -> u32 {
+		self.0.encoded_size() as u32
 	}
 }
 
@@ -170,7 +200,7 @@ pub struct TestTargetHeaderChain;
 impl TargetHeaderChain<TestPayload, TestRelayer> for TestTargetHeaderChain {
 	type Error = &'static str;
 
-	type MessagesDeliveryProof = Result<(LaneId, InboundLaneData<TestRelayer>), ()>;
+	type MessagesDeliveryProof = TestMessagesDeliveryProof;
 
 	fn verify_message(payload: &TestPayload) -> Result<(), Self::Error> {
 		if *payload == PAYLOAD_REJECTED_BY_TARGET_CHAIN {
@@ -183,7 +213,7 @@ impl TargetHeaderChain<TestPayload, TestRelayer> for TestTargetHeaderChain {
 	fn verify_messages_delivery_proof(
 		proof: Self::MessagesDeliveryProof,
 	) -> Result<(LaneId, InboundLaneData<TestRelayer>), Self::Error> {
-		proof.map_err(|_| TEST_ERROR)
+		proof.0.map_err(|_| TEST_ERROR)
 	}
 }
 
@@ -229,6 +259,12 @@ impl TestMessageDeliveryAndDispatchPayment {
 		let key = (b":relayer-reward:", relayer, fee).encode();
 		frame_support::storage::unhashed::take::<bool>(&key).is_some()
 	}
+
+	/// Returns total reward that has been paid to given relayer.
+	pub fn relayer_rewards(relayer: AccountId) -> TestMessageFee {
+		let key = (b":relayer-rewards:", relayer).encode();
+		frame_support::storage::unhashed::get(&key).unwrap_or(0)
+	}
 }
 
 impl MessageDeliveryAndDispatchPayment<AccountId, TestMessageFee> for TestMessageDeliveryAndDispatchPayment {
@@ -246,6 +282,10 @@ impl MessageDeliveryAndDispatchPayment<AccountId, TestMessageFee> for TestMessag
 	fn pay_relayer_reward(_confirmation_relayer: &AccountId, relayer: &AccountId, fee: &TestMessageFee) {
 		let key = (b":relayer-reward:", relayer, fee).encode();
 		frame_support::storage::unhashed::put(&key, &true);
+
+		let key = (b":relayer-rewards:", relayer).encode();
+		let total_rewards = Self::relayer_rewards(*relayer) + fee;
+		frame_support::storage::unhashed::put(&key, &total_rewards);
 	}
 }
 
@@ -253,6 +293,14 @@ impl MessageDeliveryAndDispatchPayment<AccountId, TestMessageFee> for TestMessag
 #[derive(Debug)]
 pub struct TestSourceHeaderChain;
 
+impl TestSourceHeaderChain {
+	/// Prune all source chain headers before given header. Proofs, generated at pruned headers,
+	/// are rejected.
+	pub fn prune_headers_before(oldest_header: u64) {
+		frame_support::storage::unhashed::put(b":oldest-source-header:", &oldest_header);
+	}
+}
+
 impl SourceHeaderChain<TestMessageFee> for TestSourceHeaderChain {
 	type Error = &'static str;
 
@@ -261,6 +309,14 @@ impl SourceHeaderChain<TestMessageFee> for TestSourceHeaderChain {
 	fn verify_messages_proof(
 		proof: Self::MessagesProof,
 	) -> Result<ProvedMessages<Message<TestMessageFee>>, Self::Error> {
+		let oldest_header: u64 = frame_support::storage::unhashed::get_or_default(b":oldest-source-header:");
+		if proof.at_header < oldest_header {
+			return Err(TEST_ERROR);
+		}
+		if sp_io::hashing::blake2_256(&proof.result.encode()) != proof.commitment {
+			return Err(TEST_ERROR);
+		}
+
 		proof
 			.result
 			.map(|proof| proof.into_iter().collect())
@@ -282,7 +338,24 @@ impl MessageDispatch<TestMessageFee> for TestMessageDispatch {
 		}
 	}
 
-	fn dispatch(_message: DispatchMessage<TestPayload, TestMessageFee>) {}
+	fn dispatch(message: DispatchMessage<TestPayload, TestMessageFee>) {
+		let key = (b":dispatched:", message.key.nonce).encode();
+		frame_support::storage::unhashed::put(&key, &true);
+		frame_support::storage::unhashed::put(b":dispatches:", &(Self::dispatches() + 1));
+	}
+}
+
+impl TestMessageDispatch {
+	/// Returns true if message with given nonce has been dispatched.
+	pub fn is_dispatched(nonce: MessageNonce) -> bool {
+		let key = (b":dispatched:", nonce).encode();
+		frame_support::storage::unhashed::get::<bool>(&key).is_some()
+	}
+
+	/// Returns total number of dispatch calls.
+	pub fn dispatches() -> u64 {
+		frame_support::storage::unhashed::get_or_default(b":dispatches:")
+	}
 }
 
 /// Return test lane message with given nonce and payload.
@@ -306,9 +379,14 @@ pub fn message_data(payload: TestPayload) -> MessageData<TestMessageFee> {
 
 /// Run message lane test.
 pub fn run_test<T>(test: impl FnOnce() -> T) -> T {
-	let t = frame_system::GenesisConfig::default()
-		.build_storage::<TestRuntime>()
-		.unwrap();
-	let mut ext = sp_io::TestExternalities::new(t);
-	ext.execute_with(test)
+	TestExternalitiesBuilder::<TestRuntime>::new().execute_with(test)
+}
+
+/// Test bridge administrators.
+pub struct TestAdmins;
+
+impl frame_support::traits::Contains<AccountId> for TestAdmins {
+	fn sorted_members() -> Vec<AccountId> {
+		vec![TEST_ADMIN]
+	}
 }
@@ -0,0 +1,169 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Helpers for submitting outbound messages from off-chain workers.
+//!
+//! Off-chain worker of some pallet (e.g. the one that periodically syncs its state with the
+//! bridged chain) may use `submit_message()` to enqueue signed `send_message()` transaction
+//! to the transaction pool. Off-chain workers are started for every imported block (and may
+//! run concurrently), so every submission is identified by the deduplication key. The block
+//! where the message has been submitted is saved in the persistent off-chain storage under this
+//! key and no other message with the same key is submitted until `resubmit_after` blocks pass.
+
+use crate::{Call, Instance, Trait};
+
+use bp_message_lane::LaneId;
+use codec::{Decode, Encode};
+use frame_system::offchain::{Account, AppCrypto, CreateSignedTransaction, SendSignedTransaction, Signer};
+use sp_runtime::{offchain::storage::StorageValueRef, traits::Saturating};
+use sp_std::prelude::*;
+
+/// Prefix of all off-chain storage keys that are used by this module.
+const STORAGE_KEY_PREFIX: &[u8] = b"message-lane::offchain::";
+
+/// Error of message submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmitMessageError {
+	/// Message with the same deduplication key has been submitted recently.
+	AlreadySubmitted,
+	/// There are no keys of given crypto in the keystore.
+	NoSigningAccount,
+	/// The `send_message()` transaction has been rejected by the transaction pool.
+	TransactionRejected,
+}
+
+/// Submit signed `send_message()` transaction from the off-chain worker.
+///
+/// The `message` function is called with the account that is going to sign the transaction
+/// and returns lane, payload and delivery and dispatch fee of the message. The `at_block` is
+/// the number of block the off-chain worker is started for.
+pub fn submit_message<T, I, C, F>(
+	dedup_key: &[u8],
+	at_block: T::BlockNumber,
+	resubmit_after: T::BlockNumber,
+	message: F,
+) -> Result<(), SubmitMessageError>
+where
+	T: Trait<I> + CreateSignedTransaction<Call<T, I>>,
+	I: Instance,
+	C: AppCrypto<T::Public, T::Signature>,
+	F: Fn(&Account<T>) -> (LaneId, T::OutboundPayload, T::OutboundMessageFee),
+{
+	let storage_key = submission_storage_key::<I>(dedup_key);
+	if !try_lock_submission(&storage_key, at_block, resubmit_after) {
+		return Err(SubmitMessageError::AlreadySubmitted);
+	}
+
+	let result = Signer::<T, C>::any_account().send_signed_transaction(|account| {
+		let (lane_id, payload, delivery_and_dispatch_fee) = message(account);
+		Call::send_message(lane_id, payload, delivery_and_dispatch_fee)
+	});
+	match result {
+		Some((account, Ok(()))) => {
+			frame_support::debug::trace!(
+				"Submitted message with deduplication key {:?} from account {:?}",
+				dedup_key,
+				account.id,
+			);
+
+			Ok(())
+		}
+		Some((_, Err(()))) => {
+			// let the next off-chain worker retry
+			StorageValueRef::persistent(&storage_key).clear();
+			Err(SubmitMessageError::TransactionRejected)
+		}
+		None => {
+			StorageValueRef::persistent(&storage_key).clear();
+			Err(SubmitMessageError::NoSigningAccount)
+		}
+	}
+}
+
+/// Return off-chain storage key that is used to deduplicate submissions with given key.
+fn submission_storage_key<I: Instance>(dedup_key: &[u8]) -> Vec<u8> {
+	let mut storage_key = STORAGE_KEY_PREFIX.to_vec();
+	storage_key.extend_from_slice(I::PREFIX.as_bytes());
+	storage_key.push(b':');
+	storage_key.extend_from_slice(dedup_key);
+	storage_key
+}
+
+/// Try to save `at_block` as the block of the latest submission.
+///
+/// Returns false if previous submission has happened less than `resubmit_after` blocks ago,
+/// or if the storage value has been concurrently modified by other off-chain worker.
+fn try_lock_submission<BlockNumber>(storage_key: &[u8], at_block: BlockNumber, resubmit_after: BlockNumber) -> bool
+where
+	BlockNumber: Encode + Decode + Copy + PartialOrd + Saturating,
+{
+	let storage = StorageValueRef::persistent(storage_key);
+	let result = storage.mutate(|last_submission: Option<Option<BlockNumber>>| match last_submission {
+		Some(Some(last_submission)) if at_block < last_submission.saturating_add(resubmit_after) => Err(()),
+		_ => Ok(at_block),
+	});
+
+	matches!(result, Ok(Ok(_)))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::DefaultInstance;
+	use sp_core::offchain::{testing::TestOffchainExt, OffchainExt};
+
+	fn run_test(test: impl FnOnce()) {
+		let (offchain, _) = TestOffchainExt::new();
+		let mut ext = sp_io::TestExternalities::default();
+		ext.register_extension(OffchainExt::new(offchain));
+		ext.execute_with(test)
+	}
+
+	#[test]
+	fn submission_is_deduplicated() {
+		run_test(|| {
+			let storage_key = submission_storage_key::<DefaultInstance>(b"heartbeat");
+
+			assert!(try_lock_submission(&storage_key, 10u64, 5));
+			assert!(!try_lock_submission(&storage_key, 10u64, 5));
+			assert!(!try_lock_submission(&storage_key, 14u64, 5));
+			assert!(try_lock_submission(&storage_key, 15u64, 5));
+		});
+	}
+
+	#[test]
+	fn submissions_with_different_keys_are_independent() {
+		run_test(|| {
+			let first_key = submission_storage_key::<DefaultInstance>(b"first");
+			let second_key = submission_storage_key::<DefaultInstance>(b"second");
+			assert_ne!(first_key, second_key);
+
+			assert!(try_lock_submission(&first_key, 10u64, 5));
+			assert!(try_lock_submission(&second_key, 10u64, 5));
+		});
+	}
+
+	#[test]
+	fn submission_is_allowed_after_lock_is_cleared() {
+		run_test(|| {
+			let storage_key = submission_storage_key::<DefaultInstance>(b"heartbeat");
+
+			assert!(try_lock_submission(&storage_key, 10u64, 5));
+			StorageValueRef::persistent(&storage_key).clear();
+			assert!(try_lock_submission(&storage_key, 11u64, 5));
+		});
+	}
+}
@@ -0,0 +1,259 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Property-based tests of the inbound and outbound lanes.
+//!
+//! Both lanes of the bridge are simulated within the same runtime: messages are sent over the
+//! outbound lane, delivered to the inbound lane with the same id, and delivery is confirmed
+//! using the pallet call with the (possibly outdated) inbound lane state. Random sequences of
+//! operations are checked against a simple model of both lanes.
+
+use crate::{
+	inbound_lane,
+	mock::{
+		message_data, run_test, Origin, TestMessageDeliveryAndDispatchPayment, TestMessageDispatch,
+		TestMessagesDeliveryProof, TestRelayer, TestRuntime, REGULAR_PAYLOAD, TEST_LANE_ID, TEST_RELAYER_A,
+		TEST_RELAYER_B, TEST_RELAYER_C,
+	},
+	outbound_lane, DefaultInstance, InboundLanes, Module, OutboundLanes, OutboundMessages,
+};
+
+use bp_message_lane::{InboundLaneData, MessageKey, MessageNonce};
+use frame_support::{assert_ok, traits::Get};
+use proptest::prelude::*;
+use std::collections::BTreeMap;
+
+/// Relayers that are delivering messages in tests.
+const RELAYERS: [TestRelayer; 3] = [TEST_RELAYER_A, TEST_RELAYER_B, TEST_RELAYER_C];
+/// Account that is submitting delivery confirmations in tests.
+const CONFIRMATION_RELAYER: u64 = 1;
+
+/// Operation over the lanes.
+#[derive(Debug, Clone)]
+enum LaneOperation {
+	/// Send single message over the outbound lane.
+	Send,
+	/// Deliver at most `count` next messages to the inbound lane.
+	Deliver { relayer: usize, count: MessageNonce },
+	/// Deliver message with unexpected nonce to the inbound lane.
+	DeliverWrongNonce { relayer: usize, offset: MessageNonce },
+	/// Deliver state of the outbound lane to the inbound lane.
+	UpdateInboundLaneState,
+	/// Confirm delivery using one of (possibly outdated) inbound lane states.
+	ConfirmDelivery { state: prop::sample::Index },
+	/// Prune at most `max` messages at the outbound lane.
+	Prune { max: MessageNonce },
+}
+
+fn lane_operation() -> impl Strategy<Value = LaneOperation> {
+	prop_oneof![
+		3 => Just(LaneOperation::Send),
+		3 => (0..RELAYERS.len(), 1..4u64).prop_map(|(relayer, count)| LaneOperation::Deliver { relayer, count }),
+		1 => (0..RELAYERS.len(), 2..4u64)
+			.prop_map(|(relayer, offset)| LaneOperation::DeliverWrongNonce { relayer, offset }),
+		2 => Just(LaneOperation::UpdateInboundLaneState),
+		2 => any::<prop::sample::Index>().prop_map(|state| LaneOperation::ConfirmDelivery { state }),
+		1 => (0..4u64).prop_map(|max| LaneOperation::Prune { max }),
+	]
+}
+
+/// Expected state of both lanes.
+#[derive(Default)]
+struct LanesModel {
+	/// Nonce of the latest message, sent over the outbound lane.
+	latest_generated_nonce: MessageNonce,
+	/// Nonce of the latest message, confirmed at the outbound lane.
+	latest_confirmed_nonce: MessageNonce,
+	/// Nonce of the oldest message that is still stored at the outbound lane.
+	oldest_unpruned_nonce: MessageNonce,
+	/// Nonce of the latest message, received by the inbound lane.
+	latest_received_nonce: MessageNonce,
+	/// Relayers that have delivered messages.
+	delivered_by: BTreeMap<MessageNonce, TestRelayer>,
+	/// Expected rewards of every relayer.
+	rewards: BTreeMap<TestRelayer, u64>,
+	/// All states of the inbound lane that the confirmation relayer may have seen.
+	inbound_lane_states: Vec<InboundLaneData<TestRelayer>>,
+}
+
+impl LanesModel {
+	fn new() -> Self {
+		LanesModel {
+			oldest_unpruned_nonce: 1,
+			inbound_lane_states: vec![Default::default()],
+			..Default::default()
+		}
+	}
+
+	fn apply(&mut self, operation: LaneOperation) {
+		match operation {
+			LaneOperation::Send => {
+				let nonce = outbound_lane::<TestRuntime, DefaultInstance>(TEST_LANE_ID)
+					.send_message(message_data(REGULAR_PAYLOAD));
+				assert_eq!(nonce, self.latest_generated_nonce + 1);
+				self.latest_generated_nonce = nonce;
+			}
+			LaneOperation::Deliver { relayer, count } => {
+				let mut lane = inbound_lane::<TestRuntime, DefaultInstance>(TEST_LANE_ID);
+				for _ in 0..count {
+					let nonce = self.latest_received_nonce + 1;
+					if nonce > self.latest_generated_nonce {
+						break;
+					}
+
+					let unconfirmed_entries = InboundLanes::<TestRuntime>::get(&TEST_LANE_ID).relayers.len() as u64;
+					let max_unconfirmed_entries =
+						<TestRuntime as crate::Trait>::MaxUnconfirmedMessagesAtInboundLane::get();
+					let is_received = lane.receive_message::<TestMessageDispatch>(
+						RELAYERS[relayer],
+						nonce,
+						message_data(REGULAR_PAYLOAD).into(),
+					);
+					assert_eq!(is_received, unconfirmed_entries < max_unconfirmed_entries);
+					if !is_received {
+						break;
+					}
+
+					assert!(TestMessageDispatch::is_dispatched(nonce));
+					self.latest_received_nonce = nonce;
+					self.delivered_by.insert(nonce, RELAYERS[relayer]);
+				}
+				self.save_inbound_lane_state();
+			}
+			LaneOperation::DeliverWrongNonce { relayer, offset } => {
+				let mut lane = inbound_lane::<TestRuntime, DefaultInstance>(TEST_LANE_ID);
+				assert!(!lane.receive_message::<TestMessageDispatch>(
+					RELAYERS[relayer],
+					self.latest_received_nonce + offset,
+					message_data(REGULAR_PAYLOAD).into(),
+				));
+				if self.latest_received_nonce != 0 {
+					assert!(!lane.receive_message::<TestMessageDispatch>(
+						RELAYERS[relayer],
+						self.latest_received_nonce,
+						message_data(REGULAR_PAYLOAD).into(),
+					));
+				}
+			}
+			LaneOperation::UpdateInboundLaneState => {
+				let outbound_lane_data = OutboundLanes::<DefaultInstance>::get(&TEST_LANE_ID);
+				let latest_confirmed_nonce = InboundLanes::<TestRuntime>::get(&TEST_LANE_ID).latest_confirmed_nonce;
+				let updated_nonce =
+					inbound_lane::<TestRuntime, DefaultInstance>(TEST_LANE_ID).receive_state_update(outbound_lane_data);
+				let expected_updated_nonce = if self.latest_confirmed_nonce > latest_confirmed_nonce {
+					Some(self.latest_confirmed_nonce)
+				} else {
+					None
+				};
+				assert_eq!(updated_nonce, expected_updated_nonce);
+				self.save_inbound_lane_state();
+			}
+			LaneOperation::ConfirmDelivery { state } => {
+				let inbound_lane_data = state.get(&self.inbound_lane_states).clone();
+				let latest_delivered_nonce = inbound_lane_data.latest_received_nonce;
+				assert_ok!(Module::<TestRuntime>::receive_messages_delivery_proof(
+					Origin::signed(CONFIRMATION_RELAYER),
+					TestMessagesDeliveryProof(Ok((TEST_LANE_ID, inbound_lane_data))),
+				));
+
+				// only newly confirmed messages are rewarded
+				for nonce in self.latest_confirmed_nonce + 1..=latest_delivered_nonce {
+					*self.rewards.entry(self.delivered_by[&nonce]).or_default() += message_data(REGULAR_PAYLOAD).fee;
+				}
+				self.latest_confirmed_nonce = std::cmp::max(self.latest_confirmed_nonce, latest_delivered_nonce);
+			}
+			LaneOperation::Prune { max } => {
+				let pruned = outbound_lane::<TestRuntime, DefaultInstance>(TEST_LANE_ID).prune_messages(max);
+				let expected_pruned = std::cmp::min(max, self.latest_confirmed_nonce + 1 - self.oldest_unpruned_nonce);
+				assert_eq!(pruned, expected_pruned);
+				self.oldest_unpruned_nonce += pruned;
+			}
+		}
+
+		self.ensure_invariants();
+	}
+
+	fn save_inbound_lane_state(&mut self) {
+		self.inbound_lane_states
+			.push(InboundLanes::<TestRuntime>::get(&TEST_LANE_ID));
+	}
+
+	fn ensure_invariants(&self) {
+		// outbound lane state matches the model
+		let outbound_lane_data = OutboundLanes::<DefaultInstance>::get(&TEST_LANE_ID);
+		assert_eq!(outbound_lane_data.latest_generated_nonce, self.latest_generated_nonce);
+		assert_eq!(outbound_lane_data.latest_received_nonce, self.latest_confirmed_nonce);
+		assert_eq!(outbound_lane_data.oldest_unpruned_nonce, self.oldest_unpruned_nonce);
+		assert!(self.latest_confirmed_nonce <= self.latest_received_nonce);
+		assert!(self.latest_received_nonce <= self.latest_generated_nonce);
+
+		// only confirmed messages are pruned
+		for nonce in 1..=self.latest_generated_nonce {
+			let is_stored = OutboundMessages::<TestRuntime>::get(MessageKey {
+				lane_id: TEST_LANE_ID,
+				nonce,
+			})
+			.is_some();
+			assert_eq!(is_stored, nonce >= self.oldest_unpruned_nonce);
+		}
+
+		// relayer entries are covering (latest_confirmed_nonce; latest_received_nonce] range without
+		// gaps and overlaps, and every entry matches relayer that has delivered messages
+		let inbound_lane_data = InboundLanes::<TestRuntime>::get(&TEST_LANE_ID);
+		assert_eq!(inbound_lane_data.latest_received_nonce, self.latest_received_nonce);
+		assert!(inbound_lane_data.latest_confirmed_nonce <= self.latest_confirmed_nonce);
+		let mut expected_nonce_low = inbound_lane_data.latest_confirmed_nonce + 1;
+		let mut previous_relayer = None;
+		for (nonce_low, nonce_high, relayer) in &inbound_lane_data.relayers {
+			assert_eq!(*nonce_low, expected_nonce_low);
+			assert!(nonce_low <= nonce_high);
+			assert_ne!(previous_relayer, Some(relayer));
+			for nonce in *nonce_low..=*nonce_high {
+				assert_eq!(self.delivered_by.get(&nonce), Some(relayer));
+			}
+
+			expected_nonce_low = nonce_high + 1;
+			previous_relayer = Some(relayer);
+		}
+		assert_eq!(expected_nonce_low, self.latest_received_nonce + 1);
+
+		// every confirmed message is rewarded exactly once
+		for relayer in RELAYERS.iter() {
+			assert_eq!(
+				TestMessageDeliveryAndDispatchPayment::relayer_rewards(*relayer),
+				self.rewards.get(relayer).cloned().unwrap_or(0),
+			);
+		}
+		assert_eq!(
+			TestMessageDeliveryAndDispatchPayment::relayer_rewards(CONFIRMATION_RELAYER),
+			0
+		);
+	}
+}
+
+proptest! {
+	#![proptest_config(ProptestConfig::with_cases(128))]
+
+	#[test]
+	fn lanes_invariants_are_maintained(operations in prop::collection::vec(lane_operation(), 1..64)) {
+		run_test(|| {
+			let mut model = LanesModel::new();
+			for operation in operations {
+				model.apply(operation);
+			}
+		});
+	}
+}
@@ -0,0 +1,264 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime module that limits number of messages that may be sent over message lanes.
+//!
+//! The module provides `RateLimitedMessageVerifier` that wraps any other `LaneMessageVerifier`
+//! and enforces two quotas: maximal number of messages that may be sent over single lane and
+//! maximal number of messages that may be sent by single submitter (over all lanes) within one
+//! period. Period is a configurable number of blocks (single block, session, era, ...). This
+//! protects the bridge from a single dapp flooding a lane and starving everyone else.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+use bp_message_lane::{source_chain::LaneMessageVerifier, LaneId, MessageNonce};
+use frame_support::{decl_module, decl_storage, traits::Get};
+use sp_runtime::traits::{One, Zero};
+use sp_std::marker::PhantomData;
+
+/// The module configuration trait.
+pub trait Trait<I = DefaultInstance>: frame_system::Trait {
+	/// Number of blocks in the rate limiting period. All quotas are reset when new period starts.
+	type PeriodLength: Get<Self::BlockNumber>;
+	/// Maximal number of messages that may be sent over single lane within one period.
+	type MaxMessagesPerLane: Get<MessageNonce>;
+	/// Maximal number of messages that may be sent by single submitter within one period.
+	type MaxMessagesPerSubmitter: Get<MessageNonce>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait<I>, I: Instance = DefaultInstance> as BridgeRateLimiter {
+		/// Map of lane id => index of period and number of messages sent over the lane within
+		/// this period.
+		pub LaneMessages: map hasher(blake2_128_concat) LaneId => (T::BlockNumber, MessageNonce);
+		/// Map of message submitter => index of period and number of messages sent by the
+		/// submitter within this period.
+		pub SubmitterMessages: map hasher(blake2_128_concat) T::AccountId => (T::BlockNumber, MessageNonce);
+	}
+}
+
+decl_module! {
+	/// Bridge rate limiter FRAME Pallet.
+	pub struct Module<T: Trait<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {}
+}
+
+/// Message verifier that enforces per-lane and per-submitter quotas before passing messages to
+/// the wrapped `Verifier`.
+///
+/// Quotas are updated as soon as the message is verified. So if the message is later rejected
+/// (e.g. because submitter is unable to pay fee), it is still counted.
+pub struct RateLimitedMessageVerifier<T, I, Verifier>(PhantomData<(T, I, Verifier)>);
+
+impl<T, I, Verifier, Payload, Fee> LaneMessageVerifier<T::AccountId, Payload, Fee>
+	for RateLimitedMessageVerifier<T, I, Verifier>
+where
+	T: Trait<I>,
+	I: Instance,
+	Verifier: LaneMessageVerifier<T::AccountId, Payload, Fee>,
+{
+	type Error = &'static str;
+
+	fn verify_message(
+		submitter: &T::AccountId,
+		delivery_and_dispatch_fee: &Fee,
+		lane: &LaneId,
+		payload: &Payload,
+	) -> Result<(), Self::Error> {
+		let period = current_period::<T, I>();
+		let lane_messages = messages_within_period::<T>(LaneMessages::<T, I>::get(lane), period);
+		if lane_messages >= T::MaxMessagesPerLane::get() {
+			return Err("Too many messages have been sent over the lane");
+		}
+		let submitter_messages = messages_within_period::<T>(SubmitterMessages::<T, I>::get(submitter), period);
+		if submitter_messages >= T::MaxMessagesPerSubmitter::get() {
+			return Err("Too many messages have been sent by the submitter");
+		}
+
+		if let Err(error) = Verifier::verify_message(submitter, delivery_and_dispatch_fee, lane, payload) {
+			return Err(error.into());
+		}
+
+		LaneMessages::<T, I>::insert(lane, (period, lane_messages + 1));
+		SubmitterMessages::<T, I>::insert(submitter, (period, submitter_messages + 1));
+
+		Ok(())
+	}
+}
+
+/// Return index of the current rate limiting period.
+fn current_period<T: Trait<I>, I: Instance>() -> T::BlockNumber {
+	let period_length = T::PeriodLength::get();
+	let period_length = if period_length.is_zero() {
+		One::one()
+	} else {
+		period_length
+	};
+	frame_system::Module::<T>::block_number() / period_length
+}
+
+/// Return number of messages that have been sent within given period.
+fn messages_within_period<T: frame_system::Trait>(
+	(stored_period, messages): (T::BlockNumber, MessageNonce),
+	period: T::BlockNumber,
+) -> MessageNonce {
+	if stored_period == period {
+		messages
+	} else {
+		0
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+	use sp_core::H256;
+	use sp_runtime::{
+		testing::Header,
+		traits::{BlakeTwo256, IdentityLookup},
+		Perbill,
+	};
+
+	type AccountId = u64;
+	type System = frame_system::Module<TestRuntime>;
+	type Verifier = RateLimitedMessageVerifier<TestRuntime, DefaultInstance, AcceptAllVerifier>;
+
+	const LANE_1: LaneId = LaneId::from_bytes([0, 0, 0, 1]);
+	const LANE_2: LaneId = LaneId::from_bytes([0, 0, 0, 2]);
+
+	/// Verifier that accepts all messages with non-zero fee.
+	pub struct AcceptAllVerifier;
+
+	impl LaneMessageVerifier<AccountId, (), u64> for AcceptAllVerifier {
+		type Error = &'static str;
+
+		fn verify_message(_: &AccountId, fee: &u64, _: &LaneId, _: &()) -> Result<(), Self::Error> {
+			if *fee == 0 {
+				return Err("Zero fee");
+			}
+
+			Ok(())
+		}
+	}
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct TestRuntime;
+
+	impl_outer_origin! {
+		pub enum Origin for TestRuntime where system = frame_system {}
+	}
+
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: Weight = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+		pub const PeriodLength: u64 = 10;
+		pub const MaxMessagesPerLane: MessageNonce = 3;
+		pub const MaxMessagesPerSubmitter: MessageNonce = 2;
+	}
+
+	impl frame_system::Trait for TestRuntime {
+		type Origin = Origin;
+		type Index = u64;
+		type Call = ();
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = AccountId;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = ();
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type DbWeight = ();
+		type BlockExecutionWeight = ();
+		type ExtrinsicBaseWeight = ();
+		type MaximumExtrinsicWeight = ();
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type MaximumBlockLength = MaximumBlockLength;
+		type Version = ();
+		type PalletInfo = ();
+		type AccountData = ();
+		type OnNewAccount = ();
+		type OnKilledAccount = ();
+		type BaseCallFilter = ();
+		type SystemWeightInfo = ();
+	}
+
+	impl Trait for TestRuntime {
+		type PeriodLength = PeriodLength;
+		type MaxMessagesPerLane = MaxMessagesPerLane;
+		type MaxMessagesPerSubmitter = MaxMessagesPerSubmitter;
+	}
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let t = frame_system::GenesisConfig::default()
+			.build_storage::<TestRuntime>()
+			.unwrap();
+		sp_io::TestExternalities::new(t)
+	}
+
+	#[test]
+	fn submitter_quota_is_enforced() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Verifier::verify_message(&1, &1, &LANE_1, &()), Ok(()));
+			assert_eq!(Verifier::verify_message(&1, &1, &LANE_2, &()), Ok(()));
+			assert_eq!(
+				Verifier::verify_message(&1, &1, &LANE_1, &()),
+				Err("Too many messages have been sent by the submitter"),
+			);
+			assert_eq!(Verifier::verify_message(&2, &1, &LANE_1, &()), Ok(()));
+		});
+	}
+
+	#[test]
+	fn lane_quota_is_enforced() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Verifier::verify_message(&1, &1, &LANE_1, &()), Ok(()));
+			assert_eq!(Verifier::verify_message(&2, &1, &LANE_1, &()), Ok(()));
+			assert_eq!(Verifier::verify_message(&3, &1, &LANE_1, &()), Ok(()));
+			assert_eq!(
+				Verifier::verify_message(&4, &1, &LANE_1, &()),
+				Err("Too many messages have been sent over the lane"),
+			);
+			assert_eq!(Verifier::verify_message(&4, &1, &LANE_2, &()), Ok(()));
+		});
+	}
+
+	#[test]
+	fn quotas_are_reset_when_new_period_starts() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(9);
+			assert_eq!(Verifier::verify_message(&1, &1, &LANE_1, &()), Ok(()));
+			assert_eq!(Verifier::verify_message(&1, &1, &LANE_1, &()), Ok(()));
+			assert!(Verifier::verify_message(&1, &1, &LANE_1, &()).is_err());
+
+			System::set_block_number(10);
+			assert_eq!(Verifier::verify_message(&1, &1, &LANE_1, &()), Ok(()));
+		});
+	}
+
+	#[test]
+	fn rejected_messages_are_not_counted() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Verifier::verify_message(&1, &0, &LANE_1, &()), Err("Zero fee"));
+			assert_eq!(SubmitterMessages::<TestRuntime>::get(1), (0, 0));
+			assert_eq!(LaneMessages::<TestRuntime>::get(LANE_1), (0, 0));
+		});
+	}
+}
@@ -0,0 +1,293 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime module that accumulates rewards of message lane relayers.
+//!
+//! The module implements `MessageDeliveryAndDispatchPayment` trait of the message-lane module.
+//! Message fees are transferred to the shared relayers-fund account when message is sent. But
+//! instead of transferring reward to the relayer when delivery is confirmed, the reward is only
+//! registered in the module storage. Relayer may then claim all accumulated rewards using single
+//! `claim_rewards` call. This keeps delivery confirmation transactions cheap and makes pending
+//! rewards of every relayer visible.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+use bp_message_lane::source_chain::MessageDeliveryAndDispatchPayment;
+use bp_runtime::{bridge_account_id, MESSAGE_LANE_MODULE_PREFIX, NO_INSTANCE_ID};
+use frame_support::{
+	decl_error, decl_event, decl_module, decl_storage,
+	traits::{Currency, ExistenceRequirement},
+};
+use frame_system::ensure_signed;
+use sp_runtime::traits::{Saturating, Zero};
+
+/// Balance type of the module.
+pub type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+/// The module configuration trait.
+pub trait Trait: frame_system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+	/// Currency that is used to pay message fees and relayer rewards.
+	type Currency: Currency<Self::AccountId>;
+}
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// There are no rewards to claim.
+		NoRewardsToClaim,
+		/// Failed to transfer rewards from the relayers-fund account.
+		FailedToPayRewards,
+	}
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as BridgeRelayers {
+		/// Map of relayer => rewards that it may claim.
+		pub RelayerRewards get(fn relayer_rewards): map hasher(blake2_128_concat) T::AccountId => BalanceOf<T>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as frame_system::Trait>::AccountId,
+		Balance = BalanceOf<T>,
+	{
+		/// Relayer has claimed its rewards.
+		RewardsClaimed(AccountId, Balance),
+	}
+);
+
+decl_module! {
+	/// Bridge relayers FRAME Pallet.
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		/// Deposit one of this module's events by using the default implementation.
+		fn deposit_event() = default;
+
+		/// Transfer all accumulated rewards to the relayer account.
+		#[weight = T::DbWeight::get().reads_writes(3, 3)]
+		pub fn claim_rewards(origin) {
+			let relayer = ensure_signed(origin)?;
+			let rewards = RelayerRewards::<T>::get(&relayer);
+			if rewards.is_zero() {
+				return Err(Error::<T>::NoRewardsToClaim.into());
+			}
+
+			T::Currency::transfer(
+				&relayers_fund_account(),
+				&relayer,
+				rewards,
+				ExistenceRequirement::AllowDeath,
+			)
+			.map_err(|error| {
+				frame_support::debug::trace!(
+					target: "runtime",
+					"Failed to pay relayer {:?} rewards {:?}: {:?}",
+					relayer,
+					rewards,
+					error,
+				);
+				Error::<T>::FailedToPayRewards
+			})?;
+
+			RelayerRewards::<T>::remove(&relayer);
+			Self::deposit_event(RawEvent::RewardsClaimed(relayer, rewards));
+		}
+	}
+}
+
+impl<T: Trait> MessageDeliveryAndDispatchPayment<T::AccountId, BalanceOf<T>> for Module<T> {
+	type Error = &'static str;
+
+	fn pay_delivery_and_dispatch_fee(submitter: &T::AccountId, fee: &BalanceOf<T>) -> Result<(), Self::Error> {
+		T::Currency::transfer(
+			submitter,
+			&relayers_fund_account(),
+			*fee,
+			ExistenceRequirement::AllowDeath,
+		)
+		.map_err(Into::into)
+	}
+
+	fn pay_relayer_reward(_confirmation_relayer: &T::AccountId, relayer: &T::AccountId, reward: &BalanceOf<T>) {
+		RelayerRewards::<T>::mutate(relayer, |rewards| *rewards = rewards.saturating_add(*reward));
+	}
+}
+
+/// Return account id of shared relayers-fund account that is storing all fees
+/// paid by submitters, until they're claimed by relayers.
+///
+/// This is the same account that is used by `pallet_message_lane::instant_payments`, so runtime
+/// may switch from instant payments to this module without moving funds.
+pub fn relayers_fund_account<AccountId: Default + codec::Decode>() -> AccountId {
+	bridge_account_id(NO_INSTANCE_ID, MESSAGE_LANE_MODULE_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_support::{
+		assert_noop, assert_ok, impl_outer_event, impl_outer_origin, parameter_types, weights::Weight,
+	};
+	use frame_system::{EventRecord, Phase};
+	use sp_core::H256;
+	use sp_runtime::{
+		testing::Header,
+		traits::{BlakeTwo256, IdentityLookup},
+		Perbill,
+	};
+
+	type AccountId = u64;
+	type Balance = u64;
+	type Balances = pallet_balances::Module<TestRuntime>;
+	type Relayers = Module<TestRuntime>;
+	type System = frame_system::Module<TestRuntime>;
+
+	const SUBMITTER: AccountId = 1;
+	const RELAYER: AccountId = 2;
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct TestRuntime;
+
+	mod relayers {
+		pub use crate::Event;
+	}
+
+	impl_outer_event! {
+		pub enum TestEvent for TestRuntime {
+			frame_system<T>,
+			pallet_balances<T>,
+			relayers<T>,
+		}
+	}
+
+	impl_outer_origin! {
+		pub enum Origin for TestRuntime where system = frame_system {}
+	}
+
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: Weight = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+		pub const ExistentialDeposit: Balance = 1;
+	}
+
+	impl frame_system::Trait for TestRuntime {
+		type Origin = Origin;
+		type Index = u64;
+		type Call = ();
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = AccountId;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = TestEvent;
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type DbWeight = ();
+		type BlockExecutionWeight = ();
+		type ExtrinsicBaseWeight = ();
+		type MaximumExtrinsicWeight = ();
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type MaximumBlockLength = MaximumBlockLength;
+		type Version = ();
+		type PalletInfo = ();
+		type AccountData = pallet_balances::AccountData<Balance>;
+		type OnNewAccount = ();
+		type OnKilledAccount = ();
+		type BaseCallFilter = ();
+		type SystemWeightInfo = ();
+	}
+
+	impl pallet_balances::Trait for TestRuntime {
+		type MaxLocks = ();
+		type Balance = Balance;
+		type DustRemoval = ();
+		type Event = TestEvent;
+		type ExistentialDeposit = ExistentialDeposit;
+		type AccountStore = frame_system::Module<TestRuntime>;
+		type WeightInfo = ();
+	}
+
+	impl Trait for TestRuntime {
+		type Event = TestEvent;
+		type Currency = Balances;
+	}
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let mut t = frame_system::GenesisConfig::default()
+			.build_storage::<TestRuntime>()
+			.unwrap();
+		pallet_balances::GenesisConfig::<TestRuntime> {
+			balances: vec![(SUBMITTER, 100)],
+		}
+		.assimilate_storage(&mut t)
+		.unwrap();
+		sp_io::TestExternalities::new(t)
+	}
+
+	#[test]
+	fn rewards_are_accumulated_and_claimed() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Relayers::pay_delivery_and_dispatch_fee(&SUBMITTER, &50));
+			assert_eq!(Balances::free_balance(relayers_fund_account::<AccountId>()), 50);
+
+			Relayers::pay_relayer_reward(&SUBMITTER, &RELAYER, &10);
+			Relayers::pay_relayer_reward(&SUBMITTER, &RELAYER, &20);
+			assert_eq!(Relayers::relayer_rewards(RELAYER), 30);
+			assert_eq!(Balances::free_balance(RELAYER), 0);
+
+			System::set_block_number(1);
+			System::reset_events();
+			assert_ok!(Relayers::claim_rewards(Origin::signed(RELAYER)));
+			assert_eq!(Relayers::relayer_rewards(RELAYER), 0);
+			assert_eq!(Balances::free_balance(RELAYER), 30);
+			assert_eq!(Balances::free_balance(relayers_fund_account::<AccountId>()), 20);
+			assert!(System::events().contains(&EventRecord {
+				phase: Phase::Initialization,
+				event: TestEvent::relayers(RawEvent::RewardsClaimed(RELAYER, 30)),
+				topics: vec![],
+			}));
+		});
+	}
+
+	#[test]
+	fn nothing_is_claimed_without_rewards() {
+		new_test_ext().execute_with(|| {
+			assert_noop!(
+				Relayers::claim_rewards(Origin::signed(RELAYER)),
+				Error::<TestRuntime>::NoRewardsToClaim,
+			);
+		});
+	}
+
+	#[test]
+	fn rewards_are_kept_if_fund_is_unable_to_pay() {
+		new_test_ext().execute_with(|| {
+			Relayers::pay_relayer_reward(&SUBMITTER, &RELAYER, &10);
+			assert_noop!(
+				Relayers::claim_rewards(Origin::signed(RELAYER)),
+				Error::<TestRuntime>::FailedToPayRewards,
+			);
+			assert_eq!(Relayers::relayer_rewards(RELAYER), 10);
+		});
+	}
+}
@@ -87,16 +87,10 @@ impl<T: Trait> Module<T> {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use bp_test_utils::{test_accounts, AccountId, TestExternalitiesBuilder};
 	use frame_support::sp_io::TestExternalities;
-	use frame_support::sp_runtime::{
-		testing::{Header, UintAuthorityId},
-		traits::{BlakeTwo256, ConvertInto, IdentityLookup},
-		Perbill, RuntimeAppPublic,
-	};
-	use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
-	use sp_core::H256;
-
-	type AccountId = u64;
+	use frame_support::sp_runtime::{testing::UintAuthorityId, traits::ConvertInto, RuntimeAppPublic};
+	use frame_support::{impl_outer_origin, parameter_types};
 
 	#[derive(Clone, Eq, PartialEq)]
 	pub struct TestRuntime;
@@ -105,39 +99,13 @@ mod tests {
 		pub enum Origin for TestRuntime {}
 	}
 
-	parameter_types! {
-		pub const BlockHashCount: u64 = 250;
-		pub const MaximumBlockWeight: Weight = 1024;
-		pub const MaximumBlockLength: u32 = 2 * 1024;
-		pub const AvailableBlockRatio: Perbill = Perbill::one();
-	}
-
-	impl frame_system::Trait for TestRuntime {
-		type Origin = Origin;
-		type Index = u64;
-		type Call = ();
-		type BlockNumber = u64;
-		type Hash = H256;
-		type Hashing = BlakeTwo256;
-		type AccountId = AccountId;
-		type Lookup = IdentityLookup<Self::AccountId>;
-		type Header = Header;
-		type Event = ();
-		type BlockHashCount = BlockHashCount;
-		type MaximumBlockWeight = MaximumBlockWeight;
-		type DbWeight = ();
-		type BlockExecutionWeight = ();
-		type ExtrinsicBaseWeight = ();
-		type MaximumExtrinsicWeight = ();
-		type AvailableBlockRatio = AvailableBlockRatio;
-		type MaximumBlockLength = MaximumBlockLength;
-		type Version = ();
-		type PalletInfo = ();
-		type AccountData = ();
-		type OnNewAccount = ();
-		type OnKilledAccount = ();
-		type BaseCallFilter = ();
-		type SystemWeightInfo = ();
+	bp_test_utils::impl_test_frame_system! {
+		runtime: TestRuntime,
+		origin: Origin,
+		call: (),
+		account_id: AccountId,
+		event: (),
+		account_data: (),
 	}
 
 	parameter_types! {
@@ -172,21 +140,14 @@ mod tests {
 	}
 
 	fn new_test_ext() -> TestExternalities {
-		let mut t = frame_system::GenesisConfig::default()
-			.build_storage::<TestRuntime>()
-			.unwrap();
-		pallet_session::GenesisConfig::<TestRuntime> {
-			keys: vec![
-				(1, 1, UintAuthorityId(1)),
-				(2, 2, UintAuthorityId(2)),
-				(3, 3, UintAuthorityId(3)),
-				(4, 4, UintAuthorityId(4)),
-				(5, 5, UintAuthorityId(5)),
-			],
-		}
-		.assimilate_storage(&mut t)
-		.unwrap();
-		TestExternalities::new(t)
+		TestExternalitiesBuilder::<TestRuntime>::new()
+			.with_genesis(pallet_session::GenesisConfig::<TestRuntime> {
+				keys: test_accounts(5)
+					.into_iter()
+					.map(|account| (account, account, UintAuthorityId(account)))
+					.collect(),
+			})
+			.build()
 	}
 
 	#[test]
@@ -0,0 +1,162 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Module that provides RPC methods specific to the bridge pallets.
+
+use crate::error::{Error, FutureResult};
+
+use bp_runtime::{BridgeStatus, BridgeStatusApi, InstanceId};
+use jsonrpc_core::futures::{future, Future};
+use jsonrpc_core_client::{transports::http, RawClient};
+use jsonrpc_derive::rpc;
+use serde::Serialize;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+	codec::Codec,
+	generic::BlockId,
+	traits::{Block as BlockT, UniqueSaturatedInto},
+};
+use std::{marker::PhantomData, sync::Arc};
+
+mod error;
+
+/// Status of the bridge with some chain.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BridgeStatusResponse<BridgedBlockNumber, BridgedBlockHash> {
+	/// Number and hash of the best bridged headers, imported by the headers pallet.
+	pub best_headers: Vec<(BridgedBlockNumber, BridgedBlockHash)>,
+	/// Number and hash of the best finalized bridged header.
+	pub best_finalized: (BridgedBlockNumber, BridgedBlockHash),
+	/// Number of the best header of the bridged chain. Only known if the node is configured
+	/// with RPC address of the bridged chain node.
+	pub peer_best_number: Option<u64>,
+	/// Number of bridged chain headers that are not yet imported by the headers pallet. Only
+	/// known if `peer_best_number` is known.
+	pub header_lag: Option<u64>,
+	/// True if headers pallet operations are halted.
+	pub is_headers_pallet_halted: bool,
+	/// True if message lane pallet operations are halted.
+	pub is_message_lane_pallet_halted: bool,
+}
+
+/// Provides RPC methods for monitoring bridge pallets.
+#[rpc]
+pub trait BridgeApi<BlockHash, BridgedBlockNumber, BridgedBlockHash> {
+	/// Returns status of the bridge with given instance at given block.
+	#[rpc(name = "bridge_status")]
+	fn status(
+		&self,
+		instance: InstanceId,
+		block: Option<BlockHash>,
+	) -> FutureResult<BridgeStatusResponse<BridgedBlockNumber, BridgedBlockHash>>;
+}
+
+/// Implements the BridgeApi trait for monitoring bridge pallets.
+pub struct BridgeRpcHandler<Block, Client> {
+	client: Arc<Client>,
+	peer_rpc: Option<String>,
+	_phantom: PhantomData<Block>,
+}
+
+impl<Block, Client> BridgeRpcHandler<Block, Client> {
+	/// Creates new bridge RPC handler. If `peer_rpc` is specified, it is used as HTTP RPC
+	/// address of the bridged chain node.
+	pub fn new(client: Arc<Client>, peer_rpc: Option<String>) -> Self {
+		BridgeRpcHandler {
+			client,
+			peer_rpc,
+			_phantom: Default::default(),
+		}
+	}
+}
+
+impl<Block, Client, BridgedBlockNumber, BridgedBlockHash> BridgeApi<Block::Hash, BridgedBlockNumber, BridgedBlockHash>
+	for BridgeRpcHandler<Block, Client>
+where
+	Block: BlockT,
+	Client: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	Client::Api: BridgeStatusApi<Block, BridgedBlockNumber, BridgedBlockHash>,
+	BridgedBlockNumber: Codec + Serialize + Copy + UniqueSaturatedInto<u64> + Send + 'static,
+	BridgedBlockHash: Codec + Serialize + Send + 'static,
+{
+	fn status(
+		&self,
+		instance: InstanceId,
+		block: Option<Block::Hash>,
+	) -> FutureResult<BridgeStatusResponse<BridgedBlockNumber, BridgedBlockHash>> {
+		let block = block.unwrap_or_else(|| self.client.info().best_hash);
+		let status = match self.client.runtime_api().bridge_status(&BlockId::Hash(block), instance) {
+			Ok(Some(status)) => status,
+			Ok(None) => return Box::new(future::err(Error::UnknownInstance.into())),
+			Err(error) => return Box::new(future::err(Error::RuntimeApi(format!("{:?}", error)).into())),
+		};
+
+		let peer_best_number: FutureResult<Option<u64>> = match self.peer_rpc {
+			Some(ref peer_rpc) => Box::new(read_peer_best_number(peer_rpc).then(|result| match result {
+				Ok(peer_best_number) => Ok::<_, jsonrpc_core::Error>(Some(peer_best_number)),
+				Err(error) => {
+					log::warn!(target: "bridge", "Failed to read bridged chain best block: {}", error);
+					Ok(None)
+				}
+			})),
+			None => Box::new(future::ok(None)),
+		};
+
+		Box::new(peer_best_number.map(move |peer_best_number| status_response(status, peer_best_number)))
+	}
+}
+
+/// Read number of the best block from the peer (bridged chain) node.
+fn read_peer_best_number(peer_rpc: &str) -> impl Future<Item = u64, Error = String> {
+	http::connect::<RawClient>(peer_rpc)
+		.and_then(|client| client.call_method("chain_getHeader", jsonrpc_core::Params::None))
+		.map_err(|error| format!("{:?}", error))
+		.and_then(|header| {
+			header
+				.get("number")
+				.and_then(|number| number.as_str())
+				.and_then(|number| u64::from_str_radix(number.trim_start_matches("0x"), 16).ok())
+				.ok_or_else(|| format!("Unexpected header: {}", header))
+		})
+}
+
+/// Convert bridge status into RPC response.
+fn status_response<BridgedBlockNumber, BridgedBlockHash>(
+	status: BridgeStatus<BridgedBlockNumber, BridgedBlockHash>,
+	peer_best_number: Option<u64>,
+) -> BridgeStatusResponse<BridgedBlockNumber, BridgedBlockHash>
+where
+	BridgedBlockNumber: Copy + UniqueSaturatedInto<u64>,
+{
+	let best_imported_number: u64 = status
+		.best_headers
+		.iter()
+		.map(|(number, _)| *number)
+		.chain(std::iter::once(status.best_finalized.0))
+		.map(UniqueSaturatedInto::<u64>::unique_saturated_into)
+		.max()
+		.unwrap_or_default();
+	BridgeStatusResponse {
+		best_headers: status.best_headers,
+		best_finalized: status.best_finalized,
+		peer_best_number,
+		header_lag: peer_best_number.map(|peer_best_number| peer_best_number.saturating_sub(best_imported_number)),
+		is_headers_pallet_halted: status.is_headers_pallet_halted,
+		is_message_lane_pallet_halted: status.is_message_lane_pallet_halted,
+	}
+}
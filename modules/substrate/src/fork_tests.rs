@@ -54,7 +54,6 @@
 //! Import a finality proof for header 2 on fork 1. This finalty proof should fail to be imported
 //! because the header is an old header.
 
-use crate::justification::tests::*;
 use crate::mock::{helpers::*, *};
 use crate::storage::{AuthoritySet, ImportedHeader};
 use crate::verifier::*;
@@ -456,7 +455,7 @@ where
 					make_justification_for_header(&header, grandpa_round, set_id, &authorities).encode();
 
 				let res = verifier
-					.import_finality_proof(header.hash(), justification.into())
+					.import_finality_proof::<TestFinalityVerifier>(header.hash(), justification.into())
 					.map_err(TestError::Finality);
 				assert_eq!(
 					res, *expected_result,
@@ -32,9 +32,14 @@
 #![allow(clippy::large_enum_variant)]
 
 use crate::storage::ImportedHeader;
+use bp_header_chain::FinalityVerifier;
 use bp_runtime::{BlockNumberOf, Chain, HashOf, HasherOf, HeaderOf};
 use frame_support::{
-	decl_error, decl_module, decl_storage, dispatch::DispatchResult, ensure, traits::Get, weights::DispatchClass,
+	decl_error, decl_module, decl_storage,
+	dispatch::DispatchResult,
+	ensure,
+	traits::{EnsureOrigin, Get},
+	weights::DispatchClass,
 };
 use frame_system::{ensure_signed, RawOrigin};
 use sp_runtime::traits::Header as HeaderT;
@@ -42,15 +47,14 @@ use sp_runtime::{traits::BadOrigin, RuntimeDebug};
 use sp_std::{marker::PhantomData, prelude::*};
 use sp_trie::StorageProof;
 
-// Re-export since the node uses these when configuring genesis
-pub use storage::{AuthoritySet, InitializationData, ScheduledChange};
+// Re-export since the node uses these when configuring genesis and the relay uses these when
+// decoding raw storage
+pub use storage::{AuthoritySet, ImportedHeader, InitializationData, ScheduledChange};
 
-pub use justification::decode_justification_target;
-pub use storage_proof::StorageProofChecker;
+pub use bp_header_chain::justification::{GrandpaFinalityVerifier, GrandpaJustification};
+pub use bp_runtime::StorageProofChecker;
 
-mod justification;
 mod storage;
-mod storage_proof;
 mod verifier;
 
 #[cfg(test)]
@@ -60,13 +64,13 @@ mod mock;
 mod fork_tests;
 
 /// Block number of the bridged chain.
-pub(crate) type BridgedBlockNumber<T> = BlockNumberOf<<T as Trait>::BridgedChain>;
+pub(crate) type BridgedBlockNumber<T, I = DefaultInstance> = BlockNumberOf<<T as Trait<I>>::BridgedChain>;
 /// Block hash of the bridged chain.
-pub(crate) type BridgedBlockHash<T> = HashOf<<T as Trait>::BridgedChain>;
+pub(crate) type BridgedBlockHash<T, I = DefaultInstance> = HashOf<<T as Trait<I>>::BridgedChain>;
 /// Hasher of the bridged chain.
-pub(crate) type BridgedBlockHasher<T> = HasherOf<<T as Trait>::BridgedChain>;
+pub(crate) type BridgedBlockHasher<T, I = DefaultInstance> = HasherOf<<T as Trait<I>>::BridgedChain>;
 /// Header of the bridged chain.
-pub(crate) type BridgedHeader<T> = HeaderOf<<T as Trait>::BridgedChain>;
+pub(crate) type BridgedHeader<T, I = DefaultInstance> = HeaderOf<<T as Trait<I>>::BridgedChain>;
 
 /// A convenience type identifying headers.
 #[derive(RuntimeDebug, PartialEq)]
@@ -77,27 +81,37 @@ pub struct HeaderId<H: HeaderT> {
 	pub hash: H::Hash,
 }
 
-pub trait Trait: frame_system::Trait {
+pub trait Trait<I = DefaultInstance>: frame_system::Trait {
 	/// Chain that we are bridging here.
 	type BridgedChain: Chain;
+	/// Origin that is allowed to initialize the pallet, change pallet owner and to halt/resume
+	/// pallet operations, in addition to root and `ModuleOwner`. It could be e.g. a council or
+	/// other collective.
+	type AdminOrigin: EnsureOrigin<Self::Origin>;
+	/// Verifier of finality proofs, generated by the bridged chain finality gadget.
+	type FinalityVerifier: FinalityVerifier<
+		Hash = HashOf<Self::BridgedChain>,
+		Number = BlockNumberOf<Self::BridgedChain>,
+		AuthoritySet = AuthoritySet,
+	>;
 }
 
 decl_storage! {
-	trait Store for Module<T: Trait> as SubstrateBridge {
+	trait Store for Module<T: Trait<I>, I: Instance = DefaultInstance> as SubstrateBridge {
 		/// The number of the highest block(s) we know of.
-		BestHeight: BridgedBlockNumber<T>;
+		BestHeight: BridgedBlockNumber<T, I>;
 		/// Hash of the header at the highest known height.
 		///
 		/// If there are multiple headers at the same "best" height
 		/// this will contain all of their hashes.
-		BestHeaders: Vec<BridgedBlockHash<T>>;
+		BestHeaders: Vec<BridgedBlockHash<T, I>>;
 		/// Hash of the best finalized header.
-		BestFinalized: BridgedBlockHash<T>;
+		BestFinalized: BridgedBlockHash<T, I>;
 		/// The set of header IDs (number, hash) which enact an authority set change and therefore
 		/// require a Grandpa justification.
-		RequiresJustification: map hasher(identity) BridgedBlockHash<T> => BridgedBlockNumber<T>;
+		RequiresJustification: map hasher(identity) BridgedBlockHash<T, I> => BridgedBlockNumber<T, I>;
 		/// Headers which have been imported into the pallet.
-		ImportedHeaders: map hasher(identity) BridgedBlockHash<T> => Option<ImportedHeader<BridgedHeader<T>>>;
+		ImportedHeaders: map hasher(identity) BridgedBlockHash<T, I> => Option<ImportedHeader<BridgedHeader<T, I>>>;
 		/// The current Grandpa Authority set.
 		CurrentAuthoritySet: AuthoritySet;
 		/// The next scheduled authority set change for a given fork.
@@ -106,7 +120,7 @@ decl_storage! {
 		/// Note that this is different than a header which _enacts_ a change.
 		// Grandpa doesn't require there to always be a pending change. In fact, most of the time
 		// there will be no pending change available.
-		NextScheduledChange: map hasher(identity) BridgedBlockHash<T> => Option<ScheduledChange<BridgedBlockNumber<T>>>;
+		NextScheduledChange: map hasher(identity) BridgedBlockHash<T, I> => Option<ScheduledChange<BridgedBlockNumber<T, I>>>;
 		/// Optional pallet owner.
 		///
 		/// Pallet owner has a right to halt all pallet operations and then resume it. If it is
@@ -119,25 +133,25 @@ decl_storage! {
 	}
 	add_extra_genesis {
 		config(owner): Option<T::AccountId>;
-		config(init_data): Option<InitializationData<BridgedHeader<T>>>;
+		config(init_data): Option<InitializationData<BridgedHeader<T, I>>>;
 		build(|config| {
 			if let Some(ref owner) = config.owner {
-				<ModuleOwner<T>>::put(owner);
+				<ModuleOwner<T, I>>::put(owner);
 			}
 
 			if let Some(init_data) = config.init_data.clone() {
-				initialize_bridge::<T>(init_data);
+				initialize_bridge::<T, I>(init_data);
 			} else {
 				// Since the bridge hasn't been initialized we shouldn't allow anyone to perform
 				// transactions.
-				IsHalted::put(true);
+				IsHalted::<I>::put(true);
 			}
 		})
 	}
 }
 
 decl_error! {
-	pub enum Error for Module<T: Trait> {
+	pub enum Error for Module<T: Trait<I>, I: Instance> {
 		/// This header has failed basic verification.
 		InvalidHeader,
 		/// This header has not been finalized.
@@ -156,8 +170,8 @@ decl_error! {
 }
 
 decl_module! {
-	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
-		type Error = Error<T>;
+	pub struct Module<T: Trait<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
+		type Error = Error<T, I>;
 
 		/// Import a signed Substrate header into the runtime.
 		///
@@ -168,19 +182,19 @@ decl_module! {
 		#[weight = 0]
 		pub fn import_signed_header(
 			origin,
-			header: BridgedHeader<T>,
+			header: BridgedHeader<T, I>,
 		) -> DispatchResult {
-			ensure_operational::<T>()?;
+			ensure_operational::<T, I>()?;
 			let _ = ensure_signed(origin)?;
 			frame_support::debug::trace!("Got header {:?}", header);
 
 			let mut verifier = verifier::Verifier {
-				storage: PalletStorage::<T>::new(),
+				storage: PalletStorage::<T, I>::new(),
 			};
 
 			let _ = verifier
 				.import_header(header)
-				.map_err(|_| <Error<T>>::InvalidHeader)?;
+				.map_err(|_| <Error<T, I>>::InvalidHeader)?;
 
 			Ok(())
 		}
@@ -194,20 +208,20 @@ decl_module! {
 		#[weight = 0]
 		pub fn finalize_header(
 			origin,
-			hash: BridgedBlockHash<T>,
+			hash: BridgedBlockHash<T, I>,
 			finality_proof: Vec<u8>,
 		) -> DispatchResult {
-			ensure_operational::<T>()?;
+			ensure_operational::<T, I>()?;
 			let _ = ensure_signed(origin)?;
 			frame_support::debug::trace!("Got header hash {:?}", hash);
 
 			let mut verifier = verifier::Verifier {
-				storage: PalletStorage::<T>::new(),
+				storage: PalletStorage::<T, I>::new(),
 			};
 
 			let _ = verifier
-				.import_finality_proof(hash, finality_proof.into())
-				.map_err(|_| <Error<T>>::UnfinalizedHeader)?;
+				.import_finality_proof::<T::FinalityVerifier>(hash, finality_proof.into())
+				.map_err(|_| <Error<T, I>>::UnfinalizedHeader)?;
 
 			Ok(())
 		}
@@ -225,12 +239,12 @@ decl_module! {
 		#[weight = 0]
 		pub fn initialize(
 			origin,
-			init_data: InitializationData<BridgedHeader<T>>,
+			init_data: InitializationData<BridgedHeader<T, I>>,
 		) {
-			ensure_owner_or_root::<T>(origin)?;
-			let init_allowed = !<BestFinalized<T>>::exists();
-			ensure!(init_allowed, <Error<T>>::AlreadyInitialized);
-			initialize_bridge::<T>(init_data.clone());
+			ensure_owner_or_root::<T, I>(origin)?;
+			let init_allowed = !<BestFinalized<T, I>>::exists();
+			ensure!(init_allowed, <Error<T, I>>::AlreadyInitialized);
+			initialize_bridge::<T, I>(init_data.clone());
 
 			frame_support::debug::info!(
 				"Pallet has been initialized with the following parameters: {:?}", init_data
@@ -239,17 +253,17 @@ decl_module! {
 
 		/// Change `ModuleOwner`.
 		///
-		/// May only be called either by root, or by `ModuleOwner`.
+		/// May only be called either by root, by `AdminOrigin`, or by `ModuleOwner`.
 		#[weight = (T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational)]
 		pub fn set_owner(origin, new_owner: Option<T::AccountId>) {
-			ensure_owner_or_root::<T>(origin)?;
+			ensure_owner_or_root::<T, I>(origin)?;
 			match new_owner {
 				Some(new_owner) => {
-					ModuleOwner::<T>::put(&new_owner);
+					ModuleOwner::<T, I>::put(&new_owner);
 					frame_support::debug::info!("Setting pallet Owner to: {:?}", new_owner);
 				},
 				None => {
-					ModuleOwner::<T>::kill();
+					ModuleOwner::<T, I>::kill();
 					frame_support::debug::info!("Removed Owner of pallet.");
 				},
 			}
@@ -257,30 +271,30 @@ decl_module! {
 
 		/// Halt all pallet operations. Operations may be resumed using `resume_operations` call.
 		///
-		/// May only be called either by root, or by `ModuleOwner`.
+		/// May only be called either by root, by `AdminOrigin`, or by `ModuleOwner`.
 		#[weight = (T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational)]
 		pub fn halt_operations(origin) {
-			ensure_owner_or_root::<T>(origin)?;
-			IsHalted::put(true);
+			ensure_owner_or_root::<T, I>(origin)?;
+			IsHalted::<I>::put(true);
 			frame_support::debug::warn!("Stopping pallet operations.");
 		}
 
 		/// Resume all pallet operations. May be called even if pallet is halted.
 		///
-		/// May only be called either by root, or by `ModuleOwner`.
+		/// May only be called either by root, by `AdminOrigin`, or by `ModuleOwner`.
 		#[weight = (T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational)]
 		pub fn resume_operations(origin) {
-			ensure_owner_or_root::<T>(origin)?;
-			IsHalted::put(false);
+			ensure_owner_or_root::<T, I>(origin)?;
+			IsHalted::<I>::put(false);
 			frame_support::debug::info!("Resuming pallet operations.");
 		}
 	}
 }
 
-impl<T: Trait> Module<T> {
+impl<T: Trait<I>, I: Instance> Module<T, I> {
 	/// Get the highest header(s) that the pallet knows of.
-	pub fn best_headers() -> Vec<(BridgedBlockNumber<T>, BridgedBlockHash<T>)> {
-		PalletStorage::<T>::new()
+	pub fn best_headers() -> Vec<(BridgedBlockNumber<T, I>, BridgedBlockHash<T, I>)> {
+		PalletStorage::<T, I>::new()
 			.best_headers()
 			.iter()
 			.map(|id| (id.number, id.hash))
@@ -292,13 +306,13 @@ impl<T: Trait> Module<T> {
 	/// Since this has been finalized correctly a user of the bridge
 	/// pallet should be confident that any transactions that were
 	/// included in this or any previous header will not be reverted.
-	pub fn best_finalized() -> BridgedHeader<T> {
-		PalletStorage::<T>::new().best_finalized_header().header
+	pub fn best_finalized() -> BridgedHeader<T, I> {
+		PalletStorage::<T, I>::new().best_finalized_header().header
 	}
 
 	/// Check if a particular header is known to the bridge pallet.
-	pub fn is_known_header(hash: BridgedBlockHash<T>) -> bool {
-		PalletStorage::<T>::new().header_exists(hash)
+	pub fn is_known_header(hash: BridgedBlockHash<T, I>) -> bool {
+		PalletStorage::<T, I>::new().header_exists(hash)
 	}
 
 	/// Check if a particular header is finalized.
@@ -307,8 +321,8 @@ impl<T: Trait> Module<T> {
 	// One thing worth noting here is that this approach won't work well
 	// once we track forks since there could be an older header on a
 	// different fork which isn't an ancestor of our best finalized header.
-	pub fn is_finalized_header(hash: BridgedBlockHash<T>) -> bool {
-		let storage = PalletStorage::<T>::new();
+	pub fn is_finalized_header(hash: BridgedBlockHash<T, I>) -> bool {
+		let storage = PalletStorage::<T, I>::new();
 		if let Some(header) = storage.header_by_hash(hash) {
 			header.is_finalized
 		} else {
@@ -319,8 +333,8 @@ impl<T: Trait> Module<T> {
 	/// Returns a list of headers which require finality proofs.
 	///
 	/// These headers require proofs because they enact authority set changes.
-	pub fn require_justifications() -> Vec<(BridgedBlockNumber<T>, BridgedBlockHash<T>)> {
-		PalletStorage::<T>::new()
+	pub fn require_justifications() -> Vec<(BridgedBlockNumber<T, I>, BridgedBlockHash<T, I>)> {
+		PalletStorage::<T, I>::new()
 			.missing_justifications()
 			.iter()
 			.map(|id| (id.number, id.hash))
@@ -331,37 +345,64 @@ impl<T: Trait> Module<T> {
 	/// known finalized header. If the proof is valid, then the `parse` callback
 	/// is called and the function returns its result.
 	pub fn parse_finalized_storage_proof<R>(
-		finalized_header_hash: BridgedBlockHash<T>,
+		finalized_header_hash: BridgedBlockHash<T, I>,
 		storage_proof: StorageProof,
-		parse: impl FnOnce(StorageProofChecker<BridgedBlockHasher<T>>) -> R,
+		parse: impl FnOnce(StorageProofChecker<BridgedBlockHasher<T, I>>) -> R,
 	) -> Result<R, sp_runtime::DispatchError> {
-		let storage = PalletStorage::<T>::new();
+		let storage = PalletStorage::<T, I>::new();
 		let header = storage
 			.header_by_hash(finalized_header_hash)
-			.ok_or(Error::<T>::UnknownHeader)?;
+			.ok_or(Error::<T, I>::UnknownHeader)?;
 		if !header.is_finalized {
-			return Err(Error::<T>::UnfinalizedHeader.into());
+			return Err(Error::<T, I>::UnfinalizedHeader.into());
 		}
 
 		let storage_proof_checker =
-			StorageProofChecker::new(*header.state_root(), storage_proof).map_err(Error::<T>::from)?;
+			StorageProofChecker::new(*header.state_root(), storage_proof).map_err(Error::<T, I>::from)?;
 		Ok(parse(storage_proof_checker))
 	}
 }
 
-/// Ensure that the origin is either root, or `ModuleOwner`.
-fn ensure_owner_or_root<T: Trait>(origin: T::Origin) -> Result<(), BadOrigin> {
+impl<T: Trait<I>, I: Instance> From<bp_runtime::StorageProofError> for Error<T, I> {
+	fn from(error: bp_runtime::StorageProofError) -> Self {
+		match error {
+			bp_runtime::StorageProofError::StorageRootMismatch => Error::StorageRootMismatch,
+			bp_runtime::StorageProofError::StorageValueUnavailable => Error::StorageValueUnavailable,
+		}
+	}
+}
+
+impl<T: Trait<I>, I: Instance> From<Error<T, I>> for bp_runtime::BridgeError {
+	fn from(error: Error<T, I>) -> Self {
+		match error {
+			Error::InvalidHeader | Error::UnfinalizedHeader | Error::UnknownHeader => {
+				bp_runtime::BridgeError::InvalidHeader
+			}
+			Error::StorageRootMismatch | Error::StorageValueUnavailable => bp_runtime::BridgeError::InvalidProof,
+			Error::Halted => bp_runtime::BridgeError::Halted,
+			Error::AlreadyInitialized => bp_runtime::BridgeError::AlreadyInitialized,
+			Error::__Ignore(_, _) => bp_runtime::BridgeError::Other,
+		}
+	}
+}
+
+/// Ensure that the origin is either root, `AdminOrigin`, or `ModuleOwner`.
+fn ensure_owner_or_root<T: Trait<I>, I: Instance>(origin: T::Origin) -> Result<(), BadOrigin> {
+	let origin = match T::AdminOrigin::try_origin(origin) {
+		Ok(_) => return Ok(()),
+		Err(origin) => origin,
+	};
 	match origin.into() {
 		Ok(RawOrigin::Root) => Ok(()),
-		Ok(RawOrigin::Signed(ref signer)) if Some(signer) == <Module<T>>::module_owner().as_ref() => Ok(()),
+		Ok(RawOrigin::Signed(ref signer)) if Some(signer) == <Module<T, I>>::module_owner().as_ref() => Ok(()),
 		_ => Err(BadOrigin),
 	}
 }
 
 /// Ensure that the pallet is in operational mode (not halted).
-fn ensure_operational<T: Trait>() -> Result<(), Error<T>> {
-	if IsHalted::get() {
-		Err(<Error<T>>::Halted)
+fn ensure_operational<T: Trait<I>, I: Instance>() -> Result<(), Error<T, I>> {
+	if IsHalted::<I>::get() {
+		Err(<Error<T, I>>::Halted)
 	} else {
 		Ok(())
 	}
@@ -369,7 +410,7 @@ fn ensure_operational<T: Trait>() -> Result<(), Error<T>> {
 
 /// Since this writes to storage with no real checks this should only be used in functions that were
 /// called by a trusted origin.
-fn initialize_bridge<T: Trait>(init_params: InitializationData<BridgedHeader<T>>) {
+fn initialize_bridge<T: Trait<I>, I: Instance>(init_params: InitializationData<BridgedHeader<T, I>>) {
 	let InitializationData {
 		header,
 		authority_list,
@@ -388,17 +429,17 @@ fn initialize_bridge<T: Trait>(init_params: InitializationData<BridgedHeader<T>>
 		);
 
 		signal_hash = Some(initial_hash);
-		<NextScheduledChange<T>>::insert(initial_hash, change);
+		<NextScheduledChange<T, I>>::insert(initial_hash, change);
 	};
 
-	<BestHeight<T>>::put(header.number());
-	<BestHeaders<T>>::put(vec![initial_hash]);
-	<BestFinalized<T>>::put(initial_hash);
+	<BestHeight<T, I>>::put(header.number());
+	<BestHeaders<T, I>>::put(vec![initial_hash]);
+	<BestFinalized<T, I>>::put(initial_hash);
 
 	let authority_set = AuthoritySet::new(authority_list, set_id);
-	CurrentAuthoritySet::put(authority_set);
+	CurrentAuthoritySet::<I>::put(authority_set);
 
-	<ImportedHeaders<T>>::insert(
+	<ImportedHeaders<T, I>>::insert(
 		initial_hash,
 		ImportedHeader {
 			header,
@@ -408,7 +449,7 @@ fn initialize_bridge<T: Trait>(init_params: InitializationData<BridgedHeader<T>>
 		},
 	);
 
-	IsHalted::put(is_halted);
+	IsHalted::<I>::put(is_halted);
 }
 
 /// Expected interface for interacting with bridge pallet storage.
@@ -473,32 +514,32 @@ pub trait BridgeStorage {
 
 /// Used to interact with the pallet storage in a more abstract way.
 #[derive(Default, Clone)]
-pub struct PalletStorage<T>(PhantomData<T>);
+pub struct PalletStorage<T, I = DefaultInstance>(PhantomData<(T, I)>);
 
-impl<T> PalletStorage<T> {
+impl<T, I> PalletStorage<T, I> {
 	fn new() -> Self {
-		Self(PhantomData::<T>::default())
+		Self(PhantomData::<(T, I)>::default())
 	}
 }
 
-impl<T: Trait> BridgeStorage for PalletStorage<T> {
-	type Header = BridgedHeader<T>;
+impl<T: Trait<I>, I: Instance> BridgeStorage for PalletStorage<T, I> {
+	type Header = BridgedHeader<T, I>;
 
-	fn write_header(&mut self, header: &ImportedHeader<BridgedHeader<T>>) {
+	fn write_header(&mut self, header: &ImportedHeader<BridgedHeader<T, I>>) {
 		use core::cmp::Ordering;
 
 		let hash = header.hash();
 		let current_height = header.number();
-		let best_height = <BestHeight<T>>::get();
+		let best_height = <BestHeight<T, I>>::get();
 
 		match current_height.cmp(&best_height) {
 			Ordering::Equal => {
-				<BestHeaders<T>>::append(hash);
+				<BestHeaders<T, I>>::append(hash);
 			}
 			Ordering::Greater => {
-				<BestHeaders<T>>::kill();
-				<BestHeaders<T>>::append(hash);
-				<BestHeight<T>>::put(current_height);
+				<BestHeaders<T, I>>::kill();
+				<BestHeaders<T, I>>::append(hash);
+				<BestHeight<T, I>>::put(current_height);
 			}
 			Ordering::Less => {
 				// This is fine. We can still have a valid header, but it might just be on a
@@ -507,72 +548,75 @@ impl<T: Trait> BridgeStorage for PalletStorage<T> {
 		}
 
 		if header.requires_justification {
-			<RequiresJustification<T>>::insert(hash, current_height);
+			<RequiresJustification<T, I>>::insert(hash, current_height);
 		} else {
 			// If the key doesn't exist this is a no-op, so it's fine to call it often
-			<RequiresJustification<T>>::remove(hash);
+			<RequiresJustification<T, I>>::remove(hash);
 		}
 
-		<ImportedHeaders<T>>::insert(hash, header);
+		<ImportedHeaders<T, I>>::insert(hash, header);
 	}
 
-	fn best_headers(&self) -> Vec<HeaderId<BridgedHeader<T>>> {
-		let number = <BestHeight<T>>::get();
-		<BestHeaders<T>>::get()
+	fn best_headers(&self) -> Vec<HeaderId<BridgedHeader<T, I>>> {
+		let number = <BestHeight<T, I>>::get();
+		<BestHeaders<T, I>>::get()
 			.iter()
 			.map(|hash| HeaderId { number, hash: *hash })
 			.collect()
 	}
 
-	fn best_finalized_header(&self) -> ImportedHeader<BridgedHeader<T>> {
-		let hash = <BestFinalized<T>>::get();
+	fn best_finalized_header(&self) -> ImportedHeader<BridgedHeader<T, I>> {
+		let hash = <BestFinalized<T, I>>::get();
 		self.header_by_hash(hash)
 			.expect("A finalized header was added at genesis, therefore this must always exist")
 	}
 
-	fn update_best_finalized(&self, hash: BridgedBlockHash<T>) {
-		<BestFinalized<T>>::put(hash);
+	fn update_best_finalized(&self, hash: BridgedBlockHash<T, I>) {
+		<BestFinalized<T, I>>::put(hash);
 	}
 
-	fn header_exists(&self, hash: BridgedBlockHash<T>) -> bool {
-		<ImportedHeaders<T>>::contains_key(hash)
+	fn header_exists(&self, hash: BridgedBlockHash<T, I>) -> bool {
+		<ImportedHeaders<T, I>>::contains_key(hash)
 	}
 
-	fn header_by_hash(&self, hash: BridgedBlockHash<T>) -> Option<ImportedHeader<BridgedHeader<T>>> {
-		<ImportedHeaders<T>>::get(hash)
+	fn header_by_hash(&self, hash: BridgedBlockHash<T, I>) -> Option<ImportedHeader<BridgedHeader<T, I>>> {
+		<ImportedHeaders<T, I>>::get(hash)
 	}
 
-	fn missing_justifications(&self) -> Vec<HeaderId<BridgedHeader<T>>> {
-		<RequiresJustification<T>>::iter()
+	fn missing_justifications(&self) -> Vec<HeaderId<BridgedHeader<T, I>>> {
+		<RequiresJustification<T, I>>::iter()
 			.map(|(hash, number)| HeaderId { number, hash })
 			.collect()
 	}
 
 	fn current_authority_set(&self) -> AuthoritySet {
-		CurrentAuthoritySet::get()
+		CurrentAuthoritySet::<I>::get()
 	}
 
 	fn update_current_authority_set(&self, new_set: AuthoritySet) {
-		CurrentAuthoritySet::put(new_set)
+		CurrentAuthoritySet::<I>::put(new_set)
 	}
 
-	fn enact_authority_set(&mut self, signal_hash: BridgedBlockHash<T>) -> Result<(), ()> {
-		let new_set = <NextScheduledChange<T>>::take(signal_hash).ok_or(())?.authority_set;
+	fn enact_authority_set(&mut self, signal_hash: BridgedBlockHash<T, I>) -> Result<(), ()> {
+		let new_set = <NextScheduledChange<T, I>>::take(signal_hash).ok_or(())?.authority_set;
 		self.update_current_authority_set(new_set);
 
 		Ok(())
 	}
 
-	fn scheduled_set_change(&self, signal_hash: BridgedBlockHash<T>) -> Option<ScheduledChange<BridgedBlockNumber<T>>> {
-		<NextScheduledChange<T>>::get(signal_hash)
+	fn scheduled_set_change(
+		&self,
+		signal_hash: BridgedBlockHash<T, I>,
+	) -> Option<ScheduledChange<BridgedBlockNumber<T, I>>> {
+		<NextScheduledChange<T, I>>::get(signal_hash)
 	}
 
 	fn schedule_next_set_change(
 		&mut self,
-		signal_hash: BridgedBlockHash<T>,
-		next_change: ScheduledChange<BridgedBlockNumber<T>>,
+		signal_hash: BridgedBlockHash<T, I>,
+		next_change: ScheduledChange<BridgedBlockNumber<T, I>>,
 	) {
-		<NextScheduledChange<T>>::insert(signal_hash, next_change)
+		<NextScheduledChange<T, I>>::insert(signal_hash, next_change)
 	}
 }
 
@@ -633,7 +677,7 @@ mod tests {
 			);
 			assert_eq!(storage.best_finalized_header().hash(), init_data.header.hash());
 			assert_eq!(storage.current_authority_set().authorities, init_data.authority_list);
-			assert_eq!(IsHalted::get(), false);
+			assert_eq!(IsHalted::<DefaultInstance>::get(), false);
 		})
 	}
 
@@ -651,11 +695,55 @@ mod tests {
 			assert_ok!(Module::<TestRuntime>::initialize(Origin::root(), init_data.clone()));
 			assert_noop!(
 				Module::<TestRuntime>::initialize(Origin::root(), init_data),
-				<Error<TestRuntime>>::AlreadyInitialized
+				<Error<TestRuntime, DefaultInstance>>::AlreadyInitialized
 			);
 		})
 	}
 
+	#[test]
+	fn pallet_may_be_initialized_at_genesis() {
+		use sp_runtime::BuildStorage;
+
+		let init_data = InitializationData {
+			header: test_header(1),
+			authority_list: authority_list(),
+			set_id: 1,
+			scheduled_change: None,
+			is_halted: false,
+		};
+		let storage = GenesisConfig::<TestRuntime> {
+			owner: Some(2),
+			init_data: Some(init_data.clone()),
+		}
+		.build_storage()
+		.unwrap();
+
+		sp_io::TestExternalities::new(storage).execute_with(|| {
+			let storage = PalletStorage::<TestRuntime>::new();
+			assert_eq!(storage.best_finalized_header().hash(), init_data.header.hash());
+			assert_eq!(storage.current_authority_set().authorities, init_data.authority_list);
+			assert_eq!(ModuleOwner::<TestRuntime>::get(), Some(2));
+			assert_eq!(IsHalted::<DefaultInstance>::get(), false);
+		});
+	}
+
+	#[test]
+	fn pallet_is_halted_if_not_initialized_at_genesis() {
+		use sp_runtime::BuildStorage;
+
+		let storage = GenesisConfig::<TestRuntime> {
+			owner: None,
+			init_data: None,
+		}
+		.build_storage()
+		.unwrap();
+
+		sp_io::TestExternalities::new(storage).execute_with(|| {
+			assert!(!BestFinalized::<TestRuntime>::exists());
+			assert_eq!(IsHalted::<DefaultInstance>::get(), true);
+		});
+	}
+
 	#[test]
 	fn pallet_owner_may_change_owner() {
 		run_test(|| {
@@ -717,16 +805,16 @@ mod tests {
 	#[test]
 	fn pallet_rejects_transactions_if_halted() {
 		run_test(|| {
-			IsHalted::put(true);
+			IsHalted::<DefaultInstance>::put(true);
 
 			assert_noop!(
 				Module::<TestRuntime>::import_signed_header(Origin::signed(1), test_header(1)),
-				Error::<TestRuntime>::Halted,
+				Error::<TestRuntime, DefaultInstance>::Halted,
 			);
 
 			assert_noop!(
 				Module::<TestRuntime>::finalize_header(Origin::signed(1), test_header(1).hash(), vec![]),
-				Error::<TestRuntime>::Halted,
+				Error::<TestRuntime, DefaultInstance>::Halted,
 			);
 		})
 	}
@@ -740,7 +828,7 @@ mod tests {
 					StorageProof::new(vec![]),
 					|_| (),
 				),
-				Error::<TestRuntime>::UnknownHeader,
+				Error::<TestRuntime, DefaultInstance>::UnknownHeader,
 			);
 		});
 	}
@@ -758,7 +846,7 @@ mod tests {
 					StorageProof::new(vec![]),
 					|_| (),
 				),
-				Error::<TestRuntime>::UnfinalizedHeader,
+				Error::<TestRuntime, DefaultInstance>::UnfinalizedHeader,
 			);
 		});
 	}
@@ -767,7 +855,7 @@ mod tests {
 	fn parse_finalized_storage_accepts_valid_proof() {
 		run_test(|| {
 			let mut storage = PalletStorage::<TestRuntime>::new();
-			let (state_root, storage_proof) = storage_proof::tests::craft_valid_storage_proof();
+			let (state_root, storage_proof) = bp_runtime::craft_valid_storage_proof();
 			let mut header = unfinalized_header(1);
 			header.is_finalized = true;
 			header.header.set_state_root(state_root);
@@ -22,14 +22,11 @@
 
 use crate::Trait;
 use bp_runtime::Chain;
-use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
-use sp_runtime::{
-	testing::{Header, H256},
-	traits::{BlakeTwo256, IdentityLookup},
-	Perbill,
-};
+use bp_test_utils::TestExternalitiesBuilder;
+use frame_support::impl_outer_origin;
+use sp_runtime::testing::Header;
 
-pub type AccountId = u64;
+pub use bp_test_utils::AccountId;
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct TestRuntime;
@@ -38,45 +35,23 @@ impl_outer_origin! {
 	pub enum Origin for TestRuntime where system = frame_system {}
 }
 
-parameter_types! {
-	pub const BlockHashCount: u64 = 250;
-	pub const MaximumBlockWeight: Weight = 1024;
-	pub const MaximumBlockLength: u32 = 2 * 1024;
-	pub const AvailableBlockRatio: Perbill = Perbill::one();
-}
-
-impl frame_system::Trait for TestRuntime {
-	type Origin = Origin;
-	type Index = u64;
-	type Call = ();
-	type BlockNumber = u64;
-	type Hash = H256;
-	type Hashing = BlakeTwo256;
-	type AccountId = AccountId;
-	type Lookup = IdentityLookup<Self::AccountId>;
-	type Header = Header;
-	type Event = ();
-	type BlockHashCount = BlockHashCount;
-	type MaximumBlockWeight = MaximumBlockWeight;
-	type DbWeight = ();
-	type BlockExecutionWeight = ();
-	type ExtrinsicBaseWeight = ();
-	type MaximumExtrinsicWeight = ();
-	type AvailableBlockRatio = AvailableBlockRatio;
-	type MaximumBlockLength = MaximumBlockLength;
-	type Version = ();
-	type PalletInfo = ();
-	type AccountData = ();
-	type OnNewAccount = ();
-	type OnKilledAccount = ();
-	type BaseCallFilter = ();
-	type SystemWeightInfo = ();
+bp_test_utils::impl_test_frame_system! {
+	runtime: TestRuntime,
+	origin: Origin,
+	call: (),
+	account_id: AccountId,
+	event: (),
+	account_data: (),
 }
 
 impl Trait for TestRuntime {
 	type BridgedChain = TestBridgedChain;
+	type AdminOrigin = frame_system::EnsureRoot<AccountId>;
+	type FinalityVerifier = TestFinalityVerifier;
 }
 
+pub type TestFinalityVerifier = crate::GrandpaFinalityVerifier<Header>;
+
 #[derive(Debug)]
 pub struct TestBridgedChain;
 
@@ -88,33 +63,22 @@ impl Chain for TestBridgedChain {
 }
 
 pub fn run_test<T>(test: impl FnOnce() -> T) -> T {
-	sp_io::TestExternalities::new(Default::default()).execute_with(test)
+	TestExternalitiesBuilder::<TestRuntime>::new().execute_with(test)
 }
 
 pub mod helpers {
 	use super::*;
 	use crate::storage::ImportedHeader;
 	use crate::{BridgedBlockHash, BridgedBlockNumber, BridgedHeader};
-	use finality_grandpa::voter_set::VoterSet;
-	use sp_finality_grandpa::{AuthorityId, AuthorityList};
-	use sp_keyring::Ed25519Keyring;
+	pub use bp_test_utils::headers::{
+		alice, authority_list, bob, charlie, header_id, make_justification_for_header, test_header,
+	};
 
 	pub type TestHeader = BridgedHeader<TestRuntime>;
 	pub type TestNumber = BridgedBlockNumber<TestRuntime>;
 	pub type TestHash = BridgedBlockHash<TestRuntime>;
 	pub type HeaderId = (TestHash, TestNumber);
 
-	pub fn test_header(num: TestNumber) -> TestHeader {
-		let mut header = TestHeader::new_from_number(num);
-		header.parent_hash = if num == 0 {
-			Default::default()
-		} else {
-			test_header(num - 1).hash()
-		};
-
-		header
-	}
-
 	pub fn unfinalized_header(num: u64) -> ImportedHeader<TestHeader> {
 		ImportedHeader {
 			header: test_header(num),
@@ -123,34 +87,4 @@ pub mod helpers {
 			signal_hash: None,
 		}
 	}
-
-	pub fn header_id(index: u8) -> HeaderId {
-		(test_header(index.into()).hash(), index as _)
-	}
-
-	pub fn extract_keyring(id: &AuthorityId) -> Ed25519Keyring {
-		let mut raw_public = [0; 32];
-		raw_public.copy_from_slice(id.as_ref());
-		Ed25519Keyring::from_raw_public(raw_public).unwrap()
-	}
-
-	pub fn voter_set() -> VoterSet<AuthorityId> {
-		VoterSet::new(authority_list()).unwrap()
-	}
-
-	pub fn authority_list() -> AuthorityList {
-		vec![(alice(), 1), (bob(), 1), (charlie(), 1)]
-	}
-
-	pub fn alice() -> AuthorityId {
-		Ed25519Keyring::Alice.public().into()
-	}
-
-	pub fn bob() -> AuthorityId {
-		Ed25519Keyring::Bob.public().into()
-	}
-
-	pub fn charlie() -> AuthorityId {
-		Ed25519Keyring::Charlie.public().into()
-	}
 }
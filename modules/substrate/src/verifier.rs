@@ -19,13 +19,13 @@
 //!
 //! When importing headers it performs checks to ensure that no invariants are broken (like
 //! importing the same header twice). When it imports finality proofs it will ensure that the proof
-//! has been signed off by the correct Grandpa authorities, and also enact any authority set changes
-//! if required.
+//! has been signed off by the correct authorities (using given `FinalityVerifier`), and also enact
+//! any authority set changes if required.
 
-use crate::justification::verify_justification;
 use crate::storage::{AuthoritySet, ImportedHeader, ScheduledChange};
 use crate::BridgeStorage;
-use finality_grandpa::voter_set::VoterSet;
+use bp_header_chain::FinalityVerifier;
+use codec::Decode;
 use sp_finality_grandpa::{ConsensusLog, GRANDPA_ENGINE_ID};
 use sp_runtime::generic::OpaqueDigestItemId;
 use sp_runtime::traits::{CheckedAdd, Header as HeaderT, One};
@@ -213,10 +213,13 @@ where
 		Ok(())
 	}
 
-	/// Verify that a previously imported header can be finalized with the given Grandpa finality
-	/// proof. If the header enacts an authority set change the change will be applied once the
-	/// header has been finalized.
-	pub fn import_finality_proof(&mut self, hash: H::Hash, proof: FinalityProof) -> Result<(), FinalizationError> {
+	/// Verify that a previously imported header can be finalized with the given finality proof.
+	/// If the header enacts an authority set change the change will be applied once the header
+	/// has been finalized.
+	pub fn import_finality_proof<F>(&mut self, hash: H::Hash, proof: FinalityProof) -> Result<(), FinalizationError>
+	where
+		F: FinalityVerifier<Hash = H::Hash, Number = H::Number, AuthoritySet = AuthoritySet>,
+	{
 		// Make sure that we've previously imported this header
 		let header = self
 			.storage
@@ -231,17 +234,9 @@ where
 		}
 
 		let current_authority_set = self.storage.current_authority_set();
-		let voter_set = VoterSet::new(current_authority_set.authorities).expect(
-			"We verified the correctness of the authority list during header import,
-			before writing them to storage. This must always be valid.",
-		);
-		verify_justification::<H>(
-			(hash, *header.number()),
-			current_authority_set.set_id,
-			voter_set,
-			&proof.0,
-		)
-		.map_err(|_| FinalizationError::InvalidJustification)?;
+		let proof = F::FinalityProof::decode(&mut &proof.0[..]).map_err(|_| FinalizationError::InvalidJustification)?;
+		F::verify_finality_proof((hash, *header.number()), &current_authority_set, &proof)
+			.map_err(|_| FinalizationError::InvalidJustification)?;
 		frame_support::debug::trace!(target: "sub-bridge", "Received valid justification for {:?}", header);
 
 		frame_support::debug::trace!(target: "sub-bridge", "Checking ancestry for headers between {:?} and {:?}", last_finalized, header);
@@ -351,7 +346,6 @@ fn find_scheduled_change<H: HeaderT>(header: &H) -> Option<sp_finality_grandpa::
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::justification::tests::*;
 	use crate::mock::helpers::*;
 	use crate::mock::*;
 	use crate::{BestFinalized, BestHeight, HeaderId, ImportedHeaders, PalletStorage};
@@ -698,7 +692,7 @@ mod tests {
 			};
 
 			assert_ok!(verifier.import_header(header.clone()));
-			assert_ok!(verifier.import_finality_proof(header.hash(), justification.into()));
+			assert_ok!(verifier.import_finality_proof::<TestFinalityVerifier>(header.hash(), justification.into()));
 			assert_eq!(storage.best_finalized_header().header, header);
 		})
 	}
@@ -726,7 +720,7 @@ mod tests {
 			};
 			assert!(verifier.import_header(header.clone()).is_ok());
 			assert!(verifier
-				.import_finality_proof(header.hash(), justification.into())
+				.import_finality_proof::<TestFinalityVerifier>(header.hash(), justification.into())
 				.is_ok());
 
 			// Make sure we marked the our headers as finalized
@@ -780,7 +774,7 @@ mod tests {
 			assert_eq!(storage.missing_justifications().len(), 1);
 			assert_eq!(storage.missing_justifications()[0].hash, header.hash());
 
-			assert_ok!(verifier.import_finality_proof(header.hash(), justification.into()));
+			assert_ok!(verifier.import_finality_proof::<TestFinalityVerifier>(header.hash(), justification.into()));
 			assert_eq!(storage.best_finalized_header().header, header);
 
 			// Make sure that we have updated the set now that we've finalized our header
@@ -811,7 +805,7 @@ mod tests {
 			// Now we want to try and import it again to see what happens
 			assert_eq!(
 				verifier
-					.import_finality_proof(genesis.hash(), vec![4, 2].into())
+					.import_finality_proof::<TestFinalityVerifier>(genesis.hash(), vec![4, 2].into())
 					.unwrap_err(),
 				FinalizationError::OldHeader
 			);
@@ -0,0 +1,280 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime module that makes message lanes usable as XCM transport between standalone chains.
+//!
+//! On the source chain, XCM messages produced by the XCM executor are wrapped into
+//! `XcmMessagePayload` and sent over the message lane (i.e. `XcmMessagePayload` is used as
+//! outbound payload of the message-lane module). On the target chain, this module acts as the
+//! message dispatcher: it decodes the XCM message and hands it over to the XCM executor, limiting
+//! execution weight by the weight that has been declared (and paid for) by the message sender.
+//!
+//! The module is generic over XCM message type and executor, so it doesn't depend on any
+//! particular XCM version.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+use bp_message_dispatch::{MessageDispatch, Weight};
+use bp_runtime::InstanceId;
+use codec::{Decode, Encode};
+use frame_support::{decl_event, decl_module, decl_storage, dispatch::Parameter, RuntimeDebug};
+use sp_runtime::DispatchError;
+use sp_std::marker::PhantomData;
+
+/// Message payload type used by XCM dispatch module.
+#[derive(RuntimeDebug, Encode, Decode, Clone, PartialEq, Eq)]
+pub struct XcmMessagePayload<Xcm> {
+	/// Maximal weight of the XCM message execution, declared by the message sender. Execution
+	/// is aborted if it requires more weight.
+	pub weight: Weight,
+	/// The XCM message itself.
+	pub xcm: Xcm,
+}
+
+/// XCM executor on the target chain.
+pub trait ExecuteXcm<Xcm> {
+	/// Execute XCM message, received from the bridged chain over given bridge instance. The
+	/// execution must not take more than `weight_limit`.
+	///
+	/// Returns weight that has been actually used.
+	fn execute_xcm(bridge: InstanceId, xcm: Xcm, weight_limit: Weight) -> Result<Weight, DispatchError>;
+}
+
+/// The module configuration trait.
+pub trait Trait<I = DefaultInstance>: frame_system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self, I>> + Into<<Self as frame_system::Trait>::Event>;
+	/// Id of the message. Whenever message is passed to the dispatch module, it emits
+	/// event with this id + dispatch result. Could be e.g. (LaneId, MessageNonce) if
+	/// it comes from message-lane module.
+	type MessageId: Parameter;
+	/// XCM message type.
+	type Xcm: Decode;
+	/// XCM executor.
+	type XcmExecutor: ExecuteXcm<Self::Xcm>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait<I>, I: Instance = DefaultInstance> as XcmDispatch {
+	}
+}
+
+decl_event!(
+	pub enum Event<T, I = DefaultInstance> where
+		<T as Trait<I>>::MessageId
+	{
+		/// XCM message has been executed with given result. Successful result carries weight
+		/// that has been used by the execution.
+		XcmExecuted(InstanceId, MessageId, Result<Weight, DispatchError>),
+		/// Phantom member, never used.
+		Dummy(PhantomData<I>),
+	}
+);
+
+decl_module! {
+	/// XCM Dispatch FRAME Pallet.
+	pub struct Module<T: Trait<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
+		/// Deposit one of this module's events by using the default implementation.
+		fn deposit_event() = default;
+	}
+}
+
+impl<T: Trait<I>, I: Instance> MessageDispatch<T::MessageId> for Module<T, I> {
+	type Message = XcmMessagePayload<T::Xcm>;
+
+	fn dispatch_weight(message: &Self::Message) -> Weight {
+		message.weight
+	}
+
+	fn dispatch(bridge: InstanceId, id: T::MessageId, message: Self::Message) {
+		let result = T::XcmExecutor::execute_xcm(bridge, message.xcm, message.weight);
+		frame_support::debug::trace!(
+			"XCM message {:?}/{:?} has been executed. Weight limit: {}. Result: {:?}",
+			bridge,
+			id,
+			message.weight,
+			result,
+		);
+
+		// the executor may be buggy - never report more weight than has been paid for
+		let result = result.map(|used_weight| sp_std::cmp::min(used_weight, message.weight));
+		Self::deposit_event(RawEvent::XcmExecuted(bridge, id, result));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_support::{impl_outer_event, impl_outer_origin, parameter_types};
+	use frame_system::{EventRecord, Phase};
+	use sp_core::H256;
+	use sp_runtime::{
+		testing::Header,
+		traits::{BlakeTwo256, IdentityLookup},
+		Perbill,
+	};
+
+	type AccountId = u64;
+	type XcmDispatch = Module<TestRuntime>;
+	type System = frame_system::Module<TestRuntime>;
+
+	type MessageId = [u8; 4];
+
+	/// Test XCM message: weight that its execution requires.
+	#[derive(Debug, Encode, Decode, Clone, PartialEq, Eq)]
+	pub struct TestXcm(Weight);
+
+	pub struct TestXcmExecutor;
+
+	impl ExecuteXcm<TestXcm> for TestXcmExecutor {
+		fn execute_xcm(_bridge: InstanceId, xcm: TestXcm, weight_limit: Weight) -> Result<Weight, DispatchError> {
+			if xcm.0 > weight_limit {
+				return Err(DispatchError::Other("Too much weight required"));
+			}
+
+			Ok(xcm.0)
+		}
+	}
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct TestRuntime;
+
+	mod xcm_dispatch {
+		pub use crate::Event;
+	}
+
+	impl_outer_event! {
+		pub enum TestEvent for TestRuntime {
+			frame_system<T>,
+			xcm_dispatch<T>,
+		}
+	}
+
+	impl_outer_origin! {
+		pub enum Origin for TestRuntime where system = frame_system {}
+	}
+
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: frame_support::weights::Weight = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+	}
+
+	impl frame_system::Trait for TestRuntime {
+		type Origin = Origin;
+		type Index = u64;
+		type Call = ();
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = AccountId;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = TestEvent;
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type DbWeight = ();
+		type BlockExecutionWeight = ();
+		type ExtrinsicBaseWeight = ();
+		type MaximumExtrinsicWeight = ();
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type MaximumBlockLength = MaximumBlockLength;
+		type Version = ();
+		type PalletInfo = ();
+		type AccountData = ();
+		type OnNewAccount = ();
+		type OnKilledAccount = ();
+		type BaseCallFilter = ();
+		type SystemWeightInfo = ();
+	}
+
+	impl Trait for TestRuntime {
+		type Event = TestEvent;
+		type MessageId = MessageId;
+		type Xcm = TestXcm;
+		type XcmExecutor = TestXcmExecutor;
+	}
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let t = frame_system::GenesisConfig::default()
+			.build_storage::<TestRuntime>()
+			.unwrap();
+		sp_io::TestExternalities::new(t)
+	}
+
+	fn dispatch_and_read_result(xcm_weight: Weight, declared_weight: Weight) -> Result<Weight, DispatchError> {
+		let bridge = b"ethb".to_owned();
+		let id = [0; 4];
+		let message = XcmMessagePayload {
+			weight: declared_weight,
+			xcm: TestXcm(xcm_weight),
+		};
+
+		System::set_block_number(1);
+		assert_eq!(XcmDispatch::dispatch_weight(&message), declared_weight);
+		XcmDispatch::dispatch(bridge, id, message);
+
+		let events = System::events();
+		assert_eq!(events.len(), 1);
+		match events[0].event {
+			TestEvent::xcm_dispatch(Event::<TestRuntime>::XcmExecuted(event_bridge, event_id, ref result)) => {
+				assert_eq!((event_bridge, event_id), (bridge, id));
+				result.clone()
+			}
+			ref event => panic!("Unexpected event: {:?}", event),
+		}
+	}
+
+	#[test]
+	fn should_execute_xcm_within_declared_weight() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(dispatch_and_read_result(10, 100), Ok(10));
+		});
+	}
+
+	#[test]
+	fn should_fail_to_execute_xcm_that_requires_more_weight_than_declared() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(
+				dispatch_and_read_result(100, 10),
+				Err(DispatchError::Other("Too much weight required")),
+			);
+		});
+	}
+
+	#[test]
+	fn should_emit_execution_event() {
+		new_test_ext().execute_with(|| {
+			let message = XcmMessagePayload {
+				weight: 100,
+				xcm: TestXcm(10),
+			};
+			System::set_block_number(1);
+			XcmDispatch::dispatch(*b"ethb", [0; 4], message);
+
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::xcm_dispatch(Event::<TestRuntime>::XcmExecuted(*b"ethb", [0; 4], Ok(10))),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+}
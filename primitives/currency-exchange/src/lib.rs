@@ -23,7 +23,13 @@
 use codec::{Decode, Encode, EncodeLike};
 use frame_support::{Parameter, RuntimeDebug};
 use sp_api::decl_runtime_apis;
-use sp_std::marker::PhantomData;
+use sp_runtime::{
+	helpers_128bit::multiply_by_rational, traits::UniqueSaturatedInto, FixedPointNumber, FixedPointOperand, FixedU128,
+};
+use sp_std::{convert::TryFrom, marker::PhantomData};
+
+#[cfg(any(feature = "test-helpers", test))]
+pub mod test_utils;
 
 /// All errors that may happen during exchange.
 #[derive(RuntimeDebug, PartialEq)]
@@ -36,12 +42,16 @@ pub enum Error {
 	InvalidRecipient,
 	/// Cannot map from peer recipient to this blockchain recipient.
 	FailedToMapRecipients,
+	/// Peer blockchain token is not mapped to any asset of this blockchain.
+	UnknownToken,
 	/// Failed to convert from peer blockchain currency to this blockhain currency.
 	FailedToConvertCurrency,
 	/// Deposit has failed.
 	DepositFailed,
 	/// Deposit has partially failed (changes to recipient account were made).
 	DepositPartiallyFailed,
+	/// Price of the peer blockchain currency is unknown or stale.
+	StalePrice,
 }
 
 /// Result of all exchange operations.
@@ -49,13 +59,15 @@ pub type Result<T> = sp_std::result::Result<T, Error>;
 
 /// Peer blockchain lock funds transaction.
 #[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
-pub struct LockFundsTransaction<TransferId, Recipient, Amount> {
+pub struct LockFundsTransaction<TransferId, Recipient, Amount, Token> {
 	/// Something that uniquely identifies this transfer.
 	pub id: TransferId,
 	/// Funds recipient on the peer chain.
 	pub recipient: Recipient,
 	/// Amount of the locked funds.
 	pub amount: Amount,
+	/// Locked peer blockchain token. None if native peer blockchain currency has been locked.
+	pub token: Option<Token>,
 }
 
 /// Peer blockchain transaction that may represent lock funds transaction.
@@ -68,10 +80,14 @@ pub trait MaybeLockFundsTransaction {
 	type Recipient;
 	/// Peer currency amount type.
 	type Amount;
+	/// Peer token identifier type.
+	type Token: Parameter;
 
 	/// Parse lock funds transaction of the peer blockchain. Returns None if
 	/// transaction format is unknown, or it isn't a lock funds transaction.
-	fn parse(tx: &Self::Transaction) -> Result<LockFundsTransaction<Self::Id, Self::Recipient, Self::Amount>>;
+	fn parse(
+		tx: &Self::Transaction,
+	) -> Result<LockFundsTransaction<Self::Id, Self::Recipient, Self::Amount, Self::Token>>;
 }
 
 /// Map that maps recipients from peer blockchain to this blockchain recipients.
@@ -96,6 +112,33 @@ pub trait CurrencyConverter {
 	fn convert(amount: Self::SourceAmount) -> Result<Self::TargetAmount>;
 }
 
+/// Provider of the peer blockchain currency price.
+///
+/// The price is the number of this blockchain currency units that are equal to the single unit
+/// of the peer blockchain currency. Providers must never return outdated prices - they should
+/// fail with `Error::StalePrice` instead, so all operations that depend on the price are paused
+/// until the price is updated.
+pub trait PriceProvider {
+	/// Return actual price of the peer blockchain currency.
+	fn price() -> Result<FixedU128>;
+
+	/// Convert peer blockchain currency amount into this blockchain currency amount.
+	fn peer_to_this<Amount: FixedPointOperand>(peer_amount: Amount) -> Result<Amount> {
+		Self::price()?
+			.checked_mul_int(peer_amount)
+			.ok_or(Error::FailedToConvertCurrency)
+	}
+
+	/// Convert this blockchain currency amount into peer blockchain currency amount.
+	fn this_to_peer<Amount: FixedPointOperand>(amount: Amount) -> Result<Amount> {
+		let price = Self::price()?;
+		multiply_by_rational(amount.unique_saturated_into(), FixedU128::DIV, price.into_inner())
+			.ok()
+			.and_then(|peer_amount| Amount::try_from(peer_amount).ok())
+			.ok_or(Error::FailedToConvertCurrency)
+	}
+}
+
 /// Currency deposit.
 pub trait DepositInto {
 	/// Recipient type.
@@ -107,6 +150,19 @@ pub trait DepositInto {
 	fn deposit_into(recipient: Self::Recipient, amount: Self::Amount) -> Result<()>;
 }
 
+/// Asset deposit.
+pub trait DepositAssetInto {
+	/// Asset identifier type.
+	type AssetId;
+	/// Recipient type.
+	type Recipient;
+	/// Asset amount type.
+	type Amount;
+
+	/// Grant some amount of given asset to given account.
+	fn deposit_asset_into(asset_id: Self::AssetId, recipient: Self::Recipient, amount: Self::Amount) -> Result<()>;
+}
+
 /// Recipients map which is used when accounts ids are the same on both chains.
 #[derive(Debug)]
 pub struct IdentityRecipients<AccountId>(PhantomData<AccountId>);
@@ -133,6 +189,36 @@ impl<Amount> CurrencyConverter for IdentityCurrencyConverter<Amount> {
 	}
 }
 
+/// Currency converter which is using price provider to convert from peer blockchain currency
+/// to this blockchain currency.
+#[derive(Debug)]
+pub struct PriceProviderCurrencyConverter<Provider, Amount>(PhantomData<(Provider, Amount)>);
+
+impl<Provider: PriceProvider, Amount: FixedPointOperand> CurrencyConverter
+	for PriceProviderCurrencyConverter<Provider, Amount>
+{
+	type SourceAmount = Amount;
+	type TargetAmount = Amount;
+
+	fn convert(amount: Self::SourceAmount) -> Result<Self::TargetAmount> {
+		Provider::peer_to_this(amount)
+	}
+}
+
+/// Asset deposit which is used when this blockchain has no assets other than its native currency.
+#[derive(Debug)]
+pub struct NoAssetsDeposit<AssetId, Recipient, Amount>(PhantomData<(AssetId, Recipient, Amount)>);
+
+impl<AssetId, Recipient, Amount> DepositAssetInto for NoAssetsDeposit<AssetId, Recipient, Amount> {
+	type AssetId = AssetId;
+	type Recipient = Recipient;
+	type Amount = Amount;
+
+	fn deposit_asset_into(_asset_id: Self::AssetId, _recipient: Self::Recipient, _amount: Self::Amount) -> Result<()> {
+		Err(Error::DepositFailed)
+	}
+}
+
 decl_runtime_apis! {
 	/// API for Rialto exchange transactions submitters.
 	pub trait RialtoCurrencyExchangeApi<Proof: Parameter> {
@@ -0,0 +1,86 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Utilities for testing code that depends on the peer blockchain currency price.
+
+use crate::{Error, PriceProvider, Result};
+
+use sp_runtime::FixedU128;
+use std::cell::RefCell;
+
+thread_local! {
+	static TEST_PRICE: RefCell<Option<FixedU128>> = RefCell::new(Some(TestPriceProvider::DEFAULT_PRICE));
+}
+
+/// Price provider that is used in tests.
+///
+/// The price is stored in the thread-local storage, so every test has its own price.
+#[derive(Debug)]
+pub struct TestPriceProvider;
+
+impl TestPriceProvider {
+	/// Price that is returned if it hasn't been changed by the test.
+	pub const DEFAULT_PRICE: FixedU128 = FixedU128::from_inner(2 * 1_000_000_000_000_000_000);
+
+	/// Change the price. If `None` is given, the price is considered stale.
+	pub fn set_price(price: Option<FixedU128>) {
+		TEST_PRICE.with(|test_price| *test_price.borrow_mut() = price);
+	}
+}
+
+impl PriceProvider for TestPriceProvider {
+	fn price() -> Result<FixedU128> {
+		TEST_PRICE
+			.with(|test_price| *test_price.borrow())
+			.ok_or(Error::StalePrice)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{CurrencyConverter, PriceProviderCurrencyConverter};
+	use sp_runtime::FixedPointNumber;
+
+	#[test]
+	fn amounts_are_converted_in_both_directions() {
+		assert_eq!(TestPriceProvider::peer_to_this(100u64), Ok(200));
+		assert_eq!(TestPriceProvider::this_to_peer(200u64), Ok(100));
+		assert_eq!(
+			PriceProviderCurrencyConverter::<TestPriceProvider, u64>::convert(100),
+			Ok(200)
+		);
+
+		TestPriceProvider::set_price(Some(FixedU128::saturating_from_rational(1, 4)));
+		assert_eq!(TestPriceProvider::peer_to_this(100u64), Ok(25));
+		assert_eq!(TestPriceProvider::this_to_peer(25u64), Ok(100));
+	}
+
+	#[test]
+	fn conversion_fails_if_price_is_stale() {
+		TestPriceProvider::set_price(None);
+		assert_eq!(TestPriceProvider::peer_to_this(100u64), Err(Error::StalePrice));
+		assert_eq!(TestPriceProvider::this_to_peer(100u64), Err(Error::StalePrice));
+	}
+
+	#[test]
+	fn conversion_fails_on_overflow() {
+		assert_eq!(
+			TestPriceProvider::peer_to_this(u64::MAX),
+			Err(Error::FailedToConvertCurrency)
+		);
+	}
+}
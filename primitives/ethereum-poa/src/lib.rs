@@ -54,6 +54,16 @@ pub type RawTransactionReceipt = Vec<u8>;
 /// An ethereum address.
 pub type Address = H160;
 
+/// Type of EIP-2930 (access list) transactions.
+pub const ACCESS_LIST_TRANSACTION_TYPE: u8 = 0x01;
+/// Type of EIP-1559 (dynamic fee) transactions.
+pub const DYNAMIC_FEE_TRANSACTION_TYPE: u8 = 0x02;
+
+/// Number of Clique header extra data bytes that are reserved for signer vanity.
+pub const CLIQUE_EXTRA_VANITY: usize = 32;
+/// Number of Clique header extra data bytes that are reserved for signer seal.
+pub const CLIQUE_EXTRA_SEAL: usize = 65;
+
 pub mod signatures;
 
 /// Complete header id.
@@ -112,6 +122,9 @@ pub struct Transaction {
 }
 
 /// Unsigned portion of ethereum transaction.
+///
+/// Typed (EIP-2718) transactions are decoded into the same structure. The `gas_price` of EIP-1559
+/// transaction is its `max_fee_per_gas`.
 #[derive(Clone, PartialEq, RuntimeDebug)]
 pub struct UnsignedTransaction {
 	/// Sender nonce.
@@ -139,6 +152,8 @@ pub struct Receipt {
 	pub logs: Vec<LogEntry>,
 	/// Transaction outcome.
 	pub outcome: TransactionOutcome,
+	/// Type (EIP-2718) of the transaction. None for legacy transactions.
+	pub transaction_type: Option<u8>,
 }
 
 /// Transaction outcome store in the receipt.
@@ -260,6 +275,66 @@ impl AuraHeader {
 			.and_then(|x| Rlp::new(x).as_list::<SealedEmptyStep>().ok())
 	}
 
+	/// Returns true if header has empty uncles list.
+	pub fn has_empty_uncles(&self) -> bool {
+		self.uncles_hash == keccak_256(&rlp::EMPTY_LIST_RLP).into()
+	}
+
+	/// Gets the seal hash of this Clique header.
+	///
+	/// Clique seals header by signing hash of the header, where signature is stripped from
+	/// the extra data.
+	pub fn clique_seal_hash(&self) -> Option<H256> {
+		let unsealed_extra_data_len = self.extra_data.len().checked_sub(CLIQUE_EXTRA_SEAL)?;
+		let mut unsealed_header = self.clone();
+		unsealed_header.extra_data.truncate(unsealed_extra_data_len);
+		Some(keccak_256(&unsealed_header.rlp(true)).into())
+	}
+
+	/// Get Clique header signature.
+	pub fn clique_signature(&self) -> Option<H520> {
+		let seal_begin = self.extra_data.len().checked_sub(CLIQUE_EXTRA_SEAL)?;
+		Some(H520::from_slice(&self.extra_data[seal_begin..]))
+	}
+
+	/// Get signers list from the Clique header extra data.
+	///
+	/// Signers list is only included in epoch checkpoint headers. Returns None if extra data
+	/// has invalid size.
+	pub fn clique_signers(&self) -> Option<Vec<Address>> {
+		let signers_len = self
+			.extra_data
+			.len()
+			.checked_sub(CLIQUE_EXTRA_VANITY + CLIQUE_EXTRA_SEAL)?;
+		if signers_len % Address::len_bytes() != 0 {
+			return None;
+		}
+
+		Some(
+			self.extra_data[CLIQUE_EXTRA_VANITY..CLIQUE_EXTRA_VANITY + signers_len]
+				.chunks(Address::len_bytes())
+				.map(Address::from_slice)
+				.collect(),
+		)
+	}
+
+	/// Get Clique header mix digest.
+	pub fn clique_mix_digest(&self) -> Option<H256> {
+		self.seal.get(0).and_then(|x| Rlp::new(x).as_val().ok())
+	}
+
+	/// Get Clique header nonce (used for voting).
+	pub fn clique_nonce(&self) -> Option<u64> {
+		let nonce = self.seal.get(1).and_then(|x| Rlp::new(x).data().ok())?;
+		if nonce.len() != 8 {
+			return None;
+		}
+
+		let mut raw_nonce = [0u8; 8];
+		raw_nonce.copy_from_slice(nonce);
+		Some(u64::from_be_bytes(raw_nonce))
+	}
+
 	/// Returns header RLP with or without seals.
 	fn rlp(&self, with_seal: bool) -> Bytes {
 		let mut s = RlpStream::new();
@@ -293,21 +368,88 @@ impl AuraHeader {
 	}
 }
 
+/// Positions of fields within transaction RLP.
+struct TransactionLayout {
+	nonce: usize,
+	gas_price: usize,
+	gas: usize,
+	to: usize,
+	value: usize,
+	payload: usize,
+	/// Position of the first signature field (`v` or `y_parity`). All fields before the signature
+	/// are signed by the sender.
+	signature: usize,
+}
+
+impl TransactionLayout {
+	/// Returns layout of transaction of given type.
+	fn new(transaction_type: Option<u8>) -> Result<Self, DecoderError> {
+		match transaction_type {
+			None => Ok(TransactionLayout {
+				nonce: 0,
+				gas_price: 1,
+				gas: 2,
+				to: 3,
+				value: 4,
+				payload: 5,
+				signature: 6,
+			}),
+			// chain_id, nonce, gas_price, gas, to, value, payload, access_list, y_parity, r, s
+			Some(ACCESS_LIST_TRANSACTION_TYPE) => Ok(TransactionLayout {
+				nonce: 1,
+				gas_price: 2,
+				gas: 3,
+				to: 4,
+				value: 5,
+				payload: 6,
+				signature: 8,
+			}),
+			// chain_id, nonce, max_priority_fee_per_gas, max_fee_per_gas, gas, to, value, payload,
+			// access_list, y_parity, r, s
+			Some(DYNAMIC_FEE_TRANSACTION_TYPE) => Ok(TransactionLayout {
+				nonce: 1,
+				gas_price: 3,
+				gas: 4,
+				to: 5,
+				value: 6,
+				payload: 7,
+				signature: 9,
+			}),
+			Some(_) => Err(DecoderError::Custom("Unsupported transaction type")),
+		}
+	}
+}
+
+/// Split EIP-2718 typed envelope (of transaction or receipt) into type and payload. Legacy
+/// transactions and receipts are RLP lists, so they have no type.
+fn split_typed_envelope(raw: &[u8]) -> Result<(Option<u8>, &[u8]), DecoderError> {
+	match raw.first() {
+		Some(&envelope_type) if envelope_type <= 0x7f => Ok((Some(envelope_type), &raw[1..])),
+		Some(_) => Ok((None, raw)),
+		None => Err(DecoderError::RlpIsTooShort),
+	}
+}
+
 impl UnsignedTransaction {
 	/// Decode unsigned portion of raw transaction RLP.
 	pub fn decode_rlp(raw_tx: &[u8]) -> Result<Self, DecoderError> {
-		let tx_rlp = Rlp::new(raw_tx);
-		let to = tx_rlp.at(3)?;
+		let (transaction_type, tx_payload) = split_typed_envelope(raw_tx)?;
+		Self::decode_with_layout(&Rlp::new(tx_payload), &TransactionLayout::new(transaction_type)?)
+	}
+
+	/// Decode unsigned portion of transaction RLP with given layout.
+	fn decode_with_layout(tx_rlp: &Rlp, layout: &TransactionLayout) -> Result<Self, DecoderError> {
+		let to = tx_rlp.at(layout.to)?;
 		Ok(UnsignedTransaction {
-			nonce: tx_rlp.val_at(0)?,
-			gas_price: tx_rlp.val_at(1)?,
-			gas: tx_rlp.val_at(2)?,
+			nonce: tx_rlp.val_at(layout.nonce)?,
+			gas_price: tx_rlp.val_at(layout.gas_price)?,
+			gas: tx_rlp.val_at(layout.gas)?,
 			to: match to.is_empty() {
 				false => Some(to.as_val()?),
 				true => None,
 			},
-			value: tx_rlp.val_at(4)?,
-			payload: tx_rlp.val_at(5)?,
+			value: tx_rlp.val_at(layout.value)?,
+			payload: tx_rlp.val_at(layout.payload)?,
 		})
 	}
 
@@ -345,6 +487,7 @@ impl UnsignedTransaction {
 impl Receipt {
 	/// Decode status from raw transaction receipt RLP.
 	pub fn is_successful_raw_receipt(raw_receipt: &[u8]) -> Result<bool, DecoderError> {
+		let (_, raw_receipt) = split_typed_envelope(raw_receipt)?;
 		let rlp = Rlp::new(raw_receipt);
 		if rlp.item_count()? == 3 {
 			// no outcome - invalid tx?
@@ -362,7 +505,34 @@ impl Receipt {
 		}
 	}
 
-	/// Returns receipt RLP.
+	/// Decode raw transaction receipt RLP.
+	pub fn decode_rlp(raw_receipt: &[u8]) -> Result<Self, DecoderError> {
+		let (transaction_type, raw_receipt) = split_typed_envelope(raw_receipt)?;
+		let rlp = Rlp::new(raw_receipt);
+		let (outcome, first_field) = match rlp.item_count()? {
+			3 => (TransactionOutcome::Unknown, 0),
+			4 => {
+				let first = rlp.at(0)?;
+				if first.is_data() && first.data()?.len() <= 1 {
+					(TransactionOutcome::StatusCode(first.as_val()?), 1)
+				} else {
+					(TransactionOutcome::StateRoot(first.as_val()?), 1)
+				}
+			}
+			_ => return Err(DecoderError::RlpIncorrectListLen),
+		};
+		let log_bloom: EthBloom = rlp.val_at(first_field + 1)?;
+
+		Ok(Receipt {
+			gas_used: rlp.val_at(first_field)?,
+			log_bloom: Bloom(*log_bloom.data()),
+			logs: rlp.list_at(first_field + 2)?,
+			outcome,
+			transaction_type,
+		})
+	}
+
+	/// Returns receipt RLP. Receipts of typed transactions are prefixed with transaction type.
 	pub fn rlp(&self) -> Bytes {
 		let mut s = RlpStream::new();
 		match self.outcome {
@@ -392,7 +562,16 @@ impl Receipt {
 			s.append(&log.data);
 		}
 
-		s.out()
+		let receipt_rlp = s.out();
+		match self.transaction_type {
+			Some(transaction_type) => {
+				let mut typed_receipt = Vec::with_capacity(1 + receipt_rlp.len());
+				typed_receipt.push(transaction_type);
+				typed_receipt.extend_from_slice(&receipt_rlp);
+				typed_receipt
+			}
+			None => receipt_rlp,
+		}
 	}
 }
 
@@ -425,6 +604,16 @@ impl Decodable for SealedEmptyStep {
 	}
 }
 
+impl Decodable for LogEntry {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		Ok(LogEntry {
+			address: rlp.val_at(0)?,
+			topics: rlp.list_at(1)?,
+			data: rlp.val_at(2)?,
+		})
+	}
+}
+
 impl LogEntry {
 	/// Calculates the bloom of this log entry.
 	pub fn bloom(&self) -> Bloom {
@@ -474,27 +663,47 @@ impl std::fmt::Debug for Bloom {
 /// Decode Ethereum transaction.
 pub fn transaction_decode_rlp(raw_tx: &[u8]) -> Result<Transaction, DecoderError> {
 	// parse transaction fields
-	let unsigned = UnsignedTransaction::decode_rlp(raw_tx)?;
-	let tx_rlp = Rlp::new(raw_tx);
-	let v: u64 = tx_rlp.val_at(6)?;
-	let r: U256 = tx_rlp.val_at(7)?;
-	let s: U256 = tx_rlp.val_at(8)?;
-
-	// reconstruct signature
+	let (transaction_type, tx_payload) = split_typed_envelope(raw_tx)?;
+	let layout = TransactionLayout::new(transaction_type)?;
+	let tx_rlp = Rlp::new(tx_payload);
+	let unsigned = UnsignedTransaction::decode_with_layout(&tx_rlp, &layout)?;
+	let v: u64 = tx_rlp.val_at(layout.signature)?;
+	let r: U256 = tx_rlp.val_at(layout.signature + 1)?;
+	let s: U256 = tx_rlp.val_at(layout.signature + 2)?;
+
+	// reconstruct signature and message that has been signed
 	let mut signature = [0u8; 65];
-	let (chain_id, v) = match v {
-		v if v == 27u64 => (None, 0),
-		v if v == 28u64 => (None, 1),
-		v if v >= 35u64 => (Some((v - 35) / 2), ((v - 1) % 2) as u8),
-		_ => (None, 4),
+	let (message, v) = match transaction_type {
+		None => {
+			let (chain_id, v) = match v {
+				v if v == 27u64 => (None, 0),
+				v if v == 28u64 => (None, 1),
+				v if v >= 35u64 => (Some((v - 35) / 2), ((v - 1) % 2) as u8),
+				_ => (None, 4),
+			};
+			(unsigned.message(chain_id), v)
+		}
+		Some(transaction_type) => {
+			// typed transaction message is the keccak(type || rlp(all fields before signature))
+			let mut message_stream = RlpStream::new_list(layout.signature);
+			for field_index in 0..layout.signature {
+				message_stream.append_raw(tx_rlp.at(field_index)?.as_raw(), 1);
+			}
+			let mut message = Vec::with_capacity(1 + tx_payload.len());
+			message.push(transaction_type);
+			message.extend_from_slice(&message_stream.out());
+
+			let v = match v {
+				0 | 1 => v as u8,
+				_ => 4,
+			};
+			(keccak_256(&message).into(), v)
+		}
 	};
 	r.to_big_endian(&mut signature[0..32]);
 	s.to_big_endian(&mut signature[32..64]);
 	signature[64] = v;
 
-	// reconstruct message that has been signed
-	let message = unsigned.message(chain_id);
-
 	// recover tx sender
 	let sender_public = sp_io::crypto::secp256k1_ecdsa_recover(&signature, &message.as_fixed_bytes())
 		.map_err(|_| rlp::DecoderError::Custom("Failed to recover transaction sender"))?;
@@ -677,6 +886,7 @@ mod tests {
 					gas_used: Default::default(),
 					log_bloom: Default::default(),
 					logs: Vec::new(),
+					transaction_type: None,
 				}
 				.rlp()
 			),
@@ -689,6 +899,7 @@ mod tests {
 					gas_used: Default::default(),
 					log_bloom: Default::default(),
 					logs: Vec::new(),
+					transaction_type: None,
 				}
 				.rlp()
 			),
@@ -701,6 +912,7 @@ mod tests {
 					gas_used: Default::default(),
 					log_bloom: Default::default(),
 					logs: Vec::new(),
+					transaction_type: None,
 				}
 				.rlp()
 			),
@@ -713,6 +925,7 @@ mod tests {
 					gas_used: Default::default(),
 					log_bloom: Default::default(),
 					logs: Vec::new(),
+					transaction_type: None,
 				}
 				.rlp()
 			),
@@ -720,6 +933,64 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn receipt_decode_rlp_works() {
+		assert!(Receipt::decode_rlp(&[]).is_err());
+
+		for outcome in vec![
+			TransactionOutcome::Unknown,
+			TransactionOutcome::StateRoot([42u8; 32].into()),
+			TransactionOutcome::StatusCode(0),
+			TransactionOutcome::StatusCode(1),
+		] {
+			for transaction_type in vec![
+				None,
+				Some(ACCESS_LIST_TRANSACTION_TYPE),
+				Some(DYNAMIC_FEE_TRANSACTION_TYPE),
+			] {
+				let log = LogEntry {
+					address: [1u8; 20].into(),
+					topics: vec![[2u8; 32].into(), [3u8; 32].into()],
+					data: vec![4, 5, 6],
+				};
+				let receipt = Receipt {
+					outcome: outcome.clone(),
+					gas_used: 21_000.into(),
+					log_bloom: log.bloom(),
+					logs: vec![log],
+					transaction_type,
+				};
+				assert_eq!(Receipt::decode_rlp(&receipt.rlp()), Ok(receipt));
+			}
+		}
+	}
+
+	#[test]
+	fn is_successful_raw_typed_receipt_works() {
+		let receipt = |status_code| {
+			Receipt {
+				outcome: TransactionOutcome::StatusCode(status_code),
+				gas_used: Default::default(),
+				log_bloom: Default::default(),
+				logs: Vec::new(),
+				transaction_type: Some(DYNAMIC_FEE_TRANSACTION_TYPE),
+			}
+			.rlp()
+		};
+
+		assert_eq!(receipt(1)[0], DYNAMIC_FEE_TRANSACTION_TYPE);
+		assert_eq!(Receipt::is_successful_raw_receipt(&receipt(0)), Ok(false));
+		assert_eq!(Receipt::is_successful_raw_receipt(&receipt(1)), Ok(true));
+	}
+
+	#[test]
+	fn unsupported_typed_transaction_rejected() {
+		assert_eq!(
+			transaction_decode_rlp(&[0x03, 0xc0]),
+			Err(DecoderError::Custom("Unsupported transaction type")),
+		);
+	}
+
 	#[test]
 	fn is_successful_raw_receipt_with_empty_data() {
 		let mut stream = RlpStream::new();
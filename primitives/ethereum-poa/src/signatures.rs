@@ -23,8 +23,8 @@
 pub use secp256k1::SecretKey;
 
 use crate::{
-	public_to_address, rlp_encode, step_validator, Address, AuraHeader, RawTransaction, UnsignedTransaction, H256,
-	H520, U256,
+	public_to_address, rlp_encode, step_validator, Address, AuraHeader, RawTransaction, UnsignedTransaction,
+	CLIQUE_EXTRA_SEAL, H256, H520, U256,
 };
 
 use secp256k1::{Message, PublicKey};
@@ -35,6 +35,9 @@ pub trait SignHeader {
 	fn sign_by(self, author: &SecretKey) -> AuraHeader;
 	/// Signs header by given authors set.
 	fn sign_by_set(self, authors: &[SecretKey]) -> AuraHeader;
+	/// Signs Clique header by given signer. Last `CLIQUE_EXTRA_SEAL` bytes of the header extra
+	/// data are replaced with the signature.
+	fn sign_clique_by(self, signer: &SecretKey) -> AuraHeader;
 }
 
 /// Utilities for signing transactions.
@@ -58,6 +61,14 @@ impl SignHeader for AuraHeader {
 		let author = step_validator(authors, step);
 		self.sign_by(author)
 	}
+
+	fn sign_clique_by(mut self, signer: &SecretKey) -> Self {
+		let message = self.clique_seal_hash().unwrap();
+		let signature = sign(signer, message);
+		let seal_begin = self.extra_data.len() - CLIQUE_EXTRA_SEAL;
+		self.extra_data[seal_begin..].copy_from_slice(signature.as_bytes());
+		self
+	}
 }
 
 impl SignTransaction for UnsignedTransaction {
@@ -98,7 +109,7 @@ pub fn secret_to_address(secret: &SecretKey) -> Address {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::{transaction_decode_rlp, Transaction};
+	use crate::{transaction_decode_rlp, Transaction, ACCESS_LIST_TRANSACTION_TYPE, DYNAMIC_FEE_TRANSACTION_TYPE};
 
 	#[test]
 	fn transaction_signed_properly() {
@@ -140,4 +151,58 @@ mod tests {
 			}),
 		);
 	}
+
+	#[test]
+	fn typed_transaction_signed_properly() {
+		let signer = SecretKey::parse(&[1u8; 32]).unwrap();
+		let signer_address = secret_to_address(&signer);
+		let unsigned = UnsignedTransaction {
+			nonce: 100.into(),
+			gas_price: 200.into(),
+			gas: 300.into(),
+			to: Some([42u8; 20].into()),
+			value: 400.into(),
+			payload: vec![1, 2, 3],
+		};
+
+		for transaction_type in vec![ACCESS_LIST_TRANSACTION_TYPE, DYNAMIC_FEE_TRANSACTION_TYPE] {
+			let is_dynamic_fee = transaction_type == DYNAMIC_FEE_TRANSACTION_TYPE;
+			let append_unsigned = |stream: &mut rlp::RlpStream| {
+				stream.append(&42u64);
+				stream.append(&unsigned.nonce);
+				if is_dynamic_fee {
+					stream.append(&U256::from(150));
+				}
+				stream.append(&unsigned.gas_price);
+				stream.append(&unsigned.gas);
+				stream.append(&unsigned.to.unwrap());
+				stream.append(&unsigned.value);
+				stream.append(&unsigned.payload);
+				stream.begin_list(0);
+			};
+			let unsigned_fields = if is_dynamic_fee { 9 } else { 8 };
+
+			let mut stream = rlp::RlpStream::new_list(unsigned_fields);
+			append_unsigned(&mut stream);
+			let mut message = vec![transaction_type];
+			message.extend_from_slice(&stream.out());
+			let signature = sign(&signer, sp_io::hashing::keccak_256(&message).into());
+
+			let mut stream = rlp::RlpStream::new_list(unsigned_fields + 3);
+			append_unsigned(&mut stream);
+			stream.append(&signature.as_fixed_bytes()[64]);
+			stream.append(&U256::from_big_endian(&signature.as_fixed_bytes()[..32]));
+			stream.append(&U256::from_big_endian(&signature.as_fixed_bytes()[32..64]));
+			let mut raw_tx = vec![transaction_type];
+			raw_tx.extend_from_slice(&stream.out());
+
+			assert_eq!(
+				transaction_decode_rlp(&raw_tx),
+				Ok(Transaction {
+					sender: signer_address,
+					unsigned: unsigned.clone(),
+				}),
+			);
+		}
+	}
 }
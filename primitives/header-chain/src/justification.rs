@@ -0,0 +1,502 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Module for checking Grandpa Finality Proofs.
+//!
+//! Adapted copy of substrate/client/finality-grandpa/src/justification.rs. If origin
+//! will ever be moved to the sp_finality_grandpa, we should reuse that implementation.
+//!
+//! The code is `no_std`-compatible, so it may be used both by runtime (pallets) and by
+//! the relay to pre-validate justifications before submitting them.
+
+use crate::{AuthoritySet, FinalityProof, FinalityVerifier};
+
+use finality_grandpa::{voter_set::VoterSet, Chain, Error as GrandpaError};
+use frame_support::weights::Weight;
+use parity_scale_codec::{Decode, Encode};
+use sp_finality_grandpa::{AuthorityId, AuthoritySignature};
+use sp_runtime::traits::Header as HeaderT;
+use sp_runtime::RuntimeDebug;
+use sp_std::collections::{btree_map::BTreeMap, btree_set::BTreeSet};
+use sp_std::{marker::PhantomData, prelude::Vec};
+
+/// Base weight of justification verification.
+// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+pub const JUSTIFICATION_BASE_WEIGHT: Weight = 10_000_000;
+
+/// Weight of single precommit verification. It includes verification of the precommit
+/// signature, which is the most expensive part of justification verification.
+// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+pub const PRECOMMIT_VERIFICATION_WEIGHT: Weight = 50_000_000;
+
+/// Weight of processing single header from votes ancestries.
+// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+pub const VOTES_ANCESTRY_WEIGHT: Weight = 1_000_000;
+
+/// Justification verification error.
+#[derive(RuntimeDebug, PartialEq)]
+pub enum Error {
+	/// Failed to decode justification.
+	JustificationDecode,
+	/// The authority set that is used to verify justification is invalid (e.g. it is empty).
+	InvalidAuthoritySet,
+	/// Justification is finalizing unexpected header.
+	InvalidJustificationTarget,
+	/// Invalid commit in justification.
+	InvalidJustificationCommit,
+	/// Justification has invalid authority singature.
+	InvalidAuthoritySignature,
+	/// The justification has precommit for the header that has no route from the target header.
+	InvalidPrecommitAncestryProof,
+	/// The justification has 'unused' headers in its precommit ancestries.
+	InvalidPrecommitAncestries,
+	/// The justification has more precommits than the authority set may produce.
+	TooManyPrecommits,
+}
+
+/// A Grandpa Justification is a proof that a given header was finalized
+/// at a certain height and with a certain set of authorities.
+///
+/// This particular proof is used to prove that headers on a bridged chain
+/// (so not our chain) have been finalized correctly.
+#[derive(Encode, Decode, RuntimeDebug)]
+pub struct GrandpaJustification<Header: HeaderT> {
+	/// The round (voting period) this justification is valid for.
+	pub round: u64,
+	/// The set of votes for the chain which is to be finalized.
+	pub commit: finality_grandpa::Commit<Header::Hash, Header::Number, AuthoritySignature, AuthorityId>,
+	/// A proof that the chain of blocks in the commit are related to each other.
+	pub votes_ancestries: Vec<Header>,
+}
+
+impl<Header: HeaderT> GrandpaJustification<Header> {
+	/// Return weight of this justification verification.
+	pub fn verification_weight(&self) -> Weight {
+		justification_verification_weight(self.commit.precommits.len() as _, self.votes_ancestries.len() as _)
+	}
+}
+
+impl<Header: HeaderT> FinalityProof<Header::Hash, Header::Number> for GrandpaJustification<Header> {
+	fn target_header(&self) -> (Header::Hash, Header::Number) {
+		(self.commit.target_hash, self.commit.target_number)
+	}
+}
+
+/// GRANDPA implementation of the `FinalityVerifier`.
+#[derive(RuntimeDebug)]
+pub struct GrandpaFinalityVerifier<Header>(PhantomData<Header>);
+
+impl<Header: HeaderT> FinalityVerifier for GrandpaFinalityVerifier<Header>
+where
+	Header::Number: finality_grandpa::BlockNumberOps,
+{
+	type Hash = Header::Hash;
+	type Number = Header::Number;
+	type AuthoritySet = AuthoritySet;
+	type FinalityProof = GrandpaJustification<Header>;
+	type Error = Error;
+
+	fn verify_finality_proof(
+		target_header: (Header::Hash, Header::Number),
+		authority_set: &AuthoritySet,
+		proof: &GrandpaJustification<Header>,
+	) -> Result<(), Error> {
+		verify_justification(target_header, authority_set, proof)
+	}
+}
+
+/// Return weight of verification of justification with given number of precommits and
+/// votes ancestries.
+pub fn justification_verification_weight(precommits: u32, votes_ancestries: u32) -> Weight {
+	JUSTIFICATION_BASE_WEIGHT
+		.saturating_add(PRECOMMIT_VERIFICATION_WEIGHT.saturating_mul(precommits as Weight))
+		.saturating_add(VOTES_ANCESTRY_WEIGHT.saturating_mul(votes_ancestries as Weight))
+}
+
+/// Decode justification target.
+pub fn decode_justification_target<Header: HeaderT>(
+	raw_justification: &[u8],
+) -> Result<(Header::Hash, Header::Number), Error> {
+	GrandpaJustification::<Header>::decode(&mut &raw_justification[..])
+		.map(|justification| justification.target_header())
+		.map_err(|_| Error::JustificationDecode)
+}
+
+/// Verify that justification, that is generated by given authority set, finalizes given header.
+pub fn verify_justification<Header: HeaderT>(
+	finalized_target: (Header::Hash, Header::Number),
+	authority_set: &AuthoritySet,
+	justification: &GrandpaJustification<Header>,
+) -> Result<(), Error>
+where
+	Header::Number: finality_grandpa::BlockNumberOps,
+{
+	let voter_set = VoterSet::new(authority_set.authorities.iter().cloned()).ok_or(Error::InvalidAuthoritySet)?;
+
+	// ensure that it is justification for the expected header
+	if justification.target_header() != finalized_target {
+		return Err(Error::InvalidJustificationTarget);
+	}
+
+	// every authority may have at most one precommit and one equivocating precommit, so larger
+	// justifications are rejected before doing any expensive work
+	if justification.commit.precommits.len() > authority_set.authorities.len().saturating_mul(2) {
+		return Err(Error::TooManyPrecommits);
+	}
+
+	// validate commit of the justification (it just assumes all signatures are valid)
+	let ancestry_chain = AncestryChain::new(&justification.votes_ancestries);
+	match finality_grandpa::validate_commit(&justification.commit, &voter_set, &ancestry_chain) {
+		Ok(ref result) if result.ghost().is_some() => {}
+		_ => return Err(Error::InvalidJustificationCommit),
+	}
+
+	// now that we know that the commit is correct, check authorities signatures
+	let mut buf = Vec::new();
+	let mut visited_hashes = BTreeSet::new();
+	for signed in &justification.commit.precommits {
+		if !sp_finality_grandpa::check_message_signature_with_buffer(
+			&finality_grandpa::Message::Precommit(signed.precommit.clone()),
+			&signed.id,
+			&signed.signature,
+			justification.round,
+			authority_set.set_id,
+			&mut buf,
+		) {
+			return Err(Error::InvalidAuthoritySignature);
+		}
+
+		if justification.commit.target_hash == signed.precommit.target_hash {
+			continue;
+		}
+
+		match ancestry_chain.ancestry(justification.commit.target_hash, signed.precommit.target_hash) {
+			Ok(route) => {
+				// ancestry starts from parent hash but the precommit target hash has been visited
+				visited_hashes.insert(signed.precommit.target_hash);
+				visited_hashes.extend(route);
+			}
+			_ => {
+				// could this happen in practice? I don't think so, but original code has this check
+				return Err(Error::InvalidPrecommitAncestryProof);
+			}
+		}
+	}
+
+	let ancestry_hashes = justification
+		.votes_ancestries
+		.iter()
+		.map(|h: &Header| h.hash())
+		.collect();
+	if visited_hashes != ancestry_hashes {
+		return Err(Error::InvalidPrecommitAncestries);
+	}
+
+	Ok(())
+}
+
+/// A utility trait implementing `finality_grandpa::Chain` using a given set of headers.
+#[derive(RuntimeDebug)]
+struct AncestryChain<Header: HeaderT> {
+	ancestry: BTreeMap<Header::Hash, Header::Hash>,
+}
+
+impl<Header: HeaderT> AncestryChain<Header> {
+	fn new(ancestry: &[Header]) -> AncestryChain<Header> {
+		AncestryChain {
+			ancestry: ancestry
+				.iter()
+				.map(|header| (header.hash(), *header.parent_hash()))
+				.collect(),
+		}
+	}
+}
+
+impl<Header: HeaderT> finality_grandpa::Chain<Header::Hash, Header::Number> for AncestryChain<Header>
+where
+	Header::Number: finality_grandpa::BlockNumberOps,
+{
+	fn ancestry(&self, base: Header::Hash, block: Header::Hash) -> Result<Vec<Header::Hash>, GrandpaError> {
+		let mut route = Vec::new();
+		let mut current_hash = block;
+		loop {
+			if current_hash == base {
+				break;
+			}
+			match self.ancestry.get(&current_hash).cloned() {
+				Some(parent_hash) => {
+					current_hash = parent_hash;
+					route.push(current_hash);
+				}
+				_ => return Err(GrandpaError::NotDescendent),
+			}
+			// every step of the valid route visits new header, so the route may be longer than
+			// the ancestry only if there's a cycle
+			if route.len() > self.ancestry.len() {
+				return Err(GrandpaError::NotDescendent);
+			}
+		}
+		route.pop(); // remove the base
+
+		Ok(route)
+	}
+
+	fn best_chain_containing(&self, _block: Header::Hash) -> Option<(Header::Hash, Header::Number)> {
+		unreachable!("is only used during voting; qed")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::*;
+	use sp_keyring::Ed25519Keyring;
+
+	fn make_justification_for_header_1() -> GrandpaJustification<TestHeader> {
+		make_justification_for_header(
+			&test_header(1),
+			TEST_GRANDPA_ROUND,
+			TEST_GRANDPA_SET_ID,
+			&authority_list(),
+		)
+	}
+
+	fn authority_set() -> AuthoritySet {
+		AuthoritySet::new(authority_list(), TEST_GRANDPA_SET_ID)
+	}
+
+	#[test]
+	fn justification_with_invalid_encoding_rejected() {
+		assert_eq!(
+			decode_justification_target::<TestHeader>(&[]),
+			Err(Error::JustificationDecode),
+		);
+	}
+
+	#[test]
+	fn justification_target_is_decoded() {
+		assert_eq!(
+			decode_justification_target::<TestHeader>(&make_justification_for_header_1().encode()),
+			Ok(header_id(1)),
+		);
+	}
+
+	#[test]
+	fn justification_with_invalid_authority_set_rejected() {
+		assert_eq!(
+			verify_justification::<TestHeader>(
+				header_id(1),
+				&AuthoritySet::new(vec![], TEST_GRANDPA_SET_ID),
+				&make_justification_for_header_1(),
+			),
+			Err(Error::InvalidAuthoritySet),
+		);
+	}
+
+	#[test]
+	fn justification_with_invalid_target_rejected() {
+		assert_eq!(
+			verify_justification::<TestHeader>(header_id(2), &authority_set(), &make_justification_for_header_1()),
+			Err(Error::InvalidJustificationTarget),
+		);
+	}
+
+	#[test]
+	fn justification_with_invalid_commit_rejected() {
+		let mut justification = make_justification_for_header_1();
+		justification.commit.precommits.clear();
+
+		assert_eq!(
+			verify_justification::<TestHeader>(header_id(1), &authority_set(), &justification),
+			Err(Error::InvalidJustificationCommit),
+		);
+	}
+
+	#[test]
+	fn justification_with_invalid_authority_signature_rejected() {
+		let mut justification = make_justification_for_header_1();
+		justification.commit.precommits[0].signature = Default::default();
+
+		assert_eq!(
+			verify_justification::<TestHeader>(header_id(1), &authority_set(), &justification),
+			Err(Error::InvalidAuthoritySignature),
+		);
+	}
+
+	#[test]
+	fn justification_with_invalid_precommit_ancestry() {
+		let mut justification = make_justification_for_header_1();
+		justification.votes_ancestries.push(test_header(10));
+
+		assert_eq!(
+			verify_justification::<TestHeader>(header_id(1), &authority_set(), &justification),
+			Err(Error::InvalidPrecommitAncestries),
+		);
+	}
+
+	#[test]
+	fn justification_with_forged_signature_rejected() {
+		let mut justification = make_justification_for_header_1();
+		let target = justification.commit.precommits[0].precommit.clone();
+		let mut forged_precommit = signed_precommit(
+			Ed25519Keyring::Bob,
+			(target.target_hash, target.target_number),
+			TEST_GRANDPA_ROUND,
+			TEST_GRANDPA_SET_ID,
+		);
+		forged_precommit.id = alice();
+		justification.commit.precommits[0] = forged_precommit;
+
+		assert_eq!(
+			verify_justification::<TestHeader>(header_id(1), &authority_set(), &justification),
+			Err(Error::InvalidAuthoritySignature),
+		);
+	}
+
+	#[test]
+	fn justification_signed_for_previous_set_rejected() {
+		let justification = make_justification_for_header(
+			&test_header(1),
+			TEST_GRANDPA_ROUND,
+			TEST_GRANDPA_SET_ID - 1,
+			&authority_list(),
+		);
+
+		assert_eq!(
+			verify_justification::<TestHeader>(header_id(1), &authority_set(), &justification),
+			Err(Error::InvalidAuthoritySignature),
+		);
+	}
+
+	#[test]
+	fn justification_of_previous_set_authorities_rejected() {
+		let next_authority_set = AuthoritySet::new(
+			vec![
+				(Ed25519Keyring::Dave.public().into(), 1),
+				(Ed25519Keyring::Eve.public().into(), 1),
+				(Ed25519Keyring::Ferdie.public().into(), 1),
+			],
+			TEST_GRANDPA_SET_ID + 1,
+		);
+		let justification = make_justification_for_header(
+			&test_header(1),
+			TEST_GRANDPA_ROUND,
+			TEST_GRANDPA_SET_ID + 1,
+			&authority_list(),
+		);
+
+		assert_eq!(
+			verify_justification::<TestHeader>(header_id(1), &next_authority_set, &justification),
+			Err(Error::InvalidJustificationCommit),
+		);
+	}
+
+	#[test]
+	fn justification_with_equivocating_precommits_rejected() {
+		// Alice is voting for both forks and Charlie isn't voting at all, so there's no supermajority
+		let header = test_header(2);
+		let mut fork_header = test_header(2);
+		fork_header.state_root = [42u8; 32].into();
+		let justification = GrandpaJustification {
+			round: TEST_GRANDPA_ROUND,
+			commit: finality_grandpa::Commit {
+				target_hash: header_id(1).0,
+				target_number: header_id(1).1,
+				precommits: vec![
+					signed_precommit(
+						Ed25519Keyring::Alice,
+						(header.hash(), 2),
+						TEST_GRANDPA_ROUND,
+						TEST_GRANDPA_SET_ID,
+					),
+					signed_precommit(
+						Ed25519Keyring::Alice,
+						(fork_header.hash(), 2),
+						TEST_GRANDPA_ROUND,
+						TEST_GRANDPA_SET_ID,
+					),
+					signed_precommit(
+						Ed25519Keyring::Bob,
+						(header.hash(), 2),
+						TEST_GRANDPA_ROUND,
+						TEST_GRANDPA_SET_ID,
+					),
+				],
+			},
+			votes_ancestries: vec![header, fork_header],
+		};
+
+		assert_eq!(
+			verify_justification::<TestHeader>(header_id(1), &authority_set(), &justification),
+			Err(Error::InvalidJustificationCommit),
+		);
+	}
+
+	#[test]
+	fn justification_with_too_many_precommits_rejected_before_signatures_verification() {
+		let mut justification = make_justification_for_header_1();
+		let mut forged_precommit = justification.commit.precommits[0].clone();
+		forged_precommit.signature = Default::default();
+		justification
+			.commit
+			.precommits
+			.extend(sp_std::iter::repeat(forged_precommit).take(4));
+
+		assert_eq!(
+			verify_justification::<TestHeader>(header_id(1), &authority_set(), &justification),
+			Err(Error::TooManyPrecommits),
+		);
+	}
+
+	#[test]
+	fn ancestry_with_cycle_is_not_followed_forever() {
+		let (hash1, hash2, hash3): (TestHash, TestHash, TestHash) =
+			([1u8; 32].into(), [2u8; 32].into(), [3u8; 32].into());
+		let ancestry_chain = AncestryChain::<TestHeader> {
+			ancestry: vec![(hash1, hash2), (hash2, hash1)].into_iter().collect(),
+		};
+
+		assert!(matches!(
+			ancestry_chain.ancestry(hash3, hash1),
+			Err(GrandpaError::NotDescendent)
+		));
+	}
+
+	#[test]
+	fn valid_justification_accepted() {
+		assert_eq!(
+			verify_justification::<TestHeader>(header_id(1), &authority_set(), &make_justification_for_header_1()),
+			Ok(()),
+		);
+		assert_eq!(
+			GrandpaFinalityVerifier::<TestHeader>::verify_finality_proof(
+				header_id(1),
+				&authority_set(),
+				&make_justification_for_header_1(),
+			),
+			Ok(()),
+		);
+	}
+
+	#[test]
+	fn verification_weight_depends_on_justification_size() {
+		let justification = make_justification_for_header_1();
+		assert_eq!(
+			justification.verification_weight(),
+			JUSTIFICATION_BASE_WEIGHT + 3 * PRECOMMIT_VERIFICATION_WEIGHT + 3 * VOTES_ANCESTRY_WEIGHT,
+		);
+	}
+}
@@ -19,10 +19,21 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use bp_runtime::Size;
 use core::clone::Clone;
 use core::cmp::Eq;
+use core::default::Default;
 use core::fmt::Debug;
-use parity_scale_codec::{Codec, EncodeLike};
+use parity_scale_codec::{Codec, Decode, Encode, EncodeLike, Error as CodecError};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_finality_grandpa::{AuthorityList, SetId};
+use sp_runtime::RuntimeDebug;
+
+pub mod justification;
+
+#[cfg(any(feature = "test-helpers", test))]
+pub mod test_utils;
 
 /// A type that can be used as a parameter in a dispatchable function.
 ///
@@ -30,15 +41,69 @@ use parity_scale_codec::{Codec, EncodeLike};
 pub trait Parameter: Codec + EncodeLike + Clone + Eq + Debug {}
 impl<T> Parameter for T where T: Codec + EncodeLike + Clone + Eq + Debug {}
 
+/// A Grandpa Authority List and ID.
+#[derive(Default, Encode, Decode, RuntimeDebug, PartialEq, Clone)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct AuthoritySet {
+	/// List of Grandpa authorities for the current round.
+	pub authorities: AuthorityList,
+	/// Monotonic identifier of the current Grandpa authority set.
+	pub set_id: SetId,
+}
+
+impl AuthoritySet {
+	/// Create a new Grandpa Authority Set.
+	pub fn new(authorities: AuthorityList, set_id: SetId) -> Self {
+		Self { authorities, set_id }
+	}
+}
+
 /// A base trait for pallets which want to keep track of a full set of headers from a bridged chain.
 pub trait BaseHeaderChain {
 	/// Transaction type.
 	type Transaction: Parameter;
-	/// Transaction inclusion proof type.
-	type TransactionInclusionProof: Parameter;
+	/// Transaction inclusion proof type. The size of the proof is used to compute weight
+	/// of the proof verification.
+	type TransactionInclusionProof: Parameter + Size;
 
 	/// Verify that transaction is a part of given block.
 	///
 	/// Returns Some(transaction) if proof is valid and None otherwise.
 	fn verify_transaction_inclusion_proof(proof: &Self::TransactionInclusionProof) -> Option<Self::Transaction>;
 }
+
+/// A proof of bridged chain header finality, generated by the bridged chain finality gadget.
+pub trait FinalityProof<Hash, Number>: Decode {
+	/// Return hash and number of the header that is finalized by this proof.
+	fn target_header(&self) -> (Hash, Number);
+
+	/// Decode finality proof and return hash and number of the header that it finalizes.
+	fn decode_target_header(raw_proof: &[u8]) -> Result<(Hash, Number), CodecError> {
+		Self::decode(&mut &raw_proof[..]).map(|proof| proof.target_header())
+	}
+}
+
+/// Verifier of bridged chain finality proofs.
+///
+/// Every finality gadget (e.g. GRANDPA) that is used by the bridged chain, should have its own
+/// verifier implementation. Pallets and relays that only need to verify finality of bridged headers
+/// should be using this trait, so they could work with any finality gadget.
+pub trait FinalityVerifier {
+	/// Bridged header hash type.
+	type Hash;
+	/// Bridged header number type.
+	type Number;
+	/// Set of authorities that are generating finality proofs.
+	type AuthoritySet;
+	/// Finality proof type.
+	type FinalityProof: FinalityProof<Self::Hash, Self::Number>;
+	/// Verification error type.
+	type Error: Debug;
+
+	/// Verify that the proof (generated by given authority set) finalizes given header.
+	fn verify_finality_proof(
+		target_header: (Self::Hash, Self::Number),
+		authority_set: &Self::AuthoritySet,
+		proof: &Self::FinalityProof,
+	) -> Result<(), Self::Error>;
+}
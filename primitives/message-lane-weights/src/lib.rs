@@ -0,0 +1,190 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Weight formulas of message lane transactions.
+//!
+//! Weights of message lane transactions are linear functions of the number of messages, size
+//! of the proof and the declared dispatch weight. The same functions are used by the runtime to
+//! charge transaction submitters and by the relay to plan its transactions. So relay estimates
+//! can't drift from the actual on-chain weights.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use bp_message_lane::Weight;
+use frame_support::weights::constants::{RocksDbWeight, WEIGHT_PER_NANOS};
+
+/// Parameters of message lane transactions weight formulas.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MessageLaneWeights {
+	/// Base weight of the messages delivery transaction.
+	pub delivery_base: Weight,
+	/// Weight of receiving single message by the messages delivery transaction. It doesn't
+	/// include message dispatch weight.
+	pub delivery_per_message: Weight,
+	/// Weight of verifying single byte of the messages proof.
+	pub delivery_per_proof_byte: Weight,
+	/// Base weight of the messages delivery confirmation transaction.
+	pub confirmation_base: Weight,
+	/// Weight of verifying single byte of the messages delivery proof.
+	pub confirmation_per_proof_byte: Weight,
+	/// Base weight of the undispatched messages dispatch transaction. It doesn't include
+	/// messages dispatch weight.
+	pub dispatch_base: Weight,
+	/// Weight of saving single received, but not dispatched message to the storage.
+	pub undispatched_per_message: Weight,
+	/// Weight of saving single byte of received, but not dispatched message to the storage.
+	pub undispatched_per_byte: Weight,
+}
+
+// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
+/// Default weight formulas parameters.
+pub const DEFAULT_MESSAGE_LANE_WEIGHTS: MessageLaneWeights = MessageLaneWeights {
+	delivery_base: 0,
+	delivery_per_message: 0,
+	delivery_per_proof_byte: 0,
+	confirmation_base: 0,
+	confirmation_per_proof_byte: 0,
+	dispatch_base: 0,
+	undispatched_per_message: 0,
+	undispatched_per_byte: 0,
+};
+
+/// Weight formulas parameters of the chain that is using `RocksDb` as its database.
+///
+/// Received, but not dispatched messages are occupying the runtime storage, so saving them costs
+/// a storage write per message plus 10 ns per byte. The undispatched messages dispatch
+/// transaction reads the lane state and writes the updated state back (2 reads and 2 writes),
+/// which is included in its base weight.
+pub fn rocks_db_message_lane_weights() -> MessageLaneWeights {
+	MessageLaneWeights {
+		dispatch_base: 520_000_000,
+		undispatched_per_message: RocksDbWeight::get().writes(1),
+		undispatched_per_byte: 10 * WEIGHT_PER_NANOS,
+		..DEFAULT_MESSAGE_LANE_WEIGHTS
+	}
+}
+
+impl MessageLaneWeights {
+	/// Weight of the messages delivery transaction:
+	///
+	/// `delivery_base + messages_count * delivery_per_message + proof_size * delivery_per_proof_byte + dispatch_weight`.
+	pub fn receive_messages_proof(&self, messages_count: u32, proof_size: u32, dispatch_weight: Weight) -> Weight {
+		self.delivery_base
+			.saturating_add(self.delivery_per_message.saturating_mul(messages_count as Weight))
+			.saturating_add(self.delivery_per_proof_byte.saturating_mul(proof_size as Weight))
+			.saturating_add(dispatch_weight)
+	}
+
+	/// Weight of saving received, but not dispatched messages to the storage:
+	///
+	/// `messages_count * undispatched_per_message + messages_size * undispatched_per_byte`.
+	pub fn save_undispatched_messages(&self, messages_count: u32, messages_size: u32) -> Weight {
+		self.undispatched_per_message
+			.saturating_mul(messages_count as Weight)
+			.saturating_add(self.undispatched_per_byte.saturating_mul(messages_size as Weight))
+	}
+
+	/// Maximal weight of the messages delivery transaction, that is reserved before the proof is
+	/// verified. It assumes that none of messages is dispatched and all of them are saved to the
+	/// storage:
+	///
+	/// `receive_messages_proof(messages_count, proof_size, dispatch_weight) + save_undispatched_messages(messages_count, proof_size)`.
+	pub fn max_receive_messages_proof(&self, messages_count: u32, proof_size: u32, dispatch_weight: Weight) -> Weight {
+		self.receive_messages_proof(messages_count, proof_size, dispatch_weight)
+			.saturating_add(self.save_undispatched_messages(messages_count, proof_size))
+	}
+
+	/// Weight of the messages delivery confirmation transaction:
+	///
+	/// `confirmation_base + proof_size * confirmation_per_proof_byte`.
+	pub fn receive_messages_delivery_proof(&self, proof_size: u32) -> Weight {
+		self.confirmation_base
+			.saturating_add(self.confirmation_per_proof_byte.saturating_mul(proof_size as Weight))
+	}
+
+	/// Weight of the undispatched messages dispatch transaction:
+	///
+	/// `dispatch_base + dispatch_weight`.
+	pub fn dispatch_undispatched_messages(&self, dispatch_weight: Weight) -> Weight {
+		self.dispatch_base.saturating_add(dispatch_weight)
+	}
+
+	/// Returns true if messages delivery transaction with given parameters, would have maximal weight
+	/// that is not larger than the `max_transaction_weight`.
+	pub fn is_delivery_transaction_fit(
+		&self,
+		max_transaction_weight: Weight,
+		messages_count: u32,
+		proof_size: u32,
+		dispatch_weight: Weight,
+	) -> bool {
+		self.max_receive_messages_proof(messages_count, proof_size, dispatch_weight) <= max_transaction_weight
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TEST_WEIGHTS: MessageLaneWeights = MessageLaneWeights {
+		delivery_base: 1000,
+		delivery_per_message: 100,
+		delivery_per_proof_byte: 10,
+		confirmation_base: 2000,
+		confirmation_per_proof_byte: 20,
+		dispatch_base: 3000,
+		undispatched_per_message: 200,
+		undispatched_per_byte: 30,
+	};
+
+	#[test]
+	fn delivery_weight_is_computed() {
+		assert_eq!(TEST_WEIGHTS.receive_messages_proof(0, 0, 0), 1000);
+		assert_eq!(TEST_WEIGHTS.receive_messages_proof(2, 5, 7), 1000 + 200 + 50 + 7);
+		assert_eq!(
+			TEST_WEIGHTS.receive_messages_proof(u32::MAX, u32::MAX, Weight::MAX),
+			Weight::MAX
+		);
+	}
+
+	#[test]
+	fn undispatched_messages_weight_is_reserved() {
+		assert_eq!(TEST_WEIGHTS.save_undispatched_messages(2, 5), 400 + 150);
+		assert_eq!(
+			TEST_WEIGHTS.max_receive_messages_proof(2, 5, 7),
+			1000 + 200 + 50 + 7 + 400 + 150
+		);
+	}
+
+	#[test]
+	fn confirmation_and_dispatch_weights_are_computed() {
+		assert_eq!(TEST_WEIGHTS.receive_messages_delivery_proof(5), 2000 + 100);
+		assert_eq!(TEST_WEIGHTS.dispatch_undispatched_messages(7), 3000 + 7);
+	}
+
+	#[test]
+	fn undispatched_message_costs_rocks_db_write() {
+		let weights = rocks_db_message_lane_weights();
+		assert_eq!(weights.save_undispatched_messages(1, 0), 100_000_000);
+		assert_eq!(weights.save_undispatched_messages(1, 10), 100_000_000 + 10 * 10_000);
+	}
+
+	#[test]
+	fn delivery_transaction_fit_is_checked() {
+		assert!(TEST_WEIGHTS.is_delivery_transaction_fit(1807, 2, 5, 7));
+		assert!(!TEST_WEIGHTS.is_delivery_transaction_fit(1806, 2, 5, 7));
+	}
+}
@@ -0,0 +1,190 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Lane identifiers.
+//!
+//! Lane id is a pair of application namespace and lane index within this namespace. Every team
+//! (application) allocates lanes within its own namespace, so lanes of different applications
+//! never collide.
+//!
+//! Lane id is encoded as 4 raw bytes (2 bytes of namespace, followed by 2 bytes of big-endian
+//! lane index), so it is compatible with the legacy `[u8; 4]` lane ids. Legacy lanes belong to
+//! the `LaneNamespace::LEGACY` namespace.
+
+use codec::{Decode, Encode};
+use frame_support::RuntimeDebug;
+use sp_std::{fmt, str::FromStr};
+
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+/// Namespace of lanes.
+#[derive(Encode, Decode, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, RuntimeDebug)]
+pub struct LaneNamespace(pub [u8; 2]);
+
+impl LaneNamespace {
+	/// Namespace of lanes that have been created before lanes namespacing has been introduced.
+	pub const LEGACY: LaneNamespace = LaneNamespace([0, 0]);
+	/// Namespace of lanes that are used to transfer tokens.
+	pub const TOKEN: LaneNamespace = LaneNamespace(*b"tk");
+	/// Namespace of lanes that are used by the chains governance.
+	pub const GOVERNANCE: LaneNamespace = LaneNamespace(*b"gv");
+}
+
+/// Lane identifier.
+#[derive(Encode, Decode, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(from = "[u8; 4]", into = "[u8; 4]"))]
+pub struct LaneId {
+	namespace: LaneNamespace,
+	index: [u8; 2],
+}
+
+impl LaneId {
+	/// Create lane id from namespace and index of the lane within this namespace.
+	pub const fn new(namespace: LaneNamespace, index: u16) -> Self {
+		LaneId {
+			namespace,
+			index: index.to_be_bytes(),
+		}
+	}
+
+	/// Create lane id from its raw (encoded) bytes.
+	pub const fn from_bytes(bytes: [u8; 4]) -> Self {
+		LaneId {
+			namespace: LaneNamespace([bytes[0], bytes[1]]),
+			index: [bytes[2], bytes[3]],
+		}
+	}
+
+	/// Return raw (encoded) bytes of the lane id.
+	pub const fn to_bytes(&self) -> [u8; 4] {
+		[self.namespace.0[0], self.namespace.0[1], self.index[0], self.index[1]]
+	}
+
+	/// Return namespace of the lane.
+	pub const fn namespace(&self) -> LaneNamespace {
+		self.namespace
+	}
+
+	/// Return index of the lane within its namespace.
+	pub const fn index(&self) -> u16 {
+		u16::from_be_bytes(self.index)
+	}
+}
+
+impl From<[u8; 4]> for LaneId {
+	fn from(bytes: [u8; 4]) -> Self {
+		LaneId::from_bytes(bytes)
+	}
+}
+
+impl From<LaneId> for [u8; 4] {
+	fn from(lane: LaneId) -> Self {
+		lane.to_bytes()
+	}
+}
+
+/// Lane id is displayed as 8-digit hex string of its raw bytes.
+impl fmt::Display for LaneId {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for byte in self.to_bytes().iter() {
+			write!(f, "{:02x}", byte)?;
+		}
+		Ok(())
+	}
+}
+
+/// Lane id is parsed either from 8-digit hex string of its raw bytes (e.g. `00000001`), or from
+/// the `<namespace>:<index>` string, where namespace is 4-digit hex string and the index is
+/// decimal number (e.g. `746b:1` for the second lane of the `LaneNamespace::TOKEN` namespace).
+impl FromStr for LaneId {
+	type Err = &'static str;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		fn parse_hex<'a>(s: &str, bytes: impl IntoIterator<Item = &'a mut u8>) -> Result<(), &'static str> {
+			for (index, byte) in bytes.into_iter().enumerate() {
+				*byte = s
+					.get(index * 2..index * 2 + 2)
+					.and_then(|digits| u8::from_str_radix(digits, 16).ok())
+					.ok_or("Invalid hex digits in lane id")?;
+			}
+			Ok(())
+		}
+
+		if let Some(separator) = s.find(':') {
+			let (namespace, index) = (&s[..separator], &s[separator + 1..]);
+			if namespace.len() != 4 {
+				return Err("Lane namespace must have exactly 4 hex digits");
+			}
+
+			let mut raw_namespace = [0u8; 2];
+			parse_hex(namespace, raw_namespace.iter_mut())?;
+			let index = index.parse().map_err(|_| "Invalid lane index")?;
+			return Ok(LaneId::new(LaneNamespace(raw_namespace), index));
+		}
+
+		if s.len() != 8 {
+			return Err("Hex lane id must have exactly 8 digits");
+		}
+
+		let mut bytes = [0u8; 4];
+		parse_hex(s, bytes.iter_mut())?;
+		Ok(LaneId::from_bytes(bytes))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn lane_id_is_compatible_with_legacy_lane_id() {
+		let lane = LaneId::from_bytes([0, 0, 0, 1]);
+		assert_eq!(lane.encode(), [0u8, 0, 0, 1].encode());
+		assert_eq!(LaneId::decode(&mut &[0u8, 0, 0, 1][..]), Ok(lane));
+		assert_eq!(lane.namespace(), LaneNamespace::LEGACY);
+		assert_eq!(lane.index(), 1);
+		assert_eq!(lane, LaneId::new(LaneNamespace::LEGACY, 1));
+	}
+
+	#[test]
+	fn lane_ids_are_ordered_as_legacy_lane_ids() {
+		assert!(LaneId::from_bytes([0, 0, 0, 255]) < LaneId::from_bytes([0, 0, 1, 0]));
+		assert!(LaneId::from_bytes([0, 1, 0, 0]) < LaneId::from_bytes([1, 0, 0, 0]));
+		assert!(LaneId::new(LaneNamespace::LEGACY, u16::MAX) < LaneId::new(LaneNamespace::GOVERNANCE, 0));
+	}
+
+	#[test]
+	fn lane_id_is_displayed_and_parsed() {
+		let lane = LaneId::new(LaneNamespace::TOKEN, 258);
+		assert_eq!(lane.to_string(), "746b0102");
+		assert_eq!("746b0102".parse(), Ok(lane));
+		assert_eq!("746b:258".parse(), Ok(lane));
+
+		assert!("746b01".parse::<LaneId>().is_err());
+		assert!("746b01zz".parse::<LaneId>().is_err());
+		assert!("74:258".parse::<LaneId>().is_err());
+		assert!("746b:65536".parse::<LaneId>().is_err());
+	}
+
+	#[test]
+	fn lane_id_is_serialized_as_legacy_lane_id() {
+		let lane = LaneId::from_bytes([0, 0, 0, 1]);
+		assert_eq!(serde_json::to_string(&lane).unwrap(), "[0,0,0,1]");
+		assert_eq!(serde_json::from_str::<LaneId>("[0,0,0,1]").unwrap(), lane);
+	}
+}
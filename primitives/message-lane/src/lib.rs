@@ -22,20 +22,22 @@
 // Generated by `DecodeLimit::decode_with_depth_limit`
 #![allow(clippy::unnecessary_mut_passed)]
 
+use bp_runtime::InstanceId;
 use codec::{Decode, Encode};
-use frame_support::RuntimeDebug;
+use frame_support::{Parameter, RuntimeDebug};
 use sp_api::decl_runtime_apis;
 use sp_std::{collections::vec_deque::VecDeque, prelude::*};
 
 pub mod source_chain;
 pub mod target_chain;
 
+mod lane_id;
+
+pub use lane_id::{LaneId, LaneNamespace};
+
 // Weight is reexported to avoid additional frame-support dependencies in message-lane related crates.
 pub use frame_support::weights::Weight;
 
-/// Lane identifier.
-pub type LaneId = [u8; 4];
-
 /// Message nonce. Valid messages will never have 0 nonce.
 pub type MessageNonce = u64;
 
@@ -45,6 +47,31 @@ pub type MessageId = (LaneId, MessageNonce);
 /// Opaque message payload. We only decode this payload when it is dispatched.
 pub type MessagePayload = Vec<u8>;
 
+/// Version of the message lane pallet storage encoding.
+///
+/// `MessageData`, `InboundLaneData` and `OutboundLaneData` are stored in the message lane pallet
+/// storage and are read from storage proofs by the bridged chain. So whenever encoding of any of
+/// these structures is changed, the storage version must be bumped, the pallet storage must be
+/// migrated and `DecodeVersioned` implementations must be able to decode values of all previous
+/// versions.
+pub type StorageVersion = u32;
+
+/// Storage version of the message lane pallet that hasn't been tracking its storage version yet.
+/// Encoding of values matches the `STORAGE_VERSION_V1` encoding.
+pub const LEGACY_STORAGE_VERSION: StorageVersion = 0;
+
+/// First explicit version of the message lane pallet storage.
+pub const STORAGE_VERSION_V1: StorageVersion = 1;
+
+/// Current version of the message lane pallet storage.
+pub const CURRENT_STORAGE_VERSION: StorageVersion = STORAGE_VERSION_V1;
+
+/// Decoding of values that have been encoded using given version of the message lane pallet storage.
+pub trait DecodeVersioned: Sized {
+	/// Decode value that has been encoded using given storage version.
+	fn decode_versioned(version: StorageVersion, encoded: &[u8]) -> Result<Self, codec::Error>;
+}
+
 /// Message key (unique message identifier) as it is stored in the storage.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
 pub struct MessageKey {
@@ -103,6 +130,17 @@ impl<RelayerId> Default for InboundLaneData<RelayerId> {
 	}
 }
 
+/// Relayer that has delivered messages over the inbound lane, but hasn't been rewarded yet.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct UnrewardedRelayer<RelayerId> {
+	/// Identifier of the relayer.
+	pub relayer: RelayerId,
+	/// Nonce of the first message, delivered by the relayer.
+	pub begin: MessageNonce,
+	/// Nonce of the last message, delivered by the relayer.
+	pub end: MessageNonce,
+}
+
 /// Outbound lane data.
 #[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
 pub struct OutboundLaneData {
@@ -126,29 +164,221 @@ impl Default for OutboundLaneData {
 	}
 }
 
+impl<Fee: Decode> DecodeVersioned for MessageData<Fee> {
+	fn decode_versioned(version: StorageVersion, encoded: &[u8]) -> Result<Self, codec::Error> {
+		decode_v1(version, encoded)
+	}
+}
+
+impl<RelayerId: Decode> DecodeVersioned for InboundLaneData<RelayerId> {
+	fn decode_versioned(version: StorageVersion, encoded: &[u8]) -> Result<Self, codec::Error> {
+		decode_v1(version, encoded)
+	}
+}
+
+impl DecodeVersioned for OutboundLaneData {
+	fn decode_versioned(version: StorageVersion, encoded: &[u8]) -> Result<Self, codec::Error> {
+		decode_v1(version, encoded)
+	}
+}
+
+/// Decode value that has the same encoding in all storage versions up to (and including) `STORAGE_VERSION_V1`.
+fn decode_v1<T: Decode>(version: StorageVersion, encoded: &[u8]) -> Result<T, codec::Error> {
+	match version {
+		LEGACY_STORAGE_VERSION | STORAGE_VERSION_V1 => T::decode(&mut &encoded[..]),
+		_ => Err("Unsupported message lane storage version".into()),
+	}
+}
+
+/// State of the message lane, as it is seen by the chain where the message-lane pallet is deployed.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct LaneState {
+	/// Lane identifier.
+	pub lane: LaneId,
+	/// Outbound lane data.
+	pub outbound: OutboundLaneData,
+	/// Nonce of latest message, received by the inbound lane.
+	pub inbound_latest_received_nonce: MessageNonce,
+	/// Nonce of latest message, which delivery has been confirmed to the bridged chain by the inbound lane.
+	pub inbound_latest_confirmed_nonce: MessageNonce,
+	/// True if all pallet operations (and so all lanes operations) are halted.
+	pub is_halted: bool,
+}
+
+impl LaneState {
+	/// Number of messages that have been sent over the outbound lane, but not yet received
+	/// by the bridged chain.
+	pub fn outbound_queued_messages(&self) -> MessageNonce {
+		self.outbound
+			.latest_generated_nonce
+			.saturating_sub(self.outbound.latest_received_nonce)
+	}
+
+	/// Number of messages that have been received by the inbound lane, but which delivery hasn't
+	/// been confirmed to the bridged chain yet.
+	pub fn inbound_unconfirmed_messages(&self) -> MessageNonce {
+		self.inbound_latest_received_nonce
+			.saturating_sub(self.inbound_latest_confirmed_nonce)
+	}
+}
+
+/// Identifier of the outbound messages digest item. The encoded digest is prefixed with this id
+/// when it is stored in the `Other` header digest item.
+pub const OUTBOUND_MESSAGES_DIGEST_ID: [u8; 4] = *b"olmd";
+
+/// Outbound messages digest, deposited in the header digest by the message lane pallet at the end of
+/// every block where new messages have been accepted by the outbound lane.
+///
+/// It allows light clients (and other external verifiers) to detect new messages without storage
+/// queries.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct OutboundMessagesDigest<Hash> {
+	/// Storage prefix of the message lane pallet instance (e.g. `MessageLane` or `Instance1MessageLane`).
+	pub instance_prefix: Vec<u8>,
+	/// Lane identifier.
+	pub lane: LaneId,
+	/// Nonce of the first message, accepted in this block.
+	pub begin: MessageNonce,
+	/// Nonce of the last message, accepted in this block.
+	pub end: MessageNonce,
+	/// Root of the ordered trie, built from SCALE-encoded `(MessageKey, MessageData)` tuples of all
+	/// messages in the `begin..=end` range.
+	pub messages_root: Hash,
+}
+
+impl<Hash: Encode + Decode> OutboundMessagesDigest<Hash> {
+	/// Encode digest as `Other` digest item data.
+	pub fn to_digest_item_data(&self) -> Vec<u8> {
+		(OUTBOUND_MESSAGES_DIGEST_ID, self).encode()
+	}
+
+	/// Decode digest from the `Other` digest item data.
+	///
+	/// Returns `None` if data is not an outbound messages digest.
+	pub fn from_digest_item_data(data: &[u8]) -> Option<Self> {
+		let (id, digest): ([u8; 4], Self) = Decode::decode(&mut &data[..]).ok()?;
+		if id != OUTBOUND_MESSAGES_DIGEST_ID {
+			return None;
+		}
+
+		Some(digest)
+	}
+}
+
 decl_runtime_apis! {
 	/// Outbound message lane API.
+	///
+	/// All methods return `None` if given message lane instance is unknown to the runtime.
 	pub trait OutboundLaneApi {
 		/// Returns dispatch weight of all messages in given inclusive range.
 		///
 		/// If some (or all) messages are missing from the storage, they'll also will
 		/// be missing from the resulting vector. The vector is ordered by the nonce.
 		fn messages_dispatch_weight(
+			instance: InstanceId,
 			lane: LaneId,
 			begin: MessageNonce,
 			end: MessageNonce,
-		) -> Vec<(MessageNonce, Weight)>;
+		) -> Option<Vec<(MessageNonce, Weight)>>;
 		/// Returns nonce of the latest message, received by bridged chain.
-		fn latest_received_nonce(lane: LaneId) -> MessageNonce;
+		fn latest_received_nonce(instance: InstanceId, lane: LaneId) -> Option<MessageNonce>;
 		/// Returns nonce of the latest message, generated by given lane.
-		fn latest_generated_nonce(lane: LaneId) -> MessageNonce;
+		fn latest_generated_nonce(instance: InstanceId, lane: LaneId) -> Option<MessageNonce>;
 	}
 
 	/// Inbound message lane API.
+	///
+	/// All methods return `None` if given message lane instance is unknown to the runtime.
 	pub trait InboundLaneApi {
 		/// Returns nonce of the latest message, received by given lane.
-		fn latest_received_nonce(lane: LaneId) -> MessageNonce;
+		fn latest_received_nonce(instance: InstanceId, lane: LaneId) -> Option<MessageNonce>;
 		/// Nonce of latest message that has been confirmed to the bridged chain.
-		fn latest_confirmed_nonce(lane: LaneId) -> MessageNonce;
+		fn latest_confirmed_nonce(instance: InstanceId, lane: LaneId) -> Option<MessageNonce>;
+	}
+
+	/// Inbound message dispatch API.
+	pub trait InboundMessageDispatchApi {
+		/// Returns weight that the message dispatcher reports for the inbound message with given
+		/// (encoded) payload, if it is delivered over given lane.
+		///
+		/// Returns `None` if given message lane instance is unknown to the runtime or if payload
+		/// can't be decoded.
+		fn estimate_message_dispatch_weight(
+			instance: InstanceId,
+			lane: LaneId,
+			payload: MessagePayload,
+		) -> Option<Weight>;
+	}
+
+	/// Inbound message lane relayers API.
+	///
+	/// All methods return `None` if given message lane instance is unknown to the runtime.
+	pub trait InboundLaneRelayersApi<InboundRelayer: Parameter> {
+		/// Returns relayers that have delivered messages over given inbound lane, but haven't
+		/// been rewarded yet. Entries are ordered by message nonces.
+		fn unrewarded_relayers(instance: InstanceId, lane: LaneId) -> Option<Vec<UnrewardedRelayer<InboundRelayer>>>;
+		/// Returns maximal number of unrewarded relayer entries at the inbound lane. When the limit
+		/// is reached, the lane rejects new messages until delivery is confirmed.
+		fn max_unrewarded_relayer_entries(instance: InstanceId) -> Option<MessageNonce>;
+	}
+
+	/// Message lane storage API.
+	///
+	/// All methods return `None` if given message lane instance is unknown to the runtime.
+	pub trait MessageLaneStorageApi {
+		/// Returns runtime storage keys of all outbound messages in given inclusive range.
+		fn messages_keys(
+			instance: InstanceId,
+			lane: LaneId,
+			begin: MessageNonce,
+			end: MessageNonce,
+		) -> Option<Vec<Vec<u8>>>;
+		/// Returns runtime storage key of outbound lane state.
+		fn outbound_lane_data_key(instance: InstanceId, lane: LaneId) -> Option<Vec<u8>>;
+		/// Returns runtime storage key of inbound lane state.
+		fn inbound_lane_data_key(instance: InstanceId, lane: LaneId) -> Option<Vec<u8>>;
+		/// Returns runtime storage key of the pallet storage version.
+		fn storage_version_key(instance: InstanceId) -> Option<Vec<u8>>;
+		/// Decodes encoded inbound lane state and returns its latest received and latest confirmed
+		/// nonces. Also returns `None` if the state can't be decoded.
+		fn inbound_lane_nonces(
+			instance: InstanceId,
+			encoded_lane_data: Vec<u8>,
+		) -> Option<(MessageNonce, MessageNonce)>;
+	}
+
+	/// Message lanes enumeration API.
+	pub trait LanesApi {
+		/// Returns state of all lanes that are known to the pallet instance (i.e. that have either
+		/// outbound, or inbound lane state stored in the runtime storage). Lanes are ordered by their
+		/// ids.
+		///
+		/// Returns `None` if given message lane instance is unknown to the runtime.
+		fn lanes(instance: InstanceId) -> Option<Vec<LaneState>>;
+	}
+
+	/// Outbound message lane fee API.
+	pub trait OutboundLaneFeeApi<OutboundMessageFee: Parameter> {
+		/// Returns minimal delivery and dispatch fee that must be paid by the submitter
+		/// of the message with given (encoded) payload, sent over given lane.
+		///
+		/// Returns `None` if given message lane instance is unknown to the runtime, if payload
+		/// can't be decoded or if fee can't be computed.
+		fn estimate_message_delivery_and_dispatch_fee(
+			instance: InstanceId,
+			lane: LaneId,
+			payload: MessagePayload,
+		) -> Option<OutboundMessageFee>;
+		/// Returns cumulative delivery and dispatch fee, paid by submitters of messages in given
+		/// inclusive range. Once delivery of these messages is confirmed, the fee is paid to relayers.
+		///
+		/// Returns `None` if given message lane instance is unknown to the runtime, if some message
+		/// is missing from the storage or if fee computation overflows.
+		fn messages_fee(
+			instance: InstanceId,
+			lane: LaneId,
+			begin: MessageNonce,
+			end: MessageNonce,
+		) -> Option<OutboundMessageFee>;
 	}
 }
@@ -18,7 +18,12 @@
 
 use crate::{InboundLaneData, LaneId};
 
-use frame_support::Parameter;
+use bp_runtime::Size;
+use codec::{Decode, Encode};
+use frame_support::{
+	sp_runtime::traits::{CheckedAdd, CheckedDiv, CheckedMul},
+	Parameter, RuntimeDebug,
+};
 use sp_std::fmt::Debug;
 
 /// Target chain API. Used by source chain to verify target chain proofs.
@@ -31,7 +36,9 @@ pub trait TargetHeaderChain<Payload, RelayerId> {
 	type Error: Debug + Into<&'static str>;
 
 	/// Proof that messages have been received by target chain.
-	type MessagesDeliveryProof: Parameter;
+	///
+	/// The size of the proof is used to compute weight of the delivery confirmation transaction.
+	type MessagesDeliveryProof: Parameter + Size;
 
 	/// Verify message payload before we accept it.
 	///
@@ -74,6 +81,75 @@ pub trait LaneMessageVerifier<Submitter, Payload, Fee> {
 	) -> Result<(), Self::Error>;
 }
 
+/// Components of the minimal delivery and dispatch fee of the message.
+///
+/// All fee components are in the target chain tokens, because most of fees are paid for
+/// transactions at the target chain.
+#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub struct MessageFeeQuote<Fee> {
+	/// Part of the fee that doesn't depend on the message: fees of the delivery transaction and
+	/// of the delivery confirmation transaction (converted to the target chain tokens).
+	pub base_fee: Fee,
+	/// Fee for every byte of the encoded message payload.
+	pub per_byte_fee: Fee,
+	/// Size of the encoded message payload.
+	pub payload_size: u32,
+	/// Fee for dispatching the message at the target chain.
+	pub dispatch_weight_fee: Fee,
+	/// Relayer interest (in percents) that is added to the sum of all other components.
+	pub relayer_fee_percent: u32,
+}
+
+impl<Fee: CheckedAdd + CheckedDiv + CheckedMul + From<u32> + Copy> MessageFeeQuote<Fee> {
+	/// Return total fee (in the target chain tokens). Returns `None` if overflow has happened.
+	pub fn total(&self) -> Option<Fee> {
+		let minimal_fee = self
+			.per_byte_fee
+			.checked_mul(&self.payload_size.into())
+			.and_then(|size_fee| self.base_fee.checked_add(&size_fee))
+			.and_then(|fee| fee.checked_add(&self.dispatch_weight_fee))?;
+
+		// having message with fee that is near the `Balance::MAX_VALUE` of the chain is
+		// unlikely and should be treated as an error => let's do multiplication first
+		minimal_fee
+			.checked_mul(&self.relayer_fee_percent.into())
+			.and_then(|interest| interest.checked_div(&100u32.into()))
+			.and_then(|interest| minimal_fee.checked_add(&interest))
+	}
+}
+
+/// Message fee estimator.
+///
+/// It is used by the lane message verifiers to check that the submitter has paid enough, and by
+/// the runtime API to tell submitters the fee they need to pay.
+pub trait MessageFeeEstimator<Payload> {
+	/// Type of fee at the source chain.
+	type SourceChainFee;
+	/// Type of fee at the target chain.
+	type TargetChainFee;
+	/// Error type.
+	type Error: Debug + Into<&'static str>;
+
+	/// Return components of the minimal delivery and dispatch fee of the message with given
+	/// payload, sent over given lane.
+	fn quote_message_fee(
+		lane: &LaneId,
+		payload: &Payload,
+	) -> Result<MessageFeeQuote<Self::TargetChainFee>, Self::Error>;
+
+	/// Convert fee in the target chain tokens into the source chain tokens, using the current
+	/// conversion rate. The result must be rounded up.
+	fn convert_to_source_chain_fee(fee: Self::TargetChainFee) -> Result<Self::SourceChainFee, Self::Error>;
+
+	/// Convert fee in the source chain tokens into the target chain tokens, using the current
+	/// conversion rate.
+	fn convert_to_target_chain_fee(fee: Self::SourceChainFee) -> Result<Self::TargetChainFee, Self::Error>;
+
+	/// Return minimal delivery and dispatch fee (in the source chain tokens) that must be paid by
+	/// the submitter of the message with given payload, sent over given lane.
+	fn estimate_message_fee(lane: &LaneId, payload: &Payload) -> Result<Self::SourceChainFee, Self::Error>;
+}
+
 /// Message delivery payment. It is called as a part of submit-message transaction. Transaction
 /// submitter is paying (in source chain tokens/assets) for:
 ///
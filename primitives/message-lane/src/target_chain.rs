@@ -16,11 +16,12 @@
 
 //! Primitives of message lane module, that are used on the target chain.
 
-use crate::{LaneId, Message, MessageData, MessageKey, OutboundLaneData};
+use crate::{LaneId, Message, MessageData, MessageKey, MessageNonce, OutboundLaneData};
 
+use bp_runtime::Size;
 use codec::{Decode, Encode, Error as CodecError};
 use frame_support::{weights::Weight, Parameter, RuntimeDebug};
-use sp_std::{collections::btree_map::BTreeMap, fmt::Debug, prelude::*};
+use sp_std::{collections::btree_map::BTreeMap, fmt::Debug, ops::RangeInclusive, prelude::*};
 
 /// Proved messages from the source chain.
 pub type ProvedMessages<Message> = BTreeMap<LaneId, ProvedLaneMessages<Message>>;
@@ -34,6 +35,27 @@ pub struct ProvedLaneMessages<Message> {
 	pub messages: Vec<Message>,
 }
 
+/// Gap in the nonces of messages.
+#[derive(RuntimeDebug, Clone, Copy, PartialEq, Eq)]
+pub struct NoncesGap {
+	/// Nonce that has been expected.
+	pub expected: MessageNonce,
+	/// Nonce that has been found instead.
+	pub actual: MessageNonce,
+}
+
+/// Accounting helpers for the proved messages of all lanes.
+pub trait ProvedMessagesAccounting<Fee> {
+	/// Return number of proved messages.
+	fn messages_count(&self) -> usize;
+	/// Return total encoded size of proved messages.
+	fn messages_size(&self) -> usize;
+	/// Return total dispatch weight of proved messages, computed using given function.
+	fn total_dispatch_weight(&self, dispatch_weight: impl Fn(&Message<Fee>) -> Weight) -> Weight;
+	/// Verify that nonces of messages of every lane are forming contiguous range.
+	fn ensure_contiguous_nonces(&self) -> Result<(), (LaneId, NoncesGap)>;
+}
+
 /// Message data with decoded dispatch payload.
 #[derive(RuntimeDebug)]
 pub struct DispatchMessageData<DispatchPayload, Fee> {
@@ -63,7 +85,9 @@ pub trait SourceHeaderChain<Fee> {
 
 	/// Proof that messages are sent from source chain. This may also include proof
 	/// of corresponding outbound lane states.
-	type MessagesProof: Parameter;
+	///
+	/// The size of the proof is used to compute weight of the delivery transaction.
+	type MessagesProof: Parameter + Size;
 
 	/// Verify messages proof and return proved messages.
 	///
@@ -101,6 +125,85 @@ impl<Message> Default for ProvedLaneMessages<Message> {
 	}
 }
 
+impl<Fee> ProvedLaneMessages<Message<Fee>> {
+	/// Return range of nonces of proved messages, or `None` if there are no messages.
+	///
+	/// The range is only valid if nonces are contiguous (see `ensure_contiguous_nonces`).
+	pub fn nonces_range(&self) -> Option<RangeInclusive<MessageNonce>> {
+		let first = self.messages.first()?.key.nonce;
+		let last = self.messages.last()?.key.nonce;
+		Some(first..=last)
+	}
+
+	/// Return total encoded size of proved messages.
+	pub fn messages_size(&self) -> usize
+	where
+		Fee: Encode,
+	{
+		self.messages
+			.iter()
+			.fold(0usize, |size, message| size.saturating_add(message.encoded_size()))
+	}
+
+	/// Return total dispatch weight of proved messages, computed using given function.
+	pub fn total_dispatch_weight(&self, dispatch_weight: impl Fn(&Message<Fee>) -> Weight) -> Weight {
+		self.messages
+			.iter()
+			.fold(0, |weight, message| weight.saturating_add(dispatch_weight(message)))
+	}
+
+	/// Verify that nonces of proved messages are forming contiguous range.
+	pub fn ensure_contiguous_nonces(&self) -> Result<(), NoncesGap> {
+		match self.messages.first() {
+			Some(first) => {
+				ensure_contiguous_nonces(first.key.nonce, self.messages.iter().map(|message| message.key.nonce))
+			}
+			None => Ok(()),
+		}
+	}
+}
+
+impl<Fee: Encode> ProvedMessagesAccounting<Fee> for ProvedMessages<Message<Fee>> {
+	fn messages_count(&self) -> usize {
+		self.values().fold(0usize, |count, lane_data| {
+			count.saturating_add(lane_data.messages.len())
+		})
+	}
+
+	fn messages_size(&self) -> usize {
+		self.values()
+			.fold(0usize, |size, lane_data| size.saturating_add(lane_data.messages_size()))
+	}
+
+	fn total_dispatch_weight(&self, dispatch_weight: impl Fn(&Message<Fee>) -> Weight) -> Weight {
+		self.values().fold(0, |weight, lane_data| {
+			weight.saturating_add(lane_data.total_dispatch_weight(&dispatch_weight))
+		})
+	}
+
+	fn ensure_contiguous_nonces(&self) -> Result<(), (LaneId, NoncesGap)> {
+		for (lane_id, lane_data) in self {
+			lane_data.ensure_contiguous_nonces().map_err(|gap| (*lane_id, gap))?;
+		}
+		Ok(())
+	}
+}
+
+/// Verify that nonces are forming contiguous range that starts with `first_nonce`.
+pub fn ensure_contiguous_nonces(
+	first_nonce: MessageNonce,
+	nonces: impl IntoIterator<Item = MessageNonce>,
+) -> Result<(), NoncesGap> {
+	let mut expected = first_nonce;
+	for actual in nonces {
+		if actual != expected {
+			return Err(NoncesGap { expected, actual });
+		}
+		expected = expected.saturating_add(1);
+	}
+	Ok(())
+}
+
 impl<DispatchPayload: Decode, Fee> From<Message<Fee>> for DispatchMessage<DispatchPayload, Fee> {
 	fn from(message: Message<Fee>) -> Self {
 		DispatchMessage {
@@ -118,3 +221,58 @@ impl<DispatchPayload: Decode, Fee> From<MessageData<Fee>> for DispatchMessageDat
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn message(nonce: MessageNonce, payload: Vec<u8>) -> Message<u64> {
+		Message {
+			key: MessageKey {
+				lane_id: Default::default(),
+				nonce,
+			},
+			data: MessageData { payload, fee: 0 },
+		}
+	}
+
+	fn proved_messages(nonces: &[MessageNonce]) -> ProvedMessages<Message<u64>> {
+		vec![(
+			LaneId::default(),
+			ProvedLaneMessages {
+				lane_state: None,
+				messages: nonces.iter().map(|nonce| message(*nonce, vec![42; 10])).collect(),
+			},
+		)]
+		.into_iter()
+		.collect()
+	}
+
+	#[test]
+	fn proved_messages_are_accounted() {
+		let messages = proved_messages(&[1, 2, 3]);
+		let lane_data = &messages[&LaneId::default()];
+		assert_eq!(messages.messages_count(), 3);
+		assert_eq!(messages.messages_size(), 3 * message(1, vec![42; 10]).encoded_size());
+		assert_eq!(
+			messages.total_dispatch_weight(|message| message.data.payload.len() as Weight),
+			30
+		);
+		assert_eq!(lane_data.nonces_range(), Some(1..=3));
+		assert_eq!(ProvedLaneMessages::<Message<u64>>::default().nonces_range(), None);
+	}
+
+	#[test]
+	fn nonces_gaps_are_detected() {
+		assert_eq!(proved_messages(&[]).ensure_contiguous_nonces(), Ok(()));
+		assert_eq!(proved_messages(&[5, 6, 7]).ensure_contiguous_nonces(), Ok(()));
+		assert_eq!(
+			proved_messages(&[5, 7]).ensure_contiguous_nonces(),
+			Err((LaneId::default(), NoncesGap { expected: 6, actual: 7 })),
+		);
+		assert_eq!(
+			ensure_contiguous_nonces(1, vec![1, 2, 2]),
+			Err(NoncesGap { expected: 3, actual: 2 }),
+		);
+	}
+}
@@ -23,10 +23,12 @@
 mod millau_hash;
 
 use bp_message_lane::MessageNonce;
+use bp_message_lane_weights::MessageLaneWeights;
 use bp_runtime::Chain;
 use frame_support::{weights::Weight, RuntimeDebug};
 use sp_core::Hasher as HasherT;
 use sp_runtime::{
+	generic::Era,
 	traits::{IdentifyAccount, Verify},
 	MultiSignature, MultiSigner,
 };
@@ -79,6 +81,20 @@ pub const MAXIMUM_EXTRINSIC_WEIGHT: Weight = MAXIMUM_BLOCK_WEIGHT / 100 * (AVAIL
 /// Maximal number of unconfirmed messages at inbound lane.
 pub const MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE: MessageNonce = 1024;
 
+/// Maximal size of the messages proof that is accepted by Millau. Larger proofs are rejected before
+/// verification.
+pub const MAX_MESSAGES_PROOF_SIZE: u32 = 1024 * 1024;
+
+/// Maximal size of the messages delivery proof that is accepted by Millau. Delivery proof only proves
+/// state of the single inbound lane, so it is much smaller than the messages proof.
+pub const MAX_MESSAGES_DELIVERY_PROOF_SIZE: u32 = 128 * 1024;
+
+/// Parameters of weight formulas of the message lane transactions at Millau. The relay is using the
+/// same parameters to plan transactions.
+pub fn message_lane_weights() -> MessageLaneWeights {
+	bp_message_lane_weights::rocks_db_message_lane_weights()
+}
+
 /// Block number type used in Millau.
 pub type BlockNumber = u64;
 
@@ -121,9 +137,72 @@ pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::Account
 /// Public key of the chain account that may be used to verify signatures.
 pub type AccountSigner = MultiSigner;
 
+/// Index of a transaction on the Millau chain.
+pub type Index = u32;
+
 /// Balance of an account.
 pub type Balance = u64;
 
+/// Return id of the Millau account that the call-dispatch module derives from the given Rialto account
+/// (or Rialto root), when dispatching messages from Rialto.
+pub fn derive_account_from_rialto_id(id: bp_runtime::SourceAccount<MultiSigner>) -> AccountId {
+	bp_runtime::derive_account_id(bp_runtime::RIALTO_BRIDGE_INSTANCE, id)
+}
+
+/// Signed extensions of the Millau transactions. The runtime uses this type directly, so every
+/// new extension must be added here, next to the `additional_signed` helper.
+pub type SignedExtensions<Runtime> = (
+	frame_system::CheckSpecVersion<Runtime>,
+	frame_system::CheckTxVersion<Runtime>,
+	frame_system::CheckGenesis<Runtime>,
+	frame_system::CheckEra<Runtime>,
+	frame_system::CheckNonce<Runtime>,
+	frame_system::CheckWeight<Runtime>,
+	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+);
+
+/// Additional data of the Millau transaction that is signed, but not included in the transaction
+/// itself. It matches the `AdditionalSigned` of `SignedExtensions`.
+pub type AdditionalSigned = (u32, u32, Hash, Hash, (), (), ());
+
+/// Build signed extensions of the Millau transaction.
+pub fn signed_extensions<Runtime>(era: Era, nonce: Index, tip: Balance) -> SignedExtensions<Runtime>
+where
+	Runtime: frame_system::Trait<Index = Index> + pallet_transaction_payment::Trait,
+	pallet_transaction_payment::ChargeTransactionPayment<Runtime>: From<Balance>,
+{
+	(
+		frame_system::CheckSpecVersion::new(),
+		frame_system::CheckTxVersion::new(),
+		frame_system::CheckGenesis::new(),
+		frame_system::CheckEra::from(era),
+		frame_system::CheckNonce::from(nonce),
+		frame_system::CheckWeight::new(),
+		pallet_transaction_payment::ChargeTransactionPayment::from(tip),
+	)
+}
+
+/// Build additional signed data of the Millau transaction.
+///
+/// The `era_block_hash` is the hash of the block where the transaction era starts, or the
+/// genesis hash if the transaction is immortal.
+pub fn additional_signed(
+	spec_version: u32,
+	transaction_version: u32,
+	genesis_hash: Hash,
+	era_block_hash: Hash,
+) -> AdditionalSigned {
+	(
+		spec_version,
+		transaction_version,
+		genesis_hash,
+		era_block_hash,
+		(),
+		(),
+		(),
+	)
+}
+
 sp_api::decl_runtime_apis! {
 	/// API for querying information about Millau headers from the Bridge Pallet instance.
 	///
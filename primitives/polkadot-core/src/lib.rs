@@ -0,0 +1,78 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Primitives of the Polkadot-like chains.
+//!
+//! Polkadot, Kusama and Westend are built from the same codebase, so they're using the same
+//! basic types: block number, hash, header, account id, index and balance. Runtimes of these
+//! chains are not available to the bridge, so everything that is runtime-specific (calls,
+//! signed extensions, runtime versions) must be handled by the bridge code itself.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use bp_runtime::Chain;
+use frame_support::RuntimeDebug;
+use sp_core::Hasher as HasherT;
+use sp_runtime::{
+	generic,
+	traits::{BlakeTwo256, IdentifyAccount, Verify},
+	MultiSignature, OpaqueExtrinsic,
+};
+
+/// Block number type used in Polkadot-like chains.
+pub type BlockNumber = u32;
+
+/// Hash type used in Polkadot-like chains.
+pub type Hash = <BlakeTwo256 as HasherT>::Out;
+
+/// The type of an object that can produce hashes on Polkadot-like chains.
+pub type Hasher = BlakeTwo256;
+
+/// The header type used by Polkadot-like chains.
+pub type Header = generic::Header<BlockNumber, Hasher>;
+
+/// Block type of Polkadot-like chains. Extrinsics are opaque, because we don't have access to
+/// the runtime types.
+pub type Block = generic::Block<Header, OpaqueExtrinsic>;
+
+/// Signed block type of Polkadot-like chains.
+pub type SignedBlock = generic::SignedBlock<Block>;
+
+/// Signature type used by Polkadot-like chains.
+pub type Signature = MultiSignature;
+
+/// Public key of the chain account that may be used to verify signatures.
+pub type AccountPublic = <Signature as Verify>::Signer;
+
+/// Id of account on Polkadot-like chains.
+pub type AccountId = <AccountPublic as IdentifyAccount>::AccountId;
+
+/// Index of a transaction on Polkadot-like chains.
+pub type Index = u32;
+
+/// Balance of an account on Polkadot-like chains.
+pub type Balance = u128;
+
+/// Polkadot-like chain.
+#[derive(RuntimeDebug)]
+pub struct PolkadotLike;
+
+impl Chain for PolkadotLike {
+	type BlockNumber = BlockNumber;
+	type Hash = Hash;
+	type Hasher = Hasher;
+	type Header = Header;
+}
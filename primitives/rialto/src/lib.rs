@@ -21,10 +21,12 @@
 #![allow(clippy::unnecessary_mut_passed)]
 
 use bp_message_lane::MessageNonce;
+use bp_message_lane_weights::MessageLaneWeights;
 use bp_runtime::Chain;
 use frame_support::{weights::Weight, RuntimeDebug};
 use sp_core::Hasher as HasherT;
 use sp_runtime::{
+	generic::Era,
 	traits::{BlakeTwo256, IdentifyAccount, Verify},
 	MultiSignature, MultiSigner,
 };
@@ -41,6 +43,28 @@ pub const MAXIMUM_EXTRINSIC_WEIGHT: Weight = MAXIMUM_BLOCK_WEIGHT / 100 * (AVAIL
 /// Maximal number of unconfirmed messages at inbound lane.
 pub const MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE: MessageNonce = 128;
 
+/// Maximal size of the messages proof that is accepted by Rialto. Larger proofs are rejected before
+/// verification.
+pub const MAX_MESSAGES_PROOF_SIZE: u32 = 1024 * 1024;
+
+/// Maximal size of the messages delivery proof that is accepted by Rialto. Delivery proof only proves
+/// state of the single inbound lane, so it is much smaller than the messages proof.
+pub const MAX_MESSAGES_DELIVERY_PROOF_SIZE: u32 = 128 * 1024;
+
+/// Parameters of weight formulas of the message lane transactions at Rialto. The relay is using the
+/// same parameters to plan transactions.
+pub fn message_lane_weights() -> MessageLaneWeights {
+	bp_message_lane_weights::rocks_db_message_lane_weights()
+}
+
+/// Index of the `frame_system` pallet within the Rialto runtime. It is used by the bridged chains
+/// to encode Rialto calls without depending on the Rialto runtime.
+pub const SYSTEM_PALLET_INDEX: u8 = 10;
+/// Index of the `frame_system::remark` call within the `frame_system` pallet.
+pub const SYSTEM_REMARK_CALL_INDEX: u8 = 1;
+/// Maximal weight of the `frame_system::remark` call at Rialto.
+pub const MAX_SYSTEM_REMARK_CALL_WEIGHT: Weight = 10_000_000;
+
 /// Block number type used in Rialto.
 pub type BlockNumber = u32;
 
@@ -83,9 +107,79 @@ pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::Account
 /// Public key of the chain account that may be used to verify signatures.
 pub type AccountSigner = MultiSigner;
 
+/// Index of a transaction on the Rialto chain.
+pub type Index = u32;
+
 /// Balance of an account.
 pub type Balance = u128;
 
+/// Return id of the Rialto account that the call-dispatch module derives from the given Millau account
+/// (or Millau root), when dispatching messages from Millau.
+pub fn derive_account_from_millau_id(id: bp_runtime::SourceAccount<MultiSigner>) -> AccountId {
+	bp_runtime::derive_account_id(bp_runtime::MILLAU_BRIDGE_INSTANCE, id)
+}
+
+/// Signed extensions of the Rialto transactions. The runtime uses this type directly, so every
+/// new extension must be added here, next to the `additional_signed` helper.
+///
+/// The `ChargeTransactionPayment` is the extension that charges transaction fees. It is declared
+/// by the runtime, so this crate doesn't depend on the fee payment pallet.
+pub type SignedExtensions<Runtime, ChargeTransactionPayment> = (
+	frame_system::CheckSpecVersion<Runtime>,
+	frame_system::CheckTxVersion<Runtime>,
+	frame_system::CheckGenesis<Runtime>,
+	frame_system::CheckEra<Runtime>,
+	frame_system::CheckNonce<Runtime>,
+	frame_system::CheckWeight<Runtime>,
+	ChargeTransactionPayment,
+);
+
+/// Additional data of the Rialto transaction that is signed, but not included in the transaction
+/// itself. It matches the `AdditionalSigned` of `SignedExtensions`.
+pub type AdditionalSigned = (u32, u32, Hash, Hash, (), (), ());
+
+/// Build signed extensions of the Rialto transaction. The fee is paid in Rialto tokens.
+pub fn signed_extensions<Runtime, ChargeTransactionPayment>(
+	era: Era,
+	nonce: Index,
+	tip: Balance,
+) -> SignedExtensions<Runtime, ChargeTransactionPayment>
+where
+	Runtime: frame_system::Trait<Index = Index>,
+	ChargeTransactionPayment: From<Balance>,
+{
+	(
+		frame_system::CheckSpecVersion::new(),
+		frame_system::CheckTxVersion::new(),
+		frame_system::CheckGenesis::new(),
+		frame_system::CheckEra::from(era),
+		frame_system::CheckNonce::from(nonce),
+		frame_system::CheckWeight::new(),
+		ChargeTransactionPayment::from(tip),
+	)
+}
+
+/// Build additional signed data of the Rialto transaction.
+///
+/// The `era_block_hash` is the hash of the block where the transaction era starts, or the
+/// genesis hash if the transaction is immortal.
+pub fn additional_signed(
+	spec_version: u32,
+	transaction_version: u32,
+	genesis_hash: Hash,
+	era_block_hash: Hash,
+) -> AdditionalSigned {
+	(
+		spec_version,
+		transaction_version,
+		genesis_hash,
+		era_block_hash,
+		(),
+		(),
+		(),
+	)
+}
+
 sp_api::decl_runtime_apis! {
 	/// API for querying information about Rialto headers from the Bridge Pallet instance.
 	///
@@ -63,6 +63,9 @@ pub trait Chain: Send + Sync + 'static {
 
 	/// A type that fulfills the abstract idea of what a Substrate hasher (a type
 	/// that produces hashes) is.
+	///
+	/// The same hasher is used to verify storage proofs of the chain, so chains that are
+	/// using hashers other than `BlakeTwo256` (e.g. `Keccak256`) may be bridged as well.
 	// Constraits come from the associated Hashing type of `sp_runtime::traits::Header`
 	// See here for more info:
 	// https://crates.parity.io/sp_runtime/traits/trait.Header.html#associatedtype.Hashing
@@ -0,0 +1,150 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Errors that are shared by bridge pallets and relays.
+//!
+//! Every bridge pallet error maps to one of `BridgeError` variants. The relay is using the same
+//! variants to tag its own errors, so it is able to tell retryable failures from fatal ones
+//! without matching error messages.
+
+use codec::{Decode, Encode};
+use frame_support::RuntimeDebug;
+
+/// Kind of the bridge operation failure.
+#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub enum BridgeError {
+	/// Submitted proof is larger than the runtime accepts.
+	ProofTooLarge,
+	/// Submitted proof is invalid or it is generated at the header that is unknown to the runtime.
+	InvalidProof,
+	/// Submitted finality justification is invalid.
+	InvalidJustification,
+	/// Submitted header is invalid, unknown or not yet finalized.
+	InvalidHeader,
+	/// Bridge (or lane) operations are halted.
+	Halted,
+	/// Submitted nonces (or headers) are not continuing the nonces (headers) known to the runtime.
+	NonceGap,
+	/// Declared weight (or messages count) doesn't match the actual one.
+	WeightMismatch,
+	/// Submitter has failed to pay the fee.
+	FeePaymentFailed,
+	/// Message has been rejected by the source chain verifiers.
+	MessageRejected,
+	/// The bridge is already initialized.
+	AlreadyInitialized,
+	/// The lane is not allowed (e.g. not whitelisted) by the runtime.
+	LaneNotAllowed,
+	/// Any other failure.
+	Other,
+}
+
+impl BridgeError {
+	/// All error kinds.
+	pub const ALL: [BridgeError; 12] = [
+		BridgeError::ProofTooLarge,
+		BridgeError::InvalidProof,
+		BridgeError::InvalidJustification,
+		BridgeError::InvalidHeader,
+		BridgeError::Halted,
+		BridgeError::NonceGap,
+		BridgeError::WeightMismatch,
+		BridgeError::FeePaymentFailed,
+		BridgeError::MessageRejected,
+		BridgeError::AlreadyInitialized,
+		BridgeError::LaneNotAllowed,
+		BridgeError::Other,
+	];
+
+	/// Return stable name of the error kind.
+	pub fn as_str(&self) -> &'static str {
+		match *self {
+			BridgeError::ProofTooLarge => "ProofTooLarge",
+			BridgeError::InvalidProof => "InvalidProof",
+			BridgeError::InvalidJustification => "InvalidJustification",
+			BridgeError::InvalidHeader => "InvalidHeader",
+			BridgeError::Halted => "Halted",
+			BridgeError::NonceGap => "NonceGap",
+			BridgeError::WeightMismatch => "WeightMismatch",
+			BridgeError::FeePaymentFailed => "FeePaymentFailed",
+			BridgeError::MessageRejected => "MessageRejected",
+			BridgeError::AlreadyInitialized => "AlreadyInitialized",
+			BridgeError::LaneNotAllowed => "LaneNotAllowed",
+			BridgeError::Other => "Other",
+		}
+	}
+
+	/// Parse error kind from its name, returned by `as_str`.
+	pub fn from_name(name: &str) -> Option<BridgeError> {
+		Self::ALL.iter().find(|error| error.as_str() == name).copied()
+	}
+
+	/// Returns true if the same operation may succeed later, e.g. when the bridge is resumed, the
+	/// required header is imported or the competing transaction is mined. Otherwise retrying the
+	/// operation is pointless until something is changed at the submitter side.
+	pub fn is_retryable(&self) -> bool {
+		match *self {
+			BridgeError::InvalidProof
+			| BridgeError::InvalidHeader
+			| BridgeError::Halted
+			| BridgeError::NonceGap
+			| BridgeError::FeePaymentFailed => true,
+			BridgeError::ProofTooLarge
+			| BridgeError::InvalidJustification
+			| BridgeError::WeightMismatch
+			| BridgeError::MessageRejected
+			| BridgeError::AlreadyInitialized
+			| BridgeError::LaneNotAllowed
+			| BridgeError::Other => false,
+		}
+	}
+
+	/// Format error message, tagged with this error kind. The kind may be extracted back from the
+	/// message using `from_tagged_message`.
+	#[cfg(feature = "std")]
+	pub fn tagged_message(&self, details: impl std::fmt::Display) -> String {
+		format!("[{}] {}", self.as_str(), details)
+	}
+
+	/// Extract error kind from the message, formatted with `tagged_message`.
+	pub fn from_tagged_message(message: &str) -> Option<BridgeError> {
+		let message = message.strip_prefix('[')?;
+		let tag_end = message.find(']')?;
+		Self::from_name(&message[..tag_end])
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn error_names_are_unique_and_parsed() {
+		for error in BridgeError::ALL.iter() {
+			assert_eq!(BridgeError::from_name(error.as_str()), Some(*error));
+		}
+		assert_eq!(BridgeError::from_name("Unknown"), None);
+	}
+
+	#[test]
+	fn error_kind_is_extracted_from_tagged_message() {
+		let message = BridgeError::NonceGap.tagged_message("Expected nonce 10, got 12");
+		assert_eq!(message, "[NonceGap] Expected nonce 10, got 12");
+		assert_eq!(BridgeError::from_tagged_message(&message), Some(BridgeError::NonceGap));
+		assert_eq!(BridgeError::from_tagged_message("NonceGap"), None);
+		assert_eq!(BridgeError::from_tagged_message("[Unknown] error"), None);
+	}
+}
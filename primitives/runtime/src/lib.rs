@@ -18,21 +18,42 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use codec::{Decode, Encode};
+use codec::{Codec, Decode, Encode};
+use frame_support::RuntimeDebug;
 use sp_io::hashing::blake2_256;
+use sp_std::prelude::*;
 
 pub use chain::{BlockNumberOf, Chain, HashOf, HasherOf, HeaderOf};
+pub use error::BridgeError;
+#[cfg(feature = "std")]
+pub use storage_proof::{craft_storage_proof, craft_valid_storage_proof};
+pub use storage_proof::{storage_proof_size, Error as StorageProofError, StorageProofChecker, StorageProofSize};
 
 mod chain;
+mod error;
+mod storage_proof;
 
 /// Use this when something must be shared among all instances.
 pub const NO_INSTANCE_ID: InstanceId = [0, 0, 0, 0];
 
-/// Bridge-with-Rialto instance id.
-pub const RIALTO_BRIDGE_INSTANCE: InstanceId = *b"rlto";
+/// Registered id of the Rialto chain.
+pub const RIALTO_CHAIN_ID: ChainId = *b"rlto";
 
-/// Bridge-with-Millau instance id.
-pub const MILLAU_BRIDGE_INSTANCE: InstanceId = *b"mlau";
+/// Registered id of the Millau chain.
+pub const MILLAU_CHAIN_ID: ChainId = *b"mlau";
+
+/// Registered id of the third chain. The third chain is a hypothetical chain, that uses Millau
+/// primitives and is bridged with Rialto to test multi-bridge setups.
+pub const THIRD_CHAIN_ID: ChainId = *b"3chn";
+
+/// Bridge-with-Rialto instance id, deployed at Millau.
+pub const RIALTO_BRIDGE_INSTANCE: InstanceId = derive_instance_id(RIALTO_CHAIN_ID, MILLAU_CHAIN_ID, 0);
+
+/// Bridge-with-Millau instance id, deployed at Rialto.
+pub const MILLAU_BRIDGE_INSTANCE: InstanceId = derive_instance_id(MILLAU_CHAIN_ID, RIALTO_CHAIN_ID, 0);
+
+/// Bridge-with-third-chain instance id, deployed at Rialto.
+pub const THIRD_CHAIN_BRIDGE_INSTANCE: InstanceId = derive_instance_id(THIRD_CHAIN_ID, RIALTO_CHAIN_ID, 0);
 
 /// Call-dispatch module prefix.
 pub const CALL_DISPATCH_MODULE_PREFIX: &[u8] = b"pallet-bridge/call-dispatch";
@@ -40,12 +61,75 @@ pub const CALL_DISPATCH_MODULE_PREFIX: &[u8] = b"pallet-bridge/call-dispatch";
 /// Message-lane module prefix.
 pub const MESSAGE_LANE_MODULE_PREFIX: &[u8] = b"pallet-bridge/message-lane";
 
+/// Prefix that is used when deriving target chain accounts from source chain accounts.
+pub const ACCOUNT_DERIVATION_PREFIX: &[u8] = b"pallet-bridge/account-derivation";
+
 /// Id of deployed module instance. We have a bunch of pallets that may be used in
 /// different bridges. E.g. message-lane pallet may be deployed twice in the same
 /// runtime to bridge ThisChain with Chain1 and Chain2. Sometimes we need to be able
 /// to identify deployed instance dynamically. This type is used for that.
 pub type InstanceId = [u8; 4];
 
+/// Id of the chain. Every chain that is bridged using our pallets, should have a unique id
+/// registered above (next to `RIALTO_CHAIN_ID` and other ids). Instance ids are derived
+/// from chain ids using `derive_instance_id`, so teams that are registering their chains
+/// independently will never pick the same instance id by accident.
+pub type ChainId = [u8; 4];
+
+/// Tag that is mixed into every derived instance id.
+const INSTANCE_ID_DERIVATION_TAG: &[u8] = b"pallet-bridge/instance";
+
+/// Type that has (encoded) size, which may be used to compute weight of operations with
+/// values of this type. E.g. it is used to charge relayers for verifying proofs, which
+/// size is not known in advance.
+pub trait Size {
+	/// Return approximate size of this object (in bytes).
+	///
+	/// The size must be computed from the actual object contents, and it must not be less than
+	/// the size of data that is processed when the object is verified. Otherwise the submitter
+	/// would be able to underpay for its verification.
+	fn size_hint(&self) -> u32;
+}
+
+/// Source chain account, which is represented by the derived account at the target chain.
+#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub enum SourceAccount<T> {
+	/// Root (governance) account of the source chain.
+	Root,
+	/// Regular account of the source chain.
+	Account(T),
+}
+
+/// Status of the bridge with some chain, as it is seen by the bridge pallets.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct BridgeStatus<BlockNumber, BlockHash> {
+	/// Number and hash of the best bridged headers, imported by the headers pallet. There may be
+	/// several headers if there are many headers at the same "best" height.
+	pub best_headers: Vec<(BlockNumber, BlockHash)>,
+	/// Number and hash of the best finalized bridged header.
+	pub best_finalized: (BlockNumber, BlockHash),
+	/// True if headers pallet operations are halted.
+	pub is_headers_pallet_halted: bool,
+	/// True if message lane pallet operations are halted.
+	pub is_message_lane_pallet_halted: bool,
+}
+
+sp_api::decl_runtime_apis! {
+	/// API for querying bridge status.
+	pub trait BridgeStatusApi<BlockNumber: Codec, BlockHash: Codec> {
+		/// Returns status of the bridge with given instance id. Returns `None` if bridge
+		/// instance is unknown to the runtime.
+		fn bridge_status(instance: InstanceId) -> Option<BridgeStatus<BlockNumber, BlockHash>>;
+	}
+
+	/// API for querying sovereign accounts of bridged chains.
+	pub trait SovereignAccountApi<AccountId: Codec, Balance: Codec> {
+		/// Returns id and free balance of the sovereign account of the bridged chain in given
+		/// bridge instance. Returns `None` if bridge instance is unknown to the runtime.
+		fn sovereign_account(instance: InstanceId) -> Option<(AccountId, Balance)>;
+	}
+}
+
 /// Returns id of account that acts as "system" account of given bridge instance.
 /// The `module_prefix` (arbitrary slice) may be used to generate module-level
 /// "system" account, so you could have separate "system" accounts for currency
@@ -60,3 +144,155 @@ where
 	let entropy = (module_prefix, bridge).using_encoded(blake2_256);
 	AccountId::decode(&mut &entropy[..]).unwrap_or_default()
 }
+
+/// Derive id of the target chain account that represents given source chain account (or the source
+/// chain root) in the given bridge instance. This is the account that the call-dispatch module uses
+/// as dispatch origin for messages from that source account.
+///
+/// The account id is decoded from the `blake2_256` hash of SCALE-encoded
+/// `(ACCOUNT_DERIVATION_PREFIX, bridge, id)` tuple. The `SourceAccount::Root` is encoded as a single
+/// zero byte and `SourceAccount::Account(id)` is encoded as one byte, followed by the encoded source
+/// account id, so root and regular accounts never collide. The scheme must never change, because
+/// derived accounts may already hold funds.
+pub fn derive_account_id<AccountId, SourceAccountId>(
+	bridge: InstanceId,
+	id: SourceAccount<SourceAccountId>,
+) -> AccountId
+where
+	AccountId: Decode + Default,
+	SourceAccountId: Encode,
+{
+	let entropy = (ACCOUNT_DERIVATION_PREFIX, bridge, id).using_encoded(blake2_256);
+	AccountId::decode(&mut &entropy[..]).unwrap_or_default()
+}
+
+/// Derive id of the bridge instance that is deployed at the `target_chain` and bridges it with
+/// the `source_chain`. The `index` is used to distinguish between multiple instances that are
+/// bridging the same pair of chains (it is zero for the first instance).
+///
+/// The id is the big-endian 32-bit FNV-1a hash of `INSTANCE_ID_DERIVATION_TAG`, followed by
+/// `source_chain`, `target_chain` and `index`. The function is `const`, so well-known instance
+/// ids may be declared as constants.
+pub const fn derive_instance_id(source_chain: ChainId, target_chain: ChainId, index: u8) -> InstanceId {
+	let mut hash = FNV_OFFSET_BASIS;
+	hash = fnv1a(hash, INSTANCE_ID_DERIVATION_TAG);
+	hash = fnv1a(hash, &source_chain);
+	hash = fnv1a(hash, &target_chain);
+	hash = fnv1a(hash, &[index]);
+	hash.to_be_bytes()
+}
+
+/// Format instance id as `0x`-prefixed hex string.
+#[cfg(feature = "std")]
+pub fn format_instance_id(instance: InstanceId) -> String {
+	format!(
+		"0x{}",
+		instance.iter().map(|byte| format!("{:02x}", byte)).collect::<String>()
+	)
+}
+
+/// Parse instance id, formatted with `format_instance_id`. Four-character ASCII strings
+/// (like `rlto`) are also accepted, so that instances with legacy ids may still be referenced.
+#[cfg(feature = "std")]
+pub fn parse_instance_id(instance: &str) -> Result<InstanceId, &'static str> {
+	if let Some(hex) = instance.strip_prefix("0x") {
+		if hex.len() != 8 || !hex.is_ascii() {
+			return Err("Hex instance id must have exactly 8 digits");
+		}
+
+		let mut result = NO_INSTANCE_ID;
+		for (index, byte) in result.iter_mut().enumerate() {
+			*byte = u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).map_err(|_| "Invalid hex instance id")?;
+		}
+		return Ok(result);
+	}
+
+	if instance.len() == 4 && instance.is_ascii() {
+		let mut result = NO_INSTANCE_ID;
+		result.copy_from_slice(instance.as_bytes());
+		return Ok(result);
+	}
+
+	Err("Instance id must be either 0x-prefixed 8-digit hex string, or 4-character ASCII string")
+}
+
+/// FNV-1a offset basis (32-bit).
+const FNV_OFFSET_BASIS: u32 = 0x811c_9dc5;
+
+/// FNV prime (32-bit).
+const FNV_PRIME: u32 = 0x0100_0193;
+
+/// Update 32-bit FNV-1a hash with given bytes.
+const fn fnv1a(mut hash: u32, bytes: &[u8]) -> u32 {
+	let mut index = 0;
+	while index < bytes.len() {
+		hash ^= bytes[index] as u32;
+		hash = hash.wrapping_mul(FNV_PRIME);
+		index += 1;
+	}
+	hash
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::H256;
+
+	#[test]
+	fn derived_instance_ids_are_stable() {
+		assert_eq!(MILLAU_BRIDGE_INSTANCE, [0x90, 0xbe, 0xfb, 0x81]);
+		assert_eq!(RIALTO_BRIDGE_INSTANCE, [0x9d, 0x7b, 0x03, 0x8d]);
+		assert_eq!(THIRD_CHAIN_BRIDGE_INSTANCE, [0xd6, 0x53, 0xd7, 0x3c]);
+	}
+
+	#[test]
+	fn derived_instance_id_depends_on_direction_and_index() {
+		let instance = derive_instance_id(MILLAU_CHAIN_ID, RIALTO_CHAIN_ID, 0);
+		assert_ne!(instance, derive_instance_id(RIALTO_CHAIN_ID, MILLAU_CHAIN_ID, 0));
+		assert_ne!(instance, derive_instance_id(MILLAU_CHAIN_ID, RIALTO_CHAIN_ID, 1));
+		assert_ne!(instance, NO_INSTANCE_ID);
+	}
+
+	#[test]
+	fn derived_account_ids_are_stable() {
+		assert_eq!(
+			derive_account_id::<H256, u64>(MILLAU_BRIDGE_INSTANCE, SourceAccount::Root),
+			"68fc7923ffa0d79794af15cbb69bdb3d737a639bc27ea1e7f6bf6f2a5b5ae0cc"
+				.parse()
+				.unwrap(),
+		);
+		assert_eq!(
+			derive_account_id::<H256, u64>(MILLAU_BRIDGE_INSTANCE, SourceAccount::Account(42)),
+			"57f4dc08ddab33ebfb766f161a38f6bf654cd3d11d63c4c73a8c5cb52a812134"
+				.parse()
+				.unwrap(),
+		);
+	}
+
+	#[test]
+	fn derived_account_id_depends_on_bridge_and_source_account() {
+		let account: H256 = derive_account_id(MILLAU_BRIDGE_INSTANCE, SourceAccount::Account(42u64));
+		assert_ne!(
+			account,
+			derive_account_id(MILLAU_BRIDGE_INSTANCE, SourceAccount::<u64>::Root)
+		);
+		assert_ne!(
+			account,
+			derive_account_id(RIALTO_BRIDGE_INSTANCE, SourceAccount::Account(42u64))
+		);
+		assert_ne!(
+			account,
+			derive_account_id(MILLAU_BRIDGE_INSTANCE, SourceAccount::Account(43u64))
+		);
+	}
+
+	#[test]
+	fn instance_id_is_formatted_and_parsed() {
+		assert_eq!(format_instance_id(MILLAU_BRIDGE_INSTANCE), "0x90befb81");
+		assert_eq!(parse_instance_id("0x90befb81"), Ok(MILLAU_BRIDGE_INSTANCE));
+		assert_eq!(parse_instance_id("rlto"), Ok(*b"rlto"));
+		assert!(parse_instance_id("0x90befb").is_err());
+		assert!(parse_instance_id("0x90befbzz").is_err());
+		assert!(parse_instance_id("rialto").is_err());
+	}
+}
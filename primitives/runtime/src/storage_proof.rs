@@ -0,0 +1,239 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Logic for checking Substrate storage proofs.
+
+use crate::Size;
+
+use hash_db::{HashDB, Hasher, EMPTY_PREFIX};
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+use sp_trie::{read_trie_value, Layout, MemoryDB, StorageProof};
+
+/// Size of the storage proof.
+#[derive(Clone, Copy, Default, RuntimeDebug, PartialEq, Eq)]
+pub struct StorageProofSize {
+	/// Number of trie nodes in the proof.
+	pub nodes: u32,
+	/// Total size (in bytes) of all trie nodes in the proof.
+	pub bytes: u32,
+}
+
+impl StorageProofSize {
+	/// Compute size of given trie nodes.
+	fn of_nodes(nodes: &[Vec<u8>]) -> Self {
+		StorageProofSize {
+			nodes: nodes.len() as u32,
+			bytes: nodes
+				.iter()
+				.fold(0u32, |bytes, node| bytes.saturating_add(node.len() as u32)),
+		}
+	}
+}
+
+/// Return size of the storage proof.
+pub fn storage_proof_size(proof: &StorageProof) -> StorageProofSize {
+	StorageProofSize::of_nodes(&proof.clone().iter_nodes().collect::<Vec<_>>())
+}
+
+impl Size for StorageProof {
+	fn size_hint(&self) -> u32 {
+		storage_proof_size(self).bytes
+	}
+}
+
+/// This struct is used to read storage values from a subset of a Merklized database. The "proof"
+/// is a subset of the nodes in the Merkle structure of the database, so that it provides
+/// authentication against a known Merkle root as well as the values in the database themselves.
+pub struct StorageProofChecker<H>
+where
+	H: Hasher,
+{
+	root: H::Out,
+	db: MemoryDB<H>,
+	proof_size: StorageProofSize,
+}
+
+impl<H> StorageProofChecker<H>
+where
+	H: Hasher,
+{
+	/// Constructs a new storage proof checker.
+	///
+	/// This returns an error if the given proof is invalid with respect to the given root.
+	pub fn new(root: H::Out, proof: StorageProof) -> Result<Self, Error> {
+		let nodes = proof.iter_nodes().collect::<Vec<_>>();
+		let proof_size = StorageProofSize::of_nodes(&nodes);
+		let db = StorageProof::new(nodes).into_memory_db();
+		if !db.contains(&root, EMPTY_PREFIX) {
+			return Err(Error::StorageRootMismatch);
+		}
+
+		let checker = StorageProofChecker { root, db, proof_size };
+		Ok(checker)
+	}
+
+	/// Returns size of the proof that is checked. It may be used to compute weight of the proof
+	/// verification.
+	pub fn proof_size(&self) -> StorageProofSize {
+		self.proof_size
+	}
+
+	/// Reads a value from the available subset of storage. If the value cannot be read due to an
+	/// incomplete or otherwise invalid proof, this returns an error.
+	pub fn read_value(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+		read_trie_value::<Layout<H>, _>(&self.db, &self.root, key).map_err(|_| Error::StorageValueUnavailable)
+	}
+}
+
+/// Storage proof verification error.
+#[derive(RuntimeDebug, PartialEq)]
+pub enum Error {
+	/// The proof doesn't contain the root node.
+	StorageRootMismatch,
+	/// Value is not available (the proof is incomplete).
+	StorageValueUnavailable,
+}
+
+/// Return valid storage proof and state root.
+///
+/// NOTE: This should only be used for **testing**.
+#[cfg(feature = "std")]
+pub fn craft_valid_storage_proof() -> (sp_core::H256, StorageProof) {
+	use sp_state_machine::{backend::Backend, prove_read, InMemoryBackend};
+
+	// construct storage proof
+	let backend = <InMemoryBackend<sp_core::Blake2Hasher>>::from(vec![
+		(None, vec![(b"key1".to_vec(), Some(b"value1".to_vec()))]),
+		(None, vec![(b"key2".to_vec(), Some(b"value2".to_vec()))]),
+		(None, vec![(b"key3".to_vec(), Some(b"value3".to_vec()))]),
+		// Value is too big to fit in a branch node
+		(None, vec![(b"key11".to_vec(), Some(vec![0u8; 32]))]),
+	]);
+	let root = backend.storage_root(std::iter::empty()).0;
+	let proof = StorageProof::new(
+		prove_read(backend, &[&b"key1"[..], &b"key2"[..], &b"key22"[..]])
+			.unwrap()
+			.iter_nodes()
+			.collect(),
+	);
+
+	(root, proof)
+}
+
+/// Return state root of the storage with given entries and the proof of all these entries.
+///
+/// The storage trie is built using given hasher, so it may be used to craft proofs of chains
+/// that are not using `BlakeTwo256` (e.g. `Keccak256`) as their hasher.
+///
+/// NOTE: This should only be used for **testing**.
+#[cfg(feature = "std")]
+pub fn craft_storage_proof<H>(entries: Vec<(Vec<u8>, Vec<u8>)>) -> (H::Out, StorageProof)
+where
+	H: Hasher,
+	H::Out: codec::Codec + Ord,
+{
+	use sp_state_machine::{backend::Backend, prove_read, InMemoryBackend};
+
+	let keys = entries.iter().map(|(key, _)| key.clone()).collect::<Vec<_>>();
+	let backend = <InMemoryBackend<H>>::from(vec![(
+		None,
+		entries.into_iter().map(|(key, value)| (key, Some(value))).collect(),
+	)]);
+	let root = backend.storage_root(std::iter::empty()).0;
+	let proof = prove_read(backend, &keys).expect("all keys are in the storage; qed");
+
+	(root, proof)
+}
+
+#[cfg(test)]
+pub mod tests {
+	use super::*;
+
+	use sp_core::{Blake2Hasher, H256};
+	use sp_runtime::traits::Keccak256;
+
+	#[test]
+	fn storage_proof_check() {
+		let (root, proof) = craft_valid_storage_proof();
+
+		// check proof in runtime
+		let checker = <StorageProofChecker<Blake2Hasher>>::new(root, proof.clone()).unwrap();
+		assert_eq!(checker.read_value(b"key1"), Ok(Some(b"value1".to_vec())));
+		assert_eq!(checker.read_value(b"key2"), Ok(Some(b"value2".to_vec())));
+		assert_eq!(checker.read_value(b"key11111"), Err(Error::StorageValueUnavailable));
+		assert_eq!(checker.read_value(b"key22"), Ok(None));
+
+		// checking proof against invalid commitment fails
+		assert_eq!(
+			<StorageProofChecker<Blake2Hasher>>::new(H256::random(), proof).err(),
+			Some(Error::StorageRootMismatch)
+		);
+	}
+
+	#[test]
+	fn storage_proof_size_is_computed() {
+		let (root, proof) = craft_valid_storage_proof();
+		let nodes = proof.clone().iter_nodes().collect::<Vec<_>>();
+		let expected_size = StorageProofSize {
+			nodes: nodes.len() as u32,
+			bytes: nodes.iter().map(|node| node.len() as u32).sum(),
+		};
+
+		assert_eq!(storage_proof_size(&proof), expected_size);
+		assert_eq!(proof.size_hint(), expected_size.bytes);
+		assert_eq!(
+			<StorageProofChecker<Blake2Hasher>>::new(root, proof)
+				.unwrap()
+				.proof_size(),
+			expected_size,
+		);
+	}
+
+	#[test]
+	fn crafted_storage_proof_contains_all_entries() {
+		let (root, proof) = craft_storage_proof::<Blake2Hasher>(vec![
+			(b"key1".to_vec(), b"value1".to_vec()),
+			(b"key2".to_vec(), vec![42u8; 64]),
+		]);
+
+		let checker = <StorageProofChecker<Blake2Hasher>>::new(root, proof).unwrap();
+		assert_eq!(checker.read_value(b"key1"), Ok(Some(b"value1".to_vec())));
+		assert_eq!(checker.read_value(b"key2"), Ok(Some(vec![42u8; 64])));
+	}
+
+	#[test]
+	fn keccak_storage_proof_check() {
+		let entries = vec![
+			(b"key1".to_vec(), b"value1".to_vec()),
+			(b"key2".to_vec(), vec![42u8; 64]),
+		];
+		let (root, proof) = craft_storage_proof::<Keccak256>(entries.clone());
+
+		let checker = <StorageProofChecker<Keccak256>>::new(root, proof.clone()).unwrap();
+		assert_eq!(checker.read_value(b"key1"), Ok(Some(b"value1".to_vec())));
+		assert_eq!(checker.read_value(b"key2"), Ok(Some(vec![42u8; 64])));
+		assert_eq!(checker.read_value(b"key3"), Ok(None));
+
+		// the same storage has different root when `BlakeTwo256` is used
+		let (blake2_root, _) = craft_storage_proof::<Blake2Hasher>(entries);
+		assert_ne!(root, blake2_root);
+		assert_eq!(
+			<StorageProofChecker<Blake2Hasher>>::new(blake2_root, proof).err(),
+			Some(Error::StorageRootMismatch)
+		);
+	}
+}
@@ -0,0 +1,53 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Test headers and GRANDPA justifications.
+
+use codec::Encode;
+
+pub use bp_header_chain::test_utils::*;
+
+/// Return test header with given number and the encoded justification that finalizes this
+/// header. The justification is signed by the `authority_list()` authorities at the
+/// `TEST_GRANDPA_ROUND` round of the `TEST_GRANDPA_SET_ID` set.
+pub fn header_with_justification(num: TestNumber) -> (TestHeader, Vec<u8>) {
+	let header = test_header(num);
+	let justification =
+		make_justification_for_header(&header, TEST_GRANDPA_ROUND, TEST_GRANDPA_SET_ID, &authority_list()).encode();
+	(header, justification)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use bp_header_chain::{justification::verify_justification, AuthoritySet};
+	use codec::Decode;
+	use sp_runtime::traits::Header;
+
+	#[test]
+	fn header_justification_is_valid() {
+		let (header, justification) = header_with_justification(5);
+		let justification = Decode::decode(&mut &justification[..]).unwrap();
+		assert_eq!(
+			verify_justification::<TestHeader>(
+				(header.hash(), *header.number()),
+				&AuthoritySet::new(authority_list(), TEST_GRANDPA_SET_ID),
+				&justification,
+			),
+			Ok(()),
+		);
+	}
+}
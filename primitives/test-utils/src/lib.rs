@@ -0,0 +1,176 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Utilities that are shared by tests of bridge pallets.
+//!
+//! The crate is only meant to be used as a dev-dependency. It provides the `frame_system`
+//! configuration that is shared by all test runtimes, test externalities builder and helpers
+//! to create test accounts, lanes and headers with justifications.
+
+#![warn(missing_docs)]
+
+use bp_message_lane::{LaneId, LaneNamespace};
+use frame_support::{parameter_types, weights::Weight};
+use sp_runtime::{BuildStorage, Perbill};
+use std::marker::PhantomData;
+
+pub mod headers;
+
+// Re-exports that are used by the `impl_test_frame_system` macro.
+#[doc(hidden)]
+pub use frame_system;
+#[doc(hidden)]
+pub use sp_core;
+#[doc(hidden)]
+pub use sp_runtime;
+
+/// Account id type used in tests.
+pub type AccountId = u64;
+
+/// Lane that is used in tests.
+pub const TEST_LANE_ID: LaneId = LaneId::new(LaneNamespace::LEGACY, 1);
+
+parameter_types! {
+	/// Number of block hashes that are stored by test runtimes.
+	pub const BlockHashCount: u64 = 250;
+	/// Maximal weight of the block (and the extrinsic) in test runtimes.
+	pub const MaximumBlockWeight: Weight = 1024;
+	/// Maximal length of the block in test runtimes.
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	/// Part of the block that is available to normal extrinsics in test runtimes.
+	pub const AvailableBlockRatio: Perbill = Perbill::one();
+}
+
+/// Implement `frame_system::Trait` for the test runtime.
+///
+/// Only associated types that are different in different test runtimes are configurable.
+/// Everything else (block number, hash, header, limits, ...) is the same in all test runtimes.
+///
+/// ```ignore
+/// bp_test_utils::impl_test_frame_system! {
+/// 	runtime: TestRuntime,
+/// 	origin: Origin,
+/// 	call: (),
+/// 	account_id: AccountId,
+/// 	event: TestEvent,
+/// 	account_data: (),
+/// }
+/// ```
+#[macro_export]
+macro_rules! impl_test_frame_system {
+	(
+		runtime: $runtime:ty,
+		origin: $origin:ty,
+		call: $call:ty,
+		account_id: $account_id:ty,
+		event: $event:ty,
+		account_data: $account_data:ty $(,)?
+	) => {
+		impl $crate::frame_system::Trait for $runtime {
+			type Origin = $origin;
+			type Index = u64;
+			type Call = $call;
+			type BlockNumber = u64;
+			type Hash = $crate::sp_core::H256;
+			type Hashing = $crate::sp_runtime::traits::BlakeTwo256;
+			type AccountId = $account_id;
+			type Lookup = $crate::sp_runtime::traits::IdentityLookup<Self::AccountId>;
+			type Header = $crate::sp_runtime::testing::Header;
+			type Event = $event;
+			type BlockHashCount = $crate::BlockHashCount;
+			type MaximumBlockWeight = $crate::MaximumBlockWeight;
+			type DbWeight = ();
+			type BlockExecutionWeight = ();
+			type ExtrinsicBaseWeight = ();
+			type MaximumExtrinsicWeight = $crate::MaximumBlockWeight;
+			type AvailableBlockRatio = $crate::AvailableBlockRatio;
+			type MaximumBlockLength = $crate::MaximumBlockLength;
+			type Version = ();
+			type PalletInfo = ();
+			type AccountData = $account_data;
+			type OnNewAccount = ();
+			type OnKilledAccount = ();
+			type BaseCallFilter = ();
+			type SystemWeightInfo = ();
+		}
+	};
+}
+
+/// Return ids of `count` test accounts. Ids are starting from 1.
+pub fn test_accounts(count: AccountId) -> Vec<AccountId> {
+	(1..=count).collect()
+}
+
+/// Return test lane with given index.
+pub fn test_lane_id(index: u16) -> LaneId {
+	LaneId::new(LaneNamespace::LEGACY, index)
+}
+
+/// Builder of test externalities.
+///
+/// Storage is initialized with the default `frame_system` genesis config. Genesis configs of
+/// other pallets may be added using `with_genesis`.
+pub struct TestExternalitiesBuilder<Runtime> {
+	storage: sp_core::storage::Storage,
+	_phantom: PhantomData<Runtime>,
+}
+
+impl<Runtime: frame_system::Trait> TestExternalitiesBuilder<Runtime> {
+	/// Create new builder.
+	pub fn new() -> Self {
+		TestExternalitiesBuilder {
+			storage: frame_system::GenesisConfig::default()
+				.build_storage::<Runtime>()
+				.expect("frame_system genesis config is always valid; qed"),
+			_phantom: Default::default(),
+		}
+	}
+
+	/// Add genesis config of some pallet to the test storage.
+	pub fn with_genesis(mut self, config: impl BuildStorage) -> Self {
+		config
+			.assimilate_storage(&mut self.storage)
+			.expect("invalid genesis config in test");
+		self
+	}
+
+	/// Build test externalities.
+	pub fn build(self) -> sp_io::TestExternalities {
+		sp_io::TestExternalities::new(self.storage)
+	}
+
+	/// Build test externalities and execute given test within them.
+	pub fn execute_with<R>(self, test: impl FnOnce() -> R) -> R {
+		self.build().execute_with(test)
+	}
+}
+
+impl<Runtime: frame_system::Trait> Default for TestExternalitiesBuilder<Runtime> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_lane_id_is_legacy_lane() {
+		assert_eq!(TEST_LANE_ID, LaneId::from_bytes([0, 0, 0, 1]));
+		assert_eq!(test_lane_id(1), TEST_LANE_ID);
+	}
+}
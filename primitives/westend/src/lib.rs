@@ -0,0 +1,66 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Primitives of Westend runtime.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// RuntimeApi generated functions
+#![allow(clippy::too_many_arguments)]
+// Runtime-generated DecodeLimit::decode_all_With_depth_limit
+#![allow(clippy::unnecessary_mut_passed)]
+
+pub use bp_polkadot_core::*;
+
+use sp_std::prelude::*;
+
+/// Westend chain.
+pub type Westend = PolkadotLike;
+
+/// Name of the `WestendHeaderApi::best_blocks` runtime method.
+pub const BEST_WESTEND_BLOCKS_METHOD: &str = "WestendHeaderApi_best_blocks";
+/// Name of the `WestendHeaderApi::finalized_block` runtime method.
+pub const FINALIZED_WESTEND_BLOCK_METHOD: &str = "WestendHeaderApi_finalized_block";
+/// Name of the `WestendHeaderApi::is_known_block` runtime method.
+pub const IS_KNOWN_WESTEND_BLOCK_METHOD: &str = "WestendHeaderApi_is_known_block";
+/// Name of the `WestendHeaderApi::incomplete_headers` runtime method.
+pub const INCOMPLETE_WESTEND_HEADERS_METHOD: &str = "WestendHeaderApi_incomplete_headers";
+
+sp_api::decl_runtime_apis! {
+	/// API for querying information about Westend headers from the Bridge Pallet instance.
+	///
+	/// This API is implemented by runtimes that are bridging with Westend chain, not the
+	/// Westend runtime itself.
+	pub trait WestendHeaderApi {
+		/// Returns number and hash of the best blocks known to the bridge module.
+		///
+		/// Will return multiple headers if there are many headers at the same "best" height.
+		///
+		/// The caller should only submit an `import_header` transaction that makes
+		/// (or leads to making) other header the best one.
+		fn best_blocks() -> Vec<(BlockNumber, Hash)>;
+		/// Returns number and hash of the best finalized block known to the bridge module.
+		fn finalized_block() -> (BlockNumber, Hash);
+		/// Returns numbers and hashes of headers that require finality proofs.
+		///
+		/// An empty response means that there are no headers which currently require a
+		/// finality proof.
+		fn incomplete_headers() -> Vec<(BlockNumber, Hash)>;
+		/// Returns true if the header is known to the runtime.
+		fn is_known_block(hash: Hash) -> bool;
+		/// Returns true if the header is considered finalized by the runtime.
+		fn is_finalized_block(hash: Hash) -> bool;
+	}
+}
@@ -0,0 +1,743 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! In-process end-to-end tests of the Millau <-> Rialto bridge.
+//!
+//! The harness starts Millau and Rialto development nodes within the test process, initializes
+//! headers bridges at both chains and starts headers and messages relays between them. Tests are
+//! then able to submit messages, to inspect state of lanes and to look for events that have
+//! been deposited at both chains.
+//!
+//! All waiting helpers are bounded both by the number of finalized blocks and by the wall-clock
+//! time without chain progress. On failure they return a report with the lane state and the
+//! message timeline, so failed CI runs may be triaged without rerunning tests.
+
+#![warn(missing_docs)]
+
+use bp_message_lane::{LaneId, LaneNamespace, MessageNonce};
+use codec::Decode;
+use frame_system::EventRecord;
+use futures::{Future, FutureExt};
+use pallet_bridge_call_dispatch::SourceAccountNonce;
+use relay_millau_client::SigningParams as MillauSigningParams;
+use relay_rialto_client::SigningParams as RialtoSigningParams;
+use relay_substrate_client::{Chain, Client, ConnectionParams};
+use sc_service::{TaskExecutor, TaskManager, TaskType};
+use sp_core::{hashing::twox_128, storage::StorageKey};
+use sp_runtime::{
+	traits::{Header as HeaderT, One, UniqueSaturatedInto},
+	DeserializeOwned,
+};
+use std::{
+	collections::BTreeMap,
+	fmt::Debug,
+	net::TcpListener,
+	pin::Pin,
+	sync::Once,
+	time::{Duration, Instant},
+};
+use substrate_relay::{
+	chain_pair,
+	lane_diagnostics::{self, LaneDiagnostics},
+	message_trace, millau_headers_to_rialto, millau_messages_to_rialto,
+	millau_rialto_relay::MillauRialto,
+	rialto_headers_to_millau, rialto_messages_to_millau, MillauClient, RialtoClient,
+};
+
+pub mod soak;
+
+/// Lane that is used in tests.
+pub const TEST_LANE: LaneId = LaneId::new(LaneNamespace::LEGACY, 0);
+/// Delivery and dispatch fee that is paid for every test message.
+pub const TEST_MESSAGE_FEE: bp_millau::Balance = 100_000_000;
+/// Delivery and dispatch fee that is paid for every Rialto -> Millau test message.
+pub const TEST_RIALTO_TO_MILLAU_MESSAGE_FEE: bp_rialto::Balance = 100_000_000;
+
+/// Account that is signing all relay transactions at both chains.
+const RELAYER_SURI: &str = "//Alice";
+/// Account that is sending messages at the source chain and which is the origin of dispatched
+/// calls at the target chain.
+const MESSAGES_SENDER_SURI: &str = "//Bob";
+/// Interval between lane state checks.
+const LANE_STATE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Maximal time the lane state may stay unchanged (or unreadable) before we give up waiting.
+///
+/// Both chains are producing blocks every few seconds, so if there are no new finalized blocks
+/// for that long, something is broken and it makes no sense to wait for the blocks limit.
+const CHAIN_STALL_TIMEOUT: Duration = Duration::from_secs(60);
+/// Maximal number of events that are included in the failure report.
+const MAX_REPORTED_EVENTS: usize = 32;
+
+static INITIALIZE_RELAY: Once = Once::new();
+
+/// Ports of the node that is running within the test process.
+#[derive(Debug, Clone, Copy)]
+pub struct NodePorts {
+	/// P2P port.
+	pub p2p: u16,
+	/// HTTP RPC port.
+	pub rpc: u16,
+	/// WebSocket RPC port.
+	pub ws: u16,
+}
+
+impl NodePorts {
+	/// Select unused ports.
+	fn select() -> Result<Self, String> {
+		// all listeners are alive until ports are selected, so we never select the same port twice
+		let listeners = (0..3)
+			.map(|_| TcpListener::bind("127.0.0.1:0"))
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|e| format!("Failed to select unused port: {}", e))?;
+		let ports = listeners
+			.iter()
+			.map(|listener| listener.local_addr().map(|address| address.port()))
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|e| format!("Failed to select unused port: {}", e))?;
+		Ok(NodePorts {
+			p2p: ports[0],
+			rpc: ports[1],
+			ws: ports[2],
+		})
+	}
+
+	/// Return command line arguments of the development node that is listening on these ports.
+	fn node_args(&self, executable_name: &str) -> Vec<String> {
+		vec![
+			executable_name.into(),
+			"--dev".into(),
+			"--tmp".into(),
+			"--alice".into(),
+			"--no-mdns".into(),
+			"--no-prometheus".into(),
+			"--no-telemetry".into(),
+			"--port".into(),
+			self.p2p.to_string(),
+			"--rpc-port".into(),
+			self.rpc.to_string(),
+			"--ws-port".into(),
+			self.ws.to_string(),
+		]
+	}
+
+	/// Return parameters of connection to the node WebSocket RPC server.
+	fn connection_params(&self) -> ConnectionParams {
+		ConnectionParams {
+			host: "127.0.0.1".into(),
+			port: self.ws,
+			..Default::default()
+		}
+	}
+}
+
+/// Direction of the messages lane.
+#[derive(Debug, Clone, Copy)]
+enum LaneDirection {
+	MillauToRialto,
+	RialtoToMillau,
+}
+
+impl LaneDirection {
+	/// Return human-readable name of the direction.
+	fn name(&self) -> &'static str {
+		match *self {
+			LaneDirection::MillauToRialto => "Millau -> Rialto",
+			LaneDirection::RialtoToMillau => "Rialto -> Millau",
+		}
+	}
+}
+
+/// Event that has been deposited at some block.
+#[derive(Debug, Clone)]
+pub struct BlockEvent<Event> {
+	/// Number of the block where the event has been deposited.
+	pub block: u64,
+	/// The event itself.
+	pub event: Event,
+}
+
+/// Development node that is running within the test process.
+pub struct TestNode {
+	/// Ports the node is listening on.
+	pub ports: NodePorts,
+	/// Node is stopped when task manager is dropped.
+	_task_manager: TaskManager,
+}
+
+/// Millau and Rialto development nodes, connected with headers and messages relays.
+///
+/// Nodes are stopped when the bridge is dropped. Relays are not stopped, but they will stop
+/// making any progress.
+pub struct TestBridge {
+	/// Millau node.
+	pub millau: TestNode,
+	/// Rialto node.
+	pub rialto: TestNode,
+	/// Client that is connected to the Millau node.
+	pub millau_client: MillauClient,
+	/// Client that is connected to the Rialto node.
+	pub rialto_client: RialtoClient,
+	/// Number of messages that we have sent over every Millau -> Rialto lane.
+	millau_to_rialto_messages: BTreeMap<LaneId, MessageNonce>,
+	/// Number of messages that the sender has sent over all Millau -> Rialto lanes.
+	millau_to_rialto_origin_nonce: SourceAccountNonce,
+	/// Number of messages that we have sent over every Rialto -> Millau lane.
+	rialto_to_millau_messages: BTreeMap<LaneId, MessageNonce>,
+	/// Number of messages that the sender has sent over all Rialto -> Millau lanes.
+	rialto_to_millau_origin_nonce: SourceAccountNonce,
+	/// Runtime that is used by both nodes. It must be dropped after nodes.
+	_tokio_runtime: tokio::runtime::Runtime,
+}
+
+impl TestBridge {
+	/// Start Millau and Rialto nodes, initialize headers bridges and start all relays.
+	///
+	/// Messages relays are serving the given lane in both directions.
+	pub fn start(lane: LaneId) -> Result<Self, String> {
+		INITIALIZE_RELAY.call_once(relay_utils::initialize::initialize_relay);
+
+		let tokio_runtime = tokio::runtime::Builder::new()
+			.thread_name("e2e-tests-tokio")
+			.threaded_scheduler()
+			.enable_all()
+			.build()
+			.map_err(|e| format!("Failed to start tokio runtime: {}", e))?;
+
+		let millau_ports = NodePorts::select()?;
+		let millau = TestNode {
+			ports: millau_ports,
+			_task_manager: tokio_runtime
+				.enter(|| {
+					millau_bridge_node::start_full_node(
+						millau_ports.node_args("millau-bridge-node"),
+						task_executor(&tokio_runtime),
+					)
+				})
+				.map_err(|e| format!("Failed to start Millau node: {}", e))?,
+		};
+		let rialto_ports = NodePorts::select()?;
+		let rialto = TestNode {
+			ports: rialto_ports,
+			_task_manager: tokio_runtime
+				.enter(|| {
+					rialto_bridge_node::start_full_node(
+						rialto_ports.node_args("rialto-bridge-node"),
+						task_executor(&tokio_runtime),
+					)
+				})
+				.map_err(|e| format!("Failed to start Rialto node: {}", e))?,
+		};
+
+		let millau_client = async_std::task::block_on(MillauClient::new(millau_ports.connection_params()))?;
+		let rialto_client = async_std::task::block_on(RialtoClient::new(rialto_ports.connection_params()))?;
+		let bridge = TestBridge {
+			millau,
+			rialto,
+			millau_client,
+			rialto_client,
+			millau_to_rialto_messages: BTreeMap::new(),
+			millau_to_rialto_origin_nonce: 0,
+			rialto_to_millau_messages: BTreeMap::new(),
+			rialto_to_millau_origin_nonce: 0,
+			_tokio_runtime: tokio_runtime,
+		};
+
+		bridge.initialize_headers_bridges()?;
+		bridge.spawn_relays(lane)?;
+
+		Ok(bridge)
+	}
+
+	/// Submit `System::remark` message to the Millau -> Rialto lane.
+	///
+	/// Messages that are submitted to different lanes must be dispatched in the same order they
+	/// are submitted. Returns nonce of the submitted message.
+	pub fn send_millau_to_rialto_remark(&mut self, lane: LaneId, remark: Vec<u8>) -> Result<MessageNonce, String> {
+		let millau_sign = millau_signing_params(MESSAGES_SENDER_SURI)?;
+		let rialto_sign = rialto_signing_params(MESSAGES_SENDER_SURI)?;
+		let rialto_call = rialto_runtime::Call::System(rialto_runtime::SystemCall::remark(remark));
+
+		async_std::task::block_on(millau_messages_to_rialto::submit_message(
+			&self.millau_client,
+			&millau_sign,
+			&rialto_sign,
+			lane,
+			rialto_call,
+			TEST_MESSAGE_FEE,
+			self.millau_to_rialto_origin_nonce,
+		))?;
+
+		self.millau_to_rialto_origin_nonce += 1;
+		let nonce = self.millau_to_rialto_messages.entry(lane).or_insert(0);
+		*nonce += 1;
+		Ok(*nonce)
+	}
+
+	/// Submit `System::remark` message to the Rialto -> Millau lane.
+	///
+	/// Returns nonce of the submitted message.
+	pub fn send_rialto_to_millau_remark(&mut self, lane: LaneId, remark: Vec<u8>) -> Result<MessageNonce, String> {
+		let rialto_sign = rialto_signing_params(MESSAGES_SENDER_SURI)?;
+		let millau_sign = millau_signing_params(MESSAGES_SENDER_SURI)?;
+		let millau_call = millau_runtime::Call::System(millau_runtime::SystemCall::remark(remark));
+
+		async_std::task::block_on(rialto_messages_to_millau::submit_message(
+			&self.rialto_client,
+			&rialto_sign,
+			&millau_sign,
+			lane,
+			millau_call,
+			TEST_RIALTO_TO_MILLAU_MESSAGE_FEE,
+			self.rialto_to_millau_origin_nonce,
+		))?;
+
+		self.rialto_to_millau_origin_nonce += 1;
+		let nonce = self.rialto_to_millau_messages.entry(lane).or_insert(0);
+		*nonce += 1;
+		Ok(*nonce)
+	}
+
+	/// Read state of the Millau -> Rialto lane.
+	pub fn millau_to_rialto_lane(&self, lane: LaneId) -> Result<LaneDiagnostics, String> {
+		self.lane_state(LaneDirection::MillauToRialto, lane)
+	}
+
+	/// Read state of the Rialto -> Millau lane.
+	pub fn rialto_to_millau_lane(&self, lane: LaneId) -> Result<LaneDiagnostics, String> {
+		self.lane_state(LaneDirection::RialtoToMillau, lane)
+	}
+
+	/// Wait until Millau -> Rialto message with given nonce is delivered to Rialto.
+	///
+	/// Fails if message is not delivered within `max_rialto_blocks` finalized Rialto blocks.
+	pub fn wait_millau_to_rialto_message_delivery(
+		&self,
+		lane: LaneId,
+		nonce: MessageNonce,
+		max_rialto_blocks: u64,
+	) -> Result<LaneDiagnostics, String> {
+		self.wait_for_message_delivery(LaneDirection::MillauToRialto, lane, nonce, max_rialto_blocks)
+	}
+
+	/// Wait until delivery of Millau -> Rialto message with given nonce is confirmed at Millau.
+	///
+	/// Fails if delivery is not confirmed within `max_millau_blocks` finalized Millau blocks.
+	pub fn wait_millau_to_rialto_message_confirmation(
+		&self,
+		lane: LaneId,
+		nonce: MessageNonce,
+		max_millau_blocks: u64,
+	) -> Result<LaneDiagnostics, String> {
+		self.wait_for_message_confirmation(LaneDirection::MillauToRialto, lane, nonce, max_millau_blocks)
+	}
+
+	/// Wait until Rialto -> Millau message with given nonce is delivered to Millau.
+	///
+	/// Fails if message is not delivered within `max_millau_blocks` finalized Millau blocks.
+	pub fn wait_rialto_to_millau_message_delivery(
+		&self,
+		lane: LaneId,
+		nonce: MessageNonce,
+		max_millau_blocks: u64,
+	) -> Result<LaneDiagnostics, String> {
+		self.wait_for_message_delivery(LaneDirection::RialtoToMillau, lane, nonce, max_millau_blocks)
+	}
+
+	/// Wait until delivery of Rialto -> Millau message with given nonce is confirmed at Rialto.
+	///
+	/// Fails if delivery is not confirmed within `max_rialto_blocks` finalized Rialto blocks.
+	pub fn wait_rialto_to_millau_message_confirmation(
+		&self,
+		lane: LaneId,
+		nonce: MessageNonce,
+		max_rialto_blocks: u64,
+	) -> Result<LaneDiagnostics, String> {
+		self.wait_for_message_confirmation(LaneDirection::RialtoToMillau, lane, nonce, max_rialto_blocks)
+	}
+
+	/// Read all events that have been deposited at Millau blocks, starting from the first block
+	/// up to the best block.
+	pub fn millau_events(&self) -> Result<Vec<BlockEvent<millau_runtime::Event>>, String> {
+		async_std::task::block_on(read_events(&self.millau_client))
+	}
+
+	/// Read all events that have been deposited at Rialto blocks, starting from the first block
+	/// up to the best block.
+	pub fn rialto_events(&self) -> Result<Vec<BlockEvent<rialto_runtime::Event>>, String> {
+		async_std::task::block_on(read_events(&self.rialto_client))
+	}
+
+	/// Find the first Millau event that matches given predicate.
+	///
+	/// If there's no such event, the error contains the latest events that have been deposited.
+	pub fn find_millau_event(
+		&self,
+		description: &str,
+		predicate: impl Fn(&millau_runtime::Event) -> bool,
+	) -> Result<BlockEvent<millau_runtime::Event>, String> {
+		find_event("Millau", self.millau_events()?, description, predicate)
+	}
+
+	/// Find the first Rialto event that matches given predicate.
+	///
+	/// If there's no such event, the error contains the latest events that have been deposited.
+	pub fn find_rialto_event(
+		&self,
+		description: &str,
+		predicate: impl Fn(&rialto_runtime::Event) -> bool,
+	) -> Result<BlockEvent<rialto_runtime::Event>, String> {
+		find_event("Rialto", self.rialto_events()?, description, predicate)
+	}
+
+	/// Read state of the lane.
+	fn lane_state(&self, direction: LaneDirection, lane: LaneId) -> Result<LaneDiagnostics, String> {
+		async_std::task::block_on(match direction {
+			LaneDirection::MillauToRialto => lane_diagnostics::millau_to_rialto(
+				self.millau_client.clone(),
+				self.rialto_client.clone(),
+				lane,
+				None,
+				None,
+				None,
+			)
+			.left_future(),
+			LaneDirection::RialtoToMillau => lane_diagnostics::rialto_to_millau(
+				self.rialto_client.clone(),
+				self.millau_client.clone(),
+				lane,
+				None,
+				None,
+				None,
+			)
+			.right_future(),
+		})
+	}
+
+	/// Wait until message with given nonce is delivered to the target chain.
+	fn wait_for_message_delivery(
+		&self,
+		direction: LaneDirection,
+		lane: LaneId,
+		nonce: MessageNonce,
+		max_target_blocks: u64,
+	) -> Result<LaneDiagnostics, String> {
+		self.wait_for_lane(
+			direction,
+			lane,
+			nonce,
+			"delivery",
+			max_target_blocks,
+			|lane| lane.target_best_finalized_block,
+			|lane| lane.latest_received_nonce_at_target >= nonce,
+		)
+	}
+
+	/// Wait until delivery of message with given nonce is confirmed at the source chain.
+	fn wait_for_message_confirmation(
+		&self,
+		direction: LaneDirection,
+		lane: LaneId,
+		nonce: MessageNonce,
+		max_source_blocks: u64,
+	) -> Result<LaneDiagnostics, String> {
+		self.wait_for_lane(
+			direction,
+			lane,
+			nonce,
+			"delivery confirmation",
+			max_source_blocks,
+			|lane| lane.source_best_finalized_block,
+			|lane| lane.latest_confirmed_nonce_at_source >= nonce,
+		)
+	}
+
+	/// Wait until lane state satisfies given condition.
+	///
+	/// Fails if condition is not satisfied within `max_blocks` blocks, or if the block number
+	/// is not changing (or lane state is unreadable) for `CHAIN_STALL_TIMEOUT`.
+	#[allow(clippy::too_many_arguments)]
+	fn wait_for_lane(
+		&self,
+		direction: LaneDirection,
+		lane: LaneId,
+		nonce: MessageNonce,
+		event: &str,
+		max_blocks: u64,
+		block_number: impl Fn(&LaneDiagnostics) -> u64,
+		condition: impl Fn(&LaneDiagnostics) -> bool,
+	) -> Result<LaneDiagnostics, String> {
+		let mut deadline_block = None;
+		let mut last_progress = (None, Instant::now());
+		loop {
+			// lane state may be unreadable until headers bridges are initialized
+			match self.lane_state(direction, lane) {
+				Ok(lane_state) if condition(&lane_state) => return Ok(lane_state),
+				Ok(lane_state) => {
+					let current_block = block_number(&lane_state);
+					let deadline_block = *deadline_block.get_or_insert(current_block + max_blocks);
+					if current_block > deadline_block {
+						return Err(self.failure_report(
+							direction,
+							lane,
+							nonce,
+							format!("{} has not happened within {} blocks", event, max_blocks),
+							Ok(lane_state),
+						));
+					}
+
+					if last_progress.0 != Some(current_block) {
+						last_progress = (Some(current_block), Instant::now());
+					} else if last_progress.1.elapsed() > CHAIN_STALL_TIMEOUT {
+						return Err(self.failure_report(
+							direction,
+							lane,
+							nonce,
+							format!(
+								"{} has not happened: there were no new finalized blocks for {:?}",
+								event, CHAIN_STALL_TIMEOUT,
+							),
+							Ok(lane_state),
+						));
+					}
+				}
+				Err(error) => {
+					log::trace!(target: "bridge", "Failed to read {} lane state: {}", direction.name(), error);
+					if last_progress.1.elapsed() > CHAIN_STALL_TIMEOUT {
+						return Err(self.failure_report(
+							direction,
+							lane,
+							nonce,
+							format!(
+								"{} has not happened: lane state is unreadable for {:?}",
+								event, CHAIN_STALL_TIMEOUT,
+							),
+							Err(error),
+						));
+					}
+				}
+			}
+
+			std::thread::sleep(LANE_STATE_POLL_INTERVAL);
+		}
+	}
+
+	/// Prepare report of the waiting failure.
+	///
+	/// The report includes the latest lane state and the timeline of the message.
+	fn failure_report(
+		&self,
+		direction: LaneDirection,
+		lane: LaneId,
+		nonce: MessageNonce,
+		reason: String,
+		lane_state: Result<LaneDiagnostics, String>,
+	) -> String {
+		let timeline = async_std::task::block_on(match direction {
+			LaneDirection::MillauToRialto => {
+				message_trace::millau_to_rialto(self.millau_client.clone(), self.rialto_client.clone(), lane, nonce)
+					.left_future()
+			}
+			LaneDirection::RialtoToMillau => {
+				message_trace::rialto_to_millau(self.rialto_client.clone(), self.millau_client.clone(), lane, nonce)
+					.right_future()
+			}
+		});
+
+		format!(
+			"{} message {} at lane {}: {}.\nLane state: {}\nMessage timeline: {}",
+			direction.name(),
+			nonce,
+			lane,
+			reason,
+			match lane_state {
+				Ok(lane_state) => format!("{:#?}", lane_state),
+				Err(error) => format!("unavailable ({})", error),
+			},
+			match timeline {
+				Ok(timeline) => format!("\n{}", timeline),
+				Err(error) => format!("unavailable ({})", error),
+			},
+		)
+	}
+
+	/// Initialize headers bridges at both chains.
+	fn initialize_headers_bridges(&self) -> Result<(), String> {
+		async_std::task::block_on(millau_headers_to_rialto::initialize(
+			self.millau_client.clone(),
+			self.rialto_client.clone(),
+			rialto_signing_params(RELAYER_SURI)?,
+			None,
+			None,
+			None,
+		))?;
+		async_std::task::block_on(rialto_headers_to_millau::initialize(
+			self.rialto_client.clone(),
+			self.millau_client.clone(),
+			millau_signing_params(RELAYER_SURI)?,
+			None,
+			None,
+			None,
+		))
+	}
+
+	/// Spawn headers and messages relays in both directions.
+	fn spawn_relays(&self, lane: LaneId) -> Result<(), String> {
+		chain_pair::spawn::<MillauRialto>(
+			self.millau_client.clone(),
+			millau_signing_params(RELAYER_SURI)?,
+			self.rialto_client.clone(),
+			rialto_signing_params(RELAYER_SURI)?,
+			lane,
+			None,
+			None,
+			None,
+			Default::default(),
+			None,
+			None,
+		)
+		.map(drop)
+	}
+}
+
+/// Return executor that is spawning node tasks using given runtime.
+fn task_executor(tokio_runtime: &tokio::runtime::Runtime) -> TaskExecutor {
+	let runtime_handle = tokio_runtime.handle().clone();
+	TaskExecutor::from(
+		move |future: Pin<Box<dyn Future<Output = ()> + Send>>, task_type| match task_type {
+			TaskType::Async => runtime_handle.spawn(future).map(drop),
+			TaskType::Blocking => runtime_handle
+				.spawn_blocking(move || futures::executor::block_on(future))
+				.map(drop),
+		},
+	)
+}
+
+/// Return Millau signing params of given account.
+fn millau_signing_params(suri: &str) -> Result<MillauSigningParams, String> {
+	MillauSigningParams::from_suri(suri, None).map_err(|e| format!("Invalid Millau account {}: {:?}", suri, e))
+}
+
+/// Return Rialto signing params of given account.
+fn rialto_signing_params(suri: &str) -> Result<RialtoSigningParams, String> {
+	RialtoSigningParams::from_suri(suri, None).map_err(|e| format!("Invalid Rialto account {}: {:?}", suri, e))
+}
+
+/// Return key of the `frame_system::Events` storage value.
+fn system_events_key() -> StorageKey {
+	StorageKey([twox_128(b"System"), twox_128(b"Events")].concat())
+}
+
+/// Read all events that have been deposited at the chain blocks, starting from the first block
+/// up to the best block.
+async fn read_events<C: Chain, Event: Decode>(client: &Client<C>) -> Result<Vec<BlockEvent<Event>>, String>
+where
+	C::Header: DeserializeOwned,
+{
+	let best_block_number = *client
+		.best_header()
+		.await
+		.map_err(|e| format!("Failed to read {} best header: {:?}", C::NAME, e))?
+		.number();
+
+	let mut events = Vec::new();
+	let mut block_number: C::BlockNumber = One::one();
+	while block_number <= best_block_number {
+		let block_hash = block_hash_by_number(client, block_number).await?;
+		events.extend(
+			read_block_events(client, block_hash)
+				.await?
+				.into_iter()
+				.map(|event| BlockEvent {
+					block: block_number.unique_saturated_into(),
+					event,
+				}),
+		);
+		block_number += One::one();
+	}
+
+	Ok(events)
+}
+
+/// Read events that have been deposited at given block.
+async fn read_block_events<C: Chain, Event: Decode>(
+	client: &Client<C>,
+	block_hash: C::Hash,
+) -> Result<Vec<Event>, String> {
+	let records: Option<Vec<EventRecord<Event, C::Hash>>> =
+		read_storage_value(client, system_events_key(), block_hash, "events").await?;
+	Ok(records
+		.unwrap_or_default()
+		.into_iter()
+		.map(|record| record.event)
+		.collect())
+}
+
+/// Return hash of the chain block with given number.
+async fn block_hash_by_number<C: Chain>(client: &Client<C>, block_number: C::BlockNumber) -> Result<C::Hash, String> {
+	client
+		.block_hash_by_number(block_number)
+		.await
+		.map_err(|e| format!("Failed to read {} block {} hash: {:?}", C::NAME, block_number, e))
+}
+
+/// Read and decode storage value at given block.
+async fn read_storage_value<C: Chain, T: Decode>(
+	client: &Client<C>,
+	storage_key: StorageKey,
+	block_hash: C::Hash,
+	description: &str,
+) -> Result<Option<T>, String> {
+	client
+		.raw_storage_value(storage_key, block_hash)
+		.await
+		.map_err(|e| format!("Failed to read {} {} at {}: {:?}", C::NAME, description, block_hash, e))?
+		.map(|encoded_value| {
+			Decode::decode(&mut &encoded_value.0[..]).map_err(|e| {
+				format!(
+					"Failed to decode {} {} at {}: {:?}",
+					C::NAME,
+					description,
+					block_hash,
+					e
+				)
+			})
+		})
+		.transpose()
+}
+
+/// Find the first event that matches given predicate.
+fn find_event<Event: Debug>(
+	chain: &str,
+	mut events: Vec<BlockEvent<Event>>,
+	description: &str,
+	predicate: impl Fn(&Event) -> bool,
+) -> Result<BlockEvent<Event>, String> {
+	if let Some(position) = events.iter().position(|event| predicate(&event.event)) {
+		return Ok(events.swap_remove(position));
+	}
+
+	let latest_events = events[events.len().saturating_sub(MAX_REPORTED_EVENTS)..]
+		.iter()
+		.map(|event| format!("\n\t#{}: {:?}", event.block, event.event))
+		.collect::<String>();
+	Err(format!(
+		"{} event '{}' has not been found among {} events. Latest events:{}",
+		chain,
+		description,
+		events.len(),
+		latest_events,
+	))
+}
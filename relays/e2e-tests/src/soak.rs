@@ -0,0 +1,736 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Soak testing of the Millau <-> Rialto bridge.
+//!
+//! The soak test is relaying continuous traffic in both directions for a long time (hours),
+//! while the checker verifies lanes invariants at every finalized block of both chains:
+//!
+//! - lane nonces are never decreasing and are consistent with each other;
+//! - unrewarded relayers entries are covering exactly the range of unconfirmed messages, so
+//!   relayers are never left without rewards;
+//! - every delivered message is dispatched exactly once, in order.
+//!
+//! These checks are meant to catch slow state corruption bugs that are not visible in short
+//! tests.
+
+use crate::{
+	block_hash_by_number, read_block_events, read_storage_value, TestBridge, CHAIN_STALL_TIMEOUT,
+	LANE_STATE_POLL_INTERVAL,
+};
+
+use bp_message_lane::{InboundLaneData, LaneId, MessageNonce, OutboundLaneData};
+use bp_runtime::{InstanceId, MILLAU_BRIDGE_INSTANCE, RIALTO_BRIDGE_INSTANCE};
+use codec::Decode;
+use pallet_bridge_call_dispatch::RawEvent as CallDispatchEvent;
+use pallet_message_lane::{storage_keys, DefaultInstance};
+use relay_substrate_client::{Chain, Client};
+use sp_core::storage::StorageKey;
+use sp_runtime::{
+	traits::{Header as HeaderT, One, UniqueSaturatedInto},
+	DeserializeOwned,
+};
+use std::{
+	fmt::Debug,
+	time::{Duration, Instant},
+};
+
+/// Default duration of the soak test.
+const DEFAULT_DURATION: Duration = Duration::from_secs(4 * 60 * 60);
+/// Default interval between sending new messages.
+const DEFAULT_MESSAGE_INTERVAL: Duration = Duration::from_secs(6);
+/// Maximal number of finalized blocks it may take to deliver and confirm all messages when
+/// traffic is stopped.
+const MAX_DRAIN_BLOCKS: u64 = 128;
+
+/// Soak test parameters.
+#[derive(Debug, Clone)]
+pub struct SoakConfig {
+	/// Lane that is used to send messages in both directions.
+	pub lane: LaneId,
+	/// How long the traffic is generated.
+	pub duration: Duration,
+	/// Interval between sending new messages (in both directions).
+	pub message_interval: Duration,
+}
+
+impl SoakConfig {
+	/// Read soak test parameters from `SOAK_DURATION_SECS` and `SOAK_MESSAGE_INTERVAL_SECS`
+	/// environment variables. Defaults are used if variables are not set.
+	pub fn from_env(lane: LaneId) -> Result<Self, String> {
+		Ok(SoakConfig {
+			lane,
+			duration: duration_from_env("SOAK_DURATION_SECS")?.unwrap_or(DEFAULT_DURATION),
+			message_interval: duration_from_env("SOAK_MESSAGE_INTERVAL_SECS")?.unwrap_or(DEFAULT_MESSAGE_INTERVAL),
+		})
+	}
+}
+
+/// Soak test results.
+#[derive(Debug, Default)]
+pub struct SoakReport {
+	/// Number of messages that have been sent from Millau to Rialto.
+	pub millau_to_rialto_messages: MessageNonce,
+	/// Number of messages that have been sent from Rialto to Millau.
+	pub rialto_to_millau_messages: MessageNonce,
+	/// Number of Millau blocks that have been checked.
+	pub millau_checked_blocks: u64,
+	/// Number of Rialto blocks that have been checked.
+	pub rialto_checked_blocks: u64,
+}
+
+/// Run soak test.
+///
+/// Messages are sent in both directions during `config.duration`. Then the traffic is stopped
+/// and all messages must be delivered and confirmed. Fails on first invariant violation.
+pub fn run(bridge: &mut TestBridge, config: &SoakConfig) -> Result<SoakReport, String> {
+	let lane = config.lane;
+	let mut millau = ChainChecker::<_, millau_runtime::Event, bp_rialto::AccountId>::new(
+		bridge.millau_client.clone(),
+		LaneKeys::new::<millau_runtime::Runtime>(lane),
+		bp_millau::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE,
+		Box::new(move |event| match event {
+			millau_runtime::Event::pallet_bridge_call_dispatch(event) => {
+				dispatch_outcome(event, RIALTO_BRIDGE_INSTANCE, lane)
+			}
+			_ => None,
+		}),
+	);
+	let mut rialto = ChainChecker::<_, rialto_runtime::Event, bp_millau::AccountId>::new(
+		bridge.rialto_client.clone(),
+		LaneKeys::new::<rialto_runtime::Runtime>(lane),
+		bp_rialto::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE,
+		Box::new(move |event| match event {
+			rialto_runtime::Event::pallet_bridge_call_dispatch(event) => {
+				dispatch_outcome(event, MILLAU_BRIDGE_INSTANCE, lane)
+			}
+			_ => None,
+		}),
+	);
+
+	let started_at = Instant::now();
+	let mut next_message_at = started_at;
+	let mut last_progress_at = (started_at, started_at);
+	let mut report = SoakReport::default();
+	loop {
+		let is_traffic_stopped = started_at.elapsed() >= config.duration;
+		if !is_traffic_stopped && Instant::now() >= next_message_at {
+			report.millau_to_rialto_messages = bridge.send_millau_to_rialto_remark(
+				lane,
+				format!("soak {}", report.millau_to_rialto_messages + 1).into_bytes(),
+			)?;
+			report.rialto_to_millau_messages = bridge.send_rialto_to_millau_remark(
+				lane,
+				format!("soak {}", report.rialto_to_millau_messages + 1).into_bytes(),
+			)?;
+			next_message_at += config.message_interval;
+		}
+
+		let checked_blocks = check_round(&mut millau, &mut rialto)?;
+		report.millau_checked_blocks += checked_blocks.0;
+		report.rialto_checked_blocks += checked_blocks.1;
+		if checked_blocks.0 != 0 {
+			last_progress_at.0 = Instant::now();
+		}
+		if checked_blocks.1 != 0 {
+			last_progress_at.1 = Instant::now();
+		}
+		if last_progress_at.0.elapsed() > CHAIN_STALL_TIMEOUT || last_progress_at.1.elapsed() > CHAIN_STALL_TIMEOUT {
+			return Err(format!(
+				"Soak test has stalled: there were no new finalized blocks at one of chains for {:?}. \
+				Checked blocks: Millau {}, Rialto {}",
+				CHAIN_STALL_TIMEOUT, report.millau_checked_blocks, report.rialto_checked_blocks,
+			));
+		}
+
+		if is_traffic_stopped {
+			break;
+		}
+
+		std::thread::sleep(LANE_STATE_POLL_INTERVAL);
+	}
+
+	// wait until all messages are delivered and confirmed and check the final state
+	bridge.wait_millau_to_rialto_message_confirmation(lane, report.millau_to_rialto_messages, MAX_DRAIN_BLOCKS)?;
+	bridge.wait_rialto_to_millau_message_confirmation(lane, report.rialto_to_millau_messages, MAX_DRAIN_BLOCKS)?;
+	let checked_blocks = check_round(&mut millau, &mut rialto)?;
+	report.millau_checked_blocks += checked_blocks.0;
+	report.rialto_checked_blocks += checked_blocks.1;
+
+	let mut violations = Vec::new();
+	check_drained(
+		"Millau -> Rialto",
+		report.millau_to_rialto_messages,
+		&millau.checker,
+		&rialto.checker,
+		&mut violations,
+	);
+	check_drained(
+		"Rialto -> Millau",
+		report.rialto_to_millau_messages,
+		&rialto.checker,
+		&millau.checker,
+		&mut violations,
+	);
+	if !violations.is_empty() {
+		return Err(format!(
+			"Bridge state is invalid after the soak test: {}. Report: {:?}",
+			violations.join("; "),
+			report,
+		));
+	}
+
+	Ok(report)
+}
+
+/// Check all new finalized blocks of both chains and lanes invariants that are involving both
+/// chains. Returns number of checked blocks at both chains.
+fn check_round(
+	millau: &mut ChainChecker<relay_millau_client::Millau, millau_runtime::Event, bp_rialto::AccountId>,
+	rialto: &mut ChainChecker<relay_rialto_client::Rialto, rialto_runtime::Event, bp_millau::AccountId>,
+) -> Result<(u64, u64), String> {
+	let previous_millau = millau.checker.latest.clone();
+	let previous_rialto = rialto.checker.latest.clone();
+
+	let checked_millau_blocks = async_std::task::block_on(millau.check_finalized_blocks())?;
+	let checked_rialto_blocks = async_std::task::block_on(rialto.check_finalized_blocks())?;
+
+	let mut violations = Vec::new();
+	check_cross_chain_invariants(
+		"Millau -> Rialto",
+		(&previous_millau.outbound, &previous_rialto.inbound),
+		(&millau.checker.latest.outbound, &rialto.checker.latest.inbound),
+		&mut violations,
+	);
+	check_cross_chain_invariants(
+		"Rialto -> Millau",
+		(&previous_rialto.outbound, &previous_millau.inbound),
+		(&rialto.checker.latest.outbound, &millau.checker.latest.inbound),
+		&mut violations,
+	);
+	if !violations.is_empty() {
+		return Err(format!(
+			"Cross-chain lanes invariants are violated: {}. Millau state: {:?}. Rialto state: {:?}",
+			violations.join("; "),
+			millau.checker.latest,
+			rialto.checker.latest,
+		));
+	}
+
+	Ok((checked_millau_blocks, checked_rialto_blocks))
+}
+
+/// Storage keys of the lane data.
+struct LaneKeys {
+	outbound_lane: StorageKey,
+	inbound_lane: StorageKey,
+	inbound_oldest_undispatched_nonce: StorageKey,
+}
+
+impl LaneKeys {
+	/// Return storage keys of given lane in the runtime storage.
+	fn new<T: pallet_message_lane::Trait>(lane: LaneId) -> Self {
+		LaneKeys {
+			outbound_lane: storage_keys::outbound_lane_data_key::<DefaultInstance>(&lane),
+			inbound_lane: storage_keys::inbound_lane_data_key::<T, DefaultInstance>(&lane),
+			inbound_oldest_undispatched_nonce: storage_keys::inbound_oldest_undispatched_nonce_key::<DefaultInstance>(
+				&lane,
+			),
+		}
+	}
+}
+
+/// Function that returns nonce and the dispatch result (true if message has been dispatched
+/// successfully) if the event is the message dispatch event.
+type DispatchOutcome<Event> = Box<dyn Fn(&Event) -> Option<(MessageNonce, bool)>>;
+
+/// Reads lanes state at every finalized block of the chain and checks lanes invariants.
+struct ChainChecker<C: Chain, Event, Relayer> {
+	client: Client<C>,
+	keys: LaneKeys,
+	dispatch_outcome: DispatchOutcome<Event>,
+	best_checked_block: C::BlockNumber,
+	checker: LanesChecker<Relayer>,
+}
+
+impl<C: Chain, Event: Decode, Relayer: Clone + Debug + Decode> ChainChecker<C, Event, Relayer>
+where
+	C::Header: DeserializeOwned,
+{
+	fn new(
+		client: Client<C>,
+		keys: LaneKeys,
+		max_unconfirmed_entries: MessageNonce,
+		dispatch_outcome: DispatchOutcome<Event>,
+	) -> Self {
+		ChainChecker {
+			client,
+			keys,
+			dispatch_outcome,
+			best_checked_block: Default::default(),
+			checker: LanesChecker::new(C::NAME, max_unconfirmed_entries),
+		}
+	}
+
+	/// Check all finalized blocks that have not been checked yet. Returns number of checked blocks.
+	async fn check_finalized_blocks(&mut self) -> Result<u64, String> {
+		let best_finalized_block_hash = self
+			.client
+			.best_finalized_header_hash()
+			.await
+			.map_err(|e| format!("Failed to read {} best finalized header hash: {:?}", C::NAME, e))?;
+		let best_finalized_block = *self
+			.client
+			.header_by_hash(best_finalized_block_hash)
+			.await
+			.map_err(|e| format!("Failed to read {} best finalized header: {:?}", C::NAME, e))?
+			.number();
+
+		let mut checked_blocks = 0;
+		while self.best_checked_block < best_finalized_block {
+			let block_number = self.best_checked_block + One::one();
+			let block_hash = block_hash_by_number(&self.client, block_number).await?;
+			let state = self.read_block_state(block_hash).await?;
+			self.checker.check_block(block_number.unique_saturated_into(), state)?;
+
+			self.best_checked_block = block_number;
+			checked_blocks += 1;
+		}
+
+		Ok(checked_blocks)
+	}
+
+	/// Read lanes state at given block.
+	async fn read_block_state(&self, block_hash: C::Hash) -> Result<BlockLanesState<Relayer>, String> {
+		Ok(BlockLanesState {
+			outbound: read_storage_value(
+				&self.client,
+				self.keys.outbound_lane.clone(),
+				block_hash,
+				"outbound lane",
+			)
+			.await?
+			.unwrap_or_default(),
+			inbound: read_storage_value(&self.client, self.keys.inbound_lane.clone(), block_hash, "inbound lane")
+				.await?
+				.unwrap_or_default(),
+			oldest_undispatched_nonce: read_storage_value(
+				&self.client,
+				self.keys.inbound_oldest_undispatched_nonce.clone(),
+				block_hash,
+				"oldest undispatched nonce",
+			)
+			.await?,
+			dispatched: read_block_events(&self.client, block_hash)
+				.await?
+				.iter()
+				.filter_map(|event| (self.dispatch_outcome)(event))
+				.collect(),
+		})
+	}
+}
+
+/// State of the outbound and inbound lanes with the same id at some block of the chain.
+#[derive(Debug, Clone)]
+struct BlockLanesState<Relayer> {
+	/// Outbound lane data.
+	outbound: OutboundLaneData,
+	/// Inbound lane data.
+	inbound: InboundLaneData<Relayer>,
+	/// Nonce of the oldest received, but not yet dispatched inbound message.
+	oldest_undispatched_nonce: Option<MessageNonce>,
+	/// Nonces and results of messages that have been dispatched at the block.
+	dispatched: Vec<(MessageNonce, bool)>,
+}
+
+impl<Relayer> Default for BlockLanesState<Relayer> {
+	fn default() -> Self {
+		BlockLanesState {
+			outbound: Default::default(),
+			inbound: Default::default(),
+			oldest_undispatched_nonce: None,
+			dispatched: Vec::new(),
+		}
+	}
+}
+
+/// Checks invariants of lanes at single chain.
+struct LanesChecker<Relayer> {
+	/// Name of the chain.
+	chain: &'static str,
+	/// Maximal number of unrewarded relayers entries at the inbound lane.
+	max_unconfirmed_entries: MessageNonce,
+	/// Lanes state at the latest checked block.
+	latest: BlockLanesState<Relayer>,
+	/// Nonce of the latest dispatched inbound message.
+	latest_dispatched_nonce: MessageNonce,
+}
+
+impl<Relayer: Debug> LanesChecker<Relayer> {
+	fn new(chain: &'static str, max_unconfirmed_entries: MessageNonce) -> Self {
+		LanesChecker {
+			chain,
+			max_unconfirmed_entries,
+			latest: Default::default(),
+			latest_dispatched_nonce: 0,
+		}
+	}
+
+	/// Check lanes state at the next block.
+	fn check_block(&mut self, block: u64, state: BlockLanesState<Relayer>) -> Result<(), String> {
+		let mut violations = Vec::new();
+		self.check_outbound_lane(&state.outbound, &mut violations);
+		self.check_inbound_lane(&state.inbound, &mut violations);
+		self.check_dispatch(&state, &mut violations);
+
+		if !violations.is_empty() {
+			return Err(format!(
+				"{} lanes invariants are violated at block {}: {}. Previous state: {:?}. Current state: {:?}",
+				self.chain,
+				block,
+				violations.join("; "),
+				self.latest,
+				state,
+			));
+		}
+
+		self.latest = state;
+		Ok(())
+	}
+
+	fn check_outbound_lane(&self, lane: &OutboundLaneData, violations: &mut Vec<String>) {
+		let previous = &self.latest.outbound;
+		check_nonce_monotonicity(
+			"outbound oldest unpruned",
+			previous.oldest_unpruned_nonce,
+			lane.oldest_unpruned_nonce,
+			violations,
+		);
+		check_nonce_monotonicity(
+			"outbound latest received",
+			previous.latest_received_nonce,
+			lane.latest_received_nonce,
+			violations,
+		);
+		check_nonce_monotonicity(
+			"outbound latest generated",
+			previous.latest_generated_nonce,
+			lane.latest_generated_nonce,
+			violations,
+		);
+
+		if lane.latest_received_nonce > lane.latest_generated_nonce {
+			violations.push(format!(
+				"outbound latest received nonce {} is larger than latest generated nonce {}",
+				lane.latest_received_nonce, lane.latest_generated_nonce,
+			));
+		}
+		if lane.oldest_unpruned_nonce > lane.latest_received_nonce + 1 {
+			violations.push(format!(
+				"outbound oldest unpruned nonce {} is larger than next after latest received nonce {}",
+				lane.oldest_unpruned_nonce, lane.latest_received_nonce,
+			));
+		}
+	}
+
+	fn check_inbound_lane(&self, lane: &InboundLaneData<Relayer>, violations: &mut Vec<String>) {
+		let previous = &self.latest.inbound;
+		check_nonce_monotonicity(
+			"inbound latest received",
+			previous.latest_received_nonce,
+			lane.latest_received_nonce,
+			violations,
+		);
+		check_nonce_monotonicity(
+			"inbound latest confirmed",
+			previous.latest_confirmed_nonce,
+			lane.latest_confirmed_nonce,
+			violations,
+		);
+
+		if lane.latest_confirmed_nonce > lane.latest_received_nonce {
+			violations.push(format!(
+				"inbound latest confirmed nonce {} is larger than latest received nonce {}",
+				lane.latest_confirmed_nonce, lane.latest_received_nonce,
+			));
+		}
+		if lane.relayers.len() as MessageNonce > self.max_unconfirmed_entries {
+			violations.push(format!(
+				"there are {} unrewarded relayers entries, while maximal number is {}",
+				lane.relayers.len(),
+				self.max_unconfirmed_entries,
+			));
+		}
+
+		// entries must cover (latest_confirmed_nonce; latest_received_nonce] range without gaps and
+		// overlaps - otherwise some relayers are never rewarded (or rewarded twice)
+		let mut expected_nonce_low = lane.latest_confirmed_nonce + 1;
+		for (nonce_low, nonce_high, relayer) in &lane.relayers {
+			if *nonce_low != expected_nonce_low || nonce_low > nonce_high {
+				violations.push(format!(
+					"unrewarded relayer {:?} entry [{}; {}] is invalid: expected entry starting at {}",
+					relayer, nonce_low, nonce_high, expected_nonce_low,
+				));
+				return;
+			}
+			expected_nonce_low = nonce_high + 1;
+		}
+		if expected_nonce_low != lane.latest_received_nonce + 1 {
+			violations.push(format!(
+				"unrewarded relayers entries are covering nonces up to {}, while latest received nonce is {}",
+				expected_nonce_low - 1,
+				lane.latest_received_nonce,
+			));
+		}
+	}
+
+	fn check_dispatch(&mut self, state: &BlockLanesState<Relayer>, violations: &mut Vec<String>) {
+		for (nonce, is_dispatched) in &state.dispatched {
+			if *nonce != self.latest_dispatched_nonce + 1 {
+				violations.push(format!(
+					"message {} has been dispatched after message {}",
+					nonce, self.latest_dispatched_nonce,
+				));
+			}
+			if !is_dispatched {
+				violations.push(format!("message {} has failed to dispatch", nonce));
+			}
+			self.latest_dispatched_nonce = *nonce;
+		}
+
+		// all messages in [oldest_undispatched_nonce; latest_received_nonce] are waiting for dispatch
+		let expected_latest_dispatched_nonce = match state.oldest_undispatched_nonce {
+			Some(oldest_undispatched_nonce) => oldest_undispatched_nonce - 1,
+			None => state.inbound.latest_received_nonce,
+		};
+		if self.latest_dispatched_nonce != expected_latest_dispatched_nonce {
+			violations.push(format!(
+				"{} messages have been dispatched, while {} messages have been delivered (oldest undispatched: {:?})",
+				self.latest_dispatched_nonce, state.inbound.latest_received_nonce, state.oldest_undispatched_nonce,
+			));
+		}
+	}
+}
+
+/// Check that the nonce is not decreasing.
+fn check_nonce_monotonicity(name: &str, previous: MessageNonce, current: MessageNonce, violations: &mut Vec<String>) {
+	if current < previous {
+		violations.push(format!("{} nonce has decreased from {} to {}", name, previous, current));
+	}
+}
+
+/// Check invariants of the lane that are involving both source and target chains.
+///
+/// Source and target states are read at different moments, so every state of one chain is
+/// compared to the state of other chain that has been read before it.
+fn check_cross_chain_invariants<Relayer>(
+	direction: &str,
+	previous: (&OutboundLaneData, &InboundLaneData<Relayer>),
+	current: (&OutboundLaneData, &InboundLaneData<Relayer>),
+	violations: &mut Vec<String>,
+) {
+	let (previous_source, previous_target) = previous;
+	let (current_source, current_target) = current;
+	if previous_source.latest_received_nonce > current_target.latest_received_nonce {
+		violations.push(format!(
+			"{}: source has confirmed delivery of message {}, while target has only received message {}",
+			direction, previous_source.latest_received_nonce, current_target.latest_received_nonce,
+		));
+	}
+	if previous_target.latest_received_nonce > current_source.latest_generated_nonce {
+		violations.push(format!(
+			"{}: target has received message {}, while source has only generated message {}",
+			direction, previous_target.latest_received_nonce, current_source.latest_generated_nonce,
+		));
+	}
+	if previous_target.latest_confirmed_nonce > current_source.latest_received_nonce {
+		violations.push(format!(
+			"{}: target knows about confirmation of message {}, while source has only confirmed message {}",
+			direction, previous_target.latest_confirmed_nonce, current_source.latest_received_nonce,
+		));
+	}
+}
+
+/// Check that all sent messages are delivered, dispatched and confirmed.
+fn check_drained<SourceRelayer: Debug, TargetRelayer: Debug>(
+	direction: &str,
+	sent_messages: MessageNonce,
+	source: &LanesChecker<SourceRelayer>,
+	target: &LanesChecker<TargetRelayer>,
+	violations: &mut Vec<String>,
+) {
+	let outbound = &source.latest.outbound;
+	if outbound.latest_generated_nonce != sent_messages || outbound.latest_received_nonce != sent_messages {
+		violations.push(format!(
+			"{}: {} messages have been sent, but source lane state is {:?}",
+			direction, sent_messages, outbound,
+		));
+	}
+	if target.latest_dispatched_nonce != sent_messages {
+		violations.push(format!(
+			"{}: {} messages have been sent, but {} messages are dispatched",
+			direction, sent_messages, target.latest_dispatched_nonce,
+		));
+	}
+}
+
+/// Return nonce and the dispatch result of the Millau <-> Rialto message if the event is the
+/// message dispatch event of given instance and lane.
+fn dispatch_outcome<AccountId, Balance, I>(
+	event: &CallDispatchEvent<(LaneId, MessageNonce), AccountId, Balance, I>,
+	instance: InstanceId,
+	lane: LaneId,
+) -> Option<(MessageNonce, bool)> {
+	let (event_instance, (event_lane, nonce), is_dispatched) = match *event {
+		CallDispatchEvent::MessageDispatched(event_instance, message_id, ref result) => {
+			(event_instance, message_id, result.is_ok())
+		}
+		CallDispatchEvent::MessageVersionSpecMismatch(event_instance, message_id, _, _)
+		| CallDispatchEvent::MessageWeightMismatch(event_instance, message_id, _, _)
+		| CallDispatchEvent::MessageSignatureMismatch(event_instance, message_id)
+		| CallDispatchEvent::MessageCallStored(event_instance, message_id) => (event_instance, message_id, false),
+		CallDispatchEvent::SovereignAccountFunded(..)
+		| CallDispatchEvent::SovereignAccountUnderfunded(..)
+		| CallDispatchEvent::Dummy(_) => return None,
+	};
+
+	if event_instance == instance && event_lane == lane {
+		Some((nonce, is_dispatched))
+	} else {
+		None
+	}
+}
+
+/// Read duration (in seconds) from given environment variable.
+fn duration_from_env(name: &str) -> Result<Option<Duration>, String> {
+	match std::env::var(name) {
+		Ok(value) => value
+			.parse()
+			.map(|secs| Some(Duration::from_secs(secs)))
+			.map_err(|e| format!("Invalid {} value {}: {}", name, value, e)),
+		Err(std::env::VarError::NotPresent) => Ok(None),
+		Err(e) => Err(format!("Invalid {} value: {}", name, e)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn block_state(
+		generated: MessageNonce,
+		received: MessageNonce,
+		relayers: Vec<(MessageNonce, MessageNonce, u64)>,
+		dispatched: Vec<MessageNonce>,
+	) -> BlockLanesState<u64> {
+		BlockLanesState {
+			outbound: OutboundLaneData {
+				oldest_unpruned_nonce: 1,
+				latest_received_nonce: 0,
+				latest_generated_nonce: generated,
+			},
+			inbound: InboundLaneData {
+				relayers: relayers.into_iter().collect(),
+				latest_received_nonce: received,
+				latest_confirmed_nonce: 0,
+			},
+			oldest_undispatched_nonce: None,
+			dispatched: dispatched.into_iter().map(|nonce| (nonce, true)).collect(),
+		}
+	}
+
+	#[test]
+	fn valid_lanes_states_are_accepted() {
+		let mut checker = LanesChecker::new("Test", 2);
+		assert_eq!(checker.check_block(1, block_state(1, 0, vec![], vec![])), Ok(()));
+		assert_eq!(
+			checker.check_block(2, block_state(2, 1, vec![(1, 1, 42)], vec![1])),
+			Ok(())
+		);
+		assert_eq!(
+			checker.check_block(3, block_state(2, 3, vec![(1, 1, 42), (2, 3, 43)], vec![2, 3])),
+			Ok(())
+		);
+	}
+
+	#[test]
+	fn decreasing_nonce_is_rejected() {
+		let mut checker = LanesChecker::new("Test", 2);
+		assert_eq!(checker.check_block(1, block_state(2, 0, vec![], vec![])), Ok(()));
+		assert!(checker.check_block(2, block_state(1, 0, vec![], vec![])).is_err());
+	}
+
+	#[test]
+	fn unrewarded_relayers_leak_is_rejected() {
+		let mut checker = LanesChecker::new("Test", 2);
+		assert!(checker
+			.check_block(1, block_state(0, 2, vec![(1, 1, 42)], vec![1, 2]))
+			.is_err());
+
+		let mut checker = LanesChecker::new("Test", 2);
+		assert!(checker
+			.check_block(1, block_state(0, 2, vec![(1, 1, 42), (1, 2, 43)], vec![1, 2]))
+			.is_err());
+
+		let mut checker = LanesChecker::new("Test", 1);
+		assert!(checker
+			.check_block(1, block_state(0, 2, vec![(1, 1, 42), (2, 2, 43)], vec![1, 2]))
+			.is_err());
+	}
+
+	#[test]
+	fn delivered_and_dispatched_messages_mismatch_is_rejected() {
+		// message is delivered, but not dispatched
+		let mut checker = LanesChecker::new("Test", 2);
+		assert!(checker
+			.check_block(1, block_state(0, 1, vec![(1, 1, 42)], vec![]))
+			.is_err());
+
+		// message is delivered and stored for later dispatch
+		let mut state = block_state(0, 1, vec![(1, 1, 42)], vec![]);
+		state.oldest_undispatched_nonce = Some(1);
+		let mut checker = LanesChecker::new("Test", 2);
+		assert_eq!(checker.check_block(1, state), Ok(()));
+
+		// message is dispatched twice
+		let mut checker = LanesChecker::new("Test", 2);
+		assert!(checker
+			.check_block(1, block_state(0, 1, vec![(1, 1, 42)], vec![1, 1]))
+			.is_err());
+	}
+
+	#[test]
+	fn cross_chain_invariants_are_checked() {
+		let source = OutboundLaneData {
+			oldest_unpruned_nonce: 1,
+			latest_received_nonce: 2,
+			latest_generated_nonce: 3,
+		};
+		let target = InboundLaneData::<u64> {
+			relayers: vec![(1, 2, 42)].into_iter().collect(),
+			latest_received_nonce: 2,
+			latest_confirmed_nonce: 0,
+		};
+
+		let mut violations = Vec::new();
+		check_cross_chain_invariants("Test", (&source, &target), (&source, &target), &mut violations);
+		assert!(violations.is_empty());
+
+		let stale_target = InboundLaneData::<u64>::default();
+		check_cross_chain_invariants("Test", (&source, &target), (&source, &stale_target), &mut violations);
+		assert_eq!(violations.len(), 1);
+	}
+}
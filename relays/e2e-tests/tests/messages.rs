@@ -0,0 +1,130 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Millau -> Rialto messages delivery tests.
+
+use bp_message_lane::CURRENT_STORAGE_VERSION;
+use bp_runtime::{StorageProofChecker, MILLAU_BRIDGE_INSTANCE, RIALTO_BRIDGE_INSTANCE};
+use bridge_runtime_common::messages::target::read_messages_from_storage_proof;
+use codec::Encode;
+use relay_e2e_tests::{TestBridge, TEST_LANE, TEST_MESSAGE_FEE};
+use rialto_runtime::millau_messages::WithMillauMessageBridge;
+use sp_runtime::traits::{BlakeTwo256, Header as HeaderT};
+
+/// Maximal number of finalized target chain blocks it may take to deliver a message.
+const MAX_DELIVERY_BLOCKS: u64 = 64;
+/// Maximal number of finalized source chain blocks it may take to confirm a message delivery.
+const MAX_CONFIRMATION_BLOCKS: u64 = 64;
+
+#[test]
+fn millau_to_rialto_messages_are_delivered_and_confirmed() {
+	let mut bridge = TestBridge::start(TEST_LANE).unwrap();
+
+	let first_nonce = bridge
+		.send_millau_to_rialto_remark(TEST_LANE, b"first".to_vec())
+		.unwrap();
+	let second_nonce = bridge
+		.send_millau_to_rialto_remark(TEST_LANE, b"second".to_vec())
+		.unwrap();
+	assert_eq!((first_nonce, second_nonce), (1, 2));
+
+	let lane_state = bridge
+		.wait_millau_to_rialto_message_delivery(TEST_LANE, second_nonce, MAX_DELIVERY_BLOCKS)
+		.unwrap();
+	assert_eq!(lane_state.latest_generated_nonce, second_nonce);
+	assert_eq!(lane_state.latest_received_nonce_at_target, second_nonce);
+
+	let lane_state = bridge
+		.wait_millau_to_rialto_message_confirmation(TEST_LANE, second_nonce, MAX_CONFIRMATION_BLOCKS)
+		.unwrap();
+	assert_eq!(lane_state.latest_confirmed_nonce_at_source, second_nonce);
+	assert!(!lane_state.is_source_pallet_halted);
+	assert!(!lane_state.is_target_pallet_halted);
+}
+
+#[test]
+fn millau_to_rialto_messages_are_read_from_rpc_generated_proofs() {
+	let mut bridge = TestBridge::start(TEST_LANE).unwrap();
+
+	// messages are pruned only when the next message is sent, so the delivered message is still
+	// in the Millau storage
+	let nonce = bridge
+		.send_millau_to_rialto_remark(TEST_LANE, b"proved".to_vec())
+		.unwrap();
+	bridge
+		.wait_millau_to_rialto_message_delivery(TEST_LANE, nonce, MAX_DELIVERY_BLOCKS)
+		.unwrap();
+
+	let ((millau_state_root, messages_proof), (rialto_state_root, delivery_proof)) = async_std::task::block_on(async {
+		let millau_block = bridge.millau_client.best_finalized_header_hash().await?;
+		let millau_header = bridge.millau_client.header_by_hash(millau_block).await?;
+		let messages_proof = bridge
+			.millau_client
+			.prove_messages(RIALTO_BRIDGE_INSTANCE, TEST_LANE, nonce..=nonce, true, millau_block)
+			.await?;
+
+		let rialto_block = bridge.rialto_client.best_finalized_header_hash().await?;
+		let rialto_header = bridge.rialto_client.header_by_hash(rialto_block).await?;
+		let delivery_proof = bridge
+			.rialto_client
+			.prove_messages_delivery(MILLAU_BRIDGE_INSTANCE, TEST_LANE, rialto_block)
+			.await?;
+
+		Ok::<_, relay_substrate_client::Error>((
+			(*millau_header.state_root(), messages_proof),
+			(*rialto_header.state_root(), delivery_proof),
+		))
+	})
+	.unwrap();
+
+	// both proofs have the storage version of the lane
+	let storage = StorageProofChecker::<BlakeTwo256>::new(millau_state_root, messages_proof.clone()).unwrap();
+	assert_eq!(
+		storage
+			.read_value(&millau_runtime::rialto_messages::storage_version_key().0)
+			.unwrap(),
+		Some(CURRENT_STORAGE_VERSION.encode()),
+	);
+	let storage = StorageProofChecker::<BlakeTwo256>::new(rialto_state_root, delivery_proof).unwrap();
+	assert_eq!(
+		storage
+			.read_value(&rialto_runtime::millau_messages::storage_version_key().0)
+			.unwrap(),
+		Some(CURRENT_STORAGE_VERSION.encode()),
+	);
+
+	// and the message is decoded by the same code that is used by the Rialto runtime
+	let storage = StorageProofChecker::<BlakeTwo256>::new(millau_state_root, messages_proof).unwrap();
+	let proved_messages = read_messages_from_storage_proof::<WithMillauMessageBridge, _>(
+		storage,
+		TEST_LANE,
+		nonce,
+		nonce,
+		|lane, nonce| millau_runtime::rialto_messages::message_key(lane, nonce).0,
+	)
+	.unwrap();
+	let proved_lane_messages = &proved_messages[&TEST_LANE];
+	assert_eq!(proved_lane_messages.messages.len(), 1);
+	assert_eq!(proved_lane_messages.messages[0].key.nonce, nonce);
+	assert_eq!(proved_lane_messages.messages[0].data.fee, TEST_MESSAGE_FEE);
+	assert_eq!(
+		proved_lane_messages
+			.lane_state
+			.as_ref()
+			.map(|lane_state| lane_state.latest_generated_nonce),
+		Some(nonce),
+	);
+}
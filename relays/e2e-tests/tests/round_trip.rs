@@ -0,0 +1,78 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Millau <-> Rialto round-trip test: the request is sent from Millau to Rialto, the reply is
+//! sent back from Rialto to Millau and all bridge events are checked at both chains.
+
+use bp_runtime::{MILLAU_BRIDGE_INSTANCE, RIALTO_BRIDGE_INSTANCE};
+use pallet_bridge_call_dispatch::RawEvent as CallDispatchEvent;
+use pallet_message_lane::RawEvent as MessageLaneEvent;
+use relay_e2e_tests::{TestBridge, TEST_LANE};
+
+/// Maximal number of finalized target chain blocks it may take to deliver a message.
+const MAX_DELIVERY_BLOCKS: u64 = 64;
+/// Maximal number of finalized source chain blocks it may take to confirm a message delivery.
+const MAX_CONFIRMATION_BLOCKS: u64 = 64;
+
+/// Unwrap the result, printing the (multiline) error report as is.
+fn ensure<T>(result: Result<T, String>) -> T {
+	result.unwrap_or_else(|error| panic!("{}", error))
+}
+
+#[test]
+fn millau_and_rialto_are_exchanging_messages_in_both_directions() {
+	let mut bridge = ensure(TestBridge::start(TEST_LANE));
+
+	// request is delivered and dispatched at Rialto
+	let request_nonce = ensure(bridge.send_millau_to_rialto_remark(TEST_LANE, b"ping".to_vec()));
+	ensure(bridge.wait_millau_to_rialto_message_delivery(TEST_LANE, request_nonce, MAX_DELIVERY_BLOCKS));
+	ensure(bridge.find_rialto_event("request is dispatched", |event| {
+		matches!(
+			event,
+			rialto_runtime::Event::pallet_bridge_call_dispatch(CallDispatchEvent::MessageDispatched(
+				instance,
+				(lane, nonce),
+				Ok(()),
+			)) if *instance == MILLAU_BRIDGE_INSTANCE && *lane == TEST_LANE && *nonce == request_nonce
+		)
+	}));
+
+	// reply is delivered and dispatched at Millau
+	let reply_nonce = ensure(bridge.send_rialto_to_millau_remark(TEST_LANE, b"pong".to_vec()));
+	ensure(bridge.wait_rialto_to_millau_message_delivery(TEST_LANE, reply_nonce, MAX_DELIVERY_BLOCKS));
+	ensure(bridge.find_millau_event("reply is dispatched", |event| {
+		matches!(
+			event,
+			millau_runtime::Event::pallet_bridge_call_dispatch(CallDispatchEvent::MessageDispatched(
+				instance,
+				(lane, nonce),
+				Ok(()),
+			)) if *instance == RIALTO_BRIDGE_INSTANCE && *lane == TEST_LANE && *nonce == reply_nonce
+		)
+	}));
+
+	// request delivery is confirmed at Millau
+	let lane_state =
+		ensure(bridge.wait_millau_to_rialto_message_confirmation(TEST_LANE, request_nonce, MAX_CONFIRMATION_BLOCKS));
+	assert_eq!(lane_state.latest_confirmed_nonce_at_source, request_nonce);
+	ensure(bridge.find_millau_event("request delivery is confirmed", |event| {
+		matches!(
+			event,
+			millau_runtime::Event::pallet_message_lane(MessageLaneEvent::MessagesDelivered(lane, begin, end))
+				if *lane == TEST_LANE && *begin <= request_nonce && request_nonce <= *end
+		)
+	}));
+}
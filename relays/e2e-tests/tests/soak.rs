@@ -0,0 +1,40 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Soak test of the Millau <-> Rialto bridge.
+//!
+//! The test is running for hours, so it is ignored by default. Run it with:
+//!
+//! ```bash
+//! SOAK_DURATION_SECS=14400 cargo test -p relay-e2e-tests --test soak -- --ignored --nocapture
+//! ```
+
+use relay_e2e_tests::{
+	soak::{self, SoakConfig},
+	TestBridge, TEST_LANE,
+};
+
+#[test]
+#[ignore]
+fn bridge_invariants_are_maintained_under_continuous_traffic() {
+	let config = SoakConfig::from_env(TEST_LANE).unwrap_or_else(|error| panic!("{}", error));
+	let mut bridge = TestBridge::start(TEST_LANE).unwrap_or_else(|error| panic!("{}", error));
+
+	let report = soak::run(&mut bridge, &config).unwrap_or_else(|error| panic!("{}", error));
+	println!("Soak test has completed: {:?}", report);
+	assert!(report.millau_to_rialto_messages > 0);
+	assert!(report.rialto_to_millau_messages > 0);
+}
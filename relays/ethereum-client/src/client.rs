@@ -16,48 +16,323 @@
 
 use crate::rpc::Ethereum;
 use crate::types::{
-	Address, Bytes, CallRequest, Header, HeaderWithTransactions, Receipt, SignedRawTx, Transaction, TransactionHash,
-	H256, U256,
+	Address, Bytes, CallRequest, FeeHistory, Header, HeaderWithTransactions, NewHeader, Receipt, SignedRawTx,
+	Transaction, TransactionHash, H256, U256,
 };
-use crate::{ConnectionParams, Error, Result};
+use crate::{ConnectionParams, ConnectionTransport, Error, NodeAddress, Result};
 
+use jsonrpsee::client::RequestError;
 use jsonrpsee::raw::RawClient;
 use jsonrpsee::transport::http::HttpTransportClient;
-use jsonrpsee::Client as RpcClient;
+use jsonrpsee::transport::ws::WsTransportClient;
+use jsonrpsee::{client::Subscription, Client as RpcClient};
+use relay_utils::rate_limiter::RateLimiter;
+use std::{
+	collections::HashMap,
+	future::Future,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	},
+	time::Duration,
+};
+
+/// Interval at which health of all configured nodes is checked.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Maximal number of blocks the node may lag behind the best node before it is considered stalled.
+const MAX_NODE_LAG: u64 = 5;
+/// Interval at which the new headers subscription checks whether active node has been changed.
+const SUBSCRIPTION_NODE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// New Ethereum headers subscription.
+///
+/// The subscription is bound to the node that has been active when it has been created. When
+/// the client switches to another node, the subscription is re-created at the new active node.
+pub struct NewHeadsSubscription {
+	/// Client that has created the subscription.
+	client: Client,
+	/// Index of the node the subscription is created at.
+	node: usize,
+	/// Subscription at the `node`.
+	subscription: Subscription<NewHeader>,
+}
+
+impl NewHeadsSubscription {
+	/// Wait for the next header.
+	///
+	/// Returns error if the client has switched to another node and we have failed to subscribe
+	/// at this node. The next call will retry subscription.
+	pub async fn next(&mut self) -> Result<NewHeader> {
+		loop {
+			let active_node = self.client.active_node.load(Ordering::SeqCst);
+			if active_node != self.node {
+				self.subscription = self.client.subscribe_new_heads_at(active_node).await?;
+				log::info!(
+					target: "bridge",
+					"Re-subscribed to new headers at Ethereum node {}:{}",
+					self.client.nodes[active_node].address.host,
+					self.client.nodes[active_node].address.port,
+				);
+				self.node = active_node;
+			}
+
+			// the previously active node may stop responding without closing the subscription, so
+			// let's check if we need to re-subscribe periodically
+			if let Ok(header) =
+				async_std::future::timeout(SUBSCRIPTION_NODE_CHECK_INTERVAL, self.subscription.next()).await
+			{
+				return Ok(header);
+			}
+		}
+	}
+}
+
+/// Single Ethereum node connection.
+struct Node {
+	/// Node address.
+	address: NodeAddress,
+	/// RPC client connected to the node.
+	client: RpcClient,
+	/// Limiter of requests to the node.
+	rate_limiter: RateLimiter,
+}
 
 /// The client used to interact with an Ethereum node through RPC.
+///
+/// The client may be connected to several redundant nodes. Only one node (active) is used at a
+/// time. If request to the active node fails with transport error, client switches to the next
+/// node. In background, the client is checking health of all nodes and is switching to the
+/// first healthy node, so the primary node is preferred.
 #[derive(Clone)]
 pub struct Client {
-	client: RpcClient,
+	/// All connected nodes. The first one is the primary node.
+	nodes: Arc<Vec<Node>>,
+	/// Index of the active node.
+	active_node: Arc<AtomicUsize>,
+	/// Transport used to connect to nodes.
+	transport: ConnectionTransport,
 }
 
 impl Client {
 	/// Create a new Ethereum RPC Client.
-	pub fn new(params: ConnectionParams) -> Self {
-		let uri = format!("http://{}:{}", params.host, params.port);
-		let transport = HttpTransportClient::new(&uri);
-		let raw_client = RawClient::new(transport);
-		let client: RpcClient = raw_client.into();
+	pub async fn new(params: ConnectionParams) -> Result<Self> {
+		let addresses = std::iter::once(NodeAddress {
+			host: params.host,
+			port: params.port,
+		})
+		.chain(params.fallback_nodes);
+
+		let mut nodes = Vec::new();
+		let mut last_error = None;
+		for address in addresses {
+			match Self::build_client(&address, params.transport).await {
+				Ok(client) => {
+					let rate_limiter = RateLimiter::new(
+						format!("{}:{}", address.host, address.port),
+						params.rate_limiter.clone(),
+					);
+					nodes.push(Node {
+						address,
+						client,
+						rate_limiter,
+					})
+				}
+				Err(error) => {
+					log::warn!(
+						target: "bridge",
+						"Failed to connect to Ethereum node {}:{}: {}",
+						address.host,
+						address.port,
+						error.to_string(),
+					);
+					last_error = Some(error);
+				}
+			}
+		}
+
+		if nodes.is_empty() {
+			return Err(last_error.expect("we have tried to connect to at least one (primary) node; qed"));
+		}
 
-		Self { client }
+		let client = Self {
+			nodes: Arc::new(nodes),
+			active_node: Arc::new(AtomicUsize::new(0)),
+			transport: params.transport,
+		};
+
+		if client.nodes.len() > 1 {
+			let client = client.clone();
+			async_std::task::spawn(async move {
+				loop {
+					async_std::task::sleep(HEALTH_CHECK_INTERVAL).await;
+					client.health_check().await;
+				}
+			});
+		}
+
+		Ok(client)
+	}
+
+	/// Build client to use in connection.
+	async fn build_client(address: &NodeAddress, transport: ConnectionTransport) -> Result<RpcClient> {
+		Ok(match transport {
+			ConnectionTransport::Http => {
+				let uri = format!("http://{}:{}", address.host, address.port);
+				RawClient::new(HttpTransportClient::new(&uri)).into()
+			}
+			ConnectionTransport::Ws | ConnectionTransport::Wss => {
+				let scheme = if transport == ConnectionTransport::Wss {
+					"wss"
+				} else {
+					"ws"
+				};
+				let uri = format!("{}://{}:{}", scheme, address.host, address.port);
+				RawClient::new(WsTransportClient::new(&uri).await?).into()
+			}
+		})
+	}
+
+	/// Call RPC method of the active node.
+	///
+	/// The call waits until rate limiter of the active node allows it. If call fails with transport
+	/// error, the next node becomes active.
+	async fn call<'a, T, F, Fut>(&'a self, call: F) -> Result<T>
+	where
+		F: FnOnce(&'a RpcClient) -> Fut,
+		Fut: Future<Output = std::result::Result<T, RequestError>>,
+	{
+		let active_node = self.active_node.load(Ordering::SeqCst);
+		let node = &self.nodes[active_node];
+		let _permit = node.rate_limiter.acquire().await;
+		let result = call(&node.client).await;
+		if let Err(RequestError::TransportError(_)) = result {
+			self.switch_node(active_node, (active_node + 1) % self.nodes.len(), "transport error");
+		}
+		result.map_err(Into::into)
 	}
+
+	/// Make another node active, unless active node has already been changed.
+	fn switch_node(&self, from: usize, to: usize, reason: &str) {
+		if from == to {
+			return;
+		}
+
+		if self
+			.active_node
+			.compare_exchange(from, to, Ordering::SeqCst, Ordering::SeqCst)
+			.is_ok()
+		{
+			log::warn!(
+				target: "bridge",
+				"Switching from Ethereum node {}:{} to {}:{}: {}",
+				self.nodes[from].address.host,
+				self.nodes[from].address.port,
+				self.nodes[to].address.host,
+				self.nodes[to].address.port,
+				reason,
+			);
+		}
+	}
+
+	/// Check health of all nodes and activate the first healthy one.
+	///
+	/// The node is healthy if it responds to requests, it isn't lagging behind other nodes and
+	/// it agrees with majority of other nodes on the header, that all healthy nodes must have.
+	async fn health_check(&self) {
+		let mut best_blocks = Vec::with_capacity(self.nodes.len());
+		for node in self.nodes.iter() {
+			let _permit = node.rate_limiter.acquire().await;
+			best_blocks.push(
+				Ethereum::block_number(&node.client)
+					.await
+					.ok()
+					.map(|number| number.as_u64()),
+			);
+		}
+
+		let cross_check_block = match cross_check_block(&best_blocks) {
+			Some(cross_check_block) => cross_check_block,
+			None => {
+				log::warn!(target: "bridge", "All Ethereum nodes have failed health check");
+				return;
+			}
+		};
+
+		let mut header_hashes = Vec::with_capacity(self.nodes.len());
+		for (node, best_block) in self.nodes.iter().zip(best_blocks.iter()) {
+			let header_hash = match *best_block {
+				Some(best_block) if best_block >= cross_check_block => {
+					let _permit = node.rate_limiter.acquire().await;
+					Ethereum::get_block_by_number(&node.client, cross_check_block, false)
+						.await
+						.ok()
+						.and_then(|header| header.hash)
+				}
+				_ => None,
+			};
+			header_hashes.push(header_hash);
+		}
+
+		let active_node = self.active_node.load(Ordering::SeqCst);
+		match select_healthy_node(&header_hashes) {
+			Some(healthy_node) => self.switch_node(active_node, healthy_node, "health check"),
+			None => log::warn!(target: "bridge", "All Ethereum nodes have failed health check"),
+		}
+	}
+}
+
+/// Select number of block that is used to cross-check nodes.
+///
+/// Returns `None` if none of nodes has responded.
+fn cross_check_block(best_blocks: &[Option<u64>]) -> Option<u64> {
+	best_blocks
+		.iter()
+		.filter_map(|best_block| *best_block)
+		.max()
+		.map(|max_best_block| max_best_block.saturating_sub(MAX_NODE_LAG))
+}
+
+/// Select the first node, that agrees with majority of nodes on the header hash at the
+/// cross-check block.
+///
+/// `header_hashes` contains `None` for nodes that have failed to respond or are lagging.
+fn select_healthy_node(header_hashes: &[Option<H256>]) -> Option<usize> {
+	let mut votes = HashMap::new();
+	for header_hash in header_hashes.iter().filter_map(|header_hash| header_hash.as_ref()) {
+		*votes.entry(header_hash).or_insert(0) += 1;
+	}
+
+	let max_votes = votes.values().max().cloned()?;
+	let mut majority_hashes = votes.iter().filter(|(_, votes)| **votes == max_votes);
+	let majority_hash = *majority_hashes.next()?.0;
+	if majority_hashes.next().is_some() {
+		// there's no majority - we can't tell which node is malfunctioning
+		return None;
+	}
+
+	header_hashes
+		.iter()
+		.position(|header_hash| header_hash.as_ref() == Some(majority_hash))
 }
 
 impl Client {
 	/// Estimate gas usage for the given call.
 	pub async fn estimate_gas(&self, call_request: CallRequest) -> Result<U256> {
-		Ok(Ethereum::estimate_gas(&self.client, call_request).await?)
+		self.call(move |client| Ethereum::estimate_gas(client, call_request))
+			.await
 	}
 
 	/// Retrieve number of the best known block from the Ethereum node.
 	pub async fn best_block_number(&self) -> Result<u64> {
-		Ok(Ethereum::block_number(&self.client).await?.as_u64())
+		Ok(self.call(Ethereum::block_number).await?.as_u64())
 	}
 
 	/// Retrieve number of the best known block from the Ethereum node.
 	pub async fn header_by_number(&self, block_number: u64) -> Result<Header> {
 		let get_full_tx_objects = false;
-		let header = Ethereum::get_block_by_number(&self.client, block_number, get_full_tx_objects).await?;
+		let header = self
+			.call(move |client| Ethereum::get_block_by_number(client, block_number, get_full_tx_objects))
+			.await?;
 		match header.number.is_some() && header.hash.is_some() && header.logs_bloom.is_some() {
 			true => Ok(header),
 			false => Err(Error::IncompleteHeader),
@@ -67,7 +342,9 @@ impl Client {
 	/// Retrieve block header by its hash from Ethereum node.
 	pub async fn header_by_hash(&self, hash: H256) -> Result<Header> {
 		let get_full_tx_objects = false;
-		let header = Ethereum::get_block_by_hash(&self.client, hash, get_full_tx_objects).await?;
+		let header = self
+			.call(move |client| Ethereum::get_block_by_hash(client, hash, get_full_tx_objects))
+			.await?;
 		match header.number.is_some() && header.hash.is_some() && header.logs_bloom.is_some() {
 			true => Ok(header),
 			false => Err(Error::IncompleteHeader),
@@ -77,7 +354,9 @@ impl Client {
 	/// Retrieve block header and its transactions by its number from Ethereum node.
 	pub async fn header_by_number_with_transactions(&self, number: u64) -> Result<HeaderWithTransactions> {
 		let get_full_tx_objects = true;
-		let header = Ethereum::get_block_by_number_with_transactions(&self.client, number, get_full_tx_objects).await?;
+		let header = self
+			.call(move |client| Ethereum::get_block_by_number_with_transactions(client, number, get_full_tx_objects))
+			.await?;
 
 		let is_complete_header = header.number.is_some() && header.hash.is_some() && header.logs_bloom.is_some();
 		if !is_complete_header {
@@ -95,7 +374,9 @@ impl Client {
 	/// Retrieve block header and its transactions by its hash from Ethereum node.
 	pub async fn header_by_hash_with_transactions(&self, hash: H256) -> Result<HeaderWithTransactions> {
 		let get_full_tx_objects = true;
-		let header = Ethereum::get_block_by_hash_with_transactions(&self.client, hash, get_full_tx_objects).await?;
+		let header = self
+			.call(move |client| Ethereum::get_block_by_hash_with_transactions(client, hash, get_full_tx_objects))
+			.await?;
 
 		let is_complete_header = header.number.is_some() && header.hash.is_some() && header.logs_bloom.is_some();
 		if !is_complete_header {
@@ -112,17 +393,20 @@ impl Client {
 
 	/// Retrieve transaction by its hash from Ethereum node.
 	pub async fn transaction_by_hash(&self, hash: H256) -> Result<Option<Transaction>> {
-		Ok(Ethereum::transaction_by_hash(&self.client, hash).await?)
+		self.call(move |client| Ethereum::transaction_by_hash(client, hash))
+			.await
 	}
 
 	/// Retrieve transaction receipt by transaction hash.
 	pub async fn transaction_receipt(&self, transaction_hash: H256) -> Result<Receipt> {
-		Ok(Ethereum::get_transaction_receipt(&self.client, transaction_hash).await?)
+		self.call(move |client| Ethereum::get_transaction_receipt(client, transaction_hash))
+			.await
 	}
 
 	/// Get the nonce of the given account.
 	pub async fn account_nonce(&self, address: Address) -> Result<U256> {
-		Ok(Ethereum::get_transaction_count(&self.client, address).await?)
+		self.call(move |client| Ethereum::get_transaction_count(client, address))
+			.await
 	}
 
 	/// Submit an Ethereum transaction.
@@ -130,13 +414,90 @@ impl Client {
 	/// The transaction must already be signed before sending it through this method.
 	pub async fn submit_transaction(&self, signed_raw_tx: SignedRawTx) -> Result<TransactionHash> {
 		let transaction = Bytes(signed_raw_tx);
-		let tx_hash = Ethereum::submit_transaction(&self.client, transaction).await?;
+		let tx_hash = self
+			.call(move |client| Ethereum::submit_transaction(client, transaction))
+			.await?;
 		log::trace!(target: "bridge", "Sent transaction to Ethereum node: {:?}", tx_hash);
 		Ok(tx_hash)
 	}
 
 	/// Call Ethereum smart contract.
 	pub async fn eth_call(&self, call_transaction: CallRequest) -> Result<Bytes> {
-		Ok(Ethereum::call(&self.client, call_transaction).await?)
+		self.call(move |client| Ethereum::call(client, call_transaction)).await
+	}
+
+	/// Retrieve fee history of given number of recent blocks, ending with the best block.
+	///
+	/// Priority fees, paid in every block, are returned at given percentiles.
+	pub async fn fee_history(&self, block_count: u64, reward_percentiles: Vec<f64>) -> Result<FeeHistory> {
+		self.call(move |client| Ethereum::fee_history(client, block_count.into(), "latest".into(), reward_percentiles))
+			.await
+	}
+
+	/// Retrieve priority fee per gas, suggested by the Ethereum node.
+	pub async fn max_priority_fee_per_gas(&self) -> Result<U256> {
+		self.call(Ethereum::max_priority_fee_per_gas).await
+	}
+
+	/// Subscribe to new best Ethereum headers.
+	///
+	/// Only works over WebSocket transport. The subscription follows the active node, i.e. it is
+	/// re-created when the client switches to another node.
+	pub async fn subscribe_new_heads(&self) -> Result<NewHeadsSubscription> {
+		if !self.transport.supports_subscriptions() {
+			return Err(Error::SubscriptionsNotSupported);
+		}
+
+		let active_node = self.active_node.load(Ordering::SeqCst);
+		Ok(NewHeadsSubscription {
+			client: self.clone(),
+			node: active_node,
+			subscription: self.subscribe_new_heads_at(active_node).await?,
+		})
+	}
+
+	/// Subscribe to new best Ethereum headers at given node.
+	async fn subscribe_new_heads_at(&self, node: usize) -> Result<Subscription<NewHeader>> {
+		let node = &self.nodes[node];
+		let _permit = node.rate_limiter.acquire().await;
+		Ok(node
+			.client
+			.subscribe(
+				"eth_subscribe",
+				jsonrpsee::common::Params::Array(vec!["newHeads".into()]),
+				"eth_unsubscribe",
+			)
+			.await?)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn cross_check_block_is_selected_properly() {
+		assert_eq!(cross_check_block(&[None, None]), None);
+		assert_eq!(
+			cross_check_block(&[Some(100), None, Some(90)]),
+			Some(100 - MAX_NODE_LAG)
+		);
+		assert_eq!(cross_check_block(&[Some(2)]), Some(0));
+	}
+
+	#[test]
+	fn primary_node_is_preferred() {
+		let hash = Some(H256::from_low_u64_be(1));
+		assert_eq!(select_healthy_node(&[hash, hash, hash]), Some(0));
+		assert_eq!(select_healthy_node(&[None, hash, hash]), Some(1));
+	}
+
+	#[test]
+	fn malfunctioning_node_is_not_selected() {
+		let hash = Some(H256::from_low_u64_be(1));
+		let bad_hash = Some(H256::from_low_u64_be(2));
+		assert_eq!(select_healthy_node(&[bad_hash, hash, hash]), Some(1));
+		assert_eq!(select_healthy_node(&[bad_hash, hash]), None);
+		assert_eq!(select_healthy_node(&[None, None]), None);
 	}
 }
@@ -17,6 +17,7 @@
 //! Ethereum node RPC errors.
 
 use jsonrpsee::client::RequestError;
+use jsonrpsee::transport::ws::WsNewDnsError;
 use relay_utils::MaybeConnectionError;
 
 /// Result type used by Ethereum client.
@@ -26,9 +27,13 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// an Ethereum node through RPC.
 #[derive(Debug)]
 pub enum Error {
+	/// Web socket connection error.
+	WsConnectionError(WsNewDnsError),
 	/// An error that can occur when making an HTTP request to
 	/// an JSON-RPC client.
 	Request(RequestError),
+	/// Subscriptions are not supported by the connection transport.
+	SubscriptionsNotSupported,
 	/// Failed to parse response.
 	ResponseParseFailed(String),
 	/// We have received a header with missing fields.
@@ -40,6 +45,14 @@ pub enum Error {
 	InvalidSubstrateBlockNumber,
 	/// An invalid index has been received from an Ethereum node.
 	InvalidIncompleteIndex,
+	/// Failed to sign transaction.
+	TransactionSigningFailed(String),
+}
+
+impl From<WsNewDnsError> for Error {
+	fn from(error: WsNewDnsError) -> Self {
+		Error::WsConnectionError(error)
+	}
 }
 
 impl From<RequestError> for Error {
@@ -50,14 +63,19 @@ impl From<RequestError> for Error {
 
 impl MaybeConnectionError for Error {
 	fn is_connection_error(&self) -> bool {
-		matches!(*self, Error::Request(RequestError::TransportError(_)))
+		matches!(
+			*self,
+			Error::Request(RequestError::TransportError(_)) | Error::WsConnectionError(_)
+		)
 	}
 }
 
 impl ToString for Error {
 	fn to_string(&self) -> String {
 		match self {
+			Self::WsConnectionError(e) => e.to_string(),
 			Self::Request(e) => e.to_string(),
+			Self::SubscriptionsNotSupported => "Subscriptions are not supported by HTTP transport".to_string(),
 			Self::ResponseParseFailed(e) => e.to_string(),
 			Self::IncompleteHeader => {
 				"Incomplete Ethereum Header Received (missing some of required fields - hash, number, logs_bloom)"
@@ -66,6 +84,7 @@ impl ToString for Error {
 			Self::IncompleteTransaction => "Incomplete Ethereum Transaction (missing required field - raw)".to_string(),
 			Self::InvalidSubstrateBlockNumber => "Received an invalid Substrate block from Ethereum Node".to_string(),
 			Self::InvalidIncompleteIndex => "Received an invalid incomplete index from Ethereum Node".to_string(),
+			Self::TransactionSigningFailed(e) => format!("Failed to sign Ethereum transaction: {}", e),
 		}
 	}
 }
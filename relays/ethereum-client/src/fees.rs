@@ -0,0 +1,188 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! EIP-1559 transaction fees selection.
+
+use crate::types::{FeeHistory, U256};
+use crate::{Client, Error, Result};
+
+/// Multiplier of the next block base fee, used to compute `max_fee_per_gas`. With multiplier
+/// of 2, transaction stays includable even if base fee grows during 6 consecutive full blocks.
+const BASE_FEE_MULTIPLIER: u64 = 2;
+
+/// EIP-1559 transaction fees.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TransactionFees {
+	/// Maximal total fee (base fee + priority fee) per gas we agree to pay.
+	pub max_fee_per_gas: U256,
+	/// Maximal priority fee (tip to block author) per gas we agree to pay.
+	pub max_priority_fee_per_gas: U256,
+}
+
+/// Strategy of selecting EIP-1559 transaction fees.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GasFeeStrategy {
+	/// Always use given fees.
+	Static(TransactionFees),
+	/// Use priority fee, suggested by the node (`eth_maxPriorityFeePerGas`).
+	Oracle,
+	/// Use given percentile of priority fees, paid by transactions in recent blocks.
+	Percentile {
+		/// Number of recent blocks to inspect.
+		blocks: u64,
+		/// Percentile (0..=100) of priority fees, paid in every block.
+		percentile: f64,
+	},
+}
+
+impl Default for TransactionFees {
+	fn default() -> Self {
+		TransactionFees {
+			max_fee_per_gas: 8_000_000_000u64.into(),          // 8 Gwei
+			max_priority_fee_per_gas: 1_000_000_000u64.into(), // 1 Gwei
+		}
+	}
+}
+
+impl Default for GasFeeStrategy {
+	fn default() -> Self {
+		GasFeeStrategy::Static(Default::default())
+	}
+}
+
+impl GasFeeStrategy {
+	/// Select fees for the new transaction.
+	///
+	/// If `max_fee_per_gas_limit` is specified, selected `max_fee_per_gas` never exceeds it.
+	pub async fn transaction_fees(
+		&self,
+		client: &Client,
+		max_fee_per_gas_limit: Option<U256>,
+	) -> Result<TransactionFees> {
+		let fees = match *self {
+			GasFeeStrategy::Static(fees) => fees,
+			GasFeeStrategy::Oracle => {
+				let fee_history = client.fee_history(1, Vec::new()).await?;
+				let max_priority_fee_per_gas = client.max_priority_fee_per_gas().await?;
+				dynamic_fees(next_base_fee(&fee_history)?, max_priority_fee_per_gas)
+			}
+			GasFeeStrategy::Percentile { blocks, percentile } => {
+				let fee_history = client.fee_history(blocks, vec![percentile]).await?;
+				dynamic_fees(next_base_fee(&fee_history)?, median_reward(&fee_history))
+			}
+		};
+
+		Ok(limit_fees(fees, max_fee_per_gas_limit))
+	}
+}
+
+/// Compute fees given next block base fee and priority fee.
+fn dynamic_fees(next_base_fee: U256, max_priority_fee_per_gas: U256) -> TransactionFees {
+	TransactionFees {
+		max_fee_per_gas: next_base_fee
+			.saturating_mul(BASE_FEE_MULTIPLIER.into())
+			.saturating_add(max_priority_fee_per_gas),
+		max_priority_fee_per_gas,
+	}
+}
+
+/// Make sure that fees are not exceeding given limit.
+fn limit_fees(fees: TransactionFees, max_fee_per_gas_limit: Option<U256>) -> TransactionFees {
+	let max_fee_per_gas = match max_fee_per_gas_limit {
+		Some(max_fee_per_gas_limit) => std::cmp::min(fees.max_fee_per_gas, max_fee_per_gas_limit),
+		None => fees.max_fee_per_gas,
+	};
+	TransactionFees {
+		max_fee_per_gas,
+		max_priority_fee_per_gas: std::cmp::min(fees.max_priority_fee_per_gas, max_fee_per_gas),
+	}
+}
+
+/// Return base fee of the next block.
+///
+/// `eth_feeHistory` returns base fees of all requested blocks and of the next block, so the
+/// last element is what we're looking for. This fails on pre-London networks.
+fn next_base_fee(fee_history: &FeeHistory) -> Result<U256> {
+	fee_history
+		.base_fee_per_gas
+		.last()
+		.cloned()
+		.ok_or_else(|| Error::ResponseParseFailed("Ethereum node has not returned next block base fee".into()))
+}
+
+/// Return median of per-block priority fees (at requested percentile).
+///
+/// Blocks without transactions are reporting zero rewards, so they're excluded.
+fn median_reward(fee_history: &FeeHistory) -> U256 {
+	let mut rewards = fee_history
+		.reward
+		.iter()
+		.flatten()
+		.filter_map(|block_rewards| block_rewards.first().cloned())
+		.filter(|reward| !reward.is_zero())
+		.collect::<Vec<_>>();
+	rewards.sort();
+	rewards.get(rewards.len() / 2).cloned().unwrap_or_else(U256::zero)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn fee_history(base_fees: Vec<u64>, rewards: Vec<u64>) -> FeeHistory {
+		FeeHistory {
+			oldest_block: 0.into(),
+			base_fee_per_gas: base_fees.into_iter().map(Into::into).collect(),
+			gas_used_ratio: Vec::new(),
+			reward: Some(rewards.into_iter().map(|reward| vec![reward.into()]).collect()),
+		}
+	}
+
+	#[test]
+	fn next_base_fee_is_last_base_fee() {
+		assert_eq!(
+			next_base_fee(&fee_history(vec![10, 20, 30], vec![])).unwrap(),
+			30.into()
+		);
+		assert!(next_base_fee(&fee_history(vec![], vec![])).is_err());
+	}
+
+	#[test]
+	fn median_reward_ignores_empty_blocks() {
+		assert_eq!(median_reward(&fee_history(vec![], vec![0, 5, 1, 0, 3])), 3.into());
+		assert_eq!(median_reward(&fee_history(vec![], vec![0, 0])), 0.into());
+	}
+
+	#[test]
+	fn dynamic_fees_are_limited() {
+		let fees = dynamic_fees(100.into(), 10.into());
+		assert_eq!(
+			fees,
+			TransactionFees {
+				max_fee_per_gas: 210.into(),
+				max_priority_fee_per_gas: 10.into(),
+			}
+		);
+		assert_eq!(limit_fees(fees, None), fees);
+		assert_eq!(
+			limit_fees(fees, Some(5.into())),
+			TransactionFees {
+				max_fee_per_gas: 5.into(),
+				max_priority_fee_per_gas: 5.into(),
+			}
+		);
+	}
+}
@@ -20,15 +20,65 @@
 
 mod client;
 mod error;
+mod fees;
 mod rpc;
 mod sign;
 
-pub use crate::client::Client;
+pub use crate::client::{Client, NewHeadsSubscription};
 pub use crate::error::{Error, Result};
+pub use crate::fees::{GasFeeStrategy, TransactionFees};
 pub use crate::sign::{sign_and_submit_transaction, SigningParams};
+pub use relay_utils::rate_limiter::RateLimiterParams;
 
 pub mod types;
 
+/// Ethereum RPC transport.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConnectionTransport {
+	/// Plain HTTP transport. Subscriptions are not supported.
+	Http,
+	/// WebSocket transport.
+	Ws,
+	/// Secure WebSocket transport.
+	Wss,
+}
+
+impl ConnectionTransport {
+	/// Returns true if transport supports subscriptions.
+	pub fn supports_subscriptions(&self) -> bool {
+		matches!(*self, ConnectionTransport::Ws | ConnectionTransport::Wss)
+	}
+}
+
+/// Ethereum node address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeAddress {
+	/// Ethereum RPC host.
+	pub host: String,
+	/// Ethereum RPC port.
+	pub port: u16,
+}
+
+impl std::str::FromStr for NodeAddress {
+	type Err = String;
+
+	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+		let mut parts = s.rsplitn(2, ':');
+		let port = parts
+			.next()
+			.and_then(|port| port.parse().ok())
+			.ok_or_else(|| format!("Invalid Ethereum node port in '{}'", s))?;
+		let host = parts
+			.next()
+			.filter(|host| !host.is_empty())
+			.ok_or_else(|| format!("Missing Ethereum node host in '{}'", s))?;
+		Ok(NodeAddress {
+			host: host.into(),
+			port,
+		})
+	}
+}
+
 /// Ethereum connection params.
 #[derive(Debug, Clone)]
 pub struct ConnectionParams {
@@ -36,6 +86,13 @@ pub struct ConnectionParams {
 	pub host: String,
 	/// Ethereum RPC port.
 	pub port: u16,
+	/// Ethereum RPC transport.
+	pub transport: ConnectionTransport,
+	/// Redundant Ethereum nodes that are used when the primary node is unhealthy. Nodes are
+	/// listed in order of preference.
+	pub fallback_nodes: Vec<NodeAddress>,
+	/// Limits of requests to every (primary and fallback) node.
+	pub rate_limiter: RateLimiterParams,
 }
 
 impl Default for ConnectionParams {
@@ -43,6 +100,9 @@ impl Default for ConnectionParams {
 		ConnectionParams {
 			host: "localhost".into(),
 			port: 8545,
+			transport: ConnectionTransport::Http,
+			fallback_nodes: Vec::new(),
+			rate_limiter: Default::default(),
 		}
 	}
 }
@@ -22,7 +22,8 @@
 #![allow(unused_variables)]
 
 use crate::types::{
-	Address, Bytes, CallRequest, Header, HeaderWithTransactions, Receipt, Transaction, TransactionHash, H256, U256, U64,
+	Address, Bytes, CallRequest, FeeHistory, Header, HeaderWithTransactions, Receipt, Transaction, TransactionHash,
+	H256, U256, U64,
 };
 
 jsonrpsee::rpc_api! {
@@ -49,5 +50,9 @@ jsonrpsee::rpc_api! {
 		fn submit_transaction(transaction: Bytes) -> TransactionHash;
 		#[rpc(method = "eth_call", positional_params)]
 		fn call(transaction_call: CallRequest) -> Bytes;
+		#[rpc(method = "eth_feeHistory", positional_params)]
+		fn fee_history(block_count: U64, newest_block: String, reward_percentiles: Vec<f64>) -> FeeHistory;
+		#[rpc(method = "eth_maxPriorityFeePerGas", positional_params)]
+		fn max_priority_fee_per_gas() -> U256;
 	}
 }
@@ -14,10 +14,18 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::types::{Address, CallRequest, U256};
-use crate::{Client, Result};
+use crate::fees::{GasFeeStrategy, TransactionFees};
+use crate::types::{Address, CallRequest, SignedRawTx, U256};
+use crate::{Client, Error, Result};
 
-use parity_crypto::publickey::KeyPair;
+use parity_crypto::{
+	publickey::{sign, KeyPair, Message},
+	Keccak256,
+};
+use rlp::RlpStream;
+
+/// EIP-2718 type of the EIP-1559 transaction.
+const EIP1559_TRANSACTION_TYPE: u8 = 0x02;
 
 /// Ethereum signing params.
 #[derive(Clone, Debug)]
@@ -26,8 +34,10 @@ pub struct SigningParams {
 	pub chain_id: u64,
 	/// Ethereum transactions signer.
 	pub signer: KeyPair,
-	/// Gas price we agree to pay.
-	pub gas_price: U256,
+	/// Strategy of selecting transaction fees.
+	pub gas_fee_strategy: GasFeeStrategy,
+	/// Maximal fee per gas we agree to pay, regardless of the fee strategy.
+	pub max_fee_per_gas_limit: Option<U256>,
 }
 
 impl Default for SigningParams {
@@ -42,11 +52,82 @@ impl Default for SigningParams {
 					.expect("secret is hardcoded, thus valid; qed"),
 			)
 			.expect("secret is hardcoded, thus valid; qed"),
-			gas_price: 8_000_000_000u64.into(), // 8 Gwei
+			gas_fee_strategy: Default::default(),
+			max_fee_per_gas_limit: None,
 		}
 	}
 }
 
+impl SigningParams {
+	/// Select fees for the new transaction.
+	pub async fn transaction_fees(&self, client: &Client) -> Result<TransactionFees> {
+		self.gas_fee_strategy
+			.transaction_fees(client, self.max_fee_per_gas_limit)
+			.await
+	}
+}
+
+/// Unsigned EIP-1559 (type 2) transaction.
+#[derive(Clone, Debug)]
+pub struct Eip1559Transaction {
+	/// Ethereum chain id.
+	pub chain_id: u64,
+	/// Transaction nonce.
+	pub nonce: U256,
+	/// Transaction fees.
+	pub fees: TransactionFees,
+	/// Gas limit.
+	pub gas: U256,
+	/// Transaction destination. `None` if transaction deploys a contract.
+	pub to: Option<Address>,
+	/// Transferred value.
+	pub value: U256,
+	/// Transaction payload.
+	pub data: Vec<u8>,
+}
+
+impl Eip1559Transaction {
+	/// Sign transaction with given key pair.
+	pub fn sign(&self, key_pair: &KeyPair) -> Result<SignedRawTx> {
+		let message = Message::from(Keccak256::<[u8; 32]>::keccak256(&self.encode(None)));
+		let signature =
+			sign(key_pair.secret(), &message).map_err(|e| Error::TransactionSigningFailed(format!("{}", e)))?;
+		Ok(self.encode(Some((
+			signature.v(),
+			U256::from_big_endian(signature.r()),
+			U256::from_big_endian(signature.s()),
+		))))
+	}
+
+	/// Encode transaction. If signature is not provided, the returned bytes is the payload
+	/// that needs to be signed.
+	fn encode(&self, signature: Option<(u8, U256, U256)>) -> Vec<u8> {
+		let mut stream = RlpStream::new_list(if signature.is_some() { 12 } else { 9 });
+		stream.append(&self.chain_id);
+		stream.append(&self.nonce);
+		stream.append(&self.fees.max_priority_fee_per_gas);
+		stream.append(&self.fees.max_fee_per_gas);
+		stream.append(&self.gas);
+		match self.to {
+			Some(ref to) => stream.append(to),
+			None => stream.append_empty_data(),
+		};
+		stream.append(&self.value);
+		stream.append(&self.data);
+		// we never use access lists
+		stream.begin_list(0);
+		if let Some((v, r, s)) = signature {
+			stream.append(&v);
+			stream.append(&r);
+			stream.append(&s);
+		}
+
+		let mut encoded = vec![EIP1559_TRANSACTION_TYPE];
+		encoded.extend_from_slice(&stream.out());
+		encoded
+	}
+}
+
 /// Sign and submit tranaction using given Ethereum client.
 pub async fn sign_and_submit_transaction(
 	client: &Client,
@@ -69,17 +150,46 @@ pub async fn sign_and_submit_transaction(
 		..Default::default()
 	};
 	let gas = client.estimate_gas(call_request).await?;
+	let fees = params.transaction_fees(client).await?;
 
-	let raw_transaction = ethereum_tx_sign::RawTransaction {
+	let raw_transaction = Eip1559Transaction {
+		chain_id: params.chain_id,
 		nonce,
+		fees,
+		gas: if double_gas { gas.saturating_mul(2.into()) } else { gas },
 		to: contract_address,
 		value: U256::zero(),
-		gas: if double_gas { gas.saturating_mul(2.into()) } else { gas },
-		gas_price: params.gas_price,
 		data: encoded_call,
 	}
-	.sign(&params.signer.secret().as_fixed_bytes().into(), &params.chain_id);
+	.sign(&params.signer)?;
 
 	let _ = client.submit_transaction(raw_transaction).await?;
 	Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn eip1559_transaction_is_encoded_properly() {
+		let transaction = Eip1559Transaction {
+			chain_id: 1,
+			nonce: 0.into(),
+			fees: TransactionFees {
+				max_fee_per_gas: 2.into(),
+				max_priority_fee_per_gas: 1.into(),
+			},
+			gas: 21_000.into(),
+			to: None,
+			value: 0.into(),
+			data: vec![],
+		};
+
+		// 0x02 || rlp([1, 0, 1, 2, 21000, "", 0, "", []])
+		assert_eq!(
+			transaction.encode(None),
+			hex::decode("02cb01800102825208808080c0").unwrap(),
+		);
+	}
+}
@@ -17,6 +17,7 @@
 //! Common types that are used in relay <-> Ethereum node communications.
 
 use headers_relay::sync_types::SourceHeader;
+use serde::Deserialize;
 
 pub use web3::types::{Address, Bytes, CallRequest, H256, U128, U256, U64};
 
@@ -48,11 +49,30 @@ impl std::ops::Deref for SyncHeader {
 	}
 }
 
+/// Ethereum header, received from the `newHeads` subscription.
+pub type NewHeader = web3::types::BlockHeader;
+
 /// Ethereum header with transactions type.
 pub type HeaderWithTransactions = web3::types::Block<Transaction>;
 
 /// Ethereum transaction receipt type.
-pub type Receipt = web3::types::TransactionReceipt;
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Receipt {
+	/// Type (EIP-2718) of the transaction. None if node doesn't support typed transactions.
+	#[serde(rename = "type")]
+	pub transaction_type: Option<U64>,
+	/// All other receipt fields.
+	#[serde(flatten)]
+	pub receipt: web3::types::TransactionReceipt,
+}
+
+impl std::ops::Deref for Receipt {
+	type Target = web3::types::TransactionReceipt;
+
+	fn deref(&self) -> &Self::Target {
+		&self.receipt
+	}
+}
 
 /// Ethereum header ID.
 pub type HeaderId = relay_utils::HeaderId<H256, u64>;
@@ -60,6 +80,20 @@ pub type HeaderId = relay_utils::HeaderId<H256, u64>;
 /// A raw Ethereum transaction that's been signed.
 pub type SignedRawTx = Vec<u8>;
 
+/// Fee history of recent blocks, returned by the `eth_feeHistory` method.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistory {
+	/// Number of the oldest block in the history.
+	pub oldest_block: U64,
+	/// Base fees of all blocks in the history and of the next block after the newest one.
+	pub base_fee_per_gas: Vec<U256>,
+	/// Ratios of gas used to gas limit of all blocks in the history.
+	pub gas_used_ratio: Vec<f64>,
+	/// Priority fees at requested percentiles, paid in all blocks in the history.
+	pub reward: Option<Vec<Vec<U256>>>,
+}
+
 impl From<Header> for SyncHeader {
 	fn from(header: Header) -> Self {
 		Self(header)
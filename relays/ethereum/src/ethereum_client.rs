@@ -15,7 +15,7 @@
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::rpc_errors::RpcError;
-use crate::substrate_sync_loop::QueuedRialtoHeader;
+use crate::substrate_sync_loop::{QueuedSubstrateHeader, SubstrateHeaderId, SubstrateSourceChain};
 
 use async_trait::async_trait;
 use codec::{Decode, Encode};
@@ -26,10 +26,9 @@ use relay_ethereum_client::{
 	types::{Address, CallRequest, HeaderId as EthereumHeaderId, Receipt, H256, U256},
 	Client as EthereumClient, Error as EthereumNodeError, SigningParams as EthereumSigningParams,
 };
-use relay_rialto_client::HeaderId as RialtoHeaderId;
-use relay_utils::{HeaderId, MaybeConnectionError};
+use relay_utils::{BlockNumberBase, HeaderId, MaybeConnectionError};
 use sp_runtime::Justification;
-use std::collections::HashSet;
+use std::{collections::HashSet, convert::TryFrom};
 
 // to encode/decode contract calls
 ethabi_contract::use_contract!(bridge_contract, "res/substrate-bridge-abi.json");
@@ -41,34 +40,50 @@ type RpcResult<T> = std::result::Result<T, RpcError>;
 #[async_trait]
 pub trait EthereumHighLevelRpc {
 	/// Returns best Substrate block that PoA chain knows of.
-	async fn best_substrate_block(&self, contract_address: Address) -> RpcResult<RialtoHeaderId>;
+	async fn best_substrate_block<C: SubstrateSourceChain>(
+		&self,
+		contract_address: Address,
+	) -> RpcResult<SubstrateHeaderId<C>>
+	where
+		C::BlockNumber: BlockNumberBase;
 
 	/// Returns true if Substrate header is known to Ethereum node.
-	async fn substrate_header_known(
+	async fn substrate_header_known<C: SubstrateSourceChain>(
 		&self,
 		contract_address: Address,
-		id: RialtoHeaderId,
-	) -> RpcResult<(RialtoHeaderId, bool)>;
+		id: SubstrateHeaderId<C>,
+	) -> RpcResult<(SubstrateHeaderId<C>, bool)>
+	where
+		C::BlockNumber: BlockNumberBase;
 
 	/// Submits Substrate headers to Ethereum contract.
-	async fn submit_substrate_headers(
+	async fn submit_substrate_headers<C: SubstrateSourceChain>(
 		&self,
 		params: EthereumSigningParams,
 		contract_address: Address,
-		headers: Vec<QueuedRialtoHeader>,
-	) -> SubmittedHeaders<RialtoHeaderId, RpcError>;
+		headers: Vec<QueuedSubstrateHeader<C>>,
+	) -> SubmittedHeaders<SubstrateHeaderId<C>, RpcError>
+	where
+		C::BlockNumber: BlockNumberBase;
 
 	/// Returns ids of incomplete Substrate headers.
-	async fn incomplete_substrate_headers(&self, contract_address: Address) -> RpcResult<HashSet<RialtoHeaderId>>;
+	async fn incomplete_substrate_headers<C: SubstrateSourceChain>(
+		&self,
+		contract_address: Address,
+	) -> RpcResult<HashSet<SubstrateHeaderId<C>>>
+	where
+		C::BlockNumber: BlockNumberBase;
 
 	/// Complete Substrate header.
-	async fn complete_substrate_header(
+	async fn complete_substrate_header<C: SubstrateSourceChain>(
 		&self,
 		params: EthereumSigningParams,
 		contract_address: Address,
-		id: RialtoHeaderId,
+		id: SubstrateHeaderId<C>,
 		justification: Justification,
-	) -> RpcResult<RialtoHeaderId>;
+	) -> RpcResult<SubstrateHeaderId<C>>
+	where
+		C::BlockNumber: BlockNumberBase;
 
 	/// Submit ethereum transaction.
 	async fn submit_ethereum_transaction(
@@ -90,7 +105,13 @@ pub trait EthereumHighLevelRpc {
 
 #[async_trait]
 impl EthereumHighLevelRpc for EthereumClient {
-	async fn best_substrate_block(&self, contract_address: Address) -> RpcResult<RialtoHeaderId> {
+	async fn best_substrate_block<C: SubstrateSourceChain>(
+		&self,
+		contract_address: Address,
+	) -> RpcResult<SubstrateHeaderId<C>>
+	where
+		C::BlockNumber: BlockNumberBase,
+	{
 		let (encoded_call, call_decoder) = bridge_contract::functions::best_known_header::call();
 		let call_request = CallRequest {
 			to: Some(contract_address),
@@ -100,20 +121,21 @@ impl EthereumHighLevelRpc for EthereumClient {
 
 		let call_result = self.eth_call(call_request).await?;
 		let (number, raw_hash) = call_decoder.decode(&call_result.0)?;
-		let hash = rialto_runtime::Hash::decode(&mut &raw_hash[..])?;
-
-		if number != number.low_u32().into() {
-			return Err(RpcError::Ethereum(EthereumNodeError::InvalidSubstrateBlockNumber));
-		}
+		let hash = C::Hash::decode(&mut &raw_hash[..])?;
+		let number =
+			substrate_block_number(number).ok_or(RpcError::Ethereum(EthereumNodeError::InvalidSubstrateBlockNumber))?;
 
-		Ok(HeaderId(number.low_u32(), hash))
+		Ok(HeaderId(number, hash))
 	}
 
-	async fn substrate_header_known(
+	async fn substrate_header_known<C: SubstrateSourceChain>(
 		&self,
 		contract_address: Address,
-		id: RialtoHeaderId,
-	) -> RpcResult<(RialtoHeaderId, bool)> {
+		id: SubstrateHeaderId<C>,
+	) -> RpcResult<(SubstrateHeaderId<C>, bool)>
+	where
+		C::BlockNumber: BlockNumberBase,
+	{
 		let (encoded_call, call_decoder) = bridge_contract::functions::is_known_header::call(id.1);
 		let call_request = CallRequest {
 			to: Some(contract_address),
@@ -127,12 +149,15 @@ impl EthereumHighLevelRpc for EthereumClient {
 		Ok((id, is_known_block))
 	}
 
-	async fn submit_substrate_headers(
+	async fn submit_substrate_headers<C: SubstrateSourceChain>(
 		&self,
 		params: EthereumSigningParams,
 		contract_address: Address,
-		headers: Vec<QueuedRialtoHeader>,
-	) -> SubmittedHeaders<RialtoHeaderId, RpcError> {
+		headers: Vec<QueuedSubstrateHeader<C>>,
+	) -> SubmittedHeaders<SubstrateHeaderId<C>, RpcError>
+	where
+		C::BlockNumber: BlockNumberBase,
+	{
 		// read nonce of signer
 		let address: Address = params.signer.address().as_fixed_bytes().into();
 		let nonce = match self.account_nonce(address).await {
@@ -161,7 +186,13 @@ impl EthereumHighLevelRpc for EthereumClient {
 		.await
 	}
 
-	async fn incomplete_substrate_headers(&self, contract_address: Address) -> RpcResult<HashSet<RialtoHeaderId>> {
+	async fn incomplete_substrate_headers<C: SubstrateSourceChain>(
+		&self,
+		contract_address: Address,
+	) -> RpcResult<HashSet<SubstrateHeaderId<C>>>
+	where
+		C::BlockNumber: BlockNumberBase,
+	{
 		let (encoded_call, call_decoder) = bridge_contract::functions::incomplete_headers::call();
 		let call_request = CallRequest {
 			to: Some(contract_address),
@@ -176,32 +207,30 @@ impl EthereumHighLevelRpc for EthereumClient {
 		let incomplete_ids = incomplete_headers_numbers
 			.into_iter()
 			.zip(incomplete_headers_hashes)
-			.filter_map(|(number, hash)| {
-				if number != number.low_u32().into() {
-					return None;
-				}
-
-				Some(HeaderId(number.low_u32(), hash))
-			})
+			.filter_map(|(number, hash)| substrate_block_number(number).map(|number| HeaderId(number, hash)))
 			.collect();
 
 		Ok(incomplete_ids)
 	}
 
-	async fn complete_substrate_header(
+	async fn complete_substrate_header<C: SubstrateSourceChain>(
 		&self,
 		params: EthereumSigningParams,
 		contract_address: Address,
-		id: RialtoHeaderId,
+		id: SubstrateHeaderId<C>,
 		justification: Justification,
-	) -> RpcResult<RialtoHeaderId> {
+	) -> RpcResult<SubstrateHeaderId<C>>
+	where
+		C::BlockNumber: BlockNumberBase,
+	{
+		let number: u64 = id.0.into();
 		let _ = self
 			.submit_ethereum_transaction(
 				&params,
 				Some(contract_address),
 				None,
 				false,
-				bridge_contract::functions::import_finality_proof::encode_input(id.0, id.1, justification),
+				bridge_contract::functions::import_finality_proof::encode_input(number, id.1, justification),
 			)
 			.await?;
 
@@ -235,6 +264,17 @@ impl EthereumHighLevelRpc for EthereumClient {
 	}
 }
 
+/// Convert block number, returned by the contract, into Substrate block number.
+///
+/// Returns `None` if number doesn't fit into Substrate block number type.
+fn substrate_block_number<N: TryFrom<u64>>(number: U256) -> Option<N> {
+	if number > u64::max_value().into() {
+		return None;
+	}
+
+	N::try_from(number.low_u64()).ok()
+}
+
 /// Max number of headers which can be sent to Solidity contract.
 pub const HEADERS_BATCH: usize = 4;
 
@@ -244,22 +284,28 @@ pub const HEADERS_BATCH: usize = 4;
 /// This struct is meant to encapsulate this limitation.
 #[derive(Debug)]
 #[cfg_attr(test, derive(Clone))]
-pub struct HeadersBatch {
-	pub header1: QueuedRialtoHeader,
-	pub header2: Option<QueuedRialtoHeader>,
-	pub header3: Option<QueuedRialtoHeader>,
-	pub header4: Option<QueuedRialtoHeader>,
+pub struct HeadersBatch<C: SubstrateSourceChain>
+where
+	C::BlockNumber: BlockNumberBase,
+{
+	pub header1: QueuedSubstrateHeader<C>,
+	pub header2: Option<QueuedSubstrateHeader<C>>,
+	pub header3: Option<QueuedSubstrateHeader<C>>,
+	pub header4: Option<QueuedSubstrateHeader<C>>,
 }
 
-impl HeadersBatch {
+impl<C: SubstrateSourceChain> HeadersBatch<C>
+where
+	C::BlockNumber: BlockNumberBase,
+{
 	/// Create new headers from given header & ids collections.
 	///
 	/// This method will pop `HEADERS_BATCH` items from both collections
-	/// and construct `Headers` object and a vector of `RialtoHeaderId`s.
+	/// and construct `Headers` object and a vector of `SubstrateHeaderId`s.
 	pub fn pop_from(
-		headers: &mut Vec<QueuedRialtoHeader>,
-		ids: &mut Vec<RialtoHeaderId>,
-	) -> Result<(Self, Vec<RialtoHeaderId>), ()> {
+		headers: &mut Vec<QueuedSubstrateHeader<C>>,
+		ids: &mut Vec<SubstrateHeaderId<C>>,
+	) -> Result<(Self, Vec<SubstrateHeaderId<C>>), ()> {
 		if headers.len() != ids.len() {
 			log::error!(target: "bridge", "Collection size mismatch ({} vs {})", headers.len(), ids.len());
 			return Err(());
@@ -289,7 +335,7 @@ impl HeadersBatch {
 	/// Returns unified array of headers.
 	///
 	/// The first element is always `Some`.
-	fn headers(&self) -> [Option<&QueuedRialtoHeader>; HEADERS_BATCH] {
+	fn headers(&self) -> [Option<&QueuedSubstrateHeader<C>>; HEADERS_BATCH] {
 		[
 			Some(&self.header1),
 			self.header2.as_ref(),
@@ -300,7 +346,7 @@ impl HeadersBatch {
 
 	/// Encodes all headers. If header is not present an empty vector will be returned.
 	pub fn encode(&self) -> [Vec<u8>; HEADERS_BATCH] {
-		let encode = |h: &QueuedRialtoHeader| h.header().encode();
+		let encode = |h: &QueuedSubstrateHeader<C>| h.header().encode();
 		let headers = self.headers();
 		[
 			headers[0].map(encode).unwrap_or_default(),
@@ -311,7 +357,7 @@ impl HeadersBatch {
 	}
 	/// Returns number of contained headers.
 	pub fn len(&self) -> usize {
-		let is_set = |h: &Option<&QueuedRialtoHeader>| if h.is_some() { 1 } else { 0 };
+		let is_set = |h: &Option<&QueuedSubstrateHeader<C>>| if h.is_some() { 1 } else { 0 };
 		self.headers().iter().map(is_set).sum()
 	}
 
@@ -334,16 +380,19 @@ impl HeadersBatch {
 
 /// Substrate headers submitter API.
 #[async_trait]
-trait HeadersSubmitter {
+trait HeadersSubmitter<C: SubstrateSourceChain>
+where
+	C::BlockNumber: BlockNumberBase,
+{
 	/// Returns Ok(0) if all given not-yet-imported headers are complete.
 	/// Returns Ok(index != 0) where index is 1-based index of first header that is incomplete.
 	///
 	/// Returns Err(()) if contract has rejected headers. This means that the contract is
 	/// unable to import first header (e.g. it may already be imported).
-	async fn is_headers_incomplete(&self, headers: &HeadersBatch) -> RpcResult<usize>;
+	async fn is_headers_incomplete(&self, headers: &HeadersBatch<C>) -> RpcResult<usize>;
 
 	/// Submit given headers to Ethereum node.
-	async fn submit_headers(&mut self, headers: HeadersBatch) -> RpcResult<()>;
+	async fn submit_headers(&mut self, headers: HeadersBatch<C>) -> RpcResult<()>;
 }
 
 /// Implementation of Substrate headers submitter that sends headers to running Ethereum node.
@@ -355,8 +404,11 @@ struct EthereumHeadersSubmitter {
 }
 
 #[async_trait]
-impl HeadersSubmitter for EthereumHeadersSubmitter {
-	async fn is_headers_incomplete(&self, headers: &HeadersBatch) -> RpcResult<usize> {
+impl<C: SubstrateSourceChain> HeadersSubmitter<C> for EthereumHeadersSubmitter
+where
+	C::BlockNumber: BlockNumberBase,
+{
+	async fn is_headers_incomplete(&self, headers: &HeadersBatch<C>) -> RpcResult<usize> {
 		let [h1, h2, h3, h4] = headers.encode();
 		let (encoded_call, call_decoder) = bridge_contract::functions::is_incomplete_headers::call(h1, h2, h3, h4);
 		let call_request = CallRequest {
@@ -374,7 +426,7 @@ impl HeadersSubmitter for EthereumHeadersSubmitter {
 		Ok(incomplete_index.low_u32() as _)
 	}
 
-	async fn submit_headers(&mut self, headers: HeadersBatch) -> RpcResult<()> {
+	async fn submit_headers(&mut self, headers: HeadersBatch<C>) -> RpcResult<()> {
 		let [h1, h2, h3, h4] = headers.encode();
 		let result = self
 			.client
@@ -396,10 +448,13 @@ impl HeadersSubmitter for EthereumHeadersSubmitter {
 }
 
 /// Submit multiple Substrate headers.
-async fn submit_substrate_headers(
-	mut header_submitter: impl HeadersSubmitter,
-	mut headers: Vec<QueuedRialtoHeader>,
-) -> SubmittedHeaders<RialtoHeaderId, RpcError> {
+async fn submit_substrate_headers<C: SubstrateSourceChain>(
+	mut header_submitter: impl HeadersSubmitter<C>,
+	mut headers: Vec<QueuedSubstrateHeader<C>>,
+) -> SubmittedHeaders<SubstrateHeaderId<C>, RpcError>
+where
+	C::BlockNumber: BlockNumberBase,
+{
 	let mut submitted_headers = SubmittedHeaders::default();
 
 	let mut ids = headers.iter().map(|header| header.id()).rev().collect::<Vec<_>>();
@@ -424,12 +479,15 @@ async fn submit_substrate_headers(
 }
 
 /// Submit 4 Substrate headers in single PoA transaction.
-async fn submit_substrate_headers_batch(
-	header_submitter: &mut impl HeadersSubmitter,
-	submitted_headers: &mut SubmittedHeaders<RialtoHeaderId, RpcError>,
-	mut ids: Vec<RialtoHeaderId>,
-	mut headers: HeadersBatch,
-) -> Option<RpcError> {
+async fn submit_substrate_headers_batch<C: SubstrateSourceChain>(
+	header_submitter: &mut impl HeadersSubmitter<C>,
+	submitted_headers: &mut SubmittedHeaders<SubstrateHeaderId<C>, RpcError>,
+	mut ids: Vec<SubstrateHeaderId<C>>,
+	mut headers: HeadersBatch<C>,
+) -> Option<RpcError>
+where
+	C::BlockNumber: BlockNumberBase,
+{
 	debug_assert_eq!(ids.len(), headers.len(),);
 
 	// if parent of first header is either incomplete, or rejected, we assume that contract
@@ -488,16 +546,20 @@ async fn submit_substrate_headers_batch(
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use relay_rialto_client::Rialto;
 	use sp_runtime::traits::Header;
 
+	type QueuedRialtoHeader = QueuedSubstrateHeader<Rialto>;
+	type RialtoHeaderId = SubstrateHeaderId<Rialto>;
+
 	struct TestHeadersSubmitter {
 		incomplete: Vec<RialtoHeaderId>,
 		failed: Vec<RialtoHeaderId>,
 	}
 
 	#[async_trait]
-	impl HeadersSubmitter for TestHeadersSubmitter {
-		async fn is_headers_incomplete(&self, headers: &HeadersBatch) -> RpcResult<usize> {
+	impl HeadersSubmitter<Rialto> for TestHeadersSubmitter {
+		async fn is_headers_incomplete(&self, headers: &HeadersBatch<Rialto>) -> RpcResult<usize> {
 			if self.incomplete.iter().any(|i| i.0 == headers.header1.id().0) {
 				Ok(1)
 			} else {
@@ -505,7 +567,7 @@ mod tests {
 			}
 		}
 
-		async fn submit_headers(&mut self, headers: HeadersBatch) -> RpcResult<()> {
+		async fn submit_headers(&mut self, headers: HeadersBatch<Rialto>) -> RpcResult<()> {
 			if self.failed.iter().any(|i| i.0 == headers.header1.id().0) {
 				Err(RpcError::Ethereum(EthereumNodeError::InvalidSubstrateBlockNumber))
 			} else {
@@ -575,7 +637,7 @@ mod tests {
 		assert!(submitted_headers.fatal_error.is_some());
 	}
 
-	fn headers_batch() -> HeadersBatch {
+	fn headers_batch() -> HeadersBatch<Rialto> {
 		let mut init_headers = vec![header(1), header(2), header(3), header(4), header(5)];
 		init_headers.reverse();
 		let mut init_ids = init_headers.iter().map(|h| h.id()).collect();
@@ -16,17 +16,19 @@
 
 use crate::ethereum_client::{bridge_contract, EthereumHighLevelRpc};
 use crate::rpc_errors::RpcError;
+use crate::substrate_sync_loop::SubstrateSourceChain;
 
 use codec::{Decode, Encode};
 use num_traits::Zero;
 use relay_ethereum_client::{
 	Client as EthereumClient, ConnectionParams as EthereumConnectionParams, SigningParams as EthereumSigningParams,
 };
-use relay_rialto_client::{HeaderId as RialtoHeaderId, Rialto};
 use relay_substrate_client::{
 	Client as SubstrateClient, ConnectionParams as SubstrateConnectionParams, OpaqueGrandpaAuthoritiesSet,
 };
 use relay_utils::HeaderId;
+use sp_core::H256;
+use sp_runtime::traits::Header as HeaderT;
 
 /// Ethereum synchronization parameters.
 #[derive(Debug)]
@@ -48,7 +50,7 @@ pub struct EthereumDeployContractParams {
 }
 
 /// Deploy Bridge contract on Ethereum chain.
-pub fn run(params: EthereumDeployContractParams) {
+pub fn run<C: SubstrateSourceChain>(params: EthereumDeployContractParams) {
 	let mut local_pool = futures::executor::LocalPool::new();
 
 	let EthereumDeployContractParams {
@@ -62,8 +64,8 @@ pub fn run(params: EthereumDeployContractParams) {
 	} = params;
 
 	let result = local_pool.run_until(async move {
-		let eth_client = EthereumClient::new(eth_params);
-		let sub_client = SubstrateClient::<Rialto>::new(sub_params).await.map_err(RpcError::Substrate)?;
+		let eth_client = EthereumClient::new(eth_params).await.map_err(RpcError::Ethereum)?;
+		let sub_client = SubstrateClient::<C>::new(sub_params).await.map_err(RpcError::Substrate)?;
 
 		let (initial_header_id, initial_header) = prepare_initial_header(&sub_client, sub_initial_header).await?;
 		let initial_set_id = sub_initial_authorities_set_id.unwrap_or(0);
@@ -99,14 +101,14 @@ pub fn run(params: EthereumDeployContractParams) {
 }
 
 /// Prepare initial header.
-async fn prepare_initial_header(
-	sub_client: &SubstrateClient<Rialto>,
+async fn prepare_initial_header<C: SubstrateSourceChain>(
+	sub_client: &SubstrateClient<C>,
 	sub_initial_header: Option<Vec<u8>>,
-) -> Result<(RialtoHeaderId, Vec<u8>), String> {
+) -> Result<(HeaderId<H256, C::BlockNumber>, Vec<u8>), String> {
 	match sub_initial_header {
-		Some(raw_initial_header) => match rialto_runtime::Header::decode(&mut &raw_initial_header[..]) {
+		Some(raw_initial_header) => match C::Header::decode(&mut &raw_initial_header[..]) {
 			Ok(initial_header) => Ok((
-				HeaderId(initial_header.number, initial_header.hash()),
+				HeaderId(*initial_header.number(), initial_header.hash()),
 				raw_initial_header,
 			)),
 			Err(error) => Err(format!("Error decoding initial header: {}", error)),
@@ -121,9 +123,9 @@ async fn prepare_initial_header(
 }
 
 /// Prepare initial GRANDPA authorities set.
-async fn prepare_initial_authorities_set(
-	sub_client: &SubstrateClient<Rialto>,
-	sub_initial_header_hash: rialto_runtime::Hash,
+async fn prepare_initial_authorities_set<C: SubstrateSourceChain>(
+	sub_client: &SubstrateClient<C>,
+	sub_initial_header_hash: H256,
 	sub_initial_authorities_set: Option<Vec<u8>>,
 ) -> Result<OpaqueGrandpaAuthoritiesSet, String> {
 	let initial_authorities_set = match sub_initial_authorities_set {
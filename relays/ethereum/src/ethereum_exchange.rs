@@ -235,15 +235,15 @@ impl TargetClient<EthereumToSubstrateExchange> for SubstrateTransactionsTarget {
 	}
 
 	async fn filter_transaction_proof(&self, proof: &EthereumTransactionInclusionProof) -> Result<bool, Self::Error> {
-		// let's try to parse transaction locally
-		let (raw_tx, raw_tx_receipt) = &proof.proof[proof.index as usize];
-		let parse_result = rialto_runtime::exchange::EthTransaction::parse(raw_tx);
+		// let's try to parse transaction locally (both plain and ERC-20 transfers are recognized)
+		let tx = &proof.proof[proof.index as usize];
+		let parse_result = rialto_runtime::exchange::EthTransaction::parse(tx);
 		if parse_result.is_err() {
 			return Ok(false);
 		}
 
 		// now let's check if transaction is successful
-		match bp_eth_poa::Receipt::is_successful_raw_receipt(raw_tx_receipt) {
+		match bp_eth_poa::Receipt::is_successful_raw_receipt(&tx.1) {
 			Ok(true) => (),
 			_ => return Ok(false),
 		}
@@ -284,7 +284,7 @@ fn run_single_transaction_relay(params: EthereumExchangeParams, eth_tx_hash: H25
 	} = params;
 
 	let result = local_pool.run_until(async move {
-		let eth_client = EthereumClient::new(eth_params);
+		let eth_client = EthereumClient::new(eth_params).await.map_err(RpcError::Ethereum)?;
 		let sub_client = SubstrateClient::<Rialto>::new(sub_params)
 			.await
 			.map_err(RpcError::Substrate)?;
@@ -330,7 +330,8 @@ fn run_auto_transactions_relay_loop(params: EthereumExchangeParams, eth_start_wi
 	} = params;
 
 	let do_run_loop = move || -> Result<(), String> {
-		let eth_client = EthereumClient::new(eth_params);
+		let eth_client = async_std::task::block_on(EthereumClient::new(eth_params))
+			.map_err(|err| format!("Error starting Ethereum client: {:?}", err))?;
 		let sub_client = async_std::task::block_on(SubstrateClient::<Rialto>::new(sub_params))
 			.map_err(|err| format!("Error starting Substrate client: {:?}", err))?;
 
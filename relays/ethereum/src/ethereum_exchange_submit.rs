@@ -54,7 +54,9 @@ pub fn run(params: EthereumExchangeSubmitParams) {
 	} = params;
 
 	let result: Result<_, String> = local_pool.run_until(async move {
-		let eth_client = EthereumClient::new(eth_params);
+		let eth_client = EthereumClient::new(eth_params)
+			.await
+			.map_err(|err| format!("error connecting to Ethereum node: {:?}", err))?;
 
 		let eth_signer_address = eth_sign.signer.address();
 		let sub_recipient_encoded = sub_recipient;
@@ -75,9 +77,15 @@ pub fn run(params: EthereumExchangeSubmitParams) {
 			})
 			.await
 			.map_err(|err| format!("error estimating gas requirements: {:?}", err))?;
+		// exchange transactions are parsed by the runtime, which only supports legacy transactions,
+		// so we're paying the whole max fee
+		let fees = eth_sign
+			.transaction_fees(&eth_client)
+			.await
+			.map_err(|err| format!("error selecting transaction fees: {:?}", err))?;
 		let eth_tx_unsigned = UnsignedTransaction {
 			nonce,
-			gas_price: eth_sign.gas_price,
+			gas_price: fees.max_fee_per_gas,
 			gas,
 			to: Some(LOCK_FUNDS_ADDRESS.into()),
 			value: eth_amount,
@@ -25,13 +25,13 @@ use crate::substrate_types::{into_substrate_ethereum_header, into_substrate_ethe
 use async_trait::async_trait;
 use codec::Encode;
 use headers_relay::{
-	sync::{HeadersSyncParams, TargetTransactionMode},
-	sync_loop::{SourceClient, TargetClient},
+	sync::{HeadersSync, HeadersSyncParams, TargetTransactionMode},
+	sync_loop::{SourceClient, SyncMaintain, TargetClient},
 	sync_types::{HeadersSyncPipeline, QueuedHeader, SourceHeader, SubmittedHeaders},
 };
 use relay_ethereum_client::{
 	types::{HeaderHash, HeaderId as EthereumHeaderId, Receipt, SyncHeader as Header},
-	Client as EthereumClient, ConnectionParams as EthereumConnectionParams,
+	Client as EthereumClient, ConnectionParams as EthereumConnectionParams, Error as EthereumNodeError,
 };
 use relay_rialto_client::{Rialto, SigningParams as RialtoSigningParams};
 use relay_substrate_client::{
@@ -40,14 +40,28 @@ use relay_substrate_client::{
 use relay_utils::metrics::MetricsParams;
 
 use std::fmt::Debug;
-use std::{collections::HashSet, sync::Arc, time::Duration};
+use std::{
+	collections::HashSet,
+	sync::{Arc, Mutex},
+	time::{Duration, Instant},
+};
+
+/// Proof that the mutex is never poisoned, because we never panic while holding it.
+const MUTEX_POISONED_PROOF: &str = "mutex is never poisoned; qed";
 
 pub mod consts {
 	use super::*;
 
 	/// Interval at which we check new Ethereum headers when we are synced/almost synced.
 	pub const ETHEREUM_TICK_INTERVAL: Duration = Duration::from_secs(10);
-	/// Max number of headers in single submit transaction.
+	/// Interval at which we check new Ethereum headers when best block number is received from
+	/// the `newHeads` subscription. Checks are cheap, because no RPC calls are made.
+	pub const ETHEREUM_SUBSCRIPTION_TICK_INTERVAL: Duration = Duration::from_secs(1);
+	/// If we haven't received new header from the `newHeads` subscription during this interval,
+	/// we're falling back to polling the Ethereum node.
+	pub const ETHEREUM_SUBSCRIPTION_STALL_TIMEOUT: Duration = Duration::from_secs(60);
+	/// Max number of headers in single submit transaction. Actual number of headers in a transaction
+	/// is adjusted dynamically, depending on Substrate block weight usage and submission results.
 	pub const MAX_HEADERS_IN_SINGLE_SUBMIT: usize = 32;
 	/// Max total size of headers in single submit transaction. This only affects signed
 	/// submissions, when several headers are submitted at once. 4096 is the maximal **expected**
@@ -104,15 +118,24 @@ impl HeadersSyncPipeline for EthereumHeadersSyncPipeline {
 /// Queued ethereum header ID.
 pub type QueuedEthereumHeader = QueuedHeader<EthereumHeadersSyncPipeline>;
 
+/// Best Ethereum block number, received from the `newHeads` subscription, along with the time
+/// when it has been received.
+type SubscriptionBestBlock = Arc<Mutex<Option<(u64, Instant)>>>;
+
 /// Ethereum client as headers source.
 struct EthereumHeadersSource {
 	/// Ethereum node client.
 	client: EthereumClient,
+	/// Best block number from the `newHeads` subscription (if subscription is active).
+	subscription_best_block: Option<SubscriptionBestBlock>,
 }
 
 impl EthereumHeadersSource {
-	fn new(client: EthereumClient) -> Self {
-		Self { client }
+	fn new(client: EthereumClient, subscription_best_block: Option<SubscriptionBestBlock>) -> Self {
+		Self {
+			client,
+			subscription_best_block,
+		}
 	}
 }
 
@@ -121,6 +144,16 @@ impl SourceClient<EthereumHeadersSyncPipeline> for EthereumHeadersSource {
 	type Error = RpcError;
 
 	async fn best_block_number(&self) -> Result<u64, Self::Error> {
+		if let Some(ref subscription_best_block) = self.subscription_best_block {
+			let subscription_best_block = *subscription_best_block.lock().expect(MUTEX_POISONED_PROOF);
+			match subscription_best_block {
+				Some((number, received_at)) if received_at.elapsed() < consts::ETHEREUM_SUBSCRIPTION_STALL_TIMEOUT => {
+					return Ok(number)
+				}
+				_ => (),
+			}
+		}
+
 		self.client.best_block_number().await.map_err(Into::into)
 	}
 
@@ -225,6 +258,84 @@ impl TargetClient<EthereumHeadersSyncPipeline> for SubstrateHeadersTarget {
 		let sub_eth_header = into_substrate_ethereum_header(header.header());
 		Ok((id, self.client.ethereum_receipts_required(sub_eth_header).await?))
 	}
+
+	fn is_too_large_batch_error(&self, error: &RpcError) -> bool {
+		error.is_exhausted_resources_error()
+	}
+}
+
+/// Ethereum -> Substrate sync maintenance.
+///
+/// Reports weight usage of the best Substrate block to the sync, so that it could adjust number
+/// of headers in single submit transaction.
+///
+/// When headers are submitted in signed transactions, it also tracks number of headers that are
+/// bonded by the relay account. Bonds are returned by the runtime when headers are finalized, so
+/// there's no need to reclaim them explicitly - but the number of bonded headers that keeps
+/// growing may be a sign that the relay is submitting non-canonical headers.
+struct EthereumSyncMaintain {
+	/// Substrate node client.
+	client: SubstrateClient<Rialto>,
+	/// Bridge instance used in Ethereum to Substrate sync.
+	bridge_instance: Arc<dyn BridgeInstance>,
+	/// Account that is bonding submitted headers, if headers are submitted in signed transactions.
+	bonding_account: Option<rialto_runtime::AccountId>,
+	/// Last known number of headers, bonded by the `bonding_account`.
+	bonded_headers: Mutex<Option<u64>>,
+}
+
+impl EthereumSyncMaintain {
+	/// Read number of bonded headers and log if it has changed.
+	async fn track_bonded_headers(&self, bonding_account: &rialto_runtime::AccountId) {
+		let bonded_headers = match self
+			.client
+			.bonded_ethereum_headers(self.bridge_instance.clone(), bonding_account)
+			.await
+		{
+			Ok(bonded_headers) => bonded_headers,
+			Err(error) => {
+				log::warn!(
+					target: "bridge",
+					"Failed to read number of Ethereum headers, bonded by {}: {:?}",
+					bonding_account,
+					error,
+				);
+				return;
+			}
+		};
+
+		let prev_bonded_headers = self
+			.bonded_headers
+			.lock()
+			.expect(MUTEX_POISONED_PROOF)
+			.replace(bonded_headers);
+		if prev_bonded_headers != Some(bonded_headers) {
+			log::debug!(
+				target: "bridge",
+				"Number of Ethereum headers, bonded by {}: {}",
+				bonding_account,
+				bonded_headers,
+			);
+		}
+	}
+}
+
+#[async_trait]
+impl SyncMaintain<EthereumHeadersSyncPipeline> for EthereumSyncMaintain {
+	async fn maintain(&self, sync: &mut HeadersSync<EthereumHeadersSyncPipeline>) {
+		match self.client.best_block_weight_usage().await {
+			Ok(weight_usage) => sync.target_block_weight_usage_response(weight_usage),
+			Err(error) => log::warn!(
+				target: "bridge",
+				"Failed to read Substrate block weight usage: {:?}",
+				error,
+			),
+		}
+
+		if let Some(ref bonding_account) = self.bonding_account {
+			self.track_bonded_headers(bonding_account).await;
+		}
+	}
 }
 
 /// Run Ethereum headers synchronization.
@@ -238,23 +349,38 @@ pub fn run(params: EthereumSyncParams) -> Result<(), RpcError> {
 		instance,
 	} = params;
 
-	let eth_client = EthereumClient::new(eth_params);
+	let eth_client = async_std::task::block_on(EthereumClient::new(eth_params))?;
 	let sub_client = async_std::task::block_on(async { SubstrateClient::<Rialto>::new(sub_params).await })?;
+	let subscription_best_block = async_std::task::block_on(subscribe_new_heads(&eth_client));
+	let source_tick = match subscription_best_block {
+		Some(_) => consts::ETHEREUM_SUBSCRIPTION_TICK_INTERVAL,
+		None => consts::ETHEREUM_TICK_INTERVAL,
+	};
 
 	let sign_sub_transactions = match sync_params.target_tx_mode {
 		TargetTransactionMode::Signed | TargetTransactionMode::Backup => true,
 		TargetTransactionMode::Unsigned => false,
 	};
 
-	let source = EthereumHeadersSource::new(eth_client);
+	let source = EthereumHeadersSource::new(eth_client, subscription_best_block);
+	let sync_maintain = EthereumSyncMaintain {
+		client: sub_client.clone(),
+		bridge_instance: instance.clone(),
+		bonding_account: if sign_sub_transactions {
+			Some(sub_sign.origin_account())
+		} else {
+			None
+		},
+		bonded_headers: Mutex::new(None),
+	};
 	let target = SubstrateHeadersTarget::new(sub_client, sign_sub_transactions, sub_sign, instance);
 
 	headers_relay::sync_loop::run(
 		source,
-		consts::ETHEREUM_TICK_INTERVAL,
+		source_tick,
 		target,
 		Rialto::AVERAGE_BLOCK_INTERVAL,
-		(),
+		sync_maintain,
 		sync_params,
 		metrics_params,
 		futures::future::pending(),
@@ -262,3 +388,49 @@ pub fn run(params: EthereumSyncParams) -> Result<(), RpcError> {
 
 	Ok(())
 }
+
+/// Subscribe to new Ethereum headers and start background task that tracks best block number.
+///
+/// Returns `None` if subscriptions are not supported by the connection transport.
+async fn subscribe_new_heads(client: &EthereumClient) -> Option<SubscriptionBestBlock> {
+	let mut subscription = match client.subscribe_new_heads().await {
+		Ok(subscription) => subscription,
+		Err(EthereumNodeError::SubscriptionsNotSupported) => return None,
+		Err(error) => {
+			log::warn!(
+				target: "bridge",
+				"Failed to subscribe to new Ethereum headers: {}. Falling back to polling",
+				error.to_string(),
+			);
+			return None;
+		}
+	};
+
+	let subscription_best_block: SubscriptionBestBlock = Arc::new(Mutex::new(None));
+	async_std::task::spawn({
+		let subscription_best_block = subscription_best_block.clone();
+		async move {
+			loop {
+				let header = match subscription.next().await {
+					Ok(header) => header,
+					Err(error) => {
+						// we'll fall back to polling until subscription is re-created
+						log::warn!(
+							target: "bridge",
+							"Failed to re-subscribe to new Ethereum headers: {}",
+							error.to_string(),
+						);
+						async_std::task::sleep(consts::ETHEREUM_TICK_INTERVAL).await;
+						continue;
+					}
+				};
+				if let Some(number) = header.number {
+					*subscription_best_block.lock().expect(MUTEX_POISONED_PROOF) =
+						Some((number.as_u64(), Instant::now()));
+				}
+			}
+		}
+	});
+
+	Some(subscription_best_block)
+}
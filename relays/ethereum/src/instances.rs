@@ -26,8 +26,11 @@
 use crate::ethereum_sync_loop::QueuedEthereumHeader;
 use crate::substrate_types::{into_substrate_ethereum_header, into_substrate_ethereum_receipts};
 
+use frame_support::storage::StorageMap;
+use pallet_bridge_eth_poa::{BondedHeaders, Instance1, Instance2};
 use rialto_runtime::exchange::EthereumTransactionInclusionProof as Proof;
-use rialto_runtime::Call;
+use rialto_runtime::{AccountId, Call, Runtime};
+use sp_core::storage::StorageKey;
 
 /// Interface for `Calls` which are needed to correctly sync the bridge.
 ///
@@ -40,6 +43,8 @@ pub trait BridgeInstance: Send + Sync + std::fmt::Debug {
 	fn build_unsigned_header_call(&self, header: QueuedEthereumHeader) -> Call;
 	/// Used to build a `Call` for importing peer transactions to a Substrate runtime.
 	fn build_currency_exchange_call(&self, proof: Proof) -> Call;
+	/// Used to build a storage key of the number of headers, bonded by given submitter.
+	fn bonded_headers_storage_key(&self, submitter: &AccountId) -> StorageKey;
 }
 
 /// Corresponds to the Rialto instance used in the bridge runtime.
@@ -76,6 +81,10 @@ impl BridgeInstance for RialtoPoA {
 		let pallet_call = rialto_runtime::BridgeCurrencyExchangeCall::import_peer_transaction(proof);
 		rialto_runtime::Call::BridgeRialtoCurrencyExchange(pallet_call)
 	}
+
+	fn bonded_headers_storage_key(&self, submitter: &AccountId) -> StorageKey {
+		StorageKey(BondedHeaders::<Runtime, Instance1>::hashed_key_for(submitter))
+	}
 }
 
 /// Corresponds to the Kovan instance used in the bridge runtime.
@@ -112,4 +121,8 @@ impl BridgeInstance for Kovan {
 		let pallet_call = rialto_runtime::BridgeCurrencyExchangeCall::import_peer_transaction(proof);
 		rialto_runtime::Call::BridgeKovanCurrencyExchange(pallet_call)
 	}
+
+	fn bonded_headers_storage_key(&self, submitter: &AccountId) -> StorageKey {
+		StorageKey(BondedHeaders::<Runtime, Instance2>::hashed_key_for(submitter))
+	}
 }
@@ -35,13 +35,18 @@ use headers_relay::sync::TargetTransactionMode;
 use hex_literal::hex;
 use instances::{BridgeInstance, Kovan, RialtoPoA};
 use parity_crypto::publickey::{KeyPair, Secret};
-use relay_utils::{initialize::initialize_relay, metrics::MetricsParams};
+use relay_utils::{initialize::initialize_relay, metrics::MetricsParams, rate_limiter::RateLimiterParams};
 use sp_core::crypto::Pair;
 use substrate_sync_loop::SubstrateSyncParams;
 
 use headers_relay::sync::HeadersSyncParams;
-use relay_ethereum_client::{ConnectionParams as EthereumConnectionParams, SigningParams as EthereumSigningParams};
-use relay_rialto_client::SigningParams as RialtoSigningParams;
+use relay_ethereum_client::{
+	ConnectionParams as EthereumConnectionParams, ConnectionTransport as EthereumConnectionTransport,
+	GasFeeStrategy as EthereumGasFeeStrategy, SigningParams as EthereumSigningParams,
+	TransactionFees as EthereumTransactionFees,
+};
+use relay_millau_client::Millau;
+use relay_rialto_client::{Rialto, SigningParams as RialtoSigningParams};
 use relay_substrate_client::ConnectionParams as SubstrateConnectionParams;
 use std::sync::Arc;
 
@@ -68,28 +73,35 @@ fn main() {
 		}
 		("sub-to-eth", Some(sub_to_eth_matches)) => {
 			log::info!(target: "bridge", "Starting SUB ➡ ETH relay.");
-			if substrate_sync_loop::run(match substrate_sync_params(&sub_to_eth_matches) {
+			let substrate_sync_params = match substrate_sync_params(&sub_to_eth_matches) {
 				Ok(substrate_sync_params) => substrate_sync_params,
 				Err(err) => {
 					log::error!(target: "bridge", "Error parsing parameters: {}", err);
 					return;
 				}
-			})
-			.is_err()
-			{
+			};
+			let result = match substrate_source_chain(&sub_to_eth_matches) {
+				SourceChain::Rialto => substrate_sync_loop::run::<Rialto>(substrate_sync_params),
+				SourceChain::Millau => substrate_sync_loop::run::<Millau>(substrate_sync_params),
+			};
+			if result.is_err() {
 				log::error!(target: "bridge", "Unable to get Substrate genesis block for Substrate sync.");
 				return;
 			};
 		}
 		("eth-deploy-contract", Some(eth_deploy_matches)) => {
 			log::info!(target: "bridge", "Deploying ETH contracts.");
-			ethereum_deploy_contract::run(match ethereum_deploy_contract_params(&eth_deploy_matches) {
+			let ethereum_deploy_params = match ethereum_deploy_contract_params(&eth_deploy_matches) {
 				Ok(ethereum_deploy_params) => ethereum_deploy_params,
 				Err(err) => {
 					log::error!(target: "bridge", "Error during contract deployment: {}", err);
 					return;
 				}
-			});
+			};
+			match substrate_source_chain(&eth_deploy_matches) {
+				SourceChain::Rialto => ethereum_deploy_contract::run::<Rialto>(ethereum_deploy_params),
+				SourceChain::Millau => ethereum_deploy_contract::run::<Millau>(ethereum_deploy_params),
+			}
 		}
 		("eth-submit-exchange-tx", Some(eth_exchange_submit_matches)) => {
 			log::info!(target: "bridge", "Submitting ETH ➡ SUB exchange transaction.");
@@ -128,6 +140,22 @@ fn ethereum_connection_params(matches: &clap::ArgMatches) -> Result<EthereumConn
 			.parse()
 			.map_err(|e| format!("Failed to parse eth-port: {}", e))?;
 	}
+	params.transport = match matches.value_of("eth-transport") {
+		Some("ws") => EthereumConnectionTransport::Ws,
+		Some("wss") => EthereumConnectionTransport::Wss,
+		Some("http") | None => EthereumConnectionTransport::Http,
+		Some(transport) => return Err(format!("Unknown eth-transport: {}", transport)),
+	};
+	if let Some(eth_fallback_nodes) = matches.values_of("eth-fallback-node") {
+		params.fallback_nodes = eth_fallback_nodes
+			.map(|eth_fallback_node| {
+				eth_fallback_node
+					.parse()
+					.map_err(|e| format!("Failed to parse eth-fallback-node: {}", e))
+			})
+			.collect::<Result<_, _>>()?;
+	}
+	params.rate_limiter = rate_limiter_params(matches, "eth")?;
 	Ok(params)
 }
 
@@ -144,9 +172,44 @@ fn ethereum_signing_params(matches: &clap::ArgMatches) -> Result<EthereumSigning
 			.parse::<u64>()
 			.map_err(|e| format!("Failed to parse eth-chain-id: {}", e))?;
 	}
+	params.gas_fee_strategy = match matches.value_of("eth-gas-fee-strategy") {
+		Some("oracle") => EthereumGasFeeStrategy::Oracle,
+		Some("percentile") => EthereumGasFeeStrategy::Percentile {
+			blocks: matches
+				.value_of("eth-fee-history-blocks")
+				.unwrap_or("20")
+				.parse()
+				.map_err(|e| format!("Failed to parse eth-fee-history-blocks: {}", e))?,
+			percentile: matches
+				.value_of("eth-fee-history-percentile")
+				.unwrap_or("50")
+				.parse()
+				.map_err(|e| format!("Failed to parse eth-fee-history-percentile: {}", e))?,
+		},
+		Some("static") | None => {
+			let mut fees = EthereumTransactionFees::default();
+			if let Some(eth_max_fee_per_gas) = matches.value_of("eth-max-fee-per-gas") {
+				fees.max_fee_per_gas = parse_wei(eth_max_fee_per_gas, "eth-max-fee-per-gas")?;
+			}
+			if let Some(eth_max_priority_fee_per_gas) = matches.value_of("eth-max-priority-fee-per-gas") {
+				fees.max_priority_fee_per_gas =
+					parse_wei(eth_max_priority_fee_per_gas, "eth-max-priority-fee-per-gas")?;
+			}
+			EthereumGasFeeStrategy::Static(fees)
+		}
+		Some(strategy) => return Err(format!("Unknown eth-gas-fee-strategy: {}", strategy)),
+	};
+	if let Some(eth_max_fee_per_gas_limit) = matches.value_of("eth-max-fee-per-gas-limit") {
+		params.max_fee_per_gas_limit = Some(parse_wei(eth_max_fee_per_gas_limit, "eth-max-fee-per-gas-limit")?);
+	}
 	Ok(params)
 }
 
+fn parse_wei(value: &str, arg_name: &str) -> Result<relay_ethereum_client::types::U256, String> {
+	relay_ethereum_client::types::U256::from_dec_str(value)
+		.map_err(|e| format!("Failed to parse {}: {:?}", arg_name, e))
+}
+
 fn substrate_connection_params(matches: &clap::ArgMatches) -> Result<SubstrateConnectionParams, String> {
 	let mut params = SubstrateConnectionParams::default();
 	if let Some(sub_host) = matches.value_of("sub-host") {
@@ -157,15 +220,39 @@ fn substrate_connection_params(matches: &clap::ArgMatches) -> Result<SubstrateCo
 			.parse()
 			.map_err(|e| format!("Failed to parse sub-port: {}", e))?;
 	}
+	params.rate_limiter = rate_limiter_params(matches, "sub")?;
 	Ok(params)
 }
 
+fn rate_limiter_params(matches: &clap::ArgMatches, prefix: &str) -> Result<RateLimiterParams, String> {
+	fn parse<T: std::str::FromStr>(matches: &clap::ArgMatches, arg_name: String) -> Result<Option<T>, String>
+	where
+		T::Err: std::fmt::Display,
+	{
+		matches
+			.value_of(&arg_name)
+			.map(|value| {
+				value
+					.parse()
+					.map_err(|e| format!("Failed to parse {}: {}", arg_name, e))
+			})
+			.transpose()
+	}
+
+	Ok(RateLimiterParams {
+		max_requests_per_second: parse(matches, format!("{}-max-requests-per-second", prefix))?,
+		max_concurrent_requests: parse(matches, format!("{}-max-concurrent-requests", prefix))?,
+	})
+}
+
 fn rialto_signing_params(matches: &clap::ArgMatches) -> Result<RialtoSigningParams, String> {
 	let mut params = RialtoSigningParams::default();
 	if let Some(sub_signer) = matches.value_of("sub-signer") {
 		let sub_signer_password = matches.value_of("sub-signer-password");
-		params.signer = sp_core::sr25519::Pair::from_string(sub_signer, sub_signer_password)
-			.map_err(|e| format!("Failed to parse sub-signer: {:?}", e))?;
+		params.signer = Arc::new(
+			sp_core::sr25519::Pair::from_string(sub_signer, sub_signer_password)
+				.map_err(|e| format!("Failed to parse sub-signer: {:?}", e))?,
+		);
 	}
 	Ok(params)
 }
@@ -375,6 +462,20 @@ fn metrics_params(matches: &clap::ArgMatches) -> Result<Option<MetricsParams>, S
 	Ok(Some(metrics_params))
 }
 
+/// Substrate chain, which headers are synchronized with the Ethereum bridge contract.
+enum SourceChain {
+	Rialto,
+	Millau,
+}
+
+fn substrate_source_chain(matches: &clap::ArgMatches) -> SourceChain {
+	match matches.value_of("sub-chain").map(str::to_lowercase).as_deref() {
+		Some("rialto") => SourceChain::Rialto,
+		Some("millau") => SourceChain::Millau,
+		_ => unreachable!("CLI config enforces a default chain, can never be None"),
+	}
+}
+
 fn instance_params(matches: &clap::ArgMatches) -> Result<Arc<dyn BridgeInstance>, String> {
 	let instance = if let Some(instance) = matches.value_of("sub-pallet-instance") {
 		match instance.to_lowercase().as_str() {
@@ -21,12 +21,13 @@ use crate::rpc_errors::RpcError;
 use async_trait::async_trait;
 use bp_eth_poa::AuraHeader as SubstrateEthereumHeader;
 use codec::{Decode, Encode};
+use frame_support::{storage::StorageValue, traits::Get, weights::ExtrinsicsWeight};
 use headers_relay::sync_types::SubmittedHeaders;
 use relay_ethereum_client::types::HeaderId as EthereumHeaderId;
 use relay_rialto_client::{Rialto, SigningParams as RialtoSigningParams};
 use relay_substrate_client::{Client as SubstrateClient, TransactionSignScheme};
 use relay_utils::HeaderId;
-use sp_core::{crypto::Pair, Bytes};
+use sp_core::{storage::StorageKey, Bytes};
 use std::{collections::VecDeque, sync::Arc};
 
 const ETH_API_IMPORT_REQUIRES_RECEIPTS: &str = "RialtoPoAHeaderApi_is_import_requires_receipts";
@@ -49,6 +50,14 @@ pub trait SubstrateHighLevelRpc {
 	async fn ethereum_receipts_required(&self, header: SubstrateEthereumHeader) -> RpcResult<bool>;
 	/// Returns whether or not the given Ethereum header is known to the Substrate runtime.
 	async fn ethereum_header_known(&self, header_id: EthereumHeaderId) -> RpcResult<bool>;
+	/// Returns weight usage (in range `0.0..=1.0`) of the best Substrate block.
+	async fn best_block_weight_usage(&self) -> RpcResult<f64>;
+	/// Returns number of Ethereum headers that are bonded by given submitter.
+	async fn bonded_ethereum_headers(
+		&self,
+		instance: Arc<dyn BridgeInstance>,
+		submitter: &rialto_runtime::AccountId,
+	) -> RpcResult<u64>;
 }
 
 #[async_trait]
@@ -100,6 +109,23 @@ impl SubstrateHighLevelRpc for SubstrateClient<Rialto> {
 
 		Ok(is_known_block)
 	}
+
+	async fn best_block_weight_usage(&self) -> RpcResult<f64> {
+		let storage_key = StorageKey(frame_system::BlockWeight::<rialto_runtime::Runtime>::hashed_key().to_vec());
+		let block_weight: ExtrinsicsWeight = self.storage_value_by_key(storage_key).await?.unwrap_or_default();
+		let max_block_weight = rialto_runtime::AvailableBlockRatio::get() * rialto_runtime::MaximumBlockWeight::get();
+
+		Ok(block_weight.total() as f64 / max_block_weight as f64)
+	}
+
+	async fn bonded_ethereum_headers(
+		&self,
+		instance: Arc<dyn BridgeInstance>,
+		submitter: &rialto_runtime::AccountId,
+	) -> RpcResult<u64> {
+		let storage_key = instance.bonded_headers_storage_key(submitter);
+		Ok(self.storage_value_by_key(storage_key).await?.unwrap_or_default())
+	}
 }
 
 /// A trait for RPC calls which are used to submit Ethereum headers to a Substrate
@@ -160,7 +186,8 @@ impl SubmitEthereumHeaders for SubstrateClient<Rialto> {
 			let nonce = self.next_account_index(account_id).await?;
 
 			let call = instance.build_signed_header_call(headers);
-			let transaction = Rialto::sign_transaction(self, &params.signer, nonce, call);
+			let era = self.transaction_era(params.transactions_mortality).await?;
+			let transaction = Rialto::sign_transaction(self, &params.signer, era, nonce, call).await?;
 
 			let _ = self.submit_extrinsic(Bytes(transaction.encode())).await?;
 			Ok(())
@@ -256,7 +283,8 @@ impl SubmitEthereumExchangeTransactionProof for SubstrateClient<Rialto> {
 		let nonce = self.next_account_index(account_id).await?;
 
 		let call = instance.build_currency_exchange_call(proof);
-		let transaction = Rialto::sign_transaction(self, &params.signer, nonce, call);
+		let era = self.transaction_era(params.transactions_mortality).await?;
+		let transaction = Rialto::sign_transaction(self, &params.signer, era, nonce, call).await?;
 
 		let _ = self.submit_extrinsic(Bytes(transaction.encode())).await?;
 		Ok(())
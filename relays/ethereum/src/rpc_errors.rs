@@ -31,6 +31,17 @@ pub enum RpcError {
 	Substrate(SubstrateNodeError),
 }
 
+impl RpcError {
+	/// Returns true if transaction has been rejected by the Substrate node because it would
+	/// exhaust block resources (`InvalidTransaction::ExhaustsResources`).
+	pub fn is_exhausted_resources_error(&self) -> bool {
+		match *self {
+			RpcError::Substrate(ref error) => error.is_exhausted_resources_error(),
+			_ => false,
+		}
+	}
+}
+
 impl From<RpcError> for String {
 	fn from(err: RpcError) -> Self {
 		match err {
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Rialto-Substrate -> Ethereum PoA synchronization.
+//! Substrate (Rialto or Millau) -> Ethereum PoA synchronization.
 
 use crate::ethereum_client::EthereumHighLevelRpc;
 use crate::rpc_errors::RpcError;
@@ -24,22 +24,24 @@ use codec::Encode;
 use headers_relay::{
 	sync::HeadersSyncParams,
 	sync_loop::TargetClient,
-	sync_types::{HeadersSyncPipeline, QueuedHeader, SourceHeader, SubmittedHeaders},
+	sync_types::{HeaderIdOf, HeadersSyncPipeline, QueuedHeader, SourceHeader, SubmittedHeaders},
 };
 use relay_ethereum_client::{
 	types::Address, Client as EthereumClient, ConnectionParams as EthereumConnectionParams,
 	SigningParams as EthereumSigningParams,
 };
-use relay_rialto_client::{HeaderId as RialtoHeaderId, Rialto, SyncHeader as RialtoSyncHeader};
+use relay_millau_client::{Millau, SyncHeader as MillauSyncHeader};
+use relay_rialto_client::{Rialto, SyncHeader as RialtoSyncHeader};
 use relay_substrate_client::{
 	headers_source::HeadersSource, Chain as SubstrateChain, Client as SubstrateClient,
 	ConnectionParams as SubstrateConnectionParams,
 };
-use relay_utils::metrics::MetricsParams;
+use relay_utils::{metrics::MetricsParams, BlockNumberBase};
+use sp_core::H256;
 use sp_runtime::Justification;
 
 use std::fmt::Debug;
-use std::{collections::HashSet, time::Duration};
+use std::{collections::HashSet, marker::PhantomData, time::Duration};
 
 pub mod consts {
 	use super::*;
@@ -71,18 +73,43 @@ pub struct SubstrateSyncParams {
 	pub metrics_params: Option<MetricsParams>,
 }
 
+/// Substrate chain, which headers may be synchronized with the Ethereum bridge contract.
+///
+/// The contract is storing 32-byte header hashes, so only chains with `H256` hashes are supported.
+pub trait SubstrateSourceChain: SubstrateChain<Hash = H256> + Clone + Copy + Debug {
+	/// Header type, used by the headers sync pipeline.
+	type SyncHeader: SourceHeader<H256, Self::BlockNumber> + From<Self::Header> + Encode;
+}
+
+impl SubstrateSourceChain for Rialto {
+	type SyncHeader = RialtoSyncHeader;
+}
+
+impl SubstrateSourceChain for Millau {
+	type SyncHeader = MillauSyncHeader;
+}
+
 /// Substrate synchronization pipeline.
 #[derive(Clone, Copy, Debug)]
-#[cfg_attr(test, derive(PartialEq))]
-pub struct SubstrateHeadersSyncPipeline;
+pub struct SubstrateHeadersSyncPipeline<C>(PhantomData<C>);
 
-impl HeadersSyncPipeline for SubstrateHeadersSyncPipeline {
-	const SOURCE_NAME: &'static str = "Substrate";
+#[cfg(test)]
+impl<C> PartialEq for SubstrateHeadersSyncPipeline<C> {
+	fn eq(&self, _: &Self) -> bool {
+		true
+	}
+}
+
+impl<C: SubstrateSourceChain> HeadersSyncPipeline for SubstrateHeadersSyncPipeline<C>
+where
+	C::BlockNumber: BlockNumberBase,
+{
+	const SOURCE_NAME: &'static str = C::NAME;
 	const TARGET_NAME: &'static str = "Ethereum";
 
-	type Hash = rialto_runtime::Hash;
-	type Number = rialto_runtime::BlockNumber;
-	type Header = RialtoSyncHeader;
+	type Hash = H256;
+	type Number = C::BlockNumber;
+	type Header = C::SyncHeader;
 	type Extra = ();
 	type Completion = Justification;
 
@@ -91,71 +118,89 @@ impl HeadersSyncPipeline for SubstrateHeadersSyncPipeline {
 	}
 }
 
-/// Queued substrate header ID.
-pub type QueuedRialtoHeader = QueuedHeader<SubstrateHeadersSyncPipeline>;
+/// Queued substrate header.
+pub type QueuedSubstrateHeader<C> = QueuedHeader<SubstrateHeadersSyncPipeline<C>>;
 
-/// Rialto node as headers source.
-type SubstrateHeadersSource = HeadersSource<Rialto, SubstrateHeadersSyncPipeline>;
+/// Substrate header ID.
+pub type SubstrateHeaderId<C> = HeaderIdOf<SubstrateHeadersSyncPipeline<C>>;
+
+/// Substrate node as headers source.
+type SubstrateHeadersSource<C> = HeadersSource<C, SubstrateHeadersSyncPipeline<C>>;
 
 /// Ethereum client as Substrate headers target.
-struct EthereumHeadersTarget {
+struct EthereumHeadersTarget<C> {
 	/// Ethereum node client.
 	client: EthereumClient,
 	/// Bridge contract address.
 	contract: Address,
 	/// Ethereum signing params.
 	sign_params: EthereumSigningParams,
+	/// Phantom reference to the source chain.
+	_phantom: PhantomData<C>,
 }
 
-impl EthereumHeadersTarget {
+impl<C> EthereumHeadersTarget<C> {
 	fn new(client: EthereumClient, contract: Address, sign_params: EthereumSigningParams) -> Self {
 		Self {
 			client,
 			contract,
 			sign_params,
+			_phantom: Default::default(),
 		}
 	}
 }
 
 #[async_trait]
-impl TargetClient<SubstrateHeadersSyncPipeline> for EthereumHeadersTarget {
+impl<C: SubstrateSourceChain> TargetClient<SubstrateHeadersSyncPipeline<C>> for EthereumHeadersTarget<C>
+where
+	C::BlockNumber: BlockNumberBase,
+{
 	type Error = RpcError;
 
-	async fn best_header_id(&self) -> Result<RialtoHeaderId, Self::Error> {
-		self.client.best_substrate_block(self.contract).await
+	async fn best_header_id(&self) -> Result<SubstrateHeaderId<C>, Self::Error> {
+		self.client.best_substrate_block::<C>(self.contract).await
 	}
 
-	async fn is_known_header(&self, id: RialtoHeaderId) -> Result<(RialtoHeaderId, bool), Self::Error> {
-		self.client.substrate_header_known(self.contract, id).await
+	async fn is_known_header(&self, id: SubstrateHeaderId<C>) -> Result<(SubstrateHeaderId<C>, bool), Self::Error> {
+		self.client.substrate_header_known::<C>(self.contract, id).await
 	}
 
-	async fn submit_headers(&self, headers: Vec<QueuedRialtoHeader>) -> SubmittedHeaders<RialtoHeaderId, Self::Error> {
+	async fn submit_headers(
+		&self,
+		headers: Vec<QueuedSubstrateHeader<C>>,
+	) -> SubmittedHeaders<SubstrateHeaderId<C>, Self::Error> {
 		self.client
 			.submit_substrate_headers(self.sign_params.clone(), self.contract, headers)
 			.await
 	}
 
-	async fn incomplete_headers_ids(&self) -> Result<HashSet<RialtoHeaderId>, Self::Error> {
-		self.client.incomplete_substrate_headers(self.contract).await
+	async fn incomplete_headers_ids(&self) -> Result<HashSet<SubstrateHeaderId<C>>, Self::Error> {
+		self.client.incomplete_substrate_headers::<C>(self.contract).await
 	}
 
 	async fn complete_header(
 		&self,
-		id: RialtoHeaderId,
+		id: SubstrateHeaderId<C>,
 		completion: Justification,
-	) -> Result<RialtoHeaderId, Self::Error> {
+	) -> Result<SubstrateHeaderId<C>, Self::Error> {
 		self.client
-			.complete_substrate_header(self.sign_params.clone(), self.contract, id, completion)
+			.complete_substrate_header::<C>(self.sign_params.clone(), self.contract, id, completion)
 			.await
 	}
 
-	async fn requires_extra(&self, header: QueuedRialtoHeader) -> Result<(RialtoHeaderId, bool), Self::Error> {
+	async fn requires_extra(
+		&self,
+		header: QueuedSubstrateHeader<C>,
+	) -> Result<(SubstrateHeaderId<C>, bool), Self::Error> {
 		Ok((header.header().id(), false))
 	}
 }
 
 /// Run Substrate headers synchronization.
-pub fn run(params: SubstrateSyncParams) -> Result<(), RpcError> {
+pub fn run<C: SubstrateSourceChain>(params: SubstrateSyncParams) -> Result<(), RpcError>
+where
+	C::BlockNumber: BlockNumberBase,
+{
 	let SubstrateSyncParams {
 		sub_params,
 		eth_params,
@@ -165,15 +210,15 @@ pub fn run(params: SubstrateSyncParams) -> Result<(), RpcError> {
 		metrics_params,
 	} = params;
 
-	let eth_client = EthereumClient::new(eth_params);
-	let sub_client = async_std::task::block_on(async { SubstrateClient::<Rialto>::new(sub_params).await })?;
+	let eth_client = async_std::task::block_on(EthereumClient::new(eth_params))?;
+	let sub_client = async_std::task::block_on(async { SubstrateClient::<C>::new(sub_params).await })?;
 
-	let target = EthereumHeadersTarget::new(eth_client, eth_contract_address, eth_sign);
-	let source = SubstrateHeadersSource::new(sub_client);
+	let target = EthereumHeadersTarget::<C>::new(eth_client, eth_contract_address, eth_sign);
+	let source = SubstrateHeadersSource::<C>::new(sub_client);
 
 	headers_relay::sync_loop::run(
 		source,
-		Rialto::AVERAGE_BLOCK_INTERVAL,
+		C::AVERAGE_BLOCK_INTERVAL,
 		target,
 		consts::ETHEREUM_TICK_INTERVAL,
 		(),
@@ -72,5 +72,10 @@ pub fn into_substrate_ethereum_receipt(receipt: &EthereumReceipt) -> SubstrateEt
 			(None, Some(root)) => SubstrateEthereumTransactionOutcome::StateRoot(root),
 			_ => SubstrateEthereumTransactionOutcome::Unknown,
 		},
+		// legacy transactions are reported as transactions of type 0
+		transaction_type: receipt
+			.transaction_type
+			.map(|transaction_type| transaction_type.as_u64() as u8)
+			.filter(|transaction_type| *transaction_type != 0),
 	}
 }
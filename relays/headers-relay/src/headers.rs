@@ -113,7 +113,6 @@ impl<P: HeadersSyncPipeline> Default for QueuedHeaders<P> {
 
 impl<P: HeadersSyncPipeline> QueuedHeaders<P> {
 	/// Returns prune border.
-	#[cfg(test)]
 	pub fn prune_border(&self) -> P::Number {
 		self.prune_border
 	}
@@ -541,6 +540,11 @@ impl<P: HeadersSyncPipeline> QueuedHeaders<P> {
 		self.prune_border = prune_border;
 	}
 
+	/// Move prune border back to given block, so that we could accept headers starting from this block.
+	pub fn reanchor(&mut self, prune_border: P::Number) {
+		self.prune_border = std::cmp::min(self.prune_border, prune_border);
+	}
+
 	/// Forgets all ever known headers.
 	pub fn clear(&mut self) {
 		self.maybe_orphan.clear();
@@ -20,9 +20,17 @@
 //! passed using `HeadersSyncParams` structure.
 
 use crate::headers::QueuedHeaders;
-use crate::sync_types::{HeaderIdOf, HeaderStatus, HeadersSyncPipeline, QueuedHeader};
+use crate::sync_types::{HeaderIdOf, HeaderStatus, HeadersSyncPipeline, QueuedHeader, SourceHeader};
 use num_traits::{One, Saturating, Zero};
 
+/// Target block weight usage (in range `0.0..=1.0`), above which we are not growing headers
+/// batch size.
+const BUSY_TARGET_BLOCK_WEIGHT_USAGE: f64 = 0.75;
+
+/// Ranges of reorg depths that we're tracking. Every range is identified by its inclusive upper
+/// bound and label.
+pub const REORG_DEPTH_RANGES: [(u64, &str); 5] = [(1, "1"), (4, "2-4"), (16, "5-16"), (64, "17-64"), (u64::MAX, "65+")];
+
 /// Common sync params.
 #[derive(Debug, Clone)]
 pub struct HeadersSyncParams {
@@ -30,7 +38,8 @@ pub struct HeadersSyncParams {
 	pub max_future_headers_to_download: usize,
 	/// Maximal number of active (we believe) submit header transactions.
 	pub max_headers_in_submitted_status: usize,
-	/// Maximal number of headers in single submit request.
+	/// Maximal number of headers in single submit request. Actual number of headers in single
+	/// submit request is selected dynamically, depending on previous submissions results.
 	pub max_headers_in_single_submit: usize,
 	/// Maximal total headers size in single submit request.
 	pub max_headers_size_in_single_submit: usize,
@@ -66,6 +75,30 @@ pub struct HeadersSync<P: HeadersSyncPipeline> {
 	headers: QueuedHeaders<P>,
 	/// Pause headers submission.
 	pause_submit: bool,
+	/// Current limit of headers in single submit request. It is halved when target node rejects
+	/// our transaction because it is too large and grows by one after every successful
+	/// submission, unless target chain is busy. It never exceeds `max_headers_in_single_submit`.
+	headers_batch_limit: usize,
+	/// True if recent target chain blocks are close to their weight limit.
+	is_target_busy: bool,
+	/// Source chain reorg that we're currently resolving.
+	active_reorg: Option<ActiveReorg<P::Number>>,
+	/// Number of resolved reorgs in every range of `REORG_DEPTH_RANGES`.
+	reorgs_by_depth: [u64; REORG_DEPTH_RANGES.len()],
+}
+
+/// Source chain reorg that is not yet resolved.
+///
+/// The reorg is detected when the target node doesn't know parent of the header that we have
+/// downloaded, although the parent number is not greater than the best header number known to
+/// the target node. It is resolved when we find the header that is known to the target node.
+#[derive(Debug)]
+struct ActiveReorg<Number> {
+	/// Best header number known to the target node when reorg has been detected.
+	target_best_number: Number,
+	/// True if we had to re-anchor (i.e. accept headers before the prune border) while resolving
+	/// the reorg.
+	is_deep: bool,
 }
 
 impl<P: HeadersSyncPipeline> HeadersSync<P> {
@@ -73,10 +106,14 @@ impl<P: HeadersSyncPipeline> HeadersSync<P> {
 	pub fn new(params: HeadersSyncParams) -> Self {
 		HeadersSync {
 			headers: QueuedHeaders::default(),
+			headers_batch_limit: params.max_headers_in_single_submit,
 			params,
 			source_best_number: None,
 			target_best_header: None,
 			pause_submit: false,
+			is_target_busy: false,
+			active_reorg: None,
+			reorgs_by_depth: Default::default(),
 		}
 	}
 
@@ -106,6 +143,16 @@ impl<P: HeadersSyncPipeline> HeadersSync<P> {
 		(&self.target_best_header, &self.source_best_number)
 	}
 
+	/// Returns current limit of headers in single submit request.
+	pub fn headers_batch_limit(&self) -> usize {
+		self.headers_batch_limit
+	}
+
+	/// Returns number of resolved reorgs in every range of `REORG_DEPTH_RANGES`.
+	pub fn reorgs_by_depth(&self) -> &[u64; REORG_DEPTH_RANGES.len()] {
+		&self.reorgs_by_depth
+	}
+
 	/// Returns reference to the headers queue.
 	pub fn headers(&self) -> &QueuedHeaders<P> {
 		&self.headers
@@ -189,7 +236,7 @@ impl<P: HeadersSyncPipeline> HeadersSync<P> {
 			if total_headers == headers_to_submit_count {
 				return false;
 			}
-			if total_headers == self.params.max_headers_in_single_submit {
+			if total_headers == self.headers_batch_limit {
 				return false;
 			}
 
@@ -205,6 +252,37 @@ impl<P: HeadersSyncPipeline> HeadersSync<P> {
 		})
 	}
 
+	/// Headers batch has been successfully submitted to the target node.
+	pub fn headers_batch_submitted(&mut self) {
+		if self.is_target_busy || self.headers_batch_limit >= self.params.max_headers_in_single_submit {
+			return;
+		}
+
+		self.headers_batch_limit += 1;
+		log::debug!(
+			target: "bridge",
+			"Increased {} headers batch limit to {}",
+			P::TARGET_NAME,
+			self.headers_batch_limit,
+		);
+	}
+
+	/// Headers batch has been rejected by the target node because it is too large.
+	pub fn headers_batch_too_large(&mut self) {
+		self.headers_batch_limit = std::cmp::max(self.headers_batch_limit / 2, 1);
+		log::debug!(
+			target: "bridge",
+			"Decreased {} headers batch limit to {}",
+			P::TARGET_NAME,
+			self.headers_batch_limit,
+		);
+	}
+
+	/// Receive weight usage (in range `0.0..=1.0`) of the recent target chain block.
+	pub fn target_block_weight_usage_response(&mut self, weight_usage: f64) {
+		self.is_target_busy = weight_usage >= BUSY_TARGET_BLOCK_WEIGHT_USAGE;
+	}
+
 	/// Receive new target header number from the source node.
 	pub fn source_best_header_number_response(&mut self, best_header_number: P::Number) {
 		log::debug!(
@@ -234,9 +312,12 @@ impl<P: HeadersSyncPipeline> HeadersSync<P> {
 		// remember that this header is now known to the Substrate runtime
 		self.headers.target_best_header_response(&best_header);
 
-		// prune ancient headers
-		self.headers
-			.prune(best_header.0.saturating_sub(self.params.prune_depth.into()));
+		// prune ancient headers, unless we're resolving reorg (we may need headers before the
+		// prune border to find the header that is known to the target node)
+		if self.active_reorg.is_none() {
+			self.headers
+				.prune(best_header.0.saturating_sub(self.params.prune_depth.into()));
+		}
 
 		// finally remember the best header itself
 		self.target_best_header = Some(best_header);
@@ -256,6 +337,81 @@ impl<P: HeadersSyncPipeline> HeadersSync<P> {
 		true
 	}
 
+	/// Receive target node response for MaybeOrphan request.
+	pub fn maybe_orphan_response(&mut self, id: &HeaderIdOf<P>, response: bool) {
+		self.headers.maybe_orphan_response(id, response);
+
+		let target_best_number = match self.target_best_header {
+			Some(target_best_header) => target_best_header.0,
+			None => return,
+		};
+		match (response, self.active_reorg.take()) {
+			(false, None) if id.0 <= target_best_number => {
+				log::info!(
+					target: "bridge",
+					"Detected {} reorg: {} node doesn't know header {:?}. Best known header is #{}",
+					P::SOURCE_NAME,
+					P::TARGET_NAME,
+					id,
+					target_best_number,
+				);
+
+				self.active_reorg = Some(ActiveReorg {
+					target_best_number,
+					is_deep: false,
+				});
+			}
+			(false, active_reorg) => self.active_reorg = active_reorg,
+			(true, None) => (),
+			(true, Some(active_reorg)) => {
+				let depth: u64 = active_reorg.target_best_number.saturating_sub(id.0).into();
+				log::info!(
+					target: "bridge",
+					"Resolved {}{} reorg of depth {}. Common ancestor is {:?}",
+					if active_reorg.is_deep { "deep " } else { "" },
+					P::SOURCE_NAME,
+					depth,
+					id,
+				);
+
+				if depth != 0 {
+					let range_index = REORG_DEPTH_RANGES
+						.iter()
+						.position(|(max_depth, _)| depth <= *max_depth)
+						.unwrap_or(REORG_DEPTH_RANGES.len() - 1);
+					self.reorgs_by_depth[range_index] += 1;
+				}
+			}
+		}
+	}
+
+	/// Receive orphan header (i.e. parent of the header that is not known to the target node)
+	/// from the source node.
+	///
+	/// If reorg is deeper than our headers cache (i.e. orphan header is before the prune border),
+	/// the sync is re-anchored at this header, so that we could continue searching for the header
+	/// that is known to the target node.
+	pub fn orphan_header_response(&mut self, header: P::Header) {
+		let id = header.id();
+		if id.0 < self.headers.prune_border() {
+			if let Some(ref mut active_reorg) = self.active_reorg {
+				if !active_reorg.is_deep {
+					log::info!(
+						target: "bridge",
+						"{} reorg is deeper than headers cache. Re-anchoring sync at {:?}",
+						P::SOURCE_NAME,
+						id,
+					);
+				}
+
+				active_reorg.is_deep = true;
+				self.headers.reanchor(id.0);
+			}
+		}
+
+		self.headers.header_response(header);
+	}
+
 	/// Pause headers submit until best header will be updated on target node.
 	pub fn pause_submit(&mut self) {
 		log::debug!(
@@ -275,6 +431,7 @@ impl<P: HeadersSyncPipeline> HeadersSync<P> {
 		self.target_best_header = None;
 		self.headers.clear();
 		self.pause_submit = false;
+		self.active_reorg = None;
 	}
 }
 
@@ -460,6 +617,76 @@ pub mod tests {
 		eth_sync.headers.headers_submitted(vec![id(101)]);
 	}
 
+	#[test]
+	fn reorg_is_detected_and_resolved() {
+		let mut eth_sync = HeadersSync::<TestHeadersSyncPipeline>::new(default_sync_params());
+
+		// ethereum reports best header #102 and substrate is at #100, which isn't part of best chain
+		eth_sync.source_best_header_number_response(102);
+		eth_sync.target_best_header_response(HeaderId(100, side_hash(100)));
+
+		// substrate doesn't know parent of #101 => reorg is detected
+		eth_sync.headers.header_response(header(101).header().clone());
+		eth_sync.maybe_orphan_response(&id(100), false);
+		assert!(eth_sync.active_reorg.is_some());
+
+		// substrate doesn't know #99 either
+		eth_sync.orphan_header_response(header(100).header().clone());
+		eth_sync.maybe_orphan_response(&id(99), false);
+		eth_sync.orphan_header_response(header(99).header().clone());
+
+		// but it knows #98 => reorg of depth 2 is resolved
+		eth_sync.maybe_orphan_response(&id(98), true);
+		assert!(eth_sync.active_reorg.is_none());
+		assert_eq!(eth_sync.reorgs_by_depth(), &[0, 1, 0, 0, 0]);
+		assert_eq!(eth_sync.headers.header(HeaderStatus::MaybeExtra), Some(&header(99)));
+	}
+
+	#[test]
+	fn orphan_header_is_not_considered_reorg() {
+		let mut eth_sync = HeadersSync::<TestHeadersSyncPipeline>::new(default_sync_params());
+		eth_sync.source_best_header_number_response(102);
+		eth_sync.target_best_header_response(id(99));
+
+		// substrate doesn't know parent of #102, but it is ahead of substrate best header
+		eth_sync.headers.header_response(header(102).header().clone());
+		eth_sync.maybe_orphan_response(&id(101), false);
+		assert!(eth_sync.active_reorg.is_none());
+	}
+
+	#[test]
+	fn sync_is_reanchored_on_deep_reorg() {
+		let mut eth_sync = HeadersSync::<TestHeadersSyncPipeline>::new(default_sync_params());
+		eth_sync.params.prune_depth = 1;
+
+		// ethereum reports best header #102 and substrate is at #100, which isn't part of best chain
+		eth_sync.source_best_header_number_response(102);
+		eth_sync.target_best_header_response(HeaderId(100, side_hash(100)));
+		assert_eq!(eth_sync.headers.prune_border(), 99);
+
+		// reorg is detected
+		eth_sync.headers.header_response(header(101).header().clone());
+		eth_sync.maybe_orphan_response(&id(100), false);
+		eth_sync.orphan_header_response(header(100).header().clone());
+		eth_sync.maybe_orphan_response(&id(99), false);
+		eth_sync.orphan_header_response(header(99).header().clone());
+		eth_sync.maybe_orphan_response(&id(98), false);
+
+		// header #98 is before the prune border, but we accept it
+		eth_sync.orphan_header_response(header(98).header().clone());
+		assert_eq!(eth_sync.headers.prune_border(), 98);
+		assert_eq!(eth_sync.headers.status(&id(98)), HeaderStatus::MaybeOrphan);
+
+		// headers are not pruned while reorg is active
+		eth_sync.target_best_header_response(HeaderId(101, side_hash(101)));
+		assert_eq!(eth_sync.headers.prune_border(), 98);
+
+		// substrate knows #97 => reorg is resolved
+		eth_sync.maybe_orphan_response(&id(97), true);
+		assert!(eth_sync.active_reorg.is_none());
+		assert_eq!(eth_sync.reorgs_by_depth(), &[0, 1, 0, 0, 0]);
+	}
+
 	#[test]
 	fn pruning_happens_on_target_best_header_response() {
 		let mut eth_sync = HeadersSync::<TestHeadersSyncPipeline>::new(default_sync_params());
@@ -520,4 +747,46 @@ pub mod tests {
 		eth_sync.target_best_header_response(id(101));
 		assert_eq!(eth_sync.select_headers_to_submit(false), Some(vec![&header(102)]));
 	}
+
+	#[test]
+	fn headers_batch_limit_is_adjusted() {
+		let mut eth_sync = HeadersSync::<TestHeadersSyncPipeline>::new(default_sync_params());
+		eth_sync.params.max_headers_in_single_submit = 4;
+		eth_sync.headers_batch_limit = 4;
+
+		// ethereum reports best header #105 and substrate is at #100
+		eth_sync.source_best_header_number_response(105);
+		eth_sync.target_best_header_response(id(100));
+		for number in 101..=105 {
+			eth_sync.headers.header_response(header(number).header().clone());
+			eth_sync.headers.maybe_extra_response(&id(number), false);
+		}
+		assert_eq!(
+			eth_sync.select_headers_to_submit(false).map(|headers| headers.len()),
+			Some(4)
+		);
+
+		// when batch is too large, limit is halved
+		eth_sync.headers_batch_too_large();
+		assert_eq!(eth_sync.headers_batch_limit(), 2);
+		assert_eq!(
+			eth_sync.select_headers_to_submit(false).map(|headers| headers.len()),
+			Some(2)
+		);
+		eth_sync.headers_batch_too_large();
+		eth_sync.headers_batch_too_large();
+		assert_eq!(eth_sync.headers_batch_limit(), 1);
+
+		// when target chain is busy, limit isn't growing
+		eth_sync.target_block_weight_usage_response(0.9);
+		eth_sync.headers_batch_submitted();
+		assert_eq!(eth_sync.headers_batch_limit(), 1);
+
+		// otherwise it grows up to the maximal value
+		eth_sync.target_block_weight_usage_response(0.1);
+		for _ in 0..10 {
+			eth_sync.headers_batch_submitted();
+		}
+		assert_eq!(eth_sync.headers_batch_limit(), 4);
+	}
 }
@@ -102,6 +102,12 @@ pub trait TargetClient<P: HeadersSyncPipeline> {
 
 	/// Returns true if header requires extra data to be submitted.
 	async fn requires_extra(&self, header: QueuedHeader<P>) -> Result<(HeaderIdOf<P>, bool), Self::Error>;
+
+	/// Returns true if headers submission has failed because we're trying to submit too many
+	/// headers at once (e.g. transaction exhausts target block resources).
+	fn is_too_large_batch_error(&self, _error: &Self::Error) -> bool {
+		false
+	}
 }
 
 /// Synchronization maintain procedure.
@@ -221,7 +227,7 @@ pub fn run<P: HeadersSyncPipeline, TC: TargetClient<P>>(
 					source_client_is_online = process_future_result(
 						source_orphan_header,
 						&mut source_retry_backoff,
-						|source_orphan_header| sync.headers_mut().header_response(source_orphan_header),
+						|source_orphan_header| sync.orphan_header_response(source_orphan_header),
 						&mut source_go_offline_future,
 						|delay| async_std::task::sleep(delay),
 						|| format!("Error retrieving orphan header from {} node", P::SOURCE_NAME),
@@ -315,7 +321,6 @@ pub fn run<P: HeadersSyncPipeline, TC: TargetClient<P>>(
 						target_existence_status,
 						&mut target_retry_backoff,
 						|(target_header, target_existence_status)| sync
-							.headers_mut()
 							.maybe_orphan_response(&target_header, target_existence_status),
 						&mut target_go_offline_future,
 						|delay| async_std::task::sleep(delay),
@@ -330,6 +335,13 @@ pub fn run<P: HeadersSyncPipeline, TC: TargetClient<P>>(
 						&& submitted_headers.incomplete.is_empty();
 					let has_submitted_headers = sync.headers().headers_in_status(HeaderStatus::Submitted) != 0;
 
+					match submitted_headers.fatal_error {
+						Some(ref fatal_error) if target_client.is_too_large_batch_error(fatal_error) =>
+							sync.headers_batch_too_large(),
+						None if !submitted_headers.submitted.is_empty() => sync.headers_batch_submitted(),
+						_ => (),
+					}
+
 					let maybe_fatal_error = match submitted_headers.fatal_error {
 						Some(fatal_error) => Err(StringifiedMaybeConnectionError::new(
 							fatal_error.is_connection_error(),
@@ -16,7 +16,7 @@
 
 //! Metrics for headers synchronization relay loop.
 
-use crate::sync::HeadersSync;
+use crate::sync::{HeadersSync, REORG_DEPTH_RANGES};
 use crate::sync_types::{HeaderStatus, HeadersSyncPipeline};
 
 use num_traits::Zero;
@@ -28,12 +28,15 @@ pub struct SyncLoopMetrics {
 	best_block_numbers: GaugeVec<U64>,
 	/// Number of headers in given states (see `HeaderStatus`).
 	blocks_in_state: GaugeVec<U64>,
+	/// Number of resolved source chain reorgs of given depth.
+	reorgs: GaugeVec<U64>,
 }
 
 impl Metrics for SyncLoopMetrics {
 	fn register(&self, registry: &Registry) -> Result<(), String> {
 		register(self.best_block_numbers.clone(), registry).map_err(|e| e.to_string())?;
 		register(self.blocks_in_state.clone(), registry).map_err(|e| e.to_string())?;
+		register(self.reorgs.clone(), registry).map_err(|e| e.to_string())?;
 		Ok(())
 	}
 }
@@ -51,6 +54,11 @@ impl Default for SyncLoopMetrics {
 				&["state"],
 			)
 			.expect("metric is static and thus valid; qed"),
+			reorgs: GaugeVec::new(
+				Opts::new("reorgs", "Number of resolved source chain reorgs of given depth"),
+				&["depth"],
+			)
+			.expect("metric is static and thus valid; qed"),
 		}
 	}
 }
@@ -90,5 +98,9 @@ impl SyncLoopMetrics {
 		self.blocks_in_state
 			.with_label_values(&["submitted"])
 			.set(headers.headers_in_status(HeaderStatus::Submitted) as _);
+
+		for ((_, depth_label), reorgs) in REORG_DEPTH_RANGES.iter().zip(sync.reorgs_by_depth().iter()) {
+			self.reorgs.with_label_values(&[*depth_label]).set(*reorgs);
+		}
 	}
 }
@@ -0,0 +1,78 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Types used to connect to the Kusama chain.
+
+use async_trait::async_trait;
+use relay_substrate_client::{
+	polkadot_like, Chain, ChainBase, ChainWithBalances, Client, TransactionEraOf, TransactionSignScheme,
+};
+use sp_core::{crypto::Ss58AddressFormat, storage::StorageKey};
+use std::time::Duration;
+
+pub use relay_substrate_client::polkadot_like::{AccountSigner, EncodedCall, SigningParams, SyncHeader};
+
+/// Kusama header id.
+pub type HeaderId = polkadot_like::HeaderId;
+
+/// SS58 address format of Kusama accounts.
+pub const SS58_FORMAT: Ss58AddressFormat = Ss58AddressFormat::KusamaAccount;
+
+/// Kusama chain definition.
+#[derive(Debug, Clone, Copy)]
+pub struct Kusama;
+
+impl ChainBase for Kusama {
+	type BlockNumber = bp_polkadot_core::BlockNumber;
+	type Hash = bp_polkadot_core::Hash;
+	type Hasher = bp_polkadot_core::Hasher;
+	type Header = bp_polkadot_core::Header;
+}
+
+impl Chain for Kusama {
+	const NAME: &'static str = "Kusama";
+	const AVERAGE_BLOCK_INTERVAL: Duration = Duration::from_secs(6);
+
+	type AccountId = bp_polkadot_core::AccountId;
+	type Index = bp_polkadot_core::Index;
+	type SignedBlock = bp_polkadot_core::SignedBlock;
+	type Call = EncodedCall;
+}
+
+impl ChainWithBalances for Kusama {
+	type NativeBalance = bp_polkadot_core::Balance;
+
+	fn account_info_storage_key(account_id: &Self::AccountId) -> StorageKey {
+		polkadot_like::account_info_storage_key(account_id)
+	}
+}
+
+#[async_trait]
+impl TransactionSignScheme for Kusama {
+	type Chain = Kusama;
+	type AccountSigner = AccountSigner;
+	type SignedTransaction = polkadot_like::UncheckedExtrinsic;
+
+	async fn sign_transaction(
+		client: &Client<Self>,
+		signer: &Self::AccountSigner,
+		era: TransactionEraOf<Self::Chain>,
+		signer_nonce: <Self::Chain as Chain>::Index,
+		call: <Self::Chain as Chain>::Call,
+	) -> relay_substrate_client::Result<Self::SignedTransaction> {
+		polkadot_like::sign_transaction(client, signer, era, signer_nonce, call).await
+	}
+}
@@ -0,0 +1,101 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Distributions of generated message parameters.
+
+use rand::Rng;
+use std::str::FromStr;
+
+/// Distribution of randomly generated values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Distribution {
+	/// Every generated value is the same.
+	Fixed(u64),
+	/// Values are uniformly distributed within inclusive range.
+	Uniform(u64, u64),
+}
+
+impl Distribution {
+	/// Generate random value.
+	pub fn sample(&self, rng: &mut impl Rng) -> u64 {
+		match *self {
+			Distribution::Fixed(value) => value,
+			Distribution::Uniform(min, max) => rng.gen_range(min, max + 1),
+		}
+	}
+}
+
+/// Distribution is parsed either from single number (`64`), or from inclusive range of
+/// uniformly distributed values (`16-1024`).
+impl FromStr for Distribution {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let parse_value = |value: &str| {
+			value
+				.trim()
+				.parse::<u64>()
+				.map_err(|e| format!("Invalid value '{}' in distribution '{}': {}", value, s, e))
+		};
+
+		match s.find('-') {
+			Some(separator) => {
+				let (min, max) = (parse_value(&s[..separator])?, parse_value(&s[separator + 1..])?);
+				if min > max {
+					return Err(format!(
+						"Invalid distribution '{}': minimal value is larger than maximal",
+						s
+					));
+				}
+				if max == u64::MAX {
+					return Err(format!("Invalid distribution '{}': maximal value is too large", s));
+				}
+
+				Ok(Distribution::Uniform(min, max))
+			}
+			None => parse_value(s).map(Distribution::Fixed),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn distribution_is_parsed() {
+		assert_eq!("64".parse(), Ok(Distribution::Fixed(64)));
+		assert_eq!("16-1024".parse(), Ok(Distribution::Uniform(16, 1024)));
+		assert_eq!("16 - 16".parse(), Ok(Distribution::Uniform(16, 16)));
+
+		assert!("".parse::<Distribution>().is_err());
+		assert!("x".parse::<Distribution>().is_err());
+		assert!("16-".parse::<Distribution>().is_err());
+		assert!("1024-16".parse::<Distribution>().is_err());
+		assert!(format!("0-{}", u64::MAX).parse::<Distribution>().is_err());
+	}
+
+	#[test]
+	fn sampled_values_are_within_distribution() {
+		let mut rng = rand::thread_rng();
+		assert_eq!(Distribution::Fixed(64).sample(&mut rng), 64);
+		assert_eq!(Distribution::Uniform(16, 16).sample(&mut rng), 16);
+		for _ in 0..100 {
+			let value = Distribution::Uniform(16, 1024).sample(&mut rng);
+			assert!((16..=1024).contains(&value));
+		}
+	}
+}
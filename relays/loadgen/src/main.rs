@@ -0,0 +1,269 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Load generator of Millau -> Rialto message lanes.
+//!
+//! Submits messages to the Millau node at the configured rate and measures how long it takes to
+//! deliver them to Rialto and to confirm their delivery at Millau. Relays that are serving lanes
+//! must be started separately. The load generator must be the only sender of messages over the
+//! lanes it is using, so that nonces of all submitted messages are known in advance.
+
+#![warn(missing_docs)]
+
+mod distribution;
+mod report;
+
+use crate::distribution::Distribution;
+use crate::report::{LaneTracker, Report};
+
+use bp_message_lane::{LaneId, LaneNamespace};
+use pallet_bridge_call_dispatch::SourceAccountNonce;
+use relay_millau_client::SigningParams as MillauSigningParams;
+use relay_rialto_client::SigningParams as RialtoSigningParams;
+use relay_substrate_client::ConnectionParams;
+use relay_utils::initialize::initialize_relay;
+use std::{
+	collections::BTreeMap,
+	path::PathBuf,
+	time::{Duration, Instant},
+};
+use structopt::StructOpt;
+use substrate_relay::{lane_diagnostics, millau_messages_to_rialto, MillauClient, RialtoClient};
+
+/// Interval at which states of lanes are read.
+const LANE_STATE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Load generator CLI args.
+#[derive(StructOpt)]
+#[structopt(about = "Millau -> Rialto message lanes load generator")]
+struct Options {
+	/// Connect to Millau node at given host.
+	#[structopt(long)]
+	millau_host: String,
+	/// Connect to Millau node websocket server at given port.
+	#[structopt(long)]
+	millau_port: u16,
+	/// Connect to Rialto node at given host.
+	#[structopt(long)]
+	rialto_host: String,
+	/// Connect to Rialto node websocket server at given port.
+	#[structopt(long)]
+	rialto_port: u16,
+	/// The SURI of secret key of the Millau account that is sending messages.
+	#[structopt(long)]
+	millau_signer: String,
+	/// The password for the SURI of secret key of the Millau account that is sending messages.
+	#[structopt(long)]
+	millau_signer_password: Option<String>,
+	/// The SURI of secret key of the Rialto account, on behalf of which messages are dispatched.
+	#[structopt(long)]
+	rialto_signer: String,
+	/// The password for the SURI of secret key of the Rialto account, on behalf of which messages
+	/// are dispatched.
+	#[structopt(long)]
+	rialto_signer_password: Option<String>,
+	/// Number of lanes to send messages over. Messages are sent over lanes with indices
+	/// `0..lanes` of the legacy namespace, in round-robin order.
+	#[structopt(long, default_value = "1")]
+	lanes: u16,
+	/// Number of messages to send.
+	#[structopt(long, default_value = "100")]
+	messages: usize,
+	/// Rate of messages submission (messages per second).
+	#[structopt(long, default_value = "1")]
+	rate: f64,
+	/// Size (in bytes) of the remark that is dispatched at Rialto. Either fixed size (`64`), or
+	/// inclusive range of uniformly distributed sizes (`16-1024`).
+	#[structopt(long, default_value = "64")]
+	payload_size: Distribution,
+	/// Delivery and dispatch fee of messages. Either fixed fee (`1000000000`), or inclusive range
+	/// of uniformly distributed fees (`100000000-1000000000`).
+	#[structopt(long, default_value = "1000000000")]
+	fee: Distribution,
+	/// Nonce of the Millau sender account at the Rialto call dispatch pallet. It is a number of
+	/// messages from this account that have already been dispatched at Rialto.
+	#[structopt(long, default_value = "0")]
+	origin_nonce: SourceAccountNonce,
+	/// Stop waiting for messages delivery (in seconds) after the last message has been submitted.
+	#[structopt(long, default_value = "600")]
+	timeout: u64,
+	/// Write report to given file. If not specified, the report is printed to stdout.
+	#[structopt(long)]
+	report: Option<PathBuf>,
+}
+
+fn main() {
+	initialize_relay();
+
+	let result = async_std::task::block_on(run(Options::from_args()));
+	if let Err(error) = result {
+		log::error!(target: "bridge", "Load generation has failed: {}", error);
+		std::process::exit(1);
+	}
+}
+
+async fn run(options: Options) -> Result<(), String> {
+	if options.lanes == 0 || !options.rate.is_finite() || options.rate <= 0.0 {
+		return Err("Number of lanes and messages submission rate must be positive".into());
+	}
+
+	let millau_client = MillauClient::new(ConnectionParams {
+		host: options.millau_host.clone(),
+		port: options.millau_port,
+		..Default::default()
+	})
+	.await?;
+	let rialto_client = RialtoClient::new(ConnectionParams {
+		host: options.rialto_host.clone(),
+		port: options.rialto_port,
+		..Default::default()
+	})
+	.await?;
+	let millau_sign = MillauSigningParams::from_suri(&options.millau_signer, options.millau_signer_password.as_deref())
+		.map_err(|e| format!("Failed to parse millau-signer: {:?}", e))?;
+	let rialto_sign = RialtoSigningParams::from_suri(&options.rialto_signer, options.rialto_signer_password.as_deref())
+		.map_err(|e| format!("Failed to parse rialto-signer: {:?}", e))?;
+
+	let lanes = (0..options.lanes)
+		.map(|index| LaneId::new(LaneNamespace::LEGACY, index))
+		.collect::<Vec<_>>();
+	let mut trackers = BTreeMap::new();
+	for lane in &lanes {
+		let lane_state = read_lane_state(&millau_client, &rialto_client, *lane).await?;
+		trackers.insert(
+			*lane,
+			LaneTracker::new(
+				lane_state.latest_generated_nonce,
+				lane_state.latest_received_nonce_at_target,
+				lane_state.latest_confirmed_nonce_at_source,
+			),
+		);
+	}
+
+	let mut rng = rand::thread_rng();
+	let mut origin_nonce = options.origin_nonce;
+	let mut attempted_submissions = 0;
+	let mut submission_failures = 0;
+	let submission_interval = Duration::from_secs_f64(1.0 / options.rate);
+	let timeout = Duration::from_secs(options.timeout);
+	let started_at = Instant::now();
+	let mut last_submission_at = started_at;
+	let mut next_submission_at = started_at;
+	let mut next_poll_at = started_at;
+	loop {
+		let is_submitting = attempted_submissions < options.messages;
+		if is_submitting && Instant::now() >= next_submission_at {
+			let lane = lanes[attempted_submissions % lanes.len()];
+			let payload_size = options.payload_size.sample(&mut rng) as usize;
+			let fee = options.fee.sample(&mut rng);
+			let rialto_call = rialto_runtime::Call::System(rialto_runtime::SystemCall::remark(vec![0u8; payload_size]));
+			let submission_result = millau_messages_to_rialto::submit_message(
+				&millau_client,
+				&millau_sign,
+				&rialto_sign,
+				lane,
+				rialto_call,
+				fee,
+				origin_nonce,
+			)
+			.await;
+			match submission_result {
+				Ok(_) => {
+					let nonce = trackers
+						.get_mut(&lane)
+						.expect("trackers are created for all lanes; qed")
+						.message_submitted(Instant::now());
+					log::debug!(target: "bridge", "Submitted message {} to lane {}", nonce, lane);
+					origin_nonce += 1;
+				}
+				Err(error) => {
+					log::warn!(target: "bridge", "Failed to submit message to lane {}: {}", lane, error);
+					submission_failures += 1;
+				}
+			}
+
+			attempted_submissions += 1;
+			last_submission_at = Instant::now();
+			next_submission_at += submission_interval;
+			continue;
+		}
+
+		if Instant::now() >= next_poll_at {
+			for lane in &lanes {
+				match read_lane_state(&millau_client, &rialto_client, *lane).await {
+					Ok(lane_state) => trackers
+						.get_mut(lane)
+						.expect("trackers are created for all lanes; qed")
+						.lane_state_updated(
+							lane_state.latest_received_nonce_at_target,
+							lane_state.latest_confirmed_nonce_at_source,
+							Instant::now(),
+						),
+					Err(error) => log::warn!(target: "bridge", "Failed to read state of lane {}: {}", lane, error),
+				}
+			}
+			next_poll_at = Instant::now() + LANE_STATE_POLL_INTERVAL;
+
+			if !is_submitting {
+				if trackers.values().all(LaneTracker::is_completed) {
+					break;
+				}
+				if last_submission_at.elapsed() > timeout {
+					log::warn!(
+						target: "bridge",
+						"Not all messages have been confirmed within {}s after the last submission",
+						options.timeout,
+					);
+					break;
+				}
+			}
+			continue;
+		}
+
+		let next_event_at = if is_submitting {
+			std::cmp::min(next_submission_at, next_poll_at)
+		} else {
+			next_poll_at
+		};
+		async_std::task::sleep(next_event_at.saturating_duration_since(Instant::now())).await;
+	}
+
+	let report = Report::new(
+		trackers.iter().map(|(lane, tracker)| (lane.to_string(), tracker)),
+		options.rate,
+		submission_failures,
+		started_at,
+		Instant::now(),
+	);
+	let json = serde_json::to_string_pretty(&report).map_err(|e| format!("Failed to serialize report: {}", e))?;
+	match options.report {
+		Some(path) => {
+			std::fs::write(&path, json).map_err(|e| format!("Failed to write report to {}: {}", path.display(), e))?
+		}
+		None => println!("{}", json),
+	}
+
+	Ok(())
+}
+
+/// Read state of the Millau -> Rialto lane.
+async fn read_lane_state(
+	millau_client: &MillauClient,
+	rialto_client: &RialtoClient,
+	lane: LaneId,
+) -> Result<lane_diagnostics::LaneDiagnostics, String> {
+	lane_diagnostics::millau_to_rialto(millau_client.clone(), rialto_client.clone(), lane, None, None, None).await
+}
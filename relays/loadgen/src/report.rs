@@ -0,0 +1,329 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tracking of submitted messages and the load generation report.
+//!
+//! Messages are considered delivered (confirmed) when the load generator reads the lane state
+//! where the message is delivered (confirmed). So latencies are measured with the lane state
+//! polling precision.
+
+use bp_message_lane::MessageNonce;
+use serde::Serialize;
+use std::{
+	collections::BTreeMap,
+	time::{Duration, Instant},
+};
+
+/// Timings of the single message.
+#[derive(Debug, Clone, Copy)]
+struct MessageTimings {
+	/// When the message has been submitted.
+	submitted_at: Instant,
+	/// When the message has been seen delivered to the target chain.
+	delivered_at: Option<Instant>,
+	/// When the message delivery has been seen confirmed at the source chain.
+	confirmed_at: Option<Instant>,
+}
+
+/// Tracker of messages that are sent over the single lane.
+///
+/// The load generator must be the only sender of messages over the lane. Then nonces of all
+/// submitted messages are known in advance.
+#[derive(Debug)]
+pub struct LaneTracker {
+	/// Nonce of the latest message that has been submitted to the lane.
+	latest_nonce: MessageNonce,
+	/// Nonce of the latest message that has been seen delivered.
+	latest_received_nonce: MessageNonce,
+	/// Nonce of the latest message that has been seen confirmed.
+	latest_confirmed_nonce: MessageNonce,
+	/// Timings of messages that have been submitted by the load generator.
+	messages: BTreeMap<MessageNonce, MessageTimings>,
+}
+
+impl LaneTracker {
+	/// Create tracker of the lane with given state.
+	pub fn new(
+		latest_generated_nonce: MessageNonce,
+		latest_received_nonce: MessageNonce,
+		latest_confirmed_nonce: MessageNonce,
+	) -> Self {
+		LaneTracker {
+			latest_nonce: latest_generated_nonce,
+			latest_received_nonce,
+			latest_confirmed_nonce,
+			messages: BTreeMap::new(),
+		}
+	}
+
+	/// Remember that the next message has been submitted. Returns nonce of the message.
+	pub fn message_submitted(&mut self, at: Instant) -> MessageNonce {
+		self.latest_nonce += 1;
+		self.messages.insert(
+			self.latest_nonce,
+			MessageTimings {
+				submitted_at: at,
+				delivered_at: None,
+				confirmed_at: None,
+			},
+		);
+		self.latest_nonce
+	}
+
+	/// Update lane state.
+	pub fn lane_state_updated(
+		&mut self,
+		latest_received_nonce: MessageNonce,
+		latest_confirmed_nonce: MessageNonce,
+		at: Instant,
+	) {
+		if latest_received_nonce > self.latest_received_nonce {
+			for timings in self
+				.messages
+				.range_mut(self.latest_received_nonce + 1..=latest_received_nonce)
+				.map(|(_, timings)| timings)
+			{
+				timings.delivered_at = Some(at);
+			}
+			self.latest_received_nonce = latest_received_nonce;
+		}
+
+		if latest_confirmed_nonce > self.latest_confirmed_nonce {
+			for timings in self
+				.messages
+				.range_mut(self.latest_confirmed_nonce + 1..=latest_confirmed_nonce)
+				.map(|(_, timings)| timings)
+			{
+				// delivery may be confirmed before we have seen the message delivered
+				timings.delivered_at.get_or_insert(at);
+				timings.confirmed_at = Some(at);
+			}
+			self.latest_confirmed_nonce = latest_confirmed_nonce;
+		}
+	}
+
+	/// Returns true if delivery of all submitted messages has been confirmed.
+	pub fn is_completed(&self) -> bool {
+		self.latest_confirmed_nonce >= self.latest_nonce
+	}
+}
+
+/// Latency statistics (in seconds).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LatencyStats {
+	/// Minimal latency.
+	pub min: f64,
+	/// Mean latency.
+	pub mean: f64,
+	/// Median latency.
+	pub p50: f64,
+	/// 90th percentile of latencies.
+	pub p90: f64,
+	/// 99th percentile of latencies.
+	pub p99: f64,
+	/// Maximal latency.
+	pub max: f64,
+}
+
+impl LatencyStats {
+	/// Compute statistics of given latencies. Returns `None` if there are no latencies.
+	pub fn new(mut latencies: Vec<Duration>) -> Option<Self> {
+		latencies.sort();
+		let (min, max) = (*latencies.first()?, *latencies.last()?);
+		let total = latencies.iter().sum::<Duration>();
+		Some(LatencyStats {
+			min: min.as_secs_f64(),
+			mean: total.as_secs_f64() / latencies.len() as f64,
+			p50: percentile(&latencies, 50).as_secs_f64(),
+			p90: percentile(&latencies, 90).as_secs_f64(),
+			p99: percentile(&latencies, 99).as_secs_f64(),
+			max: max.as_secs_f64(),
+		})
+	}
+}
+
+/// Load generation report.
+#[derive(Debug, Serialize)]
+pub struct Report {
+	/// Lanes that have been used to send messages.
+	pub lanes: Vec<String>,
+	/// Requested rate of messages submission (messages per second).
+	pub requested_rate: f64,
+	/// Number of messages that have been successfully submitted.
+	pub messages_submitted: usize,
+	/// Number of messages that the load generator has failed to submit.
+	pub submission_failures: usize,
+	/// Number of messages that have been delivered to the target chain.
+	pub messages_delivered: usize,
+	/// Number of messages, which delivery has been confirmed at the source chain.
+	pub messages_confirmed: usize,
+	/// Duration (in seconds) of the load generation.
+	pub duration: f64,
+	/// Actual rate of messages submission (messages per second).
+	pub submission_rate: f64,
+	/// Rate of messages delivery (messages per second) - number of delivered messages divided
+	/// by the time between the first submission and the last delivery.
+	pub delivery_rate: f64,
+	/// Latency of messages delivery: time between message submission and the moment when it has
+	/// been seen delivered to the target chain.
+	pub delivery_latency: Option<LatencyStats>,
+	/// Latency of messages delivery confirmation: time between message submission and the moment
+	/// when its delivery has been seen confirmed at the source chain.
+	pub confirmation_latency: Option<LatencyStats>,
+}
+
+impl Report {
+	/// Build report from state of lanes trackers.
+	pub fn new<'a>(
+		lanes: impl IntoIterator<Item = (String, &'a LaneTracker)>,
+		requested_rate: f64,
+		submission_failures: usize,
+		started_at: Instant,
+		finished_at: Instant,
+	) -> Self {
+		let mut lane_names = Vec::new();
+		let mut messages = Vec::new();
+		for (lane_name, tracker) in lanes {
+			lane_names.push(lane_name);
+			messages.extend(tracker.messages.values().cloned());
+		}
+
+		let delivery_latencies = messages
+			.iter()
+			.filter_map(|timings| timings.delivered_at.map(|at| at - timings.submitted_at))
+			.collect::<Vec<_>>();
+		let confirmation_latencies = messages
+			.iter()
+			.filter_map(|timings| timings.confirmed_at.map(|at| at - timings.submitted_at))
+			.collect::<Vec<_>>();
+		let first_submitted_at = messages.iter().map(|timings| timings.submitted_at).min();
+		let last_submitted_at = messages.iter().map(|timings| timings.submitted_at).max();
+		let last_delivered_at = messages.iter().filter_map(|timings| timings.delivered_at).max();
+		let rate = |count: usize, from: Option<Instant>, to: Option<Instant>| match (from, to) {
+			(Some(from), Some(to)) if to > from => count as f64 / (to - from).as_secs_f64(),
+			_ => 0.0,
+		};
+
+		Report {
+			lanes: lane_names,
+			requested_rate,
+			messages_submitted: messages.len(),
+			submission_failures,
+			messages_delivered: delivery_latencies.len(),
+			messages_confirmed: confirmation_latencies.len(),
+			duration: (finished_at - started_at).as_secs_f64(),
+			submission_rate: rate(messages.len(), Some(started_at), last_submitted_at),
+			delivery_rate: rate(delivery_latencies.len(), first_submitted_at, last_delivered_at),
+			delivery_latency: LatencyStats::new(delivery_latencies),
+			confirmation_latency: LatencyStats::new(confirmation_latencies),
+		}
+	}
+}
+
+/// Return given (nearest-rank) percentile of sorted values.
+fn percentile(sorted: &[Duration], percentile: usize) -> Duration {
+	let rank = (percentile * sorted.len() + 99) / 100;
+	sorted[rank.saturating_sub(1)]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn secs(latencies: &[u64]) -> Vec<Duration> {
+		latencies.iter().cloned().map(Duration::from_secs).collect()
+	}
+
+	#[test]
+	fn percentiles_are_computed() {
+		let latencies = secs(&(1..=100).collect::<Vec<_>>());
+		assert_eq!(percentile(&latencies, 50), Duration::from_secs(50));
+		assert_eq!(percentile(&latencies, 90), Duration::from_secs(90));
+		assert_eq!(percentile(&latencies, 99), Duration::from_secs(99));
+		assert_eq!(percentile(&latencies, 100), Duration::from_secs(100));
+
+		let latencies = secs(&[7]);
+		assert_eq!(percentile(&latencies, 50), Duration::from_secs(7));
+		assert_eq!(percentile(&latencies, 99), Duration::from_secs(7));
+	}
+
+	#[test]
+	fn latency_stats_are_computed() {
+		assert_eq!(LatencyStats::new(Vec::new()), None);
+		assert_eq!(
+			LatencyStats::new(secs(&[4, 1, 3, 2])),
+			Some(LatencyStats {
+				min: 1.0,
+				mean: 2.5,
+				p50: 2.0,
+				p90: 4.0,
+				p99: 4.0,
+				max: 4.0,
+			}),
+		);
+	}
+
+	#[test]
+	fn lane_tracker_tracks_deliveries_and_confirmations() {
+		let started_at = Instant::now();
+		let at = |secs| started_at + Duration::from_secs(secs);
+
+		// message #5 has been sent before load generation has started
+		let mut tracker = LaneTracker::new(5, 4, 3);
+		assert_eq!(tracker.message_submitted(at(0)), 6);
+		assert_eq!(tracker.message_submitted(at(1)), 7);
+		assert_eq!(tracker.message_submitted(at(2)), 8);
+		assert!(!tracker.is_completed());
+
+		tracker.lane_state_updated(6, 4, at(10));
+		tracker.lane_state_updated(6, 3, at(11));
+		tracker.lane_state_updated(7, 6, at(12));
+		// message #8 delivery is confirmed before we have seen it delivered
+		tracker.lane_state_updated(7, 8, at(20));
+		assert!(tracker.is_completed());
+
+		let report = Report::new(vec![("00000000".into(), &tracker)], 1.0, 1, started_at, at(30));
+		assert_eq!(report.messages_submitted, 3);
+		assert_eq!(report.submission_failures, 1);
+		assert_eq!(report.messages_delivered, 3);
+		assert_eq!(report.messages_confirmed, 3);
+		assert_eq!(report.duration, 30.0);
+		assert_eq!(report.submission_rate, 1.5);
+		assert_eq!(report.delivery_rate, 3.0 / 20.0);
+		assert_eq!(
+			report.delivery_latency,
+			Some(LatencyStats {
+				min: 10.0,
+				mean: 13.0,
+				p50: 11.0,
+				p90: 18.0,
+				p99: 18.0,
+				max: 18.0,
+			}),
+		);
+		assert_eq!(
+			report.confirmation_latency,
+			Some(LatencyStats {
+				min: 12.0,
+				mean: 49.0 / 3.0,
+				p50: 18.0,
+				p90: 19.0,
+				p99: 19.0,
+				max: 19.0,
+			}),
+		);
+	}
+}
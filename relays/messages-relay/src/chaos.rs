@@ -0,0 +1,273 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fault injection into message lane clients.
+//!
+//! Message lane clients may be wrapped into the `ChaosClient`, which drops some transaction
+//! submissions, delays proofs generation and randomly disconnects clients. It is only meant to
+//! be used on test networks, to make sure that recovery logic, transactions resubmission and
+//! alarms are working before the real incident happens.
+
+use crate::message_lane::{MessageLane, SourceHeaderIdOf, TargetHeaderIdOf};
+use crate::message_lane_loop::{
+	MessageProofParameters, MessageRewardsMap, MessageWeightsMap, MessagesDeliverySubmission, SourceClient,
+	SourceClientState, TargetClient, TargetClientState,
+};
+
+use async_trait::async_trait;
+use bp_message_lane::MessageNonce;
+use rand::Rng;
+use relay_utils::MaybeConnectionError;
+use std::{future::Future, ops::RangeInclusive, time::Duration};
+
+/// Fault injection parameters.
+#[derive(Debug, Clone, Default)]
+pub struct ChaosParams {
+	/// Probability that the transaction submission is silently dropped.
+	pub drop_submission_probability: f64,
+	/// Delay of every proof generation.
+	pub proof_delay: Duration,
+	/// Probability that the client call fails with connection error, forcing the relay to reconnect.
+	pub disconnect_probability: f64,
+}
+
+impl ChaosParams {
+	/// Returns true if any faults are injected.
+	pub fn is_enabled(&self) -> bool {
+		self.drop_submission_probability > 0.0
+			|| self.proof_delay != Duration::from_secs(0)
+			|| self.disconnect_probability > 0.0
+	}
+}
+
+/// Error of the chaos client.
+#[derive(Debug)]
+pub enum ChaosError<E> {
+	/// Error of the wrapped client.
+	Client(E),
+	/// Client has been disconnected by the fault injection.
+	Disconnected,
+}
+
+impl<E: MaybeConnectionError> MaybeConnectionError for ChaosError<E> {
+	fn is_connection_error(&self) -> bool {
+		match *self {
+			ChaosError::Client(ref error) => error.is_connection_error(),
+			ChaosError::Disconnected => true,
+		}
+	}
+}
+
+/// Message lane client with fault injection.
+#[derive(Debug, Clone)]
+pub struct ChaosClient<C> {
+	client: C,
+	params: ChaosParams,
+}
+
+impl<C> ChaosClient<C> {
+	/// Wrap given client.
+	pub fn new(client: C, params: ChaosParams) -> Self {
+		ChaosClient { client, params }
+	}
+
+	/// Call wrapped client. The call fails with connection error with configured probability.
+	async fn call<T, E>(&self, call: impl Future<Output = Result<T, E>>) -> Result<T, ChaosError<E>> {
+		if happens(self.params.disconnect_probability) {
+			log::warn!(target: "bridge", "Chaos: disconnecting client");
+			return Err(ChaosError::Disconnected);
+		}
+
+		call.await.map_err(ChaosError::Client)
+	}
+
+	/// Returns true if the transaction submission must be dropped.
+	fn drop_submission(&self) -> bool {
+		happens(self.params.drop_submission_probability)
+	}
+
+	/// Wait before generating proof.
+	async fn delay_proof(&self) {
+		if self.params.proof_delay != Duration::from_secs(0) {
+			log::warn!(
+				target: "bridge",
+				"Chaos: delaying proof generation by {}s",
+				self.params.proof_delay.as_secs_f64(),
+			);
+			async_std::task::sleep(self.params.proof_delay).await;
+		}
+	}
+}
+
+#[async_trait]
+impl<P: MessageLane, C: SourceClient<P>> SourceClient<P> for ChaosClient<C> {
+	type Error = ChaosError<C::Error>;
+
+	async fn reconnect(self) -> Result<Self, Self::Error> {
+		let ChaosClient { client, params } = self;
+		let client = client.reconnect().await.map_err(ChaosError::Client)?;
+		Ok(ChaosClient { client, params })
+	}
+
+	async fn state(&self) -> Result<SourceClientState<P>, Self::Error> {
+		self.call(self.client.state()).await
+	}
+
+	async fn latest_generated_nonce(
+		&self,
+		id: SourceHeaderIdOf<P>,
+	) -> Result<(SourceHeaderIdOf<P>, MessageNonce), Self::Error> {
+		self.call(self.client.latest_generated_nonce(id)).await
+	}
+
+	async fn latest_confirmed_received_nonce(
+		&self,
+		id: SourceHeaderIdOf<P>,
+	) -> Result<(SourceHeaderIdOf<P>, MessageNonce), Self::Error> {
+		self.call(self.client.latest_confirmed_received_nonce(id)).await
+	}
+
+	async fn generated_messages_weights(
+		&self,
+		id: SourceHeaderIdOf<P>,
+		nonces: RangeInclusive<MessageNonce>,
+	) -> Result<MessageWeightsMap, Self::Error> {
+		self.call(self.client.generated_messages_weights(id, nonces)).await
+	}
+
+	async fn generated_messages_rewards(
+		&self,
+		id: SourceHeaderIdOf<P>,
+		nonces: RangeInclusive<MessageNonce>,
+	) -> Result<MessageRewardsMap, Self::Error> {
+		self.call(self.client.generated_messages_rewards(id, nonces)).await
+	}
+
+	async fn prove_messages(
+		&self,
+		id: SourceHeaderIdOf<P>,
+		nonces: RangeInclusive<MessageNonce>,
+		proof_parameters: MessageProofParameters,
+	) -> Result<(SourceHeaderIdOf<P>, RangeInclusive<MessageNonce>, P::MessagesProof), Self::Error> {
+		self.delay_proof().await;
+		self.call(self.client.prove_messages(id, nonces, proof_parameters))
+			.await
+	}
+
+	async fn submit_messages_receiving_proof(
+		&self,
+		generated_at_block: TargetHeaderIdOf<P>,
+		proof: P::MessagesReceivingProof,
+	) -> Result<(), Self::Error> {
+		if self.drop_submission() {
+			log::warn!(
+				target: "bridge",
+				"Chaos: dropping messages receiving proof, generated at {} block {:?}",
+				P::TARGET_NAME,
+				generated_at_block,
+			);
+			return Ok(());
+		}
+
+		self.call(self.client.submit_messages_receiving_proof(generated_at_block, proof))
+			.await
+	}
+}
+
+#[async_trait]
+impl<P: MessageLane, C: TargetClient<P>> TargetClient<P> for ChaosClient<C> {
+	type Error = ChaosError<C::Error>;
+
+	async fn reconnect(self) -> Result<Self, Self::Error> {
+		let ChaosClient { client, params } = self;
+		let client = client.reconnect().await.map_err(ChaosError::Client)?;
+		Ok(ChaosClient { client, params })
+	}
+
+	async fn state(&self) -> Result<TargetClientState<P>, Self::Error> {
+		self.call(self.client.state()).await
+	}
+
+	async fn latest_received_nonce(
+		&self,
+		id: TargetHeaderIdOf<P>,
+	) -> Result<(TargetHeaderIdOf<P>, MessageNonce), Self::Error> {
+		self.call(self.client.latest_received_nonce(id)).await
+	}
+
+	async fn latest_confirmed_received_nonce(
+		&self,
+		id: TargetHeaderIdOf<P>,
+	) -> Result<(TargetHeaderIdOf<P>, MessageNonce), Self::Error> {
+		self.call(self.client.latest_confirmed_received_nonce(id)).await
+	}
+
+	async fn prove_messages_receiving(
+		&self,
+		id: TargetHeaderIdOf<P>,
+	) -> Result<(TargetHeaderIdOf<P>, P::MessagesReceivingProof), Self::Error> {
+		self.delay_proof().await;
+		self.call(self.client.prove_messages_receiving(id)).await
+	}
+
+	async fn submit_messages_proof(
+		&self,
+		generated_at_header: SourceHeaderIdOf<P>,
+		nonces: RangeInclusive<MessageNonce>,
+		proof: P::MessagesProof,
+	) -> Result<MessagesDeliverySubmission, Self::Error> {
+		if self.drop_submission() {
+			log::warn!(
+				target: "bridge",
+				"Chaos: dropping proof of messages {:?}, generated at {} block {:?}",
+				nonces,
+				P::SOURCE_NAME,
+				generated_at_header,
+			);
+			return Ok(MessagesDeliverySubmission::Submitted(nonces, None));
+		}
+
+		self.call(self.client.submit_messages_proof(generated_at_header, nonces, proof))
+			.await
+	}
+}
+
+/// Returns true with given probability.
+fn happens(probability: f64) -> bool {
+	probability > 0.0 && rand::thread_rng().gen_bool(probability.min(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn chaos_is_disabled_by_default() {
+		assert!(!ChaosParams::default().is_enabled());
+		assert!(ChaosParams {
+			proof_delay: Duration::from_secs(1),
+			..Default::default()
+		}
+		.is_enabled());
+	}
+
+	#[test]
+	fn faults_are_injected_with_given_probability() {
+		assert!((0..100).all(|_| !happens(0.0)));
+		assert!((0..100).all(|_| happens(1.0)));
+		assert!((0..100).all(|_| happens(2.0)));
+	}
+}
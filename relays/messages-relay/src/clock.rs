@@ -0,0 +1,41 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Source of the current time.
+//!
+//! Relay logic that depends on the time (deadlines, alarms, ...) reads it from the `Clock`,
+//! so it may be tested using the controllable clock instead of sleeping.
+
+use std::{sync::Arc, time::Instant};
+
+/// Source of the current time.
+pub trait Clock: Send + Sync {
+	/// Returns current moment.
+	fn now(&self) -> Instant;
+}
+
+/// Clock that is shared by relay components.
+pub type SharedClock = Arc<dyn Clock>;
+
+/// Clock that reads the system time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+	fn now(&self) -> Instant {
+		Instant::now()
+	}
+}
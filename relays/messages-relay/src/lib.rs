@@ -25,12 +25,20 @@
 #![recursion_limit = "1024"]
 #![warn(missing_docs)]
 
+mod clock;
 mod metrics;
 
+pub mod chaos;
 pub mod message_lane;
 pub mod message_lane_loop;
+pub mod message_trace;
 
 mod message_race_delivery;
 mod message_race_loop;
 mod message_race_receiving;
 mod message_race_strategy;
+
+#[cfg(test)]
+mod simulation;
+#[cfg(test)]
+mod test_utils;
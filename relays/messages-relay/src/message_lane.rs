@@ -43,6 +43,9 @@ pub trait MessageLane: Clone + Send + Sync {
 	type TargetHeaderNumber: BlockNumberBase;
 	/// Hash of the target header.
 	type TargetHeaderHash: Clone + Debug + Default + PartialEq + Send + Sync;
+
+	/// Return size (in bytes) of the messages proof, as it is submitted to the target node.
+	fn messages_proof_size(proof: &Self::MessagesProof) -> usize;
 }
 
 /// Source header id within given one-way message lane.
@@ -27,10 +27,12 @@
 use crate::message_lane::{MessageLane, SourceHeaderIdOf, TargetHeaderIdOf};
 use crate::message_race_delivery::run as run_message_delivery_race;
 use crate::message_race_receiving::run as run_message_receiving_race;
+use crate::message_trace::MessageTracer;
 use crate::metrics::MessageLaneLoopMetrics;
 
 use async_trait::async_trait;
 use bp_message_lane::{LaneId, MessageNonce, Weight};
+use bp_message_lane_weights::MessageLaneWeights;
 use futures::{channel::mpsc::unbounded, future::FutureExt, stream::StreamExt};
 use relay_utils::{
 	interval,
@@ -54,6 +56,8 @@ pub struct Params {
 	pub stall_timeout: Duration,
 	/// Message delivery race parameters.
 	pub delivery_params: MessageDeliveryParams,
+	/// OpenTelemetry collector endpoint, where traces of relayed messages are exported.
+	pub otlp_endpoint: Option<String>,
 }
 
 /// Message delivery race parameters.
@@ -63,13 +67,43 @@ pub struct MessageDeliveryParams {
 	/// unconfirmed nonces on the target node. The race would continue once they're confirmed by the
 	/// receiving race.
 	pub max_unconfirmed_nonces_at_target: MessageNonce,
-	/// Maximal cumulative dispatch weight of relayed messages in single delivery transaction.
-	pub max_messages_weight_in_single_batch: Weight,
+	/// Maximal weight of single delivery transaction at the target chain.
+	pub max_delivery_transaction_weight: Weight,
+	/// Maximal size of the messages proof, accepted by the target chain. The proof size isn't
+	/// known until the proof is generated, so the weight of verifying the largest possible proof
+	/// is reserved in every delivery transaction.
+	pub max_messages_proof_size: u32,
+	/// Parameters of the target chain delivery transaction weight formula.
+	pub delivery_weights: MessageLaneWeights,
+	/// Maximal acceptable delay between the moment when relay has seen the message at the source
+	/// node and the moment it has been delivered to the target node. If the oldest undelivered
+	/// message waits for longer, the alarm is raised (error is logged and metric is updated).
+	pub delivery_deadline: Option<Duration>,
+	/// Maximal total reward (in source chain base units) of messages that have been delivered by
+	/// this relay, but delivery of which is not yet confirmed at the source node. The relayer is
+	/// only paid after confirmation, so new messages are not delivered while this cap is exceeded.
+	pub max_unconfirmed_rewards: Option<u128>,
 }
 
 /// Messages weights map.
 pub type MessageWeightsMap = BTreeMap<MessageNonce, Weight>;
 
+/// Messages rewards map. Reward is paid (in source chain base units) to the relayer that has
+/// delivered the message, once delivery is confirmed at the source chain.
+pub type MessageRewardsMap = BTreeMap<MessageNonce, u128>;
+
+/// Message details, used by the delivery race.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MessageDetails {
+	/// Dispatch weight of the message.
+	pub dispatch_weight: Weight,
+	/// Reward of the relayer that delivers the message.
+	pub reward: u128,
+}
+
+/// Messages details map.
+pub type MessageDetailsMap = BTreeMap<MessageNonce, MessageDetails>;
+
 /// Message delivery race proof parameters.
 #[derive(Debug, PartialEq)]
 pub struct MessageProofParameters {
@@ -109,6 +143,14 @@ pub trait SourceClient<P: MessageLane>: Clone + Send + Sync {
 		nonces: RangeInclusive<MessageNonce>,
 	) -> Result<MessageWeightsMap, Self::Error>;
 
+	/// Returns mapping of message nonces, generated on this client, to rewards of relayer that
+	/// delivers them.
+	async fn generated_messages_rewards(
+		&self,
+		id: SourceHeaderIdOf<P>,
+		nonces: RangeInclusive<MessageNonce>,
+	) -> Result<MessageRewardsMap, Self::Error>;
+
 	/// Prove messages in inclusive range [begin; end].
 	async fn prove_messages(
 		&self,
@@ -156,12 +198,36 @@ pub trait TargetClient<P: MessageLane>: Clone + Send + Sync {
 	) -> Result<(TargetHeaderIdOf<P>, P::MessagesReceivingProof), Self::Error>;
 
 	/// Submit messages proof.
+	///
+	/// The client may estimate (and dry-run) the delivery transaction before submission. If the
+	/// transaction is guaranteed to fail (e.g. because the batch is too large, or the relayer
+	/// can't pay for it), it must not be submitted and `MessagesDeliverySubmission::WillFail`
+	/// must be returned. The race will retry with smaller batch then.
 	async fn submit_messages_proof(
 		&self,
 		generated_at_header: SourceHeaderIdOf<P>,
 		nonces: RangeInclusive<MessageNonce>,
 		proof: P::MessagesProof,
-	) -> Result<RangeInclusive<MessageNonce>, Self::Error>;
+	) -> Result<MessagesDeliverySubmission, Self::Error>;
+}
+
+/// Result of the messages delivery transaction submission.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MessagesDeliverySubmission {
+	/// Transaction that delivers given nonces has been submitted. If the transaction has been
+	/// estimated before submission, the estimation is also returned.
+	Submitted(RangeInclusive<MessageNonce>, Option<MessagesDeliveryEstimation>),
+	/// Transaction has not been submitted, because it is guaranteed to fail at the target node.
+	WillFail(String),
+}
+
+/// Estimated cost of the messages delivery transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MessagesDeliveryEstimation {
+	/// Fee that will be paid by the relayer for the transaction (in target chain base units).
+	pub fee: u128,
+	/// Dispatch weight of the transaction.
+	pub weight: Weight,
 }
 
 /// State of the client.
@@ -204,17 +270,22 @@ pub fn run<P: MessageLane>(
 		let metrics_msg = MessageLaneLoopMetrics::default();
 		let metrics_enabled = metrics_params.is_some();
 		metrics_start(
-			format!(
-				"{}_to_{}_MessageLoop/{}",
-				P::SOURCE_NAME,
-				P::TARGET_NAME,
-				hex::encode(params.lane)
-			),
+			format!("{}_to_{}_MessageLoop/{}", P::SOURCE_NAME, P::TARGET_NAME, params.lane),
 			metrics_params,
 			&metrics_global,
 			&metrics_msg,
 		);
 
+		let tracer = MessageTracer::new(
+			params.lane,
+			params.otlp_endpoint.clone(),
+			if metrics_enabled {
+				Some(metrics_msg.clone())
+			} else {
+				None
+			},
+		);
+
 		loop {
 			let result = run_until_connection_lost(
 				params.clone(),
@@ -230,6 +301,7 @@ pub fn run<P: MessageLane>(
 				} else {
 					None
 				},
+				tracer.clone(),
 				exit_signal.clone(),
 			)
 			.await;
@@ -290,6 +362,7 @@ async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: Targ
 	target_client: TC,
 	mut metrics_global: Option<&mut GlobalMetrics>,
 	metrics_msg: Option<MessageLaneLoopMetrics>,
+	tracer: MessageTracer<P>,
 	exit_signal: impl Future<Output = ()>,
 ) -> Result<(), FailedClient> {
 	let mut source_retry_backoff = retry_backoff();
@@ -317,6 +390,7 @@ async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: Targ
 		delivery_target_state_receiver,
 		params.stall_timeout,
 		metrics_msg.clone(),
+		tracer.clone(),
 		params.delivery_params,
 	)
 	.fuse();
@@ -332,6 +406,7 @@ async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: Targ
 		receiving_target_state_receiver,
 		params.stall_timeout,
 		metrics_msg.clone(),
+		tracer,
 	)
 	.fuse();
 
@@ -453,6 +528,7 @@ async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: Targ
 #[cfg(test)]
 pub(crate) mod tests {
 	use super::*;
+	use crate::test_utils::{TestClients, TestClientsBuilder, TestKeyring, TestSourceAccountId, TestTargetAccountId};
 	use futures::stream::StreamExt;
 	use parking_lot::Mutex;
 	use relay_utils::HeaderId;
@@ -498,28 +574,38 @@ pub(crate) mod tests {
 
 		type TargetHeaderNumber = TestTargetHeaderNumber;
 		type TargetHeaderHash = TestTargetHeaderHash;
+
+		fn messages_proof_size(proof: &TestMessagesProof) -> usize {
+			(proof.0.end() - proof.0.start() + 1) as usize
+		}
 	}
 
+	/// Callback that is called before every mock client call.
+	pub type TestTick = Arc<dyn Fn(&mut TestClientData) + Send + Sync>;
+
 	#[derive(Debug, Default, Clone)]
 	pub struct TestClientData {
-		is_source_fails: bool,
-		is_source_reconnected: bool,
-		source_state: SourceClientState<TestMessageLane>,
-		source_latest_generated_nonce: MessageNonce,
-		source_latest_confirmed_received_nonce: MessageNonce,
-		submitted_messages_receiving_proofs: Vec<TestMessagesReceivingProof>,
-		is_target_fails: bool,
-		is_target_reconnected: bool,
-		target_state: SourceClientState<TestMessageLane>,
-		target_latest_received_nonce: MessageNonce,
-		target_latest_confirmed_received_nonce: MessageNonce,
-		submitted_messages_proofs: Vec<TestMessagesProof>,
+		pub is_source_fails: bool,
+		pub is_source_reconnected: bool,
+		pub source_state: SourceClientState<TestMessageLane>,
+		pub source_latest_generated_nonce: MessageNonce,
+		pub source_latest_confirmed_received_nonce: MessageNonce,
+		pub submitted_messages_receiving_proofs: Vec<TestMessagesReceivingProof>,
+		pub confirmation_relayers: Vec<TestSourceAccountId>,
+		pub is_target_fails: bool,
+		pub is_target_reconnected: bool,
+		pub target_state: SourceClientState<TestMessageLane>,
+		pub target_latest_received_nonce: MessageNonce,
+		pub target_latest_confirmed_received_nonce: MessageNonce,
+		pub submitted_messages_proofs: Vec<TestMessagesProof>,
+		pub delivery_relayers: Vec<TestTargetAccountId>,
 	}
 
 	#[derive(Clone)]
 	pub struct TestSourceClient {
-		data: Arc<Mutex<TestClientData>>,
-		tick: Arc<dyn Fn(&mut TestClientData) + Send + Sync>,
+		pub data: Arc<Mutex<TestClientData>>,
+		pub tick: TestTick,
+		pub relayer: TestKeyring,
 	}
 
 	#[async_trait]
@@ -573,6 +659,14 @@ pub(crate) mod tests {
 			Ok(nonces.map(|nonce| (nonce, 1)).collect())
 		}
 
+		async fn generated_messages_rewards(
+			&self,
+			_id: SourceHeaderIdOf<TestMessageLane>,
+			nonces: RangeInclusive<MessageNonce>,
+		) -> Result<MessageRewardsMap, Self::Error> {
+			Ok(nonces.map(|nonce| (nonce, 1)).collect())
+		}
+
 		async fn prove_messages(
 			&self,
 			id: SourceHeaderIdOf<TestMessageLane>,
@@ -610,6 +704,7 @@ pub(crate) mod tests {
 			let mut data = self.data.lock();
 			(self.tick)(&mut *data);
 			data.submitted_messages_receiving_proofs.push(proof);
+			data.confirmation_relayers.push(self.relayer.source_account());
 			data.source_latest_confirmed_received_nonce = proof;
 			Ok(())
 		}
@@ -617,8 +712,9 @@ pub(crate) mod tests {
 
 	#[derive(Clone)]
 	pub struct TestTargetClient {
-		data: Arc<Mutex<TestClientData>>,
-		tick: Arc<dyn Fn(&mut TestClientData) + Send + Sync>,
+		pub data: Arc<Mutex<TestClientData>>,
+		pub tick: TestTick,
+		pub relayer: TestKeyring,
 	}
 
 	#[async_trait]
@@ -679,7 +775,7 @@ pub(crate) mod tests {
 			_generated_at_header: SourceHeaderIdOf<TestMessageLane>,
 			nonces: RangeInclusive<MessageNonce>,
 			proof: TestMessagesProof,
-		) -> Result<RangeInclusive<MessageNonce>, Self::Error> {
+		) -> Result<MessagesDeliverySubmission, Self::Error> {
 			let mut data = self.data.lock();
 			(self.tick)(&mut *data);
 			if data.is_target_fails {
@@ -692,38 +788,47 @@ pub(crate) mod tests {
 				data.target_latest_confirmed_received_nonce = target_latest_confirmed_received_nonce;
 			}
 			data.submitted_messages_proofs.push(proof);
-			Ok(nonces)
+			data.delivery_relayers.push(self.relayer.target_account());
+			Ok(MessagesDeliverySubmission::Submitted(nonces, None))
 		}
 	}
 
 	fn run_loop_test(
 		data: TestClientData,
-		source_tick: Arc<dyn Fn(&mut TestClientData) + Send + Sync>,
-		target_tick: Arc<dyn Fn(&mut TestClientData) + Send + Sync>,
+		source_tick: TestTick,
+		target_tick: TestTick,
 		exit_signal: impl Future<Output = ()>,
 	) -> TestClientData {
+		let clients = TestClientsBuilder::with_data(data)
+			.on_source_call(move |data| source_tick(data))
+			.on_target_call(move |data| target_tick(data))
+			.build();
+		run_loop(clients, exit_signal)
+	}
+
+	fn run_loop(clients: TestClients, exit_signal: impl Future<Output = ()>) -> TestClientData {
+		let TestClients {
+			source: source_client,
+			target: target_client,
+			data,
+		} = clients;
 		async_std::task::block_on(async {
-			let data = Arc::new(Mutex::new(data));
-
-			let source_client = TestSourceClient {
-				data: data.clone(),
-				tick: source_tick,
-			};
-			let target_client = TestTargetClient {
-				data: data.clone(),
-				tick: target_tick,
-			};
 			run(
 				Params {
-					lane: [0, 0, 0, 0],
+					lane: Default::default(),
 					source_tick: Duration::from_millis(100),
 					target_tick: Duration::from_millis(100),
 					reconnect_delay: Duration::from_millis(0),
 					stall_timeout: Duration::from_millis(60 * 1000),
 					delivery_params: MessageDeliveryParams {
 						max_unconfirmed_nonces_at_target: 4,
-						max_messages_weight_in_single_batch: 4,
+						max_delivery_transaction_weight: 4,
+						max_messages_proof_size: 0,
+						delivery_weights: bp_message_lane_weights::DEFAULT_MESSAGE_LANE_WEIGHTS,
+						delivery_deadline: None,
+						max_unconfirmed_rewards: None,
 					},
+					otlp_endpoint: None,
 				},
 				source_client,
 				target_client,
@@ -834,4 +939,35 @@ pub(crate) mod tests {
 		assert_eq!(result.submitted_messages_proofs[2].0, 9..=10);
 		assert!(!result.submitted_messages_receiving_proofs.is_empty());
 	}
+
+	#[test]
+	fn message_lane_loop_transactions_are_signed_by_relayer() {
+		let (exit_sender, exit_receiver) = unbounded();
+		let clients = TestClientsBuilder::new()
+			.source_best_block(10)
+			.source_latest_generated_nonce(4)
+			.relayer(TestKeyring::Bob)
+			.on_target_call(move |data: &mut TestClientData| {
+				// syncing headers in both directions (all at once)
+				data.target_state.best_peer = data.source_state.best_self;
+				data.source_state.best_peer = data.target_state.best_self;
+				// if all messages are confirmed => stop the loop
+				if data.source_latest_confirmed_received_nonce == 4 {
+					exit_sender.unbounded_send(()).unwrap();
+				}
+			})
+			.build();
+		let result = run_loop(clients, exit_receiver.into_future().map(|(_, _)| ()));
+
+		assert_eq!(result.submitted_messages_proofs[0].0, 1..=4);
+		assert!(result
+			.delivery_relayers
+			.iter()
+			.all(|relayer| TestKeyring::from_target_account(*relayer) == Some(TestKeyring::Bob)));
+		assert!(!result.confirmation_relayers.is_empty());
+		assert!(result
+			.confirmation_relayers
+			.iter()
+			.all(|relayer| TestKeyring::from_source_account(*relayer) == Some(TestKeyring::Bob)));
+	}
 }
@@ -13,25 +13,41 @@
 
 //! Message delivery race delivers proof-of-messages from lane.source to lane.target.
 
+use crate::clock::{SharedClock, SystemClock};
 use crate::message_lane::{MessageLane, SourceHeaderIdOf, TargetHeaderIdOf};
 use crate::message_lane_loop::{
-	MessageDeliveryParams, MessageProofParameters, MessageWeightsMap, SourceClient as MessageLaneSourceClient,
-	SourceClientState, TargetClient as MessageLaneTargetClient, TargetClientState,
+	MessageDeliveryParams, MessageDetails, MessageDetailsMap, MessageProofParameters, MessagesDeliverySubmission,
+	SourceClient as MessageLaneSourceClient, SourceClientState, TargetClient as MessageLaneTargetClient,
+	TargetClientState,
 };
 use crate::message_race_loop::{
-	MessageRace, NoncesRange, RaceState, RaceStrategy, SourceClient, SourceClientNonces, TargetClient,
+	MessageRace, NoncesRange, ProofSubmission, RaceState, RaceStrategy, SourceClient, SourceClientNonces, TargetClient,
 	TargetClientNonces,
 };
 use crate::message_race_strategy::BasicStrategy;
+use crate::message_trace::{MessageStage, MessageTracer};
 use crate::metrics::MessageLaneLoopMetrics;
 
 use async_trait::async_trait;
 use bp_message_lane::{MessageNonce, Weight};
+use bp_message_lane_weights::MessageLaneWeights;
 use futures::stream::FusedStream;
 use relay_utils::FailedClient;
-use std::{collections::BTreeMap, marker::PhantomData, ops::RangeInclusive, time::Duration};
+use std::{
+	collections::{BTreeMap, VecDeque},
+	marker::PhantomData,
+	ops::RangeInclusive,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+/// Interval between two consecutive delivery deadline alarms.
+const DELIVERY_DEADLINE_ALARM_INTERVAL: Duration = Duration::from_secs(60);
+/// Interval between rejected delivery of the single message and the next delivery attempt.
+const REJECTED_DELIVERY_RETRY_INTERVAL: Duration = Duration::from_secs(30);
 
 /// Run message delivery race.
+#[allow(clippy::too_many_arguments)]
 pub async fn run<P: MessageLane>(
 	source_client: impl MessageLaneSourceClient<P>,
 	source_state_updates: impl FusedStream<Item = SourceClientState<P>>,
@@ -39,27 +55,41 @@ pub async fn run<P: MessageLane>(
 	target_state_updates: impl FusedStream<Item = TargetClientState<P>>,
 	stall_timeout: Duration,
 	metrics_msg: Option<MessageLaneLoopMetrics>,
+	tracer: MessageTracer<P>,
 	params: MessageDeliveryParams,
 ) -> Result<(), FailedClient> {
 	crate::message_race_loop::run(
 		MessageDeliveryRaceSource {
 			client: source_client,
 			metrics_msg: metrics_msg.clone(),
+			tracer: tracer.clone(),
 			_phantom: Default::default(),
 		},
 		source_state_updates,
 		MessageDeliveryRaceTarget {
 			client: target_client,
-			metrics_msg,
+			metrics_msg: metrics_msg.clone(),
+			tracer,
 			_phantom: Default::default(),
 		},
 		target_state_updates,
 		stall_timeout,
 		MessageDeliveryStrategy::<P> {
 			max_unconfirmed_nonces_at_target: params.max_unconfirmed_nonces_at_target,
-			max_messages_weight_in_single_batch: params.max_messages_weight_in_single_batch,
+			max_delivery_transaction_weight: params.max_delivery_transaction_weight,
+			max_messages_proof_size: params.max_messages_proof_size,
+			delivery_weights: params.delivery_weights,
+			delivery_deadline: params.delivery_deadline,
+			max_unconfirmed_rewards: params.max_unconfirmed_rewards,
 			latest_confirmed_nonce_at_source: None,
 			target_nonces: None,
+			undelivered_nonces: VecDeque::new(),
+			unconfirmed_rewards: BTreeMap::new(),
+			last_delivery_deadline_alarm: None,
+			max_messages_in_next_batch: None,
+			single_message_rejected_at: None,
+			metrics_msg,
+			clock: Arc::new(SystemClock),
 			strategy: BasicStrategy::new(),
 		},
 	)
@@ -89,6 +119,7 @@ impl<P: MessageLane> MessageRace for MessageDeliveryRace<P> {
 struct MessageDeliveryRaceSource<P: MessageLane, C> {
 	client: C,
 	metrics_msg: Option<MessageLaneLoopMetrics>,
+	tracer: MessageTracer<P>,
 	_phantom: PhantomData<P>,
 }
 
@@ -99,7 +130,7 @@ where
 	C: MessageLaneSourceClient<P>,
 {
 	type Error = C::Error;
-	type NoncesRange = MessageWeightsMap;
+	type NoncesRange = MessageDetailsMap;
 	type ProofParameters = MessageProofParameters;
 
 	async fn nonces(
@@ -116,12 +147,32 @@ where
 		}
 
 		let new_nonces = if latest_generated_nonce > prev_latest_nonce {
-			self.client
-				.generated_messages_weights(at_block.clone(), prev_latest_nonce + 1..=latest_generated_nonce)
-				.await?
+			let new_nonces = prev_latest_nonce + 1..=latest_generated_nonce;
+			let weights = self
+				.client
+				.generated_messages_weights(at_block.clone(), new_nonces.clone())
+				.await?;
+			let rewards = self
+				.client
+				.generated_messages_rewards(at_block.clone(), new_nonces)
+				.await?;
+			weights
+				.into_iter()
+				.map(|(nonce, dispatch_weight)| {
+					let reward = rewards.get(&nonce).cloned().unwrap_or_default();
+					(
+						nonce,
+						MessageDetails {
+							dispatch_weight,
+							reward,
+						},
+					)
+				})
+				.collect()
 		} else {
-			MessageWeightsMap::new()
+			MessageDetailsMap::new()
 		};
+		self.tracer.messages_sent(&at_block, new_nonces.keys().cloned());
 
 		Ok((
 			at_block,
@@ -138,7 +189,13 @@ where
 		nonces: RangeInclusive<MessageNonce>,
 		proof_parameters: Self::ProofParameters,
 	) -> Result<(SourceHeaderIdOf<P>, RangeInclusive<MessageNonce>, P::MessagesProof), Self::Error> {
-		self.client.prove_messages(at_block, nonces, proof_parameters).await
+		let declared_dispatch_weight = proof_parameters.dispatch_weight;
+		let (at_block, nonces, proof) = self.client.prove_messages(at_block, nonces, proof_parameters).await?;
+		if let Some(metrics_msg) = self.metrics_msg.as_ref() {
+			metrics_msg.update_generated_messages_proof(P::messages_proof_size(&proof), declared_dispatch_weight);
+		}
+		self.tracer.messages_reached_stage(MessageStage::Proved, nonces.clone());
+		Ok((at_block, nonces, proof))
 	}
 }
 
@@ -146,6 +203,7 @@ where
 struct MessageDeliveryRaceTarget<P: MessageLane, C> {
 	client: C,
 	metrics_msg: Option<MessageLaneLoopMetrics>,
+	tracer: MessageTracer<P>,
 	_phantom: PhantomData<P>,
 }
 
@@ -168,6 +226,7 @@ where
 			metrics_msg.update_target_latest_received_nonce::<P>(latest_received_nonce);
 			metrics_msg.update_target_latest_confirmed_nonce::<P>(latest_confirmed_nonce);
 		}
+		self.tracer.messages_delivered(latest_received_nonce);
 
 		Ok((
 			at_block,
@@ -183,10 +242,43 @@ where
 		generated_at_block: SourceHeaderIdOf<P>,
 		nonces: RangeInclusive<MessageNonce>,
 		proof: P::MessagesProof,
-	) -> Result<RangeInclusive<MessageNonce>, Self::Error> {
-		self.client
-			.submit_messages_proof(generated_at_block, nonces, proof)
-			.await
+	) -> Result<ProofSubmission, Self::Error> {
+		let submission = self
+			.client
+			.submit_messages_proof(generated_at_block, nonces.clone(), proof)
+			.await?;
+		let (nonces, estimation) = match submission {
+			MessagesDeliverySubmission::Submitted(nonces, estimation) => (nonces, estimation),
+			MessagesDeliverySubmission::WillFail(reason) => {
+				log::warn!(
+					target: "bridge",
+					"Delivery of {} -> {} messages {:?} will fail: {}",
+					P::SOURCE_NAME,
+					P::TARGET_NAME,
+					nonces,
+					reason,
+				);
+				return Ok(ProofSubmission::Rejected(nonces));
+			}
+		};
+
+		if let Some(estimation) = estimation {
+			log::debug!(
+				target: "bridge",
+				"Estimated {} -> {} messages delivery transaction for nonces {:?}: fee {}, weight {}",
+				P::SOURCE_NAME,
+				P::TARGET_NAME,
+				nonces,
+				estimation.fee,
+				estimation.weight,
+			);
+		}
+		if let Some(metrics_msg) = self.metrics_msg.as_ref() {
+			metrics_msg.update_submitted_delivery(nonces.end() - nonces.start() + 1, estimation);
+		}
+		self.tracer
+			.messages_reached_stage(MessageStage::DeliverySubmitted, nonces.clone());
+		Ok(ProofSubmission::Submitted(nonces))
 	}
 }
 
@@ -194,29 +286,367 @@ where
 struct MessageDeliveryStrategy<P: MessageLane> {
 	/// Maximal unconfirmed nonces at target client.
 	max_unconfirmed_nonces_at_target: MessageNonce,
-	/// Maximal cumulative messages weight in the single delivery transaction.
-	max_messages_weight_in_single_batch: Weight,
+	/// Maximal weight of the single delivery transaction.
+	max_delivery_transaction_weight: Weight,
+	/// Maximal size of the messages proof.
+	max_messages_proof_size: u32,
+	/// Parameters of the delivery transaction weight formula.
+	delivery_weights: MessageLaneWeights,
+	/// Maximal acceptable delivery delay of the oldest undelivered message.
+	delivery_deadline: Option<Duration>,
+	/// Maximal total reward of messages that are delivered by this relay, but not yet confirmed.
+	max_unconfirmed_rewards: Option<u128>,
 	/// Latest confirmed nonce at the source client.
 	latest_confirmed_nonce_at_source: Option<MessageNonce>,
 	/// Target nonces from the source client.
 	target_nonces: Option<TargetClientNonces>,
+	/// Nonces that are known to the source client, but not yet delivered to the target client.
+	undelivered_nonces: VecDeque<UndeliveredNonces<SourceHeaderIdOf<P>>>,
+	/// Rewards of messages that have been selected for delivery by this relay, but delivery of
+	/// which is not yet confirmed at the source client. It is a conservative estimation - the
+	/// message could have been delivered by other relayer.
+	unconfirmed_rewards: BTreeMap<MessageNonce, u128>,
+	/// Moment when latest delivery deadline alarm has been raised.
+	last_delivery_deadline_alarm: Option<Instant>,
+	/// Maximal number of messages in the next batch. It is set when the target node rejects the
+	/// batch and is reset when new messages are delivered to the target node.
+	max_messages_in_next_batch: Option<MessageNonce>,
+	/// Moment when the target node has rejected the batch with single message.
+	single_message_rejected_at: Option<Instant>,
+	/// Message lane metrics.
+	metrics_msg: Option<MessageLaneLoopMetrics>,
+	/// Clock that is used to track delivery deadlines.
+	clock: SharedClock,
 	/// Basic delivery strategy.
 	strategy: MessageDeliveryStrategyBase<P>,
 }
 
+/// Nonces that have been seen at the source node, but are not yet delivered to the target node.
+#[derive(Debug, Clone, PartialEq)]
+struct UndeliveredNonces<SourceHeaderId> {
+	/// Range of undelivered nonces.
+	nonces: RangeInclusive<MessageNonce>,
+	/// Source header at which relay has first seen these nonces.
+	seen_at_block: SourceHeaderId,
+	/// Moment when relay has first seen these nonces.
+	seen_at: Instant,
+}
+
+/// Suspected cause of messages delivery delay.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DeliveryDelayCause {
+	/// Source header that has emitted messages is not yet finalized by the target node.
+	NoHeaders,
+	/// Target node refuses to accept new messages until delivery confirmations are relayed back
+	/// to the source node.
+	NoConfirmations,
+	/// Relay doesn't deliver new messages until its rewards for already delivered messages are
+	/// confirmed at the source node.
+	UnconfirmedRewards,
+	/// Proof has been generated, but its submission fails (e.g. because fee is too low or relayer
+	/// has no funds).
+	SubmissionFailed,
+	/// Delivery transaction of the oldest undelivered message is guaranteed to fail at the
+	/// target node, so it isn't submitted.
+	DeliveryRejected,
+	/// Delivery transaction has been submitted, but it has not been finalized yet.
+	TransactionStuck,
+	/// Nothing is obviously wrong.
+	Unknown,
+}
+
+impl std::fmt::Display for DeliveryDelayCause {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		let cause = match *self {
+			DeliveryDelayCause::NoHeaders => "source headers are not relayed to the target node",
+			DeliveryDelayCause::NoConfirmations => "delivery confirmations are not relayed to the source node",
+			DeliveryDelayCause::UnconfirmedRewards => "too many relayer rewards are not yet confirmed",
+			DeliveryDelayCause::SubmissionFailed => "delivery transaction submission fails (low fee?)",
+			DeliveryDelayCause::DeliveryRejected => "delivery transaction will fail at the target node",
+			DeliveryDelayCause::TransactionStuck => "delivery transaction is stuck",
+			DeliveryDelayCause::Unknown => "unknown",
+		};
+
+		f.write_str(cause)
+	}
+}
+
+impl<P: MessageLane> MessageDeliveryStrategy<P> {
+	/// Remember nonces that have been seen at the source node.
+	fn remember_undelivered_nonces(&mut self, at_block: &SourceHeaderIdOf<P>, new_nonces: &MessageDetailsMap) {
+		if new_nonces.is_empty() {
+			return;
+		}
+
+		let latest_known_nonce = self
+			.undelivered_nonces
+			.back()
+			.map(|undelivered| *undelivered.nonces.end())
+			.or_else(|| {
+				self.target_nonces
+					.as_ref()
+					.map(|target_nonces| target_nonces.latest_nonce)
+			})
+			.unwrap_or_default();
+		let begin = std::cmp::max(new_nonces.begin(), latest_known_nonce + 1);
+		let end = new_nonces.end();
+		if begin > end {
+			return;
+		}
+
+		self.undelivered_nonces.push_back(UndeliveredNonces {
+			nonces: begin..=end,
+			seen_at_block: at_block.clone(),
+			seen_at: self.clock.now(),
+		});
+	}
+
+	/// Forget nonces that have been delivered to the target node.
+	fn forget_delivered_nonces(&mut self, latest_received_nonce: MessageNonce) {
+		while let Some(undelivered) = self.undelivered_nonces.front_mut() {
+			if *undelivered.nonces.end() <= latest_received_nonce {
+				self.undelivered_nonces.pop_front();
+				continue;
+			}
+
+			if *undelivered.nonces.start() <= latest_received_nonce {
+				undelivered.nonces = latest_received_nonce + 1..=*undelivered.nonces.end();
+			}
+			break;
+		}
+	}
+
+	/// Forget rewards of messages, which delivery has been confirmed at the source node.
+	fn forget_confirmed_rewards(&mut self, latest_confirmed_nonce: MessageNonce) {
+		self.unconfirmed_rewards = self.unconfirmed_rewards.split_off(&(latest_confirmed_nonce + 1));
+		self.update_unconfirmed_rewards_metric();
+	}
+
+	/// Return total reward of messages, that are delivered by this relay, but not yet confirmed.
+	fn total_unconfirmed_rewards(&self) -> u128 {
+		self.unconfirmed_rewards
+			.values()
+			.fold(0u128, |total, reward| total.saturating_add(*reward))
+	}
+
+	/// Returns true if total reward of unconfirmed messages exceeds configured cap.
+	fn is_unconfirmed_rewards_cap_exceeded(&self) -> bool {
+		self.max_unconfirmed_rewards
+			.map(|max_unconfirmed_rewards| self.total_unconfirmed_rewards() > max_unconfirmed_rewards)
+			.unwrap_or(false)
+	}
+
+	/// Update unconfirmed rewards metric.
+	fn update_unconfirmed_rewards_metric(&self) {
+		if let Some(metrics_msg) = self.metrics_msg.as_ref() {
+			metrics_msg.update_unconfirmed_rewards(self.total_unconfirmed_rewards());
+		}
+	}
+
+	/// Check if the oldest undelivered message has exceeded delivery deadline and raise an alarm
+	/// if required.
+	fn check_delivery_deadline(
+		&mut self,
+		race_state: &RaceState<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::MessagesProof>,
+	) {
+		let oldest_undelivered = match self.undelivered_nonces.front() {
+			Some(oldest_undelivered) => oldest_undelivered,
+			None => {
+				if let Some(metrics_msg) = self.metrics_msg.as_ref() {
+					metrics_msg.update_oldest_undelivered_message_age(Duration::from_secs(0), false);
+				}
+				return;
+			}
+		};
+
+		let now = self.clock.now();
+		let age = now.saturating_duration_since(oldest_undelivered.seen_at);
+		let delivery_deadline = self.delivery_deadline;
+		let is_delivery_deadline_exceeded = delivery_deadline
+			.map(|delivery_deadline| age > delivery_deadline)
+			.unwrap_or(false);
+		if let Some(metrics_msg) = self.metrics_msg.as_ref() {
+			metrics_msg.update_oldest_undelivered_message_age(age, is_delivery_deadline_exceeded);
+		}
+
+		if !is_delivery_deadline_exceeded {
+			return;
+		}
+
+		let need_alarm = self
+			.last_delivery_deadline_alarm
+			.map(|last_alarm| now.saturating_duration_since(last_alarm) > DELIVERY_DEADLINE_ALARM_INTERVAL)
+			.unwrap_or(true);
+		if !need_alarm {
+			return;
+		}
+
+		let oldest_undelivered_nonces = oldest_undelivered.nonces.clone();
+		let latest_undelivered_nonce = self
+			.undelivered_nonces
+			.back()
+			.map(|undelivered| *undelivered.nonces.end())
+			.unwrap_or_else(|| *oldest_undelivered_nonces.end());
+		log::error!(
+			target: "bridge",
+			"Messages {:?} are not delivered from {} to {} for {}s (deadline is {}s). Suspected cause: {}",
+			*oldest_undelivered_nonces.start()..=latest_undelivered_nonce,
+			MessageDeliveryRace::<P>::source_name(),
+			MessageDeliveryRace::<P>::target_name(),
+			age.as_secs(),
+			delivery_deadline.map(|d| d.as_secs()).unwrap_or_default(),
+			self.delivery_delay_cause(race_state),
+		);
+		self.last_delivery_deadline_alarm = Some(now);
+	}
+
+	/// Select the last nonce of the next batch. Returns `None` if no messages may be delivered.
+	///
+	/// The batch is the longest sequence of ready messages (starting from the oldest undelivered
+	/// message) that fits into `max_nonces` messages and into the delivery transaction weight. But
+	/// if the lane is congested (i.e. not all ready messages fit into the batch), we select the
+	/// sequence with the largest reward per unit of the delivery transaction weight. So during spam
+	/// events, the batch that delivers the message with large reward isn't padded with dust messages
+	/// that follow it - they're left to the next batch. Sequences with equal reward per weight are
+	/// resolved in favor of the longer one, so dust alone is delivered at the full speed.
+	fn select_batch_end(
+		&self,
+		race_state: &RaceState<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::MessagesProof>,
+		max_nonces: MessageNonce,
+	) -> Option<MessageNonce> {
+		let mut selected_count: MessageNonce = 0;
+		let mut selected_dispatch_weight: Weight = 0;
+		let mut selected_reward: u128 = 0;
+		let mut is_congested = false;
+		// (last nonce, total reward, delivery transaction weight) of all sequences that fit the batch
+		let mut candidates = Vec::new();
+		let ready_messages = self
+			.strategy
+			.ready_source_nonces(race_state)
+			.flat_map(|range| range.iter());
+		for (nonce, details) in ready_messages {
+			// limit number of messages in the batch
+			let new_selected_count = selected_count + 1;
+			if new_selected_count > max_nonces {
+				is_congested = true;
+				break;
+			}
+
+			// limit messages in the batch by weight of the delivery transaction
+			let new_selected_dispatch_weight = match selected_dispatch_weight.checked_add(details.dispatch_weight) {
+				Some(new_selected_dispatch_weight)
+					if self.delivery_weights.is_delivery_transaction_fit(
+						self.max_delivery_transaction_weight,
+						new_selected_count as u32,
+						self.max_messages_proof_size,
+						new_selected_dispatch_weight,
+					) =>
+				{
+					new_selected_dispatch_weight
+				}
+				_ => {
+					is_congested = true;
+					break;
+				}
+			};
+
+			selected_count = new_selected_count;
+			selected_dispatch_weight = new_selected_dispatch_weight;
+			selected_reward = selected_reward.saturating_add(details.reward);
+			candidates.push((
+				*nonce,
+				selected_reward,
+				self.delivery_weights.max_receive_messages_proof(
+					selected_count as u32,
+					self.max_messages_proof_size,
+					selected_dispatch_weight,
+				),
+			));
+		}
+
+		if !is_congested {
+			return candidates.last().map(|(nonce, _, _)| *nonce);
+		}
+
+		candidates
+			.into_iter()
+			.fold(None, |best, candidate| match best {
+				Some(best) if !is_better_or_equal_reward_per_weight(&candidate, &best) => Some(best),
+				_ => Some(candidate),
+			})
+			.map(|(nonce, _, _)| nonce)
+	}
+
+	/// Guess why the oldest undelivered message is not yet delivered.
+	fn delivery_delay_cause(
+		&self,
+		race_state: &RaceState<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::MessagesProof>,
+	) -> DeliveryDelayCause {
+		if race_state.nonces_submitted.is_some() {
+			return DeliveryDelayCause::TransactionStuck;
+		}
+		if race_state.nonces_to_submit.is_some() {
+			return DeliveryDelayCause::SubmissionFailed;
+		}
+		if self.single_message_rejected_at.is_some() {
+			return DeliveryDelayCause::DeliveryRejected;
+		}
+
+		let oldest_seen_at_block = self
+			.undelivered_nonces
+			.front()
+			.map(|undelivered| &undelivered.seen_at_block);
+		let best_source_header_at_target = race_state.target_state.as_ref().map(|state| &state.best_peer);
+		match (oldest_seen_at_block, best_source_header_at_target) {
+			(Some(seen_at_block), Some(best_at_target)) if seen_at_block.0 > best_at_target.0 => {
+				return DeliveryDelayCause::NoHeaders
+			}
+			(Some(_), None) => return DeliveryDelayCause::NoHeaders,
+			_ => (),
+		}
+
+		let confirmations_missing = match (self.target_nonces.as_ref(), self.latest_confirmed_nonce_at_source) {
+			(Some(target_nonces), Some(latest_confirmed_nonce_at_source)) => target_nonces
+				.latest_nonce
+				.checked_sub(latest_confirmed_nonce_at_source)
+				.unwrap_or_default(),
+			_ => 0,
+		};
+		if confirmations_missing >= self.max_unconfirmed_nonces_at_target {
+			return DeliveryDelayCause::NoConfirmations;
+		}
+		if self.is_unconfirmed_rewards_cap_exceeded() {
+			return DeliveryDelayCause::UnconfirmedRewards;
+		}
+
+		DeliveryDelayCause::Unknown
+	}
+}
+
+/// Returns true if the `(_, reward, weight)` batch has larger (or the same) reward per unit of weight
+/// than the other batch.
+fn is_better_or_equal_reward_per_weight(
+	(_, reward, weight): &(MessageNonce, u128, Weight),
+	(_, other_reward, other_weight): &(MessageNonce, u128, Weight),
+) -> bool {
+	let weight = std::cmp::max(*weight, 1) as u128;
+	let other_weight = std::cmp::max(*other_weight, 1) as u128;
+	reward.saturating_mul(other_weight) >= other_reward.saturating_mul(weight)
+}
+
 type MessageDeliveryStrategyBase<P> = BasicStrategy<
 	<P as MessageLane>::SourceHeaderNumber,
 	<P as MessageLane>::SourceHeaderHash,
 	<P as MessageLane>::TargetHeaderNumber,
 	<P as MessageLane>::TargetHeaderHash,
-	MessageWeightsMap,
+	MessageDetailsMap,
 	<P as MessageLane>::MessagesProof,
 >;
 
 impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::MessagesProof>
 	for MessageDeliveryStrategy<P>
 {
-	type SourceNoncesRange = MessageWeightsMap;
+	type SourceNoncesRange = MessageDetailsMap;
 	type ProofParameters = MessageProofParameters;
 
 	fn is_empty(&self) -> bool {
@@ -237,6 +667,10 @@ impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::M
 		nonces: SourceClientNonces<Self::SourceNoncesRange>,
 	) {
 		self.latest_confirmed_nonce_at_source = nonces.confirmed_nonce;
+		if let Some(confirmed_nonce) = nonces.confirmed_nonce {
+			self.forget_confirmed_rewards(confirmed_nonce);
+		}
+		self.remember_undelivered_nonces(&at_block, &nonces.new_nonces);
 		self.strategy.source_nonces_updated(at_block, nonces)
 	}
 
@@ -245,10 +679,26 @@ impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::M
 		nonces: TargetClientNonces,
 		race_state: &mut RaceState<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::MessagesProof>,
 	) {
+		let is_new_message_delivered = self
+			.target_nonces
+			.as_ref()
+			.map(|target_nonces| nonces.latest_nonce > target_nonces.latest_nonce)
+			.unwrap_or(true);
+		if is_new_message_delivered {
+			self.max_messages_in_next_batch = None;
+			self.single_message_rejected_at = None;
+		}
+
+		self.forget_delivered_nonces(nonces.latest_nonce);
 		self.target_nonces = Some(nonces.clone());
 		self.strategy.target_nonces_updated(nonces, race_state)
 	}
 
+	/// Select nonces of messages to deliver in the next delivery transaction.
+	///
+	/// The inbound lane only accepts messages in nonces order, so the batch always starts with the
+	/// oldest undelivered message. When the lane is congested, the batch end is selected using
+	/// rewards of messages (see `select_batch_end`).
 	fn select_nonces_to_deliver(
 		&mut self,
 		race_state: &RaceState<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::MessagesProof>,
@@ -258,6 +708,17 @@ impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::M
 			MessageDeliveryRace(Source|Target) always fills confirmed_nonce field;\
 			qed";
 
+		self.check_delivery_deadline(race_state);
+
+		// if the target node has rejected delivery of the single message, let's wait a bit before
+		// trying again
+		if let Some(single_message_rejected_at) = self.single_message_rejected_at {
+			let rejected_ago = self.clock.now().saturating_duration_since(single_message_rejected_at);
+			if rejected_ago < REJECTED_DELIVERY_RETRY_INTERVAL {
+				return None;
+			}
+		}
+
 		let latest_confirmed_nonce_at_source = self.latest_confirmed_nonce_at_source?;
 		let target_nonces = self.target_nonces.as_ref()?;
 
@@ -291,6 +752,21 @@ impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::M
 			_ => (),
 		}
 
+		// The relayer is paid for delivery only after confirmation is delivered back to the source
+		// chain. If there are too many unpaid rewards, let's wait until confirmations are delivered.
+		if self.is_unconfirmed_rewards_cap_exceeded() {
+			log::debug!(
+				target: "bridge",
+				"Cannot deliver any more messages from {} to {}. Too many unconfirmed rewards: {}, max={:?}",
+				MessageDeliveryRace::<P>::source_name(),
+				MessageDeliveryRace::<P>::target_name(),
+				self.total_unconfirmed_rewards(),
+				self.max_unconfirmed_rewards,
+			);
+
+			return None;
+		}
+
 		// Ok - we may have new nonces to deliver. But target may still reject new messages, because we haven't
 		// notified it that (some) messages have been confirmed. So we may want to include updated
 		// `source.latest_confirmed` in the proof.
@@ -320,32 +796,27 @@ impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::M
 			.checked_sub(future_confirmed_nonce_at_target)
 			.and_then(|diff| self.max_unconfirmed_nonces_at_target.checked_sub(diff))
 			.unwrap_or_default();
-		let max_messages_weight_in_single_batch = self.max_messages_weight_in_single_batch;
+		// if the target node has rejected the previous batch, the next batch is smaller
+		let max_nonces = match self.max_messages_in_next_batch {
+			Some(max_messages_in_next_batch) => std::cmp::min(max_nonces, max_messages_in_next_batch),
+			None => max_nonces,
+		};
+		let batch_end = self.select_batch_end(race_state, max_nonces).unwrap_or_default();
 		let mut selected_weight: Weight = 0;
-		let mut selected_count: MessageNonce = 0;
+		let mut selected_rewards = Vec::new();
 
 		let selected_nonces = self
 			.strategy
 			.select_nonces_to_deliver_with_selector(race_state, |range| {
 				let to_requeue = range
 					.into_iter()
-					.skip_while(|(_, weight)| {
-						// limit messages in the batch by weight
-						let new_selected_weight = match selected_weight.checked_add(*weight) {
-							Some(new_selected_weight) if new_selected_weight <= max_messages_weight_in_single_batch => {
-								new_selected_weight
-							}
-							_ => return false,
-						};
-
-						// limit number of messages in the batch
-						let new_selected_count = selected_count + 1;
-						if new_selected_count > max_nonces {
+					.skip_while(|(nonce, details)| {
+						if *nonce > batch_end {
 							return false;
 						}
 
-						selected_weight = new_selected_weight;
-						selected_count = new_selected_count;
+						selected_weight = selected_weight.saturating_add(details.dispatch_weight);
+						selected_rewards.push((*nonce, details.reward));
 						true
 					})
 					.collect::<BTreeMap<_, _>>();
@@ -356,6 +827,9 @@ impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::M
 				}
 			})?;
 
+		self.unconfirmed_rewards.extend(selected_rewards);
+		self.update_unconfirmed_rewards_metric();
+
 		Some((
 			selected_nonces,
 			MessageProofParameters {
@@ -364,9 +838,26 @@ impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::M
 			},
 		))
 	}
+
+	fn nonces_rejected(&mut self, nonces: RangeInclusive<MessageNonce>) {
+		// the target node would reject the whole batch => let's try to deliver the first half of
+		// the batch (e.g. because it fits into the block, or the relayer may pay for it)
+		let rejected_messages = nonces.end().saturating_sub(*nonces.start()) + 1;
+		self.max_messages_in_next_batch = Some(std::cmp::max(rejected_messages / 2, 1));
+		if rejected_messages == 1 {
+			self.single_message_rejected_at = Some(self.clock.now());
+		}
+
+		for nonce in nonces.clone() {
+			self.unconfirmed_rewards.remove(&nonce);
+		}
+		self.update_unconfirmed_rewards_metric();
+
+		self.strategy.nonces_rejected(nonces);
+	}
 }
 
-impl NoncesRange for MessageWeightsMap {
+impl NoncesRange for MessageDetailsMap {
 	fn begin(&self) -> MessageNonce {
 		self.keys().next().cloned().unwrap_or_default()
 	}
@@ -388,10 +879,12 @@ impl NoncesRange for MessageWeightsMap {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::clock::Clock;
 	use crate::message_lane_loop::{
 		tests::{header_id, TestMessageLane, TestMessagesProof, TestSourceHeaderId, TestTargetHeaderId},
 		ClientState,
 	};
+	use crate::test_utils::TestClock;
 
 	type TestRaceState = RaceState<TestSourceHeaderId, TestTargetHeaderId, TestMessagesProof>;
 	type TestStrategy = MessageDeliveryStrategy<TestMessageLane>;
@@ -412,19 +905,31 @@ mod tests {
 
 		let mut race_strategy = TestStrategy {
 			max_unconfirmed_nonces_at_target: 4,
-			max_messages_weight_in_single_batch: 4,
+			max_delivery_transaction_weight: 4,
+			max_messages_proof_size: 0,
+			delivery_weights: bp_message_lane_weights::DEFAULT_MESSAGE_LANE_WEIGHTS,
+			delivery_deadline: None,
+			max_unconfirmed_rewards: None,
 			latest_confirmed_nonce_at_source: Some(19),
 			target_nonces: Some(TargetClientNonces {
 				latest_nonce: 19,
 				confirmed_nonce: Some(19),
 			}),
+			undelivered_nonces: VecDeque::new(),
+			unconfirmed_rewards: BTreeMap::new(),
+			last_delivery_deadline_alarm: None,
+			max_messages_in_next_batch: None,
+			single_message_rejected_at: None,
+			metrics_msg: None,
+			clock: Arc::new(TestClock::new()),
 			strategy: BasicStrategy::new(),
 		};
 
+		race_strategy.remember_undelivered_nonces(&header_id(1), &details_map(20..=23));
 		race_strategy.strategy.source_nonces_updated(
 			header_id(1),
 			SourceClientNonces {
-				new_nonces: vec![(20, 1), (21, 1), (22, 1), (23, 1)].into_iter().collect(),
+				new_nonces: details_map(20..=23),
 				confirmed_nonce: Some(19),
 			},
 		);
@@ -435,6 +940,46 @@ mod tests {
 		(race_state, race_strategy)
 	}
 
+	fn details_map(nonces: RangeInclusive<MessageNonce>) -> MessageDetailsMap {
+		nonces
+			.map(|nonce| {
+				(
+					nonce,
+					MessageDetails {
+						dispatch_weight: 1,
+						reward: 1,
+					},
+				)
+			})
+			.collect()
+	}
+
+	fn queue_messages_with_rewards(race_state: &mut TestRaceState, strategy: &mut TestStrategy, rewards: &[u128]) {
+		strategy.strategy = BasicStrategy::new();
+		strategy.strategy.source_nonces_updated(
+			header_id(1),
+			SourceClientNonces {
+				new_nonces: rewards
+					.iter()
+					.enumerate()
+					.map(|(index, reward)| {
+						(
+							20 + index as MessageNonce,
+							MessageDetails {
+								dispatch_weight: 1,
+								reward: *reward,
+							},
+						)
+					})
+					.collect(),
+				confirmed_nonce: Some(19),
+			},
+		);
+		strategy
+			.strategy
+			.target_nonces_updated(strategy.target_nonces.clone().unwrap(), race_state);
+	}
+
 	fn proof_parameters(state_required: bool, weight: Weight) -> MessageProofParameters {
 		MessageProofParameters {
 			outbound_state_proof_required: state_required,
@@ -443,9 +988,19 @@ mod tests {
 	}
 
 	#[test]
-	fn weights_map_works_as_nonces_range() {
-		fn build_map(range: RangeInclusive<MessageNonce>) -> MessageWeightsMap {
-			range.map(|idx| (idx, idx)).collect()
+	fn details_map_works_as_nonces_range() {
+		fn build_map(range: RangeInclusive<MessageNonce>) -> MessageDetailsMap {
+			range
+				.map(|idx| {
+					(
+						idx,
+						MessageDetails {
+							dispatch_weight: idx,
+							reward: idx as u128,
+						},
+					)
+				})
+				.collect()
 		}
 
 		let map = build_map(20..=30);
@@ -471,6 +1026,54 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn message_delivery_strategy_tracks_unconfirmed_rewards() {
+		let (mut state, mut strategy) = prepare_strategy();
+
+		// rewards of selected messages are unconfirmed
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=23), proof_parameters(false, 4)))
+		);
+		assert_eq!(strategy.total_unconfirmed_rewards(), 4);
+
+		// rewards are forgotten once delivery is confirmed at the source node
+		strategy.target_nonces_updated(
+			TargetClientNonces {
+				latest_nonce: 23,
+				confirmed_nonce: Some(19),
+			},
+			&mut state,
+		);
+		strategy.source_nonces_updated(
+			header_id(2),
+			SourceClientNonces {
+				new_nonces: MessageDetailsMap::new(),
+				confirmed_nonce: Some(21),
+			},
+		);
+		assert_eq!(strategy.total_unconfirmed_rewards(), 2);
+	}
+
+	#[test]
+	fn message_delivery_strategy_selects_nothing_if_too_many_rewards_are_unconfirmed() {
+		let (state, mut strategy) = prepare_strategy();
+
+		strategy.max_unconfirmed_rewards = Some(1);
+		strategy.unconfirmed_rewards = vec![(18, 1), (19, 1)].into_iter().collect();
+		assert_eq!(strategy.select_nonces_to_deliver(&state), None);
+		assert_eq!(
+			strategy.delivery_delay_cause(&state),
+			DeliveryDelayCause::UnconfirmedRewards
+		);
+
+		strategy.max_unconfirmed_rewards = Some(2);
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=23), proof_parameters(false, 4)))
+		);
+	}
+
 	#[test]
 	fn message_delivery_strategy_selects_nothing_if_too_many_confirmations_missing() {
 		let (state, mut strategy) = prepare_strategy();
@@ -501,7 +1104,27 @@ mod tests {
 		let (state, mut strategy) = prepare_strategy();
 
 		// not all queued messages may fit in the batch, because batch has max weight
-		strategy.max_messages_weight_in_single_batch = 3;
+		strategy.max_delivery_transaction_weight = 3;
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=22), proof_parameters(false, 3)))
+		);
+	}
+
+	#[test]
+	fn message_delivery_strategy_limits_batch_by_delivery_transaction_weight() {
+		let (state, mut strategy) = prepare_strategy();
+
+		// every message adds 1 (dispatch) + 1 (delivery) to the transaction weight and we also
+		// reserve weight for verifying the largest possible proof
+		strategy.max_delivery_transaction_weight = 10;
+		strategy.max_messages_proof_size = 2;
+		strategy.delivery_weights = MessageLaneWeights {
+			delivery_base: 2,
+			delivery_per_message: 1,
+			delivery_per_proof_byte: 1,
+			..bp_message_lane_weights::DEFAULT_MESSAGE_LANE_WEIGHTS
+		};
 		assert_eq!(
 			strategy.select_nonces_to_deliver(&state),
 			Some(((20..=22), proof_parameters(false, 3)))
@@ -522,4 +1145,218 @@ mod tests {
 			Some(((20..=22), proof_parameters(false, 3)))
 		);
 	}
+
+	#[test]
+	fn message_delivery_strategy_ignores_rewards_if_lane_is_not_congested() {
+		let (mut state, mut strategy) = prepare_strategy();
+
+		// all messages fit into the batch, so we deliver them all
+		queue_messages_with_rewards(&mut state, &mut strategy, &[100, 0, 0, 0]);
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=23), proof_parameters(false, 4)))
+		);
+	}
+
+	#[test]
+	fn message_delivery_strategy_prioritizes_batch_with_larger_reward_if_lane_is_congested() {
+		let (mut state, mut strategy) = prepare_strategy();
+
+		// only 4 messages fit into the batch and the batch `20..=21` has the largest reward per weight,
+		// so dust messages that follow are left to the next batch
+		queue_messages_with_rewards(&mut state, &mut strategy, &[1, 100, 1, 1, 1000]);
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=21), proof_parameters(false, 2)))
+		);
+		assert_eq!(strategy.total_unconfirmed_rewards(), 101);
+	}
+
+	#[test]
+	fn message_delivery_strategy_delivers_longest_batch_of_dust_if_lane_is_congested() {
+		let (mut state, mut strategy) = prepare_strategy();
+
+		// all batches have the same reward per weight, so we deliver as much as possible
+		queue_messages_with_rewards(&mut state, &mut strategy, &[0, 0, 0, 0, 0, 0]);
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=23), proof_parameters(false, 4)))
+		);
+	}
+
+	#[test]
+	fn message_delivery_strategy_tracks_undelivered_nonces() {
+		let (_, mut strategy) = prepare_strategy();
+		assert_eq!(
+			strategy
+				.undelivered_nonces
+				.iter()
+				.map(|undelivered| undelivered.nonces.clone())
+				.collect::<Vec<_>>(),
+			vec![20..=23],
+		);
+
+		// already known nonces are not remembered twice
+		strategy.remember_undelivered_nonces(&header_id(2), &details_map(22..=24));
+		assert_eq!(
+			strategy
+				.undelivered_nonces
+				.iter()
+				.map(|undelivered| (undelivered.nonces.clone(), undelivered.seen_at_block))
+				.collect::<Vec<_>>(),
+			vec![(20..=23, header_id(1)), (24..=24, header_id(2))],
+		);
+
+		// delivered nonces are forgotten
+		strategy.forget_delivered_nonces(21);
+		assert_eq!(
+			strategy
+				.undelivered_nonces
+				.iter()
+				.map(|undelivered| undelivered.nonces.clone())
+				.collect::<Vec<_>>(),
+			vec![22..=23, 24..=24],
+		);
+		strategy.forget_delivered_nonces(24);
+		assert!(strategy.undelivered_nonces.is_empty());
+	}
+
+	#[test]
+	fn message_delivery_strategy_guesses_delivery_delay_cause() {
+		let (mut state, mut strategy) = prepare_strategy();
+		assert_eq!(strategy.delivery_delay_cause(&state), DeliveryDelayCause::Unknown);
+
+		// messages are generated at the header that is not yet known to the target node
+		strategy.undelivered_nonces[0].seen_at_block = header_id(2);
+		assert_eq!(strategy.delivery_delay_cause(&state), DeliveryDelayCause::NoHeaders);
+		strategy.undelivered_nonces[0].seen_at_block = header_id(1);
+
+		// too many confirmations are missing
+		strategy.latest_confirmed_nonce_at_source =
+			Some(strategy.target_nonces.as_ref().unwrap().latest_nonce - strategy.max_unconfirmed_nonces_at_target);
+		assert_eq!(
+			strategy.delivery_delay_cause(&state),
+			DeliveryDelayCause::NoConfirmations
+		);
+
+		// proof is generated, but not yet submitted
+		state.nonces_to_submit = Some((header_id(1), 20..=23, (20..=23, None)));
+		assert_eq!(
+			strategy.delivery_delay_cause(&state),
+			DeliveryDelayCause::SubmissionFailed
+		);
+
+		// proof is submitted, but transaction is not yet finalized
+		state.nonces_to_submit = None;
+		state.nonces_submitted = Some(20..=23);
+		assert_eq!(
+			strategy.delivery_delay_cause(&state),
+			DeliveryDelayCause::TransactionStuck
+		);
+	}
+
+	#[test]
+	fn message_delivery_strategy_raises_delivery_deadline_alarm() {
+		let (state, mut strategy) = prepare_strategy();
+		let clock = TestClock::new();
+		strategy.clock = Arc::new(clock.clone());
+		strategy.undelivered_nonces[0].seen_at = clock.now();
+
+		// deadline is not exceeded
+		strategy.delivery_deadline = Some(Duration::from_secs(600));
+		clock.advance(Duration::from_secs(600));
+		strategy.check_delivery_deadline(&state);
+		assert_eq!(strategy.last_delivery_deadline_alarm, None);
+
+		// deadline is exceeded
+		clock.advance(Duration::from_secs(1));
+		strategy.check_delivery_deadline(&state);
+		assert_eq!(strategy.last_delivery_deadline_alarm, Some(clock.now()));
+	}
+
+	#[test]
+	fn message_delivery_strategy_repeats_delivery_deadline_alarm_after_interval() {
+		let (state, mut strategy) = prepare_strategy();
+		let clock = TestClock::new();
+		strategy.clock = Arc::new(clock.clone());
+		strategy.undelivered_nonces[0].seen_at = clock.now();
+		strategy.delivery_deadline = Some(Duration::from_secs(0));
+
+		clock.advance(Duration::from_secs(1));
+		strategy.check_delivery_deadline(&state);
+		let first_alarm = clock.now();
+		assert_eq!(strategy.last_delivery_deadline_alarm, Some(first_alarm));
+
+		// alarm is not raised again until the interval has passed
+		clock.advance(DELIVERY_DEADLINE_ALARM_INTERVAL);
+		strategy.check_delivery_deadline(&state);
+		assert_eq!(strategy.last_delivery_deadline_alarm, Some(first_alarm));
+
+		clock.advance(Duration::from_secs(1));
+		strategy.check_delivery_deadline(&state);
+		assert_eq!(strategy.last_delivery_deadline_alarm, Some(clock.now()));
+	}
+
+	#[test]
+	fn message_delivery_strategy_shrinks_rejected_batch() {
+		let (state, mut strategy) = prepare_strategy();
+		let clock = TestClock::new();
+		strategy.clock = Arc::new(clock.clone());
+
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=23), proof_parameters(false, 4)))
+		);
+		assert_eq!(strategy.total_unconfirmed_rewards(), 4);
+
+		// rejected messages are selected again, but in smaller batch
+		strategy.nonces_rejected(20..=23);
+		assert_eq!(strategy.total_unconfirmed_rewards(), 0);
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=21), proof_parameters(false, 2)))
+		);
+		strategy.nonces_rejected(20..=21);
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=20), proof_parameters(false, 1)))
+		);
+
+		// if even single message is rejected, we retry after some time
+		strategy.nonces_rejected(20..=20);
+		assert_eq!(strategy.select_nonces_to_deliver(&state), None);
+		assert_eq!(
+			strategy.delivery_delay_cause(&state),
+			DeliveryDelayCause::DeliveryRejected
+		);
+		clock.advance(REJECTED_DELIVERY_RETRY_INTERVAL);
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=20), proof_parameters(false, 1)))
+		);
+	}
+
+	#[test]
+	fn message_delivery_strategy_resets_batch_limit_when_messages_are_delivered() {
+		let (mut state, mut strategy) = prepare_strategy();
+
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=23), proof_parameters(false, 4)))
+		);
+		strategy.nonces_rejected(20..=23);
+
+		// message has been delivered (e.g. by other relayer) => the limit is reset
+		strategy.target_nonces_updated(
+			TargetClientNonces {
+				latest_nonce: 20,
+				confirmed_nonce: Some(19),
+			},
+			&mut state,
+		);
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((21..=23), proof_parameters(false, 3)))
+		);
+	}
 }
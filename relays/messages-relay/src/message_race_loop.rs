@@ -60,7 +60,7 @@ type SourceClientState<P> = ClientState<<P as MessageRace>::SourceHeaderId, <P a
 type TargetClientState<P> = ClientState<<P as MessageRace>::TargetHeaderId, <P as MessageRace>::SourceHeaderId>;
 
 /// Inclusive nonces range.
-pub trait NoncesRange: Debug + Sized {
+pub trait NoncesRange: Debug + Clone + Sized {
 	/// Get begin of the range.
 	fn begin(&self) -> MessageNonce;
 	/// Get end of the range.
@@ -131,7 +131,16 @@ pub trait TargetClient<P: MessageRace> {
 		generated_at_block: P::SourceHeaderId,
 		nonces: RangeInclusive<MessageNonce>,
 		proof: P::Proof,
-	) -> Result<RangeInclusive<MessageNonce>, Self::Error>;
+	) -> Result<ProofSubmission, Self::Error>;
+}
+
+/// Result of the proof submission.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProofSubmission {
+	/// Proof of given nonces has been submitted to the target node.
+	Submitted(RangeInclusive<MessageNonce>),
+	/// Proof of given nonces has not been submitted, because the target node would reject it.
+	Rejected(RangeInclusive<MessageNonce>),
 }
 
 /// Race strategy.
@@ -163,6 +172,9 @@ pub trait RaceStrategy<SourceHeaderId, TargetHeaderId, Proof> {
 		&mut self,
 		race_state: &RaceState<SourceHeaderId, TargetHeaderId, Proof>,
 	) -> Option<(RangeInclusive<MessageNonce>, Self::ProofParameters)>;
+	/// Called when the target node has rejected proof of `nonces`, selected by the latest
+	/// `select_nonces_to_deliver` call. These nonces must be selected again later.
+	fn nonces_rejected(&mut self, nonces: RangeInclusive<MessageNonce>);
 }
 
 /// State of the race.
@@ -310,16 +322,29 @@ pub async fn run<P: MessageRace, SC: SourceClient<P>>(
 				target_client_is_online = process_future_result(
 					proof_submit_result,
 					&mut target_retry_backoff,
-					|nonces_range| {
-						log::debug!(
-							target: "bridge",
-							"Successfully submitted proof of nonces {:?} to {}",
-							nonces_range,
-							P::target_name(),
-						);
-
-						race_state.nonces_to_submit = None;
-						race_state.nonces_submitted = Some(nonces_range);
+					|submission| match submission {
+						ProofSubmission::Submitted(nonces_range) => {
+							log::debug!(
+								target: "bridge",
+								"Successfully submitted proof of nonces {:?} to {}",
+								nonces_range,
+								P::target_name(),
+							);
+
+							race_state.nonces_to_submit = None;
+							race_state.nonces_submitted = Some(nonces_range);
+						}
+						ProofSubmission::Rejected(nonces_range) => {
+							log::debug!(
+								target: "bridge",
+								"Proof of nonces {:?} has been rejected by {}",
+								nonces_range,
+								P::target_name(),
+							);
+
+							race_state.nonces_to_submit = None;
+							strategy.nonces_rejected(nonces_range);
+						}
 					},
 					&mut target_go_offline_future,
 					|delay| async_std::task::sleep(delay),
@@ -19,9 +19,10 @@ use crate::message_lane_loop::{
 	TargetClientState,
 };
 use crate::message_race_loop::{
-	MessageRace, NoncesRange, SourceClient, SourceClientNonces, TargetClient, TargetClientNonces,
+	MessageRace, NoncesRange, ProofSubmission, SourceClient, SourceClientNonces, TargetClient, TargetClientNonces,
 };
 use crate::message_race_strategy::BasicStrategy;
+use crate::message_trace::MessageTracer;
 use crate::metrics::MessageLaneLoopMetrics;
 
 use async_trait::async_trait;
@@ -48,6 +49,7 @@ pub async fn run<P: MessageLane>(
 	target_state_updates: impl FusedStream<Item = TargetClientState<P>>,
 	stall_timeout: Duration,
 	metrics_msg: Option<MessageLaneLoopMetrics>,
+	tracer: MessageTracer<P>,
 ) -> Result<(), FailedClient> {
 	crate::message_race_loop::run(
 		ReceivingConfirmationsRaceSource {
@@ -59,6 +61,7 @@ pub async fn run<P: MessageLane>(
 		ReceivingConfirmationsRaceTarget {
 			client: source_client,
 			metrics_msg,
+			tracer,
 			_phantom: Default::default(),
 		},
 		source_state_updates,
@@ -147,6 +150,7 @@ where
 struct ReceivingConfirmationsRaceTarget<P: MessageLane, C> {
 	client: C,
 	metrics_msg: Option<MessageLaneLoopMetrics>,
+	tracer: MessageTracer<P>,
 	_phantom: PhantomData<P>,
 }
 
@@ -166,6 +170,7 @@ where
 		if let Some(metrics_msg) = self.metrics_msg.as_ref() {
 			metrics_msg.update_source_latest_confirmed_nonce::<P>(latest_confirmed_nonce);
 		}
+		self.tracer.messages_confirmed(latest_confirmed_nonce);
 		Ok((
 			at_block,
 			TargetClientNonces {
@@ -180,11 +185,11 @@ where
 		generated_at_block: TargetHeaderIdOf<P>,
 		nonces: RangeInclusive<MessageNonce>,
 		proof: P::MessagesReceivingProof,
-	) -> Result<RangeInclusive<MessageNonce>, Self::Error> {
+	) -> Result<ProofSubmission, Self::Error> {
 		self.client
 			.submit_messages_receiving_proof(generated_at_block, proof)
 			.await?;
-		Ok(nonces)
+		Ok(ProofSubmission::Submitted(nonces))
 	}
 }
 
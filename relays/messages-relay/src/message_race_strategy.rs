@@ -37,6 +37,9 @@ pub struct BasicStrategy<
 	source_queue: VecDeque<(HeaderId<SourceHeaderHash, SourceHeaderNumber>, SourceNoncesRange)>,
 	/// Best nonce known to target node.
 	target_nonce: MessageNonce,
+	/// Queued ranges that have been (partially) selected by the latest nonces selection. They're
+	/// returned to the queue if the target node rejects selected nonces.
+	selected_ranges: Vec<(HeaderId<SourceHeaderHash, SourceHeaderNumber>, SourceNoncesRange)>,
 	/// Unused generic types dump.
 	_phantom: PhantomData<(TargetHeaderNumber, TargetHeaderHash, Proof)>,
 }
@@ -53,6 +56,7 @@ where
 		BasicStrategy {
 			source_queue: VecDeque::new(),
 			target_nonce: Default::default(),
+			selected_ranges: Vec::new(),
 			_phantom: Default::default(),
 		}
 	}
@@ -90,6 +94,7 @@ where
 		// 3) selector is used for more complicated logic
 		let best_header_at_target = &race_state.target_state.as_ref()?.best_peer;
 		let mut nonces_end = None;
+		self.selected_ranges.clear();
 
 		while let Some((queued_at, queued_range)) = self.source_queue.pop_front() {
 			// select (sub) range to deliver
@@ -101,6 +106,7 @@ where
 				Some(queued_range)
 			} else {
 				// selector returns `Some(range)` if this `range` needs to be requeued
+				self.selected_ranges.push((queued_at.clone(), queued_range.clone()));
 				selector(queued_range)
 			};
 
@@ -119,6 +125,9 @@ where
 
 					if range_to_requeue.begin() != queued_range_begin {
 						nonces_end = Some(range_to_requeue.begin() - 1);
+					} else if queued_at.0 <= best_header_at_target.0 {
+						// nothing has been selected from this range
+						self.selected_ranges.pop();
 					}
 					self.source_queue.push_front((queued_at, range_to_requeue));
 					break;
@@ -131,6 +140,52 @@ where
 
 		nonces_end.map(|nonces_end| RangeInclusive::new(self.target_nonce + 1, nonces_end))
 	}
+
+	/// Returns queued ranges of nonces that may be delivered right now, i.e. that have been
+	/// generated at source headers that are already known to the target node.
+	pub fn ready_source_nonces<'a>(
+		&'a self,
+		race_state: &RaceState<
+			HeaderId<SourceHeaderHash, SourceHeaderNumber>,
+			HeaderId<TargetHeaderHash, TargetHeaderNumber>,
+			Proof,
+		>,
+	) -> impl Iterator<Item = &'a SourceNoncesRange> + 'a {
+		let best_header_at_target = race_state.target_state.as_ref().map(|state| state.best_peer.0.clone());
+		self.source_queue
+			.iter()
+			.take_while(move |(queued_at, _)| {
+				best_header_at_target
+					.as_ref()
+					.map(|best_header_at_target| queued_at.0 <= *best_header_at_target)
+					.unwrap_or(false)
+			})
+			.map(|(_, range)| range)
+	}
+
+	/// Return nonces that have been selected by the latest nonces selection back to the queue.
+	pub fn requeue_selected_nonces(&mut self) {
+		let selected_end = match self.selected_ranges.last() {
+			Some((_, range)) => range.end(),
+			None => return,
+		};
+
+		// the non-selected part of the last selected range is still in the queue
+		while let Some(true) = self
+			.source_queue
+			.front()
+			.map(|(_, range)| range.begin() <= selected_end)
+		{
+			self.source_queue.pop_front();
+		}
+
+		let target_nonce = self.target_nonce;
+		for (queued_at, range) in self.selected_ranges.drain(..).rev() {
+			if let Some(range) = range.greater_than(target_nonce) {
+				self.source_queue.push_front((queued_at, range));
+			}
+		}
+	}
 }
 
 impl<SourceHeaderNumber, SourceHeaderHash, TargetHeaderNumber, TargetHeaderHash, SourceNoncesRange, Proof>
@@ -235,6 +290,10 @@ where
 		self.select_nonces_to_deliver_with_selector(race_state, |_| None)
 			.map(|range| (range, ()))
 	}
+
+	fn nonces_rejected(&mut self, _nonces: RangeInclusive<MessageNonce>) {
+		self.requeue_selected_nonces();
+	}
 }
 
 #[cfg(test)]
@@ -410,6 +469,55 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn rejected_nonces_are_returned_to_the_queue() {
+		let mut state = RaceState::<_, _, TestMessagesProof>::default();
+		let mut strategy = BasicStrategy::<TestMessageLane>::new();
+		strategy.source_nonces_updated(header_id(1), source_nonces(1..=10));
+		strategy.source_nonces_updated(header_id(2), source_nonces(11..=20));
+		strategy.source_nonces_updated(header_id(3), source_nonces(21..=30));
+
+		state.target_state = Some(ClientState {
+			best_self: header_id(0),
+			best_peer: header_id(3),
+		});
+		assert_eq!(
+			strategy.select_nonces_to_deliver_with_selector(&state, |range| range.greater_than(15)),
+			Some(1..=15),
+		);
+		assert_eq!(
+			strategy.source_queue,
+			vec![(header_id(2), 16..=20), (header_id(3), 21..=30)]
+		);
+
+		strategy.nonces_rejected(1..=15);
+		assert_eq!(
+			strategy.source_queue,
+			vec![(header_id(1), 1..=10), (header_id(2), 11..=20), (header_id(3), 21..=30)]
+		);
+		assert_eq!(
+			strategy.select_nonces_to_deliver_with_selector(&state, |range| range.greater_than(5)),
+			Some(1..=5),
+		);
+	}
+
+	#[test]
+	fn rejected_nonces_that_are_already_delivered_are_not_returned_to_the_queue() {
+		let mut state = RaceState::<_, _, TestMessagesProof>::default();
+		let mut strategy = BasicStrategy::<TestMessageLane>::new();
+		strategy.source_nonces_updated(header_id(1), source_nonces(1..=10));
+
+		state.target_state = Some(ClientState {
+			best_self: header_id(0),
+			best_peer: header_id(1),
+		});
+		assert_eq!(strategy.select_nonces_to_deliver(&state), Some((1..=10, ())));
+
+		strategy.target_nonces_updated(target_nonces(5), &mut state);
+		strategy.nonces_rejected(1..=10);
+		assert_eq!(strategy.source_queue, vec![(header_id(1), 6..=10)]);
+	}
+
 	fn run_panic_test_for_incorrect_selector(
 		invalid_selector: impl Fn(SourceNoncesRange) -> Option<SourceNoncesRange>,
 	) {
@@ -0,0 +1,394 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tracing of relayed messages.
+//!
+//! Every message that is relayed by the loop gets the trace id, which is built from the lane id,
+//! the message nonce and the hash of the source header where the relay has first seen the
+//! message. The relay doesn't know the header where message has actually been sent, so if the
+//! relay has been started after the message has been sent, the hash points to a later header.
+//! The `trace-message` command of the relay binary may be used to find the actual header.
+//!
+//! The trace id is logged at every stage of the message lifecycle that the relay observes. If
+//! the OpenTelemetry collector endpoint is configured, every message, which delivery is confirmed,
+//! is also exported to the collector as a single span, with stages exported as span events.
+//! The OpenTelemetry trace id is built from the lane id and the message nonce, so the trace
+//! may be found without knowing the source header hash.
+
+use crate::message_lane::{MessageLane, SourceHeaderIdOf};
+use crate::metrics::MessageLaneLoopMetrics;
+
+use bp_message_lane::{LaneId, MessageNonce};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::{
+	collections::BTreeMap,
+	fmt,
+	ops::RangeInclusive,
+	sync::Arc,
+	time::{Duration, SystemTime},
+};
+
+/// Path of the traces endpoint, relative to the OpenTelemetry collector endpoint.
+const OTLP_TRACES_PATH: &str = "/v1/traces";
+
+/// Message trace id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageTraceId<SourceHeaderHash> {
+	/// Lane that the message has been sent over.
+	pub lane: LaneId,
+	/// Message nonce.
+	pub nonce: MessageNonce,
+	/// Hash of the source header where the message has been sent (or first seen by the relay).
+	pub source_block_hash: SourceHeaderHash,
+}
+
+/// Trace id is displayed as `<lane>:<nonce>@<source block hash>`.
+impl<SourceHeaderHash: fmt::Debug> fmt::Display for MessageTraceId<SourceHeaderHash> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}:{}@{:?}", self.lane, self.nonce, self.source_block_hash)
+	}
+}
+
+/// Stage of the message lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MessageStage {
+	/// Message has been sent at the source chain.
+	Sent,
+	/// Message may be proved to the target chain: the source header with the message is finalized
+	/// by the target chain and the relay has generated the message proof at this header.
+	Proved,
+	/// Transaction with the message proof has been submitted to the target chain.
+	DeliverySubmitted,
+	/// Message has been received by the target chain.
+	Delivered,
+	/// Message has been dispatched by the target chain. This stage isn't observed by the relay.
+	Dispatched,
+	/// Message delivery has been confirmed at the source chain.
+	Confirmed,
+}
+
+impl MessageStage {
+	/// Return name of the stage.
+	pub fn as_str(&self) -> &'static str {
+		match *self {
+			MessageStage::Sent => "sent",
+			MessageStage::Proved => "proved",
+			MessageStage::DeliverySubmitted => "delivery_submitted",
+			MessageStage::Delivered => "delivered",
+			MessageStage::Dispatched => "dispatched",
+			MessageStage::Confirmed => "confirmed",
+		}
+	}
+}
+
+impl fmt::Display for MessageStage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// Stages of single message, observed by the relay.
+#[derive(Debug, Clone, PartialEq)]
+struct MessageTrace<SourceHeaderHash> {
+	/// Message trace id.
+	id: MessageTraceId<SourceHeaderHash>,
+	/// Observed stages and moments when they have been observed.
+	stages: Vec<(MessageStage, SystemTime)>,
+}
+
+/// Tracer of messages, relayed by the message lane loop.
+///
+/// Cloning only clones references.
+#[derive(Clone)]
+pub(crate) struct MessageTracer<P: MessageLane> {
+	/// Lane that is served by the loop.
+	lane: LaneId,
+	/// Traces of messages, which delivery is not yet confirmed.
+	traces: Arc<Mutex<BTreeMap<MessageNonce, MessageTrace<P::SourceHeaderHash>>>>,
+	/// OpenTelemetry collector endpoint.
+	otlp_endpoint: Option<Arc<String>>,
+	/// Message lane metrics.
+	metrics_msg: Option<MessageLaneLoopMetrics>,
+}
+
+impl<P: MessageLane> MessageTracer<P> {
+	/// Create new tracer.
+	pub fn new(lane: LaneId, otlp_endpoint: Option<String>, metrics_msg: Option<MessageLaneLoopMetrics>) -> Self {
+		MessageTracer {
+			lane,
+			traces: Arc::new(Mutex::new(BTreeMap::new())),
+			otlp_endpoint: otlp_endpoint.map(Arc::new),
+			metrics_msg,
+		}
+	}
+
+	/// Start tracing messages that have been seen at given source header.
+	pub fn messages_sent(&self, at_block: &SourceHeaderIdOf<P>, nonces: impl IntoIterator<Item = MessageNonce>) {
+		let now = SystemTime::now();
+		let mut traces = self.traces.lock();
+		for nonce in nonces {
+			if traces.contains_key(&nonce) {
+				continue;
+			}
+
+			let trace = MessageTrace {
+				id: MessageTraceId {
+					lane: self.lane,
+					nonce,
+					source_block_hash: at_block.1.clone(),
+				},
+				stages: vec![(MessageStage::Sent, now)],
+			};
+			self.log_stage(&trace.id, MessageStage::Sent);
+			traces.insert(nonce, trace);
+		}
+	}
+
+	/// Remember that messages with given nonces have reached given stage.
+	pub fn messages_reached_stage(&self, stage: MessageStage, nonces: RangeInclusive<MessageNonce>) {
+		let now = SystemTime::now();
+		let mut traces = self.traces.lock();
+		for (_, trace) in traces.range_mut(nonces) {
+			if trace.stages.iter().all(|(known_stage, _)| *known_stage != stage) {
+				self.log_stage(&trace.id, stage);
+				trace.stages.push((stage, now));
+			}
+		}
+	}
+
+	/// Remember that all messages up to given nonce have been delivered to the target node.
+	pub fn messages_delivered(&self, latest_received_nonce: MessageNonce) {
+		self.messages_reached_stage(MessageStage::Delivered, 0..=latest_received_nonce);
+	}
+
+	/// Remember that delivery of all messages up to given nonce has been confirmed and stop
+	/// tracing these messages.
+	pub fn messages_confirmed(&self, latest_confirmed_nonce: MessageNonce) {
+		self.messages_reached_stage(MessageStage::Confirmed, 0..=latest_confirmed_nonce);
+
+		let confirmed_traces = {
+			let mut traces = self.traces.lock();
+			let unconfirmed_traces = traces.split_off(&latest_confirmed_nonce.saturating_add(1));
+			std::mem::replace(&mut *traces, unconfirmed_traces)
+		};
+		if confirmed_traces.is_empty() {
+			return;
+		}
+
+		if let Some(ref otlp_endpoint) = self.otlp_endpoint {
+			let payload = encode_otlp_traces::<P>(confirmed_traces.values());
+			let otlp_endpoint = otlp_endpoint.clone();
+			async_std::task::spawn(async move {
+				if let Err(error) = post_json(&otlp_endpoint, &payload).await {
+					log::warn!(target: "bridge", "Failed to export message traces: {}", error);
+				}
+			});
+		}
+	}
+
+	/// Log message stage and update metrics.
+	fn log_stage(&self, id: &MessageTraceId<P::SourceHeaderHash>, stage: MessageStage) {
+		log::debug!(
+			target: "bridge",
+			"{} -> {} message has reached stage {}: trace_id={}",
+			P::SOURCE_NAME,
+			P::TARGET_NAME,
+			stage,
+			id,
+		);
+
+		if let Some(metrics_msg) = self.metrics_msg.as_ref() {
+			metrics_msg.update_traced_message_nonce(stage, id.nonce);
+		}
+	}
+}
+
+/// Encode message traces using OpenTelemetry protocol (JSON encoding).
+fn encode_otlp_traces<'a, P: MessageLane>(traces: impl Iterator<Item = &'a MessageTrace<P::SourceHeaderHash>>) -> Value
+where
+	P::SourceHeaderHash: 'a,
+{
+	let spans = traces
+		.map(|trace| {
+			let start = trace.stages.iter().map(|(_, time)| *time).min();
+			let end = trace.stages.iter().map(|(_, time)| *time).max();
+			json!({
+				"traceId": otlp_trace_id(trace.id.lane, trace.id.nonce),
+				"spanId": hex::encode(trace.id.nonce.to_be_bytes()),
+				"name": format!("{} -> {} message", P::SOURCE_NAME, P::TARGET_NAME),
+				"kind": 1,
+				"startTimeUnixNano": encode_time(start),
+				"endTimeUnixNano": encode_time(end),
+				"attributes": [
+					encode_attribute("bridge.message.trace_id", trace.id.to_string()),
+					encode_attribute("bridge.message.lane", trace.id.lane.to_string()),
+					encode_attribute("bridge.message.nonce", trace.id.nonce.to_string()),
+					encode_attribute("bridge.message.source_block_hash", format!("{:?}", trace.id.source_block_hash)),
+				],
+				"events": trace.stages.iter().map(|(stage, time)| json!({
+					"timeUnixNano": encode_time(Some(*time)),
+					"name": stage.as_str(),
+				})).collect::<Vec<_>>(),
+			})
+		})
+		.collect::<Vec<_>>();
+
+	json!({
+		"resourceSpans": [{
+			"resource": {
+				"attributes": [
+					encode_attribute("service.name", format!("{}-to-{}-messages-relay", P::SOURCE_NAME, P::TARGET_NAME)),
+				],
+			},
+			"instrumentationLibrarySpans": [{
+				"instrumentationLibrary": { "name": "messages-relay" },
+				"spans": spans,
+			}],
+		}],
+	})
+}
+
+/// OpenTelemetry trace id of the message: lane id, followed by big-endian nonce and zero padding.
+fn otlp_trace_id(lane: LaneId, nonce: MessageNonce) -> String {
+	let mut trace_id = [0u8; 16];
+	trace_id[..4].copy_from_slice(&lane.to_bytes());
+	trace_id[4..12].copy_from_slice(&nonce.to_be_bytes());
+	hex::encode(trace_id)
+}
+
+/// Encode OpenTelemetry string attribute.
+fn encode_attribute(key: &str, value: String) -> Value {
+	json!({ "key": key, "value": { "stringValue": value } })
+}
+
+/// Encode time as a number of nanoseconds since UNIX epoch.
+fn encode_time(time: Option<SystemTime>) -> String {
+	time.and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+		.unwrap_or_else(|| Duration::from_secs(0))
+		.as_nanos()
+		.to_string()
+}
+
+/// Send JSON-encoded traces to the OpenTelemetry collector.
+async fn post_json(otlp_endpoint: &str, payload: &Value) -> Result<(), String> {
+	let url = format!("{}{}", otlp_endpoint.trim_end_matches('/'), OTLP_TRACES_PATH);
+	let body = surf::Body::from_json(payload).map_err(|e| format!("Failed to encode traces: {}", e))?;
+	let response = surf::post(&url)
+		.body(body)
+		.await
+		.map_err(|e| format!("Failed to push traces to {}: {}", url, e))?;
+	if !response.status().is_success() {
+		return Err(format!("Failed to push traces to {}: {}", url, response.status()));
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::message_lane_loop::tests::TestMessageLane;
+	use bp_message_lane::LaneNamespace;
+	use relay_utils::HeaderId;
+
+	fn tracer() -> MessageTracer<TestMessageLane> {
+		MessageTracer::new(LaneId::new(LaneNamespace::LEGACY, 1), None, None)
+	}
+
+	fn stages(tracer: &MessageTracer<TestMessageLane>, nonce: MessageNonce) -> Vec<MessageStage> {
+		tracer.traces.lock()[&nonce]
+			.stages
+			.iter()
+			.map(|(stage, _)| *stage)
+			.collect()
+	}
+
+	#[test]
+	fn message_trace_id_is_displayed() {
+		let id = MessageTraceId {
+			lane: LaneId::new(LaneNamespace::TOKEN, 1),
+			nonce: 42,
+			source_block_hash: 7u64,
+		};
+		assert_eq!(id.to_string(), "746b0001:42@7");
+	}
+
+	#[test]
+	fn tracer_records_every_stage_once() {
+		let tracer = tracer();
+		tracer.messages_sent(&HeaderId(1, 1), 1..=2);
+		tracer.messages_sent(&HeaderId(2, 2), 2..=3);
+		tracer.messages_reached_stage(MessageStage::Proved, 1..=3);
+		tracer.messages_reached_stage(MessageStage::Proved, 1..=3);
+		tracer.messages_reached_stage(MessageStage::DeliverySubmitted, 1..=3);
+		tracer.messages_delivered(2);
+
+		assert_eq!(tracer.traces.lock()[&2].id.source_block_hash, 1);
+		assert_eq!(tracer.traces.lock()[&3].id.source_block_hash, 2);
+		assert_eq!(
+			stages(&tracer, 2),
+			vec![
+				MessageStage::Sent,
+				MessageStage::Proved,
+				MessageStage::DeliverySubmitted,
+				MessageStage::Delivered,
+			],
+		);
+		assert_eq!(
+			stages(&tracer, 3),
+			vec![
+				MessageStage::Sent,
+				MessageStage::Proved,
+				MessageStage::DeliverySubmitted
+			],
+		);
+	}
+
+	#[test]
+	fn tracer_forgets_confirmed_messages() {
+		let tracer = tracer();
+		tracer.messages_sent(&HeaderId(1, 1), 1..=3);
+		tracer.messages_delivered(3);
+		tracer.messages_confirmed(2);
+
+		assert_eq!(tracer.traces.lock().keys().cloned().collect::<Vec<_>>(), vec![3]);
+	}
+
+	#[test]
+	fn message_traces_are_encoded() {
+		let trace = MessageTrace {
+			id: MessageTraceId {
+				lane: LaneId::new(LaneNamespace::LEGACY, 1),
+				nonce: 258,
+				source_block_hash: 7u64,
+			},
+			stages: vec![
+				(MessageStage::Sent, SystemTime::UNIX_EPOCH + Duration::from_secs(1)),
+				(MessageStage::Confirmed, SystemTime::UNIX_EPOCH + Duration::from_secs(3)),
+			],
+		};
+		let payload = encode_otlp_traces::<TestMessageLane>(std::iter::once(&trace));
+		let span = &payload["resourceSpans"][0]["instrumentationLibrarySpans"][0]["spans"][0];
+
+		assert_eq!(span["traceId"], "00000001000000000000010200000000");
+		assert_eq!(span["spanId"], "0000000000000102");
+		assert_eq!(span["startTimeUnixNano"], "1000000000");
+		assert_eq!(span["endTimeUnixNano"], "3000000000");
+		assert_eq!(span["events"][1]["name"], "confirmed");
+		assert_eq!(span["attributes"][0]["value"]["stringValue"], "00000001:258@7");
+	}
+}
@@ -17,10 +17,15 @@
 //! Metrics for message lane relay loop.
 
 use crate::message_lane::MessageLane;
-use crate::message_lane_loop::{SourceClientState, TargetClientState};
+use crate::message_lane_loop::{MessagesDeliveryEstimation, SourceClientState, TargetClientState};
+use crate::message_trace::MessageStage;
 
-use bp_message_lane::MessageNonce;
-use relay_utils::metrics::{register, GaugeVec, Metrics, Opts, Registry, U64};
+use bp_message_lane::{MessageNonce, Weight};
+use relay_utils::metrics::{
+	exponential_buckets, register, Gauge, GaugeVec, Histogram, HistogramOpts, HistogramVec, Metrics, Opts, Registry,
+	F64, U64,
+};
+use std::time::Duration;
 
 /// Message lane relay metrics.
 ///
@@ -32,12 +37,40 @@ pub struct MessageLaneLoopMetrics {
 	/// Lane state nonces: "source_latest_generated", "source_latest_confirmed",
 	/// "target_latest_received", "target_latest_confirmed".
 	lane_state_nonces: GaugeVec<U64>,
+	/// Age (in seconds) of the oldest message that is not yet delivered to the target node.
+	oldest_undelivered_message_age: Gauge<U64>,
+	/// Set to 1 if the oldest undelivered message has exceeded the delivery deadline, 0 otherwise.
+	delivery_deadline_exceeded: Gauge<U64>,
+	/// Estimation of the latest messages delivery transaction: "fee", "weight".
+	delivery_transaction_estimation: GaugeVec<F64>,
+	/// Total reward of messages that have been delivered by this relay, but delivery of which is
+	/// not yet confirmed at the source node.
+	unconfirmed_rewards: Gauge<F64>,
+	/// Nonce of the latest traced message that has reached given stage. The Prometheus endpoint
+	/// doesn't support exemplars, so the nonce (together with the lane) identifies the trace.
+	traced_message_nonces: GaugeVec<U64>,
+	/// Size (in bytes) of generated messages proofs.
+	messages_proof_size: Histogram,
+	/// Number of messages in submitted delivery transactions.
+	messages_per_delivery: Histogram,
+	/// Weight of delivery transactions: "declared" (the cumulative dispatch weight of messages,
+	/// declared by the relayer) and "actual" (the weight of the whole transaction, computed by
+	/// the target node).
+	delivery_weight: HistogramVec,
 }
 
 impl Metrics for MessageLaneLoopMetrics {
 	fn register(&self, registry: &Registry) -> Result<(), String> {
 		register(self.best_block_numbers.clone(), registry).map_err(|e| e.to_string())?;
 		register(self.lane_state_nonces.clone(), registry).map_err(|e| e.to_string())?;
+		register(self.oldest_undelivered_message_age.clone(), registry).map_err(|e| e.to_string())?;
+		register(self.delivery_deadline_exceeded.clone(), registry).map_err(|e| e.to_string())?;
+		register(self.delivery_transaction_estimation.clone(), registry).map_err(|e| e.to_string())?;
+		register(self.unconfirmed_rewards.clone(), registry).map_err(|e| e.to_string())?;
+		register(self.traced_message_nonces.clone(), registry).map_err(|e| e.to_string())?;
+		register(self.messages_proof_size.clone(), registry).map_err(|e| e.to_string())?;
+		register(self.messages_per_delivery.clone(), registry).map_err(|e| e.to_string())?;
+		register(self.delivery_weight.clone(), registry).map_err(|e| e.to_string())?;
 		Ok(())
 	}
 }
@@ -52,6 +85,59 @@ impl Default for MessageLaneLoopMetrics {
 			.expect("metric is static and thus valid; qed"),
 			lane_state_nonces: GaugeVec::new(Opts::new("lane_state_nonces", "Nonces of the lane state"), &["type"])
 				.expect("metric is static and thus valid; qed"),
+			oldest_undelivered_message_age: Gauge::new(
+				"oldest_undelivered_message_age",
+				"Age (in seconds) of the oldest undelivered message",
+			)
+			.expect("metric is static and thus valid; qed"),
+			delivery_deadline_exceeded: Gauge::new(
+				"delivery_deadline_exceeded",
+				"Whether the oldest undelivered message has exceeded the delivery deadline",
+			)
+			.expect("metric is static and thus valid; qed"),
+			delivery_transaction_estimation: GaugeVec::new(
+				Opts::new(
+					"delivery_transaction_estimation",
+					"Estimated fee and weight of the latest messages delivery transaction",
+				),
+				&["type"],
+			)
+			.expect("metric is static and thus valid; qed"),
+			unconfirmed_rewards: Gauge::new(
+				"unconfirmed_rewards",
+				"Total reward of delivered messages that is not yet confirmed at the source node",
+			)
+			.expect("metric is static and thus valid; qed"),
+			traced_message_nonces: GaugeVec::new(
+				Opts::new(
+					"traced_message_nonces",
+					"Nonce of the latest traced message that has reached given stage",
+				),
+				&["stage"],
+			)
+			.expect("metric is static and thus valid; qed"),
+			messages_proof_size: Histogram::with_opts(
+				HistogramOpts::new("messages_proof_size", "Size (in bytes) of generated messages proofs")
+					.buckets(exponential_buckets(256.0, 2.0, 14).expect("buckets are static and thus valid; qed")),
+			)
+			.expect("metric is static and thus valid; qed"),
+			messages_per_delivery: Histogram::with_opts(
+				HistogramOpts::new(
+					"messages_per_delivery",
+					"Number of messages in submitted delivery transactions",
+				)
+				.buckets(exponential_buckets(1.0, 2.0, 11).expect("buckets are static and thus valid; qed")),
+			)
+			.expect("metric is static and thus valid; qed"),
+			delivery_weight: HistogramVec::new(
+				HistogramOpts::new(
+					"delivery_weight",
+					"Declared dispatch weight of messages and actual weight of delivery transactions",
+				)
+				.buckets(exponential_buckets(1_000_000.0, 4.0, 12).expect("buckets are static and thus valid; qed")),
+				&["type"],
+			)
+			.expect("metric is static and thus valid; qed"),
 		}
 	}
 }
@@ -104,4 +190,50 @@ impl MessageLaneLoopMetrics {
 			.with_label_values(&["target_latest_confirmed"])
 			.set(target_latest_confirmed_nonce);
 	}
+
+	/// Update age of the oldest undelivered message and delivery deadline alarm.
+	pub fn update_oldest_undelivered_message_age(&self, age: Duration, is_delivery_deadline_exceeded: bool) {
+		self.oldest_undelivered_message_age.set(age.as_secs());
+		self.delivery_deadline_exceeded
+			.set(if is_delivery_deadline_exceeded { 1 } else { 0 });
+	}
+
+	/// Update total reward of delivered, but not yet confirmed messages.
+	pub fn update_unconfirmed_rewards(&self, unconfirmed_rewards: u128) {
+		self.unconfirmed_rewards.set(unconfirmed_rewards as f64);
+	}
+
+	/// Update metrics of the generated messages proof.
+	pub fn update_generated_messages_proof(&self, proof_size: usize, declared_dispatch_weight: Weight) {
+		self.messages_proof_size.observe(proof_size as f64);
+		self.delivery_weight
+			.with_label_values(&["declared"])
+			.observe(declared_dispatch_weight as f64);
+	}
+
+	/// Update metrics of the submitted messages delivery transaction.
+	///
+	/// Estimation metrics are only updated if the transaction has been estimated before submission.
+	pub fn update_submitted_delivery(&self, messages: MessageNonce, estimation: Option<MessagesDeliveryEstimation>) {
+		self.messages_per_delivery.observe(messages as f64);
+		if let Some(estimation) = estimation {
+			self.delivery_transaction_estimation
+				.with_label_values(&["fee"])
+				.set(estimation.fee as f64);
+			self.delivery_transaction_estimation
+				.with_label_values(&["weight"])
+				.set(estimation.weight as f64);
+			self.delivery_weight
+				.with_label_values(&["actual"])
+				.observe(estimation.weight as f64);
+		}
+	}
+
+	/// Update nonce of the latest traced message that has reached given stage.
+	pub fn update_traced_message_nonce(&self, stage: MessageStage, nonce: MessageNonce) {
+		let gauge = self.traced_message_nonces.with_label_values(&[stage.as_str()]);
+		if nonce > gauge.get() {
+			gauge.set(nonce);
+		}
+	}
 }
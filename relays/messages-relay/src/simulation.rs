@@ -0,0 +1,1007 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Deterministic simulation of the message lane loop.
+//!
+//! The real message lane loop is started against simulated source and target chains. Both
+//! chains are living in virtual time: every client call advances the virtual clock by some
+//! RPC latency and new blocks are produced when the clock crosses block boundaries. So the
+//! evolution of chains only depends on the sequence of client calls and not on the wall clock,
+//! which allows to use tiny loop ticks and to simulate hours of relay work in a fraction of second.
+//!
+//! Several relayers (loops with their own target clients) may serve the same lane. Delivery
+//! transactions of the slower relayer are rejected by the simulated target chain, and every
+//! confirmed message must be rewarded to the relayer that has actually delivered it.
+//!
+//! Every scenario is generated from the single seed. The seed defines chains parameters, the
+//! messages generation and failures that are injected into clients: node outages, RPC errors,
+//! dropped transactions and reorgs of non-finalized blocks. The interleaving of relay futures
+//! is not controlled by the simulation, so invariants that are checked must hold for any
+//! interleaving. Every invariant violation is reported along with the scenario seed.
+//!
+//! The number of simulated scenarios may be changed with the `MESSAGES_RELAY_SIMULATION_SCENARIOS`
+//! environment variable and the seed of the first scenario - with the `MESSAGES_RELAY_SIMULATION_SEED`.
+
+use crate::chaos::{ChaosClient, ChaosParams};
+use crate::message_lane::{SourceHeaderIdOf, TargetHeaderIdOf};
+use crate::message_lane_loop::{
+	run,
+	tests::{
+		TestError, TestMessageLane, TestMessagesProof, TestMessagesReceivingProof, TestSourceHeaderId,
+		TestTargetHeaderId,
+	},
+	ClientState, MessageDeliveryParams, MessageProofParameters, MessageRewardsMap, MessageWeightsMap,
+	MessagesDeliverySubmission, Params, SourceClient, SourceClientState, TargetClient, TargetClientState,
+};
+
+use async_trait::async_trait;
+use bp_message_lane::{MessageNonce, Weight};
+use futures::{
+	channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+	future::FutureExt,
+	stream::StreamExt,
+};
+use parking_lot::Mutex;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use relay_utils::HeaderId;
+use std::{
+	collections::BTreeMap,
+	ops::{Range, RangeInclusive},
+	sync::Arc,
+	time::Duration,
+};
+
+/// Number of scenarios that are simulated by default.
+const DEFAULT_SCENARIOS: u64 = 16;
+/// Interval of source and target loop ticks (wall clock).
+const TICK: Duration = Duration::from_millis(1);
+/// Stall timeout of the loop (wall clock). Dropped transactions are only resubmitted after the stall.
+const STALL_TIMEOUT: Duration = Duration::from_millis(250);
+/// Maximal duration of every scenario (wall clock). Scenario fails if it hasn't been completed in time.
+const WALL_CLOCK_LIMIT: Duration = Duration::from_secs(60);
+/// Maximal number of messages that are generated at single source block.
+const MAX_MESSAGES_PER_BLOCK: MessageNonce = 2;
+/// Difference between hashes of blocks with the same number at two consecutive forks.
+const FORK_HASH_OFFSET: u64 = 1_000_000_000;
+
+/// Index of the relayer that is serving the lane.
+type Relayer = usize;
+
+/// Parameters of the simulated scenario. All durations are in virtual milliseconds.
+#[derive(Debug, Clone)]
+struct Scenario {
+	/// Seed that the scenario has been generated from.
+	seed: u64,
+	/// Number of messages that are generated at the source chain.
+	messages: MessageNonce,
+	/// Interval between source chain blocks.
+	source_block_time: u64,
+	/// Interval between target chain blocks.
+	target_block_time: u64,
+	/// Number of blocks between best and best finalized blocks of both chains.
+	finality_lag: u64,
+	/// Maximal latency of single RPC call.
+	max_rpc_latency: u64,
+	/// Periods when the source node is unavailable.
+	source_outages: Vec<Range<u64>>,
+	/// Periods when the target node is unavailable.
+	target_outages: Vec<Range<u64>>,
+	/// Probability that the RPC call fails with connection error.
+	rpc_error_probability: f64,
+	/// Probability that the submitted transaction is silently dropped.
+	transaction_drop_probability: f64,
+	/// Probability that non-finalized blocks are reverted before producing new block.
+	reorg_probability: f64,
+	/// Probability that the headers relay updates header of the peer chain at the new block.
+	header_sync_probability: f64,
+	/// Maximal number of unconfirmed messages at the target chain.
+	max_unconfirmed_nonces_at_target: MessageNonce,
+	/// Maximal weight of the messages delivery transaction. Every message has weight 1.
+	max_delivery_transaction_weight: Weight,
+	/// Scenario fails if all messages aren't confirmed by this moment.
+	deadline: u64,
+}
+
+impl Scenario {
+	/// Generate scenario from given seed.
+	fn generate(seed: u64) -> Self {
+		let mut rng = StdRng::seed_from_u64(seed);
+		let messages = rng.gen_range(1, 33);
+		let source_block_time = rng.gen_range(1_000, 6_001);
+		let target_block_time = rng.gen_range(1_000, 6_001);
+		let outages_horizon = messages * 10 * std::cmp::max(source_block_time, target_block_time);
+		let outages = |rng: &mut StdRng| {
+			let mut outages = (0..rng.gen_range(0, 3))
+				.map(|_| {
+					let start = rng.gen_range(0, outages_horizon);
+					start..start + rng.gen_range(1_000, 60_001)
+				})
+				.collect::<Vec<_>>();
+			outages.sort_by_key(|outage| outage.start);
+			outages
+		};
+		let source_outages = outages(&mut rng);
+		let target_outages = outages(&mut rng);
+		let outages_duration = source_outages
+			.iter()
+			.chain(target_outages.iter())
+			.map(|outage| outage.end - outage.start)
+			.sum::<u64>();
+
+		Scenario {
+			seed,
+			messages,
+			source_block_time,
+			target_block_time,
+			finality_lag: rng.gen_range(0, 4),
+			max_rpc_latency: rng.gen_range(100, 2_001),
+			source_outages,
+			target_outages,
+			rpc_error_probability: rng.gen_range(0.0, 0.05),
+			transaction_drop_probability: rng.gen_range(0.0, 0.1),
+			reorg_probability: rng.gen_range(0.0, 0.1),
+			header_sync_probability: rng.gen_range(0.3, 1.0),
+			max_unconfirmed_nonces_at_target: rng.gen_range(1, 17),
+			max_delivery_transaction_weight: rng.gen_range(1, 9),
+			deadline: outages_horizon * 10 + outages_duration,
+		}
+	}
+}
+
+/// Lane state at the simulated chain block.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct LaneState {
+	/// Nonce of the latest generated (at source) or received (at target) message.
+	latest_nonce: MessageNonce,
+	/// Nonce of the latest message, which delivery has been confirmed at the source (or the
+	/// target is aware of).
+	latest_confirmed_nonce: MessageNonce,
+}
+
+/// Block of the simulated chain.
+#[derive(Debug, Clone, PartialEq)]
+struct Block {
+	/// Block id.
+	id: HeaderId<u64, u64>,
+	/// Lane state after the block has been imported.
+	lane: LaneState,
+	/// Finalized header of the peer chain, which is known to this chain.
+	best_peer: HeaderId<u64, u64>,
+	/// Number of transactions, included into the block.
+	transactions: usize,
+	/// At the target chain: relayers that have delivered received messages. At the source chain:
+	/// relayers that have been rewarded for confirmed messages.
+	relayers: BTreeMap<MessageNonce, Relayer>,
+}
+
+/// Simulated chain.
+#[derive(Debug)]
+struct Chain<Transaction> {
+	/// Canonical chain blocks. Block at index N has number N.
+	blocks: Vec<Block>,
+	/// Number of the best finalized block.
+	finalized: u64,
+	/// Transactions that will be included into the next block.
+	pool: Vec<Transaction>,
+	/// Moment when the next block is produced.
+	next_block_at: u64,
+	/// Number of reorgs that have happened at this chain.
+	fork: u64,
+}
+
+impl<Transaction> Chain<Transaction> {
+	fn new(block_time: u64) -> Self {
+		Chain {
+			blocks: vec![Block {
+				id: HeaderId(0, 0),
+				lane: Default::default(),
+				best_peer: HeaderId(0, 0),
+				transactions: 0,
+				relayers: BTreeMap::new(),
+			}],
+			finalized: 0,
+			pool: Vec::new(),
+			next_block_at: block_time,
+			fork: 0,
+		}
+	}
+
+	fn best(&self) -> &Block {
+		self.blocks.last().expect("chain always has genesis block; qed")
+	}
+
+	fn best_finalized(&self) -> &Block {
+		&self.blocks[self.finalized as usize]
+	}
+
+	fn finalized_block(&self, id: HeaderId<u64, u64>) -> Option<&Block> {
+		self.blocks
+			.get(id.0 as usize)
+			.filter(|block| block.id == id && id.0 <= self.finalized)
+	}
+
+	/// Revert all non-finalized blocks. Returns number of lost transactions if there were any
+	/// blocks to revert.
+	fn revert_unfinalized_blocks(&mut self) -> Option<usize> {
+		if self.finalized + 1 == self.blocks.len() as u64 {
+			return None;
+		}
+
+		self.fork += 1;
+		Some(
+			self.blocks
+				.drain(self.finalized as usize + 1..)
+				.map(|block| block.transactions)
+				.sum(),
+		)
+	}
+
+	fn push_block(
+		&mut self,
+		lane: LaneState,
+		best_peer: HeaderId<u64, u64>,
+		transactions: usize,
+		relayers: BTreeMap<MessageNonce, Relayer>,
+		finality_lag: u64,
+	) {
+		let number = self.blocks.len() as u64;
+		self.blocks.push(Block {
+			id: HeaderId(number, number + self.fork * FORK_HASH_OFFSET),
+			lane,
+			best_peer,
+			transactions,
+			relayers,
+		});
+		self.finalized = std::cmp::max(self.finalized, number.saturating_sub(finality_lag));
+	}
+}
+
+/// Messages delivery transaction, submitted to the target chain.
+type DeliveryTransaction = (
+	Relayer,
+	TestSourceHeaderId,
+	RangeInclusive<MessageNonce>,
+	TestMessagesProof,
+);
+/// Messages delivery confirmation transaction, submitted to the source chain.
+type ConfirmationTransaction = (TestTargetHeaderId, TestMessagesReceivingProof);
+
+/// Chain that is called by the relay.
+#[derive(Debug, Clone, Copy)]
+enum SimulatedChain {
+	Source,
+	Target,
+}
+
+/// Simulation statistics.
+#[derive(Debug, Clone, Default)]
+struct Stats {
+	/// Number of RPC calls that have failed.
+	rpc_errors: usize,
+	/// Number of reorgs at both chains.
+	reorgs: usize,
+	/// Number of transactions that have been lost because of reorgs.
+	lost_transactions: usize,
+	/// Number of transactions that have been dropped from the pool.
+	dropped_transactions: usize,
+	/// Number of submitted messages delivery transactions.
+	delivery_transactions: usize,
+	/// Number of submitted messages delivery confirmation transactions.
+	confirmation_transactions: usize,
+	/// Number of transactions that have been rejected by the runtime because they've been outdated.
+	rejected_transactions: usize,
+	/// Number of rejected delivery transactions, which only contained already received messages.
+	duplicate_deliveries: usize,
+}
+
+/// Simulated environment of the message lane loop.
+struct Simulation {
+	scenario: Scenario,
+	rng: StdRng,
+	/// Current virtual time.
+	now: u64,
+	source: Chain<ConfirmationTransaction>,
+	target: Chain<DeliveryTransaction>,
+	stats: Stats,
+	violations: Vec<String>,
+	is_completed: bool,
+	exit_senders: Vec<UnboundedSender<()>>,
+}
+
+/// Result of the simulated scenario.
+#[derive(Debug)]
+struct SimulationReport {
+	scenario: Scenario,
+	/// Virtual time when the scenario has been stopped.
+	virtual_time: u64,
+	/// Source chain lane state at the best finalized block.
+	source_lane: LaneState,
+	/// Target chain lane state at the best finalized block.
+	target_lane: LaneState,
+	/// True if all messages have been delivered and confirmed.
+	is_completed: bool,
+	/// Number of confirmed messages, rewarded to every relayer.
+	rewards: BTreeMap<Relayer, MessageNonce>,
+	stats: Stats,
+	violations: Vec<String>,
+}
+
+impl Simulation {
+	fn new(scenario: Scenario, exit_senders: Vec<UnboundedSender<()>>) -> Self {
+		Simulation {
+			rng: StdRng::seed_from_u64(scenario.seed),
+			now: 0,
+			source: Chain::new(scenario.source_block_time),
+			target: Chain::new(scenario.target_block_time),
+			stats: Default::default(),
+			violations: Vec::new(),
+			is_completed: false,
+			exit_senders,
+			scenario,
+		}
+	}
+
+	/// Simulate RPC call to the node of given chain.
+	fn call(&mut self, chain: SimulatedChain) -> Result<(), TestError> {
+		self.now += self.rng.gen_range(1, self.scenario.max_rpc_latency + 1);
+		self.produce_blocks();
+		self.check_completion();
+
+		let is_failed = self.rng.gen_bool(self.scenario.rpc_error_probability);
+		let outages = match chain {
+			SimulatedChain::Source => &self.scenario.source_outages,
+			SimulatedChain::Target => &self.scenario.target_outages,
+		};
+		if is_failed || outages.iter().any(|outage| outage.contains(&self.now)) {
+			self.stats.rpc_errors += 1;
+			return Err(TestError);
+		}
+
+		Ok(())
+	}
+
+	/// Produce all blocks that are scheduled before current virtual time.
+	fn produce_blocks(&mut self) {
+		loop {
+			let is_source_block = self.source.next_block_at <= self.target.next_block_at;
+			let next_block_at = std::cmp::min(self.source.next_block_at, self.target.next_block_at);
+			if next_block_at > self.now {
+				break;
+			}
+
+			if is_source_block {
+				self.produce_source_block();
+				self.source.next_block_at += self.scenario.source_block_time;
+			} else {
+				self.produce_target_block();
+				self.target.next_block_at += self.scenario.target_block_time;
+			}
+		}
+	}
+
+	fn produce_source_block(&mut self) {
+		if self.rng.gen_bool(self.scenario.reorg_probability) {
+			if let Some(lost_transactions) = self.source.revert_unfinalized_blocks() {
+				self.stats.reorgs += 1;
+				self.stats.lost_transactions += lost_transactions;
+			}
+		}
+
+		let parent = self.source.best().clone();
+		let mut lane = parent.lane;
+		let mut relayers = parent.relayers.clone();
+		let new_messages = self.rng.gen_range(0, MAX_MESSAGES_PER_BLOCK + 1);
+		lane.latest_nonce += std::cmp::min(new_messages, self.scenario.messages - lane.latest_nonce);
+
+		let transactions = std::mem::take(&mut self.source.pool);
+		let transactions_count = transactions.len();
+		for (at_target, latest_received_nonce) in transactions {
+			if at_target.0 > parent.best_peer.0 || self.target.finalized_block(at_target).is_none() {
+				self.violations.push(format!(
+					"Confirmation transaction is proved at target header {:?}, unknown to the source chain",
+					at_target,
+				));
+				continue;
+			}
+			if latest_received_nonce > lane.latest_nonce {
+				self.violations.push(format!(
+					"Confirmation of message {} that has not been generated at the source chain",
+					latest_received_nonce,
+				));
+				continue;
+			}
+			if latest_received_nonce <= lane.latest_confirmed_nonce {
+				self.stats.rejected_transactions += 1;
+				continue;
+			}
+
+			// the relayer that has delivered the message is rewarded
+			let target_relayers = &self
+				.target
+				.finalized_block(at_target)
+				.expect("checked above; qed")
+				.relayers;
+			for nonce in lane.latest_confirmed_nonce + 1..=latest_received_nonce {
+				match target_relayers.get(&nonce) {
+					Some(relayer) => {
+						relayers.insert(nonce, *relayer);
+					}
+					None => self.violations.push(format!(
+						"Confirmation of message {} that has not been delivered at target header {:?}",
+						nonce, at_target,
+					)),
+				}
+			}
+
+			lane.latest_confirmed_nonce = latest_received_nonce;
+		}
+
+		let best_peer = if self.rng.gen_bool(self.scenario.header_sync_probability) {
+			self.target.best_finalized().id
+		} else {
+			parent.best_peer
+		};
+		self.source.push_block(
+			lane,
+			best_peer,
+			transactions_count,
+			relayers,
+			self.scenario.finality_lag,
+		);
+	}
+
+	fn produce_target_block(&mut self) {
+		if self.rng.gen_bool(self.scenario.reorg_probability) {
+			if let Some(lost_transactions) = self.target.revert_unfinalized_blocks() {
+				self.stats.reorgs += 1;
+				self.stats.lost_transactions += lost_transactions;
+			}
+		}
+
+		let parent = self.target.best().clone();
+		let mut lane = parent.lane;
+		let mut relayers = parent.relayers.clone();
+
+		let transactions = std::mem::take(&mut self.target.pool);
+		let transactions_count = transactions.len();
+		for (relayer, at_source, nonces, (proof_nonces, proof_latest_confirmed_nonce)) in transactions {
+			let source_block = match self.source.finalized_block(at_source) {
+				Some(source_block) if at_source.0 <= parent.best_peer.0 => source_block,
+				_ => {
+					self.violations.push(format!(
+						"Messages are proved at source header {:?}, unknown to the target chain",
+						at_source,
+					));
+					continue;
+				}
+			};
+			if proof_nonces != nonces || *nonces.end() > source_block.lane.latest_nonce {
+				self.violations.push(format!(
+					"Messages {:?} are not generated at source header {:?} or the proof is invalid",
+					nonces, at_source,
+				));
+				continue;
+			}
+
+			if let Some(proof_latest_confirmed_nonce) = proof_latest_confirmed_nonce {
+				lane.latest_confirmed_nonce = std::cmp::max(lane.latest_confirmed_nonce, proof_latest_confirmed_nonce);
+			}
+
+			// messages are received one-by-one, so the range must start right after the latest
+			// received nonce, or overlap with already received messages
+			let max_nonce = std::cmp::min(
+				*nonces.end(),
+				lane.latest_confirmed_nonce + self.scenario.max_unconfirmed_nonces_at_target,
+			);
+			if *nonces.start() > lane.latest_nonce + 1 || max_nonce <= lane.latest_nonce {
+				self.stats.rejected_transactions += 1;
+				if *nonces.end() <= lane.latest_nonce {
+					self.stats.duplicate_deliveries += 1;
+				}
+				continue;
+			}
+
+			for nonce in lane.latest_nonce + 1..=max_nonce {
+				relayers.insert(nonce, relayer);
+			}
+			lane.latest_nonce = max_nonce;
+		}
+
+		let best_peer = if self.rng.gen_bool(self.scenario.header_sync_probability) {
+			self.source.best_finalized().id
+		} else {
+			parent.best_peer
+		};
+		self.target.push_block(
+			lane,
+			best_peer,
+			transactions_count,
+			relayers,
+			self.scenario.finality_lag,
+		);
+	}
+
+	/// Stop the loop if all messages are confirmed or the deadline has been reached.
+	fn check_completion(&mut self) {
+		let is_completed = self.source.best_finalized().lane.latest_confirmed_nonce == self.scenario.messages;
+		if !is_completed && self.now <= self.scenario.deadline {
+			return;
+		}
+
+		if !self.exit_senders.is_empty() {
+			self.is_completed = is_completed;
+			for exit_sender in self.exit_senders.drain(..) {
+				let _ = exit_sender.unbounded_send(());
+			}
+		}
+	}
+
+	/// Submit transaction to the pool. The transaction may be silently dropped.
+	fn submit<Transaction>(
+		rng: &mut StdRng,
+		stats: &mut Stats,
+		scenario: &Scenario,
+		chain: &mut Chain<Transaction>,
+		transaction: Transaction,
+	) {
+		if rng.gen_bool(scenario.transaction_drop_probability) {
+			stats.dropped_transactions += 1;
+			return;
+		}
+
+		chain.pool.push(transaction);
+	}
+
+	fn source_block(&mut self, id: TestSourceHeaderId) -> Result<Block, TestError> {
+		match self.source.finalized_block(id) {
+			Some(block) => Ok(block.clone()),
+			None => {
+				self.violations
+					.push(format!("Source node is queried at unknown header {:?}", id));
+				Err(TestError)
+			}
+		}
+	}
+
+	fn target_block(&mut self, id: TestTargetHeaderId) -> Result<Block, TestError> {
+		match self.target.finalized_block(id) {
+			Some(block) => Ok(block.clone()),
+			None => {
+				self.violations
+					.push(format!("Target node is queried at unknown header {:?}", id));
+				Err(TestError)
+			}
+		}
+	}
+
+	fn check_generated(&mut self, block: &Block, nonces: &RangeInclusive<MessageNonce>) {
+		if *nonces.end() > block.lane.latest_nonce {
+			self.violations.push(format!(
+				"Messages {:?} are requested at source header {:?}, but only {} messages are generated there",
+				nonces, block.id, block.lane.latest_nonce,
+			));
+		}
+	}
+
+	/// Check that every confirmed message is rewarded to the relayer that has delivered it.
+	fn check_rewards(&self) -> Vec<String> {
+		let (source, target) = (self.source.best_finalized(), self.target.best_finalized());
+		(1..=source.lane.latest_confirmed_nonce)
+			.filter_map(|nonce| {
+				let (rewarded, delivered) = (source.relayers.get(&nonce), target.relayers.get(&nonce));
+				if rewarded.is_none() || rewarded != delivered {
+					Some(format!(
+						"Message {} is delivered by relayer {:?}, but relayer {:?} is rewarded",
+						nonce, delivered, rewarded,
+					))
+				} else {
+					None
+				}
+			})
+			.collect()
+	}
+
+	fn report(&self) -> SimulationReport {
+		let mut rewards = BTreeMap::new();
+		for relayer in self.source.best_finalized().relayers.values() {
+			*rewards.entry(*relayer).or_default() += 1;
+		}
+
+		let mut violations = self.violations.clone();
+		violations.extend(self.check_rewards());
+
+		SimulationReport {
+			scenario: self.scenario.clone(),
+			virtual_time: self.now,
+			source_lane: self.source.best_finalized().lane,
+			target_lane: self.target.best_finalized().lane,
+			is_completed: self.is_completed,
+			rewards,
+			stats: self.stats.clone(),
+			violations,
+		}
+	}
+}
+
+#[derive(Clone)]
+struct SimulatedSourceClient {
+	simulation: Arc<Mutex<Simulation>>,
+}
+
+#[async_trait]
+impl SourceClient<TestMessageLane> for SimulatedSourceClient {
+	type Error = TestError;
+
+	async fn reconnect(self) -> Result<Self, Self::Error> {
+		self.simulation.lock().call(SimulatedChain::Source)?;
+		Ok(self)
+	}
+
+	async fn state(&self) -> Result<SourceClientState<TestMessageLane>, Self::Error> {
+		let mut simulation = self.simulation.lock();
+		simulation.call(SimulatedChain::Source)?;
+		let best_finalized = simulation.source.best_finalized();
+		Ok(ClientState {
+			best_self: best_finalized.id,
+			best_peer: best_finalized.best_peer,
+		})
+	}
+
+	async fn latest_generated_nonce(
+		&self,
+		id: SourceHeaderIdOf<TestMessageLane>,
+	) -> Result<(SourceHeaderIdOf<TestMessageLane>, MessageNonce), Self::Error> {
+		let mut simulation = self.simulation.lock();
+		simulation.call(SimulatedChain::Source)?;
+		Ok((id, simulation.source_block(id)?.lane.latest_nonce))
+	}
+
+	async fn latest_confirmed_received_nonce(
+		&self,
+		id: SourceHeaderIdOf<TestMessageLane>,
+	) -> Result<(SourceHeaderIdOf<TestMessageLane>, MessageNonce), Self::Error> {
+		let mut simulation = self.simulation.lock();
+		simulation.call(SimulatedChain::Source)?;
+		Ok((id, simulation.source_block(id)?.lane.latest_confirmed_nonce))
+	}
+
+	async fn generated_messages_weights(
+		&self,
+		id: SourceHeaderIdOf<TestMessageLane>,
+		nonces: RangeInclusive<MessageNonce>,
+	) -> Result<MessageWeightsMap, Self::Error> {
+		let mut simulation = self.simulation.lock();
+		simulation.call(SimulatedChain::Source)?;
+		let block = simulation.source_block(id)?;
+		simulation.check_generated(&block, &nonces);
+		Ok(nonces.map(|nonce| (nonce, 1)).collect())
+	}
+
+	async fn generated_messages_rewards(
+		&self,
+		id: SourceHeaderIdOf<TestMessageLane>,
+		nonces: RangeInclusive<MessageNonce>,
+	) -> Result<MessageRewardsMap, Self::Error> {
+		let mut simulation = self.simulation.lock();
+		simulation.call(SimulatedChain::Source)?;
+		let block = simulation.source_block(id)?;
+		simulation.check_generated(&block, &nonces);
+		Ok(nonces.map(|nonce| (nonce, 1)).collect())
+	}
+
+	async fn prove_messages(
+		&self,
+		id: SourceHeaderIdOf<TestMessageLane>,
+		nonces: RangeInclusive<MessageNonce>,
+		proof_parameters: MessageProofParameters,
+	) -> Result<
+		(
+			SourceHeaderIdOf<TestMessageLane>,
+			RangeInclusive<MessageNonce>,
+			TestMessagesProof,
+		),
+		Self::Error,
+	> {
+		let mut simulation = self.simulation.lock();
+		simulation.call(SimulatedChain::Source)?;
+		let block = simulation.source_block(id)?;
+		simulation.check_generated(&block, &nonces);
+		Ok((
+			id,
+			nonces.clone(),
+			(
+				nonces,
+				if proof_parameters.outbound_state_proof_required {
+					Some(block.lane.latest_confirmed_nonce)
+				} else {
+					None
+				},
+			),
+		))
+	}
+
+	async fn submit_messages_receiving_proof(
+		&self,
+		generated_at_block: TargetHeaderIdOf<TestMessageLane>,
+		proof: TestMessagesReceivingProof,
+	) -> Result<(), Self::Error> {
+		let mut simulation = self.simulation.lock();
+		simulation.call(SimulatedChain::Source)?;
+		let Simulation {
+			ref mut rng,
+			ref mut stats,
+			ref scenario,
+			ref mut source,
+			..
+		} = *simulation;
+		stats.confirmation_transactions += 1;
+		Simulation::submit(rng, stats, scenario, source, (generated_at_block, proof));
+		Ok(())
+	}
+}
+
+#[derive(Clone)]
+struct SimulatedTargetClient {
+	simulation: Arc<Mutex<Simulation>>,
+	relayer: Relayer,
+}
+
+#[async_trait]
+impl TargetClient<TestMessageLane> for SimulatedTargetClient {
+	type Error = TestError;
+
+	async fn reconnect(self) -> Result<Self, Self::Error> {
+		self.simulation.lock().call(SimulatedChain::Target)?;
+		Ok(self)
+	}
+
+	async fn state(&self) -> Result<TargetClientState<TestMessageLane>, Self::Error> {
+		let mut simulation = self.simulation.lock();
+		simulation.call(SimulatedChain::Target)?;
+		let best_finalized = simulation.target.best_finalized();
+		Ok(ClientState {
+			best_self: best_finalized.id,
+			best_peer: best_finalized.best_peer,
+		})
+	}
+
+	async fn latest_received_nonce(
+		&self,
+		id: TargetHeaderIdOf<TestMessageLane>,
+	) -> Result<(TargetHeaderIdOf<TestMessageLane>, MessageNonce), Self::Error> {
+		let mut simulation = self.simulation.lock();
+		simulation.call(SimulatedChain::Target)?;
+		Ok((id, simulation.target_block(id)?.lane.latest_nonce))
+	}
+
+	async fn latest_confirmed_received_nonce(
+		&self,
+		id: TargetHeaderIdOf<TestMessageLane>,
+	) -> Result<(TargetHeaderIdOf<TestMessageLane>, MessageNonce), Self::Error> {
+		let mut simulation = self.simulation.lock();
+		simulation.call(SimulatedChain::Target)?;
+		Ok((id, simulation.target_block(id)?.lane.latest_confirmed_nonce))
+	}
+
+	async fn prove_messages_receiving(
+		&self,
+		id: TargetHeaderIdOf<TestMessageLane>,
+	) -> Result<(TargetHeaderIdOf<TestMessageLane>, TestMessagesReceivingProof), Self::Error> {
+		let mut simulation = self.simulation.lock();
+		simulation.call(SimulatedChain::Target)?;
+		Ok((id, simulation.target_block(id)?.lane.latest_nonce))
+	}
+
+	async fn submit_messages_proof(
+		&self,
+		generated_at_header: SourceHeaderIdOf<TestMessageLane>,
+		nonces: RangeInclusive<MessageNonce>,
+		proof: TestMessagesProof,
+	) -> Result<MessagesDeliverySubmission, Self::Error> {
+		let mut simulation = self.simulation.lock();
+		simulation.call(SimulatedChain::Target)?;
+		let Simulation {
+			ref mut rng,
+			ref mut stats,
+			ref scenario,
+			ref mut target,
+			..
+		} = *simulation;
+		stats.delivery_transactions += 1;
+		Simulation::submit(
+			rng,
+			stats,
+			scenario,
+			target,
+			(self.relayer, generated_at_header, nonces.clone(), proof),
+		);
+		Ok(MessagesDeliverySubmission::Submitted(nonces, None))
+	}
+}
+
+/// Run `relayers` message lane loops, serving the same lane, in the simulated environment until
+/// scenario is completed.
+///
+/// Additional faults may be injected into simulated clients using `chaos_params`.
+fn simulate(scenario: Scenario, chaos_params: ChaosParams, relayers: usize) -> SimulationReport {
+	let (exit_senders, exit_receivers): (Vec<_>, Vec<_>) = (0..relayers).map(|_| unbounded()).unzip();
+	let simulation = Arc::new(Mutex::new(Simulation::new(scenario.clone(), exit_senders)));
+
+	let loops = exit_receivers
+		.into_iter()
+		.enumerate()
+		.map(|(relayer, exit_receiver)| {
+			let scenario = scenario.clone();
+			let chaos_params = chaos_params.clone();
+			let simulation = simulation.clone();
+			std::thread::spawn(move || run_relayer(scenario, chaos_params, simulation, relayer, exit_receiver))
+		})
+		.collect::<Vec<_>>();
+	for relayer_loop in loops {
+		relayer_loop.join().expect("relayer loop has panicked");
+	}
+
+	let report = simulation.lock().report();
+	report
+}
+
+/// Run message lane loop of single relayer until simulation is completed.
+fn run_relayer(
+	scenario: Scenario,
+	chaos_params: ChaosParams,
+	simulation: Arc<Mutex<Simulation>>,
+	relayer: Relayer,
+	exit_receiver: UnboundedReceiver<()>,
+) {
+	let exit_signal = futures::future::select(
+		exit_receiver.into_future(),
+		Box::pin(async_std::task::sleep(WALL_CLOCK_LIMIT)),
+	)
+	.map(|_| ());
+
+	run(
+		Params {
+			lane: Default::default(),
+			source_tick: TICK,
+			target_tick: TICK,
+			reconnect_delay: Duration::from_millis(0),
+			stall_timeout: STALL_TIMEOUT,
+			delivery_params: MessageDeliveryParams {
+				max_unconfirmed_nonces_at_target: scenario.max_unconfirmed_nonces_at_target,
+				max_delivery_transaction_weight: scenario.max_delivery_transaction_weight,
+				max_messages_proof_size: 0,
+				delivery_weights: bp_message_lane_weights::DEFAULT_MESSAGE_LANE_WEIGHTS,
+				delivery_deadline: None,
+				max_unconfirmed_rewards: None,
+			},
+			otlp_endpoint: None,
+		},
+		ChaosClient::new(
+			SimulatedSourceClient {
+				simulation: simulation.clone(),
+			},
+			chaos_params.clone(),
+		),
+		ChaosClient::new(SimulatedTargetClient { simulation, relayer }, chaos_params),
+		None,
+		exit_signal,
+	);
+}
+
+mod tests {
+	use super::*;
+
+	fn env_var_or(name: &str, default: u64) -> u64 {
+		std::env::var(name)
+			.ok()
+			.and_then(|value| value.parse().ok())
+			.unwrap_or(default)
+	}
+
+	#[test]
+	fn simulated_chains_are_deterministic() {
+		let simulate_calls = |seed| {
+			let (exit_sender, _exit_receiver) = unbounded();
+			let mut simulation = Simulation::new(Scenario::generate(seed), vec![exit_sender]);
+			let results = (0..1_000)
+				.map(|i| {
+					simulation
+						.call(if i % 3 == 0 {
+							SimulatedChain::Source
+						} else {
+							SimulatedChain::Target
+						})
+						.is_ok()
+				})
+				.collect::<Vec<_>>();
+			(
+				results,
+				simulation.now,
+				simulation.source.blocks.clone(),
+				simulation.target.blocks.clone(),
+			)
+		};
+
+		assert_eq!(simulate_calls(42), simulate_calls(42));
+		assert_ne!(simulate_calls(42), simulate_calls(43));
+	}
+
+	fn ensure_scenarios_succeed(
+		seeds: Range<u64>,
+		chaos_params: ChaosParams,
+		relayers: usize,
+	) -> Vec<SimulationReport> {
+		let mut reports = Vec::new();
+		let mut failed = Vec::new();
+		for seed in seeds {
+			let report = simulate(Scenario::generate(seed), chaos_params.clone(), relayers);
+			if !report.is_completed || !report.violations.is_empty() {
+				failed.push(format!(
+					"seed {}: completed={}, virtual time={}ms, source lane={:?}, target lane={:?}, \
+					rewards={:?}, stats={:?}, violations={:?}, scenario={:?}",
+					report.scenario.seed,
+					report.is_completed,
+					report.virtual_time,
+					report.source_lane,
+					report.target_lane,
+					report.rewards,
+					report.stats,
+					report.violations,
+					report.scenario,
+				));
+			}
+			reports.push(report);
+		}
+
+		assert!(failed.is_empty(), "Failed simulated scenarios:\n{}", failed.join("\n"));
+		reports
+	}
+
+	#[test]
+	fn message_lane_loop_survives_simulated_scenarios() {
+		let first_seed = env_var_or("MESSAGES_RELAY_SIMULATION_SEED", 0);
+		let scenarios = env_var_or("MESSAGES_RELAY_SIMULATION_SCENARIOS", DEFAULT_SCENARIOS);
+		ensure_scenarios_succeed(first_seed..first_seed + scenarios, Default::default(), 1);
+	}
+
+	#[test]
+	fn message_lane_loop_survives_chaos() {
+		ensure_scenarios_succeed(
+			0..4,
+			ChaosParams {
+				drop_submission_probability: 0.2,
+				proof_delay: Duration::from_millis(1),
+				disconnect_probability: 0.05,
+			},
+			1,
+		);
+	}
+
+	#[test]
+	fn two_relayers_are_serving_the_same_lane() {
+		let reports = ensure_scenarios_succeed(0..8, Default::default(), 2);
+
+		// no message is lost and every confirmed message is rewarded once (checked by the simulation)
+		for report in &reports {
+			assert_eq!(report.target_lane.latest_nonce, report.scenario.messages);
+			assert_eq!(report.rewards.values().sum::<MessageNonce>(), report.scenario.messages,);
+		}
+
+		// relayers are actually racing: both are rewarded and duplicate deliveries are rejected
+		assert!(reports.iter().any(|report| report.rewards.len() == 2));
+		assert!(reports.iter().any(|report| report.stats.duplicate_deliveries != 0));
+	}
+}
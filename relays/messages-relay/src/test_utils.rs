@@ -0,0 +1,264 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Utilities for the messages relay unit tests.
+//!
+//! - `TestClock` is the clock that only moves when the test asks it to, so time-dependent
+//!   logic may be tested without sleeping;
+//! - `TestKeyring` provides deterministic relayer accounts at both test chains;
+//! - `TestClientsBuilder` builds mock source and target clients that share the same state.
+
+use crate::clock::Clock;
+use crate::message_lane_loop::tests::{
+	TestClientData, TestSourceClient, TestSourceHeaderNumber, TestTargetClient, TestTargetHeaderNumber, TestTick,
+};
+
+use bp_message_lane::MessageNonce;
+use parking_lot::Mutex;
+use relay_utils::HeaderId;
+use std::{
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+/// Account id at the test source chain.
+pub type TestSourceAccountId = u64;
+/// Account id at the test target chain.
+pub type TestTargetAccountId = u64;
+
+/// First account id of the keyring at the test source chain.
+const SOURCE_ACCOUNTS_BASE: TestSourceAccountId = 1_000;
+/// First account id of the keyring at the test target chain. Accounts at different chains are
+/// never equal, so tests would catch accounts of wrong chain.
+const TARGET_ACCOUNTS_BASE: TestTargetAccountId = 2_000;
+
+/// Clock that is only moving when the test advances it.
+#[derive(Clone)]
+pub struct TestClock {
+	start: Instant,
+	elapsed: Arc<Mutex<Duration>>,
+}
+
+impl TestClock {
+	/// Create new clock.
+	pub fn new() -> Self {
+		TestClock {
+			start: Instant::now(),
+			elapsed: Arc::new(Mutex::new(Duration::from_secs(0))),
+		}
+	}
+
+	/// Move the clock (and all its clones) forward.
+	pub fn advance(&self, by: Duration) {
+		*self.elapsed.lock() += by;
+	}
+}
+
+impl Default for TestClock {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Clock for TestClock {
+	fn now(&self) -> Instant {
+		self.start + *self.elapsed.lock()
+	}
+}
+
+/// Deterministic relayers keyring of both test chains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TestKeyring {
+	Alice,
+	Bob,
+	Charlie,
+	Dave,
+}
+
+impl TestKeyring {
+	/// All keyring relayers.
+	pub const ALL: [TestKeyring; 4] = [
+		TestKeyring::Alice,
+		TestKeyring::Bob,
+		TestKeyring::Charlie,
+		TestKeyring::Dave,
+	];
+
+	/// Return relayer account at the source chain.
+	pub fn source_account(self) -> TestSourceAccountId {
+		SOURCE_ACCOUNTS_BASE + self as TestSourceAccountId
+	}
+
+	/// Return relayer account at the target chain.
+	pub fn target_account(self) -> TestTargetAccountId {
+		TARGET_ACCOUNTS_BASE + self as TestTargetAccountId
+	}
+
+	/// Return relayer that owns given source chain account.
+	pub fn from_source_account(account: TestSourceAccountId) -> Option<Self> {
+		Self::ALL
+			.iter()
+			.copied()
+			.find(|relayer| relayer.source_account() == account)
+	}
+
+	/// Return relayer that owns given target chain account.
+	pub fn from_target_account(account: TestTargetAccountId) -> Option<Self> {
+		Self::ALL
+			.iter()
+			.copied()
+			.find(|relayer| relayer.target_account() == account)
+	}
+}
+
+impl Default for TestKeyring {
+	fn default() -> Self {
+		TestKeyring::Alice
+	}
+}
+
+impl Default for TestClientsBuilder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Mock source and target clients that are sharing the same state.
+pub struct TestClients {
+	/// Source client.
+	pub source: TestSourceClient,
+	/// Target client.
+	pub target: TestTargetClient,
+	/// State, shared by both clients.
+	pub data: Arc<Mutex<TestClientData>>,
+}
+
+/// Builder of mock clients.
+pub struct TestClientsBuilder {
+	data: TestClientData,
+	relayer: TestKeyring,
+	source_tick: TestTick,
+	target_tick: TestTick,
+}
+
+impl TestClientsBuilder {
+	/// Start building clients with default (empty) state.
+	pub fn new() -> Self {
+		Self::with_data(Default::default())
+	}
+
+	/// Start building clients with given state.
+	///
+	/// Transactions are signed by `TestKeyring::Alice` unless other relayer is selected.
+	pub fn with_data(data: TestClientData) -> Self {
+		TestClientsBuilder {
+			data,
+			relayer: Default::default(),
+			source_tick: Arc::new(|_| {}),
+			target_tick: Arc::new(|_| {}),
+		}
+	}
+
+	/// Set best block of the source chain.
+	pub fn source_best_block(mut self, number: TestSourceHeaderNumber) -> Self {
+		self.data.source_state.best_self = HeaderId(number, number);
+		self
+	}
+
+	/// Set best block of the target chain.
+	pub fn target_best_block(mut self, number: TestTargetHeaderNumber) -> Self {
+		self.data.target_state.best_self = HeaderId(number, number);
+		self
+	}
+
+	/// Set nonce of the latest message, generated at the source chain.
+	pub fn source_latest_generated_nonce(mut self, nonce: MessageNonce) -> Self {
+		self.data.source_latest_generated_nonce = nonce;
+		self
+	}
+
+	/// Set nonce of the latest message, received by the target chain.
+	pub fn target_latest_received_nonce(mut self, nonce: MessageNonce) -> Self {
+		self.data.target_latest_received_nonce = nonce;
+		self
+	}
+
+	/// Set relayer that is signing transactions at both chains.
+	pub fn relayer(mut self, relayer: TestKeyring) -> Self {
+		self.relayer = relayer;
+		self
+	}
+
+	/// Set callback that is called before every source client call.
+	pub fn on_source_call(mut self, tick: impl Fn(&mut TestClientData) + Send + Sync + 'static) -> Self {
+		self.source_tick = Arc::new(tick);
+		self
+	}
+
+	/// Set callback that is called before every target client call.
+	pub fn on_target_call(mut self, tick: impl Fn(&mut TestClientData) + Send + Sync + 'static) -> Self {
+		self.target_tick = Arc::new(tick);
+		self
+	}
+
+	/// Build clients.
+	pub fn build(self) -> TestClients {
+		let data = Arc::new(Mutex::new(self.data));
+		TestClients {
+			source: TestSourceClient {
+				data: data.clone(),
+				tick: self.source_tick,
+				relayer: self.relayer,
+			},
+			target: TestTargetClient {
+				data: data.clone(),
+				tick: self.target_tick,
+				relayer: self.relayer,
+			},
+			data,
+		}
+	}
+}
+
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_clock_only_moves_when_advanced() {
+		let clock = TestClock::new();
+		let start = clock.now();
+		assert_eq!(clock.now(), start);
+
+		clock.clone().advance(Duration::from_secs(10));
+		assert_eq!(clock.now(), start + Duration::from_secs(10));
+	}
+
+	#[test]
+	fn keyring_accounts_are_unique_at_both_chains() {
+		for relayer in TestKeyring::ALL.iter().copied() {
+			assert_eq!(
+				TestKeyring::from_source_account(relayer.source_account()),
+				Some(relayer)
+			);
+			assert_eq!(
+				TestKeyring::from_target_account(relayer.target_account()),
+				Some(relayer)
+			);
+			assert_eq!(TestKeyring::from_source_account(relayer.target_account()), None);
+			assert_eq!(TestKeyring::from_target_account(relayer.source_account()), None);
+		}
+	}
+}
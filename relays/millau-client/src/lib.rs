@@ -16,17 +16,25 @@
 
 //! Types used to connect to the Millau-Substrate chain.
 
+use async_trait::async_trait;
 use codec::Encode;
 use headers_relay::sync_types::SourceHeader;
-use relay_substrate_client::{Chain, ChainBase, ChainWithBalances, Client, TransactionSignScheme};
+use relay_substrate_client::{
+	Chain, ChainBase, ChainWithBalances, Client, RemoteSigner, TransactionEraOf, TransactionSignScheme,
+	TransactionSigner,
+};
 use sp_core::{storage::StorageKey, Pair};
 use sp_runtime::{
 	generic::SignedPayload,
 	traits::{Header as HeaderT, IdentifyAccount},
 };
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
+
+pub use millau_runtime::{BridgeRialtoCall, BridgeWestendCall};
 
-pub use millau_runtime::BridgeRialtoCall;
+/// Millau transactions signer.
+pub type AccountSigner =
+	dyn TransactionSigner<Public = sp_core::sr25519::Public, Signature = sp_core::sr25519::Signature>;
 
 /// Millau header id.
 pub type HeaderId = relay_utils::HeaderId<millau_runtime::Hash, millau_runtime::BlockNumber>;
@@ -63,43 +71,41 @@ impl ChainWithBalances for Millau {
 	}
 }
 
+#[async_trait]
 impl TransactionSignScheme for Millau {
 	type Chain = Millau;
-	type AccountKeyPair = sp_core::sr25519::Pair;
+	type AccountSigner = AccountSigner;
 	type SignedTransaction = millau_runtime::UncheckedExtrinsic;
 
-	fn sign_transaction(
+	async fn sign_transaction(
 		client: &Client<Self>,
-		signer: &Self::AccountKeyPair,
+		signer: &Self::AccountSigner,
+		era: TransactionEraOf<Self::Chain>,
 		signer_nonce: <Self::Chain as Chain>::Index,
 		call: <Self::Chain as Chain>::Call,
-	) -> Self::SignedTransaction {
+	) -> relay_substrate_client::Result<Self::SignedTransaction> {
 		let raw_payload = SignedPayload::from_raw(
 			call,
-			(
-				frame_system::CheckSpecVersion::<millau_runtime::Runtime>::new(),
-				frame_system::CheckTxVersion::<millau_runtime::Runtime>::new(),
-				frame_system::CheckGenesis::<millau_runtime::Runtime>::new(),
-				frame_system::CheckEra::<millau_runtime::Runtime>::from(sp_runtime::generic::Era::Immortal),
-				frame_system::CheckNonce::<millau_runtime::Runtime>::from(signer_nonce),
-				frame_system::CheckWeight::<millau_runtime::Runtime>::new(),
-				pallet_transaction_payment::ChargeTransactionPayment::<millau_runtime::Runtime>::from(0),
-			),
-			(
+			bp_millau::signed_extensions::<millau_runtime::Runtime>(era.frame_era(), signer_nonce, 0),
+			bp_millau::additional_signed(
 				millau_runtime::VERSION.spec_version,
 				millau_runtime::VERSION.transaction_version,
 				*client.genesis_hash(),
-				*client.genesis_hash(),
-				(),
-				(),
-				(),
+				era.signed_payload(*client.genesis_hash()),
 			),
 		);
-		let signature = raw_payload.using_encoded(|payload| signer.sign(payload));
+		let signature = signer
+			.sign(raw_payload.using_encoded(|payload| payload.to_vec()))
+			.await?;
 		let signer: sp_runtime::MultiSigner = signer.public().into();
 		let (call, extra, _) = raw_payload.deconstruct();
 
-		millau_runtime::UncheckedExtrinsic::new_signed(call, signer.into_account(), signature.into(), extra)
+		Ok(millau_runtime::UncheckedExtrinsic::new_signed(
+			call,
+			signer.into_account(),
+			signature.into(),
+			extra,
+		))
 	}
 }
 
@@ -107,16 +113,62 @@ impl TransactionSignScheme for Millau {
 #[derive(Clone)]
 pub struct SigningParams {
 	/// Substrate transactions signer.
-	pub signer: sp_core::sr25519::Pair,
+	pub signer: Arc<AccountSigner>,
+	/// Transactions mortality period (in blocks). If `None`, transactions are immortal.
+	pub transactions_mortality: Option<u32>,
+	/// Account on behalf of which the signer is submitting calls. If set, all calls are wrapped
+	/// into `proxy.proxy` call, so the signer only needs to be a (restricted) proxy of this account.
+	pub proxied_account: Option<millau_runtime::AccountId>,
 }
 
 impl SigningParams {
 	/// Create signing params from SURI and password.
 	pub fn from_suri(suri: &str, password: Option<&str>) -> Result<Self, sp_core::crypto::SecretStringError> {
 		Ok(SigningParams {
-			signer: sp_core::sr25519::Pair::from_string(suri, password)?,
+			signer: Arc::new(sp_core::sr25519::Pair::from_string(suri, password)?),
+			transactions_mortality: None,
+			proxied_account: None,
+		})
+	}
+
+	/// Create signing params that are using remote signing service.
+	pub async fn from_remote_signer(
+		uri: &str,
+		public: sp_core::sr25519::Public,
+	) -> Result<Self, relay_substrate_client::Error> {
+		Ok(SigningParams {
+			signer: Arc::new(RemoteSigner::<_, sp_core::sr25519::Signature>::new(uri, public).await?),
+			transactions_mortality: None,
+			proxied_account: None,
 		})
 	}
+
+	/// Account that is the origin of all calls, submitted using these params.
+	pub fn origin_account(&self) -> millau_runtime::AccountId {
+		self.proxied_account
+			.clone()
+			.unwrap_or_else(|| self.signer.public().as_array_ref().clone().into())
+	}
+
+	/// Wrap call into `proxy.proxy` call if the signer is acting as a proxy of other account.
+	pub fn wrap_call(&self, call: millau_runtime::Call) -> millau_runtime::Call {
+		match self.proxied_account {
+			Some(ref real) => millau_runtime::ProxyCall::proxy(real.clone(), None, Box::new(call)).into(),
+			None => call,
+		}
+	}
+
+	/// Wrap calls into single call. If there are several calls, they're wrapped into
+	/// `utility.batch_all` call, so either all calls succeed, or all are reverted. The resulting
+	/// call is then wrapped into `proxy.proxy` call if the signer is acting as a proxy.
+	pub fn wrap_calls(&self, mut calls: Vec<millau_runtime::Call>) -> millau_runtime::Call {
+		let call = if calls.len() == 1 {
+			calls.remove(0)
+		} else {
+			millau_runtime::UtilityCall::batch_all(calls).into()
+		};
+		self.wrap_call(call)
+	}
 }
 
 impl std::fmt::Debug for SigningParams {
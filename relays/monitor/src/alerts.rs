@@ -0,0 +1,343 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Alerts and their delivery.
+//!
+//! Fired and resolved alerts may be pushed to the generic webhook and/or to the Prometheus
+//! Alertmanager. Both receive alerts in the same format: webhook receives only alerts that have
+//! been fired or resolved since the previous push, and Alertmanager receives all firing alerts
+//! on every push, as it expects.
+
+use serde::Serialize;
+use std::{
+	collections::BTreeMap,
+	time::{Duration, SystemTime},
+};
+
+/// Alert severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+	/// Something needs attention, but the bridge still works.
+	Warning,
+	/// The bridge doesn't work.
+	Critical,
+}
+
+impl Severity {
+	/// Return severity label value.
+	fn as_str(&self) -> &'static str {
+		match *self {
+			Severity::Warning => "warning",
+			Severity::Critical => "critical",
+		}
+	}
+}
+
+/// Single alert.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+	/// Alert name.
+	pub name: &'static str,
+	/// Alert severity.
+	pub severity: Severity,
+	/// Labels that (along with the name) are identifying the alert.
+	pub labels: BTreeMap<&'static str, String>,
+	/// Human-readable description of the alert.
+	pub summary: String,
+}
+
+impl Alert {
+	/// Create new alert without labels.
+	pub fn new(name: &'static str, severity: Severity, summary: String) -> Self {
+		Alert {
+			name,
+			severity,
+			labels: BTreeMap::new(),
+			summary,
+		}
+	}
+
+	/// Add label to the alert.
+	pub fn with_label(mut self, name: &'static str, value: impl Into<String>) -> Self {
+		self.labels.insert(name, value.into());
+		self
+	}
+
+	/// Return key that is identifying the alert.
+	fn key(&self) -> AlertKey {
+		(self.name, self.labels.clone())
+	}
+}
+
+/// Key that is identifying the alert.
+type AlertKey = (&'static str, BTreeMap<&'static str, String>);
+
+/// Alert that is (or has been) firing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertState {
+	/// The alert itself.
+	pub alert: Alert,
+	/// Time when the alert has been fired.
+	pub starts_at: SystemTime,
+	/// Time when the alert has been resolved.
+	pub ends_at: Option<SystemTime>,
+}
+
+/// Changes of alerts after rules evaluation.
+#[derive(Debug, Default)]
+pub struct AlertsUpdate {
+	/// Alerts that have been fired.
+	pub fired: Vec<AlertState>,
+	/// Alerts that have been resolved.
+	pub resolved: Vec<AlertState>,
+	/// All firing alerts (including just fired).
+	pub firing: Vec<AlertState>,
+}
+
+/// Tracker of firing alerts.
+#[derive(Debug, Default)]
+pub struct AlertsTracker {
+	/// Alerts that are currently firing.
+	firing: BTreeMap<AlertKey, AlertState>,
+}
+
+impl AlertsTracker {
+	/// Update firing alerts with alerts from the latest rules evaluation. Alerts that are no
+	/// longer firing are resolved.
+	pub fn update(&mut self, alerts: Vec<Alert>, now: SystemTime) -> AlertsUpdate {
+		let mut update = AlertsUpdate::default();
+		let mut firing = BTreeMap::new();
+		for alert in alerts {
+			let key = alert.key();
+			let state = match self.firing.remove(&key) {
+				Some(state) => AlertState { alert, ..state },
+				None => {
+					let state = AlertState {
+						alert,
+						starts_at: now,
+						ends_at: None,
+					};
+					update.fired.push(state.clone());
+					state
+				}
+			};
+			firing.insert(key, state);
+		}
+
+		update.resolved = std::mem::replace(&mut self.firing, firing)
+			.into_iter()
+			.map(|(_, state)| AlertState {
+				ends_at: Some(now),
+				..state
+			})
+			.collect();
+		update.firing = self.firing.values().cloned().collect();
+		update
+	}
+}
+
+/// Destination of alerts.
+#[derive(Debug, Clone)]
+pub enum AlertsSink {
+	/// Generic webhook that receives fired and resolved alerts.
+	Webhook(String),
+	/// Prometheus Alertmanager that receives all firing and resolved alerts.
+	Alertmanager(String),
+}
+
+impl AlertsSink {
+	/// Push alerts update to the sink.
+	pub async fn push(&self, update: &AlertsUpdate) -> Result<(), String> {
+		match *self {
+			AlertsSink::Webhook(ref url) => {
+				if update.fired.is_empty() && update.resolved.is_empty() {
+					return Ok(());
+				}
+
+				let alerts = update.fired.iter().chain(update.resolved.iter());
+				post_json(
+					url,
+					&WebhookPayload {
+						alerts: encode_alerts(alerts),
+					},
+				)
+				.await
+			}
+			AlertsSink::Alertmanager(ref url) => {
+				if update.firing.is_empty() && update.resolved.is_empty() {
+					return Ok(());
+				}
+
+				let alerts = update.firing.iter().chain(update.resolved.iter());
+				post_json(
+					&format!("{}/api/v2/alerts", url.trim_end_matches('/')),
+					&encode_alerts(alerts),
+				)
+				.await
+			}
+		}
+	}
+}
+
+/// Payload of the webhook request.
+#[derive(Debug, Serialize)]
+struct WebhookPayload {
+	/// Fired and resolved alerts.
+	alerts: Vec<EncodedAlert>,
+}
+
+/// Alert, encoded using Alertmanager format.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EncodedAlert {
+	/// Either `firing`, or `resolved`.
+	status: &'static str,
+	/// Alert labels, including `alertname` and `severity`.
+	labels: BTreeMap<&'static str, String>,
+	/// Alert annotations.
+	annotations: BTreeMap<&'static str, String>,
+	/// RFC3339-encoded time when alert has been fired.
+	starts_at: String,
+	/// RFC3339-encoded time when alert has been resolved.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	ends_at: Option<String>,
+}
+
+/// Encode alerts using Alertmanager format.
+fn encode_alerts<'a>(alerts: impl Iterator<Item = &'a AlertState>) -> Vec<EncodedAlert> {
+	alerts
+		.map(|state| {
+			let mut labels = state.alert.labels.clone();
+			labels.insert("alertname", state.alert.name.into());
+			labels.insert("severity", state.alert.severity.as_str().into());
+
+			let mut annotations = BTreeMap::new();
+			annotations.insert("summary", state.alert.summary.clone());
+
+			EncodedAlert {
+				status: if state.ends_at.is_some() { "resolved" } else { "firing" },
+				labels,
+				annotations,
+				starts_at: encode_time(state.starts_at),
+				ends_at: state.ends_at.map(encode_time),
+			}
+		})
+		.collect()
+}
+
+/// Encode time using RFC3339 format.
+fn encode_time(time: SystemTime) -> String {
+	let since_epoch = time
+		.duration_since(SystemTime::UNIX_EPOCH)
+		.unwrap_or_else(|_| Duration::from_secs(0));
+	time::OffsetDateTime::from_unix_timestamp(since_epoch.as_secs() as i64).format(time::Format::Rfc3339)
+}
+
+/// Send JSON-encoded payload to given URL.
+async fn post_json(url: &str, payload: &impl Serialize) -> Result<(), String> {
+	let body = surf::Body::from_json(payload).map_err(|e| format!("Failed to encode alerts: {}", e))?;
+	let response = surf::post(url)
+		.body(body)
+		.await
+		.map_err(|e| format!("Failed to push alerts to {}: {}", url, e))?;
+	if !response.status().is_success() {
+		return Err(format!("Failed to push alerts to {}: {}", url, response.status()));
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn alert(name: &'static str, lane: &str) -> Alert {
+		Alert::new(name, Severity::Warning, format!("{} at {}", name, lane)).with_label("lane", lane)
+	}
+
+	fn at(secs: u64) -> SystemTime {
+		SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+	}
+
+	#[test]
+	fn alerts_are_fired_and_resolved() {
+		let mut tracker = AlertsTracker::default();
+
+		let update = tracker.update(vec![alert("A", "00000000"), alert("A", "00000001")], at(1));
+		assert_eq!(update.fired.len(), 2);
+		assert!(update.resolved.is_empty());
+		assert_eq!(update.firing, update.fired);
+
+		let update = tracker.update(vec![alert("A", "00000001"), alert("B", "00000001")], at(2));
+		assert_eq!(
+			update.fired,
+			vec![AlertState {
+				alert: alert("B", "00000001"),
+				starts_at: at(2),
+				ends_at: None,
+			}],
+		);
+		assert_eq!(
+			update.resolved,
+			vec![AlertState {
+				alert: alert("A", "00000000"),
+				starts_at: at(1),
+				ends_at: Some(at(2)),
+			}],
+		);
+		assert_eq!(
+			update.firing.iter().map(|state| state.starts_at).collect::<Vec<_>>(),
+			vec![at(1), at(2)],
+		);
+
+		let update = tracker.update(Vec::new(), at(3));
+		assert!(update.fired.is_empty());
+		assert_eq!(update.resolved.len(), 2);
+		assert!(update.firing.is_empty());
+	}
+
+	#[test]
+	fn alerts_are_encoded_using_alertmanager_format() {
+		let firing = AlertState {
+			alert: alert("A", "00000000"),
+			starts_at: at(1_600_000_000),
+			ends_at: None,
+		};
+		let resolved = AlertState {
+			ends_at: Some(at(1_600_000_060)),
+			..firing.clone()
+		};
+
+		assert_eq!(
+			serde_json::to_value(encode_alerts(vec![firing, resolved].iter())).unwrap(),
+			serde_json::json!([
+				{
+					"status": "firing",
+					"labels": { "alertname": "A", "lane": "00000000", "severity": "warning" },
+					"annotations": { "summary": "A at 00000000" },
+					"startsAt": "2020-09-13T12:26:40+00:00",
+				},
+				{
+					"status": "resolved",
+					"labels": { "alertname": "A", "lane": "00000000", "severity": "warning" },
+					"annotations": { "summary": "A at 00000000" },
+					"startsAt": "2020-09-13T12:26:40+00:00",
+					"endsAt": "2020-09-13T12:27:40+00:00",
+				},
+			]),
+		);
+	}
+}
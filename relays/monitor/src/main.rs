@@ -0,0 +1,270 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Monitor of Millau <-> Rialto bridge lanes.
+//!
+//! Periodically reads states of the configured lanes from both chains, evaluates alerting rules
+//! (lane lag, header lag, halted pallets, relayer balances) and pushes fired and resolved alerts
+//! to the webhook and/or Alertmanager. The monitor only reads chains state, so it keeps working
+//! (and alerting) when relays are unhealthy.
+
+#![warn(missing_docs)]
+
+mod alerts;
+mod rules;
+
+use crate::alerts::{AlertsSink, AlertsTracker};
+use crate::rules::{LaneHistory, Thresholds};
+
+use bp_message_lane::LaneId;
+use relay_millau_client::Millau;
+use relay_rialto_client::Rialto;
+use relay_substrate_client::{Chain, ConnectionParams};
+use relay_utils::initialize::initialize_relay;
+use std::{
+	collections::BTreeMap,
+	time::{Duration, Instant, SystemTime},
+};
+use structopt::StructOpt;
+use substrate_relay::{lane_diagnostics, MillauClient, RialtoClient};
+
+/// Monitor CLI args.
+#[derive(StructOpt)]
+#[structopt(about = "Millau <-> Rialto bridge monitor")]
+struct Options {
+	/// Connect to Millau node at given host.
+	#[structopt(long)]
+	millau_host: String,
+	/// Connect to Millau node websocket server at given port.
+	#[structopt(long)]
+	millau_port: u16,
+	/// Connect to Rialto node at given host.
+	#[structopt(long)]
+	rialto_host: String,
+	/// Connect to Rialto node websocket server at given port.
+	#[structopt(long)]
+	rialto_port: u16,
+	/// Millau -> Rialto lane to monitor. May be specified multiple times.
+	#[structopt(long)]
+	millau_to_rialto_lane: Vec<LaneId>,
+	/// Rialto -> Millau lane to monitor. May be specified multiple times.
+	#[structopt(long)]
+	rialto_to_millau_lane: Vec<LaneId>,
+	/// SS58-encoded Millau account of the relayer, which balance is monitored.
+	#[structopt(long)]
+	millau_relayer: Option<bp_millau::AccountId>,
+	/// SS58-encoded Rialto account of the relayer, which balance is monitored.
+	#[structopt(long)]
+	rialto_relayer: Option<bp_rialto::AccountId>,
+	/// Alert if Millau relayer balance is below given value. Defaults to the existential deposit.
+	#[structopt(long)]
+	min_millau_relayer_balance: Option<bp_millau::Balance>,
+	/// Alert if Rialto relayer balance is below given value. Defaults to the existential deposit.
+	#[structopt(long)]
+	min_rialto_relayer_balance: Option<bp_rialto::Balance>,
+	/// Alert if there are more undelivered messages at the lane.
+	#[structopt(long, default_value = "128")]
+	max_undelivered_messages: bp_message_lane::MessageNonce,
+	/// Alert if there are more delivered, but unconfirmed messages at the lane.
+	#[structopt(long, default_value = "128")]
+	max_unconfirmed_messages: bp_message_lane::MessageNonce,
+	/// Alert if lane has pending messages (or confirmations), but there has been no progress for
+	/// given number of seconds.
+	#[structopt(long, default_value = "600")]
+	max_lane_stall: u64,
+	/// Alert if chain is lagging more than given number of blocks behind the bridged chain
+	/// finality.
+	#[structopt(long, default_value = "32")]
+	max_header_lag: u64,
+	/// Interval (in seconds) between rules evaluations. It must be less than the Alertmanager
+	/// `resolve_timeout`, otherwise firing alerts will be resolved by the Alertmanager.
+	#[structopt(long, default_value = "60")]
+	interval: u64,
+	/// Push fired and resolved alerts to given URL. May be specified multiple times.
+	#[structopt(long)]
+	webhook_url: Vec<String>,
+	/// Push firing and resolved alerts to Alertmanager at given URL. May be specified multiple
+	/// times.
+	#[structopt(long)]
+	alertmanager_url: Vec<String>,
+}
+
+fn main() {
+	initialize_relay();
+
+	let result = async_std::task::block_on(run(Options::from_args()));
+	if let Err(error) = result {
+		log::error!(target: "bridge", "Monitor has failed: {}", error);
+		std::process::exit(1);
+	}
+}
+
+async fn run(options: Options) -> Result<(), String> {
+	if options.millau_to_rialto_lane.is_empty() && options.rialto_to_millau_lane.is_empty() {
+		return Err("At least one lane must be specified".into());
+	}
+	if options.webhook_url.is_empty() && options.alertmanager_url.is_empty() {
+		log::warn!(
+			target: "bridge",
+			"Neither webhook, nor Alertmanager URL is specified. Alerts will only be logged",
+		);
+	}
+
+	let mut millau_client = MillauClient::new(ConnectionParams {
+		host: options.millau_host.clone(),
+		port: options.millau_port,
+		..Default::default()
+	})
+	.await?;
+	let mut rialto_client = RialtoClient::new(ConnectionParams {
+		host: options.rialto_host.clone(),
+		port: options.rialto_port,
+		..Default::default()
+	})
+	.await?;
+
+	let sinks = options
+		.webhook_url
+		.iter()
+		.cloned()
+		.map(AlertsSink::Webhook)
+		.chain(options.alertmanager_url.iter().cloned().map(AlertsSink::Alertmanager))
+		.collect::<Vec<_>>();
+	let millau_to_rialto_thresholds = Thresholds {
+		max_undelivered_messages: options.max_undelivered_messages,
+		max_unconfirmed_messages: options.max_unconfirmed_messages,
+		max_lane_stall: Duration::from_secs(options.max_lane_stall),
+		max_header_lag: options.max_header_lag,
+		min_source_relayer_balance: options.min_millau_relayer_balance.map(Into::into),
+		min_target_relayer_balance: options.min_rialto_relayer_balance,
+	};
+	let rialto_to_millau_thresholds = Thresholds {
+		min_source_relayer_balance: millau_to_rialto_thresholds.min_target_relayer_balance,
+		min_target_relayer_balance: millau_to_rialto_thresholds.min_source_relayer_balance,
+		..millau_to_rialto_thresholds.clone()
+	};
+
+	let mut histories = BTreeMap::new();
+	let mut tracker = AlertsTracker::default();
+	loop {
+		let mut alerts = Vec::new();
+		alerts.extend(check_node(&mut millau_client).await);
+		alerts.extend(check_node(&mut rialto_client).await);
+
+		for lane in &options.millau_to_rialto_lane {
+			let diagnostics = lane_diagnostics::millau_to_rialto(
+				millau_client.clone(),
+				rialto_client.clone(),
+				*lane,
+				options.millau_relayer.clone(),
+				options.rialto_relayer.clone(),
+				None,
+			)
+			.await;
+			alerts.extend(evaluate_lane(
+				&mut histories,
+				Millau::NAME,
+				Rialto::NAME,
+				*lane,
+				diagnostics,
+				&millau_to_rialto_thresholds,
+			));
+		}
+		for lane in &options.rialto_to_millau_lane {
+			let diagnostics = lane_diagnostics::rialto_to_millau(
+				rialto_client.clone(),
+				millau_client.clone(),
+				*lane,
+				options.rialto_relayer.clone(),
+				options.millau_relayer.clone(),
+				None,
+			)
+			.await;
+			alerts.extend(evaluate_lane(
+				&mut histories,
+				Rialto::NAME,
+				Millau::NAME,
+				*lane,
+				diagnostics,
+				&rialto_to_millau_thresholds,
+			));
+		}
+
+		let update = tracker.update(alerts, SystemTime::now());
+		for state in &update.fired {
+			log::warn!(target: "bridge", "Alert {} is firing: {}", state.alert.name, state.alert.summary);
+		}
+		for state in &update.resolved {
+			log::info!(target: "bridge", "Alert {} is resolved: {}", state.alert.name, state.alert.summary);
+		}
+		for sink in &sinks {
+			if let Err(error) = sink.push(&update).await {
+				log::error!(target: "bridge", "{}", error);
+			}
+		}
+
+		async_std::task::sleep(Duration::from_secs(options.interval)).await;
+	}
+}
+
+/// Check that the node is responding. If it isn't, try to reconnect and return alert if it has
+/// failed.
+async fn check_node<C: Chain>(client: &mut relay_substrate_client::Client<C>) -> Option<alerts::Alert> {
+	let error = match client.best_finalized_header_hash().await {
+		Ok(_) => return None,
+		Err(error) => error,
+	};
+
+	log::warn!(
+		target: "bridge",
+		"{} node is not responding: {:?}. Reconnecting",
+		C::NAME,
+		error,
+	);
+	match client.clone().reconnect().await {
+		Ok(reconnected_client) => *client = reconnected_client,
+		Err(error) => log::warn!(target: "bridge", "Failed to reconnect to {} node: {:?}", C::NAME, error),
+	}
+
+	Some(rules::node_unavailable_alert(C::NAME, &format!("{:?}", error)))
+}
+
+/// Evaluate rules against the lane state.
+fn evaluate_lane(
+	histories: &mut BTreeMap<(&'static str, LaneId), LaneHistory>,
+	source: &'static str,
+	target: &'static str,
+	lane: LaneId,
+	diagnostics: Result<lane_diagnostics::LaneDiagnostics, String>,
+	thresholds: &Thresholds,
+) -> Vec<alerts::Alert> {
+	let diagnostics = match diagnostics {
+		Ok(diagnostics) => diagnostics,
+		Err(error) => {
+			return vec![rules::lane_state_unavailable_alert(
+				source,
+				target,
+				&lane.to_string(),
+				&error,
+			)]
+		}
+	};
+
+	let now = Instant::now();
+	let history = histories.entry((source, lane)).or_default();
+	history.update(&diagnostics, now);
+	rules::evaluate_lane(&diagnostics, history, thresholds, now)
+}
@@ -0,0 +1,452 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Alerting rules.
+//!
+//! Rules are evaluated against the lane state that is read from both chains of the bridge. Rules
+//! that are detecting stalled lanes also need previous states of the lane, which are kept by the
+//! `LaneHistory`.
+
+use crate::alerts::{Alert, Severity};
+
+use bp_message_lane::MessageNonce;
+use std::time::{Duration, Instant};
+use substrate_relay::lane_diagnostics::{LaneDiagnostics, LaneProblem};
+
+/// Thresholds of alerting rules.
+#[derive(Debug, Clone)]
+pub struct Thresholds {
+	/// Maximal number of messages that are generated at the source chain, but not yet delivered
+	/// to the target chain.
+	pub max_undelivered_messages: MessageNonce,
+	/// Maximal number of messages that are delivered to the target chain, but their delivery is
+	/// not yet confirmed at the source chain.
+	pub max_unconfirmed_messages: MessageNonce,
+	/// Maximal time during which lane may have pending messages (or confirmations) without any
+	/// progress.
+	pub max_lane_stall: Duration,
+	/// Maximal number of finalized blocks of the bridged chain that are not yet known to the chain.
+	pub max_header_lag: u64,
+	/// Minimal balance of the relayer account at the source chain. If not specified, the minimal
+	/// balance that is required to pay for transactions is used.
+	pub min_source_relayer_balance: Option<u128>,
+	/// Minimal balance of the relayer account at the target chain. If not specified, the minimal
+	/// balance that is required to pay for transactions is used.
+	pub min_target_relayer_balance: Option<u128>,
+}
+
+/// Previous states of the lane.
+#[derive(Debug, Default)]
+pub struct LaneHistory {
+	/// Latest known nonce of the message, received by the target chain and the time since which
+	/// there are undelivered messages and this nonce has not changed.
+	received: Option<(MessageNonce, Instant)>,
+	/// Latest known nonce of the message, which delivery is confirmed at the source chain and the
+	/// time since which there are unconfirmed messages and this nonce has not changed.
+	confirmed: Option<(MessageNonce, Instant)>,
+}
+
+impl LaneHistory {
+	/// Remember the lane state, observed at given time.
+	pub fn update(&mut self, diagnostics: &LaneDiagnostics, now: Instant) {
+		update_progress(
+			&mut self.received,
+			diagnostics.latest_received_nonce_at_target,
+			diagnostics.latest_generated_nonce > diagnostics.latest_received_nonce_at_target,
+			now,
+		);
+		update_progress(
+			&mut self.confirmed,
+			diagnostics.latest_confirmed_nonce_at_source,
+			diagnostics.latest_received_nonce_at_target > diagnostics.latest_confirmed_nonce_at_source,
+			now,
+		);
+	}
+
+	/// Return time during which there has been no progress in messages delivery.
+	fn delivery_stalled_for(&self, now: Instant) -> Duration {
+		stalled_for(&self.received, now)
+	}
+
+	/// Return time during which there has been no progress in confirmations delivery.
+	fn confirmation_stalled_for(&self, now: Instant) -> Duration {
+		stalled_for(&self.confirmed, now)
+	}
+}
+
+/// Evaluate all rules against the lane state and return alerts that are firing.
+///
+/// The `history` must already be updated with the `diagnostics`.
+pub fn evaluate_lane(
+	diagnostics: &LaneDiagnostics,
+	history: &LaneHistory,
+	thresholds: &Thresholds,
+	now: Instant,
+) -> Vec<Alert> {
+	let lane_alert = |name, severity, summary| {
+		Alert::new(name, severity, summary)
+			.with_label("source", diagnostics.source)
+			.with_label("target", diagnostics.target)
+			.with_label("lane", &diagnostics.lane)
+	};
+	let mut alerts = Vec::new();
+
+	let undelivered_messages = diagnostics
+		.latest_generated_nonce
+		.saturating_sub(diagnostics.latest_received_nonce_at_target);
+	if undelivered_messages > thresholds.max_undelivered_messages {
+		alerts.push(lane_alert(
+			"BridgeLaneUndeliveredMessages",
+			Severity::Warning,
+			format!(
+				"{} messages of {} -> {} lane {} are not delivered (threshold: {})",
+				undelivered_messages,
+				diagnostics.source,
+				diagnostics.target,
+				diagnostics.lane,
+				thresholds.max_undelivered_messages,
+			),
+		));
+	}
+	let delivery_stalled_for = history.delivery_stalled_for(now);
+	if undelivered_messages != 0 && delivery_stalled_for > thresholds.max_lane_stall {
+		alerts.push(lane_alert(
+			"BridgeLaneDeliveryStalled",
+			Severity::Critical,
+			format!(
+				"No messages of {} -> {} lane {} have been delivered for {}s. {} messages are pending",
+				diagnostics.source,
+				diagnostics.target,
+				diagnostics.lane,
+				delivery_stalled_for.as_secs(),
+				undelivered_messages,
+			),
+		));
+	}
+
+	let unconfirmed_messages = diagnostics
+		.latest_received_nonce_at_target
+		.saturating_sub(diagnostics.latest_confirmed_nonce_at_source);
+	if unconfirmed_messages > thresholds.max_unconfirmed_messages {
+		alerts.push(lane_alert(
+			"BridgeLaneUnconfirmedMessages",
+			Severity::Warning,
+			format!(
+				"Delivery of {} messages of {} -> {} lane {} is not confirmed (threshold: {})",
+				unconfirmed_messages,
+				diagnostics.source,
+				diagnostics.target,
+				diagnostics.lane,
+				thresholds.max_unconfirmed_messages,
+			),
+		));
+	}
+	let confirmation_stalled_for = history.confirmation_stalled_for(now);
+	if unconfirmed_messages != 0 && confirmation_stalled_for > thresholds.max_lane_stall {
+		alerts.push(lane_alert(
+			"BridgeLaneConfirmationStalled",
+			Severity::Critical,
+			format!(
+				"No deliveries over {} -> {} lane {} have been confirmed for {}s. {} confirmations are pending",
+				diagnostics.source,
+				diagnostics.target,
+				diagnostics.lane,
+				confirmation_stalled_for.as_secs(),
+				unconfirmed_messages,
+			),
+		));
+	}
+
+	for problem in &diagnostics.problems {
+		if let LaneProblem::NonceGap { details } = problem {
+			alerts.push(lane_alert(
+				"BridgeLaneNonceGap",
+				Severity::Critical,
+				format!("Nonces of lane {} are inconsistent: {}", diagnostics.lane, details),
+			));
+		}
+	}
+
+	alerts.extend(header_lag_alert(
+		diagnostics.target,
+		diagnostics.source,
+		diagnostics.source_best_finalized_block,
+		diagnostics.source_best_finalized_block_at_target,
+		thresholds.max_header_lag,
+	));
+	alerts.extend(header_lag_alert(
+		diagnostics.source,
+		diagnostics.target,
+		diagnostics.target_best_finalized_block,
+		diagnostics.target_best_finalized_block_at_source,
+		thresholds.max_header_lag,
+	));
+
+	alerts.extend(pallet_halted_alert(
+		diagnostics.source,
+		diagnostics.is_source_pallet_halted,
+	));
+	alerts.extend(pallet_halted_alert(
+		diagnostics.target,
+		diagnostics.is_target_pallet_halted,
+	));
+
+	alerts.extend(relayer_balance_alert(
+		diagnostics.source,
+		diagnostics.source_relayer_balance,
+		thresholds
+			.min_source_relayer_balance
+			.unwrap_or(diagnostics.min_source_relayer_balance),
+	));
+	alerts.extend(relayer_balance_alert(
+		diagnostics.target,
+		diagnostics.target_relayer_balance,
+		thresholds
+			.min_target_relayer_balance
+			.unwrap_or(diagnostics.min_target_relayer_balance),
+	));
+
+	alerts
+}
+
+/// Return alert that is firing when the lane state can't be read.
+pub fn lane_state_unavailable_alert(source: &'static str, target: &'static str, lane: &str, error: &str) -> Alert {
+	Alert::new(
+		"BridgeLaneStateUnavailable",
+		Severity::Critical,
+		format!(
+			"Failed to read state of {} -> {} lane {}: {}",
+			source, target, lane, error
+		),
+	)
+	.with_label("source", source)
+	.with_label("target", target)
+	.with_label("lane", lane)
+}
+
+/// Return alert that is firing when the chain node is not responding.
+pub fn node_unavailable_alert(chain: &'static str, error: &str) -> Alert {
+	Alert::new(
+		"BridgeNodeUnavailable",
+		Severity::Critical,
+		format!("{} node is not responding: {}", chain, error),
+	)
+	.with_label("chain", chain)
+}
+
+/// Return alert if the chain is lagging behind the bridged chain finality.
+fn header_lag_alert(
+	chain: &'static str,
+	bridged_chain: &'static str,
+	best_finalized_block: u64,
+	best_finalized_block_at_chain: u64,
+	max_header_lag: u64,
+) -> Option<Alert> {
+	let header_lag = best_finalized_block.saturating_sub(best_finalized_block_at_chain);
+	if header_lag <= max_header_lag {
+		return None;
+	}
+
+	Some(
+		Alert::new(
+			"BridgeHeadersLag",
+			Severity::Warning,
+			format!(
+				"{} knows {} finalized block {}, but the best finalized {} block is {} (threshold: {} blocks)",
+				chain,
+				bridged_chain,
+				best_finalized_block_at_chain,
+				bridged_chain,
+				best_finalized_block,
+				max_header_lag,
+			),
+		)
+		.with_label("chain", chain)
+		.with_label("bridged_chain", bridged_chain),
+	)
+}
+
+/// Return alert if the message lane pallet is halted.
+fn pallet_halted_alert(chain: &'static str, is_halted: bool) -> Option<Alert> {
+	if !is_halted {
+		return None;
+	}
+
+	Some(
+		Alert::new(
+			"BridgePalletHalted",
+			Severity::Critical,
+			format!("Message lane pallet is halted at {}", chain),
+		)
+		.with_label("chain", chain),
+	)
+}
+
+/// Return alert if the relayer balance is below the threshold.
+fn relayer_balance_alert(chain: &'static str, balance: Option<u128>, min_balance: u128) -> Option<Alert> {
+	let balance = balance?;
+	if balance >= min_balance {
+		return None;
+	}
+
+	Some(
+		Alert::new(
+			"BridgeRelayerBalanceLow",
+			Severity::Warning,
+			format!(
+				"Relayer balance at {} is {}, which is below the threshold {}",
+				chain, balance, min_balance,
+			),
+		)
+		.with_label("chain", chain),
+	)
+}
+
+/// Remember the nonce if it has changed since last update, or if there's nothing pending.
+fn update_progress(
+	progress: &mut Option<(MessageNonce, Instant)>,
+	nonce: MessageNonce,
+	has_pending: bool,
+	now: Instant,
+) {
+	match *progress {
+		Some((known_nonce, _)) if known_nonce == nonce && has_pending => (),
+		_ => *progress = Some((nonce, now)),
+	}
+}
+
+/// Return time since the nonce has been changed.
+fn stalled_for(progress: &Option<(MessageNonce, Instant)>, now: Instant) -> Duration {
+	progress
+		.map(|(_, since)| now.saturating_duration_since(since))
+		.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn thresholds() -> Thresholds {
+		Thresholds {
+			max_undelivered_messages: 10,
+			max_unconfirmed_messages: 10,
+			max_lane_stall: Duration::from_secs(60),
+			max_header_lag: 5,
+			min_source_relayer_balance: None,
+			min_target_relayer_balance: Some(1000),
+		}
+	}
+
+	fn healthy_lane() -> LaneDiagnostics {
+		LaneDiagnostics {
+			source: "Millau",
+			target: "Rialto",
+			lane: "00000000".into(),
+			source_best_finalized_block: 100,
+			source_best_finalized_block_at_target: 98,
+			target_best_finalized_block: 200,
+			target_best_finalized_block_at_source: 200,
+			latest_generated_nonce: 20,
+			latest_received_nonce_at_target: 15,
+			latest_confirmed_nonce_at_source: 10,
+			min_source_relayer_balance: 10,
+			source_relayer_balance: Some(100),
+			target_relayer_balance: Some(10_000),
+			..Default::default()
+		}
+	}
+
+	fn alert_names(alerts: Vec<Alert>) -> Vec<&'static str> {
+		alerts.into_iter().map(|alert| alert.name).collect()
+	}
+
+	#[test]
+	fn healthy_lane_has_no_alerts() {
+		let now = Instant::now();
+		let diagnostics = healthy_lane();
+		let mut history = LaneHistory::default();
+		history.update(&diagnostics, now);
+
+		assert!(evaluate_lane(&diagnostics, &history, &thresholds(), now).is_empty());
+	}
+
+	#[test]
+	fn alerts_are_firing_when_thresholds_are_exceeded() {
+		let now = Instant::now();
+		let diagnostics = LaneDiagnostics {
+			source_best_finalized_block_at_target: 94,
+			latest_generated_nonce: 40,
+			latest_received_nonce_at_target: 29,
+			is_target_pallet_halted: true,
+			source_relayer_balance: Some(9),
+			target_relayer_balance: Some(999),
+			problems: vec![LaneProblem::NonceGap { details: "gap".into() }],
+			..healthy_lane()
+		};
+		let mut history = LaneHistory::default();
+		history.update(&diagnostics, now);
+
+		assert_eq!(
+			alert_names(evaluate_lane(&diagnostics, &history, &thresholds(), now)),
+			vec![
+				"BridgeLaneUndeliveredMessages",
+				"BridgeLaneUnconfirmedMessages",
+				"BridgeLaneNonceGap",
+				"BridgeHeadersLag",
+				"BridgePalletHalted",
+				"BridgeRelayerBalanceLow",
+				"BridgeRelayerBalanceLow",
+			],
+		);
+	}
+
+	#[test]
+	fn stalled_lane_alerts_are_firing_only_when_there_is_no_progress() {
+		let started_at = Instant::now();
+		let mut diagnostics = healthy_lane();
+		let mut history = LaneHistory::default();
+		history.update(&diagnostics, started_at);
+
+		// messages are delivered, but confirmations are stalled
+		let now = started_at + Duration::from_secs(61);
+		diagnostics.latest_received_nonce_at_target = 16;
+		history.update(&diagnostics, now);
+		assert_eq!(
+			alert_names(evaluate_lane(&diagnostics, &history, &thresholds(), now)),
+			vec!["BridgeLaneConfirmationStalled"],
+		);
+
+		// everything is delivered and confirmed => no progress is expected
+		let now = started_at + Duration::from_secs(200);
+		diagnostics.latest_received_nonce_at_target = 20;
+		diagnostics.latest_confirmed_nonce_at_source = 20;
+		history.update(&diagnostics, now);
+		let now = now + Duration::from_secs(200);
+		history.update(&diagnostics, now);
+		assert!(evaluate_lane(&diagnostics, &history, &thresholds(), now).is_empty());
+
+		// new message is not delivered for too long
+		diagnostics.latest_generated_nonce = 21;
+		history.update(&diagnostics, now);
+		assert!(evaluate_lane(&diagnostics, &history, &thresholds(), now).is_empty());
+		let now = now + Duration::from_secs(61);
+		history.update(&diagnostics, now);
+		assert_eq!(
+			alert_names(evaluate_lane(&diagnostics, &history, &thresholds(), now)),
+			vec!["BridgeLaneDeliveryStalled"],
+		);
+	}
+}
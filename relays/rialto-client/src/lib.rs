@@ -16,18 +16,26 @@
 
 //! Types used to connect to the Rialto-Substrate chain.
 
+use async_trait::async_trait;
 use codec::Encode;
 use headers_relay::sync_types::SourceHeader;
-use relay_substrate_client::{Chain, ChainBase, ChainWithBalances, Client, TransactionSignScheme};
+use relay_substrate_client::{
+	Chain, ChainBase, ChainWithBalances, Client, RemoteSigner, TransactionEraOf, TransactionSignScheme,
+	TransactionSigner,
+};
 use sp_core::{storage::StorageKey, Pair};
 use sp_runtime::{
 	generic::SignedPayload,
 	traits::{Header as HeaderT, IdentifyAccount},
 };
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 pub use rialto_runtime::BridgeMillauCall;
 
+/// Rialto transactions signer.
+pub type AccountSigner =
+	dyn TransactionSigner<Public = sp_core::sr25519::Public, Signature = sp_core::sr25519::Signature>;
+
 /// Rialto header id.
 pub type HeaderId = relay_utils::HeaderId<rialto_runtime::Hash, rialto_runtime::BlockNumber>;
 
@@ -63,43 +71,45 @@ impl ChainWithBalances for Rialto {
 	}
 }
 
+#[async_trait]
 impl TransactionSignScheme for Rialto {
 	type Chain = Rialto;
-	type AccountKeyPair = sp_core::sr25519::Pair;
+	type AccountSigner = AccountSigner;
 	type SignedTransaction = rialto_runtime::UncheckedExtrinsic;
 
-	fn sign_transaction(
+	async fn sign_transaction(
 		client: &Client<Self>,
-		signer: &Self::AccountKeyPair,
+		signer: &Self::AccountSigner,
+		era: TransactionEraOf<Self::Chain>,
 		signer_nonce: <Self::Chain as Chain>::Index,
 		call: <Self::Chain as Chain>::Call,
-	) -> Self::SignedTransaction {
+	) -> relay_substrate_client::Result<Self::SignedTransaction> {
 		let raw_payload = SignedPayload::from_raw(
 			call,
-			(
-				frame_system::CheckSpecVersion::<rialto_runtime::Runtime>::new(),
-				frame_system::CheckTxVersion::<rialto_runtime::Runtime>::new(),
-				frame_system::CheckGenesis::<rialto_runtime::Runtime>::new(),
-				frame_system::CheckEra::<rialto_runtime::Runtime>::from(sp_runtime::generic::Era::Immortal),
-				frame_system::CheckNonce::<rialto_runtime::Runtime>::from(signer_nonce),
-				frame_system::CheckWeight::<rialto_runtime::Runtime>::new(),
-				pallet_transaction_payment::ChargeTransactionPayment::<rialto_runtime::Runtime>::from(0),
+			bp_rialto::signed_extensions::<rialto_runtime::Runtime, rialto_runtime::ChargeTransactionPayment>(
+				era.frame_era(),
+				signer_nonce,
+				0,
 			),
-			(
+			bp_rialto::additional_signed(
 				rialto_runtime::VERSION.spec_version,
 				rialto_runtime::VERSION.transaction_version,
 				*client.genesis_hash(),
-				*client.genesis_hash(),
-				(),
-				(),
-				(),
+				era.signed_payload(*client.genesis_hash()),
 			),
 		);
-		let signature = raw_payload.using_encoded(|payload| signer.sign(payload));
+		let signature = signer
+			.sign(raw_payload.using_encoded(|payload| payload.to_vec()))
+			.await?;
 		let signer: sp_runtime::MultiSigner = signer.public().into();
 		let (call, extra, _) = raw_payload.deconstruct();
 
-		rialto_runtime::UncheckedExtrinsic::new_signed(call, signer.into_account(), signature.into(), extra)
+		Ok(rialto_runtime::UncheckedExtrinsic::new_signed(
+			call,
+			signer.into_account(),
+			signature.into(),
+			extra,
+		))
 	}
 }
 
@@ -107,16 +117,62 @@ impl TransactionSignScheme for Rialto {
 #[derive(Clone)]
 pub struct SigningParams {
 	/// Substrate transactions signer.
-	pub signer: sp_core::sr25519::Pair,
+	pub signer: Arc<AccountSigner>,
+	/// Transactions mortality period (in blocks). If `None`, transactions are immortal.
+	pub transactions_mortality: Option<u32>,
+	/// Account on behalf of which the signer is submitting calls. If set, all calls are wrapped
+	/// into `proxy.proxy` call, so the signer only needs to be a (restricted) proxy of this account.
+	pub proxied_account: Option<rialto_runtime::AccountId>,
 }
 
 impl SigningParams {
 	/// Create signing params from SURI and password.
 	pub fn from_suri(suri: &str, password: Option<&str>) -> Result<Self, sp_core::crypto::SecretStringError> {
 		Ok(SigningParams {
-			signer: sp_core::sr25519::Pair::from_string(suri, password)?,
+			signer: Arc::new(sp_core::sr25519::Pair::from_string(suri, password)?),
+			transactions_mortality: None,
+			proxied_account: None,
+		})
+	}
+
+	/// Create signing params that are using remote signing service.
+	pub async fn from_remote_signer(
+		uri: &str,
+		public: sp_core::sr25519::Public,
+	) -> Result<Self, relay_substrate_client::Error> {
+		Ok(SigningParams {
+			signer: Arc::new(RemoteSigner::<_, sp_core::sr25519::Signature>::new(uri, public).await?),
+			transactions_mortality: None,
+			proxied_account: None,
 		})
 	}
+
+	/// Account that is the origin of all calls, submitted using these params.
+	pub fn origin_account(&self) -> rialto_runtime::AccountId {
+		self.proxied_account
+			.clone()
+			.unwrap_or_else(|| self.signer.public().as_array_ref().clone().into())
+	}
+
+	/// Wrap call into `proxy.proxy` call if the signer is acting as a proxy of other account.
+	pub fn wrap_call(&self, call: rialto_runtime::Call) -> rialto_runtime::Call {
+		match self.proxied_account {
+			Some(ref real) => rialto_runtime::ProxyCall::proxy(real.clone(), None, Box::new(call)).into(),
+			None => call,
+		}
+	}
+
+	/// Wrap calls into single call. If there are several calls, they're wrapped into
+	/// `utility.batch_all` call, so either all calls succeed, or all are reverted. The resulting
+	/// call is then wrapped into `proxy.proxy` call if the signer is acting as a proxy.
+	pub fn wrap_calls(&self, mut calls: Vec<rialto_runtime::Call>) -> rialto_runtime::Call {
+		let call = if calls.len() == 1 {
+			calls.remove(0)
+		} else {
+			rialto_runtime::UtilityCall::batch_all(calls).into()
+		};
+		self.wrap_call(call)
+	}
 }
 
 impl std::fmt::Debug for SigningParams {
@@ -128,7 +184,9 @@ impl std::fmt::Debug for SigningParams {
 impl Default for SigningParams {
 	fn default() -> Self {
 		SigningParams {
-			signer: sp_keyring::AccountKeyring::Alice.pair(),
+			signer: Arc::new(sp_keyring::AccountKeyring::Alice.pair()),
+			transactions_mortality: None,
+			proxied_account: None,
 		}
 	}
 }
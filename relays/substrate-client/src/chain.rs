@@ -15,19 +15,31 @@
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::client::Client;
+use crate::signer::TransactionSigner;
+use crate::Result;
 
-use bp_runtime::Chain as ChainBase;
+use async_trait::async_trait;
+
+use bp_runtime::{BlockNumberOf, Chain as ChainBase, HashOf};
+use codec::Encode;
 use frame_support::Parameter;
 use jsonrpsee::common::{DeserializeOwned, Serialize};
 use num_traits::{CheckedSub, Zero};
-use sp_core::{storage::StorageKey, Pair};
+use relay_utils::HeaderId;
+use sp_core::storage::StorageKey;
 use sp_runtime::{
-	generic::SignedBlock,
-	traits::{AtLeast32Bit, Dispatchable, MaybeDisplay, MaybeSerialize, MaybeSerializeDeserialize, Member},
+	generic::{Era, SignedBlock},
+	traits::{AtLeast32Bit, MaybeDisplay, MaybeSerialize, MaybeSerializeDeserialize, Member, UniqueSaturatedInto},
 	Justification,
 };
 use std::{fmt::Debug, time::Duration};
 
+/// Maximal mortality period of the transaction.
+///
+/// The phase of eras with larger periods is quantized, so the era birth block may be before the
+/// block that the transaction is signed with. Such transactions would be rejected by the chain.
+const MAX_MORTALITY_PERIOD: u64 = 4096;
+
 /// Substrate-based chain from minimal relay-client point of view.
 pub trait Chain: ChainBase {
 	/// Chain name.
@@ -54,7 +66,10 @@ pub trait Chain: ChainBase {
 	/// Block type.
 	type SignedBlock: Member + Serialize + DeserializeOwned + BlockWithJustification;
 	/// The aggregated `Call` type.
-	type Call: Dispatchable + Debug;
+	///
+	/// It is only required to be encodable, so chains which runtimes are not available to the
+	/// relay may use encoded calls instead.
+	type Call: Encode + Debug;
 }
 
 /// Substrate-based chain with `frame_system::Trait::AccountData` set to
@@ -74,21 +89,71 @@ pub trait BlockWithJustification {
 }
 
 /// Substrate-based chain transactions signing scheme.
+#[async_trait]
 pub trait TransactionSignScheme {
 	/// Chain that this scheme is to be used.
 	type Chain: Chain;
-	/// Type of key pairs used to sign transactions.
-	type AccountKeyPair: Pair;
+	/// Type of signer that is used to sign transactions.
+	type AccountSigner: TransactionSigner + ?Sized;
 	/// Signed transaction.
 	type SignedTransaction;
 
 	/// Create transaction for given runtime call, signed by given account.
-	fn sign_transaction(
+	async fn sign_transaction(
 		client: &Client<Self::Chain>,
-		signer: &Self::AccountKeyPair,
+		signer: &Self::AccountSigner,
+		era: TransactionEraOf<Self::Chain>,
 		signer_nonce: <Self::Chain as Chain>::Index,
 		call: <Self::Chain as Chain>::Call,
-	) -> Self::SignedTransaction;
+	) -> Result<Self::SignedTransaction>;
+}
+
+/// Transaction era used by the chain.
+pub type TransactionEraOf<C> = TransactionEra<BlockNumberOf<C>, HashOf<C>>;
+
+/// Era of the transaction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransactionEra<BlockNumber, BlockHash> {
+	/// Transaction is immortal.
+	Immortal,
+	/// Transaction is valid for given number of blocks, starting from given block.
+	Mortal(HeaderId<BlockHash, BlockNumber>, u32),
+}
+
+impl<BlockNumber: Copy + UniqueSaturatedInto<u64>, BlockHash: Copy> TransactionEra<BlockNumber, BlockHash> {
+	/// Prepare transaction era, based on mortality period and current best (finalized) block.
+	pub fn new(best_block_id: HeaderId<BlockHash, BlockNumber>, mortality_period: Option<u32>) -> Self {
+		mortality_period
+			.map(|mortality_period| TransactionEra::Mortal(best_block_id, mortality_period))
+			.unwrap_or(TransactionEra::Immortal)
+	}
+
+	/// Create new immortal transaction era.
+	pub fn immortal() -> Self {
+		TransactionEra::Immortal
+	}
+
+	/// Returns `frame_system::CheckEra` compatible era.
+	///
+	/// The mortality period is rounded up to the power of two and clamped to the
+	/// `MAX_MORTALITY_PERIOD`, so the era is always born at the block it is signed with.
+	pub fn frame_era(&self) -> Era {
+		match *self {
+			TransactionEra::Immortal => Era::immortal(),
+			TransactionEra::Mortal(header_id, period) => Era::mortal(
+				(period as u64).min(MAX_MORTALITY_PERIOD),
+				header_id.0.unique_saturated_into(),
+			),
+		}
+	}
+
+	/// Returns header hash that needs to be included in the signature payload.
+	pub fn signed_payload(&self, genesis_hash: BlockHash) -> BlockHash {
+		match *self {
+			TransactionEra::Immortal => genesis_hash,
+			TransactionEra::Mortal(header_id, _) => header_id.1,
+		}
+	}
 }
 
 impl BlockWithJustification for () {
@@ -102,3 +167,86 @@ impl<Block> BlockWithJustification for SignedBlock<Block> {
 		self.justification.as_ref()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::traits::{Hash as HashT, Header as HeaderT, Keccak256};
+
+	type TestEra = TransactionEra<u64, u64>;
+
+	/// Chain that is using `Keccak256` hasher and 20-byte account ids.
+	struct KeccakChain;
+
+	type KeccakHeader = sp_runtime::generic::Header<u32, Keccak256>;
+
+	impl ChainBase for KeccakChain {
+		type BlockNumber = u32;
+		type Hash = sp_core::H256;
+		type Hasher = Keccak256;
+		type Header = KeccakHeader;
+	}
+
+	impl Chain for KeccakChain {
+		const NAME: &'static str = "Keccak";
+		const AVERAGE_BLOCK_INTERVAL: Duration = Duration::from_millis(1);
+
+		type AccountId = sp_core::H160;
+		type Index = u32;
+		type SignedBlock = ();
+		type Call = ();
+	}
+
+	fn keccak_chain_transaction_era(
+		best_block_id: HeaderId<HashOf<KeccakChain>, BlockNumberOf<KeccakChain>>,
+	) -> TransactionEraOf<KeccakChain> {
+		TransactionEra::new(best_block_id, Some(64))
+	}
+
+	#[test]
+	fn immortal_transaction_era_works() {
+		let era = TestEra::new(HeaderId(100, 100), None);
+		assert_eq!(era, TestEra::immortal());
+		assert_eq!(era.frame_era(), Era::Immortal);
+		assert_eq!(era.signed_payload(42), 42);
+	}
+
+	#[test]
+	fn mortal_transaction_era_works() {
+		let era = TestEra::new(HeaderId(100, 200), Some(64));
+		assert_eq!(era.frame_era(), Era::mortal(64, 100));
+		assert_eq!(era.frame_era().birth(100), 100);
+		assert_eq!(era.frame_era().death(100), 164);
+		assert_eq!(era.signed_payload(42), 200);
+	}
+
+	#[test]
+	fn mortal_transaction_era_with_large_period_is_born_at_signed_block() {
+		for period in &[5_000, 65_536, u32::MAX] {
+			for block in &[100_001, 1_000_003, 4_294_967_291] {
+				let era = TestEra::new(HeaderId(*block, 200), Some(*period));
+				assert_eq!(era.frame_era().birth(*block), *block);
+				assert_eq!(era.frame_era().death(*block), *block + MAX_MORTALITY_PERIOD);
+			}
+		}
+	}
+
+	#[test]
+	fn chain_with_keccak256_hasher_and_20_byte_accounts_is_supported() {
+		let header = KeccakHeader::new(
+			100,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		);
+		assert_eq!(header.hash(), Keccak256::hash_of(&header));
+
+		let era = keccak_chain_transaction_era(HeaderId(*header.number(), header.hash()));
+		assert_eq!(era.frame_era(), Era::mortal(64, 100));
+		assert_eq!(era.signed_payload(Default::default()), header.hash());
+
+		let account_id: <KeccakChain as Chain>::AccountId = [42u8; 20].into();
+		assert_eq!(account_id.encode(), vec![42u8; 20]);
+	}
+}
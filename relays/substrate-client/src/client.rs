@@ -16,26 +16,46 @@
 
 //! Substrate node client.
 
-use crate::chain::{Chain, ChainWithBalances};
+use crate::chain::{Chain, ChainWithBalances, TransactionEra, TransactionEraOf};
 use crate::rpc::{Substrate, SubstrateMessageLane};
-use crate::{ConnectionParams, Error, Result};
+use crate::storage::{RuntimeStorage, StorageEntry};
+use crate::{ConnectionParams, Error, NodeAddress, Result};
 
+use async_std::sync::{Arc, Mutex, MutexGuard};
 use bp_message_lane::{LaneId, MessageNonce};
 use bp_runtime::InstanceId;
-use codec::Decode;
+use codec::{Decode, Encode};
 use frame_system::AccountInfo;
+use jsonrpsee::client::RequestError;
 use jsonrpsee::common::DeserializeOwned;
 use jsonrpsee::raw::RawClient;
 use jsonrpsee::transport::ws::WsTransportClient;
 use jsonrpsee::{client::Subscription, Client as RpcClient};
 use num_traits::Zero;
 use pallet_balances::AccountData;
-use sp_core::Bytes;
+use pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo;
+use relay_utils::{rate_limiter::RateLimiter, HeaderId};
+use sp_core::{
+	storage::{StorageData, StorageKey},
+	Bytes,
+};
+use sp_runtime::{traits::Header as HeaderT, ApplyExtrinsicResult};
 use sp_trie::StorageProof;
 use sp_version::RuntimeVersion;
-use std::ops::RangeInclusive;
+use std::{
+	future::Future,
+	ops::RangeInclusive,
+	sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Number of consecutive failed requests after which the node is considered unhealthy. The error
+/// is then reported as connection error, so that the relay reconnects to the next node.
+///
+/// It is only used if fallback nodes are configured.
+const MAX_CONSECUTIVE_REQUEST_FAILURES: usize = 3;
 
 const SUB_API_GRANDPA_AUTHORITIES: &str = "GrandpaApi_grandpa_authorities";
+const SUB_API_TX_PAYMENT_QUERY_INFO: &str = "TransactionPaymentApi_query_info";
 
 /// Opaque justifications subscription type.
 pub type JustificationsSubscription = Subscription<Bytes>;
@@ -45,22 +65,45 @@ pub type OpaqueGrandpaAuthoritiesSet = Vec<u8>;
 
 /// Substrate client type.
 ///
+/// The client may be configured with several redundant nodes. Only one node is used at a time.
+/// When the client reconnects, it switches to the next node, but only if that node has the same
+/// genesis block hash.
+///
 /// Cloning `Client` is a cheap operation.
 pub struct Client<C: Chain> {
 	/// Client connection params.
 	params: ConnectionParams,
+	/// Index of the connected node in the `params.nodes()`.
+	node: usize,
 	/// Substrate RPC client.
 	client: RpcClient,
+	/// Number of consecutive failed requests to the connected node. It is shared by all client
+	/// clones and is reset on reconnect.
+	request_failures: Arc<AtomicUsize>,
+	/// Limiter of requests to the node. It is shared by all client clones and survives reconnects.
+	rate_limiter: RateLimiter,
 	/// Genesis block hash.
 	genesis_hash: C::Hash,
+	/// Lock that is held while signed transaction is prepared and submitted. It is shared by all
+	/// client clones, so that different relays, running in the same process, are not using the
+	/// same account nonce.
+	submit_signed_extrinsic_lock: Arc<Mutex<()>>,
+	/// Storage entries of the runtime, read from the runtime metadata. It is shared by all client
+	/// clones and is only read when typed storage is queried for the first time.
+	runtime_storage: Arc<Mutex<Option<RuntimeStorage>>>,
 }
 
 impl<C: Chain> Clone for Client<C> {
 	fn clone(&self) -> Self {
 		Client {
 			params: self.params.clone(),
+			node: self.node,
 			client: self.client.clone(),
+			request_failures: self.request_failures.clone(),
+			rate_limiter: self.rate_limiter.clone(),
 			genesis_hash: self.genesis_hash,
+			submit_signed_extrinsic_lock: self.submit_signed_extrinsic_lock.clone(),
+			runtime_storage: self.runtime_storage.clone(),
 		}
 	}
 }
@@ -75,35 +118,151 @@ impl<C: Chain> std::fmt::Debug for Client<C> {
 
 impl<C: Chain> Client<C> {
 	/// Returns client that is able to call RPCs on Substrate node over websocket connection.
+	///
+	/// The client connects to the first available node. Genesis hash of this node is used to
+	/// verify fallback nodes later.
 	pub async fn new(params: ConnectionParams) -> Result<Self> {
-		let client = Self::build_client(params.clone()).await?;
-
-		let number: C::BlockNumber = Zero::zero();
-		let genesis_hash = Substrate::<C, _, _>::chain_get_block_hash(&client, number).await?;
+		let rate_limiter = RateLimiter::new(format!("{}:{}", params.host, params.port), params.rate_limiter.clone());
+		let (node, client, genesis_hash) = Self::connect_to_any_node(&params, 0, &rate_limiter, None).await?;
 
 		Ok(Self {
 			params,
+			node,
 			client,
+			request_failures: Arc::new(AtomicUsize::new(0)),
+			rate_limiter,
 			genesis_hash,
+			submit_signed_extrinsic_lock: Arc::new(Mutex::new(())),
+			runtime_storage: Arc::new(Mutex::new(None)),
 		})
 	}
 
 	/// Reopen client connection.
+	///
+	/// If fallback nodes are configured, the client connects to the next node that has the
+	/// same genesis hash.
 	pub async fn reconnect(self) -> Result<Self> {
+		let (node, client, _) =
+			Self::connect_to_any_node(&self.params, self.node + 1, &self.rate_limiter, Some(self.genesis_hash)).await?;
+
 		Ok(Self {
-			params: self.params.clone(),
-			client: Self::build_client(self.params).await?,
+			params: self.params,
+			node,
+			client,
+			request_failures: Arc::new(AtomicUsize::new(0)),
+			rate_limiter: self.rate_limiter,
 			genesis_hash: self.genesis_hash,
+			submit_signed_extrinsic_lock: self.submit_signed_extrinsic_lock,
+			runtime_storage: self.runtime_storage,
 		})
 	}
 
+	/// Connect to the first available node, starting from the node with given index.
+	///
+	/// If `expected_genesis_hash` is specified, nodes with different genesis hash are skipped.
+	/// Returns index of the connected node, connection and genesis hash of the node.
+	async fn connect_to_any_node(
+		params: &ConnectionParams,
+		first_node: usize,
+		rate_limiter: &RateLimiter,
+		expected_genesis_hash: Option<C::Hash>,
+	) -> Result<(usize, RpcClient, C::Hash)> {
+		let nodes = params.nodes();
+		let mut last_error = None;
+		for node in (first_node..first_node + nodes.len()).map(|node| node % nodes.len()) {
+			let address = &nodes[node];
+			match Self::connect_to_node(address, rate_limiter, expected_genesis_hash).await {
+				Ok((client, genesis_hash)) => {
+					if nodes.len() > 1 {
+						log::info!(
+							target: "bridge",
+							"Connected to {} node {}:{}",
+							C::NAME,
+							address.host,
+							address.port,
+						);
+					}
+
+					return Ok((node, client, genesis_hash));
+				}
+				Err(error) => {
+					if nodes.len() > 1 {
+						log::warn!(
+							target: "bridge",
+							"Failed to connect to {} node {}:{}: {}",
+							C::NAME,
+							address.host,
+							address.port,
+							error.to_string(),
+						);
+					}
+
+					last_error = Some(error);
+				}
+			}
+		}
+
+		Err(last_error.expect("there's always at least one (primary) node; qed"))
+	}
+
+	/// Connect to given node and verify its genesis hash.
+	async fn connect_to_node(
+		address: &NodeAddress,
+		rate_limiter: &RateLimiter,
+		expected_genesis_hash: Option<C::Hash>,
+	) -> Result<(RpcClient, C::Hash)> {
+		let client = Self::build_client(address).await?;
+
+		let number: C::BlockNumber = Zero::zero();
+		let genesis_hash = {
+			let _permit = rate_limiter.acquire().await;
+			Substrate::<C, _, _>::chain_get_block_hash(&client, number).await?
+		};
+		match expected_genesis_hash {
+			Some(expected_genesis_hash) if expected_genesis_hash != genesis_hash => Err(Error::Custom(format!(
+				"Node has genesis hash {:?}, while {:?} is expected",
+				genesis_hash, expected_genesis_hash,
+			))),
+			_ => Ok((client, genesis_hash)),
+		}
+	}
+
 	/// Build client to use in connection.
-	async fn build_client(params: ConnectionParams) -> Result<RpcClient> {
-		let uri = format!("ws://{}:{}", params.host, params.port);
+	async fn build_client(address: &NodeAddress) -> Result<RpcClient> {
+		let uri = format!("ws://{}:{}", address.host, address.port);
 		let transport = WsTransportClient::new(&uri).await?;
 		let raw_client = RawClient::new(transport);
 		Ok(raw_client.into())
 	}
+
+	/// Call RPC method of the connected node.
+	///
+	/// The call waits until rate limiter allows it. If fallback nodes are configured and too many
+	/// consecutive calls have failed, the error is reported as connection error.
+	async fn call<'a, T, F, Fut>(&'a self, call: F) -> Result<T>
+	where
+		F: FnOnce(&'a RpcClient) -> Fut,
+		Fut: Future<Output = std::result::Result<T, RequestError>>,
+	{
+		let _permit = self.rate_limiter.acquire().await;
+		match call(&self.client).await {
+			Ok(result) => {
+				self.request_failures.store(0, Ordering::SeqCst);
+				Ok(result)
+			}
+			Err(error) => {
+				let request_failures = self.request_failures.fetch_add(1, Ordering::SeqCst) + 1;
+				if request_failures >= MAX_CONSECUTIVE_REQUEST_FAILURES && !self.params.fallback_nodes.is_empty() {
+					return Err(Error::UnhealthyNode(format!(
+						"{} consecutive requests have failed. Last error: {}",
+						request_failures, error,
+					)));
+				}
+
+				Err(error.into())
+			}
+		}
+	}
 }
 
 impl<C: Chain> Client<C> {
@@ -114,7 +273,8 @@ impl<C: Chain> Client<C> {
 
 	/// Return hash of the best finalized block.
 	pub async fn best_finalized_header_hash(&self) -> Result<C::Hash> {
-		Ok(Substrate::<C, _, _>::chain_get_finalized_head(&self.client).await?)
+		self.call(|client| Substrate::<C, _, _>::chain_get_finalized_head(client))
+			.await
 	}
 
 	/// Returns the best Substrate header.
@@ -122,12 +282,14 @@ impl<C: Chain> Client<C> {
 	where
 		C::Header: DeserializeOwned,
 	{
-		Ok(Substrate::<C, _, _>::chain_get_header(&self.client, None).await?)
+		self.call(|client| Substrate::<C, _, _>::chain_get_header(client, None))
+			.await
 	}
 
 	/// Get a Substrate block from its hash.
 	pub async fn get_block(&self, block_hash: Option<C::Hash>) -> Result<C::SignedBlock> {
-		Ok(Substrate::<C, _, _>::chain_get_block(&self.client, block_hash).await?)
+		self.call(move |client| Substrate::<C, _, _>::chain_get_block(client, block_hash))
+			.await
 	}
 
 	/// Get a Substrate header by its hash.
@@ -135,12 +297,14 @@ impl<C: Chain> Client<C> {
 	where
 		C::Header: DeserializeOwned,
 	{
-		Ok(Substrate::<C, _, _>::chain_get_header(&self.client, block_hash).await?)
+		self.call(move |client| Substrate::<C, _, _>::chain_get_header(client, block_hash))
+			.await
 	}
 
 	/// Get a Substrate block hash by its number.
 	pub async fn block_hash_by_number(&self, number: C::BlockNumber) -> Result<C::Hash> {
-		Ok(Substrate::<C, _, _>::chain_get_block_hash(&self.client, number).await?)
+		self.call(move |client| Substrate::<C, _, _>::chain_get_block_hash(client, number))
+			.await
 	}
 
 	/// Get a Substrate header by its number.
@@ -154,7 +318,112 @@ impl<C: Chain> Client<C> {
 
 	/// Return runtime version.
 	pub async fn runtime_version(&self) -> Result<RuntimeVersion> {
-		Ok(Substrate::<C, _, _>::runtime_version(&self.client).await?)
+		self.call(|client| Substrate::<C, _, _>::runtime_version(client)).await
+	}
+
+	/// Read and decode value from the runtime storage at the best block.
+	///
+	/// Returns `None` if there's no value at given key.
+	pub async fn storage_value_by_key<T: Decode>(&self, storage_key: StorageKey) -> Result<Option<T>> {
+		self.decoded_storage_value(storage_key, None).await
+	}
+
+	/// Read and decode plain storage value of given pallet at given block (or the best block, if
+	/// `at_block` is `None`).
+	///
+	/// Returns `None` if there's no value in the storage.
+	pub async fn storage_value<T: Decode>(
+		&self,
+		pallet: &str,
+		name: &str,
+		at_block: Option<C::Hash>,
+	) -> Result<Option<T>> {
+		let storage_key = self.storage_entry(pallet, name).await?.value_key()?;
+		self.decoded_storage_value(storage_key, at_block).await
+	}
+
+	/// Read and decode storage map entry of given pallet at given block (or the best block, if
+	/// `at_block` is `None`).
+	///
+	/// Returns `None` if there's no entry with given key in the storage.
+	pub async fn storage_map_entry<K: Encode, V: Decode>(
+		&self,
+		pallet: &str,
+		name: &str,
+		key: &K,
+		at_block: Option<C::Hash>,
+	) -> Result<Option<V>> {
+		let storage_key = self.storage_entry(pallet, name).await?.map_key(key)?;
+		self.decoded_storage_value(storage_key, at_block).await
+	}
+
+	/// Read and decode storage double map entry of given pallet at given block (or the best block,
+	/// if `at_block` is `None`).
+	///
+	/// Returns `None` if there's no entry with given keys in the storage.
+	pub async fn storage_double_map_entry<K1: Encode, K2: Encode, V: Decode>(
+		&self,
+		pallet: &str,
+		name: &str,
+		key1: &K1,
+		key2: &K2,
+		at_block: Option<C::Hash>,
+	) -> Result<Option<V>> {
+		let storage_key = self.storage_entry(pallet, name).await?.double_map_key(key1, key2)?;
+		self.decoded_storage_value(storage_key, at_block).await
+	}
+
+	/// Return storage entry of given pallet.
+	///
+	/// Storage entries are read from the runtime metadata when this method is called for the first
+	/// time. The metadata is read again if the entry is missing (e.g. because runtime has been
+	/// upgraded since then).
+	pub async fn storage_entry(&self, pallet: &str, name: &str) -> Result<StorageEntry> {
+		let mut runtime_storage = self.runtime_storage.lock().await;
+		if let Some(entry) = runtime_storage.as_ref().and_then(|storage| storage.entry(pallet, name)) {
+			return Ok(entry.clone());
+		}
+
+		let encoded_metadata = self
+			.call(|client| Substrate::<C, _, _>::runtime_metadata(client))
+			.await?;
+		let storage = RuntimeStorage::from_metadata(&encoded_metadata.0)?;
+		let entry = storage.entry(pallet, name).cloned();
+		*runtime_storage = Some(storage);
+		entry.ok_or_else(|| Error::UnknownStorageEntry(format!("{}.{} at {}", pallet, name, C::NAME)))
+	}
+
+	/// Read and decode value from the runtime storage.
+	async fn decoded_storage_value<T: Decode>(
+		&self,
+		storage_key: StorageKey,
+		at_block: Option<C::Hash>,
+	) -> Result<Option<T>> {
+		self.call(move |client| Substrate::<C, _, _>::get_storage_at(client, storage_key, at_block))
+			.await?
+			.map(|encoded_value| T::decode(&mut &encoded_value.0[..]).map_err(Error::ResponseParseFailed))
+			.transpose()
+	}
+
+	/// Read raw value from the runtime storage at given block.
+	///
+	/// Returns `None` if there's no value at given key.
+	pub async fn raw_storage_value(&self, storage_key: StorageKey, at_block: C::Hash) -> Result<Option<StorageData>> {
+		self.call(move |client| Substrate::<C, _, _>::get_storage_at(client, storage_key, Some(at_block)))
+			.await
+	}
+
+	/// Return at most `count` storage keys with given prefix at given block. Keys are ordered
+	/// lexicographically and are starting after the `start_key` (if it is specified).
+	pub async fn storage_keys_paged(
+		&self,
+		prefix: StorageKey,
+		count: u32,
+		start_key: Option<StorageKey>,
+		at_block: C::Hash,
+	) -> Result<Vec<StorageKey>> {
+		self.call(move |client| Substrate::<C, _, _>::get_keys_paged(client, prefix, count, start_key, Some(at_block)))
+			.await
 	}
 
 	/// Return native tokens balance of the account.
@@ -163,7 +432,8 @@ impl<C: Chain> Client<C> {
 		C: ChainWithBalances,
 	{
 		let storage_key = C::account_info_storage_key(&account);
-		let encoded_account_data = Substrate::<C, _, _>::get_storage(&self.client, storage_key)
+		let encoded_account_data = self
+			.call(move |client| Substrate::<C, _, _>::get_storage(client, storage_key))
 			.await?
 			.ok_or(Error::AccountDoesNotExist)?;
 		let decoded_account_data =
@@ -172,28 +442,88 @@ impl<C: Chain> Client<C> {
 		Ok(decoded_account_data.data.free)
 	}
 
+	/// Return era for the new transaction.
+	///
+	/// If `mortality_period` is `None`, the transaction is immortal. Otherwise it is valid for
+	/// `mortality_period` blocks, starting from the best finalized block.
+	pub async fn transaction_era(&self, mortality_period: Option<u32>) -> Result<TransactionEraOf<C>>
+	where
+		C::Header: DeserializeOwned,
+	{
+		if mortality_period.is_none() {
+			return Ok(TransactionEra::immortal());
+		}
+
+		let best_finalized_header_hash = self.best_finalized_header_hash().await?;
+		let best_finalized_header = self.header_by_hash(best_finalized_header_hash).await?;
+		Ok(TransactionEra::new(
+			HeaderId(*best_finalized_header.number(), best_finalized_header_hash),
+			mortality_period,
+		))
+	}
+
 	/// Get the nonce of the given Substrate account.
 	///
 	/// Note: It's the caller's responsibility to make sure `account` is a valid ss58 address.
 	pub async fn next_account_index(&self, account: C::AccountId) -> Result<C::Index> {
-		Ok(Substrate::<C, _, _>::system_account_next_index(&self.client, account).await?)
+		self.call(move |client| Substrate::<C, _, _>::system_account_next_index(client, account))
+			.await
 	}
 
 	/// Submit an extrinsic for inclusion in a block.
 	///
 	/// Note: The given transaction does not need be SCALE encoded beforehand.
 	pub async fn submit_extrinsic(&self, transaction: Bytes) -> Result<C::Hash> {
-		let tx_hash = Substrate::<C, _, _>::author_submit_extrinsic(&self.client, transaction).await?;
+		let tx_hash = self
+			.call(move |client| Substrate::<C, _, _>::author_submit_extrinsic(client, transaction))
+			.await?;
 		log::trace!(target: "bridge", "Sent transaction to Substrate node: {:?}", tx_hash);
 		Ok(tx_hash)
 	}
 
+	/// Dry run given (signed) extrinsic on top of the best block and return its execution result.
+	///
+	/// Note: `system_dryRun` is an unsafe RPC method, so it must be allowed by the node.
+	pub async fn dry_run_extrinsic(&self, transaction: Bytes) -> Result<ApplyExtrinsicResult> {
+		let encoded_result = self
+			.call(move |client| Substrate::<C, _, _>::system_dry_run(client, transaction, None))
+			.await?;
+		Decode::decode(&mut &encoded_result.0[..]).map_err(Error::ResponseParseFailed)
+	}
+
+	/// Estimate dispatch weight and fee of given (signed) extrinsic.
+	pub async fn extrinsic_dispatch_info(&self, transaction: Bytes) -> Result<RuntimeDispatchInfo<C::NativeBalance>>
+	where
+		C: ChainWithBalances,
+	{
+		let transaction_len = transaction.0.len() as u32;
+		let mut data = transaction.0;
+		transaction_len.encode_to(&mut data);
+
+		let encoded_response = self
+			.call(move |client| {
+				Substrate::<C, _, _>::state_call(client, SUB_API_TX_PAYMENT_QUERY_INFO.to_string(), Bytes(data), None)
+			})
+			.await?;
+		Decode::decode(&mut &encoded_response.0[..]).map_err(Error::ResponseParseFailed)
+	}
+
+	/// Acquire lock that must be held while signed transaction is prepared and submitted.
+	///
+	/// Transactions that are signed by the same account must not be prepared concurrently,
+	/// because otherwise they could be using the same nonce.
+	pub async fn lock_signed_extrinsic_submission(&self) -> MutexGuard<'_, ()> {
+		self.submit_signed_extrinsic_lock.lock().await
+	}
+
 	/// Get the GRANDPA authority set at given block.
 	pub async fn grandpa_authorities_set(&self, block: C::Hash) -> Result<OpaqueGrandpaAuthoritiesSet> {
 		let call = SUB_API_GRANDPA_AUTHORITIES.to_string();
 		let data = Bytes(Vec::new());
 
-		let encoded_response = Substrate::<C, _, _>::state_call(&self.client, call, data, Some(block)).await?;
+		let encoded_response = self
+			.call(move |client| Substrate::<C, _, _>::state_call(client, call, data, Some(block)))
+			.await?;
 		let authority_list = encoded_response.0;
 
 		Ok(authority_list)
@@ -201,9 +531,8 @@ impl<C: Chain> Client<C> {
 
 	/// Execute runtime call at given block.
 	pub async fn state_call(&self, method: String, data: Bytes, at_block: Option<C::Hash>) -> Result<Bytes> {
-		Substrate::<C, _, _>::state_call(&self.client, method, data, at_block)
+		self.call(move |client| Substrate::<C, _, _>::state_call(client, method, data, at_block))
 			.await
-			.map_err(Into::into)
 	}
 
 	/// Returns proof-of-message(s) in given inclusive range.
@@ -215,17 +544,19 @@ impl<C: Chain> Client<C> {
 		include_outbound_lane_state: bool,
 		at_block: C::Hash,
 	) -> Result<StorageProof> {
-		let encoded_trie_nodes = SubstrateMessageLane::<C, _, _>::prove_messages(
-			&self.client,
-			instance,
-			lane,
-			*range.start(),
-			*range.end(),
-			include_outbound_lane_state,
-			Some(at_block),
-		)
-		.await
-		.map_err(Error::Request)?;
+		let encoded_trie_nodes = self
+			.call(move |client| {
+				SubstrateMessageLane::<C, _, _>::prove_messages(
+					client,
+					instance,
+					lane,
+					*range.start(),
+					*range.end(),
+					include_outbound_lane_state,
+					Some(at_block),
+				)
+			})
+			.await?;
 		let decoded_trie_nodes: Vec<Vec<u8>> =
 			Decode::decode(&mut &encoded_trie_nodes[..]).map_err(Error::ResponseParseFailed)?;
 		Ok(StorageProof::new(decoded_trie_nodes))
@@ -238,10 +569,11 @@ impl<C: Chain> Client<C> {
 		lane: LaneId,
 		at_block: C::Hash,
 	) -> Result<StorageProof> {
-		let encoded_trie_nodes =
-			SubstrateMessageLane::<C, _, _>::prove_messages_delivery(&self.client, instance, lane, Some(at_block))
-				.await
-				.map_err(Error::Request)?;
+		let encoded_trie_nodes = self
+			.call(move |client| {
+				SubstrateMessageLane::<C, _, _>::prove_messages_delivery(client, instance, lane, Some(at_block))
+			})
+			.await?;
 		let decoded_trie_nodes: Vec<Vec<u8>> =
 			Decode::decode(&mut &encoded_trie_nodes[..]).map_err(Error::ResponseParseFailed)?;
 		Ok(StorageProof::new(decoded_trie_nodes))
@@ -249,6 +581,7 @@ impl<C: Chain> Client<C> {
 
 	/// Return new justifications stream.
 	pub async fn subscribe_justifications(self) -> Result<JustificationsSubscription> {
+		let _permit = self.rate_limiter.acquire().await;
 		Ok(self
 			.client
 			.subscribe(
@@ -16,10 +16,16 @@
 
 //! Substrate node RPC errors.
 
+use bp_runtime::BridgeError;
 use jsonrpsee::client::RequestError;
+use jsonrpsee::common::ErrorCode;
 use jsonrpsee::transport::ws::WsNewDnsError;
 use relay_utils::MaybeConnectionError;
 
+/// Code of the RPC error that is returned when transaction is rejected by the transaction pool
+/// because it is invalid (`POOL_INVALID_TX` of the Substrate author RPC).
+const POOL_INVALID_TX_ERROR_CODE: i64 = 1010;
+
 /// Result type used by Substrate client.
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -36,10 +42,51 @@ pub enum Error {
 	ResponseParseFailed(codec::Error),
 	/// Account does not exist on the chain.
 	AccountDoesNotExist,
-	/// Custom logic error.
+	/// The runtime metadata could not be parsed.
+	MetadataParseFailed(String),
+	/// The storage entry is not declared in the runtime metadata.
+	UnknownStorageEntry(String),
+	/// The storage entry has unexpected kind (e.g. map is read as a plain value).
+	UnexpectedStorageEntryKind(String),
+	/// Too many requests to the node have failed, so the client needs to switch to another node.
+	UnhealthyNode(String),
+	/// Custom logic error. If it is a bridge error, the message is tagged with the
+	/// `BridgeError` kind (see `Error::bridge`).
 	Custom(String),
 }
 
+impl Error {
+	/// Create custom error, tagged with given bridge error kind.
+	pub fn bridge(kind: BridgeError, details: impl std::fmt::Display) -> Self {
+		Error::Custom(kind.tagged_message(details))
+	}
+
+	/// Returns kind of the bridge error. Returns `None` if this isn't a tagged bridge error.
+	pub fn bridge_error(&self) -> Option<BridgeError> {
+		match *self {
+			Error::Custom(ref error) => BridgeError::from_tagged_message(error),
+			_ => None,
+		}
+	}
+
+	/// Returns true if this is a bridge error and retrying the same operation is pointless.
+	pub fn is_fatal_bridge_error(&self) -> bool {
+		self.bridge_error().map(|kind| !kind.is_retryable()).unwrap_or(false)
+	}
+
+	/// Returns true if transaction has been rejected by the node because it would exhaust block
+	/// resources (`InvalidTransaction::ExhaustsResources`).
+	pub fn is_exhausted_resources_error(&self) -> bool {
+		match *self {
+			Error::Request(RequestError::Request(ref error)) => {
+				error.code == ErrorCode::ServerError(POOL_INVALID_TX_ERROR_CODE)
+					&& error.data.as_ref().and_then(|data| data.as_str()) == Some("ExhaustsResources")
+			}
+			_ => false,
+		}
+	}
+}
+
 impl From<WsNewDnsError> for Error {
 	fn from(error: WsNewDnsError) -> Self {
 		Error::WsConnectionError(error)
@@ -54,7 +101,10 @@ impl From<RequestError> for Error {
 
 impl MaybeConnectionError for Error {
 	fn is_connection_error(&self) -> bool {
-		matches!(*self, Error::Request(RequestError::TransportError(_)))
+		matches!(
+			*self,
+			Error::Request(RequestError::TransportError(_)) | Error::UnhealthyNode(_)
+		)
 	}
 }
 
@@ -71,7 +121,50 @@ impl ToString for Error {
 			Self::Request(e) => e.to_string(),
 			Self::ResponseParseFailed(e) => e.what().to_string(),
 			Self::AccountDoesNotExist => "Account does not exist on the chain".into(),
+			Self::MetadataParseFailed(e) => format!("Failed to parse runtime metadata: {}", e),
+			Self::UnknownStorageEntry(e) => format!("Unknown storage entry: {}", e),
+			Self::UnexpectedStorageEntryKind(e) => format!("Unexpected storage entry kind: {}", e),
+			Self::UnhealthyNode(e) => format!("Node is unhealthy: {}", e),
 			Self::Custom(e) => e.clone(),
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn bridge_errors_are_classified() {
+		assert_eq!(
+			Error::bridge(BridgeError::NonceGap, "details").bridge_error(),
+			Some(BridgeError::NonceGap)
+		);
+		assert!(!Error::bridge(BridgeError::NonceGap, "details").is_fatal_bridge_error());
+		assert!(Error::bridge(BridgeError::ProofTooLarge, "details").is_fatal_bridge_error());
+		assert_eq!(Error::Custom("details".into()).bridge_error(), None);
+		assert_eq!(Error::AccountDoesNotExist.bridge_error(), None);
+	}
+
+	#[test]
+	fn exhausted_resources_error_is_detected() {
+		let pool_error = |code, data: &str| {
+			Error::Request(RequestError::Request(jsonrpsee::common::Error {
+				code: ErrorCode::ServerError(code),
+				message: "Invalid Transaction".into(),
+				data: Some(data.into()),
+			}))
+		};
+
+		assert!(pool_error(POOL_INVALID_TX_ERROR_CODE, "ExhaustsResources").is_exhausted_resources_error());
+		assert!(!pool_error(POOL_INVALID_TX_ERROR_CODE, "Payment").is_exhausted_resources_error());
+		assert!(!pool_error(POOL_INVALID_TX_ERROR_CODE + 1, "ExhaustsResources").is_exhausted_resources_error());
+		assert!(!Error::Custom("Transaction would exhaust the block limits".into()).is_exhausted_resources_error());
+	}
+
+	#[test]
+	fn unhealthy_node_error_is_connection_error() {
+		assert!(Error::UnhealthyNode("details".into()).is_connection_error());
+		assert!(!Error::Custom("details".into()).is_connection_error());
+	}
+}
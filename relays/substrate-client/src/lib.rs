@@ -22,18 +22,56 @@ mod chain;
 mod client;
 mod error;
 mod rpc;
+mod signer;
+mod storage;
 
 pub mod guard;
 pub mod headers_source;
+pub mod polkadot_like;
 
-pub use crate::chain::{BlockWithJustification, Chain, ChainWithBalances, TransactionSignScheme};
+pub use crate::chain::{
+	BlockWithJustification, Chain, ChainWithBalances, TransactionEra, TransactionEraOf, TransactionSignScheme,
+};
 pub use crate::client::{Client, JustificationsSubscription, OpaqueGrandpaAuthoritiesSet};
 pub use crate::error::{Error, Result};
+pub use crate::signer::{RemoteSigner, TransactionSigner};
+pub use crate::storage::{RuntimeStorage, StorageEntry, StorageEntryKind};
 pub use bp_runtime::{BlockNumberOf, Chain as ChainBase, HashOf, HeaderOf};
+pub use frame_metadata::StorageHasher;
+pub use relay_utils::rate_limiter::RateLimiterParams;
 
 /// Header id used by the chain.
 pub type HeaderIdOf<C> = relay_utils::HeaderId<HashOf<C>, BlockNumberOf<C>>;
 
+/// Substrate node address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeAddress {
+	/// Websocket server hostname.
+	pub host: String,
+	/// Websocket server TCP port.
+	pub port: u16,
+}
+
+impl std::str::FromStr for NodeAddress {
+	type Err = String;
+
+	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+		let mut parts = s.rsplitn(2, ':');
+		let port = parts
+			.next()
+			.and_then(|port| port.parse().ok())
+			.ok_or_else(|| format!("Invalid Substrate node port in '{}'", s))?;
+		let host = parts
+			.next()
+			.filter(|host| !host.is_empty())
+			.ok_or_else(|| format!("Missing Substrate node host in '{}'", s))?;
+		Ok(NodeAddress {
+			host: host.into(),
+			port,
+		})
+	}
+}
+
 /// Substrate-over-websocket connection params.
 #[derive(Debug, Clone)]
 pub struct ConnectionParams {
@@ -41,6 +79,23 @@ pub struct ConnectionParams {
 	pub host: String,
 	/// Websocket server TCP port.
 	pub port: u16,
+	/// Redundant nodes of the same chain that are used when connection to the current node fails.
+	/// Nodes are listed in order of preference.
+	pub fallback_nodes: Vec<NodeAddress>,
+	/// Limits of requests to the node.
+	pub rate_limiter: RateLimiterParams,
+}
+
+impl ConnectionParams {
+	/// Return addresses of all (primary and fallback) nodes.
+	pub fn nodes(&self) -> Vec<NodeAddress> {
+		std::iter::once(NodeAddress {
+			host: self.host.clone(),
+			port: self.port,
+		})
+		.chain(self.fallback_nodes.iter().cloned())
+		.collect()
+	}
 }
 
 impl Default for ConnectionParams {
@@ -48,6 +103,27 @@ impl Default for ConnectionParams {
 		ConnectionParams {
 			host: "localhost".into(),
 			port: 9944,
+			fallback_nodes: Vec::new(),
+			rate_limiter: Default::default(),
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn node_address_is_parsed() {
+		assert_eq!(
+			"127.0.0.1:9944".parse(),
+			Ok(NodeAddress {
+				host: "127.0.0.1".into(),
+				port: 9944,
+			}),
+		);
+		assert!("127.0.0.1".parse::<NodeAddress>().is_err());
+		assert!(":9944".parse::<NodeAddress>().is_err());
+		assert!("localhost:port".parse::<NodeAddress>().is_err());
+	}
+}
@@ -0,0 +1,279 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Types and helpers that are shared by clients of the Polkadot-like chains (Polkadot, Kusama,
+//! Westend).
+//!
+//! Runtimes of these chains are not available to the relay, so calls are passed around in
+//! their encoded form and transactions are constructed by the relay itself. Runtimes of public
+//! chains are upgraded independently of the relay, so runtime versions that are included in
+//! the signed payload are read from the node instead of being hardcoded.
+
+use crate::chain::{Chain, TransactionEraOf};
+use crate::client::Client;
+use crate::signer::{RemoteSigner, TransactionSigner};
+use crate::Result;
+
+use bp_polkadot_core::{AccountId, Balance, BlockNumber, Hash, Header, Index, Signature};
+use codec::{Compact, Encode, Output};
+use headers_relay::sync_types::SourceHeader;
+use sp_core::{
+	hashing::{blake2_128, blake2_256, twox_128},
+	storage::StorageKey,
+	Pair,
+};
+use sp_runtime::{
+	generic::Era,
+	traits::{Header as HeaderT, IdentifyAccount},
+	MultiSigner,
+};
+use std::sync::Arc;
+
+/// Version of the extrinsic format that is supported by Polkadot-like chains.
+const TRANSACTION_FORMAT_VERSION: u8 = 4;
+/// Bit that is set in the extrinsic format version byte if extrinsic is signed.
+const SIGNED_TRANSACTION_BIT: u8 = 0b1000_0000;
+
+/// Polkadot-like chain transactions signer.
+pub type AccountSigner =
+	dyn TransactionSigner<Public = sp_core::sr25519::Public, Signature = sp_core::sr25519::Signature>;
+
+/// Header id of Polkadot-like chain.
+pub type HeaderId = relay_utils::HeaderId<Hash, BlockNumber>;
+
+/// Encoded call of Polkadot-like chain.
+///
+/// The call is encoded as is, without length prefix, so it may be used wherever runtime call
+/// is expected.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EncodedCall(pub Vec<u8>);
+
+impl Encode for EncodedCall {
+	fn size_hint(&self) -> usize {
+		self.0.len()
+	}
+
+	fn encode_to<T: Output>(&self, dest: &mut T) {
+		dest.write(&self.0)
+	}
+}
+
+/// Signed extensions of Polkadot-like chain transaction that are included in the transaction.
+///
+/// All other extensions (spec and transaction versions, genesis hash, weight and claims checks)
+/// are either empty, or only contribute to the signed payload.
+#[derive(Clone, Debug, PartialEq, Encode)]
+pub struct SignedExtensions {
+	/// Transaction era.
+	pub era: Era,
+	/// Nonce of the transaction signer.
+	pub nonce: Compact<Index>,
+	/// Tip that is paid to the block author.
+	pub tip: Compact<Balance>,
+}
+
+/// Signed transaction of Polkadot-like chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UncheckedExtrinsic {
+	/// Account that has signed the transaction.
+	pub signer: AccountId,
+	/// Transaction signature.
+	pub signature: Signature,
+	/// Signed extensions.
+	pub extensions: SignedExtensions,
+	/// Encoded call.
+	pub call: EncodedCall,
+}
+
+impl Encode for UncheckedExtrinsic {
+	fn encode(&self) -> Vec<u8> {
+		let mut transaction = vec![TRANSACTION_FORMAT_VERSION | SIGNED_TRANSACTION_BIT];
+		self.signer.encode_to(&mut transaction);
+		self.signature.encode_to(&mut transaction);
+		self.extensions.encode_to(&mut transaction);
+		self.call.encode_to(&mut transaction);
+		transaction.encode()
+	}
+}
+
+/// Sign given call using the payload format of Polkadot-like chains.
+pub async fn sign_transaction<C>(
+	client: &Client<C>,
+	signer: &AccountSigner,
+	era: TransactionEraOf<C>,
+	signer_nonce: Index,
+	call: EncodedCall,
+) -> Result<UncheckedExtrinsic>
+where
+	C: Chain<Hash = Hash, BlockNumber = BlockNumber, Index = Index>,
+{
+	let runtime_version = client.runtime_version().await?;
+	let genesis_hash = *client.genesis_hash();
+	let extensions = SignedExtensions {
+		era: era.frame_era(),
+		nonce: Compact(signer_nonce),
+		tip: Compact(0),
+	};
+	let additional_signed = (
+		runtime_version.spec_version,
+		runtime_version.transaction_version,
+		genesis_hash,
+		era.signed_payload(genesis_hash),
+	);
+	let payload = (&call, &extensions, &additional_signed).encode();
+	// the same rule is used by the `sp_runtime::generic::SignedPayload`
+	let payload = if payload.len() > 256 {
+		blake2_256(&payload).to_vec()
+	} else {
+		payload
+	};
+
+	let signature = signer.sign(payload).await?;
+	let signer: MultiSigner = signer.public().into();
+	Ok(UncheckedExtrinsic {
+		signer: signer.into_account(),
+		signature: signature.into(),
+		extensions,
+		call,
+	})
+}
+
+/// Return runtime storage key for getting `frame_system::AccountInfo` of given account.
+pub fn account_info_storage_key(account_id: &AccountId) -> StorageKey {
+	let encoded_account_id = account_id.encode();
+	let mut key = twox_128(b"System").to_vec();
+	key.extend_from_slice(&twox_128(b"Account"));
+	key.extend_from_slice(&blake2_128(&encoded_account_id));
+	key.extend_from_slice(&encoded_account_id);
+	StorageKey(key)
+}
+
+/// Polkadot-like chain signing params.
+#[derive(Clone)]
+pub struct SigningParams {
+	/// Substrate transactions signer.
+	pub signer: Arc<AccountSigner>,
+	/// Transactions mortality period (in blocks). If `None`, transactions are immortal.
+	pub transactions_mortality: Option<u32>,
+}
+
+impl SigningParams {
+	/// Create signing params from SURI and password.
+	pub fn from_suri(
+		suri: &str,
+		password: Option<&str>,
+	) -> std::result::Result<Self, sp_core::crypto::SecretStringError> {
+		Ok(SigningParams {
+			signer: Arc::new(sp_core::sr25519::Pair::from_string(suri, password)?),
+			transactions_mortality: None,
+		})
+	}
+
+	/// Create signing params that are using remote signing service.
+	pub async fn from_remote_signer(uri: &str, public: sp_core::sr25519::Public) -> Result<Self> {
+		Ok(SigningParams {
+			signer: Arc::new(RemoteSigner::<_, sp_core::sr25519::Signature>::new(uri, public).await?),
+			transactions_mortality: None,
+		})
+	}
+
+	/// Account that is the origin of all calls, submitted using these params.
+	pub fn origin_account(&self) -> AccountId {
+		self.signer.public().as_array_ref().clone().into()
+	}
+}
+
+impl std::fmt::Debug for SigningParams {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "{}", self.signer.public())
+	}
+}
+
+/// Polkadot-like chain header type used in headers sync.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyncHeader(Header);
+
+impl std::ops::Deref for SyncHeader {
+	type Target = Header;
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl From<Header> for SyncHeader {
+	fn from(header: Header) -> Self {
+		Self(header)
+	}
+}
+
+impl From<SyncHeader> for Header {
+	fn from(header: SyncHeader) -> Self {
+		header.0
+	}
+}
+
+impl SourceHeader<Hash, BlockNumber> for SyncHeader {
+	fn id(&self) -> HeaderId {
+		relay_utils::HeaderId(*self.number(), self.hash())
+	}
+
+	fn parent_id(&self) -> HeaderId {
+		relay_utils::HeaderId(*self.number() - 1, *self.parent_hash())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use hex_literal::hex;
+
+	#[test]
+	fn account_info_storage_key_is_computed() {
+		let account_id = AccountId::from([1u8; 32]);
+		let storage_key = account_info_storage_key(&account_id);
+		assert_eq!(
+			storage_key.0[..48].to_vec(),
+			hex!("26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da9c035f853fcd0f0589e30c9e2dc1a0f57")
+				.to_vec(),
+		);
+		assert_eq!(storage_key.0[48..].to_vec(), vec![1u8; 32]);
+	}
+
+	#[test]
+	fn unchecked_extrinsic_is_encoded_as_signed_v4_extrinsic() {
+		let transaction = UncheckedExtrinsic {
+			signer: AccountId::from([1u8; 32]),
+			signature: sp_core::sr25519::Signature::from_raw([2u8; 64]).into(),
+			extensions: SignedExtensions {
+				era: Era::Immortal,
+				nonce: Compact(3),
+				tip: Compact(0),
+			},
+			call: EncodedCall(vec![4, 5]),
+		};
+
+		let mut expected = vec![0x84];
+		expected.extend_from_slice(&[1u8; 32]);
+		// `MultiSignature::Sr25519` variant
+		expected.push(1);
+		expected.extend_from_slice(&[2u8; 64]);
+		// immortal era, compact nonce and compact tip
+		expected.extend_from_slice(&[0, 3 << 2, 0]);
+		expected.extend_from_slice(&[4, 5]);
+		assert_eq!(transaction.encode(), expected.encode());
+	}
+}
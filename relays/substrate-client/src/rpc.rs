@@ -45,12 +45,25 @@ jsonrpsee::rpc_api! {
 		fn system_account_next_index(account_id: C::AccountId) -> C::Index;
 		#[rpc(method = "author_submitExtrinsic", positional_params)]
 		fn author_submit_extrinsic(extrinsic: Bytes) -> C::Hash;
+		#[rpc(method = "system_dryRun", positional_params)]
+		fn system_dry_run(extrinsic: Bytes, at_block: Option<C::Hash>) -> Bytes;
 		#[rpc(method = "state_call", positional_params)]
 		fn state_call(method: String, data: Bytes, at_block: Option<C::Hash>) -> Bytes;
 		#[rpc(method = "state_getStorage", positional_params)]
 		fn get_storage(key: StorageKey) -> Option<StorageData>;
+		#[rpc(method = "state_getStorage", positional_params)]
+		fn get_storage_at(key: StorageKey, at_block: Option<C::Hash>) -> Option<StorageData>;
+		#[rpc(method = "state_getKeysPaged", positional_params)]
+		fn get_keys_paged(
+			prefix: StorageKey,
+			count: u32,
+			start_key: Option<StorageKey>,
+			at_block: Option<C::Hash>,
+		) -> Vec<StorageKey>;
 		#[rpc(method = "state_getRuntimeVersion", positional_params)]
 		fn runtime_version() -> RuntimeVersion;
+		#[rpc(method = "state_getMetadata", positional_params)]
+		fn runtime_metadata() -> Bytes;
 	}
 
 	pub(crate) SubstrateMessageLane<C: Chain> {
@@ -71,4 +84,9 @@ jsonrpsee::rpc_api! {
 			block: Option<C::Hash>,
 		) -> Bytes;
 	}
+
+	pub(crate) RemoteSignerApi {
+		#[rpc(method = "signer_signPayload", positional_params)]
+		fn sign_payload(public: Bytes, payload: Bytes) -> Bytes;
+	}
 }
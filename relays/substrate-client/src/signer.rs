@@ -0,0 +1,132 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transactions signers.
+//!
+//! Signer is either a key pair that is held by the relay process itself, or a remote signing
+//! service (e.g. one that is backed by the hardware wallet), so that the relay never sees the
+//! raw seed.
+
+use crate::rpc::RemoteSignerApi;
+use crate::{Error, Result};
+
+use async_trait::async_trait;
+use codec::{Decode, Encode};
+use jsonrpsee::raw::RawClient;
+use jsonrpsee::transport::ws::WsTransportClient;
+use jsonrpsee::Client as RpcClient;
+use sp_core::{Bytes, Pair};
+use std::marker::PhantomData;
+
+/// Transactions signer.
+#[async_trait]
+pub trait TransactionSigner: Send + Sync {
+	/// Public key of the signer.
+	type Public;
+	/// Signature that is produced by the signer.
+	type Signature;
+
+	/// Return public key of the signer.
+	fn public(&self) -> Self::Public;
+	/// Sign given payload.
+	async fn sign(&self, payload: Vec<u8>) -> Result<Self::Signature>;
+}
+
+#[async_trait]
+impl<P: Pair> TransactionSigner for P
+where
+	P::Signature: Send,
+{
+	type Public = P::Public;
+	type Signature = P::Signature;
+
+	fn public(&self) -> Self::Public {
+		Pair::public(self)
+	}
+
+	async fn sign(&self, payload: Vec<u8>) -> Result<Self::Signature> {
+		Ok(Pair::sign(self, &payload))
+	}
+}
+
+/// Signer that delegates signing to the remote signing service.
+///
+/// The service is expected to expose `signer_signPayload(public, payload)` JSON-RPC method,
+/// which signs `payload` with the key, identified by the SCALE-encoded `public` key, and
+/// returns SCALE-encoded signature.
+pub struct RemoteSigner<Public, Signature> {
+	/// Signing service client.
+	client: RpcClient,
+	/// Public key of the signer.
+	public: Public,
+	/// Phantom signature type.
+	_phantom: PhantomData<Signature>,
+}
+
+impl<Public, Signature> RemoteSigner<Public, Signature> {
+	/// Connect to remote signing service at given websocket URI.
+	pub async fn new(uri: &str, public: Public) -> Result<Self> {
+		let transport = WsTransportClient::new(uri).await?;
+		let raw_client = RawClient::new(transport);
+		Ok(RemoteSigner {
+			client: raw_client.into(),
+			public,
+			_phantom: Default::default(),
+		})
+	}
+}
+
+impl<Public: std::fmt::Debug, Signature> std::fmt::Debug for RemoteSigner<Public, Signature> {
+	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+		fmt.debug_struct("RemoteSigner").field("public", &self.public).finish()
+	}
+}
+
+#[async_trait]
+impl<Public, Signature> TransactionSigner for RemoteSigner<Public, Signature>
+where
+	Public: Clone + Encode + Send + Sync,
+	Signature: Decode + Send + Sync,
+{
+	type Public = Public;
+	type Signature = Signature;
+
+	fn public(&self) -> Self::Public {
+		self.public.clone()
+	}
+
+	async fn sign(&self, payload: Vec<u8>) -> Result<Self::Signature> {
+		let encoded_signature =
+			RemoteSignerApi::sign_payload(&self.client, Bytes(self.public.encode()), Bytes(payload)).await?;
+		Signature::decode(&mut &encoded_signature.0[..]).map_err(Error::ResponseParseFailed)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn key_pair_signer_works() {
+		let pair = sp_core::sr25519::Pair::from_seed(&[42u8; 32]);
+		let signer: &dyn TransactionSigner<Public = sp_core::sr25519::Public, Signature = sp_core::sr25519::Signature> =
+			&pair;
+
+		let signature = async_std::task::block_on(signer.sign(b"payload".to_vec())).unwrap();
+		assert_eq!(signer.public(), Pair::public(&pair));
+		assert!(sp_core::sr25519::Pair::verify(&signature, b"payload", &signer.public()));
+	}
+}
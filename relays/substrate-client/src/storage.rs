@@ -0,0 +1,358 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime storage entries, described by the runtime metadata.
+//!
+//! Storage keys are computed using pallet prefixes and hashers, read from the runtime metadata,
+//! so the relay doesn't need to hardcode them and keeps working when storage is renamed.
+
+use crate::{Error, Result};
+
+use codec::{Decode, Encode};
+use frame_metadata::{
+	DecodeDifferent, RuntimeMetadata, RuntimeMetadataPrefixed, StorageEntryType, StorageHasher, META_RESERVED,
+};
+use sp_core::{
+	hashing::{blake2_128, blake2_256, twox_128, twox_256, twox_64},
+	storage::StorageKey,
+};
+use std::collections::HashMap;
+
+/// Kind of the runtime storage entry.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StorageEntryKind {
+	/// Plain storage value.
+	Value,
+	/// Map with keys hashed by given hasher.
+	Map(StorageHasher),
+	/// Double map with keys hashed by given hashers.
+	DoubleMap(StorageHasher, StorageHasher),
+}
+
+/// Runtime storage entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageEntry {
+	/// Storage prefix of the pallet.
+	pub prefix: String,
+	/// Name of the storage entry.
+	pub name: String,
+	/// Kind of the storage entry.
+	pub kind: StorageEntryKind,
+}
+
+impl StorageEntry {
+	/// Return storage key of the plain storage value.
+	pub fn value_key(&self) -> Result<StorageKey> {
+		match self.kind {
+			StorageEntryKind::Value => Ok(StorageKey(self.entry_prefix())),
+			_ => Err(self.unexpected_kind("value")),
+		}
+	}
+
+	/// Return storage key of the map entry.
+	pub fn map_key(&self, key: &impl Encode) -> Result<StorageKey> {
+		match self.kind {
+			StorageEntryKind::Map(ref hasher) => {
+				let mut storage_key = self.entry_prefix();
+				storage_key.extend(hash(hasher, &key.encode()));
+				Ok(StorageKey(storage_key))
+			}
+			_ => Err(self.unexpected_kind("map")),
+		}
+	}
+
+	/// Return storage key of the double map entry.
+	pub fn double_map_key(&self, key1: &impl Encode, key2: &impl Encode) -> Result<StorageKey> {
+		match self.kind {
+			StorageEntryKind::DoubleMap(ref hasher1, ref hasher2) => {
+				let mut storage_key = self.entry_prefix();
+				storage_key.extend(hash(hasher1, &key1.encode()));
+				storage_key.extend(hash(hasher2, &key2.encode()));
+				Ok(StorageKey(storage_key))
+			}
+			_ => Err(self.unexpected_kind("double map")),
+		}
+	}
+
+	/// Return prefix of all storage keys of this entry.
+	fn entry_prefix(&self) -> Vec<u8> {
+		let mut prefix = twox_128(self.prefix.as_bytes()).to_vec();
+		prefix.extend_from_slice(&twox_128(self.name.as_bytes()));
+		prefix
+	}
+
+	fn unexpected_kind(&self, expected_kind: &str) -> Error {
+		Error::UnexpectedStorageEntryKind(format!(
+			"{}.{} is not a {}, but {:?}",
+			self.prefix, self.name, expected_kind, self.kind,
+		))
+	}
+}
+
+/// Storage entries of all runtime pallets.
+#[derive(Debug, Default)]
+pub struct RuntimeStorage {
+	/// Storage entries, indexed by pallet name (as declared in the runtime) and entry name.
+	entries: HashMap<(String, String), StorageEntry>,
+}
+
+impl RuntimeStorage {
+	/// Read storage entries from encoded runtime metadata.
+	pub fn from_metadata(mut encoded_metadata: &[u8]) -> Result<Self> {
+		let metadata = RuntimeMetadataPrefixed::decode(&mut encoded_metadata).map_err(Error::ResponseParseFailed)?;
+		if metadata.0 != META_RESERVED {
+			return Err(Error::MetadataParseFailed(format!(
+				"invalid metadata magic number: {}",
+				metadata.0
+			)));
+		}
+		let modules = match metadata.1 {
+			RuntimeMetadata::V12(metadata) => decoded(metadata.modules)?,
+			_ => return Err(Error::MetadataParseFailed("unsupported metadata version".into())),
+		};
+
+		let mut entries = HashMap::new();
+		for module in modules {
+			let storage = match module.storage {
+				Some(storage) => decoded(storage)?,
+				None => continue,
+			};
+			let pallet = decoded(module.name)?;
+			let prefix = decoded(storage.prefix)?;
+			for entry in decoded(storage.entries)? {
+				let name = decoded(entry.name)?;
+				let kind = match entry.ty {
+					StorageEntryType::Plain(_) => StorageEntryKind::Value,
+					StorageEntryType::Map { hasher, .. } => StorageEntryKind::Map(hasher),
+					StorageEntryType::DoubleMap {
+						hasher, key2_hasher, ..
+					} => StorageEntryKind::DoubleMap(hasher, key2_hasher),
+				};
+				entries.insert(
+					(pallet.clone(), name.clone()),
+					StorageEntry {
+						prefix: prefix.clone(),
+						name,
+						kind,
+					},
+				);
+			}
+		}
+
+		Ok(RuntimeStorage { entries })
+	}
+
+	/// Return storage entry of given pallet.
+	pub fn entry(&self, pallet: &str, name: &str) -> Option<&StorageEntry> {
+		self.entries.get(&(pallet.to_owned(), name.to_owned()))
+	}
+}
+
+/// Return decoded part of the metadata.
+fn decoded<B, O>(value: DecodeDifferent<B, O>) -> Result<O> {
+	match value {
+		DecodeDifferent::Decoded(value) => Ok(value),
+		DecodeDifferent::Encode(_) => Err(Error::MetadataParseFailed("metadata is not decoded".into())),
+	}
+}
+
+/// Hash encoded storage key using given hasher.
+fn hash(hasher: &StorageHasher, data: &[u8]) -> Vec<u8> {
+	match hasher {
+		StorageHasher::Blake2_128 => blake2_128(data).to_vec(),
+		StorageHasher::Blake2_256 => blake2_256(data).to_vec(),
+		StorageHasher::Blake2_128Concat => blake2_128(data).iter().chain(data).cloned().collect(),
+		StorageHasher::Twox128 => twox_128(data).to_vec(),
+		StorageHasher::Twox256 => twox_256(data).to_vec(),
+		StorageHasher::Twox64Concat => twox_64(data).iter().chain(data).cloned().collect(),
+		StorageHasher::Identity => data.to_vec(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_metadata::{
+		ExtrinsicMetadata, ModuleMetadata, RuntimeMetadataV12, StorageEntryMetadata, StorageEntryModifier,
+		StorageMetadata,
+	};
+	use hex_literal::hex;
+
+	fn entry_metadata(name: &str, ty: StorageEntryType) -> StorageEntryMetadata {
+		StorageEntryMetadata {
+			name: DecodeDifferent::Decoded(name.into()),
+			modifier: StorageEntryModifier::Optional,
+			ty,
+			default: DecodeDifferent::Decoded(vec![0]),
+			documentation: DecodeDifferent::Decoded(vec![]),
+		}
+	}
+
+	fn encoded_metadata() -> Vec<u8> {
+		let type_name = |name: &str| DecodeDifferent::Decoded(name.into());
+		let module = ModuleMetadata {
+			name: DecodeDifferent::Decoded("BridgeRialtoMessageLane".into()),
+			storage: Some(DecodeDifferent::Decoded(StorageMetadata {
+				prefix: DecodeDifferent::Decoded("MessageLane".into()),
+				entries: DecodeDifferent::Decoded(vec![
+					entry_metadata(
+						"PalletStorageVersion",
+						StorageEntryType::Plain(type_name("StorageVersion")),
+					),
+					entry_metadata(
+						"InboundLanes",
+						StorageEntryType::Map {
+							hasher: StorageHasher::Blake2_128Concat,
+							key: type_name("LaneId"),
+							value: type_name("InboundLaneData"),
+							unused: false,
+						},
+					),
+					entry_metadata(
+						"OutboundMessages",
+						StorageEntryType::Map {
+							hasher: StorageHasher::Blake2_128Concat,
+							key: type_name("MessageKey"),
+							value: type_name("MessageData"),
+							unused: false,
+						},
+					),
+					entry_metadata(
+						"DoubleMap",
+						StorageEntryType::DoubleMap {
+							hasher: StorageHasher::Twox64Concat,
+							key1: type_name("u64"),
+							key2: type_name("LaneId"),
+							value: type_name("u64"),
+							key2_hasher: StorageHasher::Blake2_128Concat,
+						},
+					),
+				]),
+			})),
+			calls: None,
+			event: None,
+			constants: DecodeDifferent::Decoded(vec![]),
+			errors: DecodeDifferent::Decoded(vec![]),
+			index: 0,
+		};
+		let module_without_storage = ModuleMetadata {
+			name: DecodeDifferent::Decoded("Timestamp".into()),
+			storage: None,
+			calls: None,
+			event: None,
+			constants: DecodeDifferent::Decoded(vec![]),
+			errors: DecodeDifferent::Decoded(vec![]),
+			index: 1,
+		};
+
+		RuntimeMetadataPrefixed(
+			META_RESERVED,
+			RuntimeMetadata::V12(RuntimeMetadataV12 {
+				modules: DecodeDifferent::Decoded(vec![module, module_without_storage]),
+				extrinsic: ExtrinsicMetadata {
+					version: 4,
+					signed_extensions: vec![],
+				},
+			}),
+		)
+		.encode()
+	}
+
+	fn runtime_storage() -> RuntimeStorage {
+		RuntimeStorage::from_metadata(&encoded_metadata()).unwrap()
+	}
+
+	#[test]
+	fn storage_value_key_is_computed_using_metadata() {
+		let storage = runtime_storage();
+		let entry = storage
+			.entry("BridgeRialtoMessageLane", "PalletStorageVersion")
+			.unwrap();
+		assert_eq!(
+			entry.value_key().unwrap().0,
+			hex!("87f1ffe31b52878f09495ca7482df1a41601562ebcdff856cb2f34e65f3b2659").to_vec(),
+		);
+	}
+
+	#[test]
+	fn storage_map_key_is_computed_using_metadata() {
+		let storage = runtime_storage();
+		assert_eq!(
+			storage
+				.entry("BridgeRialtoMessageLane", "InboundLanes")
+				.unwrap()
+				.map_key(b"test")
+				.unwrap()
+				.0,
+			hex!("87f1ffe31b52878f09495ca7482df1a4e5f83cf83f2127eb47afdc35d6e43fab44a8995dd50b6657a037a7839304535b74657374").to_vec(),
+		);
+		assert_eq!(
+			storage
+				.entry("BridgeRialtoMessageLane", "OutboundMessages")
+				.unwrap()
+				.map_key(&(*b"test", 42u64))
+				.unwrap()
+				.0,
+			hex!("87f1ffe31b52878f09495ca7482df1a48a395e6242c6813b196ca31ed0547ea79446af0e09063bd4a7874aef8a997cec746573742a00000000000000").to_vec(),
+		);
+	}
+
+	#[test]
+	fn storage_double_map_key_is_computed_using_metadata() {
+		let storage = runtime_storage();
+		let entry = storage.entry("BridgeRialtoMessageLane", "DoubleMap").unwrap();
+		assert_eq!(
+			entry.double_map_key(&42u64, b"test").unwrap().0,
+			hex!("87f1ffe31b52878f09495ca7482df1a47f33b43bfd8411d12c644b0580975f945343d1b66f8056b52a0000000000000044a8995dd50b6657a037a7839304535b74657374").to_vec(),
+		);
+	}
+
+	#[test]
+	fn storage_entry_kind_is_checked() {
+		let storage = runtime_storage();
+		let value = storage
+			.entry("BridgeRialtoMessageLane", "PalletStorageVersion")
+			.unwrap();
+		let map = storage.entry("BridgeRialtoMessageLane", "InboundLanes").unwrap();
+		assert!(matches!(
+			value.map_key(&0u64),
+			Err(Error::UnexpectedStorageEntryKind(_))
+		));
+		assert!(matches!(map.value_key(), Err(Error::UnexpectedStorageEntryKind(_))));
+		assert!(matches!(
+			map.double_map_key(&0u64, &0u64),
+			Err(Error::UnexpectedStorageEntryKind(_))
+		));
+	}
+
+	#[test]
+	fn unknown_storage_entries_are_not_found() {
+		let storage = runtime_storage();
+		assert!(storage.entry("BridgeRialtoMessageLane", "IsHalted").is_none());
+		assert!(storage.entry("Timestamp", "Now").is_none());
+		assert!(storage.entry("MessageLane", "InboundLanes").is_none());
+	}
+
+	#[test]
+	fn invalid_metadata_is_rejected() {
+		let mut metadata = encoded_metadata();
+		metadata[0] = 0;
+		assert!(matches!(
+			RuntimeStorage::from_metadata(&metadata),
+			Err(Error::MetadataParseFailed(_))
+		));
+	}
+}
@@ -0,0 +1,217 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bridge state snapshots.
+//!
+//! Snapshot contains all storage entries of the bridge pallets (imported headers, lanes and
+//! messages) at the best finalized block of the live node. Entries are stored as raw hex-encoded
+//! key => value pairs, using the same format as the raw chain spec genesis storage. So the
+//! snapshot may be imported into the genesis of the fresh dev chain (see the
+//! `import-bridge-state` subcommand of Millau and Rialto nodes) to debug the bridge offline.
+//! Genesis entries of the exported pallets are replaced with the snapshot entries during import,
+//! so prefixes of pallets storage keys are also included in the snapshot.
+
+use crate::{MillauClient, RialtoClient};
+
+use relay_substrate_client::{Chain, Client};
+use serde::{Deserialize, Serialize};
+use sp_core::{bytes::to_hex, hashing::twox_128, storage::StorageKey};
+use sp_runtime::{
+	traits::{Header as HeaderT, UniqueSaturatedInto},
+	DeserializeOwned,
+};
+use std::{collections::BTreeMap, path::Path};
+
+/// Number of storage keys that are read using single RPC call.
+const KEYS_PAGE_SIZE: u32 = 512;
+
+/// Bridge pallets state snapshot.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct BridgeStateSnapshot {
+	/// Name of the chain.
+	pub chain: String,
+	/// Number of the block, at which state has been read.
+	pub block_number: u64,
+	/// Hex-encoded hash of the block, at which state has been read.
+	pub block_hash: String,
+	/// Names of pallets which state is included in the snapshot, mapped to hex-encoded prefixes of
+	/// their storage keys.
+	pub pallets: BTreeMap<String, String>,
+	/// Hex-encoded storage keys and values.
+	pub storage: BTreeMap<String, String>,
+}
+
+impl BridgeStateSnapshot {
+	/// Read snapshot from the file.
+	pub fn read(path: &Path) -> Result<Self, String> {
+		let json = std::fs::read(path).map_err(|e| format!("Failed to read bridge state from {:?}: {}", path, e))?;
+		serde_json::from_slice(&json).map_err(|e| format!("Failed to deserialize bridge state: {}", e))
+	}
+
+	/// Write snapshot to the file.
+	pub fn write(&self, path: &Path) -> Result<(), String> {
+		let json =
+			serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize bridge state: {}", e))?;
+		std::fs::write(path, json).map_err(|e| format!("Failed to write bridge state to {:?}: {}", path, e))
+	}
+}
+
+/// Export state of Millau bridge pallets.
+pub async fn export_millau_bridge_state(client: MillauClient) -> Result<BridgeStateSnapshot, String> {
+	export_bridge_state(
+		client,
+		&[
+			(
+				"BridgeRialto",
+				<pallet_substrate_bridge::DefaultInstance as pallet_substrate_bridge::Instance>::PREFIX,
+			),
+			(
+				"BridgeRialtoMessageLane",
+				<pallet_message_lane::DefaultInstance as pallet_message_lane::Instance>::PREFIX,
+			),
+		],
+	)
+	.await
+}
+
+/// Export state of Rialto bridge pallets.
+pub async fn export_rialto_bridge_state(client: RialtoClient) -> Result<BridgeStateSnapshot, String> {
+	export_bridge_state(
+		client,
+		&[
+			(
+				"BridgeMillau",
+				<pallet_substrate_bridge::DefaultInstance as pallet_substrate_bridge::Instance>::PREFIX,
+			),
+			(
+				"BridgeMillauMessageLane",
+				<pallet_message_lane::DefaultInstance as pallet_message_lane::Instance>::PREFIX,
+			),
+			(
+				"BridgeThirdChain",
+				<rialto_runtime::third_chain_messages::ThirdChainHeadersInstance as pallet_substrate_bridge::Instance>::PREFIX,
+			),
+			(
+				"BridgeThirdChainMessageLane",
+				<rialto_runtime::third_chain_messages::ThirdChainMessageLaneInstance as pallet_message_lane::Instance>::PREFIX,
+			),
+		],
+	)
+	.await
+}
+
+/// Read all storage entries of given pallets at the best finalized block.
+///
+/// Pallets are given as (pallet name, module storage prefix) pairs.
+async fn export_bridge_state<C: Chain>(
+	client: Client<C>,
+	pallets: &[(&'static str, &'static str)],
+) -> Result<BridgeStateSnapshot, String>
+where
+	C::Header: DeserializeOwned,
+{
+	let block_hash = client
+		.best_finalized_header_hash()
+		.await
+		.map_err(|e| format!("Failed to read {} best finalized header hash: {:?}", C::NAME, e))?;
+	let block_number = *client
+		.header_by_hash(block_hash)
+		.await
+		.map_err(|e| format!("Failed to read {} header {:?}: {:?}", C::NAME, block_hash, e))?
+		.number();
+
+	let mut exported_pallets = BTreeMap::new();
+	let mut storage = BTreeMap::new();
+	for (pallet, module_prefix) in pallets {
+		let prefix = StorageKey(module_storage_prefix(module_prefix).to_vec());
+		exported_pallets.insert((*pallet).into(), to_hex(&prefix.0, false));
+		let mut start_key = None;
+		loop {
+			let keys = client
+				.storage_keys_paged(prefix.clone(), KEYS_PAGE_SIZE, start_key, block_hash)
+				.await
+				.map_err(|e| format!("Failed to read {} storage keys of {}: {:?}", C::NAME, pallet, e))?;
+			for key in &keys {
+				let value = client
+					.raw_storage_value(key.clone(), block_hash)
+					.await
+					.map_err(|e| format!("Failed to read {} storage value of {}: {:?}", C::NAME, pallet, e))?;
+				// the key may have been removed, but we're reading at the same block, so it is not
+				// expected
+				if let Some(value) = value {
+					storage.insert(to_hex(&key.0, false), to_hex(&value.0, false));
+				}
+			}
+
+			if keys.len() < KEYS_PAGE_SIZE as usize {
+				break;
+			}
+			start_key = keys.last().cloned();
+		}
+
+		log::info!(
+			target: "bridge",
+			"Exported {} storage of {} pallet. Total entries: {}",
+			C::NAME,
+			pallet,
+			storage.len(),
+		);
+	}
+
+	Ok(BridgeStateSnapshot {
+		chain: C::NAME.into(),
+		block_number: block_number.unique_saturated_into(),
+		block_hash: format!("{:?}", block_hash),
+		pallets: exported_pallets,
+		storage,
+	})
+}
+
+/// Return prefix of all storage keys of the pallet with given module storage prefix.
+fn module_storage_prefix(module_prefix: &str) -> [u8; 16] {
+	twox_128(module_prefix.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn message_lane_storage_keys_belong_to_exported_pallet() {
+		let lane = bp_message_lane::LaneId::from_bytes(*b"test");
+		let outbound_lane_key =
+			pallet_message_lane::storage_keys::outbound_lane_data_key::<pallet_message_lane::DefaultInstance>(&lane);
+		assert!(outbound_lane_key.0.starts_with(&module_storage_prefix(
+			<pallet_message_lane::DefaultInstance as pallet_message_lane::Instance>::PREFIX
+		)));
+	}
+
+	#[test]
+	fn snapshot_storage_is_serialized_as_raw_genesis_storage() {
+		let snapshot = BridgeStateSnapshot {
+			chain: "Millau".into(),
+			block_number: 1,
+			block_hash: "0x00".into(),
+			pallets: vec![("BridgeRialto".into(), to_hex(&[1], false))].into_iter().collect(),
+			storage: vec![(to_hex(&[1, 2], false), to_hex(&[3], false))]
+				.into_iter()
+				.collect(),
+		};
+		let json = serde_json::to_value(&snapshot).unwrap();
+		assert_eq!(json["storage"], serde_json::json!({ "0x0102": "0x03" }));
+		assert_eq!(serde_json::from_value::<BridgeStateSnapshot>(json).unwrap(), snapshot);
+	}
+}
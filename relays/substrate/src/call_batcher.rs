@@ -0,0 +1,235 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Batching of calls, submitted by different relays to the same chain.
+//!
+//! When several relays (e.g. messages delivery relay and delivery confirmations relay of the
+//! opposite lane) are submitting transactions to the same chain using the same account, their
+//! calls may be wrapped into single transaction. It saves per-transaction fees on chains where
+//! they are expensive.
+//!
+//! The first call that is submitted to the batcher opens the batch. All calls that are submitted
+//! within the batching window are then submitted in a single transaction.
+
+use codec::Encode;
+use futures::{channel::oneshot, future::BoxFuture};
+use relay_substrate_client::{Chain, Client, Error as SubstrateError, HashOf, TransactionSignScheme};
+use sp_core::Bytes;
+use sp_runtime::DeserializeOwned;
+use std::{
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+
+/// Function that submits given calls in a single transaction and returns hash of this
+/// transaction.
+pub type CallsSubmitter<C> =
+	Arc<dyn Fn(Vec<<C as Chain>::Call>) -> BoxFuture<'static, Result<HashOf<C>, SubstrateError>> + Send + Sync>;
+
+/// Call that is waiting for the batch to be submitted.
+type PendingCall<C> = (<C as Chain>::Call, oneshot::Sender<Result<HashOf<C>, SubstrateError>>);
+
+/// Batcher of calls that are submitted to the same chain.
+pub struct CallBatcher<C: Chain> {
+	window: Duration,
+	submitter: CallsSubmitter<C>,
+	pending: Arc<Mutex<Vec<PendingCall<C>>>>,
+}
+
+impl<C: Chain> Clone for CallBatcher<C> {
+	fn clone(&self) -> Self {
+		CallBatcher {
+			window: self.window,
+			submitter: self.submitter.clone(),
+			pending: self.pending.clone(),
+		}
+	}
+}
+
+impl<C: Chain> CallBatcher<C>
+where
+	C::Call: Send + 'static,
+{
+	/// Create new batcher that is collecting calls for the `window` period and then submits them
+	/// using given submitter.
+	pub fn new(window: Duration, submitter: CallsSubmitter<C>) -> Self {
+		CallBatcher {
+			window,
+			submitter,
+			pending: Arc::new(Mutex::new(Vec::new())),
+		}
+	}
+
+	/// Submit call as a part of the next batch and wait until the batch is submitted.
+	///
+	/// Returns hash of the transaction that includes the call. If the batch submission has failed,
+	/// the caller that has opened the batch gets the original error, and all other callers are
+	/// getting `SubstrateError::Custom` error.
+	pub async fn submit_call(&self, call: C::Call) -> Result<HashOf<C>, SubstrateError> {
+		let (sender, receiver) = oneshot::channel();
+		let opens_batch = {
+			let mut pending = self.pending.lock().expect("batcher mutex is never poisoned; qed");
+			pending.push((call, sender));
+			pending.len() == 1
+		};
+
+		if opens_batch {
+			let window = self.window;
+			let submitter = self.submitter.clone();
+			let pending = self.pending.clone();
+			async_std::task::spawn(async move {
+				async_std::task::sleep(window).await;
+				let batch = std::mem::take(&mut *pending.lock().expect("batcher mutex is never poisoned; qed"));
+				submit_batch::<C>(submitter, batch).await;
+			});
+		}
+
+		receiver.await.unwrap_or_else(|_| {
+			Err(SubstrateError::Custom(format!(
+				"Batch of {} calls has been dropped before submission",
+				C::NAME,
+			)))
+		})
+	}
+}
+
+/// Submit batch of calls and notify all waiters.
+async fn submit_batch<C: Chain>(submitter: CallsSubmitter<C>, batch: Vec<PendingCall<C>>) {
+	let (calls, senders): (Vec<_>, Vec<_>) = batch.into_iter().unzip();
+	let calls_count = calls.len();
+	let result = submitter(calls).await;
+	match result {
+		Ok(tx_hash) => {
+			log::trace!(
+				target: "bridge",
+				"Submitted batch of {} {} calls in transaction {:?}",
+				calls_count,
+				C::NAME,
+				tx_hash,
+			);
+
+			for sender in senders {
+				let _ = sender.send(Ok(tx_hash));
+			}
+		}
+		Err(error) => {
+			let error_message = format!(
+				"Failed to submit batch of {} {} calls: {:?}",
+				calls_count,
+				C::NAME,
+				error
+			);
+			let mut senders = senders.into_iter();
+			if let Some(sender) = senders.next() {
+				let _ = sender.send(Err(error));
+			}
+			for sender in senders {
+				let _ = sender.send(Err(SubstrateError::Custom(error_message.clone())));
+			}
+		}
+	}
+}
+
+/// Sign given call and submit it to the node.
+///
+/// Signed extrinsic submission lock of the client is held while the transaction is prepared and
+/// submitted.
+pub async fn sign_and_submit_call<C>(
+	client: &Client<C>,
+	signer: &C::AccountSigner,
+	signer_account: C::AccountId,
+	transactions_mortality: Option<u32>,
+	call: C::Call,
+) -> Result<HashOf<C>, SubstrateError>
+where
+	C: Chain + TransactionSignScheme<Chain = C>,
+	C::Header: DeserializeOwned,
+	C::SignedTransaction: Encode,
+{
+	let _submission_lock = client.lock_signed_extrinsic_submission().await;
+	let nonce = client.next_account_index(signer_account).await?;
+	let era = client.transaction_era(transactions_mortality).await?;
+	let transaction = C::sign_transaction(client, signer, era, nonce, call).await?;
+	client.submit_extrinsic(Bytes(transaction.encode())).await
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::FutureExt;
+	use relay_millau_client::Millau;
+	use sp_core::H256;
+
+	type SubmittedBatches = Arc<Mutex<Vec<Vec<millau_runtime::Call>>>>;
+
+	fn call(index: u8) -> millau_runtime::Call {
+		millau_runtime::SystemCall::remark(vec![index]).into()
+	}
+
+	fn test_batcher(fail: bool) -> (CallBatcher<Millau>, SubmittedBatches) {
+		let submitted = SubmittedBatches::default();
+		let batcher = CallBatcher::new(Duration::from_millis(100), {
+			let submitted = submitted.clone();
+			Arc::new(move |calls: Vec<millau_runtime::Call>| {
+				let mut submitted = submitted.lock().unwrap();
+				submitted.push(calls);
+				let result = if fail {
+					Err(SubstrateError::AccountDoesNotExist)
+				} else {
+					Ok(H256::from_low_u64_be(submitted.len() as u64))
+				};
+				futures::future::ready(result).boxed()
+			})
+		});
+		(batcher, submitted)
+	}
+
+	#[test]
+	fn calls_within_window_are_submitted_in_single_batch() {
+		let (batcher, submitted) = test_batcher(false);
+		let (result1, result2) = async_std::task::block_on(futures::future::join(
+			batcher.submit_call(call(1)),
+			batcher.submit_call(call(2)),
+		));
+
+		assert_eq!(result1.unwrap(), H256::from_low_u64_be(1));
+		assert_eq!(result2.unwrap(), H256::from_low_u64_be(1));
+		assert_eq!(*submitted.lock().unwrap(), vec![vec![call(1), call(2)]]);
+	}
+
+	#[test]
+	fn calls_after_batch_submission_are_submitted_in_next_batch() {
+		let (batcher, submitted) = test_batcher(false);
+		let result1 = async_std::task::block_on(batcher.submit_call(call(1)));
+		let result2 = async_std::task::block_on(batcher.submit_call(call(2)));
+
+		assert_eq!(result1.unwrap(), H256::from_low_u64_be(1));
+		assert_eq!(result2.unwrap(), H256::from_low_u64_be(2));
+		assert_eq!(*submitted.lock().unwrap(), vec![vec![call(1)], vec![call(2)]]);
+	}
+
+	#[test]
+	fn batch_submission_error_is_reported_to_all_callers() {
+		let (batcher, _) = test_batcher(true);
+		let (result1, result2) = async_std::task::block_on(futures::future::join(
+			batcher.submit_call(call(1)),
+			batcher.submit_call(call(2)),
+		));
+
+		assert!(matches!(result1, Err(SubstrateError::AccountDoesNotExist)));
+		assert!(matches!(result2, Err(SubstrateError::Custom(_))));
+	}
+}
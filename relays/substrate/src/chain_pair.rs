@@ -0,0 +1,330 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Chain pairs that are served by the relay.
+//!
+//! Every bridge between two Substrate chains is described by the `ChainPair` implementation,
+//! which is composed of two `SubstrateBridge` implementations (one for every direction). The
+//! bridge declares both chains, their signing params and builders of headers and messages
+//! relays. Relay subcommands are generated for every registered chain pair, so adding new bridge
+//! doesn't require any changes to the command dispatcher.
+
+use crate::call_batcher::CallBatcher;
+
+use async_trait::async_trait;
+use bp_message_lane::LaneId;
+use messages_relay::chaos::ChaosParams;
+use relay_substrate_client::{Chain, Client};
+use relay_utils::metrics::MetricsParams;
+use sp_core::Bytes;
+use sp_finality_grandpa::SetId as GrandpaAuthoritiesSetId;
+use std::{thread::JoinHandle, time::Duration};
+
+/// Bridge between two Substrate chains in one direction: headers and messages of the source
+/// chain are relayed to the target chain.
+#[async_trait]
+pub trait SubstrateBridge {
+	/// Source chain of the bridge.
+	type Source: Chain;
+	/// Target chain of the bridge.
+	type Target: Chain;
+	/// Params that are used to sign source chain transactions.
+	type SourceSigningParams: Clone + Send + Sync + 'static;
+	/// Params that are used to sign target chain transactions.
+	type TargetSigningParams: Clone + Send + Sync + 'static;
+
+	/// Initialize source chain headers bridge at the target chain.
+	async fn initialize_headers_bridge(
+		source_client: Client<Self::Source>,
+		target_client: Client<Self::Target>,
+		target_sign: Self::TargetSigningParams,
+		params: HeadersBridgeInitializationParams,
+	) -> Result<(), String>;
+
+	/// Relay source chain headers to the target chain.
+	async fn relay_headers(
+		source_client: Client<Self::Source>,
+		target_client: Client<Self::Target>,
+		target_sign: Self::TargetSigningParams,
+		metrics_params: Option<MetricsParams>,
+	);
+
+	/// Serve lane of source -> target messages.
+	fn relay_messages(
+		source_client: Client<Self::Source>,
+		source_sign: Self::SourceSigningParams,
+		target_client: Client<Self::Target>,
+		target_sign: Self::TargetSigningParams,
+		params: MessagesRelayParams<Self::Source, Self::Target>,
+	);
+
+	/// Create batcher of target chain calls. Batches are signed by the `target_sign` account.
+	fn target_call_batcher(
+		target_client: Client<Self::Target>,
+		target_sign: Self::TargetSigningParams,
+		window: Duration,
+	) -> CallBatcher<Self::Target>;
+}
+
+/// Pair of chains that are bridged in both directions.
+pub trait ChainPair {
+	/// The left chain of the pair.
+	type Left: Chain;
+	/// The right chain of the pair.
+	type Right: Chain;
+	/// Left -> right bridge.
+	type LeftToRight: SubstrateBridge<Source = Self::Left, Target = Self::Right>;
+	/// Right -> left bridge.
+	type RightToLeft: SubstrateBridge<
+		Source = Self::Right,
+		Target = Self::Left,
+		SourceSigningParams = RightSigningParams<Self>,
+		TargetSigningParams = LeftSigningParams<Self>,
+	>;
+}
+
+/// Signing params of the left chain of the pair.
+pub type LeftSigningParams<P> = <<P as ChainPair>::LeftToRight as SubstrateBridge>::SourceSigningParams;
+/// Signing params of the right chain of the pair.
+pub type RightSigningParams<P> = <<P as ChainPair>::LeftToRight as SubstrateBridge>::TargetSigningParams;
+
+/// Params of the headers bridge initialization.
+#[derive(Debug, Default)]
+pub struct HeadersBridgeInitializationParams {
+	/// Encoded header to initialize bridge with. If not specified, genesis header is used.
+	pub initial_header: Option<Bytes>,
+	/// Encoded GRANDPA authorities set to initialize bridge with. If not specified, set from
+	/// genesis block is used.
+	pub initial_authorities_set: Option<Bytes>,
+	/// Id of the GRANDPA authorities set to initialize bridge with. If not specified, zero is used.
+	pub initial_authorities_set_id: Option<GrandpaAuthoritiesSetId>,
+}
+
+/// Params of the messages relay.
+pub struct MessagesRelayParams<Source: Chain, Target: Chain> {
+	/// Lane that is served by the relay.
+	pub lane: LaneId,
+	/// Maximal acceptable delivery delay of the oldest undelivered message.
+	pub delivery_deadline: Option<Duration>,
+	/// Maximal total reward of delivered, but not yet confirmed messages.
+	pub max_unconfirmed_rewards: Option<u128>,
+	/// Faults that are injected into the relay.
+	pub chaos_params: ChaosParams,
+	/// Prometheus metrics params.
+	pub metrics_params: Option<MetricsParams>,
+	/// OpenTelemetry collector endpoint, where traces of relayed messages are exported.
+	pub otlp_endpoint: Option<String>,
+	/// Batcher of delivery confirmation calls. If `None`, every call is submitted in its own
+	/// transaction.
+	pub source_batcher: Option<CallBatcher<Source>>,
+	/// Batcher of messages delivery calls. If `None`, every call is submitted in its own
+	/// transaction.
+	pub target_batcher: Option<CallBatcher<Target>>,
+}
+
+/// Run headers and messages relays of the chain pair, in both directions.
+///
+/// See `spawn` for details.
+#[allow(clippy::too_many_arguments)]
+pub fn run<P: ChainPair>(
+	left_client: Client<P::Left>,
+	left_sign: LeftSigningParams<P>,
+	right_client: Client<P::Right>,
+	right_sign: RightSigningParams<P>,
+	lane: LaneId,
+	delivery_deadline: Option<Duration>,
+	max_unconfirmed_rewards: Option<u128>,
+	batch_window: Option<Duration>,
+	chaos_params: ChaosParams,
+	metrics_params: Option<MetricsParams>,
+	otlp_endpoint: Option<String>,
+) -> Result<(), String>
+where
+	<P::Left as Chain>::Call: Send,
+	<P::Right as Chain>::Call: Send,
+{
+	let relays = spawn::<P>(
+		left_client,
+		left_sign,
+		right_client,
+		right_sign,
+		lane,
+		delivery_deadline,
+		max_unconfirmed_rewards,
+		batch_window,
+		chaos_params,
+		metrics_params,
+		otlp_endpoint,
+	)?;
+
+	for (name, relay) in relays {
+		if relay.join().is_err() {
+			log::error!(target: "bridge", "{} relay has panicked", name);
+		} else {
+			log::error!(target: "bridge", "{} relay has unexpectedly stopped", name);
+		}
+	}
+
+	Ok(())
+}
+
+/// Spawn headers and messages relays of the chain pair, in both directions, without waiting for
+/// them to stop.
+///
+/// All relays are sharing the same two client connections. Since clients are also sharing
+/// transaction submission locks, relays that are submitting transactions to the same chain never
+/// reuse the same account nonce.
+///
+/// Every relay is exposing its own Prometheus endpoint. If metrics are enabled, endpoints are
+/// started at consecutive ports, starting from the port in `metrics_params`: left -> right
+/// headers, right -> left headers, left -> right messages and right -> left messages.
+///
+/// Faults, configured by `chaos_params`, are only injected into messages relays. Traces of
+/// relayed messages are exported to the `otlp_endpoint` (if specified) by both messages relays.
+///
+/// If `batch_window` is specified, messages delivery and delivery confirmation calls, submitted
+/// to the same chain within this window, are wrapped into single `utility.batch_all` transaction.
+///
+/// Returns names and thread handles of all spawned relays.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn<P: ChainPair>(
+	left_client: Client<P::Left>,
+	left_sign: LeftSigningParams<P>,
+	right_client: Client<P::Right>,
+	right_sign: RightSigningParams<P>,
+	lane: LaneId,
+	delivery_deadline: Option<Duration>,
+	max_unconfirmed_rewards: Option<u128>,
+	batch_window: Option<Duration>,
+	chaos_params: ChaosParams,
+	metrics_params: Option<MetricsParams>,
+	otlp_endpoint: Option<String>,
+) -> Result<Vec<(String, JoinHandle<()>)>, String>
+where
+	<P::Left as Chain>::Call: Send,
+	<P::Right as Chain>::Call: Send,
+{
+	let relay_metrics_params = |port_offset: u16| {
+		metrics_params.clone().map(|mut metrics_params| {
+			metrics_params.port = metrics_params.port.saturating_add(port_offset);
+			metrics_params
+		})
+	};
+
+	let left_batcher =
+		batch_window.map(|window| P::RightToLeft::target_call_batcher(left_client.clone(), left_sign.clone(), window));
+	let right_batcher = batch_window
+		.map(|window| P::LeftToRight::target_call_batcher(right_client.clone(), right_sign.clone(), window));
+
+	let relays = vec![
+		spawn_relay(relay_name::<P::LeftToRight>("Headers"), {
+			let (left_client, right_client, right_sign) =
+				(left_client.clone(), right_client.clone(), right_sign.clone());
+			let metrics_params = relay_metrics_params(0);
+			move || {
+				async_std::task::block_on(P::LeftToRight::relay_headers(
+					left_client,
+					right_client,
+					right_sign,
+					metrics_params,
+				))
+			}
+		})?,
+		spawn_relay(relay_name::<P::RightToLeft>("Headers"), {
+			let (right_client, left_client, left_sign) = (right_client.clone(), left_client.clone(), left_sign.clone());
+			let metrics_params = relay_metrics_params(1);
+			move || {
+				async_std::task::block_on(P::RightToLeft::relay_headers(
+					right_client,
+					left_client,
+					left_sign,
+					metrics_params,
+				))
+			}
+		})?,
+		spawn_relay(relay_name::<P::LeftToRight>("Messages"), {
+			let (left_client, left_sign, right_client, right_sign) = (
+				left_client.clone(),
+				left_sign.clone(),
+				right_client.clone(),
+				right_sign.clone(),
+			);
+			let params = MessagesRelayParams {
+				lane,
+				delivery_deadline,
+				max_unconfirmed_rewards,
+				chaos_params: chaos_params.clone(),
+				metrics_params: relay_metrics_params(2),
+				otlp_endpoint: otlp_endpoint.clone(),
+				source_batcher: left_batcher.clone(),
+				target_batcher: right_batcher.clone(),
+			};
+			move || P::LeftToRight::relay_messages(left_client, left_sign, right_client, right_sign, params)
+		})?,
+		spawn_relay(relay_name::<P::RightToLeft>("Messages"), {
+			let params = MessagesRelayParams {
+				lane,
+				delivery_deadline,
+				max_unconfirmed_rewards,
+				chaos_params,
+				metrics_params: relay_metrics_params(3),
+				otlp_endpoint,
+				source_batcher: right_batcher,
+				target_batcher: left_batcher,
+			};
+			move || P::RightToLeft::relay_messages(right_client, right_sign, left_client, left_sign, params)
+		})?,
+	];
+
+	Ok(relays)
+}
+
+/// Returns name of the relay (e.g. `MillauHeadersToRialto`).
+fn relay_name<B: SubstrateBridge>(relay_kind: &str) -> String {
+	format!(
+		"{}{}To{}",
+		<B::Source as Chain>::NAME,
+		relay_kind,
+		<B::Target as Chain>::NAME
+	)
+}
+
+/// Spawn relay in a separate thread.
+fn spawn_relay(name: String, relay: impl FnOnce() + Send + 'static) -> Result<(String, JoinHandle<()>), String> {
+	std::thread::Builder::new()
+		.name(name.clone())
+		.spawn(relay)
+		.map(|handle| (name, handle))
+		.map_err(|e| format!("Failed to spawn {} relay thread: {}", name, e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::millau_rialto_relay::MillauRialto;
+
+	#[test]
+	fn relay_names_are_composed_from_chain_names() {
+		assert_eq!(
+			relay_name::<<MillauRialto as ChainPair>::LeftToRight>("Headers"),
+			"MillauHeadersToRialto"
+		);
+		assert_eq!(
+			relay_name::<<MillauRialto as ChainPair>::RightToLeft>("Messages"),
+			"RialtoMessagesToMillau"
+		);
+	}
+}
@@ -17,9 +17,12 @@
 //! Deal with CLI args of substrate-to-substrate relay.
 
 use bp_message_lane::LaneId;
+use relay_substrate_client::{Chain, Client, ConnectionParams};
 use sp_core::Bytes;
 use sp_finality_grandpa::SetId as GrandpaAuthoritiesSetId;
+use std::{path::PathBuf, time::Duration};
 use structopt::{clap::arg_enum, StructOpt};
+use substrate_relay::chain_pair::{self, ChainPair, HeadersBridgeInitializationParams, SubstrateBridge};
 
 /// Parse relay CLI args.
 pub fn parse_args() -> Command {
@@ -30,83 +33,249 @@ pub fn parse_args() -> Command {
 #[derive(StructOpt)]
 #[structopt(about = "Substrate-to-Substrate relay")]
 pub enum Command {
-	/// Initialize Millau headers bridge in Rialto.
-	InitializeMillauHeadersBridgeInRialto {
+	// subcommands of all registered chain pairs: headers bridge initialization, headers and
+	// messages relays
+	#[structopt(flatten)]
+	ChainPair(ChainPairCommand),
+	/// Initialize Westend headers bridge in Millau.
+	InitializeWestendHeadersBridgeInMillau {
 		#[structopt(flatten)]
-		millau: MillauConnectionParams,
+		westend: WestendConnectionParams,
 		#[structopt(flatten)]
-		rialto: RialtoConnectionParams,
+		millau: MillauConnectionParams,
 		#[structopt(flatten)]
-		rialto_sign: RialtoSigningParams,
+		millau_sign: MillauSigningParams,
 		#[structopt(flatten)]
-		millau_bridge_params: MillauBridgeInitializationParams,
+		westend_bridge_params: WestendBridgeInitializationParams,
 	},
-	/// Relay Millau headers to Rialto.
-	MillauHeadersToRialto {
+	/// Relay Westend headers to Millau.
+	///
+	/// This is a headers-only relay - there are no message lanes between Westend and Millau.
+	WestendHeadersToMillau {
 		#[structopt(flatten)]
-		millau: MillauConnectionParams,
+		westend: WestendConnectionParams,
 		#[structopt(flatten)]
-		rialto: RialtoConnectionParams,
+		millau: MillauConnectionParams,
 		#[structopt(flatten)]
-		rialto_sign: RialtoSigningParams,
+		millau_sign: MillauSigningParams,
 		#[structopt(flatten)]
 		prometheus_params: PrometheusParams,
 	},
-	/// Initialize Rialto headers bridge in Millau.
-	InitializeRialtoHeadersBridgeInMillau {
-		#[structopt(flatten)]
-		rialto: RialtoConnectionParams,
+	/// Submit message to given Millau -> Rialto lane.
+	SubmitMillauToRialtoMessage {
 		#[structopt(flatten)]
 		millau: MillauConnectionParams,
 		#[structopt(flatten)]
 		millau_sign: MillauSigningParams,
 		#[structopt(flatten)]
-		rialto_bridge_params: RialtoBridgeInitializationParams,
+		rialto_sign: RialtoSigningParams,
+		/// Hex-encoded lane id.
+		#[structopt(long)]
+		lane: HexLaneId,
+		/// Message type.
+		#[structopt(long, possible_values = &ToRialtoMessage::variants())]
+		message: ToRialtoMessage,
+		/// Delivery and dispatch fee.
+		#[structopt(long)]
+		fee: bp_millau::Balance,
+		/// Nonce of the Millau sender account at the Rialto call dispatch pallet. It is a number of
+		/// messages from this account that have already been dispatched at Rialto.
+		#[structopt(long, default_value = "0")]
+		origin_nonce: pallet_bridge_call_dispatch::SourceAccountNonce,
 	},
-	/// Relay Rialto headers to Millau.
-	RialtoHeadersToMillau {
-		#[structopt(flatten)]
-		rialto: RialtoConnectionParams,
+	/// Inspect given Millau -> Rialto lane and report why messages are not delivered or confirmed.
+	///
+	/// Lane state and all detected problems are printed to stdout in JSON format.
+	DiagnoseMillauToRialtoLane {
 		#[structopt(flatten)]
 		millau: MillauConnectionParams,
 		#[structopt(flatten)]
-		millau_sign: MillauSigningParams,
-		#[structopt(flatten)]
-		prometheus_params: PrometheusParams,
+		rialto: RialtoConnectionParams,
+		/// Hex-encoded lane id.
+		#[structopt(long)]
+		lane: HexLaneId,
+		/// SS58-encoded Millau account of the relayer, which is delivering confirmations.
+		#[structopt(long)]
+		millau_relayer: Option<bp_millau::AccountId>,
+		/// SS58-encoded Rialto account of the relayer, which is delivering messages.
+		#[structopt(long)]
+		rialto_relayer: Option<bp_rialto::AccountId>,
+		/// Minimal fee that makes message delivery profitable for the relayer.
+		#[structopt(long)]
+		min_profitable_fee: Option<bp_millau::Balance>,
 	},
-	/// Serve given lane of Millau -> Rialto messages.
-	MillauMessagesToRialto {
+	/// Inspect given Rialto -> Millau lane and report why messages are not delivered or confirmed.
+	///
+	/// Lane state and all detected problems are printed to stdout in JSON format.
+	DiagnoseRialtoToMillauLane {
+		#[structopt(flatten)]
+		rialto: RialtoConnectionParams,
 		#[structopt(flatten)]
 		millau: MillauConnectionParams,
+		/// Hex-encoded lane id.
+		#[structopt(long)]
+		lane: HexLaneId,
+		/// SS58-encoded Rialto account of the relayer, which is delivering confirmations.
+		#[structopt(long)]
+		rialto_relayer: Option<bp_rialto::AccountId>,
+		/// SS58-encoded Millau account of the relayer, which is delivering messages.
+		#[structopt(long)]
+		millau_relayer: Option<bp_millau::AccountId>,
+		/// Minimal fee that makes message delivery profitable for the relayer.
+		#[structopt(long)]
+		min_profitable_fee: Option<bp_rialto::Balance>,
+	},
+	/// Inspect given lane and print its state at both chains: nonces, queued messages, unrewarded
+	/// relayers, the newest source header known to the target chain and health verdict.
+	///
+	/// If direction is not specified, both Millau -> Rialto and Rialto -> Millau lanes are
+	/// inspected.
+	InspectLane {
 		#[structopt(flatten)]
-		millau_sign: MillauSigningParams,
+		millau: MillauConnectionParams,
 		#[structopt(flatten)]
 		rialto: RialtoConnectionParams,
+		/// Hex-encoded lane id.
+		#[structopt(long)]
+		lane: HexLaneId,
+		/// Direction of the lane.
+		#[structopt(long, possible_values = &LaneDirection::variants(), case_insensitive = true)]
+		direction: Option<LaneDirection>,
+		/// Output format.
+		#[structopt(
+			long,
+			possible_values = &OutputFormat::variants(),
+			case_insensitive = true,
+			default_value = "Human"
+		)]
+		format: OutputFormat,
+	},
+	/// Reconstruct timeline of the message: find finalized blocks of both chains where the message
+	/// has been sent, proved, delivered, dispatched and confirmed.
+	///
+	/// Nodes must keep state of all blocks since the message has been sent (i.e. run in archive mode).
+	TraceMessage {
 		#[structopt(flatten)]
-		rialto_sign: RialtoSigningParams,
+		millau: MillauConnectionParams,
 		#[structopt(flatten)]
-		prometheus_params: PrometheusParams,
-		/// Hex-encoded id of lane that should be served by relay.
+		rialto: RialtoConnectionParams,
+		/// Hex-encoded lane id.
 		#[structopt(long)]
 		lane: HexLaneId,
+		/// Direction of the lane.
+		#[structopt(long, possible_values = &LaneDirection::variants(), case_insensitive = true)]
+		direction: LaneDirection,
+		/// Message nonce.
+		#[structopt(long)]
+		nonce: bp_message_lane::MessageNonce,
+		/// Output format.
+		#[structopt(
+			long,
+			possible_values = &OutputFormat::variants(),
+			case_insensitive = true,
+			default_value = "Human"
+		)]
+		format: OutputFormat,
 	},
-	/// Submit message to given Millau -> Rialto lane.
-	SubmitMillauToRialtoMessage {
+	/// Print SS58-encoded Rialto account, that is used to dispatch messages from given Millau account.
+	///
+	/// If Millau account is not specified, the account that represents Millau root is printed.
+	DeriveRialtoAccountFromMillau {
+		/// SS58-encoded sr25519 public key of the Millau account.
+		#[structopt(long)]
+		millau_account_public: Option<String>,
+	},
+	/// Print SS58-encoded Millau account, that is used to dispatch messages from given Rialto account.
+	///
+	/// If Rialto account is not specified, the account that represents Rialto root is printed.
+	DeriveMillauAccountFromRialto {
+		/// SS58-encoded sr25519 public key of the Rialto account.
+		#[structopt(long)]
+		rialto_account_public: Option<String>,
+	},
+	/// Find Millau bridge pallet storage item that the raw storage key belongs to and decode the
+	/// key and (optionally) the value.
+	///
+	/// Decoded entry is printed to stdout in JSON format.
+	DecodeMillauStorage {
+		/// Hex-encoded storage key.
+		#[structopt(long)]
+		key: Bytes,
+		/// Hex-encoded storage value.
+		#[structopt(long)]
+		value: Option<Bytes>,
+	},
+	/// Find Rialto bridge pallet storage item that the raw storage key belongs to and decode the
+	/// key and (optionally) the value.
+	///
+	/// Decoded entry is printed to stdout in JSON format.
+	DecodeRialtoStorage {
+		/// Hex-encoded storage key.
+		#[structopt(long)]
+		key: Bytes,
+		/// Hex-encoded storage value.
+		#[structopt(long)]
+		value: Option<Bytes>,
+	},
+	/// Export storage of Millau bridge pallets at the best finalized block to the file.
+	///
+	/// The snapshot may be imported into the genesis of the fresh Millau dev chain.
+	ExportMillauBridgeState {
 		#[structopt(flatten)]
 		millau: MillauConnectionParams,
+		/// Path to the file where snapshot is written.
+		#[structopt(long)]
+		output: PathBuf,
+	},
+	/// Export storage of Rialto bridge pallets at the best finalized block to the file.
+	///
+	/// The snapshot may be imported into the genesis of the fresh Rialto dev chain.
+	ExportRialtoBridgeState {
 		#[structopt(flatten)]
-		millau_sign: MillauSigningParams,
+		rialto: RialtoConnectionParams,
+		/// Path to the file where snapshot is written.
+		#[structopt(long)]
+		output: PathBuf,
+	},
+	/// Replay verification of the Millau -> Rialto messages proof, submitted to Rialto, and explain
+	/// which check has failed.
+	///
+	/// The `receive_messages_proof` transaction is read either from the file, or from the Rialto block.
+	ReplayMillauToRialtoMessagesProof {
 		#[structopt(flatten)]
-		rialto_sign: RialtoSigningParams,
-		/// Hex-encoded lane id.
+		transaction: RialtoTransactionParams,
+		/// Path to the snapshot of Rialto bridge pallets state, exported by the
+		/// `export-rialto-bridge-state` subcommand.
 		#[structopt(long)]
-		lane: HexLaneId,
-		/// Message type.
-		#[structopt(long, possible_values = &ToRialtoMessage::variants())]
-		message: ToRialtoMessage,
-		/// Delivery and dispatch fee.
+		snapshot: PathBuf,
+		/// Output format.
+		#[structopt(
+			long,
+			possible_values = &OutputFormat::variants(),
+			case_insensitive = true,
+			default_value = "Human"
+		)]
+		format: OutputFormat,
+	},
+	/// Replay verification of the Rialto -> Millau messages proof, submitted to Millau, and explain
+	/// which check has failed.
+	///
+	/// The `receive_messages_proof` transaction is read either from the file, or from the Millau block.
+	ReplayRialtoToMillauMessagesProof {
+		#[structopt(flatten)]
+		transaction: MillauTransactionParams,
+		/// Path to the snapshot of Millau bridge pallets state, exported by the
+		/// `export-millau-bridge-state` subcommand.
 		#[structopt(long)]
-		fee: bp_millau::Balance,
+		snapshot: PathBuf,
+		/// Output format.
+		#[structopt(
+			long,
+			possible_values = &OutputFormat::variants(),
+			case_insensitive = true,
+			default_value = "Human"
+		)]
+		format: OutputFormat,
 	},
 }
 
@@ -118,7 +287,25 @@ arg_enum! {
 	}
 }
 
-/// Lane id.
+arg_enum! {
+	#[derive(Debug, Clone, Copy, PartialEq)]
+	/// Direction of the message lane.
+	pub enum LaneDirection {
+		MillauToRialto,
+		RialtoToMillau,
+	}
+}
+
+arg_enum! {
+	#[derive(Debug, Clone, Copy, PartialEq)]
+	/// Format of the command output.
+	pub enum OutputFormat {
+		Human,
+		Json,
+	}
+}
+
+/// Lane id, parsed either from 8-digit hex string or from `<namespace>:<index>` string.
 #[derive(Debug)]
 pub struct HexLaneId(LaneId);
 
@@ -129,12 +316,10 @@ impl From<HexLaneId> for LaneId {
 }
 
 impl std::str::FromStr for HexLaneId {
-	type Err = hex::FromHexError;
+	type Err = &'static str;
 
 	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		let mut lane_id = LaneId::default();
-		hex::decode_to_slice(s, &mut lane_id)?;
-		Ok(HexLaneId(lane_id))
+		s.parse().map(HexLaneId)
 	}
 }
 
@@ -165,6 +350,77 @@ impl From<PrometheusParams> for Option<relay_utils::metrics::MetricsParams> {
 	}
 }
 
+/// Messages relay params.
+#[derive(StructOpt)]
+pub struct MessagesRelayParams {
+	/// Hex-encoded id of lane that should be served by relay.
+	#[structopt(long)]
+	pub lane: HexLaneId,
+	/// Maximal acceptable delivery delay (in seconds) of the oldest undelivered message. If it
+	/// is exceeded, the relay logs an error and raises the `delivery_deadline_exceeded` metric.
+	#[structopt(long)]
+	pub delivery_deadline: Option<u64>,
+	/// Maximal total reward (in source chain base units) of messages that have been delivered by
+	/// the relay, but delivery of which is not yet confirmed at the source chain. If it is
+	/// exceeded, new messages are not delivered until confirmations are relayed. If not
+	/// specified, the reward is not limited.
+	#[structopt(long)]
+	pub max_unconfirmed_rewards: Option<u128>,
+	#[structopt(flatten)]
+	pub chaos_params: ChaosParams,
+	/// OpenTelemetry collector endpoint (e.g. `http://localhost:4318`), where traces of relayed
+	/// messages are exported.
+	#[structopt(long)]
+	pub otlp_endpoint: Option<String>,
+}
+
+impl MessagesRelayParams {
+	/// Convert CLI params into params of the messages relay. Calls of the relay are not batched.
+	pub fn into_relay_params<Source: Chain, Target: Chain>(
+		self,
+		prometheus_params: PrometheusParams,
+	) -> chain_pair::MessagesRelayParams<Source, Target> {
+		chain_pair::MessagesRelayParams {
+			lane: self.lane.into(),
+			delivery_deadline: self.delivery_deadline.map(Duration::from_secs),
+			max_unconfirmed_rewards: self.max_unconfirmed_rewards,
+			chaos_params: self.chaos_params.into(),
+			metrics_params: prometheus_params.into(),
+			otlp_endpoint: self.otlp_endpoint,
+			source_batcher: None,
+			target_batcher: None,
+		}
+	}
+}
+
+/// Fault injection params of messages relays.
+///
+/// Options are hidden, because they are only meant to be used on test networks - to verify that
+/// recovery logic, transactions resubmission and alarms are working.
+#[derive(StructOpt)]
+pub struct ChaosParams {
+	/// Percent of transaction submissions that are silently dropped.
+	#[structopt(long, hidden = true, default_value = "0")]
+	pub chaos_drop_submissions: f64,
+	/// Delay (in seconds) of every messages and messages delivery proof generation.
+	#[structopt(long, hidden = true, default_value = "0")]
+	pub chaos_delay_proofs: u64,
+	/// Percent of client calls that are failing with connection error, forcing the relay to reconnect.
+	#[structopt(long, hidden = true, default_value = "0")]
+	pub chaos_disconnect: f64,
+}
+
+impl From<ChaosParams> for messages_relay::chaos::ChaosParams {
+	fn from(cli_params: ChaosParams) -> messages_relay::chaos::ChaosParams {
+		let probability = |percent: f64| (percent / 100.0).max(0.0).min(1.0);
+		messages_relay::chaos::ChaosParams {
+			drop_submission_probability: probability(cli_params.chaos_drop_submissions),
+			proof_delay: std::time::Duration::from_secs(cli_params.chaos_delay_proofs),
+			disconnect_probability: probability(cli_params.chaos_disconnect),
+		}
+	}
+}
+
 macro_rules! declare_chain_options {
 	($chain:ident, $chain_prefix:ident) => {
 		paste::item! {
@@ -177,17 +433,32 @@ macro_rules! declare_chain_options {
 				#[doc = "Connect to " $chain " node websocket server at given port."]
 				#[structopt(long)]
 				pub [<$chain_prefix _port>]: u16,
-			}
-
-			#[doc = $chain " signing params."]
-			#[derive(StructOpt)]
-			pub struct [<$chain SigningParams>] {
-				#[doc = "The SURI of secret key to use when transactions are submitted to the " $chain " node."]
+				#[doc = "Address (host:port) of the redundant " $chain " node, used when connection to the current node \
+					fails. May be specified several times - nodes are used in order of preference."]
+				#[structopt(long, number_of_values = 1)]
+				pub [<$chain_prefix _fallback_node>]: Vec<relay_substrate_client::NodeAddress>,
+				#[doc = "Maximal number of requests per second that may be sent to the " $chain " node. Requests above \
+					the limit are queued. If not specified, the rate is not limited."]
 				#[structopt(long)]
-				pub [<$chain_prefix _signer>]: String,
-				#[doc = "The password for the SURI of secret key to use when transactions are submitted to the " $chain " node."]
+				pub [<$chain_prefix _max_requests_per_second>]: Option<u32>,
+				#[doc = "Maximal number of concurrent requests to the " $chain " node. Requests above the limit are \
+					queued. If not specified, the number of concurrent requests is not limited."]
 				#[structopt(long)]
-				pub [<$chain_prefix _signer_password>]: Option<String>,
+				pub [<$chain_prefix _max_concurrent_requests>]: Option<usize>,
+			}
+
+			impl From<[<$chain ConnectionParams>]> for relay_substrate_client::ConnectionParams {
+				fn from(params: [<$chain ConnectionParams>]) -> Self {
+					relay_substrate_client::ConnectionParams {
+						host: params.[<$chain_prefix _host>],
+						port: params.[<$chain_prefix _port>],
+						fallback_nodes: params.[<$chain_prefix _fallback_node>],
+						rate_limiter: relay_substrate_client::RateLimiterParams {
+							max_requests_per_second: params.[<$chain_prefix _max_requests_per_second>],
+							max_concurrent_requests: params.[<$chain_prefix _max_concurrent_requests>],
+						},
+					}
+				}
 			}
 
 			#[doc = $chain " headers bridge initialization params."]
@@ -203,9 +474,456 @@ macro_rules! declare_chain_options {
 				#[structopt(long)]
 				pub [<$chain_prefix _initial_authorities_set_id>]: Option<GrandpaAuthoritiesSetId>,
 			}
+
+			impl From<[<$chain BridgeInitializationParams>]> for HeadersBridgeInitializationParams {
+				fn from(params: [<$chain BridgeInitializationParams>]) -> Self {
+					HeadersBridgeInitializationParams {
+						initial_header: params.[<$chain_prefix _initial_header>],
+						initial_authorities_set: params.[<$chain_prefix _initial_authorities>],
+						initial_authorities_set_id: params.[<$chain_prefix _initial_authorities_set_id>],
+					}
+				}
+			}
 		}
 	};
 }
 
 declare_chain_options!(Rialto, rialto);
 declare_chain_options!(Millau, millau);
+declare_chain_options!(Westend, westend);
+
+/// Declare options of the chain, which transactions are submitted or inspected by the relay.
+macro_rules! declare_chain_transaction_options {
+	($chain:ident, $chain_prefix:ident) => {
+		paste::item! {
+			#[doc = $chain " signing params."]
+			#[derive(StructOpt)]
+			pub struct [<$chain SigningParams>] {
+				#[doc = "The SURI of secret key to use when transactions are submitted to the " $chain " node."]
+				#[structopt(long)]
+				pub [<$chain_prefix _signer>]: Option<String>,
+				#[doc = "The password for the SURI of secret key to use when transactions are submitted to the " $chain " node."]
+				#[structopt(long)]
+				pub [<$chain_prefix _signer_password>]: Option<String>,
+				#[doc = "Websocket URI of the remote signing service that is used to sign transactions, submitted to the " $chain " node. \
+					Must be used instead of the SURI, so that the secret key is never loaded into the relay process."]
+				#[structopt(long)]
+				pub [<$chain_prefix _remote_signer>]: Option<String>,
+				#[doc = "SS58-encoded public key of the remote signer key that is used to sign " $chain " transactions."]
+				#[structopt(long)]
+				pub [<$chain_prefix _remote_signer_public>]: Option<String>,
+				#[doc = "Mortality period (in blocks) of transactions that are submitted to the " $chain " node. The era \
+					starts at the best finalized block. The period is rounded to the power of two in [4; 65536] range \
+					and should not exceed `BlockHashCount` of the " $chain " runtime. If not specified, transactions are immortal."]
+				#[structopt(long)]
+				pub [<$chain_prefix _transactions_mortality>]: Option<u32>,
+				#[doc = "SS58 address of the " $chain " account on behalf of which the signer is submitting transactions. \
+					If specified, the signer must be a proxy of this account and all calls are wrapped into the \
+					`proxy.proxy` call."]
+				#[structopt(long)]
+				pub [<$chain_prefix _signer_proxied_account>]: Option<String>,
+			}
+
+			impl [<$chain SigningParams>] {
+				#[doc = "Parse " $chain " signing params."]
+				pub async fn parse(&self) -> Result<[<relay_ $chain_prefix _client>]::SigningParams, String> {
+					let mut params = match (
+						self.[<$chain_prefix _signer>].as_ref(),
+						self.[<$chain_prefix _remote_signer>].as_ref(),
+						self.[<$chain_prefix _remote_signer_public>].as_ref(),
+					) {
+						(Some(signer), None, None) => [<relay_ $chain_prefix _client>]::SigningParams::from_suri(
+							signer,
+							self.[<$chain_prefix _signer_password>].as_deref(),
+						)
+						.map_err(|e| format!("Failed to parse {}-signer: {:?}", stringify!($chain_prefix), e))?,
+						(None, Some(remote_signer), Some(remote_signer_public)) => {
+							let public = <sp_core::sr25519::Public as sp_core::crypto::Ss58Codec>::from_ss58check(
+								remote_signer_public,
+							)
+							.map_err(|e| format!("Failed to parse {}-remote-signer-public: {:?}", stringify!($chain_prefix), e))?;
+							[<relay_ $chain_prefix _client>]::SigningParams::from_remote_signer(remote_signer, public)
+								.await
+								.map_err(|e| format!("Failed to connect to {}-remote-signer: {:?}", stringify!($chain_prefix), e))?
+						}
+						_ => {
+							return Err(format!(
+								"Either {0}-signer, or both {0}-remote-signer and {0}-remote-signer-public must be specified",
+								stringify!($chain_prefix),
+							))
+						}
+					};
+					params.transactions_mortality = self.[<$chain_prefix _transactions_mortality>];
+					params.proxied_account = self
+						.[<$chain_prefix _signer_proxied_account>]
+						.as_ref()
+						.map(|account| account.parse())
+						.transpose()
+						.map_err(|e| format!("Failed to parse {}-signer-proxied-account: {:?}", stringify!($chain_prefix), e))?;
+					Ok(params)
+				}
+			}
+
+			#[doc = "Params of the " $chain " transaction that is read either from the file, or from the " $chain " block."]
+			#[derive(StructOpt)]
+			pub struct [<$chain TransactionParams>] {
+				#[doc = "Path to the file with hex-encoded " $chain " transaction."]
+				#[structopt(long)]
+				pub transaction_file: Option<PathBuf>,
+				#[doc = "Connect to " $chain " node at given host to read the transaction from the block."]
+				#[structopt(long)]
+				pub [<$chain_prefix _host>]: Option<String>,
+				#[doc = "Connect to " $chain " node websocket server at given port to read the transaction from the block."]
+				#[structopt(long)]
+				pub [<$chain_prefix _port>]: Option<u16>,
+				#[doc = "Hex-encoded hash of the " $chain " block with the transaction."]
+				#[structopt(long)]
+				pub block_hash: Option<Bytes>,
+				#[doc = "Index of the transaction within the " $chain " block."]
+				#[structopt(long)]
+				pub transaction_index: Option<usize>,
+			}
+
+			impl [<$chain TransactionParams>] {
+				#[doc = "Read encoded " $chain " transaction."]
+				pub async fn read(&self) -> Result<Vec<u8>, String> {
+					if let Some(ref transaction_file) = self.transaction_file {
+						let transaction = std::fs::read_to_string(transaction_file)
+							.map_err(|e| format!("Failed to read transaction from {:?}: {}", transaction_file, e))?;
+						return sp_core::bytes::from_hex(transaction.trim())
+							.map_err(|e| format!("Failed to parse hex-encoded transaction: {}", e));
+					}
+
+					match (
+						self.[<$chain_prefix _host>].as_ref(),
+						self.[<$chain_prefix _port>],
+						self.block_hash.as_ref(),
+						self.transaction_index,
+					) {
+						(Some(host), Some(port), Some(block_hash), Some(transaction_index)) => {
+							let block_hash = codec::Decode::decode(&mut &block_hash.0[..])
+								.map_err(|e| format!("Failed to decode {} block hash: {:?}", stringify!($chain), e))?;
+							let client = relay_substrate_client::Client::<[<relay_ $chain_prefix _client>]::$chain>::new(
+								relay_substrate_client::ConnectionParams {
+									host: host.clone(),
+									port,
+									..Default::default()
+								},
+							)
+							.await?;
+							let block = client
+								.get_block(Some(block_hash))
+								.await
+								.map_err(|e| format!("Failed to read {} block: {:?}", stringify!($chain), e))?;
+							block
+								.block
+								.extrinsics
+								.get(transaction_index)
+								.map(codec::Encode::encode)
+								.ok_or_else(|| format!(
+									"{} block has no transaction with index {}",
+									stringify!($chain),
+									transaction_index,
+								))
+						}
+						_ => Err(format!(
+							"Either transaction-file, or all of {0}-host, {0}-port, block-hash and transaction-index must be specified",
+							stringify!($chain_prefix),
+						)),
+					}
+				}
+			}
+		}
+	};
+}
+
+declare_chain_transaction_options!(Rialto, rialto);
+declare_chain_transaction_options!(Millau, millau);
+
+/// Declare subcommands of chain pairs, served by the relay.
+///
+/// Every pair is registered as `Pair: Left <-> Right`, where `Pair` is the `ChainPair`
+/// implementation and `Left` and `Right` are chains whose CLI options are declared using the
+/// `declare_chain_options` and `declare_chain_transaction_options` macros. Subcommands are
+/// generated for both directions of every pair.
+macro_rules! declare_chain_pairs {
+	($($pair:path: $left:ident <-> $right:ident),* $(,)?) => {
+		paste::item! {
+			/// Subcommands of all registered chain pairs.
+			#[derive(StructOpt)]
+			pub enum ChainPairCommand {
+				$(
+					#[doc = "Initialize " $left " headers bridge in " $right "."]
+					[<Initialize $left HeadersBridgeIn $right>] {
+						#[structopt(flatten)]
+						source: [<$left ConnectionParams>],
+						#[structopt(flatten)]
+						target: [<$right ConnectionParams>],
+						#[structopt(flatten)]
+						target_sign: [<$right SigningParams>],
+						#[structopt(flatten)]
+						bridge_params: [<$left BridgeInitializationParams>],
+					},
+					#[doc = "Relay " $left " headers to " $right "."]
+					[<$left HeadersTo $right>] {
+						#[structopt(flatten)]
+						source: [<$left ConnectionParams>],
+						#[structopt(flatten)]
+						target: [<$right ConnectionParams>],
+						#[structopt(flatten)]
+						target_sign: [<$right SigningParams>],
+						#[structopt(flatten)]
+						prometheus_params: PrometheusParams,
+					},
+					#[doc = "Initialize " $right " headers bridge in " $left "."]
+					[<Initialize $right HeadersBridgeIn $left>] {
+						#[structopt(flatten)]
+						source: [<$right ConnectionParams>],
+						#[structopt(flatten)]
+						target: [<$left ConnectionParams>],
+						#[structopt(flatten)]
+						target_sign: [<$left SigningParams>],
+						#[structopt(flatten)]
+						bridge_params: [<$right BridgeInitializationParams>],
+					},
+					#[doc = "Relay " $right " headers to " $left "."]
+					[<$right HeadersTo $left>] {
+						#[structopt(flatten)]
+						source: [<$right ConnectionParams>],
+						#[structopt(flatten)]
+						target: [<$left ConnectionParams>],
+						#[structopt(flatten)]
+						target_sign: [<$left SigningParams>],
+						#[structopt(flatten)]
+						prometheus_params: PrometheusParams,
+					},
+					#[doc = "Serve given lane of " $left " -> " $right " messages."]
+					[<$left MessagesTo $right>] {
+						#[structopt(flatten)]
+						source: [<$left ConnectionParams>],
+						#[structopt(flatten)]
+						source_sign: [<$left SigningParams>],
+						#[structopt(flatten)]
+						target: [<$right ConnectionParams>],
+						#[structopt(flatten)]
+						target_sign: [<$right SigningParams>],
+						#[structopt(flatten)]
+						prometheus_params: PrometheusParams,
+						#[structopt(flatten)]
+						messages_params: MessagesRelayParams,
+					},
+					#[doc = "Serve given lane of " $right " -> " $left " messages."]
+					[<$right MessagesTo $left>] {
+						#[structopt(flatten)]
+						source: [<$right ConnectionParams>],
+						#[structopt(flatten)]
+						source_sign: [<$right SigningParams>],
+						#[structopt(flatten)]
+						target: [<$left ConnectionParams>],
+						#[structopt(flatten)]
+						target_sign: [<$left SigningParams>],
+						#[structopt(flatten)]
+						prometheus_params: PrometheusParams,
+						#[structopt(flatten)]
+						messages_params: MessagesRelayParams,
+					},
+					#[doc = "Relay " $left " <-> " $right " headers and serve given lane of messages in both directions."]
+					#[doc = ""]
+					#[doc = "All relays are running in the same process and are sharing connections to both nodes."]
+					#[doc = "Relays are exposing Prometheus metrics at `prometheus-port`, `prometheus-port + 1`,"]
+					#[doc = "`prometheus-port + 2` and `prometheus-port + 3`."]
+					[<Relay $left $right>] {
+						#[structopt(flatten)]
+						left: [<$left ConnectionParams>],
+						#[structopt(flatten)]
+						left_sign: [<$left SigningParams>],
+						#[structopt(flatten)]
+						right: [<$right ConnectionParams>],
+						#[structopt(flatten)]
+						right_sign: [<$right SigningParams>],
+						#[structopt(flatten)]
+						prometheus_params: PrometheusParams,
+						#[structopt(flatten)]
+						messages_params: MessagesRelayParams,
+						#[doc = "Batching window (in milliseconds). If specified, messages delivery and delivery confirmation \
+							calls that are submitted to the same chain within this window are wrapped into single \
+							`utility.batch_all` transaction."]
+						#[structopt(long)]
+						batch_window: Option<u64>,
+					},
+				)*
+			}
+
+			impl ChainPairCommand {
+				/// Run the chain pair subcommand.
+				pub async fn run(self) -> Result<(), String> {
+					match self {
+						$(
+							ChainPairCommand::[<Initialize $left HeadersBridgeIn $right>] {
+								source,
+								target,
+								target_sign,
+								bridge_params,
+							} => {
+								initialize_headers_bridge::<<$pair as ChainPair>::LeftToRight>(
+									source.into(),
+									target.into(),
+									target_sign.parse().await?,
+									bridge_params.into(),
+								)
+								.await
+							}
+							ChainPairCommand::[<$left HeadersTo $right>] {
+								source,
+								target,
+								target_sign,
+								prometheus_params,
+							} => {
+								relay_headers::<<$pair as ChainPair>::LeftToRight>(
+									source.into(),
+									target.into(),
+									target_sign.parse().await?,
+									prometheus_params.into(),
+								)
+								.await
+							}
+							ChainPairCommand::[<Initialize $right HeadersBridgeIn $left>] {
+								source,
+								target,
+								target_sign,
+								bridge_params,
+							} => {
+								initialize_headers_bridge::<<$pair as ChainPair>::RightToLeft>(
+									source.into(),
+									target.into(),
+									target_sign.parse().await?,
+									bridge_params.into(),
+								)
+								.await
+							}
+							ChainPairCommand::[<$right HeadersTo $left>] {
+								source,
+								target,
+								target_sign,
+								prometheus_params,
+							} => {
+								relay_headers::<<$pair as ChainPair>::RightToLeft>(
+									source.into(),
+									target.into(),
+									target_sign.parse().await?,
+									prometheus_params.into(),
+								)
+								.await
+							}
+							ChainPairCommand::[<$left MessagesTo $right>] {
+								source,
+								source_sign,
+								target,
+								target_sign,
+								prometheus_params,
+								messages_params,
+							} => {
+								relay_messages::<<$pair as ChainPair>::LeftToRight>(
+									source.into(),
+									source_sign.parse().await?,
+									target.into(),
+									target_sign.parse().await?,
+									messages_params.into_relay_params(prometheus_params),
+								)
+								.await
+							}
+							ChainPairCommand::[<$right MessagesTo $left>] {
+								source,
+								source_sign,
+								target,
+								target_sign,
+								prometheus_params,
+								messages_params,
+							} => {
+								relay_messages::<<$pair as ChainPair>::RightToLeft>(
+									source.into(),
+									source_sign.parse().await?,
+									target.into(),
+									target_sign.parse().await?,
+									messages_params.into_relay_params(prometheus_params),
+								)
+								.await
+							}
+							ChainPairCommand::[<Relay $left $right>] {
+								left,
+								left_sign,
+								right,
+								right_sign,
+								prometheus_params,
+								messages_params,
+								batch_window,
+							} => {
+								let left_client = Client::new(left.into()).await?;
+								let left_sign = left_sign.parse().await?;
+								let right_client = Client::new(right.into()).await?;
+								let right_sign = right_sign.parse().await?;
+
+								chain_pair::run::<$pair>(
+									left_client,
+									left_sign,
+									right_client,
+									right_sign,
+									messages_params.lane.into(),
+									messages_params.delivery_deadline.map(Duration::from_secs),
+									messages_params.max_unconfirmed_rewards,
+									batch_window.map(Duration::from_millis),
+									messages_params.chaos_params.into(),
+									prometheus_params.into(),
+									messages_params.otlp_endpoint,
+								)
+							}
+						)*
+					}
+				}
+			}
+		}
+	};
+}
+
+declare_chain_pairs! {
+	substrate_relay::millau_rialto_relay::MillauRialto: Millau <-> Rialto,
+}
+
+/// Initialize source chain headers bridge at the target chain.
+async fn initialize_headers_bridge<B: SubstrateBridge>(
+	source: ConnectionParams,
+	target: ConnectionParams,
+	target_sign: B::TargetSigningParams,
+	params: HeadersBridgeInitializationParams,
+) -> Result<(), String> {
+	let source_client = Client::new(source).await?;
+	let target_client = Client::new(target).await?;
+	B::initialize_headers_bridge(source_client, target_client, target_sign, params).await
+}
+
+/// Relay source chain headers to the target chain.
+async fn relay_headers<B: SubstrateBridge>(
+	source: ConnectionParams,
+	target: ConnectionParams,
+	target_sign: B::TargetSigningParams,
+	metrics_params: Option<relay_utils::metrics::MetricsParams>,
+) -> Result<(), String> {
+	let source_client = Client::new(source).await?;
+	let target_client = Client::new(target).await?;
+	B::relay_headers(source_client, target_client, target_sign, metrics_params).await;
+	Ok(())
+}
+
+/// Serve lane of source -> target messages.
+async fn relay_messages<B: SubstrateBridge>(
+	source: ConnectionParams,
+	source_sign: B::SourceSigningParams,
+	target: ConnectionParams,
+	target_sign: B::TargetSigningParams,
+	params: chain_pair::MessagesRelayParams<B::Source, B::Target>,
+) -> Result<(), String> {
+	let source_client = Client::new(source).await?;
+	let target_client = Client::new(target).await?;
+	B::relay_messages(source_client, source_sign, target_client, target_sign, params);
+	Ok(())
+}
@@ -22,20 +22,23 @@
 //! with this header.
 
 use codec::Decode;
+use futures::Future;
 use pallet_substrate_bridge::InitializationData;
 use relay_substrate_client::{Chain, Client};
 use sp_core::Bytes;
 use sp_finality_grandpa::{AuthorityList as GrandpaAuthoritiesSet, SetId as GrandpaAuthoritiesSetId};
 
 /// Submit headers-bridge initialization transaction.
-pub async fn initialize<SourceChain: Chain, TargetChain: Chain>(
+pub async fn initialize<SourceChain: Chain, TargetChain: Chain, PrepareTxFuture>(
 	source_client: Client<SourceChain>,
 	target_client: Client<TargetChain>,
 	raw_initial_header: Option<Bytes>,
 	raw_initial_authorities_set: Option<Bytes>,
 	initial_authorities_set_id: Option<GrandpaAuthoritiesSetId>,
-	prepare_initialize_transaction: impl FnOnce(InitializationData<SourceChain::Header>) -> Result<Bytes, String>,
-) {
+	prepare_initialize_transaction: impl FnOnce(InitializationData<SourceChain::Header>) -> PrepareTxFuture,
+) where
+	PrepareTxFuture: Future<Output = Result<Bytes, String>>,
+{
 	let result = do_initialize(
 		source_client,
 		target_client,
@@ -65,14 +68,17 @@ pub async fn initialize<SourceChain: Chain, TargetChain: Chain>(
 }
 
 /// Craft and submit initialization transaction, returning any error that may occur.
-async fn do_initialize<SourceChain: Chain, TargetChain: Chain>(
+async fn do_initialize<SourceChain: Chain, TargetChain: Chain, PrepareTxFuture>(
 	source_client: Client<SourceChain>,
 	target_client: Client<TargetChain>,
 	raw_initial_header: Option<Bytes>,
 	raw_initial_authorities_set: Option<Bytes>,
 	initial_authorities_set_id: Option<GrandpaAuthoritiesSetId>,
-	prepare_initialize_transaction: impl FnOnce(InitializationData<SourceChain::Header>) -> Result<Bytes, String>,
-) -> Result<TargetChain::Hash, String> {
+	prepare_initialize_transaction: impl FnOnce(InitializationData<SourceChain::Header>) -> PrepareTxFuture,
+) -> Result<TargetChain::Hash, String>
+where
+	PrepareTxFuture: Future<Output = Result<Bytes, String>>,
+{
 	let initialization_data = prepare_initialization_data(
 		source_client,
 		raw_initial_header,
@@ -80,7 +86,7 @@ async fn do_initialize<SourceChain: Chain, TargetChain: Chain>(
 		initial_authorities_set_id,
 	)
 	.await?;
-	let initialization_tx = prepare_initialize_transaction(initialization_data)?;
+	let initialization_tx = prepare_initialize_transaction(initialization_data).await?;
 	let initialization_tx_hash = target_client
 		.submit_extrinsic(initialization_tx)
 		.await
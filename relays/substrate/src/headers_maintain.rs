@@ -141,6 +141,7 @@ where
 
 		// finally - submit selected justification
 		if let Some((target, justification)) = justification_to_submit {
+			let _submission_lock = self.target_client.lock_signed_extrinsic_submission().await;
 			let submit_result = self
 				.pipeline
 				.make_complete_header_transaction(target, justification)
@@ -190,7 +191,7 @@ where
 			};
 
 			// decode justification target
-			let target = pallet_substrate_bridge::decode_justification_target::<SourceHeader>(&justification);
+			let target = bp_header_chain::justification::decode_justification_target::<SourceHeader>(&justification);
 			let target = match target {
 				Ok((target_hash, target_number)) => HeaderId(target_number.into(), target_hash.into()),
 				Err(error) => {
@@ -120,6 +120,7 @@ where
 
 		let header = headers.remove(0);
 		let id = header.id();
+		let _submission_lock = self.client.lock_signed_extrinsic_submission().await;
 		let submit_transaction_result = self
 			.pipeline
 			.make_submit_header_transaction(header)
@@ -162,6 +163,7 @@ where
 		id: HeaderIdOf<P>,
 		completion: Justification,
 	) -> Result<HeaderIdOf<P>, Self::Error> {
+		let _submission_lock = self.client.lock_signed_extrinsic_submission().await;
 		let tx = self.pipeline.make_complete_header_transaction(id, completion).await?;
 		self.client.submit_extrinsic(Bytes(tx.encode())).await?;
 		Ok(id)
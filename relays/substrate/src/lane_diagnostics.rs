@@ -0,0 +1,612 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Lane diagnostics.
+//!
+//! Inspects both ends of the message lane and reports reasons why messages are not delivered
+//! and/or confirmed: missing finalized headers, nonce gaps, halted pallets, unprofitable fees
+//! and exhausted relayer balances.
+
+use crate::messages_source::read_client_state;
+use crate::{MillauClient, RialtoClient};
+
+use bp_message_lane::{LaneId, MessageData, MessageKey, MessageNonce};
+use bp_runtime::InstanceId;
+use codec::{Decode, Encode};
+use frame_support::traits::Get;
+use relay_substrate_client::{ChainWithBalances, Client, Error as SubstrateError, HashOf};
+use relay_utils::BlockNumberBase;
+use serde::Serialize;
+use sp_core::Bytes;
+use sp_runtime::{traits::UniqueSaturatedInto, DeserializeOwned};
+
+/// Single lane end (chain) parameters that are required for diagnostics.
+pub struct LaneEnd<C: ChainWithBalances> {
+	/// Client connected to the chain.
+	pub client: Client<C>,
+	/// Id of the bridge instance at this chain (i.e. the id of the bridged chain).
+	pub instance: InstanceId,
+	/// Name of the message lane pallet in the chain runtime.
+	pub message_lane_pallet: &'static str,
+	/// Relayer account at this chain, if we need to check its balance.
+	pub relayer: Option<C::AccountId>,
+	/// Minimal relayer balance. If the relayer balance is below this value, it is unable to
+	/// pay for transactions.
+	pub min_relayer_balance: u128,
+}
+
+/// Lane state, read from both chains.
+#[derive(Debug, Default, Serialize)]
+pub struct LaneDiagnostics {
+	/// Name of the source chain.
+	pub source: &'static str,
+	/// Name of the target chain.
+	pub target: &'static str,
+	/// Hex-encoded lane id.
+	pub lane: String,
+	/// Best finalized source block number.
+	pub source_best_finalized_block: u64,
+	/// Best finalized source block number, known to the target chain.
+	pub source_best_finalized_block_at_target: u64,
+	/// Best finalized target block number.
+	pub target_best_finalized_block: u64,
+	/// Best finalized target block number, known to the source chain.
+	pub target_best_finalized_block_at_source: u64,
+	/// Nonce of the latest message, generated at the best finalized source block.
+	pub latest_generated_nonce: MessageNonce,
+	/// Nonce of the latest message, generated at the source block that is known to the target.
+	pub latest_generated_nonce_at_known_source_block: MessageNonce,
+	/// Nonce of the latest message, received by the target chain.
+	pub latest_received_nonce_at_target: MessageNonce,
+	/// Nonce of the latest message, received by the target chain at the target block that is
+	/// known to the source.
+	pub latest_received_nonce_at_known_target_block: MessageNonce,
+	/// Nonce of the latest message, which delivery confirmation is known to the target chain.
+	pub latest_confirmed_nonce_at_target: MessageNonce,
+	/// Nonce of the latest message, which delivery is confirmed at the source chain.
+	pub latest_confirmed_nonce_at_source: MessageNonce,
+	/// Maximal number of unconfirmed messages at the target inbound lane.
+	pub max_unconfirmed_messages_at_target: MessageNonce,
+	/// True if the source message lane pallet is halted.
+	pub is_source_pallet_halted: bool,
+	/// True if the target message lane pallet is halted.
+	pub is_target_pallet_halted: bool,
+	/// Fee, paid for the oldest undelivered message (if there are undelivered messages).
+	pub oldest_undelivered_message_fee: Option<u128>,
+	/// Minimal fee that makes message delivery profitable (if specified).
+	pub min_profitable_fee: Option<u128>,
+	/// Free balance of the relayer account at the source chain (if account is specified).
+	pub source_relayer_balance: Option<u128>,
+	/// Minimal balance of the relayer account at the source chain.
+	pub min_source_relayer_balance: u128,
+	/// Free balance of the relayer account at the target chain (if account is specified).
+	pub target_relayer_balance: Option<u128>,
+	/// Minimal balance of the relayer account at the target chain.
+	pub min_target_relayer_balance: u128,
+	/// Problems, detected by analyzing lane state.
+	pub problems: Vec<LaneProblem>,
+}
+
+/// Lane problem that prevents messages from being delivered and/or confirmed.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "problem")]
+pub enum LaneProblem {
+	/// There are undelivered messages, but they are generated at source blocks that are not
+	/// yet finalized by the target chain.
+	MissingSourceHeadersAtTarget {
+		/// Number of messages that can't be proved to the target chain.
+		unprovable_messages: MessageNonce,
+	},
+	/// There are delivered messages, but they are delivered at target blocks that are not yet
+	/// finalized by the source chain.
+	MissingTargetHeadersAtSource {
+		/// Number of delivery confirmations that can't be proved to the source chain.
+		unprovable_confirmations: MessageNonce,
+	},
+	/// Lane nonces are inconsistent.
+	NonceGap {
+		/// Description of the gap.
+		details: String,
+	},
+	/// Target chain rejects new messages until previous deliveries are confirmed.
+	TooManyUnconfirmedMessages {
+		/// Number of unconfirmed messages at the target chain.
+		unconfirmed_messages: MessageNonce,
+		/// Maximal number of unconfirmed messages at the target chain.
+		max_unconfirmed_messages: MessageNonce,
+	},
+	/// Message lane pallet is halted.
+	PalletHalted {
+		/// Name of the chain where pallet is halted.
+		chain: &'static str,
+	},
+	/// Fee, paid for the oldest undelivered message, doesn't cover delivery costs.
+	UnprofitableFee {
+		/// Nonce of the oldest undelivered message.
+		nonce: MessageNonce,
+		/// Fee, paid for the message.
+		fee: u128,
+		/// Minimal profitable fee.
+		min_profitable_fee: u128,
+	},
+	/// Relayer balance is too low to pay for transactions.
+	RelayerBalanceExhausted {
+		/// Name of the chain where relayer balance is exhausted.
+		chain: &'static str,
+		/// Current relayer balance.
+		balance: u128,
+		/// Minimal relayer balance.
+		min_balance: u128,
+	},
+}
+
+/// Diagnose Millau -> Rialto lane.
+pub async fn millau_to_rialto(
+	millau_client: MillauClient,
+	rialto_client: RialtoClient,
+	lane: LaneId,
+	millau_relayer: Option<bp_millau::AccountId>,
+	rialto_relayer: Option<bp_rialto::AccountId>,
+	min_profitable_fee: Option<bp_millau::Balance>,
+) -> Result<LaneDiagnostics, String> {
+	diagnose_lane(
+		LaneEnd {
+			client: millau_client,
+			instance: bp_runtime::RIALTO_BRIDGE_INSTANCE,
+			message_lane_pallet: "BridgeRialtoMessageLane",
+			relayer: millau_relayer,
+			min_relayer_balance: millau_runtime::ExistentialDeposit::get().into(),
+		},
+		LaneEnd {
+			client: rialto_client,
+			instance: bp_runtime::MILLAU_BRIDGE_INSTANCE,
+			message_lane_pallet: "BridgeMillauMessageLane",
+			relayer: rialto_relayer,
+			min_relayer_balance: rialto_runtime::ExistentialDeposit::get(),
+		},
+		lane,
+		bp_rialto::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE,
+		min_profitable_fee.map(Into::into),
+	)
+	.await
+}
+
+/// Diagnose Rialto -> Millau lane.
+pub async fn rialto_to_millau(
+	rialto_client: RialtoClient,
+	millau_client: MillauClient,
+	lane: LaneId,
+	rialto_relayer: Option<bp_rialto::AccountId>,
+	millau_relayer: Option<bp_millau::AccountId>,
+	min_profitable_fee: Option<bp_rialto::Balance>,
+) -> Result<LaneDiagnostics, String> {
+	diagnose_lane(
+		LaneEnd {
+			client: rialto_client,
+			instance: bp_runtime::MILLAU_BRIDGE_INSTANCE,
+			message_lane_pallet: "BridgeMillauMessageLane",
+			relayer: rialto_relayer,
+			min_relayer_balance: rialto_runtime::ExistentialDeposit::get(),
+		},
+		LaneEnd {
+			client: millau_client,
+			instance: bp_runtime::RIALTO_BRIDGE_INSTANCE,
+			message_lane_pallet: "BridgeRialtoMessageLane",
+			relayer: millau_relayer,
+			min_relayer_balance: millau_runtime::ExistentialDeposit::get().into(),
+		},
+		lane,
+		bp_millau::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE,
+		min_profitable_fee,
+	)
+	.await
+}
+
+/// Read lane state from both chains and analyze it.
+async fn diagnose_lane<Source, Target>(
+	source: LaneEnd<Source>,
+	target: LaneEnd<Target>,
+	lane: LaneId,
+	max_unconfirmed_messages_at_target: MessageNonce,
+	min_profitable_fee: Option<u128>,
+) -> Result<LaneDiagnostics, String>
+where
+	Source: ChainWithBalances,
+	Source::Header: DeserializeOwned,
+	Source::Index: DeserializeOwned,
+	Source::BlockNumber: BlockNumberBase,
+	Source::NativeBalance: UniqueSaturatedInto<u128>,
+	Target: ChainWithBalances,
+	Target::Header: DeserializeOwned,
+	Target::Index: DeserializeOwned,
+	Target::BlockNumber: BlockNumberBase,
+	Target::NativeBalance: UniqueSaturatedInto<u128>,
+{
+	let source_state = read_client_state::<_, HashOf<Target>, Target::BlockNumber>(&source.client, Target::NAME)
+		.await
+		.map_err(|e| format!("Failed to read {} state: {:?}", Source::NAME, e))?;
+	let target_state = read_client_state::<_, HashOf<Source>, Source::BlockNumber>(&target.client, Source::NAME)
+		.await
+		.map_err(|e| format!("Failed to read {} state: {:?}", Target::NAME, e))?;
+
+	let mut diagnostics = LaneDiagnostics {
+		source: Source::NAME,
+		target: Target::NAME,
+		lane: lane.to_string(),
+		source_best_finalized_block: source_state.best_self.0.into(),
+		source_best_finalized_block_at_target: target_state.best_peer.0.into(),
+		target_best_finalized_block: target_state.best_self.0.into(),
+		target_best_finalized_block_at_source: source_state.best_peer.0.into(),
+		latest_generated_nonce: read_nonce(
+			&source,
+			"OutboundLaneApi_latest_generated_nonce",
+			lane,
+			source_state.best_self.1,
+		)
+		.await?,
+		latest_generated_nonce_at_known_source_block: read_nonce(
+			&source,
+			"OutboundLaneApi_latest_generated_nonce",
+			lane,
+			target_state.best_peer.1,
+		)
+		.await?,
+		latest_received_nonce_at_target: read_nonce(
+			&target,
+			"InboundLaneApi_latest_received_nonce",
+			lane,
+			target_state.best_self.1,
+		)
+		.await?,
+		latest_received_nonce_at_known_target_block: read_nonce(
+			&target,
+			"InboundLaneApi_latest_received_nonce",
+			lane,
+			source_state.best_peer.1,
+		)
+		.await?,
+		latest_confirmed_nonce_at_target: read_nonce(
+			&target,
+			"InboundLaneApi_latest_confirmed_nonce",
+			lane,
+			target_state.best_self.1,
+		)
+		.await?,
+		latest_confirmed_nonce_at_source: read_nonce(
+			&source,
+			"OutboundLaneApi_latest_received_nonce",
+			lane,
+			source_state.best_self.1,
+		)
+		.await?,
+		max_unconfirmed_messages_at_target,
+		is_source_pallet_halted: read_is_halted(&source).await?,
+		is_target_pallet_halted: read_is_halted(&target).await?,
+		oldest_undelivered_message_fee: None,
+		min_profitable_fee,
+		source_relayer_balance: read_relayer_balance(&source).await?,
+		min_source_relayer_balance: source.min_relayer_balance,
+		target_relayer_balance: read_relayer_balance(&target).await?,
+		min_target_relayer_balance: target.min_relayer_balance,
+		problems: Vec::new(),
+	};
+
+	if diagnostics.latest_generated_nonce > diagnostics.latest_received_nonce_at_target {
+		let oldest_undelivered_message_key = MessageKey {
+			lane_id: lane,
+			nonce: diagnostics.latest_received_nonce_at_target + 1,
+		};
+		diagnostics.oldest_undelivered_message_fee = source
+			.client
+			.storage_map_entry::<_, MessageData<Source::NativeBalance>>(
+				source.message_lane_pallet,
+				"OutboundMessages",
+				&oldest_undelivered_message_key,
+				None,
+			)
+			.await
+			.map_err(|e| format!("Failed to read {} outbound message: {:?}", Source::NAME, e))?
+			.map(|message_data| message_data.fee.unique_saturated_into());
+	}
+
+	diagnostics.problems = find_problems(&diagnostics);
+	Ok(diagnostics)
+}
+
+/// Analyze lane state and return all detected problems.
+fn find_problems(diagnostics: &LaneDiagnostics) -> Vec<LaneProblem> {
+	let mut problems = Vec::new();
+
+	if diagnostics.is_source_pallet_halted {
+		problems.push(LaneProblem::PalletHalted {
+			chain: diagnostics.source,
+		});
+	}
+	if diagnostics.is_target_pallet_halted {
+		problems.push(LaneProblem::PalletHalted {
+			chain: diagnostics.target,
+		});
+	}
+
+	if diagnostics.latest_received_nonce_at_target > diagnostics.latest_generated_nonce {
+		problems.push(LaneProblem::NonceGap {
+			details: format!(
+				"{} has received message {}, but {} has only generated messages up to {}",
+				diagnostics.target,
+				diagnostics.latest_received_nonce_at_target,
+				diagnostics.source,
+				diagnostics.latest_generated_nonce,
+			),
+		});
+	}
+	if diagnostics.latest_confirmed_nonce_at_source > diagnostics.latest_received_nonce_at_target {
+		problems.push(LaneProblem::NonceGap {
+			details: format!(
+				"{} has confirmed delivery of message {}, but {} has only received messages up to {}",
+				diagnostics.source,
+				diagnostics.latest_confirmed_nonce_at_source,
+				diagnostics.target,
+				diagnostics.latest_received_nonce_at_target,
+			),
+		});
+	}
+
+	let has_undelivered_messages = diagnostics.latest_generated_nonce > diagnostics.latest_received_nonce_at_target;
+	if has_undelivered_messages
+		&& diagnostics.latest_generated_nonce_at_known_source_block <= diagnostics.latest_received_nonce_at_target
+	{
+		problems.push(LaneProblem::MissingSourceHeadersAtTarget {
+			unprovable_messages: diagnostics.latest_generated_nonce
+				- diagnostics.latest_generated_nonce_at_known_source_block,
+		});
+	}
+
+	let has_unconfirmed_messages =
+		diagnostics.latest_received_nonce_at_target > diagnostics.latest_confirmed_nonce_at_source;
+	if has_unconfirmed_messages
+		&& diagnostics.latest_received_nonce_at_known_target_block <= diagnostics.latest_confirmed_nonce_at_source
+	{
+		problems.push(LaneProblem::MissingTargetHeadersAtSource {
+			unprovable_confirmations: diagnostics.latest_received_nonce_at_target
+				- diagnostics.latest_received_nonce_at_known_target_block,
+		});
+	}
+
+	let unconfirmed_messages_at_target = diagnostics
+		.latest_received_nonce_at_target
+		.saturating_sub(diagnostics.latest_confirmed_nonce_at_target);
+	if has_undelivered_messages && unconfirmed_messages_at_target >= diagnostics.max_unconfirmed_messages_at_target {
+		problems.push(LaneProblem::TooManyUnconfirmedMessages {
+			unconfirmed_messages: unconfirmed_messages_at_target,
+			max_unconfirmed_messages: diagnostics.max_unconfirmed_messages_at_target,
+		});
+	}
+
+	if let (Some(fee), Some(min_profitable_fee)) = (
+		diagnostics.oldest_undelivered_message_fee,
+		diagnostics.min_profitable_fee,
+	) {
+		if fee < min_profitable_fee {
+			problems.push(LaneProblem::UnprofitableFee {
+				nonce: diagnostics.latest_received_nonce_at_target + 1,
+				fee,
+				min_profitable_fee,
+			});
+		}
+	}
+
+	let relayer_balances = [
+		(
+			diagnostics.source,
+			diagnostics.source_relayer_balance,
+			diagnostics.min_source_relayer_balance,
+		),
+		(
+			diagnostics.target,
+			diagnostics.target_relayer_balance,
+			diagnostics.min_target_relayer_balance,
+		),
+	];
+	for &(chain, balance, min_balance) in relayer_balances.iter() {
+		match balance {
+			Some(balance) if balance < min_balance => problems.push(LaneProblem::RelayerBalanceExhausted {
+				chain,
+				balance,
+				min_balance,
+			}),
+			_ => (),
+		}
+	}
+
+	problems
+}
+
+/// Read lane nonce using given runtime API method.
+async fn read_nonce<C: ChainWithBalances>(
+	lane_end: &LaneEnd<C>,
+	method: &str,
+	lane: LaneId,
+	at_block: HashOf<C>,
+) -> Result<MessageNonce, String> {
+	let encoded_response = lane_end
+		.client
+		.state_call(method.into(), Bytes((lane_end.instance, lane).encode()), Some(at_block))
+		.await
+		.map_err(|e| format!("Failed to call {} at {}: {:?}", method, C::NAME, e))?;
+	Option::<MessageNonce>::decode(&mut &encoded_response.0[..])
+		.map_err(|e| format!("Failed to decode {} response from {}: {:?}", method, C::NAME, e))?
+		.ok_or_else(|| format!("Message lane instance is unknown to {}", C::NAME))
+}
+
+/// Read message lane pallet `IsHalted` flag.
+async fn read_is_halted<C: ChainWithBalances>(lane_end: &LaneEnd<C>) -> Result<bool, String> {
+	lane_end
+		.client
+		.storage_value::<bool>(lane_end.message_lane_pallet, "IsHalted", None)
+		.await
+		.map(|is_halted| is_halted.unwrap_or(false))
+		.map_err(|e| format!("Failed to read {} pallet state: {:?}", C::NAME, e))
+}
+
+/// Read free balance of the relayer account (if it is specified).
+async fn read_relayer_balance<C>(lane_end: &LaneEnd<C>) -> Result<Option<u128>, String>
+where
+	C: ChainWithBalances,
+	C::NativeBalance: UniqueSaturatedInto<u128>,
+{
+	let relayer = match lane_end.relayer {
+		Some(ref relayer) => relayer.clone(),
+		None => return Ok(None),
+	};
+
+	match lane_end.client.free_native_balance(relayer).await {
+		Ok(balance) => Ok(Some(balance.unique_saturated_into())),
+		Err(SubstrateError::AccountDoesNotExist) => Ok(Some(0)),
+		Err(e) => Err(format!("Failed to read {} relayer balance: {:?}", C::NAME, e)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn healthy_lane() -> LaneDiagnostics {
+		LaneDiagnostics {
+			source: "Source",
+			target: "Target",
+			latest_generated_nonce: 10,
+			latest_generated_nonce_at_known_source_block: 10,
+			latest_received_nonce_at_target: 5,
+			latest_received_nonce_at_known_target_block: 5,
+			latest_confirmed_nonce_at_target: 3,
+			latest_confirmed_nonce_at_source: 3,
+			max_unconfirmed_messages_at_target: 100,
+			oldest_undelivered_message_fee: Some(100),
+			min_profitable_fee: Some(100),
+			source_relayer_balance: Some(1_000),
+			min_source_relayer_balance: 500,
+			target_relayer_balance: Some(1_000),
+			min_target_relayer_balance: 500,
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn no_problems_found_at_healthy_lane() {
+		assert_eq!(find_problems(&healthy_lane()), vec![]);
+	}
+
+	#[test]
+	fn halted_pallets_are_detected() {
+		let mut diagnostics = healthy_lane();
+		diagnostics.is_source_pallet_halted = true;
+		diagnostics.is_target_pallet_halted = true;
+		assert_eq!(
+			find_problems(&diagnostics),
+			vec![
+				LaneProblem::PalletHalted { chain: "Source" },
+				LaneProblem::PalletHalted { chain: "Target" },
+			],
+		);
+	}
+
+	#[test]
+	fn missing_source_headers_are_detected() {
+		let mut diagnostics = healthy_lane();
+		diagnostics.latest_generated_nonce_at_known_source_block = 5;
+		assert_eq!(
+			find_problems(&diagnostics),
+			vec![LaneProblem::MissingSourceHeadersAtTarget { unprovable_messages: 5 }],
+		);
+	}
+
+	#[test]
+	fn missing_target_headers_are_detected() {
+		let mut diagnostics = healthy_lane();
+		diagnostics.latest_received_nonce_at_known_target_block = 3;
+		assert_eq!(
+			find_problems(&diagnostics),
+			vec![LaneProblem::MissingTargetHeadersAtSource {
+				unprovable_confirmations: 2
+			}],
+		);
+	}
+
+	#[test]
+	fn unprovable_confirmations_are_counted_from_latest_known_target_block() {
+		let mut diagnostics = healthy_lane();
+		diagnostics.latest_received_nonce_at_target = 7;
+		diagnostics.latest_received_nonce_at_known_target_block = 2;
+		diagnostics.latest_confirmed_nonce_at_source = 3;
+		assert_eq!(
+			find_problems(&diagnostics),
+			vec![LaneProblem::MissingTargetHeadersAtSource {
+				unprovable_confirmations: 5
+			}],
+		);
+	}
+
+	#[test]
+	fn nonce_gap_is_detected() {
+		let mut diagnostics = healthy_lane();
+		diagnostics.latest_confirmed_nonce_at_source = 7;
+		assert!(matches!(
+			find_problems(&diagnostics).as_slice(),
+			[LaneProblem::NonceGap { .. }],
+		));
+	}
+
+	#[test]
+	fn too_many_unconfirmed_messages_are_detected() {
+		let mut diagnostics = healthy_lane();
+		diagnostics.max_unconfirmed_messages_at_target = 2;
+		assert_eq!(
+			find_problems(&diagnostics),
+			vec![LaneProblem::TooManyUnconfirmedMessages {
+				unconfirmed_messages: 2,
+				max_unconfirmed_messages: 2,
+			}],
+		);
+	}
+
+	#[test]
+	fn unprofitable_fee_is_detected() {
+		let mut diagnostics = healthy_lane();
+		diagnostics.oldest_undelivered_message_fee = Some(99);
+		assert_eq!(
+			find_problems(&diagnostics),
+			vec![LaneProblem::UnprofitableFee {
+				nonce: 6,
+				fee: 99,
+				min_profitable_fee: 100,
+			}],
+		);
+	}
+
+	#[test]
+	fn exhausted_relayer_balance_is_detected() {
+		let mut diagnostics = healthy_lane();
+		diagnostics.target_relayer_balance = Some(0);
+		assert_eq!(
+			find_problems(&diagnostics),
+			vec![LaneProblem::RelayerBalanceExhausted {
+				chain: "Target",
+				balance: 0,
+				min_balance: 500,
+			}],
+		);
+	}
+}
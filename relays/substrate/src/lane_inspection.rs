@@ -0,0 +1,380 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Lane inspection.
+//!
+//! Reads complete state of the message lane from both chains: outbound and inbound lanes data,
+//! queued messages, unrewarded relayers and the newest source header that is known to the target
+//! chain. Lane health verdict is based on the lane diagnostics. Lanes data is read from the
+//! storage at best blocks, so it may be ahead of the diagnostics, which are read at best
+//! finalized blocks.
+
+use crate::lane_diagnostics::{self, LaneProblem};
+use crate::messages_source::read_client_state;
+use crate::{MillauClient, RialtoClient};
+
+use bp_message_lane::{InboundLaneData, LaneId, MessageData, MessageNonce, OutboundLaneData};
+use relay_substrate_client::{ChainWithBalances, Client, HashOf};
+use relay_utils::BlockNumberBase;
+use serde::Serialize;
+use sp_core::storage::StorageKey;
+use sp_runtime::{traits::UniqueSaturatedInto, DeserializeOwned};
+use std::fmt;
+
+/// Maximal number of queued messages that are read from the source chain.
+const MAX_INSPECTED_MESSAGES: MessageNonce = 32;
+
+/// Lane state, read from both chains.
+#[derive(Debug, Serialize)]
+pub struct LaneInspection {
+	/// Name of the source chain.
+	pub source: &'static str,
+	/// Name of the target chain.
+	pub target: &'static str,
+	/// Hex-encoded lane id.
+	pub lane: String,
+	/// Nonces of the outbound lane at the source chain.
+	pub outbound_lane: OutboundLaneNonces,
+	/// Nonces of the inbound lane at the target chain.
+	pub inbound_lane: InboundLaneNonces,
+	/// Number of messages that are not yet confirmed at the source chain.
+	pub queued_messages_count: MessageNonce,
+	/// Oldest messages that are not yet confirmed at the source chain. At most
+	/// `MAX_INSPECTED_MESSAGES` messages are read.
+	pub queued_messages: Vec<QueuedMessage>,
+	/// Relayers that have delivered messages to the target chain, but are not yet rewarded.
+	pub unrewarded_relayers: Vec<UnrewardedRelayer>,
+	/// Newest finalized source header that is known to the target chain.
+	pub source_header_at_target: HeaderInfo,
+	/// True if no problems have been detected.
+	pub is_healthy: bool,
+	/// Problems, detected by the lane diagnostics.
+	pub problems: Vec<LaneProblem>,
+}
+
+/// Nonces of the outbound lane.
+#[derive(Debug, Serialize)]
+pub struct OutboundLaneNonces {
+	/// Nonce of the oldest message that is not yet pruned.
+	pub oldest_unpruned_nonce: MessageNonce,
+	/// Nonce of the latest message, which delivery is confirmed.
+	pub latest_received_nonce: MessageNonce,
+	/// Nonce of the latest generated message.
+	pub latest_generated_nonce: MessageNonce,
+}
+
+/// Nonces of the inbound lane.
+#[derive(Debug, Serialize)]
+pub struct InboundLaneNonces {
+	/// Nonce of the latest received message.
+	pub latest_received_nonce: MessageNonce,
+	/// Nonce of the latest message, which delivery confirmation is known to the target chain.
+	pub latest_confirmed_nonce: MessageNonce,
+}
+
+/// Message that is queued at the source chain.
+#[derive(Debug, Serialize)]
+pub struct QueuedMessage {
+	/// Message nonce.
+	pub nonce: MessageNonce,
+	/// Fee, paid for message delivery and dispatch.
+	pub fee: u128,
+	/// Size of the message payload.
+	pub payload_size: usize,
+}
+
+/// Relayer that has delivered messages, but is not yet rewarded.
+#[derive(Debug, Serialize)]
+pub struct UnrewardedRelayer {
+	/// Relayer account at the source chain.
+	pub relayer: String,
+	/// Nonce of the first delivered message.
+	pub begin: MessageNonce,
+	/// Nonce of the last delivered message.
+	pub end: MessageNonce,
+}
+
+/// Header number and hash.
+#[derive(Debug, Serialize)]
+pub struct HeaderInfo {
+	/// Header number.
+	pub number: u64,
+	/// Hex-encoded header hash.
+	pub hash: String,
+}
+
+/// Inspect Millau -> Rialto lane.
+pub async fn millau_to_rialto(
+	millau_client: MillauClient,
+	rialto_client: RialtoClient,
+	lane: LaneId,
+) -> Result<LaneInspection, String> {
+	let diagnostics =
+		lane_diagnostics::millau_to_rialto(millau_client.clone(), rialto_client.clone(), lane, None, None, None)
+			.await?;
+	inspect_lane(
+		millau_client,
+		rialto_client,
+		lane,
+		diagnostics,
+		pallet_message_lane::storage_keys::outbound_lane_data_key::<pallet_message_lane::DefaultInstance>(&lane),
+		pallet_message_lane::storage_keys::inbound_lane_data_key::<
+			rialto_runtime::Runtime,
+			pallet_message_lane::DefaultInstance,
+		>(&lane),
+		|nonce| {
+			pallet_message_lane::storage_keys::message_key::<
+				millau_runtime::Runtime,
+				pallet_message_lane::DefaultInstance,
+			>(&lane, nonce)
+		},
+	)
+	.await
+}
+
+/// Inspect Rialto -> Millau lane.
+pub async fn rialto_to_millau(
+	rialto_client: RialtoClient,
+	millau_client: MillauClient,
+	lane: LaneId,
+) -> Result<LaneInspection, String> {
+	let diagnostics =
+		lane_diagnostics::rialto_to_millau(rialto_client.clone(), millau_client.clone(), lane, None, None, None)
+			.await?;
+	inspect_lane(
+		rialto_client,
+		millau_client,
+		lane,
+		diagnostics,
+		pallet_message_lane::storage_keys::outbound_lane_data_key::<pallet_message_lane::DefaultInstance>(&lane),
+		pallet_message_lane::storage_keys::inbound_lane_data_key::<
+			millau_runtime::Runtime,
+			pallet_message_lane::DefaultInstance,
+		>(&lane),
+		|nonce| {
+			pallet_message_lane::storage_keys::message_key::<
+				rialto_runtime::Runtime,
+				pallet_message_lane::DefaultInstance,
+			>(&lane, nonce)
+		},
+	)
+	.await
+}
+
+/// Read lane state from both chains.
+async fn inspect_lane<Source, Target>(
+	source_client: Client<Source>,
+	target_client: Client<Target>,
+	lane: LaneId,
+	diagnostics: lane_diagnostics::LaneDiagnostics,
+	outbound_lane_storage_key: StorageKey,
+	inbound_lane_storage_key: StorageKey,
+	outbound_message_storage_key: impl Fn(MessageNonce) -> StorageKey,
+) -> Result<LaneInspection, String>
+where
+	Source: ChainWithBalances,
+	Source::BlockNumber: BlockNumberBase,
+	Source::NativeBalance: UniqueSaturatedInto<u128>,
+	Target: ChainWithBalances,
+	Target::Header: DeserializeOwned,
+	Target::Index: DeserializeOwned,
+{
+	let outbound_lane = source_client
+		.storage_value_by_key::<OutboundLaneData>(outbound_lane_storage_key)
+		.await
+		.map_err(|e| format!("Failed to read {} outbound lane: {:?}", Source::NAME, e))?
+		.unwrap_or_default();
+	let inbound_lane = target_client
+		.storage_value_by_key::<InboundLaneData<Source::AccountId>>(inbound_lane_storage_key)
+		.await
+		.map_err(|e| format!("Failed to read {} inbound lane: {:?}", Target::NAME, e))?
+		.unwrap_or_default();
+	let target_state = read_client_state::<_, HashOf<Source>, Source::BlockNumber>(&target_client, Source::NAME)
+		.await
+		.map_err(|e| format!("Failed to read {} state: {:?}", Target::NAME, e))?;
+
+	let first_queued_nonce = std::cmp::max(
+		outbound_lane.oldest_unpruned_nonce,
+		outbound_lane.latest_received_nonce + 1,
+	);
+	let queued_messages_count = (outbound_lane.latest_generated_nonce + 1).saturating_sub(first_queued_nonce);
+	let mut queued_messages = Vec::new();
+	for nonce in first_queued_nonce..first_queued_nonce + std::cmp::min(queued_messages_count, MAX_INSPECTED_MESSAGES) {
+		let message = source_client
+			.storage_value_by_key::<MessageData<Source::NativeBalance>>(outbound_message_storage_key(nonce))
+			.await
+			.map_err(|e| format!("Failed to read {} outbound message {}: {:?}", Source::NAME, nonce, e))?;
+		if let Some(message) = message {
+			queued_messages.push(QueuedMessage {
+				nonce,
+				fee: message.fee.unique_saturated_into(),
+				payload_size: message.payload.len(),
+			});
+		}
+	}
+
+	Ok(LaneInspection {
+		source: Source::NAME,
+		target: Target::NAME,
+		lane: lane.to_string(),
+		outbound_lane: OutboundLaneNonces {
+			oldest_unpruned_nonce: outbound_lane.oldest_unpruned_nonce,
+			latest_received_nonce: outbound_lane.latest_received_nonce,
+			latest_generated_nonce: outbound_lane.latest_generated_nonce,
+		},
+		inbound_lane: InboundLaneNonces {
+			latest_received_nonce: inbound_lane.latest_received_nonce,
+			latest_confirmed_nonce: inbound_lane.latest_confirmed_nonce,
+		},
+		queued_messages_count,
+		queued_messages,
+		unrewarded_relayers: inbound_lane
+			.relayers
+			.into_iter()
+			.map(|(begin, end, relayer)| UnrewardedRelayer {
+				relayer: relayer.to_string(),
+				begin,
+				end,
+			})
+			.collect(),
+		source_header_at_target: HeaderInfo {
+			number: target_state.best_peer.0.into(),
+			hash: format!("{:?}", target_state.best_peer.1),
+		},
+		is_healthy: diagnostics.problems.is_empty(),
+		problems: diagnostics.problems,
+	})
+}
+
+impl fmt::Display for LaneInspection {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(
+			f,
+			"{} -> {} lane {}: {}",
+			self.source,
+			self.target,
+			self.lane,
+			if self.is_healthy { "healthy" } else { "NOT healthy" },
+		)?;
+		writeln!(
+			f,
+			"  Outbound lane at {}: oldest unpruned nonce {}, latest confirmed nonce {}, latest generated nonce {}",
+			self.source,
+			self.outbound_lane.oldest_unpruned_nonce,
+			self.outbound_lane.latest_received_nonce,
+			self.outbound_lane.latest_generated_nonce,
+		)?;
+		writeln!(
+			f,
+			"  Inbound lane at {}: latest received nonce {}, latest confirmed nonce {}",
+			self.target, self.inbound_lane.latest_received_nonce, self.inbound_lane.latest_confirmed_nonce,
+		)?;
+		writeln!(
+			f,
+			"  Newest {} header known to {}: #{} ({})",
+			self.source, self.target, self.source_header_at_target.number, self.source_header_at_target.hash,
+		)?;
+
+		writeln!(f, "  Queued messages: {}", self.queued_messages_count)?;
+		for message in &self.queued_messages {
+			writeln!(
+				f,
+				"    nonce {}: fee {}, payload {} bytes",
+				message.nonce, message.fee, message.payload_size,
+			)?;
+		}
+		if self.queued_messages_count > self.queued_messages.len() as MessageNonce {
+			writeln!(
+				f,
+				"    ... {} more",
+				self.queued_messages_count - self.queued_messages.len() as MessageNonce,
+			)?;
+		}
+
+		writeln!(f, "  Unrewarded relayers: {}", self.unrewarded_relayers.len())?;
+		for relayer in &self.unrewarded_relayers {
+			writeln!(
+				f,
+				"    {}: messages {}..={}",
+				relayer.relayer, relayer.begin, relayer.end
+			)?;
+		}
+
+		if !self.problems.is_empty() {
+			writeln!(f, "  Problems:")?;
+			for problem in &self.problems {
+				writeln!(f, "    {:?}", problem)?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn lane_inspection_is_displayed() {
+		let inspection = LaneInspection {
+			source: "Millau",
+			target: "Rialto",
+			lane: "00000000".into(),
+			outbound_lane: OutboundLaneNonces {
+				oldest_unpruned_nonce: 3,
+				latest_received_nonce: 4,
+				latest_generated_nonce: 40,
+			},
+			inbound_lane: InboundLaneNonces {
+				latest_received_nonce: 6,
+				latest_confirmed_nonce: 4,
+			},
+			queued_messages_count: 36,
+			queued_messages: vec![QueuedMessage {
+				nonce: 5,
+				fee: 100,
+				payload_size: 64,
+			}],
+			unrewarded_relayers: vec![UnrewardedRelayer {
+				relayer: "relayer".into(),
+				begin: 5,
+				end: 6,
+			}],
+			source_header_at_target: HeaderInfo {
+				number: 100,
+				hash: "0x00".into(),
+			},
+			is_healthy: false,
+			problems: vec![LaneProblem::PalletHalted { chain: "Rialto" }],
+		};
+
+		assert_eq!(
+			inspection.to_string(),
+			"Millau -> Rialto lane 00000000: NOT healthy\n\
+			\x20 Outbound lane at Millau: oldest unpruned nonce 3, latest confirmed nonce 4, latest generated nonce 40\n\
+			\x20 Inbound lane at Rialto: latest received nonce 6, latest confirmed nonce 4\n\
+			\x20 Newest Millau header known to Rialto: #100 (0x00)\n\
+			\x20 Queued messages: 36\n\
+			\x20   nonce 5: fee 100, payload 64 bytes\n\
+			\x20   ... 35 more\n\
+			\x20 Unrewarded relayers: 1\n\
+			\x20   relayer: messages 5..=6\n\
+			\x20 Problems:\n\
+			\x20   PalletHalted { chain: \"Rialto\" }\n",
+		);
+	}
+}
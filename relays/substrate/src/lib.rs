@@ -0,0 +1,50 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Substrate-to-substrate relay library.
+//!
+//! The library is used by the `substrate-relay` binary. It may also be used to run relays
+//! within other processes (e.g. in end-to-end tests).
+
+#![warn(missing_docs)]
+
+/// Millau node client.
+pub type MillauClient = relay_substrate_client::Client<relay_millau_client::Millau>;
+/// Rialto node client.
+pub type RialtoClient = relay_substrate_client::Client<relay_rialto_client::Rialto>;
+/// Westend node client.
+pub type WestendClient = relay_substrate_client::Client<relay_westend_client::Westend>;
+
+pub mod bridge_state;
+pub mod call_batcher;
+pub mod chain_pair;
+pub mod headers_initialize;
+mod headers_maintain;
+mod headers_pipeline;
+mod headers_target;
+pub mod lane_diagnostics;
+pub mod lane_inspection;
+pub mod message_trace;
+mod messages_source;
+mod messages_target;
+pub mod millau_headers_to_rialto;
+pub mod millau_messages_to_rialto;
+pub mod millau_rialto_relay;
+pub mod proof_replay;
+pub mod rialto_headers_to_millau;
+pub mod rialto_messages_to_millau;
+pub mod storage_decoder;
+pub mod westend_headers_to_millau;
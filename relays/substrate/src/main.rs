@@ -18,30 +18,15 @@
 
 #![warn(missing_docs)]
 
-use codec::Encode;
-use frame_support::weights::GetDispatchInfo;
-use pallet_bridge_call_dispatch::{CallOrigin, MessagePayload};
-use relay_millau_client::{Millau, SigningParams as MillauSigningParams};
-use relay_rialto_client::{Rialto, SigningParams as RialtoSigningParams};
-use relay_substrate_client::{ConnectionParams, TransactionSignScheme};
+use bp_runtime::SourceAccount;
 use relay_utils::initialize::initialize_relay;
-use sp_core::{Bytes, Pair};
-
-/// Millau node client.
-pub type MillauClient = relay_substrate_client::Client<Millau>;
-/// Rialto node client.
-pub type RialtoClient = relay_substrate_client::Client<Rialto>;
+use sp_runtime::MultiSigner;
+use substrate_relay::{
+	bridge_state, lane_diagnostics, lane_inspection, message_trace, millau_messages_to_rialto, proof_replay,
+	storage_decoder, westend_headers_to_millau, MillauClient, RialtoClient, WestendClient,
+};
 
 mod cli;
-mod headers_initialize;
-mod headers_maintain;
-mod headers_pipeline;
-mod headers_target;
-mod messages_source;
-mod messages_target;
-mod millau_headers_to_rialto;
-mod millau_messages_to_rialto;
-mod rialto_headers_to_millau;
 
 fn main() {
 	initialize_relay();
@@ -54,186 +39,36 @@ fn main() {
 
 async fn run_command(command: cli::Command) -> Result<(), String> {
 	match command {
-		cli::Command::InitializeMillauHeadersBridgeInRialto {
-			millau,
-			rialto,
-			rialto_sign,
-			millau_bridge_params,
-		} => {
-			let millau_client = MillauClient::new(ConnectionParams {
-				host: millau.millau_host,
-				port: millau.millau_port,
-			})
-			.await?;
-			let rialto_client = RialtoClient::new(ConnectionParams {
-				host: rialto.rialto_host,
-				port: rialto.rialto_port,
-			})
-			.await?;
-			let rialto_sign = RialtoSigningParams::from_suri(
-				&rialto_sign.rialto_signer,
-				rialto_sign.rialto_signer_password.as_deref(),
-			)
-			.map_err(|e| format!("Failed to parse rialto-signer: {:?}", e))?;
-			let rialto_signer_next_index = rialto_client
-				.next_account_index(rialto_sign.signer.public().into())
-				.await?;
-
-			headers_initialize::initialize(
-				millau_client,
-				rialto_client.clone(),
-				millau_bridge_params.millau_initial_header,
-				millau_bridge_params.millau_initial_authorities,
-				millau_bridge_params.millau_initial_authorities_set_id,
-				move |initialization_data| {
-					Ok(Bytes(
-						Rialto::sign_transaction(
-							&rialto_client,
-							&rialto_sign.signer,
-							rialto_signer_next_index,
-							millau_runtime::SudoCall::sudo(Box::new(
-								rialto_runtime::BridgeMillauCall::initialize(initialization_data).into(),
-							))
-							.into(),
-						)
-						.encode(),
-					))
-				},
-			)
-			.await;
-		}
-		cli::Command::MillauHeadersToRialto {
-			millau,
-			rialto,
-			rialto_sign,
-			prometheus_params,
-		} => {
-			let millau_client = MillauClient::new(ConnectionParams {
-				host: millau.millau_host,
-				port: millau.millau_port,
-			})
-			.await?;
-			let rialto_client = RialtoClient::new(ConnectionParams {
-				host: rialto.rialto_host,
-				port: rialto.rialto_port,
-			})
-			.await?;
-			let rialto_sign = RialtoSigningParams::from_suri(
-				&rialto_sign.rialto_signer,
-				rialto_sign.rialto_signer_password.as_deref(),
-			)
-			.map_err(|e| format!("Failed to parse rialto-signer: {:?}", e))?;
-			millau_headers_to_rialto::run(millau_client, rialto_client, rialto_sign, prometheus_params.into()).await;
-		}
-		cli::Command::InitializeRialtoHeadersBridgeInMillau {
-			rialto,
+		cli::Command::ChainPair(command) => command.run().await?,
+		cli::Command::InitializeWestendHeadersBridgeInMillau {
+			westend,
 			millau,
 			millau_sign,
-			rialto_bridge_params,
+			westend_bridge_params,
 		} => {
-			let rialto_client = RialtoClient::new(ConnectionParams {
-				host: rialto.rialto_host,
-				port: rialto.rialto_port,
-			})
-			.await?;
-			let millau_client = MillauClient::new(ConnectionParams {
-				host: millau.millau_host,
-				port: millau.millau_port,
-			})
-			.await?;
-			let millau_sign = MillauSigningParams::from_suri(
-				&millau_sign.millau_signer,
-				millau_sign.millau_signer_password.as_deref(),
-			)
-			.map_err(|e| format!("Failed to parse millau-signer: {:?}", e))?;
-			let millau_signer_next_index = millau_client
-				.next_account_index(millau_sign.signer.public().into())
-				.await?;
-
-			headers_initialize::initialize(
-				rialto_client,
-				millau_client.clone(),
-				rialto_bridge_params.rialto_initial_header,
-				rialto_bridge_params.rialto_initial_authorities,
-				rialto_bridge_params.rialto_initial_authorities_set_id,
-				move |initialization_data| {
-					Ok(Bytes(
-						Millau::sign_transaction(
-							&millau_client,
-							&millau_sign.signer,
-							millau_signer_next_index,
-							millau_runtime::SudoCall::sudo(Box::new(
-								millau_runtime::BridgeRialtoCall::initialize(initialization_data).into(),
-							))
-							.into(),
-						)
-						.encode(),
-					))
-				},
+			let westend_client = WestendClient::new(westend.into()).await?;
+			let millau_client = MillauClient::new(millau.into()).await?;
+			let millau_sign = millau_sign.parse().await?;
+			westend_headers_to_millau::initialize(
+				westend_client,
+				millau_client,
+				millau_sign,
+				westend_bridge_params.westend_initial_header,
+				westend_bridge_params.westend_initial_authorities,
+				westend_bridge_params.westend_initial_authorities_set_id,
 			)
-			.await;
-		}
-		cli::Command::RialtoHeadersToMillau {
-			rialto,
-			millau,
-			millau_sign,
-			prometheus_params,
-		} => {
-			let rialto_client = RialtoClient::new(ConnectionParams {
-				host: rialto.rialto_host,
-				port: rialto.rialto_port,
-			})
 			.await?;
-			let millau_client = MillauClient::new(ConnectionParams {
-				host: millau.millau_host,
-				port: millau.millau_port,
-			})
-			.await?;
-			let millau_sign = MillauSigningParams::from_suri(
-				&millau_sign.millau_signer,
-				millau_sign.millau_signer_password.as_deref(),
-			)
-			.map_err(|e| format!("Failed to parse millau-signer: {:?}", e))?;
-
-			rialto_headers_to_millau::run(rialto_client, millau_client, millau_sign, prometheus_params.into()).await;
 		}
-		cli::Command::MillauMessagesToRialto {
+		cli::Command::WestendHeadersToMillau {
+			westend,
 			millau,
 			millau_sign,
-			rialto,
-			rialto_sign,
 			prometheus_params,
-			lane,
 		} => {
-			let millau_client = MillauClient::new(ConnectionParams {
-				host: millau.millau_host,
-				port: millau.millau_port,
-			})
-			.await?;
-			let millau_sign = MillauSigningParams::from_suri(
-				&millau_sign.millau_signer,
-				millau_sign.millau_signer_password.as_deref(),
-			)
-			.map_err(|e| format!("Failed to parse millau-signer: {:?}", e))?;
-			let rialto_client = RialtoClient::new(ConnectionParams {
-				host: rialto.rialto_host,
-				port: rialto.rialto_port,
-			})
-			.await?;
-			let rialto_sign = RialtoSigningParams::from_suri(
-				&rialto_sign.rialto_signer,
-				rialto_sign.rialto_signer_password.as_deref(),
-			)
-			.map_err(|e| format!("Failed to parse rialto-signer: {:?}", e))?;
-
-			millau_messages_to_rialto::run(
-				millau_client,
-				millau_sign,
-				rialto_client,
-				rialto_sign,
-				lane.into(),
-				prometheus_params.into(),
-			);
+			let westend_client = WestendClient::new(westend.into()).await?;
+			let millau_client = MillauClient::new(millau.into()).await?;
+			let millau_sign = millau_sign.parse().await?;
+			westend_headers_to_millau::run(westend_client, millau_client, millau_sign, prometheus_params.into()).await;
 		}
 		cli::Command::SubmitMillauToRialtoMessage {
 			millau,
@@ -242,22 +77,11 @@ async fn run_command(command: cli::Command) -> Result<(), String> {
 			lane,
 			message,
 			fee,
+			origin_nonce,
 		} => {
-			let millau_client = MillauClient::new(ConnectionParams {
-				host: millau.millau_host,
-				port: millau.millau_port,
-			})
-			.await?;
-			let millau_sign = MillauSigningParams::from_suri(
-				&millau_sign.millau_signer,
-				millau_sign.millau_signer_password.as_deref(),
-			)
-			.map_err(|e| format!("Failed to parse millau-signer: {:?}", e))?;
-			let rialto_sign = RialtoSigningParams::from_suri(
-				&rialto_sign.rialto_signer,
-				rialto_sign.rialto_signer_password.as_deref(),
-			)
-			.map_err(|e| format!("Failed to parse rialto-signer: {:?}", e))?;
+			let millau_client = MillauClient::new(millau.into()).await?;
+			let millau_sign = millau_sign.parse().await?;
+			let rialto_sign = rialto_sign.parse().await?;
 
 			let rialto_call = match message {
 				cli::ToRialtoMessage::Remark => rialto_runtime::Call::System(rialto_runtime::SystemCall::remark(
@@ -272,46 +96,223 @@ async fn run_command(command: cli::Command) -> Result<(), String> {
 					.to_vec(),
 				)),
 			};
-			let rialto_call_weight = rialto_call.get_dispatch_info().weight;
 
-			let millau_sender_public: bp_millau::AccountSigner = millau_sign.signer.public().clone().into();
-			let rialto_origin_public = rialto_sign.signer.public();
+			millau_messages_to_rialto::submit_message(
+				&millau_client,
+				&millau_sign,
+				&rialto_sign,
+				lane.into(),
+				rialto_call,
+				fee,
+				origin_nonce,
+			)
+			.await?;
+		}
+		cli::Command::DiagnoseMillauToRialtoLane {
+			millau,
+			rialto,
+			lane,
+			millau_relayer,
+			rialto_relayer,
+			min_profitable_fee,
+		} => {
+			let millau_client = MillauClient::new(millau.into()).await?;
+			let rialto_client = RialtoClient::new(rialto.into()).await?;
+
+			let diagnostics = lane_diagnostics::millau_to_rialto(
+				millau_client,
+				rialto_client,
+				lane.into(),
+				millau_relayer,
+				rialto_relayer,
+				min_profitable_fee,
+			)
+			.await?;
+			print_lane_diagnostics(&diagnostics)?;
+		}
+		cli::Command::DiagnoseRialtoToMillauLane {
+			rialto,
+			millau,
+			lane,
+			rialto_relayer,
+			millau_relayer,
+			min_profitable_fee,
+		} => {
+			let rialto_client = RialtoClient::new(rialto.into()).await?;
+			let millau_client = MillauClient::new(millau.into()).await?;
+
+			let diagnostics = lane_diagnostics::rialto_to_millau(
+				rialto_client,
+				millau_client,
+				lane.into(),
+				rialto_relayer,
+				millau_relayer,
+				min_profitable_fee,
+			)
+			.await?;
+			print_lane_diagnostics(&diagnostics)?;
+		}
+		cli::Command::InspectLane {
+			millau,
+			rialto,
+			lane,
+			direction,
+			format,
+		} => {
+			let millau_client = MillauClient::new(millau.into()).await?;
+			let rialto_client = RialtoClient::new(rialto.into()).await?;
+
+			let lane = lane.into();
+			let mut inspections = Vec::new();
+			if direction != Some(cli::LaneDirection::RialtoToMillau) {
+				inspections
+					.push(lane_inspection::millau_to_rialto(millau_client.clone(), rialto_client.clone(), lane).await?);
+			}
+			if direction != Some(cli::LaneDirection::MillauToRialto) {
+				inspections.push(lane_inspection::rialto_to_millau(rialto_client, millau_client, lane).await?);
+			}
+			print_lane_inspections(&inspections, format)?;
+		}
+		cli::Command::TraceMessage {
+			millau,
+			rialto,
+			lane,
+			direction,
+			nonce,
+			format,
+		} => {
+			let millau_client = MillauClient::new(millau.into()).await?;
+			let rialto_client = RialtoClient::new(rialto.into()).await?;
+
+			let timeline = match direction {
+				cli::LaneDirection::MillauToRialto => {
+					message_trace::millau_to_rialto(millau_client, rialto_client, lane.into(), nonce).await?
+				}
+				cli::LaneDirection::RialtoToMillau => {
+					message_trace::rialto_to_millau(rialto_client, millau_client, lane.into(), nonce).await?
+				}
+			};
+			print_message_timeline(&timeline, format)?;
+		}
+		cli::Command::DeriveRialtoAccountFromMillau { millau_account_public } => {
+			let millau_account = parse_source_account(millau_account_public.as_deref(), "millau-account-public")?;
+			println!("{}", bp_rialto::derive_account_from_millau_id(millau_account));
+		}
+		cli::Command::DeriveMillauAccountFromRialto { rialto_account_public } => {
+			let rialto_account = parse_source_account(rialto_account_public.as_deref(), "rialto-account-public")?;
+			println!("{}", bp_millau::derive_account_from_rialto_id(rialto_account));
+		}
+		cli::Command::DecodeMillauStorage { key, value } => {
+			let entry = storage_decoder::decode_millau_storage(&key.0, value.as_ref().map(|value| &value.0[..]))?;
+			print_storage_entry(&entry)?;
+		}
+		cli::Command::DecodeRialtoStorage { key, value } => {
+			let entry = storage_decoder::decode_rialto_storage(&key.0, value.as_ref().map(|value| &value.0[..]))?;
+			print_storage_entry(&entry)?;
+		}
+		cli::Command::ExportMillauBridgeState { millau, output } => {
+			let millau_client = MillauClient::new(millau.into()).await?;
+			bridge_state::export_millau_bridge_state(millau_client)
+				.await?
+				.write(&output)?;
+		}
+		cli::Command::ExportRialtoBridgeState { rialto, output } => {
+			let rialto_client = RialtoClient::new(rialto.into()).await?;
+			bridge_state::export_rialto_bridge_state(rialto_client)
+				.await?
+				.write(&output)?;
+		}
+		cli::Command::ReplayMillauToRialtoMessagesProof {
+			transaction,
+			snapshot,
+			format,
+		} => {
+			let transaction = transaction.read().await?;
+			let snapshot = bridge_state::BridgeStateSnapshot::read(&snapshot)?;
+			print_proof_replay(&proof_replay::millau_to_rialto(&transaction, &snapshot), format)?;
+		}
+		cli::Command::ReplayRialtoToMillauMessagesProof {
+			transaction,
+			snapshot,
+			format,
+		} => {
+			let transaction = transaction.read().await?;
+			let snapshot = bridge_state::BridgeStateSnapshot::read(&snapshot)?;
+			print_proof_replay(&proof_replay::rialto_to_millau(&transaction, &snapshot), format)?;
+		}
+	}
+
+	Ok(())
+}
 
-			let mut rialto_origin_signature_message = Vec::new();
-			rialto_call.encode_to(&mut rialto_origin_signature_message);
-			millau_sender_public.encode_to(&mut rialto_origin_signature_message);
-			let rialto_origin_signature = rialto_sign.signer.sign(&rialto_origin_signature_message);
+/// Parse optional SS58-encoded sr25519 public key of the source chain account. If key is not
+/// specified, the source chain root is assumed.
+fn parse_source_account(public: Option<&str>, arg_name: &str) -> Result<SourceAccount<MultiSigner>, String> {
+	match public {
+		Some(public) => <sp_core::sr25519::Public as sp_core::crypto::Ss58Codec>::from_ss58check(public)
+			.map(|public| SourceAccount::Account(public.into()))
+			.map_err(|e| format!("Failed to parse {}: {:?}", arg_name, e)),
+		None => Ok(SourceAccount::Root),
+	}
+}
 
-			let millau_call =
-				millau_runtime::Call::BridgeRialtoMessageLane(millau_runtime::MessageLaneCall::send_message(
-					lane.into(),
-					MessagePayload {
-						spec_version: millau_runtime::VERSION.spec_version,
-						weight: rialto_call_weight,
-						origin: CallOrigin::RealAccount(
-							millau_sender_public,
-							rialto_origin_public.into(),
-							rialto_origin_signature.into(),
-						),
-						call: rialto_call.encode(),
-					},
-					fee,
-				));
+/// Print lane diagnostics to stdout in JSON format.
+fn print_lane_diagnostics(diagnostics: &lane_diagnostics::LaneDiagnostics) -> Result<(), String> {
+	let json = serde_json::to_string_pretty(diagnostics)
+		.map_err(|e| format!("Failed to serialize lane diagnostics: {}", e))?;
+	println!("{}", json);
+	Ok(())
+}
 
-			let signed_millau_call = Millau::sign_transaction(
-				&millau_client,
-				&millau_sign.signer,
-				millau_client
-					.next_account_index(millau_sign.signer.public().clone().into())
-					.await?,
-				millau_call,
-			);
+/// Print lane inspections to stdout in given format.
+fn print_lane_inspections(
+	inspections: &[lane_inspection::LaneInspection],
+	format: cli::OutputFormat,
+) -> Result<(), String> {
+	match format {
+		cli::OutputFormat::Human => {
+			for inspection in inspections {
+				print!("{}", inspection);
+			}
+		}
+		cli::OutputFormat::Json => {
+			let json = serde_json::to_string_pretty(inspections)
+				.map_err(|e| format!("Failed to serialize lane inspections: {}", e))?;
+			println!("{}", json);
+		}
+	}
+	Ok(())
+}
 
-			millau_client
-				.submit_extrinsic(Bytes(signed_millau_call.encode()))
-				.await?;
+/// Print message timeline to stdout.
+fn print_message_timeline(timeline: &message_trace::MessageTimeline, format: cli::OutputFormat) -> Result<(), String> {
+	match format {
+		cli::OutputFormat::Human => print!("{}", timeline),
+		cli::OutputFormat::Json => {
+			let json = serde_json::to_string_pretty(timeline)
+				.map_err(|e| format!("Failed to serialize message timeline: {}", e))?;
+			println!("{}", json);
 		}
 	}
+	Ok(())
+}
+
+/// Print messages proof verification replay to stdout.
+fn print_proof_replay(replay: &proof_replay::ProofReplay, format: cli::OutputFormat) -> Result<(), String> {
+	match format {
+		cli::OutputFormat::Human => print!("{}", replay),
+		cli::OutputFormat::Json => {
+			let json =
+				serde_json::to_string_pretty(replay).map_err(|e| format!("Failed to serialize proof replay: {}", e))?;
+			println!("{}", json);
+		}
+	}
+	Ok(())
+}
 
+/// Print decoded storage entry to stdout in JSON format.
+fn print_storage_entry(entry: &storage_decoder::DecodedStorageEntry) -> Result<(), String> {
+	let json = serde_json::to_string_pretty(entry).map_err(|e| format!("Failed to serialize storage entry: {}", e))?;
+	println!("{}", json);
 	Ok(())
 }
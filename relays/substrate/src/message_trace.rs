@@ -0,0 +1,457 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Message timeline reconstruction.
+//!
+//! Finds blocks of both chains where the message has reached every stage of its lifecycle:
+//! sent, proved (the source header with the message is finalized by the target chain), delivered,
+//! dispatched and confirmed. Every stage is found using binary search over the best finalized
+//! chain, so only nodes that are keeping state of old blocks (archive nodes) may be used.
+
+use crate::lane_inspection::HeaderInfo;
+use crate::{MillauClient, RialtoClient};
+
+use bp_message_lane::{InboundLaneData, LaneId, MessageNonce, OutboundLaneData};
+use codec::Decode;
+use futures::Future;
+use messages_relay::message_trace::{MessageStage, MessageTraceId};
+use relay_substrate_client::{Chain, Client, HashOf};
+use serde::Serialize;
+use sp_core::{
+	storage::{StorageData, StorageKey},
+	Bytes,
+};
+use sp_runtime::{
+	traits::{Header as HeaderT, One, UniqueSaturatedInto},
+	DeserializeOwned,
+};
+use std::fmt;
+
+/// Timeline of the message.
+#[derive(Debug, Serialize)]
+pub struct MessageTimeline {
+	/// Name of the source chain.
+	pub source: &'static str,
+	/// Name of the target chain.
+	pub target: &'static str,
+	/// Hex-encoded lane id.
+	pub lane: String,
+	/// Message nonce.
+	pub nonce: MessageNonce,
+	/// Message trace id.
+	pub trace_id: String,
+	/// Stages of the message lifecycle, in order.
+	pub stages: Vec<TimelineEntry>,
+}
+
+/// Single stage of the message timeline.
+#[derive(Debug, Serialize)]
+pub struct TimelineEntry {
+	/// Name of the stage.
+	pub stage: &'static str,
+	/// Chain where the stage happens.
+	pub chain: &'static str,
+	/// First finalized block where the message has reached the stage. `None` if the stage is not
+	/// yet reached.
+	pub block: Option<HeaderInfo>,
+}
+
+/// Storage keys that are used to trace the message.
+struct TraceStorageKeys {
+	/// Key of the outbound lane data at the source chain.
+	outbound_lane: StorageKey,
+	/// Key of the inbound lane data at the target chain.
+	inbound_lane: StorageKey,
+	/// Key of the undispatched inbound message at the target chain.
+	inbound_undispatched_message: StorageKey,
+}
+
+/// Trace Millau -> Rialto message.
+pub async fn millau_to_rialto(
+	millau_client: MillauClient,
+	rialto_client: RialtoClient,
+	lane: LaneId,
+	nonce: MessageNonce,
+) -> Result<MessageTimeline, String> {
+	trace_message(
+		millau_client,
+		rialto_client,
+		lane,
+		nonce,
+		TraceStorageKeys {
+			outbound_lane: pallet_message_lane::storage_keys::outbound_lane_data_key::<
+				pallet_message_lane::DefaultInstance,
+			>(&lane),
+			inbound_lane: pallet_message_lane::storage_keys::inbound_lane_data_key::<
+				rialto_runtime::Runtime,
+				pallet_message_lane::DefaultInstance,
+			>(&lane),
+			inbound_undispatched_message: pallet_message_lane::storage_keys::inbound_undispatched_message_key::<
+				rialto_runtime::Runtime,
+				pallet_message_lane::DefaultInstance,
+			>(&lane, nonce),
+		},
+	)
+	.await
+}
+
+/// Trace Rialto -> Millau message.
+pub async fn rialto_to_millau(
+	rialto_client: RialtoClient,
+	millau_client: MillauClient,
+	lane: LaneId,
+	nonce: MessageNonce,
+) -> Result<MessageTimeline, String> {
+	trace_message(
+		rialto_client,
+		millau_client,
+		lane,
+		nonce,
+		TraceStorageKeys {
+			outbound_lane: pallet_message_lane::storage_keys::outbound_lane_data_key::<
+				pallet_message_lane::DefaultInstance,
+			>(&lane),
+			inbound_lane: pallet_message_lane::storage_keys::inbound_lane_data_key::<
+				millau_runtime::Runtime,
+				pallet_message_lane::DefaultInstance,
+			>(&lane),
+			inbound_undispatched_message: pallet_message_lane::storage_keys::inbound_undispatched_message_key::<
+				millau_runtime::Runtime,
+				pallet_message_lane::DefaultInstance,
+			>(&lane, nonce),
+		},
+	)
+	.await
+}
+
+/// Reconstruct timeline of the message.
+async fn trace_message<Source, Target>(
+	source_client: Client<Source>,
+	target_client: Client<Target>,
+	lane: LaneId,
+	nonce: MessageNonce,
+	keys: TraceStorageKeys,
+) -> Result<MessageTimeline, String>
+where
+	Source: Chain,
+	Source::Header: DeserializeOwned,
+	Target: Chain,
+	Target::Header: DeserializeOwned,
+{
+	let source_best = best_finalized_block_number(&source_client).await?;
+	let target_best = best_finalized_block_number(&target_client).await?;
+
+	let sent = first_block_where(Source::BlockNumber::default(), source_best, |number| {
+		let key = keys.outbound_lane.clone();
+		let client = &source_client;
+		async move {
+			let lane_data: OutboundLaneData = read_storage_at(client, key, number).await?.unwrap_or_default();
+			Ok(lane_data.latest_generated_nonce >= nonce)
+		}
+	})
+	.await?;
+	let sent = match sent {
+		Some(sent) => sent,
+		None => {
+			return Err(format!(
+				"Message {} is not sent over {} -> {} lane {} (or the sending block is not finalized)",
+				nonce,
+				Source::NAME,
+				Target::NAME,
+				lane,
+			))
+		}
+	};
+	let sent_hash = block_hash(&source_client, sent).await?;
+
+	let proved = first_block_where(Target::BlockNumber::default(), target_best, |number| {
+		let client = &target_client;
+		async move {
+			let hash = block_hash(client, number).await?;
+			let encoded_finalized_source_block = client
+				.state_call(
+					format!("{}HeaderApi_finalized_block", Source::NAME),
+					Bytes(Vec::new()),
+					Some(hash),
+				)
+				.await
+				.map_err(|e| {
+					format!(
+						"Failed to read best finalized {} block at {}: {:?}",
+						Source::NAME,
+						Target::NAME,
+						e
+					)
+				})?;
+			let (finalized_source_block, _): (Source::BlockNumber, HashOf<Source>) =
+				Decode::decode(&mut &encoded_finalized_source_block.0[..])
+					.map_err(|e| format!("Failed to decode best finalized {} block: {:?}", Source::NAME, e))?;
+			Ok(finalized_source_block >= sent)
+		}
+	})
+	.await?;
+
+	let delivered = match proved {
+		Some(proved) => {
+			first_block_where(proved, target_best, |number| {
+				let key = keys.inbound_lane.clone();
+				let client = &target_client;
+				async move {
+					let lane_data: InboundLaneData<Source::AccountId> =
+						read_storage_at(client, key, number).await?.unwrap_or_default();
+					Ok(lane_data.latest_received_nonce >= nonce)
+				}
+			})
+			.await?
+		}
+		None => None,
+	};
+
+	let dispatched = match delivered {
+		Some(delivered) => {
+			first_block_where(delivered, target_best, |number| {
+				let key = keys.inbound_undispatched_message.clone();
+				let client = &target_client;
+				async move { Ok(read_raw_storage_at(client, key, number).await?.is_none()) }
+			})
+			.await?
+		}
+		None => None,
+	};
+
+	let confirmed = match delivered {
+		Some(_) => {
+			first_block_where(sent, source_best, |number| {
+				let key = keys.outbound_lane.clone();
+				let client = &source_client;
+				async move {
+					let lane_data: OutboundLaneData = read_storage_at(client, key, number).await?.unwrap_or_default();
+					Ok(lane_data.latest_received_nonce >= nonce)
+				}
+			})
+			.await?
+		}
+		None => None,
+	};
+
+	Ok(MessageTimeline {
+		source: Source::NAME,
+		target: Target::NAME,
+		lane: lane.to_string(),
+		nonce,
+		trace_id: MessageTraceId {
+			lane,
+			nonce,
+			source_block_hash: sent_hash,
+		}
+		.to_string(),
+		stages: vec![
+			timeline_entry(&source_client, MessageStage::Sent, Some(sent)).await?,
+			timeline_entry(&target_client, MessageStage::Proved, proved).await?,
+			timeline_entry(&target_client, MessageStage::Delivered, delivered).await?,
+			timeline_entry(&target_client, MessageStage::Dispatched, dispatched).await?,
+			timeline_entry(&source_client, MessageStage::Confirmed, confirmed).await?,
+		],
+	})
+}
+
+/// Find the first block in `[begin; end]` range where given condition holds.
+///
+/// The condition must be monotonic: once it holds at some block, it must hold at all
+/// descendant blocks.
+async fn first_block_where<Number, Condition, ConditionFuture>(
+	mut begin: Number,
+	mut end: Number,
+	condition: Condition,
+) -> Result<Option<Number>, String>
+where
+	Number: sp_runtime::traits::AtLeast32BitUnsigned + Copy,
+	Condition: Fn(Number) -> ConditionFuture,
+	ConditionFuture: Future<Output = Result<bool, String>>,
+{
+	if begin > end || !condition(end).await? {
+		return Ok(None);
+	}
+
+	while begin < end {
+		let middle = begin + (end - begin) / Number::from(2u32);
+		if condition(middle).await? {
+			end = middle;
+		} else {
+			begin = middle + One::one();
+		}
+	}
+
+	Ok(Some(end))
+}
+
+/// Build timeline entry for the stage that has been reached at given block.
+async fn timeline_entry<C: Chain>(
+	client: &Client<C>,
+	stage: MessageStage,
+	number: Option<C::BlockNumber>,
+) -> Result<TimelineEntry, String> {
+	let block = match number {
+		Some(number) => Some(HeaderInfo {
+			number: number.unique_saturated_into(),
+			hash: format!("{:?}", block_hash(client, number).await?),
+		}),
+		None => None,
+	};
+
+	Ok(TimelineEntry {
+		stage: stage.as_str(),
+		chain: C::NAME,
+		block,
+	})
+}
+
+/// Read number of the best finalized block.
+async fn best_finalized_block_number<C: Chain>(client: &Client<C>) -> Result<C::BlockNumber, String>
+where
+	C::Header: DeserializeOwned,
+{
+	let hash = client
+		.best_finalized_header_hash()
+		.await
+		.map_err(|e| format!("Failed to read {} best finalized header hash: {:?}", C::NAME, e))?;
+	let header = client
+		.header_by_hash(hash)
+		.await
+		.map_err(|e| format!("Failed to read {} header {:?}: {:?}", C::NAME, hash, e))?;
+	Ok(*header.number())
+}
+
+/// Read hash of the block with given number.
+async fn block_hash<C: Chain>(client: &Client<C>, number: C::BlockNumber) -> Result<C::Hash, String> {
+	client
+		.block_hash_by_number(number)
+		.await
+		.map_err(|e| format!("Failed to read {} block #{} hash: {:?}", C::NAME, number, e))
+}
+
+/// Read raw storage value at the block with given number.
+async fn read_raw_storage_at<C: Chain>(
+	client: &Client<C>,
+	key: StorageKey,
+	number: C::BlockNumber,
+) -> Result<Option<StorageData>, String> {
+	let hash = block_hash(client, number).await?;
+	client
+		.raw_storage_value(key, hash)
+		.await
+		.map_err(|e| format!("Failed to read {} storage at block #{}: {:?}", C::NAME, number, e))
+}
+
+/// Read and decode storage value at the block with given number.
+async fn read_storage_at<C: Chain, T: Decode>(
+	client: &Client<C>,
+	key: StorageKey,
+	number: C::BlockNumber,
+) -> Result<Option<T>, String> {
+	read_raw_storage_at(client, key, number)
+		.await?
+		.map(|value| {
+			T::decode(&mut &value.0[..]).map_err(|e| {
+				format!(
+					"Failed to decode {} storage value at block #{}: {:?}",
+					C::NAME,
+					number,
+					e
+				)
+			})
+		})
+		.transpose()
+}
+
+impl fmt::Display for MessageTimeline {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(
+			f,
+			"{} -> {} message {} at lane {}: trace id {}",
+			self.source, self.target, self.nonce, self.lane, self.trace_id,
+		)?;
+		for entry in &self.stages {
+			match entry.block {
+				Some(ref block) => writeln!(
+					f,
+					"  {:<12} at {} block #{} ({})",
+					entry.stage, entry.chain, block.number, block.hash,
+				)?,
+				None => writeln!(f, "  {:<12} pending at {}", entry.stage, entry.chain)?,
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn first_block_where_nonce(latest_nonces: &[MessageNonce], nonce: MessageNonce) -> Option<u32> {
+		async_std::task::block_on(first_block_where(0u32, latest_nonces.len() as u32 - 1, |number| {
+			let latest_nonce = latest_nonces[number as usize];
+			async move { Ok(latest_nonce >= nonce) }
+		}))
+		.unwrap()
+	}
+
+	#[test]
+	fn first_block_is_found() {
+		let latest_nonces = [0, 0, 1, 1, 3, 3, 3, 4];
+		assert_eq!(first_block_where_nonce(&latest_nonces, 1), Some(2));
+		assert_eq!(first_block_where_nonce(&latest_nonces, 2), Some(4));
+		assert_eq!(first_block_where_nonce(&latest_nonces, 3), Some(4));
+		assert_eq!(first_block_where_nonce(&latest_nonces, 4), Some(7));
+		assert_eq!(first_block_where_nonce(&latest_nonces, 5), None);
+		assert_eq!(first_block_where_nonce(&[1], 1), Some(0));
+	}
+
+	#[test]
+	fn message_timeline_is_displayed() {
+		let timeline = MessageTimeline {
+			source: "Millau",
+			target: "Rialto",
+			lane: "00000000".into(),
+			nonce: 5,
+			trace_id: "00000000:5@0x01".into(),
+			stages: vec![
+				TimelineEntry {
+					stage: MessageStage::Sent.as_str(),
+					chain: "Millau",
+					block: Some(HeaderInfo {
+						number: 10,
+						hash: "0x01".into(),
+					}),
+				},
+				TimelineEntry {
+					stage: MessageStage::Proved.as_str(),
+					chain: "Rialto",
+					block: None,
+				},
+			],
+		};
+
+		assert_eq!(
+			timeline.to_string(),
+			"Millau -> Rialto message 5 at lane 00000000: trace id 00000000:5@0x01\n\
+			\x20 sent         at Millau block #10 (0x01)\n\
+			\x20 proved       pending at Rialto\n",
+		);
+	}
+}
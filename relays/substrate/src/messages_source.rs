@@ -19,18 +19,23 @@
 //! <BridgedName> chain.
 
 use async_trait::async_trait;
-use bp_message_lane::{LaneId, MessageNonce};
-use bp_runtime::InstanceId;
+use bp_message_lane::{target_chain::ensure_contiguous_nonces, LaneId, MessageData, MessageKey, MessageNonce};
+use bp_runtime::{BridgeError, InstanceId};
 use codec::{Decode, Encode};
 use frame_support::weights::Weight;
 use messages_relay::{
 	message_lane::{MessageLane, SourceHeaderIdOf, TargetHeaderIdOf},
-	message_lane_loop::{ClientState, MessageProofParameters, MessageWeightsMap, SourceClient, SourceClientState},
+	message_lane_loop::{
+		ClientState, MessageProofParameters, MessageRewardsMap, MessageWeightsMap, SourceClient, SourceClientState,
+	},
 };
-use relay_substrate_client::{Chain, Client, Error as SubstrateError, HashOf, HeaderIdOf};
+use relay_substrate_client::{Chain, ChainWithBalances, Client, Error as SubstrateError, HashOf, HeaderIdOf};
 use relay_utils::{BlockNumberBase, HeaderId};
 use sp_core::Bytes;
-use sp_runtime::{traits::Header as HeaderT, DeserializeOwned};
+use sp_runtime::{
+	traits::{Header as HeaderT, UniqueSaturatedInto},
+	DeserializeOwned,
+};
 use sp_trie::StorageProof;
 use std::{marker::PhantomData, ops::RangeInclusive};
 
@@ -45,6 +50,7 @@ pub struct SubstrateMessagesSource<C: Chain, P, M> {
 	tx_maker: M,
 	lane: LaneId,
 	instance: InstanceId,
+	message_lane_pallet: &'static str,
 	_marker: PhantomData<P>,
 }
 
@@ -60,16 +66,44 @@ pub trait SubstrateTransactionMaker<C: Chain, P: MessageLane>: Clone + Send + Sy
 		generated_at_block: TargetHeaderIdOf<P>,
 		proof: P::MessagesReceivingProof,
 	) -> Result<Self::SignedTransaction, SubstrateError>;
+
+	/// Submit messages receiving proof transaction.
+	///
+	/// By default, the transaction is made using `make_messages_receiving_proof_transaction` and
+	/// is submitted as is. Makers may override it to submit the confirmation call in some other
+	/// way (e.g. batched with other calls).
+	async fn submit_messages_receiving_proof_transaction(
+		&self,
+		client: &Client<C>,
+		generated_at_block: TargetHeaderIdOf<P>,
+		proof: P::MessagesReceivingProof,
+	) -> Result<(), SubstrateError> {
+		let _submission_lock = client.lock_signed_extrinsic_submission().await;
+		let tx = self
+			.make_messages_receiving_proof_transaction(generated_at_block, proof)
+			.await?;
+		client.submit_extrinsic(Bytes(tx.encode())).await?;
+		Ok(())
+	}
 }
 
 impl<C: Chain, P, M> SubstrateMessagesSource<C, P, M> {
 	/// Create new Substrate headers source.
-	pub fn new(client: Client<C>, tx_maker: M, lane: LaneId, instance: InstanceId) -> Self {
+	///
+	/// The `message_lane_pallet` is the name of the message lane pallet in the source runtime.
+	pub fn new(
+		client: Client<C>,
+		tx_maker: M,
+		lane: LaneId,
+		instance: InstanceId,
+		message_lane_pallet: &'static str,
+	) -> Self {
 		SubstrateMessagesSource {
 			client,
 			tx_maker,
 			lane,
 			instance,
+			message_lane_pallet,
 			_marker: Default::default(),
 		}
 	}
@@ -82,6 +116,7 @@ impl<C: Chain, P, M: Clone> Clone for SubstrateMessagesSource<C, P, M> {
 			tx_maker: self.tx_maker.clone(),
 			lane: self.lane,
 			instance: self.instance,
+			message_lane_pallet: self.message_lane_pallet,
 			_marker: Default::default(),
 		}
 	}
@@ -90,7 +125,8 @@ impl<C: Chain, P, M: Clone> Clone for SubstrateMessagesSource<C, P, M> {
 #[async_trait]
 impl<C, P, M> SourceClient<P> for SubstrateMessagesSource<C, P, M>
 where
-	C: Chain,
+	C: ChainWithBalances,
+	C::NativeBalance: UniqueSaturatedInto<u128>,
 	C::Header: DeserializeOwned,
 	C::Index: DeserializeOwned,
 	C::BlockNumber: BlockNumberBase,
@@ -122,14 +158,14 @@ where
 		let encoded_response = self
 			.client
 			.state_call(
-				// TODO: https://github.com/paritytech/parity-bridges-common/issues/457
 				"OutboundLaneApi_latest_generated_nonce".into(),
-				Bytes(self.lane.encode()),
+				Bytes((self.instance, self.lane).encode()),
 				Some(id.1),
 			)
 			.await?;
-		let latest_generated_nonce: MessageNonce =
-			Decode::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)?;
+		let latest_generated_nonce: MessageNonce = Option::<MessageNonce>::decode(&mut &encoded_response.0[..])
+			.map_err(SubstrateError::ResponseParseFailed)?
+			.ok_or_else(|| unknown_instance_error(self.instance))?;
 		Ok((id, latest_generated_nonce))
 	}
 
@@ -140,14 +176,14 @@ where
 		let encoded_response = self
 			.client
 			.state_call(
-				// TODO: https://github.com/paritytech/parity-bridges-common/issues/457
 				"OutboundLaneApi_latest_received_nonce".into(),
-				Bytes(self.lane.encode()),
+				Bytes((self.instance, self.lane).encode()),
 				Some(id.1),
 			)
 			.await?;
-		let latest_received_nonce: MessageNonce =
-			Decode::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)?;
+		let latest_received_nonce: MessageNonce = Option::<MessageNonce>::decode(&mut &encoded_response.0[..])
+			.map_err(SubstrateError::ResponseParseFailed)?
+			.ok_or_else(|| unknown_instance_error(self.instance))?;
 		Ok((id, latest_received_nonce))
 	}
 
@@ -159,29 +195,55 @@ where
 		let encoded_response = self
 			.client
 			.state_call(
-				// TODO: https://github.com/paritytech/parity-bridges-common/issues/457
 				"OutboundLaneApi_messages_dispatch_weight".into(),
-				Bytes((self.lane, nonces.start(), nonces.end()).encode()),
+				Bytes((self.instance, self.lane, nonces.start(), nonces.end()).encode()),
 				Some(id.1),
 			)
 			.await?;
 		let weights: Vec<(MessageNonce, Weight)> =
-			Decode::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)?;
+			Option::<Vec<(MessageNonce, Weight)>>::decode(&mut &encoded_response.0[..])
+				.map_err(SubstrateError::ResponseParseFailed)?
+				.ok_or_else(|| unknown_instance_error(self.instance))?;
 
-		let mut expected_nonce = *nonces.start();
-		let mut weights_map = MessageWeightsMap::new();
-		for (nonce, weight) in weights {
-			if nonce != expected_nonce {
-				return Err(SubstrateError::Custom(format!(
+		ensure_contiguous_nonces(*nonces.start(), weights.iter().map(|(nonce, _)| *nonce)).map_err(|gap| {
+			SubstrateError::bridge(
+				BridgeError::NonceGap,
+				format!(
 					"Unexpected nonce in messages_dispatch_weight call result. Expected {}, got {}",
-					expected_nonce, nonce
-				)));
-			}
+					gap.expected, gap.actual
+				),
+			)
+		})?;
 
-			weights_map.insert(nonce, weight);
-			expected_nonce += 1;
+		Ok(weights.into_iter().collect())
+	}
+
+	async fn generated_messages_rewards(
+		&self,
+		id: SourceHeaderIdOf<P>,
+		nonces: RangeInclusive<MessageNonce>,
+	) -> Result<MessageRewardsMap, Self::Error> {
+		let mut rewards = MessageRewardsMap::new();
+		for nonce in nonces {
+			let message_key = MessageKey {
+				lane_id: self.lane,
+				nonce,
+			};
+			let message_data = self
+				.client
+				.storage_map_entry::<_, MessageData<C::NativeBalance>>(
+					self.message_lane_pallet,
+					"OutboundMessages",
+					&message_key,
+					Some(id.1),
+				)
+				.await?;
+			if let Some(message_data) = message_data {
+				rewards.insert(nonce, message_data.fee.unique_saturated_into());
+			}
 		}
-		Ok(weights_map)
+
+		Ok(rewards)
 	}
 
 	async fn prove_messages(
@@ -209,12 +271,9 @@ where
 		generated_at_block: TargetHeaderIdOf<P>,
 		proof: P::MessagesReceivingProof,
 	) -> Result<(), Self::Error> {
-		let tx = self
-			.tx_maker
-			.make_messages_receiving_proof_transaction(generated_at_block, proof)
-			.await?;
-		self.client.submit_extrinsic(Bytes(tx.encode())).await?;
-		Ok(())
+		self.tx_maker
+			.submit_messages_receiving_proof_transaction(&self.client, generated_at_block, proof)
+			.await
 	}
 }
 
@@ -255,3 +314,14 @@ where
 		best_peer: peer_on_self_best_finalized_id,
 	})
 }
+
+/// Error that is returned when the runtime is not aware of the message lane instance.
+pub fn unknown_instance_error(instance: InstanceId) -> SubstrateError {
+	SubstrateError::bridge(
+		BridgeError::Other,
+		format!(
+			"Message lane instance {} is unknown to the runtime",
+			bp_runtime::format_instance_id(instance),
+		),
+	)
+}
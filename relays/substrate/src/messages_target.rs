@@ -18,7 +18,7 @@
 //! runtime that implements `<BridgedChainName>HeaderApi` to allow bridging with
 //! <BridgedName> chain.
 
-use crate::messages_source::read_client_state;
+use crate::messages_source::{read_client_state, unknown_instance_error};
 
 use async_trait::async_trait;
 use bp_message_lane::{LaneId, MessageNonce};
@@ -26,12 +26,15 @@ use bp_runtime::InstanceId;
 use codec::{Decode, Encode};
 use messages_relay::{
 	message_lane::{MessageLane, SourceHeaderIdOf, TargetHeaderIdOf},
-	message_lane_loop::{TargetClient, TargetClientState},
+	message_lane_loop::{MessagesDeliveryEstimation, MessagesDeliverySubmission, TargetClient, TargetClientState},
 };
-use relay_substrate_client::{Chain, Client, Error as SubstrateError, HashOf};
-use relay_utils::BlockNumberBase;
+use relay_substrate_client::{Chain, ChainWithBalances, Client, Error as SubstrateError, HashOf};
+use relay_utils::{BlockNumberBase, MaybeConnectionError};
 use sp_core::Bytes;
-use sp_runtime::{traits::Header as HeaderT, DeserializeOwned};
+use sp_runtime::{
+	traits::{Header as HeaderT, UniqueSaturatedInto},
+	DeserializeOwned,
+};
 use sp_trie::StorageProof;
 use std::{marker::PhantomData, ops::RangeInclusive};
 
@@ -46,7 +49,7 @@ pub struct SubstrateMessagesTarget<C: Chain, P, M> {
 
 /// Substrate transactions maker.
 #[async_trait]
-pub trait SubstrateTransactionMaker<C: Chain, P: MessageLane>: Clone + Send + Sync {
+pub trait SubstrateTransactionMaker<C: ChainWithBalances, P: MessageLane>: Clone + Send + Sync {
 	/// Signed transaction type.
 	type SignedTransaction: Send + Sync + Encode;
 
@@ -57,6 +60,95 @@ pub trait SubstrateTransactionMaker<C: Chain, P: MessageLane>: Clone + Send + Sy
 		nonces: RangeInclusive<MessageNonce>,
 		proof: P::MessagesProof,
 	) -> Result<Self::SignedTransaction, SubstrateError>;
+
+	/// Submit messages delivery transaction.
+	///
+	/// By default, the transaction is made using `make_messages_delivery_transaction` and is
+	/// submitted using `submit_estimated_messages_delivery_transaction`. Makers may override it
+	/// to submit the delivery call in some other way (e.g. batched with other calls).
+	async fn submit_messages_delivery_transaction(
+		&self,
+		client: &Client<C>,
+		generated_at_header: SourceHeaderIdOf<P>,
+		nonces: RangeInclusive<MessageNonce>,
+		proof: P::MessagesProof,
+	) -> Result<MessagesDeliverySubmission, SubstrateError>
+	where
+		C::NativeBalance: UniqueSaturatedInto<u128>,
+	{
+		submit_estimated_messages_delivery_transaction(self, client, generated_at_header, nonces, proof).await
+	}
+}
+
+/// Make, dry-run, estimate and submit messages delivery transaction.
+///
+/// The signed extrinsic submission lock is held until the transaction is submitted, so the same
+/// transaction (with the same nonce) is dry-run, estimated and submitted. The `system_dryRun` is
+/// an unsafe RPC method, so if the node refuses to dry-run the transaction, it is submitted without
+/// this check. Estimation failures are not fatal either.
+pub async fn submit_estimated_messages_delivery_transaction<C, P, M>(
+	tx_maker: &M,
+	client: &Client<C>,
+	generated_at_header: SourceHeaderIdOf<P>,
+	nonces: RangeInclusive<MessageNonce>,
+	proof: P::MessagesProof,
+) -> Result<MessagesDeliverySubmission, SubstrateError>
+where
+	C: ChainWithBalances,
+	C::NativeBalance: UniqueSaturatedInto<u128>,
+	P: MessageLane,
+	M: SubstrateTransactionMaker<C, P>,
+{
+	let _submission_lock = client.lock_signed_extrinsic_submission().await;
+	let tx = tx_maker
+		.make_messages_delivery_transaction(generated_at_header, nonces.clone(), proof)
+		.await?;
+	let encoded_tx = Bytes(tx.encode());
+
+	match client.dry_run_extrinsic(encoded_tx.clone()).await {
+		Ok(Ok(Ok(()))) => (),
+		Ok(Ok(Err(dispatch_error))) => {
+			return Ok(MessagesDeliverySubmission::WillFail(format!(
+				"delivery call fails at {} with {:?}",
+				C::NAME,
+				dispatch_error,
+			)))
+		}
+		Ok(Err(validity_error)) => {
+			return Ok(MessagesDeliverySubmission::WillFail(format!(
+				"delivery transaction is invalid at {}: {:?}",
+				C::NAME,
+				validity_error,
+			)))
+		}
+		Err(error) if error.is_connection_error() => return Err(error),
+		Err(error) => log::debug!(
+			target: "bridge",
+			"Failed to dry-run {} messages delivery transaction: {:?}. Submitting without dry-run",
+			C::NAME,
+			error,
+		),
+	}
+
+	let estimation = match client.extrinsic_dispatch_info(encoded_tx.clone()).await {
+		Ok(dispatch_info) => Some(MessagesDeliveryEstimation {
+			fee: dispatch_info.partial_fee.unique_saturated_into(),
+			weight: dispatch_info.weight,
+		}),
+		Err(error) if error.is_connection_error() => return Err(error),
+		Err(error) => {
+			log::debug!(
+				target: "bridge",
+				"Failed to estimate {} messages delivery transaction: {:?}",
+				C::NAME,
+				error,
+			);
+			None
+		}
+	};
+
+	client.submit_extrinsic(encoded_tx).await?;
+	Ok(MessagesDeliverySubmission::Submitted(nonces, estimation))
 }
 
 impl<C: Chain, P, M> SubstrateMessagesTarget<C, P, M> {
@@ -87,7 +179,8 @@ impl<C: Chain, P, M: Clone> Clone for SubstrateMessagesTarget<C, P, M> {
 #[async_trait]
 impl<C, P, M> TargetClient<P> for SubstrateMessagesTarget<C, P, M>
 where
-	C: Chain,
+	C: ChainWithBalances,
+	C::NativeBalance: UniqueSaturatedInto<u128>,
 	C::Header: DeserializeOwned,
 	C::Index: DeserializeOwned,
 	<C::Header as HeaderT>::Number: BlockNumberBase,
@@ -119,14 +212,14 @@ where
 		let encoded_response = self
 			.client
 			.state_call(
-				// TODO: https://github.com/paritytech/parity-bridges-common/issues/457
 				"InboundLaneApi_latest_received_nonce".into(),
-				Bytes(self.lane.encode()),
+				Bytes((self.instance, self.lane).encode()),
 				Some(id.1),
 			)
 			.await?;
-		let latest_received_nonce: MessageNonce =
-			Decode::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)?;
+		let latest_received_nonce: MessageNonce = Option::<MessageNonce>::decode(&mut &encoded_response.0[..])
+			.map_err(SubstrateError::ResponseParseFailed)?
+			.ok_or_else(|| unknown_instance_error(self.instance))?;
 		Ok((id, latest_received_nonce))
 	}
 
@@ -137,14 +230,14 @@ where
 		let encoded_response = self
 			.client
 			.state_call(
-				// TODO: https://github.com/paritytech/parity-bridges-common/issues/457
 				"OutboundLaneApi_latest_received_nonce".into(),
-				Bytes(self.lane.encode()),
+				Bytes((self.instance, self.lane).encode()),
 				Some(id.1),
 			)
 			.await?;
-		let latest_received_nonce: MessageNonce =
-			Decode::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)?;
+		let latest_received_nonce: MessageNonce = Option::<MessageNonce>::decode(&mut &encoded_response.0[..])
+			.map_err(SubstrateError::ResponseParseFailed)?
+			.ok_or_else(|| unknown_instance_error(self.instance))?;
 		Ok((id, latest_received_nonce))
 	}
 
@@ -165,12 +258,9 @@ where
 		generated_at_header: SourceHeaderIdOf<P>,
 		nonces: RangeInclusive<MessageNonce>,
 		proof: P::MessagesProof,
-	) -> Result<RangeInclusive<MessageNonce>, Self::Error> {
-		let tx = self
-			.tx_maker
-			.make_messages_delivery_transaction(generated_at_header, nonces.clone(), proof)
-			.await?;
-		self.client.submit_extrinsic(Bytes(tx.encode())).await?;
-		Ok(nonces)
+	) -> Result<MessagesDeliverySubmission, Self::Error> {
+		self.tx_maker
+			.submit_messages_delivery_transaction(&self.client, generated_at_header, nonces, proof)
+			.await
 	}
 }
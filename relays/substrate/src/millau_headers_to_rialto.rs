@@ -26,11 +26,13 @@ use bp_millau::{
 	BEST_MILLAU_BLOCKS_METHOD, FINALIZED_MILLAU_BLOCK_METHOD, INCOMPLETE_MILLAU_HEADERS_METHOD,
 	IS_KNOWN_MILLAU_BLOCK_METHOD,
 };
+use codec::Encode;
 use headers_relay::sync_types::QueuedHeader;
 use relay_millau_client::{HeaderId as MillauHeaderId, Millau, SyncHeader as MillauSyncHeader};
 use relay_rialto_client::{BridgeMillauCall, Rialto, SigningParams as RialtoSigningParams};
 use relay_substrate_client::{Error as SubstrateError, TransactionSignScheme};
-use sp_core::Pair;
+use sp_core::Bytes;
+use sp_finality_grandpa::SetId as GrandpaAuthoritiesSetId;
 use sp_runtime::Justification;
 
 /// Millau-to-Rialto headers sync pipeline.
@@ -54,8 +56,20 @@ impl SubstrateHeadersSyncPipeline for MillauHeadersToRialto {
 	) -> Result<Self::SignedTransaction, SubstrateError> {
 		let account_id = self.target_sign.signer.public().as_array_ref().clone().into();
 		let nonce = self.target_client.next_account_index(account_id).await?;
-		let call = BridgeMillauCall::import_signed_header(header.header().clone().into()).into();
-		let transaction = Rialto::sign_transaction(&self.target_client, &self.target_sign.signer, nonce, call);
+		let call =
+			rialto_runtime::Call::BridgeMillau(BridgeMillauCall::import_signed_header(header.header().clone().into()));
+		let era = self
+			.target_client
+			.transaction_era(self.target_sign.transactions_mortality)
+			.await?;
+		let transaction = Rialto::sign_transaction(
+			&self.target_client,
+			&self.target_sign.signer,
+			era,
+			nonce,
+			self.target_sign.wrap_call(call),
+		)
+		.await?;
 		Ok(transaction)
 	}
 
@@ -66,12 +80,69 @@ impl SubstrateHeadersSyncPipeline for MillauHeadersToRialto {
 	) -> Result<Self::SignedTransaction, SubstrateError> {
 		let account_id = self.target_sign.signer.public().as_array_ref().clone().into();
 		let nonce = self.target_client.next_account_index(account_id).await?;
-		let call = BridgeMillauCall::finalize_header(id.1, completion).into();
-		let transaction = Rialto::sign_transaction(&self.target_client, &self.target_sign.signer, nonce, call);
+		let call = rialto_runtime::Call::BridgeMillau(BridgeMillauCall::finalize_header(id.1, completion));
+		let era = self
+			.target_client
+			.transaction_era(self.target_sign.transactions_mortality)
+			.await?;
+		let transaction = Rialto::sign_transaction(
+			&self.target_client,
+			&self.target_sign.signer,
+			era,
+			nonce,
+			self.target_sign.wrap_call(call),
+		)
+		.await?;
 		Ok(transaction)
 	}
 }
 
+/// Initialize Millau headers bridge in Rialto.
+///
+/// If initial header, authorities set or its id are not specified, they are read from the best
+/// finalized Millau header.
+pub async fn initialize(
+	millau_client: MillauClient,
+	rialto_client: RialtoClient,
+	rialto_sign: RialtoSigningParams,
+	raw_initial_header: Option<Bytes>,
+	raw_initial_authorities_set: Option<Bytes>,
+	initial_authorities_set_id: Option<GrandpaAuthoritiesSetId>,
+) -> Result<(), String> {
+	let rialto_signer_next_index = rialto_client
+		.next_account_index(rialto_sign.signer.public().into())
+		.await?;
+	let rialto_transaction_era = rialto_client
+		.transaction_era(rialto_sign.transactions_mortality)
+		.await?;
+
+	crate::headers_initialize::initialize(
+		millau_client,
+		rialto_client.clone(),
+		raw_initial_header,
+		raw_initial_authorities_set,
+		initial_authorities_set_id,
+		move |initialization_data| async move {
+			Rialto::sign_transaction(
+				&rialto_client,
+				&rialto_sign.signer,
+				rialto_transaction_era,
+				rialto_signer_next_index,
+				millau_runtime::SudoCall::sudo(Box::new(rialto_runtime::Call::BridgeMillau(
+					rialto_runtime::BridgeMillauCall::initialize(initialization_data),
+				)))
+				.into(),
+			)
+			.await
+			.map(|transaction| Bytes(transaction.encode()))
+			.map_err(|err| format!("Failed to sign Rialto initialization transaction: {:?}", err))
+		},
+	)
+	.await;
+
+	Ok(())
+}
+
 /// Run Millau-to-Rialto headers sync.
 pub async fn run(
 	millau_client: MillauClient,
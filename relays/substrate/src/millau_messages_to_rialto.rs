@@ -16,20 +16,32 @@
 
 //! Millau-to-Rialto messages sync entrypoint.
 
+use crate::call_batcher::CallBatcher;
 use crate::messages_source::{SubstrateMessagesSource, SubstrateTransactionMaker as SubstrateSourceTransactionMaker};
-use crate::messages_target::{SubstrateMessagesTarget, SubstrateTransactionMaker as SubstrateTargetTransactionMaker};
+use crate::messages_target::{
+	submit_estimated_messages_delivery_transaction, SubstrateMessagesTarget,
+	SubstrateTransactionMaker as SubstrateTargetTransactionMaker,
+};
 use crate::{MillauClient, RialtoClient};
 
 use async_trait::async_trait;
 use bp_message_lane::{LaneId, MessageNonce};
 use bp_runtime::{MILLAU_BRIDGE_INSTANCE, RIALTO_BRIDGE_INSTANCE};
-use frame_support::weights::Weight;
-use messages_relay::message_lane::MessageLane;
+use codec::Encode;
+use frame_support::weights::{GetDispatchInfo, Weight};
+use messages_relay::{
+	chaos::{ChaosClient, ChaosParams},
+	message_lane::MessageLane,
+	message_lane_loop::MessagesDeliverySubmission,
+};
+use pallet_bridge_call_dispatch::{
+	real_account_signature_payload, CallOrigin, MessageCall, MessagePayload, SourceAccountNonce,
+};
 use relay_millau_client::{HeaderId as MillauHeaderId, Millau, SigningParams as MillauSigningParams};
 use relay_rialto_client::{HeaderId as RialtoHeaderId, Rialto, SigningParams as RialtoSigningParams};
 use relay_substrate_client::{BlockNumberOf, Error as SubstrateError, HashOf, TransactionSignScheme};
 use relay_utils::metrics::MetricsParams;
-use sp_core::Pair;
+use sp_core::Bytes;
 use sp_trie::StorageProof;
 use std::{ops::RangeInclusive, time::Duration};
 
@@ -60,6 +72,10 @@ impl MessageLane for MillauMessagesToRialto {
 
 	type TargetHeaderNumber = BlockNumberOf<Rialto>;
 	type TargetHeaderHash = HashOf<Rialto>;
+
+	fn messages_proof_size(proof: &Self::MessagesProof) -> usize {
+		proof.1.encoded_size()
+	}
 }
 
 /// Millau node as messages source.
@@ -70,6 +86,20 @@ type MillauSourceClient = SubstrateMessagesSource<Millau, MillauMessagesToRialto
 struct MillauTransactionMaker {
 	client: MillauClient,
 	sign: MillauSigningParams,
+	batcher: Option<CallBatcher<Millau>>,
+}
+
+impl MillauTransactionMaker {
+	/// Make call that confirms messages delivery.
+	fn messages_receiving_proof_call(proof: FromRialtoMessagesReceivingProof) -> millau_runtime::Call {
+		let (bridged_header_hash, storage_proof, lane) = proof;
+		let proof = millau_runtime::rialto_messages::ToRialtoMessagesDeliveryProof {
+			bridged_header_hash,
+			storage_proof,
+			lane,
+		};
+		millau_runtime::MessageLaneCall::receive_messages_delivery_proof(proof).into()
+	}
 }
 
 #[async_trait]
@@ -83,10 +113,33 @@ impl SubstrateSourceTransactionMaker<Millau, MillauMessagesToRialto> for MillauT
 	) -> Result<Self::SignedTransaction, SubstrateError> {
 		let account_id = self.sign.signer.public().as_array_ref().clone().into();
 		let nonce = self.client.next_account_index(account_id).await?;
-		let call = millau_runtime::MessageLaneCall::receive_messages_delivery_proof(proof).into();
-		let transaction = Millau::sign_transaction(&self.client, &self.sign.signer, nonce, call);
+		let call = Self::messages_receiving_proof_call(proof);
+		let era = self.client.transaction_era(self.sign.transactions_mortality).await?;
+		let transaction =
+			Millau::sign_transaction(&self.client, &self.sign.signer, era, nonce, self.sign.wrap_call(call)).await?;
 		Ok(transaction)
 	}
+
+	async fn submit_messages_receiving_proof_transaction(
+		&self,
+		client: &MillauClient,
+		generated_at_block: RialtoHeaderId,
+		proof: FromRialtoMessagesReceivingProof,
+	) -> Result<(), SubstrateError> {
+		match self.batcher {
+			Some(ref batcher) => batcher
+				.submit_call(Self::messages_receiving_proof_call(proof))
+				.await
+				.map(drop),
+			None => {
+				let _submission_lock = client.lock_signed_extrinsic_submission().await;
+				let tx = self
+					.make_messages_receiving_proof_transaction(generated_at_block, proof)
+					.await?;
+				client.submit_extrinsic(Bytes(tx.encode())).await.map(drop)
+			}
+		}
+	}
 }
 
 /// Rialto node as messages target.
@@ -98,6 +151,28 @@ struct RialtoTransactionMaker {
 	client: RialtoClient,
 	relayer_id: bp_millau::AccountId,
 	sign: RialtoSigningParams,
+	batcher: Option<CallBatcher<Rialto>>,
+}
+
+impl RialtoTransactionMaker {
+	/// Make call that delivers messages.
+	fn messages_delivery_call(&self, proof: FromMillauMessagesProof) -> rialto_runtime::Call {
+		let (dispatch_weight, (bridged_header_hash, storage_proof, lane, nonces_start, nonces_end)) = proof;
+		let messages_count = (nonces_end - nonces_start + 1) as u32;
+		let proof = rialto_runtime::millau_messages::FromMillauMessagesProof {
+			bridged_header_hash,
+			storage_proof,
+			lane,
+			nonces_start,
+			nonces_end,
+		};
+		rialto_runtime::Call::BridgeMillauMessageLane(rialto_runtime::MessageLaneCall::receive_messages_proof(
+			self.relayer_id.clone(),
+			proof,
+			messages_count,
+			dispatch_weight,
+		))
+	}
 }
 
 #[async_trait]
@@ -110,31 +185,63 @@ impl SubstrateTargetTransactionMaker<Rialto, MillauMessagesToRialto> for RialtoT
 		_nonces: RangeInclusive<MessageNonce>,
 		proof: FromMillauMessagesProof,
 	) -> Result<Self::SignedTransaction, SubstrateError> {
-		let (dispatch_weight, proof) = proof;
 		let account_id = self.sign.signer.public().as_array_ref().clone().into();
 		let nonce = self.client.next_account_index(account_id).await?;
-		let call =
-			rialto_runtime::MessageLaneCall::receive_messages_proof(self.relayer_id.clone(), proof, dispatch_weight)
-				.into();
-		let transaction = Rialto::sign_transaction(&self.client, &self.sign.signer, nonce, call);
+		let call = self.messages_delivery_call(proof);
+		let era = self.client.transaction_era(self.sign.transactions_mortality).await?;
+		let transaction =
+			Rialto::sign_transaction(&self.client, &self.sign.signer, era, nonce, self.sign.wrap_call(call)).await?;
 		Ok(transaction)
 	}
+
+	async fn submit_messages_delivery_transaction(
+		&self,
+		client: &RialtoClient,
+		generated_at_header: MillauHeaderId,
+		nonces: RangeInclusive<MessageNonce>,
+		proof: FromMillauMessagesProof,
+	) -> Result<MessagesDeliverySubmission, SubstrateError> {
+		match self.batcher {
+			Some(ref batcher) => batcher
+				.submit_call(self.messages_delivery_call(proof))
+				.await
+				.map(|_| MessagesDeliverySubmission::Submitted(nonces, None)),
+			None => {
+				submit_estimated_messages_delivery_transaction(self, client, generated_at_header, nonces, proof).await
+			}
+		}
+	}
 }
 
 /// Run Millau-to-Rialto messages sync.
+///
+/// If batchers are specified, delivery confirmations are submitted to Millau using the
+/// `millau_batcher` and messages are delivered to Rialto using the `rialto_batcher`. Calls may
+/// then be submitted in the same transaction with calls of other relays.
+#[allow(clippy::too_many_arguments)]
 pub fn run(
 	millau_client: MillauClient,
 	millau_sign: MillauSigningParams,
 	rialto_client: RialtoClient,
 	rialto_sign: RialtoSigningParams,
 	lane: LaneId,
+	delivery_deadline: Option<Duration>,
+	max_unconfirmed_rewards: Option<u128>,
+	chaos_params: ChaosParams,
 	metrics_params: Option<MetricsParams>,
+	otlp_endpoint: Option<String>,
+	millau_batcher: Option<CallBatcher<Millau>>,
+	rialto_batcher: Option<CallBatcher<Rialto>>,
 ) {
 	let millau_tick = Duration::from_secs(5);
 	let rialto_tick = Duration::from_secs(5);
 	let reconnect_delay = Duration::from_secs(10);
 	let stall_timeout = Duration::from_secs(5 * 60);
-	let relayer_id = millau_sign.signer.public().as_array_ref().clone().into();
+	let relayer_id = millau_sign.origin_account();
+
+	if chaos_params.is_enabled() {
+		log::warn!(target: "bridge", "Injecting faults into messages relay: {:?}", chaos_params);
+	}
 
 	messages_relay::message_lane_loop::run(
 		messages_relay::message_lane_loop::Params {
@@ -145,31 +252,106 @@ pub fn run(
 			stall_timeout,
 			delivery_params: messages_relay::message_lane_loop::MessageDeliveryParams {
 				max_unconfirmed_nonces_at_target: bp_rialto::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE,
-				// TODO: subtract base weight of delivery from this when it'll be known
-				// https://github.com/paritytech/parity-bridges-common/issues/78
-				max_messages_weight_in_single_batch: bp_rialto::MAXIMUM_EXTRINSIC_WEIGHT,
+				max_delivery_transaction_weight: bp_rialto::MAXIMUM_EXTRINSIC_WEIGHT,
+				max_messages_proof_size: bp_rialto::MAX_MESSAGES_PROOF_SIZE,
+				delivery_weights: bp_rialto::message_lane_weights(),
+				delivery_deadline,
+				max_unconfirmed_rewards,
 			},
+			otlp_endpoint,
 		},
-		MillauSourceClient::new(
-			millau_client.clone(),
-			MillauTransactionMaker {
-				client: millau_client,
-				sign: millau_sign,
-			},
-			lane,
-			RIALTO_BRIDGE_INSTANCE,
+		ChaosClient::new(
+			MillauSourceClient::new(
+				millau_client.clone(),
+				MillauTransactionMaker {
+					client: millau_client,
+					sign: millau_sign,
+					batcher: millau_batcher,
+				},
+				lane,
+				RIALTO_BRIDGE_INSTANCE,
+				"BridgeRialtoMessageLane",
+			),
+			chaos_params.clone(),
 		),
-		RialtoTargetClient::new(
-			rialto_client.clone(),
-			RialtoTransactionMaker {
-				client: rialto_client,
-				relayer_id,
-				sign: rialto_sign,
-			},
-			lane,
-			MILLAU_BRIDGE_INSTANCE,
+		ChaosClient::new(
+			RialtoTargetClient::new(
+				rialto_client.clone(),
+				RialtoTransactionMaker {
+					client: rialto_client,
+					relayer_id,
+					sign: rialto_sign,
+					batcher: rialto_batcher,
+				},
+				lane,
+				MILLAU_BRIDGE_INSTANCE,
+			),
+			chaos_params,
 		),
 		metrics_params,
 		futures::future::pending(),
 	);
 }
+
+/// Submit Millau -> Rialto message.
+///
+/// The `rialto_call` is dispatched at Rialto on behalf of the `rialto_sign` account. The
+/// `origin_nonce` is the number of messages from the Millau sender account that have already
+/// been dispatched at Rialto.
+///
+/// Returns hash of the submitted Millau transaction.
+pub async fn submit_message(
+	millau_client: &MillauClient,
+	millau_sign: &MillauSigningParams,
+	rialto_sign: &RialtoSigningParams,
+	lane: LaneId,
+	rialto_call: rialto_runtime::Call,
+	fee: bp_millau::Balance,
+	origin_nonce: SourceAccountNonce,
+) -> Result<HashOf<Millau>, String> {
+	let rialto_call_weight = rialto_call.get_dispatch_info().weight;
+
+	let millau_sender_public: bp_millau::AccountSigner = millau_sign.signer.public().clone().into();
+	let rialto_origin_public = rialto_sign.signer.public();
+
+	let rialto_message_call = MessageCall::Single(rialto_call);
+	let rialto_origin_signature_message = real_account_signature_payload(
+		&rialto_message_call,
+		&millau_sender_public,
+		bp_runtime::MILLAU_BRIDGE_INSTANCE,
+		origin_nonce,
+	);
+	let rialto_origin_signature = rialto_sign.signer.sign(rialto_origin_signature_message).await?;
+
+	let millau_call = millau_runtime::Call::BridgeRialtoMessageLane(millau_runtime::MessageLaneCall::send_message(
+		lane,
+		MessagePayload {
+			spec_version: millau_runtime::VERSION.spec_version,
+			weight: rialto_call_weight,
+			origin: CallOrigin::RealAccount(
+				millau_sender_public,
+				rialto_origin_public.into(),
+				rialto_origin_signature.into(),
+			),
+			call: rialto_message_call.map(|call| call.encode()),
+		},
+		fee,
+	));
+
+	let signed_millau_call = Millau::sign_transaction(
+		millau_client,
+		&millau_sign.signer,
+		millau_client
+			.transaction_era(millau_sign.transactions_mortality)
+			.await?,
+		millau_client
+			.next_account_index(millau_sign.signer.public().clone().into())
+			.await?,
+		millau_call,
+	)
+	.await?;
+
+	Ok(millau_client
+		.submit_extrinsic(Bytes(signed_millau_call.encode()))
+		.await?)
+}
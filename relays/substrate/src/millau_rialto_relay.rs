@@ -0,0 +1,230 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Millau <-> Rialto chain pair.
+//!
+//! Defines bridges between Millau and Rialto chains in both directions. Headers and messages
+//! relays of both bridges may be started within single process (see `chain_pair::spawn`).
+//!
+//! Messages relays may also be configured to batch their calls. Then messages delivery and
+//! delivery confirmation calls, submitted to the same chain within the batching window, are
+//! wrapped into single `utility.batch_all` transaction.
+
+use crate::call_batcher::{sign_and_submit_call, CallBatcher};
+use crate::chain_pair::{ChainPair, HeadersBridgeInitializationParams, MessagesRelayParams, SubstrateBridge};
+use crate::{MillauClient, RialtoClient};
+
+use async_trait::async_trait;
+use futures::FutureExt;
+use relay_millau_client::{Millau, SigningParams as MillauSigningParams};
+use relay_rialto_client::{Rialto, SigningParams as RialtoSigningParams};
+use relay_utils::metrics::MetricsParams;
+use std::{sync::Arc, time::Duration};
+
+/// Millau <-> Rialto chain pair.
+#[derive(Debug, Clone, Copy)]
+pub struct MillauRialto;
+
+impl ChainPair for MillauRialto {
+	type Left = Millau;
+	type Right = Rialto;
+	type LeftToRight = MillauToRialto;
+	type RightToLeft = RialtoToMillau;
+}
+
+/// Millau -> Rialto bridge.
+#[derive(Debug, Clone, Copy)]
+pub struct MillauToRialto;
+
+#[async_trait]
+impl SubstrateBridge for MillauToRialto {
+	type Source = Millau;
+	type Target = Rialto;
+	type SourceSigningParams = MillauSigningParams;
+	type TargetSigningParams = RialtoSigningParams;
+
+	async fn initialize_headers_bridge(
+		source_client: MillauClient,
+		target_client: RialtoClient,
+		target_sign: RialtoSigningParams,
+		params: HeadersBridgeInitializationParams,
+	) -> Result<(), String> {
+		crate::millau_headers_to_rialto::initialize(
+			source_client,
+			target_client,
+			target_sign,
+			params.initial_header,
+			params.initial_authorities_set,
+			params.initial_authorities_set_id,
+		)
+		.await
+	}
+
+	async fn relay_headers(
+		source_client: MillauClient,
+		target_client: RialtoClient,
+		target_sign: RialtoSigningParams,
+		metrics_params: Option<MetricsParams>,
+	) {
+		crate::millau_headers_to_rialto::run(source_client, target_client, target_sign, metrics_params).await
+	}
+
+	fn relay_messages(
+		source_client: MillauClient,
+		source_sign: MillauSigningParams,
+		target_client: RialtoClient,
+		target_sign: RialtoSigningParams,
+		params: MessagesRelayParams<Millau, Rialto>,
+	) {
+		crate::millau_messages_to_rialto::run(
+			source_client,
+			source_sign,
+			target_client,
+			target_sign,
+			params.lane,
+			params.delivery_deadline,
+			params.max_unconfirmed_rewards,
+			params.chaos_params,
+			params.metrics_params,
+			params.otlp_endpoint,
+			params.source_batcher,
+			params.target_batcher,
+		)
+	}
+
+	fn target_call_batcher(
+		target_client: RialtoClient,
+		target_sign: RialtoSigningParams,
+		window: Duration,
+	) -> CallBatcher<Rialto> {
+		rialto_call_batcher(target_client, target_sign, window)
+	}
+}
+
+/// Rialto -> Millau bridge.
+#[derive(Debug, Clone, Copy)]
+pub struct RialtoToMillau;
+
+#[async_trait]
+impl SubstrateBridge for RialtoToMillau {
+	type Source = Rialto;
+	type Target = Millau;
+	type SourceSigningParams = RialtoSigningParams;
+	type TargetSigningParams = MillauSigningParams;
+
+	async fn initialize_headers_bridge(
+		source_client: RialtoClient,
+		target_client: MillauClient,
+		target_sign: MillauSigningParams,
+		params: HeadersBridgeInitializationParams,
+	) -> Result<(), String> {
+		crate::rialto_headers_to_millau::initialize(
+			source_client,
+			target_client,
+			target_sign,
+			params.initial_header,
+			params.initial_authorities_set,
+			params.initial_authorities_set_id,
+		)
+		.await
+	}
+
+	async fn relay_headers(
+		source_client: RialtoClient,
+		target_client: MillauClient,
+		target_sign: MillauSigningParams,
+		metrics_params: Option<MetricsParams>,
+	) {
+		crate::rialto_headers_to_millau::run(source_client, target_client, target_sign, metrics_params).await
+	}
+
+	fn relay_messages(
+		source_client: RialtoClient,
+		source_sign: RialtoSigningParams,
+		target_client: MillauClient,
+		target_sign: MillauSigningParams,
+		params: MessagesRelayParams<Rialto, Millau>,
+	) {
+		crate::rialto_messages_to_millau::run(
+			source_client,
+			source_sign,
+			target_client,
+			target_sign,
+			params.lane,
+			params.delivery_deadline,
+			params.max_unconfirmed_rewards,
+			params.chaos_params,
+			params.metrics_params,
+			params.otlp_endpoint,
+			params.source_batcher,
+			params.target_batcher,
+		)
+	}
+
+	fn target_call_batcher(
+		target_client: MillauClient,
+		target_sign: MillauSigningParams,
+		window: Duration,
+	) -> CallBatcher<Millau> {
+		millau_call_batcher(target_client, target_sign, window)
+	}
+}
+
+/// Create batcher of Millau calls. Batches are signed by the `sign` account.
+fn millau_call_batcher(client: MillauClient, sign: MillauSigningParams, window: Duration) -> CallBatcher<Millau> {
+	CallBatcher::new(
+		window,
+		Arc::new(move |calls: Vec<millau_runtime::Call>| {
+			let (client, sign) = (client.clone(), sign.clone());
+			async move {
+				let signer_account = sign.signer.public().as_array_ref().clone().into();
+				let call = sign.wrap_calls(calls);
+				sign_and_submit_call(
+					&client,
+					&*sign.signer,
+					signer_account,
+					sign.transactions_mortality,
+					call,
+				)
+				.await
+			}
+			.boxed()
+		}),
+	)
+}
+
+/// Create batcher of Rialto calls. Batches are signed by the `sign` account.
+fn rialto_call_batcher(client: RialtoClient, sign: RialtoSigningParams, window: Duration) -> CallBatcher<Rialto> {
+	CallBatcher::new(
+		window,
+		Arc::new(move |calls: Vec<rialto_runtime::Call>| {
+			let (client, sign) = (client.clone(), sign.clone());
+			async move {
+				let signer_account = sign.signer.public().as_array_ref().clone().into();
+				let call = sign.wrap_calls(calls);
+				sign_and_submit_call(
+					&client,
+					&*sign.signer,
+					signer_account,
+					sign.transactions_mortality,
+					call,
+				)
+				.await
+			}
+			.boxed()
+		}),
+	)
+}
@@ -0,0 +1,858 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Replay of the messages proof verification.
+//!
+//! Invalid `receive_messages_proof` transaction is rejected with the opaque `InvalidMessagesProof`
+//! error. The replay repeats all checks of this transaction (including storage proof
+//! verification) against the snapshot of the target chain bridge pallets state (see
+//! `bridge_state`) and reports every step: which storage keys have been read from the snapshot
+//! and from the storage proof, what has been decoded and which check has failed.
+
+use crate::bridge_state::BridgeStateSnapshot;
+use crate::storage_decoder::storage_prefix;
+
+use bp_message_lane::{
+	DecodeVersioned, InboundLaneData, LaneId, MessageData, MessageNonce, OutboundLaneData, StorageVersion,
+	LEGACY_STORAGE_VERSION,
+};
+use bp_runtime::{storage_proof_size, Size, StorageProofChecker};
+use codec::{Decode, Encode};
+use frame_support::weights::Weight;
+use pallet_substrate_bridge::ImportedHeader;
+use serde::Serialize;
+use sp_core::{
+	bytes::{from_hex, to_hex},
+	storage::StorageKey,
+};
+use sp_runtime::traits::{Hash as HashT, Header as HeaderT};
+use sp_trie::StorageProof;
+use std::fmt::{self, Debug};
+
+/// Replay of the messages proof verification.
+#[derive(Debug, Serialize)]
+pub struct ProofReplay {
+	/// Name of the source chain.
+	pub source: &'static str,
+	/// Name of the target chain.
+	pub target: &'static str,
+	/// Performed checks. Replay stops at the first failed check.
+	pub steps: Vec<ReplayStep>,
+	/// Error of the failed check, or `None` if all checks have passed.
+	pub failure: Option<String>,
+}
+
+/// Single check of the messages proof verification.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ReplayStep {
+	/// Short description of the check.
+	pub check: String,
+	/// True if the check has passed.
+	pub passed: bool,
+	/// What has been read and decoded during the check.
+	pub details: String,
+}
+
+/// Replay verification of Millau -> Rialto messages proof, submitted to Rialto.
+///
+/// The snapshot must contain state of Rialto bridge pallets.
+pub fn millau_to_rialto(transaction: &[u8], snapshot: &BridgeStateSnapshot) -> ProofReplay {
+	let params = ReplayParams {
+		source: relay_millau_client::Millau::NAME,
+		target: relay_rialto_client::Rialto::NAME,
+		headers_module_prefix: <pallet_substrate_bridge::DefaultInstance as pallet_substrate_bridge::Instance>::PREFIX,
+		lane_module_prefix: <pallet_message_lane::DefaultInstance as pallet_message_lane::Instance>::PREFIX,
+		max_messages_proof_size: bp_rialto::MAX_MESSAGES_PROOF_SIZE,
+		storage_version_key: pallet_message_lane::storage_keys::storage_version_key::<
+			pallet_message_lane::DefaultInstance,
+		>(),
+		message_key: pallet_message_lane::storage_keys::message_key::<
+			millau_runtime::Runtime,
+			pallet_message_lane::DefaultInstance,
+		>,
+		outbound_lane_data_key: pallet_message_lane::storage_keys::outbound_lane_data_key::<
+			pallet_message_lane::DefaultInstance,
+		>,
+		inbound_lane_data_key: pallet_message_lane::storage_keys::inbound_lane_data_key::<
+			rialto_runtime::Runtime,
+			pallet_message_lane::DefaultInstance,
+		>,
+		payload_dispatch_weight: |payload| {
+			rialto_runtime::millau_messages::FromMillauMessagePayload::decode(&mut &payload[..])
+				.map(|payload| payload.into_inner().weight)
+		},
+	};
+
+	let mut replay = Replay::new(&params);
+	let _ = decode_transaction::<rialto_runtime::UncheckedExtrinsic>(&mut replay, transaction)
+		.and_then(|call| match rialto_delivery_call(call) {
+			Ok(call) => Some(call),
+			Err(details) => replay.fail(FIND_DELIVERY_CALL, details, "UnexpectedCall"),
+		})
+		.and_then(|call| {
+			replay_delivery::<
+				bp_millau::Header,
+				<rialto_runtime::Runtime as pallet_message_lane::Trait>::InboundMessageFee,
+				<rialto_runtime::Runtime as pallet_message_lane::Trait>::InboundRelayer,
+			>(&mut replay, &params, snapshot, call)
+		});
+	replay.into_proof_replay()
+}
+
+/// Replay verification of Rialto -> Millau messages proof, submitted to Millau.
+///
+/// The snapshot must contain state of Millau bridge pallets.
+pub fn rialto_to_millau(transaction: &[u8], snapshot: &BridgeStateSnapshot) -> ProofReplay {
+	let params = ReplayParams {
+		source: relay_rialto_client::Rialto::NAME,
+		target: relay_millau_client::Millau::NAME,
+		headers_module_prefix: <pallet_substrate_bridge::DefaultInstance as pallet_substrate_bridge::Instance>::PREFIX,
+		lane_module_prefix: <pallet_message_lane::DefaultInstance as pallet_message_lane::Instance>::PREFIX,
+		max_messages_proof_size: bp_millau::MAX_MESSAGES_PROOF_SIZE,
+		storage_version_key: pallet_message_lane::storage_keys::storage_version_key::<
+			pallet_message_lane::DefaultInstance,
+		>(),
+		message_key: pallet_message_lane::storage_keys::message_key::<
+			rialto_runtime::Runtime,
+			pallet_message_lane::DefaultInstance,
+		>,
+		outbound_lane_data_key: pallet_message_lane::storage_keys::outbound_lane_data_key::<
+			pallet_message_lane::DefaultInstance,
+		>,
+		inbound_lane_data_key: pallet_message_lane::storage_keys::inbound_lane_data_key::<
+			millau_runtime::Runtime,
+			pallet_message_lane::DefaultInstance,
+		>,
+		payload_dispatch_weight: |payload| {
+			millau_runtime::rialto_messages::FromRialtoMessagePayload::decode(&mut &payload[..])
+				.map(|payload| payload.into_inner().weight)
+		},
+	};
+
+	let mut replay = Replay::new(&params);
+	let _ = decode_transaction::<millau_runtime::UncheckedExtrinsic>(&mut replay, transaction)
+		.and_then(|call| match millau_delivery_call(call) {
+			Ok(call) => Some(call),
+			Err(details) => replay.fail(FIND_DELIVERY_CALL, details, "UnexpectedCall"),
+		})
+		.and_then(|call| {
+			replay_delivery::<
+				bp_rialto::Header,
+				<millau_runtime::Runtime as pallet_message_lane::Trait>::InboundMessageFee,
+				<millau_runtime::Runtime as pallet_message_lane::Trait>::InboundRelayer,
+			>(&mut replay, &params, snapshot, call)
+		});
+	replay.into_proof_replay()
+}
+
+impl fmt::Display for ProofReplay {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(
+			f,
+			"Replay of {} -> {} messages proof verification:",
+			self.source, self.target
+		)?;
+		for step in &self.steps {
+			writeln!(
+				f,
+				"  [{}] {}: {}",
+				if step.passed { "ok" } else { "FAILED" },
+				step.check,
+				step.details,
+			)?;
+		}
+		match self.failure {
+			Some(ref failure) => writeln!(f, "Verification has failed: {}", failure),
+			None => writeln!(f, "All checks have passed"),
+		}
+	}
+}
+
+/// Description of the check that finds messages delivery call in the transaction.
+const FIND_DELIVERY_CALL: &str = "Find messages delivery call";
+
+/// Parameters of the messages proof verification at the target chain.
+struct ReplayParams {
+	/// Name of the source chain.
+	source: &'static str,
+	/// Name of the target chain.
+	target: &'static str,
+	/// Module prefix of the target chain pallet that is tracking source chain headers.
+	headers_module_prefix: &'static str,
+	/// Module prefix of the target chain message lane pallet.
+	lane_module_prefix: &'static str,
+	/// Maximal size of the messages proof, accepted by the target chain.
+	max_messages_proof_size: u32,
+	/// Storage key of the source chain message lane pallet storage version.
+	storage_version_key: StorageKey,
+	/// Storage key of the message at the source chain.
+	message_key: fn(&LaneId, MessageNonce) -> StorageKey,
+	/// Storage key of the outbound lane data at the source chain.
+	outbound_lane_data_key: fn(&LaneId) -> StorageKey,
+	/// Storage key of the inbound lane data at the target chain.
+	inbound_lane_data_key: fn(&LaneId) -> StorageKey,
+	/// Return dispatch weight of the message payload.
+	payload_dispatch_weight: fn(&[u8]) -> Result<Weight, codec::Error>,
+}
+
+/// Messages delivery call.
+struct DeliveryCall<BridgedHeaderHash> {
+	/// Relayer account at the source chain.
+	relayer: String,
+	/// Hash of the source chain header the proof is for.
+	bridged_header_hash: BridgedHeaderHash,
+	/// Storage proof of messages and (optionally) outbound lane state.
+	storage_proof: StorageProof,
+	/// Id of the lane.
+	lane: LaneId,
+	/// Nonce of the first message being delivered.
+	nonces_start: MessageNonce,
+	/// Nonce of the last message being delivered.
+	nonces_end: MessageNonce,
+	/// Declared number of messages in the proof.
+	messages_count: u32,
+	/// Declared dispatch weight of all messages in the proof.
+	dispatch_weight: Weight,
+}
+
+/// Replay in progress.
+struct Replay {
+	source: &'static str,
+	target: &'static str,
+	steps: Vec<ReplayStep>,
+	failure: Option<String>,
+}
+
+impl Replay {
+	fn new(params: &ReplayParams) -> Self {
+		Replay {
+			source: params.source,
+			target: params.target,
+			steps: Vec::new(),
+			failure: None,
+		}
+	}
+
+	/// Record passed check.
+	fn pass(&mut self, check: &str, details: String) {
+		self.steps.push(ReplayStep {
+			check: check.into(),
+			passed: true,
+			details,
+		});
+	}
+
+	/// Record failed check and the error that the runtime would return.
+	fn fail<T>(&mut self, check: &str, details: String, error: &str) -> Option<T> {
+		self.failure = Some(format!("{}: {}", error, details));
+		self.steps.push(ReplayStep {
+			check: check.into(),
+			passed: false,
+			details,
+		});
+		None
+	}
+
+	fn into_proof_replay(self) -> ProofReplay {
+		ProofReplay {
+			source: self.source,
+			target: self.target,
+			steps: self.steps,
+			failure: self.failure,
+		}
+	}
+}
+
+/// Decode signed transaction and return its call.
+fn decode_transaction<Extrinsic: Decode + TransactionCall>(
+	replay: &mut Replay,
+	transaction: &[u8],
+) -> Option<Extrinsic::Call> {
+	const CHECK: &str = "Decode transaction";
+
+	let target = replay.target;
+	let transaction = match Extrinsic::decode(&mut &transaction[..]) {
+		Ok(transaction) => transaction,
+		Err(e) => {
+			return replay.fail(
+				CHECK,
+				format!("Failed to decode {} transaction: {}", target, e),
+				"BadTransaction",
+			)
+		}
+	};
+	match transaction.signer() {
+		Some(signer) => {
+			replay.pass(CHECK, format!("Transaction is signed by {}", signer));
+			Some(transaction.into_call())
+		}
+		None => replay.fail(CHECK, "Transaction is not signed".into(), "BadOrigin"),
+	}
+}
+
+/// Signed transaction of the target chain.
+trait TransactionCall {
+	/// Call type of the target chain.
+	type Call;
+
+	/// Return debug representation of the transaction signer, if it is signed.
+	fn signer(&self) -> Option<String>;
+	/// Consume self and return transaction call.
+	fn into_call(self) -> Self::Call;
+}
+
+impl TransactionCall for rialto_runtime::UncheckedExtrinsic {
+	type Call = rialto_runtime::Call;
+
+	fn signer(&self) -> Option<String> {
+		self.signature.as_ref().map(|(address, _, _)| format!("{:?}", address))
+	}
+
+	fn into_call(self) -> Self::Call {
+		self.function
+	}
+}
+
+impl TransactionCall for millau_runtime::UncheckedExtrinsic {
+	type Call = millau_runtime::Call;
+
+	fn signer(&self) -> Option<String> {
+		self.signature.as_ref().map(|(address, _, _)| format!("{:?}", address))
+	}
+
+	fn into_call(self) -> Self::Call {
+		self.function
+	}
+}
+
+/// Find Millau -> Rialto messages delivery call, possibly wrapped into the proxy call.
+fn rialto_delivery_call(call: rialto_runtime::Call) -> Result<DeliveryCall<bp_millau::Hash>, String> {
+	match call {
+		rialto_runtime::Call::Proxy(rialto_runtime::ProxyCall::proxy(_, _, call)) => rialto_delivery_call(*call),
+		rialto_runtime::Call::BridgeMillauMessageLane(rialto_runtime::MessageLaneCall::receive_messages_proof(
+			relayer,
+			proof,
+			messages_count,
+			dispatch_weight,
+		)) => Ok(DeliveryCall {
+			relayer: relayer.to_string(),
+			bridged_header_hash: proof.bridged_header_hash,
+			storage_proof: proof.storage_proof,
+			lane: proof.lane,
+			nonces_start: proof.nonces_start,
+			nonces_end: proof.nonces_end,
+			messages_count,
+			dispatch_weight,
+		}),
+		call => Err(format!("Transaction call is not a messages delivery call: {:?}", call)),
+	}
+}
+
+/// Find Rialto -> Millau messages delivery call, possibly wrapped into the proxy call.
+fn millau_delivery_call(call: millau_runtime::Call) -> Result<DeliveryCall<bp_rialto::Hash>, String> {
+	match call {
+		millau_runtime::Call::Proxy(millau_runtime::ProxyCall::proxy(_, _, call)) => millau_delivery_call(*call),
+		millau_runtime::Call::BridgeRialtoMessageLane(millau_runtime::MessageLaneCall::receive_messages_proof(
+			relayer,
+			proof,
+			messages_count,
+			dispatch_weight,
+		)) => Ok(DeliveryCall {
+			relayer: relayer.to_string(),
+			bridged_header_hash: proof.bridged_header_hash,
+			storage_proof: proof.storage_proof,
+			lane: proof.lane,
+			nonces_start: proof.nonces_start,
+			nonces_end: proof.nonces_end,
+			messages_count,
+			dispatch_weight,
+		}),
+		call => Err(format!("Transaction call is not a messages delivery call: {:?}", call)),
+	}
+}
+
+/// Repeat checks of the `receive_messages_proof` call.
+///
+/// Checks are performed in the same order as in the runtime, so the first failed check is the
+/// one that has caused transaction failure.
+fn replay_delivery<BridgedHeader, Fee, Relayer>(
+	replay: &mut Replay,
+	params: &ReplayParams,
+	snapshot: &BridgeStateSnapshot,
+	call: DeliveryCall<BridgedHeader::Hash>,
+) -> Option<()>
+where
+	BridgedHeader: HeaderT + Decode,
+	Fee: Decode + Debug,
+	Relayer: Decode,
+{
+	replay.pass(
+		FIND_DELIVERY_CALL,
+		format!(
+			"Relayer {} is delivering messages [{}; {}] of lane {} (declared count: {}, declared dispatch weight: {}) \
+			using proof at {} header {:?}",
+			call.relayer,
+			call.nonces_start,
+			call.nonces_end,
+			call.lane,
+			call.messages_count,
+			call.dispatch_weight,
+			params.source,
+			call.bridged_header_hash,
+		),
+	);
+
+	// the pallet must be operational
+	const CHECK_OPERATIONAL: &str = "Check that message lane pallet is operational";
+	let is_halted_key = storage_prefix(params.lane_module_prefix, "IsHalted");
+	match snapshot_value::<bool>(snapshot, &is_halted_key) {
+		Ok(Some(true)) => return replay.fail(CHECK_OPERATIONAL, "Pallet is halted".into(), "Halted"),
+		Ok(_) => replay.pass(CHECK_OPERATIONAL, "Pallet is not halted".into()),
+		Err(e) => return replay.fail(CHECK_OPERATIONAL, e, "InvalidSnapshot"),
+	}
+
+	// the proof size must not exceed the limit
+	const CHECK_PROOF_SIZE: &str = "Check proof size";
+	let proof_size = storage_proof_size(&call.storage_proof);
+	let details = format!(
+		"Proof has {} trie nodes of {} bytes (limit is {} bytes)",
+		proof_size.nodes,
+		call.storage_proof.size_hint(),
+		params.max_messages_proof_size,
+	);
+	if call.storage_proof.size_hint() > params.max_messages_proof_size {
+		return replay.fail(CHECK_PROOF_SIZE, details, "MessagesProofTooLarge");
+	}
+	replay.pass(CHECK_PROOF_SIZE, details);
+
+	// the header must be known to the headers pallet and must be finalized
+	const CHECK_HEADER: &str = "Read finalized source header";
+	let header_key = imported_header_key(params, &call.bridged_header_hash);
+	let header = match snapshot_value::<ImportedHeader<BridgedHeader>>(snapshot, &header_key) {
+		Ok(Some(header)) => header,
+		Ok(None) => {
+			return replay.fail(
+				CHECK_HEADER,
+				format!(
+					"Header {:?} is not known to the {} headers pallet (key {})",
+					call.bridged_header_hash,
+					params.source,
+					to_hex(&header_key, false),
+				),
+				"UnknownHeader",
+			)
+		}
+		Err(e) => return replay.fail(CHECK_HEADER, e, "InvalidSnapshot"),
+	};
+	let header_details = format!(
+		"Header #{:?} {:?} with state root {:?}",
+		header.header.number(),
+		call.bridged_header_hash,
+		header.header.state_root(),
+	);
+	if !header.is_finalized {
+		return replay.fail(
+			CHECK_HEADER,
+			format!("{} is not finalized", header_details),
+			"UnfinalizedHeader",
+		);
+	}
+	replay.pass(CHECK_HEADER, format!("{} is finalized", header_details));
+
+	// the proof must contain the state root node
+	const CHECK_ROOT: &str = "Check that proof is generated at the header state";
+	let root_mismatch_details = explain_root_mismatch::<BridgedHeader>(params, snapshot, &call.storage_proof);
+	let storage =
+		match StorageProofChecker::<BridgedHeader::Hashing>::new(*header.header.state_root(), call.storage_proof) {
+			Ok(storage) => storage,
+			Err(_) => return replay.fail(CHECK_ROOT, root_mismatch_details, "StorageRootMismatch"),
+		};
+	replay.pass(CHECK_ROOT, "Proof contains state root node".into());
+
+	// storage version that is proved to be missing means that the source chain is using the legacy
+	// storage version
+	const CHECK_STORAGE_VERSION: &str = "Read source message lane pallet storage version";
+	let storage_version = match read_proof_value(&storage, &params.storage_version_key) {
+		Ok(Some(raw_storage_version)) => match StorageVersion::decode(&mut &raw_storage_version[..]) {
+			Ok(storage_version) => storage_version,
+			Err(e) => {
+				return replay.fail(
+					CHECK_STORAGE_VERSION,
+					format!("Failed to decode storage version from the proof: {}", e),
+					"InvalidMessagesProof",
+				)
+			}
+		},
+		Ok(None) => LEGACY_STORAGE_VERSION,
+		Err(e) => return replay.fail(CHECK_STORAGE_VERSION, e, "InvalidMessagesProof"),
+	};
+	replay.pass(
+		CHECK_STORAGE_VERSION,
+		format!(
+			"Storage version is {} (key {})",
+			storage_version,
+			to_hex(&params.storage_version_key.0, false),
+		),
+	);
+
+	// all messages must be in the proof
+	let mut messages_dispatch_weight: Weight = 0;
+	let mut messages = 0usize;
+	for nonce in call.nonces_start..=call.nonces_end {
+		let check = format!("Read message {}", nonce);
+		let message_key = (params.message_key)(&call.lane, nonce);
+		let raw_message_data = match read_proof_value(&storage, &message_key) {
+			Ok(Some(raw_message_data)) => raw_message_data,
+			Ok(None) => {
+				return replay.fail(
+					&check,
+					format!(
+						"Message is missing from the proof (key {})",
+						to_hex(&message_key.0, false)
+					),
+					"InvalidMessagesProof",
+				)
+			}
+			Err(e) => return replay.fail(&check, e, "InvalidMessagesProof"),
+		};
+		let message_data = match MessageData::<Fee>::decode_versioned(storage_version, &raw_message_data) {
+			Ok(message_data) => message_data,
+			Err(e) => {
+				return replay.fail(
+					&check,
+					format!(
+						"Failed to decode message of {} bytes using storage version {}: {}",
+						raw_message_data.len(),
+						storage_version,
+						e,
+					),
+					"InvalidMessagesProof",
+				)
+			}
+		};
+		// invalid payload doesn't invalidate the proof - the message is just not dispatched
+		let dispatch_weight = (params.payload_dispatch_weight)(&message_data.payload);
+		if let Ok(dispatch_weight) = dispatch_weight {
+			messages_dispatch_weight = messages_dispatch_weight.saturating_add(dispatch_weight);
+		}
+		replay.pass(
+			&check,
+			format!(
+				"Message fee is {:?}, payload has {} bytes, {}",
+				message_data.fee,
+				message_data.payload.len(),
+				match dispatch_weight {
+					Ok(dispatch_weight) => format!("dispatch weight is {}", dispatch_weight),
+					Err(e) => format!("payload can't be decoded ({}) and won't be dispatched", e),
+				},
+			),
+		);
+		messages += 1;
+	}
+
+	// outbound lane state is optional, but if it is in the proof, it must be valid
+	const CHECK_LANE_STATE: &str = "Read source outbound lane state";
+	let outbound_lane_data_key = (params.outbound_lane_data_key)(&call.lane);
+	let has_lane_state = match read_proof_value(&storage, &outbound_lane_data_key) {
+		Ok(Some(raw_lane_data)) => match OutboundLaneData::decode_versioned(storage_version, &raw_lane_data) {
+			Ok(lane_data) => {
+				replay.pass(
+					CHECK_LANE_STATE,
+					format!(
+						"Latest message, which delivery has been confirmed at {}, is {}",
+						params.source, lane_data.latest_received_nonce,
+					),
+				);
+				true
+			}
+			Err(e) => {
+				return replay.fail(
+					CHECK_LANE_STATE,
+					format!("Failed to decode outbound lane data from the proof: {}", e),
+					"InvalidMessagesProof",
+				)
+			}
+		},
+		_ => {
+			replay.pass(CHECK_LANE_STATE, "Outbound lane state is not in the proof".into());
+			false
+		}
+	};
+
+	const CHECK_NOT_EMPTY: &str = "Check that proof is not empty";
+	if !has_lane_state && messages == 0 {
+		return replay.fail(
+			CHECK_NOT_EMPTY,
+			"Proof has neither messages, nor outbound lane state".into(),
+			"InvalidMessagesProof",
+		);
+	}
+	replay.pass(CHECK_NOT_EMPTY, format!("Proof has {} messages", messages));
+
+	const CHECK_COUNT: &str = "Check declared messages count";
+	let details = format!(
+		"Proof has {} messages, declared count is {}",
+		messages, call.messages_count
+	);
+	if messages > call.messages_count as usize {
+		return replay.fail(CHECK_COUNT, details, "InvalidMessagesCount");
+	}
+	replay.pass(CHECK_COUNT, details);
+
+	// following checks are not failing the transaction, but some messages may be rejected or
+	// not dispatched
+	const CHECK_INBOUND_LANE: &str = "Read target inbound lane state";
+	let inbound_lane_data_key = (params.inbound_lane_data_key)(&call.lane);
+	let inbound_lane_data = match snapshot_value::<InboundLaneData<Relayer>>(snapshot, &inbound_lane_data_key.0) {
+		Ok(inbound_lane_data) => inbound_lane_data.unwrap_or_default(),
+		Err(e) => return replay.fail(CHECK_INBOUND_LANE, e, "InvalidSnapshot"),
+	};
+	let expected_nonce = inbound_lane_data.latest_received_nonce + 1;
+	replay.pass(
+		CHECK_INBOUND_LANE,
+		if messages != 0 && call.nonces_start != expected_nonce {
+			format!(
+				"Expected nonce of the next message is {}, so messages starting from {} will be rejected",
+				expected_nonce, call.nonces_start,
+			)
+		} else {
+			format!("Expected nonce of the next message is {}", expected_nonce)
+		},
+	);
+
+	const CHECK_DISPATCH_WEIGHT: &str = "Check declared dispatch weight";
+	replay.pass(
+		CHECK_DISPATCH_WEIGHT,
+		if messages_dispatch_weight > call.dispatch_weight {
+			format!(
+				"Messages dispatch weight is {}, declared weight is {}, so some messages won't be dispatched",
+				messages_dispatch_weight, call.dispatch_weight,
+			)
+		} else {
+			format!(
+				"Messages dispatch weight is {}, declared weight is {}",
+				messages_dispatch_weight, call.dispatch_weight,
+			)
+		},
+	);
+
+	Some(())
+}
+
+/// Return storage key of the imported header.
+fn imported_header_key(params: &ReplayParams, header_hash: &impl Encode) -> Vec<u8> {
+	let mut key = storage_prefix(params.headers_module_prefix, "ImportedHeaders").to_vec();
+	key.extend(header_hash.encode());
+	key
+}
+
+/// Explain why proof doesn't match state root of the header.
+///
+/// If proof has been generated at the state of other header, known to the headers pallet, this
+/// header is reported.
+fn explain_root_mismatch<BridgedHeader: HeaderT + Decode>(
+	params: &ReplayParams,
+	snapshot: &BridgeStateSnapshot,
+	storage_proof: &StorageProof,
+) -> String {
+	let node_hashes = storage_proof
+		.clone()
+		.iter_nodes()
+		.map(|node| BridgedHeader::Hashing::hash(&node))
+		.collect::<Vec<_>>();
+	let headers_prefix = to_hex(&storage_prefix(params.headers_module_prefix, "ImportedHeaders"), false);
+	let proof_header = snapshot
+		.storage
+		.iter()
+		.filter(|(key, _)| key.starts_with(&headers_prefix))
+		.filter_map(|(_, value)| from_hex(value).ok())
+		.filter_map(|value| ImportedHeader::<BridgedHeader>::decode(&mut &value[..]).ok())
+		.find(|header| node_hashes.contains(header.header.state_root()));
+
+	match proof_header {
+		Some(proof_header) => format!(
+			"None of {} proof nodes is the header state root node. Proof has been generated at the state \
+			of {} header #{:?} {:?}",
+			node_hashes.len(),
+			params.source,
+			proof_header.header.number(),
+			proof_header.header.hash(),
+		),
+		None => format!(
+			"None of {} proof nodes is the header state root node. Proof doesn't match state of any {} header \
+			known to the headers pallet",
+			node_hashes.len(),
+			params.source,
+		),
+	}
+}
+
+/// Read value from the storage proof.
+fn read_proof_value<H: sp_core::Hasher>(
+	storage: &StorageProofChecker<H>,
+	key: &StorageKey,
+) -> Result<Option<Vec<u8>>, String> {
+	storage.read_value(&key.0).map_err(|_| {
+		format!(
+			"Proof has no trie nodes that are required to read the key {}",
+			to_hex(&key.0, false)
+		)
+	})
+}
+
+/// Read and decode value from the snapshot.
+fn snapshot_value<T: Decode>(snapshot: &BridgeStateSnapshot, key: &[u8]) -> Result<Option<T>, String> {
+	let key = to_hex(key, false);
+	snapshot
+		.storage
+		.get(&key)
+		.map(|value| {
+			let value = from_hex(value).map_err(|e| format!("Invalid hex value of the key {}: {}", key, e))?;
+			T::decode(&mut &value[..]).map_err(|e| format!("Failed to decode value of the key {}: {}", key, e))
+		})
+		.transpose()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn test_params() -> ReplayParams {
+		ReplayParams {
+			source: "Source",
+			target: "Target",
+			headers_module_prefix: "Headers",
+			lane_module_prefix: "MessageLane",
+			max_messages_proof_size: 1024 * 1024,
+			storage_version_key: StorageKey(b"version".to_vec()),
+			message_key: |_, nonce| StorageKey(format!("key{}", nonce).into_bytes()),
+			outbound_lane_data_key: |_| StorageKey(b"lane".to_vec()),
+			inbound_lane_data_key: |_| StorageKey(b"inbound_lane".to_vec()),
+			payload_dispatch_weight: |_| Ok(0),
+		}
+	}
+
+	fn test_snapshot(params: &ReplayParams, header: &ImportedHeader<bp_rialto::Header>) -> BridgeStateSnapshot {
+		BridgeStateSnapshot {
+			chain: "Target".into(),
+			block_number: 0,
+			block_hash: "0x00".into(),
+			pallets: Default::default(),
+			storage: vec![(
+				to_hex(&imported_header_key(params, &header.header.hash()), false),
+				to_hex(&header.encode(), false),
+			)]
+			.into_iter()
+			.collect(),
+		}
+	}
+
+	fn test_imported_header(
+		number: bp_rialto::BlockNumber,
+		state_root: bp_rialto::Hash,
+	) -> ImportedHeader<bp_rialto::Header> {
+		ImportedHeader {
+			header: bp_rialto::Header::new(
+				number,
+				Default::default(),
+				state_root,
+				Default::default(),
+				Default::default(),
+			),
+			requires_justification: false,
+			is_finalized: true,
+			signal_hash: None,
+		}
+	}
+
+	fn test_call(bridged_header_hash: bp_rialto::Hash, storage_proof: StorageProof) -> DeliveryCall<bp_rialto::Hash> {
+		DeliveryCall {
+			relayer: "relayer".into(),
+			bridged_header_hash,
+			storage_proof,
+			lane: Default::default(),
+			nonces_start: 1,
+			nonces_end: 1,
+			messages_count: 1,
+			dispatch_weight: 0,
+		}
+	}
+
+	fn replay(
+		params: &ReplayParams,
+		snapshot: &BridgeStateSnapshot,
+		call: DeliveryCall<bp_rialto::Hash>,
+	) -> ProofReplay {
+		let mut replay = Replay::new(params);
+		replay_delivery::<bp_rialto::Header, u64, u64>(&mut replay, params, snapshot, call);
+		replay.into_proof_replay()
+	}
+
+	#[test]
+	fn replay_reports_invalid_message() {
+		// proof has `key1` and `key2` values, which are treated as invalid messages 1 and 2
+		let (state_root, storage_proof) = bp_runtime::craft_valid_storage_proof();
+		let params = test_params();
+		let header = test_imported_header(10, state_root);
+		let snapshot = test_snapshot(&params, &header);
+
+		let mut call = test_call(header.header.hash(), storage_proof);
+		call.nonces_end = 3;
+		let replay = replay(&params, &snapshot, call);
+
+		let failed_step = replay.steps.last().unwrap();
+		assert_eq!(failed_step.check, "Read message 1");
+		assert!(!failed_step.passed);
+		assert!(replay.steps[..replay.steps.len() - 1].iter().all(|step| step.passed));
+		assert!(replay
+			.failure
+			.unwrap()
+			.starts_with("InvalidMessagesProof: Failed to decode message"));
+	}
+
+	#[test]
+	fn replay_reports_header_that_matches_proof() {
+		let (state_root, storage_proof) = bp_runtime::craft_valid_storage_proof();
+		let params = test_params();
+		let proof_header = test_imported_header(10, state_root);
+		let call_header = test_imported_header(20, Default::default());
+		let mut snapshot = test_snapshot(&params, &proof_header);
+		snapshot.storage.extend(test_snapshot(&params, &call_header).storage);
+
+		let replay = replay(&params, &snapshot, test_call(call_header.header.hash(), storage_proof));
+
+		let failure = replay.failure.unwrap();
+		assert!(failure.starts_with("StorageRootMismatch: "));
+		assert!(failure.ends_with(&format!(
+			"Proof has been generated at the state of Source header #10 {:?}",
+			proof_header.header.hash(),
+		)));
+	}
+
+	#[test]
+	fn replay_reports_unknown_header() {
+		let params = test_params();
+		let snapshot = test_snapshot(&params, &test_imported_header(10, Default::default()));
+
+		let replay = replay(
+			&params,
+			&snapshot,
+			test_call(Default::default(), StorageProof::new(vec![])),
+		);
+
+		assert!(replay.failure.unwrap().starts_with("UnknownHeader: "));
+	}
+}
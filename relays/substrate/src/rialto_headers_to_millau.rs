@@ -26,11 +26,13 @@ use bp_rialto::{
 	BEST_RIALTO_BLOCKS_METHOD, FINALIZED_RIALTO_BLOCK_METHOD, INCOMPLETE_RIALTO_HEADERS_METHOD,
 	IS_KNOWN_RIALTO_BLOCK_METHOD,
 };
+use codec::Encode;
 use headers_relay::sync_types::QueuedHeader;
 use relay_millau_client::{BridgeRialtoCall, Millau, SigningParams as MillauSigningParams};
 use relay_rialto_client::{HeaderId as RialtoHeaderId, Rialto, SyncHeader as RialtoSyncHeader};
 use relay_substrate_client::{Error as SubstrateError, TransactionSignScheme};
-use sp_core::Pair;
+use sp_core::Bytes;
+use sp_finality_grandpa::SetId as GrandpaAuthoritiesSetId;
 use sp_runtime::Justification;
 
 /// Rialto-to-Millau headers sync pipeline.
@@ -54,7 +56,18 @@ impl SubstrateHeadersSyncPipeline for RialtoHeadersToMillau {
 		let account_id = self.target_sign.signer.public().as_array_ref().clone().into();
 		let nonce = self.target_client.next_account_index(account_id).await?;
 		let call = BridgeRialtoCall::import_signed_header(header.header().clone().into()).into();
-		let transaction = Millau::sign_transaction(&self.target_client, &self.target_sign.signer, nonce, call);
+		let era = self
+			.target_client
+			.transaction_era(self.target_sign.transactions_mortality)
+			.await?;
+		let transaction = Millau::sign_transaction(
+			&self.target_client,
+			&self.target_sign.signer,
+			era,
+			nonce,
+			self.target_sign.wrap_call(call),
+		)
+		.await?;
 		Ok(transaction)
 	}
 
@@ -66,11 +79,68 @@ impl SubstrateHeadersSyncPipeline for RialtoHeadersToMillau {
 		let account_id = self.target_sign.signer.public().as_array_ref().clone().into();
 		let nonce = self.target_client.next_account_index(account_id).await?;
 		let call = BridgeRialtoCall::finalize_header(id.1, completion).into();
-		let transaction = Millau::sign_transaction(&self.target_client, &self.target_sign.signer, nonce, call);
+		let era = self
+			.target_client
+			.transaction_era(self.target_sign.transactions_mortality)
+			.await?;
+		let transaction = Millau::sign_transaction(
+			&self.target_client,
+			&self.target_sign.signer,
+			era,
+			nonce,
+			self.target_sign.wrap_call(call),
+		)
+		.await?;
 		Ok(transaction)
 	}
 }
 
+/// Initialize Rialto headers bridge in Millau.
+///
+/// If initial header, authorities set or its id are not specified, they are read from the best
+/// finalized Rialto header.
+pub async fn initialize(
+	rialto_client: RialtoClient,
+	millau_client: MillauClient,
+	millau_sign: MillauSigningParams,
+	raw_initial_header: Option<Bytes>,
+	raw_initial_authorities_set: Option<Bytes>,
+	initial_authorities_set_id: Option<GrandpaAuthoritiesSetId>,
+) -> Result<(), String> {
+	let millau_signer_next_index = millau_client
+		.next_account_index(millau_sign.signer.public().into())
+		.await?;
+	let millau_transaction_era = millau_client
+		.transaction_era(millau_sign.transactions_mortality)
+		.await?;
+
+	crate::headers_initialize::initialize(
+		rialto_client,
+		millau_client.clone(),
+		raw_initial_header,
+		raw_initial_authorities_set,
+		initial_authorities_set_id,
+		move |initialization_data| async move {
+			Millau::sign_transaction(
+				&millau_client,
+				&millau_sign.signer,
+				millau_transaction_era,
+				millau_signer_next_index,
+				millau_runtime::SudoCall::sudo(Box::new(
+					millau_runtime::BridgeRialtoCall::initialize(initialization_data).into(),
+				))
+				.into(),
+			)
+			.await
+			.map(|transaction| Bytes(transaction.encode()))
+			.map_err(|err| format!("Failed to sign Millau initialization transaction: {:?}", err))
+		},
+	)
+	.await;
+
+	Ok(())
+}
+
 /// Run Rialto-to-Millau headers sync.
 pub async fn run(
 	rialto_client: RialtoClient,
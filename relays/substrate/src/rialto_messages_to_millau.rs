@@ -0,0 +1,360 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Rialto-to-Millau messages sync entrypoint.
+
+use crate::call_batcher::CallBatcher;
+use crate::messages_source::{SubstrateMessagesSource, SubstrateTransactionMaker as SubstrateSourceTransactionMaker};
+use crate::messages_target::{
+	submit_estimated_messages_delivery_transaction, SubstrateMessagesTarget,
+	SubstrateTransactionMaker as SubstrateTargetTransactionMaker,
+};
+use crate::{MillauClient, RialtoClient};
+
+use async_trait::async_trait;
+use bp_message_lane::{LaneId, MessageNonce};
+use bp_runtime::{MILLAU_BRIDGE_INSTANCE, RIALTO_BRIDGE_INSTANCE};
+use codec::Encode;
+use frame_support::weights::{GetDispatchInfo, Weight};
+use messages_relay::{
+	chaos::{ChaosClient, ChaosParams},
+	message_lane::MessageLane,
+	message_lane_loop::MessagesDeliverySubmission,
+};
+use pallet_bridge_call_dispatch::{
+	real_account_signature_payload, CallOrigin, MessageCall, MessagePayload, SourceAccountNonce,
+};
+use relay_millau_client::{HeaderId as MillauHeaderId, Millau, SigningParams as MillauSigningParams};
+use relay_rialto_client::{HeaderId as RialtoHeaderId, Rialto, SigningParams as RialtoSigningParams};
+use relay_substrate_client::{BlockNumberOf, Error as SubstrateError, HashOf, TransactionSignScheme};
+use relay_utils::metrics::MetricsParams;
+use sp_core::Bytes;
+use sp_trie::StorageProof;
+use std::{ops::RangeInclusive, time::Duration};
+
+/// Rialto -> Millau messages proof:
+///
+/// - cumulative dispatch-weight of messages in the batch;
+/// - proof that we'll actually submit to the Millau node.
+type FromRialtoMessagesProof = (
+	Weight,
+	(HashOf<Rialto>, StorageProof, LaneId, MessageNonce, MessageNonce),
+);
+/// Millau -> Rialto messages receiving proof.
+type FromMillauMessagesReceivingProof = (HashOf<Millau>, StorageProof, LaneId);
+
+/// Rialto-to-Millau messages pipeline.
+#[derive(Debug, Clone, Copy)]
+struct RialtoMessagesToMillau;
+
+impl MessageLane for RialtoMessagesToMillau {
+	const SOURCE_NAME: &'static str = "Rialto";
+	const TARGET_NAME: &'static str = "Millau";
+
+	type MessagesProof = FromRialtoMessagesProof;
+	type MessagesReceivingProof = FromMillauMessagesReceivingProof;
+
+	type SourceHeaderNumber = BlockNumberOf<Rialto>;
+	type SourceHeaderHash = HashOf<Rialto>;
+
+	type TargetHeaderNumber = BlockNumberOf<Millau>;
+	type TargetHeaderHash = HashOf<Millau>;
+
+	fn messages_proof_size(proof: &Self::MessagesProof) -> usize {
+		proof.1.encoded_size()
+	}
+}
+
+/// Rialto node as messages source.
+type RialtoSourceClient = SubstrateMessagesSource<Rialto, RialtoMessagesToMillau, RialtoTransactionMaker>;
+
+/// Rialto transaction maker.
+#[derive(Clone)]
+struct RialtoTransactionMaker {
+	client: RialtoClient,
+	sign: RialtoSigningParams,
+	batcher: Option<CallBatcher<Rialto>>,
+}
+
+impl RialtoTransactionMaker {
+	/// Make call that confirms messages delivery.
+	fn messages_receiving_proof_call(proof: FromMillauMessagesReceivingProof) -> rialto_runtime::Call {
+		let (bridged_header_hash, storage_proof, lane) = proof;
+		let proof = rialto_runtime::millau_messages::ToMillauMessagesDeliveryProof {
+			bridged_header_hash,
+			storage_proof,
+			lane,
+		};
+		rialto_runtime::Call::BridgeMillauMessageLane(rialto_runtime::MessageLaneCall::receive_messages_delivery_proof(
+			proof,
+		))
+	}
+}
+
+#[async_trait]
+impl SubstrateSourceTransactionMaker<Rialto, RialtoMessagesToMillau> for RialtoTransactionMaker {
+	type SignedTransaction = <Rialto as TransactionSignScheme>::SignedTransaction;
+
+	async fn make_messages_receiving_proof_transaction(
+		&self,
+		_generated_at_block: MillauHeaderId,
+		proof: FromMillauMessagesReceivingProof,
+	) -> Result<Self::SignedTransaction, SubstrateError> {
+		let account_id = self.sign.signer.public().as_array_ref().clone().into();
+		let nonce = self.client.next_account_index(account_id).await?;
+		let call = Self::messages_receiving_proof_call(proof);
+		let era = self.client.transaction_era(self.sign.transactions_mortality).await?;
+		let transaction =
+			Rialto::sign_transaction(&self.client, &self.sign.signer, era, nonce, self.sign.wrap_call(call)).await?;
+		Ok(transaction)
+	}
+
+	async fn submit_messages_receiving_proof_transaction(
+		&self,
+		client: &RialtoClient,
+		generated_at_block: MillauHeaderId,
+		proof: FromMillauMessagesReceivingProof,
+	) -> Result<(), SubstrateError> {
+		match self.batcher {
+			Some(ref batcher) => batcher
+				.submit_call(Self::messages_receiving_proof_call(proof))
+				.await
+				.map(drop),
+			None => {
+				let _submission_lock = client.lock_signed_extrinsic_submission().await;
+				let tx = self
+					.make_messages_receiving_proof_transaction(generated_at_block, proof)
+					.await?;
+				client.submit_extrinsic(Bytes(tx.encode())).await.map(drop)
+			}
+		}
+	}
+}
+
+/// Millau node as messages target.
+type MillauTargetClient = SubstrateMessagesTarget<Millau, RialtoMessagesToMillau, MillauTransactionMaker>;
+
+/// Millau transaction maker.
+#[derive(Clone)]
+struct MillauTransactionMaker {
+	client: MillauClient,
+	relayer_id: bp_rialto::AccountId,
+	sign: MillauSigningParams,
+	batcher: Option<CallBatcher<Millau>>,
+}
+
+impl MillauTransactionMaker {
+	/// Make call that delivers messages.
+	fn messages_delivery_call(&self, proof: FromRialtoMessagesProof) -> millau_runtime::Call {
+		let (dispatch_weight, (bridged_header_hash, storage_proof, lane, nonces_start, nonces_end)) = proof;
+		let messages_count = (nonces_end - nonces_start + 1) as u32;
+		let proof = millau_runtime::rialto_messages::FromRialtoMessagesProof {
+			bridged_header_hash,
+			storage_proof,
+			lane,
+			nonces_start,
+			nonces_end,
+		};
+		millau_runtime::MessageLaneCall::receive_messages_proof(
+			self.relayer_id.clone(),
+			proof,
+			messages_count,
+			dispatch_weight,
+		)
+		.into()
+	}
+}
+
+#[async_trait]
+impl SubstrateTargetTransactionMaker<Millau, RialtoMessagesToMillau> for MillauTransactionMaker {
+	type SignedTransaction = <Millau as TransactionSignScheme>::SignedTransaction;
+
+	async fn make_messages_delivery_transaction(
+		&self,
+		_generated_at_header: RialtoHeaderId,
+		_nonces: RangeInclusive<MessageNonce>,
+		proof: FromRialtoMessagesProof,
+	) -> Result<Self::SignedTransaction, SubstrateError> {
+		let account_id = self.sign.signer.public().as_array_ref().clone().into();
+		let nonce = self.client.next_account_index(account_id).await?;
+		let call = self.messages_delivery_call(proof);
+		let era = self.client.transaction_era(self.sign.transactions_mortality).await?;
+		let transaction =
+			Millau::sign_transaction(&self.client, &self.sign.signer, era, nonce, self.sign.wrap_call(call)).await?;
+		Ok(transaction)
+	}
+
+	async fn submit_messages_delivery_transaction(
+		&self,
+		client: &MillauClient,
+		generated_at_header: RialtoHeaderId,
+		nonces: RangeInclusive<MessageNonce>,
+		proof: FromRialtoMessagesProof,
+	) -> Result<MessagesDeliverySubmission, SubstrateError> {
+		match self.batcher {
+			Some(ref batcher) => batcher
+				.submit_call(self.messages_delivery_call(proof))
+				.await
+				.map(|_| MessagesDeliverySubmission::Submitted(nonces, None)),
+			None => {
+				submit_estimated_messages_delivery_transaction(self, client, generated_at_header, nonces, proof).await
+			}
+		}
+	}
+}
+
+/// Run Rialto-to-Millau messages sync.
+///
+/// If batchers are specified, delivery confirmations are submitted to Rialto using the
+/// `rialto_batcher` and messages are delivered to Millau using the `millau_batcher`. Calls may
+/// then be submitted in the same transaction with calls of other relays.
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+	rialto_client: RialtoClient,
+	rialto_sign: RialtoSigningParams,
+	millau_client: MillauClient,
+	millau_sign: MillauSigningParams,
+	lane: LaneId,
+	delivery_deadline: Option<Duration>,
+	max_unconfirmed_rewards: Option<u128>,
+	chaos_params: ChaosParams,
+	metrics_params: Option<MetricsParams>,
+	otlp_endpoint: Option<String>,
+	rialto_batcher: Option<CallBatcher<Rialto>>,
+	millau_batcher: Option<CallBatcher<Millau>>,
+) {
+	let rialto_tick = Duration::from_secs(5);
+	let millau_tick = Duration::from_secs(5);
+	let reconnect_delay = Duration::from_secs(10);
+	let stall_timeout = Duration::from_secs(5 * 60);
+	let relayer_id = rialto_sign.origin_account();
+
+	if chaos_params.is_enabled() {
+		log::warn!(target: "bridge", "Injecting faults into messages relay: {:?}", chaos_params);
+	}
+
+	messages_relay::message_lane_loop::run(
+		messages_relay::message_lane_loop::Params {
+			lane,
+			source_tick: rialto_tick,
+			target_tick: millau_tick,
+			reconnect_delay,
+			stall_timeout,
+			delivery_params: messages_relay::message_lane_loop::MessageDeliveryParams {
+				max_unconfirmed_nonces_at_target: bp_millau::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE,
+				max_delivery_transaction_weight: bp_millau::MAXIMUM_EXTRINSIC_WEIGHT,
+				max_messages_proof_size: bp_millau::MAX_MESSAGES_PROOF_SIZE,
+				delivery_weights: bp_millau::message_lane_weights(),
+				delivery_deadline,
+				max_unconfirmed_rewards,
+			},
+			otlp_endpoint,
+		},
+		ChaosClient::new(
+			RialtoSourceClient::new(
+				rialto_client.clone(),
+				RialtoTransactionMaker {
+					client: rialto_client,
+					sign: rialto_sign,
+					batcher: rialto_batcher,
+				},
+				lane,
+				MILLAU_BRIDGE_INSTANCE,
+				"BridgeMillauMessageLane",
+			),
+			chaos_params.clone(),
+		),
+		ChaosClient::new(
+			MillauTargetClient::new(
+				millau_client.clone(),
+				MillauTransactionMaker {
+					client: millau_client,
+					relayer_id,
+					sign: millau_sign,
+					batcher: millau_batcher,
+				},
+				lane,
+				RIALTO_BRIDGE_INSTANCE,
+			),
+			chaos_params,
+		),
+		metrics_params,
+		futures::future::pending(),
+	);
+}
+
+/// Submit Rialto -> Millau message.
+///
+/// The `millau_call` is dispatched at Millau on behalf of the `millau_sign` account. The
+/// `origin_nonce` is the number of messages from the Rialto sender account that have already
+/// been dispatched at Millau.
+///
+/// Returns hash of the submitted Rialto transaction.
+pub async fn submit_message(
+	rialto_client: &RialtoClient,
+	rialto_sign: &RialtoSigningParams,
+	millau_sign: &MillauSigningParams,
+	lane: LaneId,
+	millau_call: millau_runtime::Call,
+	fee: bp_rialto::Balance,
+	origin_nonce: SourceAccountNonce,
+) -> Result<HashOf<Rialto>, String> {
+	let millau_call_weight = millau_call.get_dispatch_info().weight;
+
+	let rialto_sender_public: bp_rialto::AccountSigner = rialto_sign.signer.public().clone().into();
+	let millau_origin_public = millau_sign.signer.public();
+
+	let millau_message_call = MessageCall::Single(millau_call);
+	let millau_origin_signature_message = real_account_signature_payload(
+		&millau_message_call,
+		&rialto_sender_public,
+		bp_runtime::RIALTO_BRIDGE_INSTANCE,
+		origin_nonce,
+	);
+	let millau_origin_signature = millau_sign.signer.sign(millau_origin_signature_message).await?;
+
+	let rialto_call = rialto_runtime::Call::BridgeMillauMessageLane(rialto_runtime::MessageLaneCall::send_message(
+		lane,
+		MessagePayload {
+			spec_version: millau_runtime::VERSION.spec_version,
+			weight: millau_call_weight,
+			origin: CallOrigin::RealAccount(
+				rialto_sender_public,
+				millau_origin_public.into(),
+				millau_origin_signature.into(),
+			),
+			call: millau_message_call.map(|call| call.encode()),
+		},
+		fee,
+	));
+
+	let signed_rialto_call = Rialto::sign_transaction(
+		rialto_client,
+		&rialto_sign.signer,
+		rialto_client
+			.transaction_era(rialto_sign.transactions_mortality)
+			.await?,
+		rialto_client
+			.next_account_index(rialto_sign.signer.public().clone().into())
+			.await?,
+		rialto_call,
+	)
+	.await?;
+
+	Ok(rialto_client
+		.submit_extrinsic(Bytes(signed_rialto_call.encode()))
+		.await?)
+}
@@ -0,0 +1,342 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Decoder of bridge pallets storage.
+//!
+//! Given raw storage key (and, optionally, value) from the runtime storage, finds the storage
+//! item of the bridge pallet that it belongs to and decodes map key and value. It is meant to be
+//! used when debugging raw state dumps and storage migrations.
+
+use bp_message_lane::{
+	InboundLaneData, LaneId, MessageData, MessageKey, MessageNonce, OutboundLaneData, StorageVersion,
+};
+use bp_runtime::{BlockNumberOf, HashOf, HeaderOf};
+use codec::Decode;
+use pallet_substrate_bridge::{AuthoritySet, ImportedHeader, ScheduledChange};
+use relay_substrate_client::Chain;
+use serde::Serialize;
+use sp_core::hashing::twox_128;
+use std::fmt::Debug;
+
+/// Decoded storage entry.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DecodedStorageEntry {
+	/// Name of the chain.
+	pub chain: &'static str,
+	/// Name of the pallet in the chain runtime.
+	pub pallet: &'static str,
+	/// Name of the storage item.
+	pub storage: &'static str,
+	/// Decoded map key, if storage item is a map.
+	pub key: Option<String>,
+	/// Decoded value, if it has been provided.
+	pub value: Option<String>,
+}
+
+/// Decode Millau storage entry.
+pub fn decode_millau_storage(key: &[u8], value: Option<&[u8]>) -> Result<DecodedStorageEntry, String> {
+	let mut items =
+		message_lane_items::<millau_runtime::Runtime, pallet_message_lane::DefaultInstance>("BridgeRialtoMessageLane");
+	items.extend(substrate_bridge_items::<
+		millau_runtime::Runtime,
+		pallet_substrate_bridge::DefaultInstance,
+	>("BridgeRialto"));
+	decode_storage(relay_millau_client::Millau::NAME, &items, key, value)
+}
+
+/// Decode Rialto storage entry.
+pub fn decode_rialto_storage(key: &[u8], value: Option<&[u8]>) -> Result<DecodedStorageEntry, String> {
+	let mut items =
+		message_lane_items::<rialto_runtime::Runtime, pallet_message_lane::DefaultInstance>("BridgeMillauMessageLane");
+	items.extend(message_lane_items::<
+		rialto_runtime::Runtime,
+		rialto_runtime::third_chain_messages::ThirdChainMessageLaneInstance,
+	>("BridgeThirdChainMessageLane"));
+	items.extend(substrate_bridge_items::<
+		rialto_runtime::Runtime,
+		pallet_substrate_bridge::DefaultInstance,
+	>("BridgeMillau"));
+	items.extend(substrate_bridge_items::<
+		rialto_runtime::Runtime,
+		rialto_runtime::third_chain_messages::ThirdChainHeadersInstance,
+	>("BridgeThirdChain"));
+	decode_storage(relay_rialto_client::Rialto::NAME, &items, key, value)
+}
+
+/// Function that decodes raw data and returns debug representation of decoded value.
+type DecodeFn = fn(&[u8]) -> Result<String, String>;
+
+/// Kind of the storage item.
+enum StorageKind {
+	/// Plain storage value.
+	Value,
+	/// Map, which keys are prefixed with hash of given length.
+	Map {
+		/// Length of the key hash, prepended to the encoded key.
+		hash_len: usize,
+		/// Key decoder.
+		decode_key: DecodeFn,
+	},
+}
+
+/// Storage item of the bridge pallet.
+struct StorageItem {
+	/// Name of the pallet in the chain runtime.
+	pallet: &'static str,
+	/// Name of the storage item.
+	storage: &'static str,
+	/// Prefix of all storage keys of this item.
+	prefix: [u8; 32],
+	/// Kind of the storage item.
+	kind: StorageKind,
+	/// Value decoder.
+	decode_value: DecodeFn,
+}
+
+impl StorageItem {
+	/// Create plain storage value item.
+	fn value<V: Decode + Debug>(pallet: &'static str, module_prefix: &str, storage: &'static str) -> Self {
+		StorageItem {
+			pallet,
+			storage,
+			prefix: storage_prefix(module_prefix, storage),
+			kind: StorageKind::Value,
+			decode_value: decode::<V>,
+		}
+	}
+
+	/// Create `blake2_128_concat` map item.
+	fn blake2_128_concat_map<K: Decode + Debug, V: Decode + Debug>(
+		pallet: &'static str,
+		module_prefix: &str,
+		storage: &'static str,
+	) -> Self {
+		StorageItem {
+			pallet,
+			storage,
+			prefix: storage_prefix(module_prefix, storage),
+			kind: StorageKind::Map {
+				hash_len: 16,
+				decode_key: decode::<K>,
+			},
+			decode_value: decode::<V>,
+		}
+	}
+
+	/// Create `identity` map item.
+	fn identity_map<K: Decode + Debug, V: Decode + Debug>(
+		pallet: &'static str,
+		module_prefix: &str,
+		storage: &'static str,
+	) -> Self {
+		StorageItem {
+			pallet,
+			storage,
+			prefix: storage_prefix(module_prefix, storage),
+			kind: StorageKind::Map {
+				hash_len: 0,
+				decode_key: decode::<K>,
+			},
+			decode_value: decode::<V>,
+		}
+	}
+}
+
+/// Return storage items of the message lane pallet instance.
+fn message_lane_items<T, I>(pallet: &'static str) -> Vec<StorageItem>
+where
+	T: pallet_message_lane::Trait<I>,
+	I: pallet_message_lane::Instance,
+{
+	vec![
+		StorageItem::value::<T::AccountId>(pallet, I::PREFIX, "ModuleOwner"),
+		StorageItem::value::<bool>(pallet, I::PREFIX, "IsHalted"),
+		StorageItem::value::<StorageVersion>(pallet, I::PREFIX, "PalletStorageVersion"),
+		StorageItem::blake2_128_concat_map::<LaneId, InboundLaneData<T::InboundRelayer>>(
+			pallet,
+			I::PREFIX,
+			"InboundLanes",
+		),
+		StorageItem::blake2_128_concat_map::<LaneId, MessageNonce>(
+			pallet,
+			I::PREFIX,
+			"InboundOldestUndispatchedNonces",
+		),
+		StorageItem::blake2_128_concat_map::<MessageKey, MessageData<T::InboundMessageFee>>(
+			pallet,
+			I::PREFIX,
+			"InboundUndispatchedMessages",
+		),
+		StorageItem::blake2_128_concat_map::<LaneId, OutboundLaneData>(pallet, I::PREFIX, "OutboundLanes"),
+		StorageItem::blake2_128_concat_map::<MessageKey, MessageData<T::OutboundMessageFee>>(
+			pallet,
+			I::PREFIX,
+			"OutboundMessages",
+		),
+		StorageItem::blake2_128_concat_map::<LaneId, (MessageNonce, MessageNonce)>(
+			pallet,
+			I::PREFIX,
+			"OutboundMessagesInBlock",
+		),
+	]
+}
+
+/// Return storage items of the substrate bridge pallet instance.
+fn substrate_bridge_items<T, I>(pallet: &'static str) -> Vec<StorageItem>
+where
+	T: pallet_substrate_bridge::Trait<I>,
+	I: pallet_substrate_bridge::Instance,
+{
+	type Number<T, I> = BlockNumberOf<<T as pallet_substrate_bridge::Trait<I>>::BridgedChain>;
+	type Hash<T, I> = HashOf<<T as pallet_substrate_bridge::Trait<I>>::BridgedChain>;
+	type Header<T, I> = HeaderOf<<T as pallet_substrate_bridge::Trait<I>>::BridgedChain>;
+
+	vec![
+		StorageItem::value::<Number<T, I>>(pallet, I::PREFIX, "BestHeight"),
+		StorageItem::value::<Vec<Hash<T, I>>>(pallet, I::PREFIX, "BestHeaders"),
+		StorageItem::value::<Hash<T, I>>(pallet, I::PREFIX, "BestFinalized"),
+		StorageItem::identity_map::<Hash<T, I>, Number<T, I>>(pallet, I::PREFIX, "RequiresJustification"),
+		StorageItem::identity_map::<Hash<T, I>, ImportedHeader<Header<T, I>>>(pallet, I::PREFIX, "ImportedHeaders"),
+		StorageItem::value::<AuthoritySet>(pallet, I::PREFIX, "CurrentAuthoritySet"),
+		StorageItem::identity_map::<Hash<T, I>, ScheduledChange<Number<T, I>>>(
+			pallet,
+			I::PREFIX,
+			"NextScheduledChange",
+		),
+		StorageItem::value::<T::AccountId>(pallet, I::PREFIX, "ModuleOwner"),
+		StorageItem::value::<bool>(pallet, I::PREFIX, "IsHalted"),
+	]
+}
+
+/// Find storage item that the key belongs to and decode the entry.
+fn decode_storage(
+	chain: &'static str,
+	items: &[StorageItem],
+	key: &[u8],
+	value: Option<&[u8]>,
+) -> Result<DecodedStorageEntry, String> {
+	let item = items.iter().find(|item| key.starts_with(&item.prefix)).ok_or_else(|| {
+		format!(
+			"Key doesn't belong to any known storage item of {} bridge pallets",
+			chain
+		)
+	})?;
+
+	let key_suffix = &key[item.prefix.len()..];
+	let decoded_key = match item.kind {
+		StorageKind::Value if key_suffix.is_empty() => None,
+		StorageKind::Value => return Err(format!("Key of {} storage value is too long", item.storage)),
+		StorageKind::Map { hash_len, decode_key } => {
+			let encoded_key = key_suffix
+				.get(hash_len..)
+				.ok_or_else(|| format!("Key of {} map entry is too short", item.storage))?;
+			Some(decode_key(encoded_key).map_err(|e| format!("Failed to decode {} map key: {}", item.storage, e))?)
+		}
+	};
+	let decoded_value = value
+		.map(|value| (item.decode_value)(value))
+		.transpose()
+		.map_err(|e| format!("Failed to decode {} value: {}", item.storage, e))?;
+
+	Ok(DecodedStorageEntry {
+		chain,
+		pallet: item.pallet,
+		storage: item.storage,
+		key: decoded_key,
+		value: decoded_value,
+	})
+}
+
+/// Return prefix of all keys of the storage item.
+pub(crate) fn storage_prefix(module_prefix: &str, storage: &str) -> [u8; 32] {
+	let mut prefix = [0u8; 32];
+	prefix[..16].copy_from_slice(&twox_128(module_prefix.as_bytes()));
+	prefix[16..].copy_from_slice(&twox_128(storage.as_bytes()));
+	prefix
+}
+
+/// Decode value and return its debug representation. All data must be consumed by the decoder.
+fn decode<T: Decode + Debug>(mut data: &[u8]) -> Result<String, String> {
+	let decoded = T::decode(&mut data).map_err(|e| format!("{:?}", e))?;
+	if !data.is_empty() {
+		return Err(format!("{} trailing bytes", data.len()));
+	}
+
+	Ok(format!("{:?}", decoded))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+
+	#[test]
+	fn millau_outbound_message_is_decoded() {
+		let key = pallet_message_lane::storage_keys::message_key::<
+			millau_runtime::Runtime,
+			pallet_message_lane::DefaultInstance,
+		>(&LaneId::from_bytes([0, 0, 0, 1]), 42);
+		let value = MessageData::<bp_millau::Balance> {
+			payload: vec![1, 2, 3],
+			fee: 100,
+		};
+
+		assert_eq!(
+			decode_millau_storage(&key.0, Some(&value.encode())),
+			Ok(DecodedStorageEntry {
+				chain: "Millau",
+				pallet: "BridgeRialtoMessageLane",
+				storage: "OutboundMessages",
+				key: Some(format!(
+					"{:?}",
+					MessageKey {
+						lane_id: LaneId::from_bytes([0, 0, 0, 1]),
+						nonce: 42,
+					}
+				)),
+				value: Some(format!("{:?}", value)),
+			}),
+		);
+	}
+
+	#[test]
+	fn rialto_storage_of_pallet_instances_is_decoded() {
+		let lane = LaneId::from_bytes([0, 0, 0, 1]);
+		let key = pallet_message_lane::storage_keys::outbound_lane_data_key::<
+			rialto_runtime::third_chain_messages::ThirdChainMessageLaneInstance,
+		>(&lane);
+		let entry = decode_rialto_storage(&key.0, None).unwrap();
+		assert_eq!(entry.pallet, "BridgeThirdChainMessageLane");
+		assert_eq!(entry.storage, "OutboundLanes");
+		assert_eq!(entry.key, Some(format!("{:?}", lane)));
+		assert_eq!(entry.value, None);
+
+		let key = pallet_message_lane::storage_keys::storage_version_key::<pallet_message_lane::DefaultInstance>();
+		let entry = decode_rialto_storage(&key.0, Some(&1u32.encode())).unwrap();
+		assert_eq!(entry.pallet, "BridgeMillauMessageLane");
+		assert_eq!(entry.storage, "PalletStorageVersion");
+		assert_eq!(entry.key, None);
+		assert_eq!(entry.value, Some("1".into()));
+	}
+
+	#[test]
+	fn invalid_storage_entries_are_rejected() {
+		let key = pallet_message_lane::storage_keys::storage_version_key::<pallet_message_lane::DefaultInstance>();
+		assert!(decode_millau_storage(&key.0, Some(&[1, 0, 0, 0, 0])).is_err());
+		assert!(decode_millau_storage(&[key.0.clone(), vec![0]].concat(), None).is_err());
+		assert!(decode_millau_storage(&[0u8; 32], None).is_err());
+	}
+}
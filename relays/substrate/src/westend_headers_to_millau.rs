@@ -0,0 +1,161 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Westend-to-Millau headers sync entrypoint.
+//!
+//! This is a headers-only relay: Westend runtime has no bridge pallets, so messages can't be
+//! relayed in any direction.
+
+use crate::{
+	headers_pipeline::SubstrateHeadersToSubstrate, headers_target::SubstrateHeadersSyncPipeline, MillauClient,
+	WestendClient,
+};
+
+use async_trait::async_trait;
+use bp_westend::{
+	BEST_WESTEND_BLOCKS_METHOD, FINALIZED_WESTEND_BLOCK_METHOD, INCOMPLETE_WESTEND_HEADERS_METHOD,
+	IS_KNOWN_WESTEND_BLOCK_METHOD,
+};
+use codec::Encode;
+use headers_relay::sync_types::QueuedHeader;
+use relay_millau_client::{BridgeWestendCall, Millau, SigningParams as MillauSigningParams};
+use relay_substrate_client::{Error as SubstrateError, TransactionSignScheme};
+use relay_westend_client::{HeaderId as WestendHeaderId, SyncHeader as WestendSyncHeader, Westend};
+use sp_core::Bytes;
+use sp_finality_grandpa::SetId as GrandpaAuthoritiesSetId;
+use sp_runtime::Justification;
+
+/// Westend-to-Millau headers sync pipeline.
+type WestendHeadersToMillau = SubstrateHeadersToSubstrate<Westend, WestendSyncHeader, Millau, MillauSigningParams>;
+/// Westend header in-the-queue.
+type QueuedWestendHeader = QueuedHeader<WestendHeadersToMillau>;
+
+#[async_trait]
+impl SubstrateHeadersSyncPipeline for WestendHeadersToMillau {
+	const BEST_BLOCK_METHOD: &'static str = BEST_WESTEND_BLOCKS_METHOD;
+	const FINALIZED_BLOCK_METHOD: &'static str = FINALIZED_WESTEND_BLOCK_METHOD;
+	const IS_KNOWN_BLOCK_METHOD: &'static str = IS_KNOWN_WESTEND_BLOCK_METHOD;
+	const INCOMPLETE_HEADERS_METHOD: &'static str = INCOMPLETE_WESTEND_HEADERS_METHOD;
+
+	type SignedTransaction = <Millau as TransactionSignScheme>::SignedTransaction;
+
+	async fn make_submit_header_transaction(
+		&self,
+		header: QueuedWestendHeader,
+	) -> Result<Self::SignedTransaction, SubstrateError> {
+		let account_id = self.target_sign.signer.public().as_array_ref().clone().into();
+		let nonce = self.target_client.next_account_index(account_id).await?;
+		let call = BridgeWestendCall::import_signed_header(header.header().clone().into()).into();
+		let era = self
+			.target_client
+			.transaction_era(self.target_sign.transactions_mortality)
+			.await?;
+		let transaction = Millau::sign_transaction(
+			&self.target_client,
+			&self.target_sign.signer,
+			era,
+			nonce,
+			self.target_sign.wrap_call(call),
+		)
+		.await?;
+		Ok(transaction)
+	}
+
+	async fn make_complete_header_transaction(
+		&self,
+		id: WestendHeaderId,
+		completion: Justification,
+	) -> Result<Self::SignedTransaction, SubstrateError> {
+		let account_id = self.target_sign.signer.public().as_array_ref().clone().into();
+		let nonce = self.target_client.next_account_index(account_id).await?;
+		let call = BridgeWestendCall::finalize_header(id.1, completion).into();
+		let era = self
+			.target_client
+			.transaction_era(self.target_sign.transactions_mortality)
+			.await?;
+		let transaction = Millau::sign_transaction(
+			&self.target_client,
+			&self.target_sign.signer,
+			era,
+			nonce,
+			self.target_sign.wrap_call(call),
+		)
+		.await?;
+		Ok(transaction)
+	}
+}
+
+/// Initialize Westend headers bridge in Millau.
+///
+/// If initial header, authorities set or its id are not specified, they are read from the best
+/// finalized Westend header.
+pub async fn initialize(
+	westend_client: WestendClient,
+	millau_client: MillauClient,
+	millau_sign: MillauSigningParams,
+	raw_initial_header: Option<Bytes>,
+	raw_initial_authorities_set: Option<Bytes>,
+	initial_authorities_set_id: Option<GrandpaAuthoritiesSetId>,
+) -> Result<(), String> {
+	let millau_signer_next_index = millau_client
+		.next_account_index(millau_sign.signer.public().into())
+		.await?;
+	let millau_transaction_era = millau_client
+		.transaction_era(millau_sign.transactions_mortality)
+		.await?;
+
+	crate::headers_initialize::initialize(
+		westend_client,
+		millau_client.clone(),
+		raw_initial_header,
+		raw_initial_authorities_set,
+		initial_authorities_set_id,
+		move |initialization_data| async move {
+			Millau::sign_transaction(
+				&millau_client,
+				&millau_sign.signer,
+				millau_transaction_era,
+				millau_signer_next_index,
+				millau_runtime::SudoCall::sudo(Box::new(
+					millau_runtime::BridgeWestendCall::initialize(initialization_data).into(),
+				))
+				.into(),
+			)
+			.await
+			.map(|transaction| Bytes(transaction.encode()))
+			.map_err(|err| format!("Failed to sign Millau initialization transaction: {:?}", err))
+		},
+	)
+	.await;
+
+	Ok(())
+}
+
+/// Run Westend-to-Millau headers sync.
+pub async fn run(
+	westend_client: WestendClient,
+	millau_client: MillauClient,
+	millau_sign: MillauSigningParams,
+	metrics_params: Option<relay_utils::metrics::MetricsParams>,
+) {
+	crate::headers_pipeline::run(
+		WestendHeadersToMillau::new(millau_client.clone(), millau_sign),
+		westend_client,
+		millau_client,
+		metrics_params,
+	)
+	.await;
+}
@@ -14,7 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-pub use substrate_prometheus_endpoint::{register, Counter, CounterVec, Gauge, GaugeVec, Opts, Registry, F64, U64};
+pub use substrate_prometheus_endpoint::{
+	exponential_buckets, register, Counter, CounterVec, Gauge, GaugeVec, Histogram, HistogramOpts, HistogramVec, Opts,
+	Registry, F64, U64,
+};
 
 use std::net::SocketAddr;
 use substrate_prometheus_endpoint::init_prometheus;
@@ -36,6 +39,8 @@ pub trait Metrics {
 }
 
 /// Global Prometheus metrics.
+///
+/// Metrics of all RPC rate limiters of the process are registered along with global metrics.
 #[derive(Debug)]
 pub struct GlobalMetrics {
 	system: System,
@@ -103,6 +108,7 @@ impl Metrics for GlobalMetrics {
 		register(self.system_average_load.clone(), registry).map_err(|e| e.to_string())?;
 		register(self.process_cpu_usage_percentage.clone(), registry).map_err(|e| e.to_string())?;
 		register(self.process_memory_usage_bytes.clone(), registry).map_err(|e| e.to_string())?;
+		crate::rate_limiter::register_metrics(registry)?;
 		Ok(())
 	}
 }
@@ -0,0 +1,411 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Limiting rate and concurrency of RPC requests.
+//!
+//! Public RPC providers are banning clients that are sending too many requests. Every RPC
+//! client of the relay may be configured to send at most given number of requests per second
+//! and to have at most given number of requests in flight. Requests that are exceeding the
+//! limits are queued and are sent in order of arrival.
+
+use crate::metrics::{
+	exponential_buckets, register, CounterVec, GaugeVec, HistogramOpts, HistogramVec, Opts, Registry, U64,
+};
+
+use async_std::sync::Mutex;
+use futures::{
+	channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+	StreamExt,
+};
+use std::{
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+lazy_static::lazy_static! {
+	/// Metrics of all rate limiters of the process.
+	static ref METRICS: RateLimiterMetrics = RateLimiterMetrics::new();
+}
+
+/// Limits of requests to single RPC endpoint.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateLimiterParams {
+	/// Maximal number of requests per second. Bursts of up to one second worth of requests are
+	/// allowed. If `None`, the rate is not limited.
+	pub max_requests_per_second: Option<u32>,
+	/// Maximal number of concurrent (in-flight) requests. If `None`, the number of concurrent
+	/// requests is not limited.
+	pub max_concurrent_requests: Option<usize>,
+}
+
+impl RateLimiterParams {
+	/// Returns true if any limit is configured.
+	pub fn is_limited(&self) -> bool {
+		self.max_requests_per_second.is_some() || self.max_concurrent_requests.is_some()
+	}
+}
+
+/// Rate limiter of requests to single RPC endpoint.
+///
+/// Cloning `RateLimiter` is a cheap operation. All clones are sharing the same limits.
+#[derive(Clone)]
+pub struct RateLimiter {
+	inner: Arc<Inner>,
+}
+
+/// Permit to send single request. The concurrent requests slot is released when permit is dropped.
+#[must_use = "request slot is released when the permit is dropped"]
+pub struct RequestPermit {
+	endpoint: String,
+	release: Option<UnboundedSender<()>>,
+}
+
+struct Inner {
+	/// Endpoint name, used in logs and metrics.
+	endpoint: String,
+	/// Configured limits.
+	params: RateLimiterParams,
+	/// Sender of released concurrent requests slots.
+	release: Option<UnboundedSender<()>>,
+	/// Queue of requests, waiting for their turn.
+	queue: Mutex<Queue>,
+}
+
+struct Queue {
+	/// Requests budget.
+	bucket: Option<TokenBucket>,
+	/// Free concurrent requests slots.
+	slots: Option<UnboundedReceiver<()>>,
+}
+
+impl RateLimiter {
+	/// Create rate limiter of requests to given endpoint.
+	///
+	/// Zero limits are treated as limit of one request (per second).
+	pub fn new(endpoint: String, params: RateLimiterParams) -> Self {
+		let bucket = params
+			.max_requests_per_second
+			.map(|rate| TokenBucket::new(rate.max(1), Instant::now()));
+		let (release, slots) = match params.max_concurrent_requests {
+			Some(max_concurrent_requests) => {
+				let (release, slots) = unbounded();
+				for _ in 0..max_concurrent_requests.max(1) {
+					release
+						.unbounded_send(())
+						.expect("receiver is alive, because we own it; qed");
+				}
+				(Some(release), Some(slots))
+			}
+			None => (None, None),
+		};
+
+		if params.is_limited() {
+			log::info!(
+				target: "bridge",
+				"Requests to {} are limited: {:?}",
+				endpoint,
+				params,
+			);
+		}
+
+		RateLimiter {
+			inner: Arc::new(Inner {
+				endpoint,
+				params,
+				release,
+				queue: Mutex::new(Queue { bucket, slots }),
+			}),
+		}
+	}
+
+	/// Create rate limiter that never delays requests to given endpoint.
+	pub fn unlimited(endpoint: String) -> Self {
+		Self::new(endpoint, RateLimiterParams::default())
+	}
+
+	/// Return configured limits.
+	pub fn params(&self) -> &RateLimiterParams {
+		&self.inner.params
+	}
+
+	/// Wait until next request may be sent.
+	///
+	/// The request must be sent while returned permit is alive.
+	pub async fn acquire(&self) -> RequestPermit {
+		let endpoint = self.inner.endpoint.as_str();
+		METRICS.requests.with_label_values(&[endpoint]).inc();
+		if !self.inner.params.is_limited() {
+			return self.permit(false);
+		}
+
+		let queued_at = Instant::now();
+		let _queued = QueuedRequest::new(endpoint);
+
+		let (has_slot, delay) = {
+			let mut queue = self.inner.queue.lock().await;
+			let has_slot = match queue.slots.as_mut() {
+				Some(slots) => {
+					slots.next().await.expect("sender is alive, because we own it; qed");
+					true
+				}
+				None => false,
+			};
+			let delay = queue
+				.bucket
+				.as_mut()
+				.map(|bucket| bucket.take(Instant::now()))
+				.unwrap_or_default();
+			(has_slot, delay)
+		};
+		let permit = self.permit(has_slot);
+		if delay != Duration::from_secs(0) {
+			async_std::task::sleep(delay).await;
+		}
+
+		METRICS
+			.queue_time
+			.with_label_values(&[endpoint])
+			.observe(queued_at.elapsed().as_secs_f64());
+
+		permit
+	}
+
+	/// Create permit to send request. If `has_slot` is true, the permit occupies concurrent
+	/// requests slot.
+	fn permit(&self, has_slot: bool) -> RequestPermit {
+		METRICS
+			.in_flight_requests
+			.with_label_values(&[self.inner.endpoint.as_str()])
+			.inc();
+		RequestPermit {
+			endpoint: self.inner.endpoint.clone(),
+			release: if has_slot { self.inner.release.clone() } else { None },
+		}
+	}
+}
+
+impl std::fmt::Debug for RateLimiter {
+	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+		fmt.debug_struct("RateLimiter")
+			.field("endpoint", &self.inner.endpoint)
+			.field("params", &self.inner.params)
+			.finish()
+	}
+}
+
+impl Drop for RequestPermit {
+	fn drop(&mut self) {
+		METRICS
+			.in_flight_requests
+			.with_label_values(&[self.endpoint.as_str()])
+			.dec();
+		if let Some(release) = self.release.take() {
+			// may only fail if rate limiter is dropped => nobody is waiting for the slot
+			let _ = release.unbounded_send(());
+		}
+	}
+}
+
+/// Request that is waiting in the queue. Dropping it (e.g. when waiting future is cancelled)
+/// removes request from the queue metrics.
+struct QueuedRequest<'a> {
+	endpoint: &'a str,
+}
+
+impl<'a> QueuedRequest<'a> {
+	fn new(endpoint: &'a str) -> Self {
+		METRICS.queued_requests.with_label_values(&[endpoint]).inc();
+		QueuedRequest { endpoint }
+	}
+}
+
+impl<'a> Drop for QueuedRequest<'a> {
+	fn drop(&mut self) {
+		METRICS.queued_requests.with_label_values(&[self.endpoint]).dec();
+	}
+}
+
+/// Token bucket that is refilled at constant rate.
+#[derive(Debug)]
+struct TokenBucket {
+	/// Number of tokens added every second.
+	rate: f64,
+	/// Maximal number of tokens in the bucket.
+	capacity: f64,
+	/// Current number of tokens. Negative when tokens are reserved by queued requests.
+	tokens: f64,
+	/// Time when tokens have been refilled last time.
+	refilled_at: Instant,
+}
+
+impl TokenBucket {
+	/// Create full bucket.
+	fn new(rate: u32, now: Instant) -> Self {
+		let rate = rate as f64;
+		TokenBucket {
+			rate,
+			capacity: rate,
+			tokens: rate,
+			refilled_at: now,
+		}
+	}
+
+	/// Take single token from the bucket and return delay after which the token is available.
+	fn take(&mut self, now: Instant) -> Duration {
+		let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+		self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+		self.refilled_at = now;
+
+		self.tokens -= 1.0;
+		if self.tokens >= 0.0 {
+			Duration::from_secs(0)
+		} else {
+			Duration::from_secs_f64(-self.tokens / self.rate)
+		}
+	}
+}
+
+/// Metrics of all rate limiters of the process.
+#[derive(Clone)]
+struct RateLimiterMetrics {
+	/// Total number of requests.
+	requests: CounterVec<U64>,
+	/// Number of requests that are waiting for their turn.
+	queued_requests: GaugeVec<U64>,
+	/// Number of requests that are in flight.
+	in_flight_requests: GaugeVec<U64>,
+	/// Time that requests spend in the queue.
+	queue_time: HistogramVec,
+}
+
+impl RateLimiterMetrics {
+	fn new() -> Self {
+		RateLimiterMetrics {
+			requests: CounterVec::new(Opts::new("rpc_requests", "Total number of RPC requests"), &["endpoint"])
+				.expect("metric is static and thus valid; qed"),
+			queued_requests: GaugeVec::new(
+				Opts::new(
+					"rpc_queued_requests",
+					"Number of RPC requests that are waiting for rate limiter",
+				),
+				&["endpoint"],
+			)
+			.expect("metric is static and thus valid; qed"),
+			in_flight_requests: GaugeVec::new(
+				Opts::new("rpc_in_flight_requests", "Number of RPC requests that are in flight"),
+				&["endpoint"],
+			)
+			.expect("metric is static and thus valid; qed"),
+			queue_time: HistogramVec::new(
+				HistogramOpts::new(
+					"rpc_request_queue_time_seconds",
+					"Time that RPC requests have spent waiting for rate limiter",
+				)
+				.buckets(exponential_buckets(0.001, 4.0, 9).expect("buckets are static and thus valid; qed")),
+				&["endpoint"],
+			)
+			.expect("metric is static and thus valid; qed"),
+		}
+	}
+}
+
+/// Register metrics of all rate limiters in the registry.
+pub(crate) fn register_metrics(registry: &Registry) -> Result<(), String> {
+	register(METRICS.requests.clone(), registry).map_err(|e| e.to_string())?;
+	register(METRICS.queued_requests.clone(), registry).map_err(|e| e.to_string())?;
+	register(METRICS.in_flight_requests.clone(), registry).map_err(|e| e.to_string())?;
+	register(METRICS.queue_time.clone(), registry).map_err(|e| e.to_string())?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::FutureExt;
+
+	fn params(max_requests_per_second: Option<u32>, max_concurrent_requests: Option<usize>) -> RateLimiterParams {
+		RateLimiterParams {
+			max_requests_per_second,
+			max_concurrent_requests,
+		}
+	}
+
+	#[test]
+	fn token_bucket_allows_bursts_of_one_second_worth_of_requests() {
+		let now = Instant::now();
+		let mut bucket = TokenBucket::new(4, now);
+		for _ in 0..4 {
+			assert_eq!(bucket.take(now), Duration::from_secs(0));
+		}
+		assert_eq!(bucket.take(now), Duration::from_millis(250));
+		assert_eq!(bucket.take(now), Duration::from_millis(500));
+	}
+
+	#[test]
+	fn token_bucket_is_refilled_at_configured_rate() {
+		let now = Instant::now();
+		let mut bucket = TokenBucket::new(4, now);
+		for _ in 0..4 {
+			bucket.take(now);
+		}
+
+		let now = now + Duration::from_millis(500);
+		assert_eq!(bucket.take(now), Duration::from_secs(0));
+		assert_eq!(bucket.take(now), Duration::from_secs(0));
+		assert_eq!(bucket.take(now), Duration::from_millis(250));
+
+		// bucket is never filled above its capacity
+		let now = now + Duration::from_secs(60);
+		for _ in 0..4 {
+			assert_eq!(bucket.take(now), Duration::from_secs(0));
+		}
+		assert_eq!(bucket.take(now), Duration::from_millis(250));
+	}
+
+	#[test]
+	fn unlimited_rate_limiter_never_delays_requests() {
+		let limiter = RateLimiter::unlimited("test".into());
+		let permits = (0..1024)
+			.map(|_| limiter.acquire().now_or_never().expect("not limited"))
+			.collect::<Vec<_>>();
+		assert_eq!(permits.len(), 1024);
+	}
+
+	#[test]
+	fn concurrent_requests_are_limited() {
+		let limiter = RateLimiter::new("test".into(), params(None, Some(2)));
+		let permit1 = limiter.acquire().now_or_never().expect("slot is free");
+		let _permit2 = limiter.acquire().now_or_never().expect("slot is free");
+
+		let mut queued_request = Box::pin(limiter.acquire());
+		assert!((&mut queued_request).now_or_never().is_none());
+
+		drop(permit1);
+		assert!(queued_request.now_or_never().is_some());
+	}
+
+	#[test]
+	fn requests_rate_is_limited() {
+		let limiter = RateLimiter::new("test".into(), params(Some(10), Some(1)));
+		let started_at = Instant::now();
+		async_std::task::block_on(async {
+			for _ in 0..15 {
+				let _permit = limiter.acquire().await;
+			}
+		});
+		assert!(started_at.elapsed() >= Duration::from_millis(450));
+	}
+}
@@ -0,0 +1,15 @@
+#!/bin/bash
+
+# Benchmark bridge pallets and regenerate weights of Rialto and Millau runtimes. Pass `--check`
+# to check that committed weights are close to measured weights instead.
+#
+# Committed weights must be generated on the reference machine, which is also used by the CI.
+
+set -e
+
+cargo build --release --features runtime-benchmarks -p rialto-bridge-node -p millau-bridge-node
+
+./target/release/rialto-bridge-node benchmark-weights --chain=dev \
+    --output=./bin/rialto/runtime/src/weights.rs "$@"
+./target/release/millau-bridge-node benchmark-weights --chain=dev \
+    --output=./bin/millau/runtime/src/weights.rs "$@"